@@ -268,6 +268,7 @@ export declare class PocketOption {
    * * `asset` - The trading asset/symbol (e.g., "EUR/USD")
    * * `period` - The candle period in seconds
    * * `offset` - Time offset for historical data
+   * * `allowPartial` - Returns the received candles instead of throwing if some candles are missing (defaults to false)
    *
    * # Returns
    * A JSON string containing the candle data
@@ -279,7 +280,7 @@ export declare class PocketOption {
    * console.log(`Retrieved ${data.length} candles`);
    * ```
    */
-  getCandles(asset: string, period: number, offset: number): Promise<any>
+  getCandles(asset: string, period: number, offset: number, allowPartial?: boolean | undefined | null): Promise<any>
   /**
    * Retrieves the current account balance.
    *
@@ -258,6 +258,7 @@ impl PocketOption {
     /// * `asset` - The trading asset/symbol (e.g., "EUR/USD")
     /// * `period` - The candle period in seconds
     /// * `offset` - Time offset for historical data
+    /// * `allowPartial` - Returns the received candles instead of throwing if some candles are missing (defaults to false)
     ///
     /// # Returns
     /// A JSON string containing the candle data
@@ -269,10 +270,16 @@ impl PocketOption {
     /// console.log(`Retrieved ${data.length} candles`);
     /// ```
     #[napi]
-    pub async fn get_candles(&self, asset: String, period: i64, offset: i64) -> Result<Value> {
+    pub async fn get_candles(
+        &self,
+        asset: String,
+        period: i64,
+        offset: i64,
+        allow_partial: Option<bool>,
+    ) -> Result<Value> {
         let res = self
             .client
-            .get_candles(asset, period, offset)
+            .get_candles(asset, period, offset, allow_partial.unwrap_or(false))
             .await
             .map_err(|e| Error::from_reason(e.to_string()))?;
         serde_json::to_value(&res).map_err(|e| Error::from_reason(e.to_string()))
@@ -7,5 +7,27 @@ from .BinaryOptionsToolsV2 import __doc__  # noqa: F401
 from .pocketoption import __all__ as __pocket_all__
 from . import tracing
 from . import validator
+from . import service
+from . import windows_service
+from . import control
+from . import protocol
+from . import streams
+from . import benchmarks
+from . import replay
+from . import storage
+from . import patterns
+from . import levels
+from . import charts
+from . import ssid
+from . import cookies
+from .streams import merge_streams  # noqa: F401
+from .protocol import ProtocolConsole  # noqa: F401
+from .replay import CandleReplay  # noqa: F401
+from .storage import storage_dirs, set_storage_dirs  # noqa: F401
+from .patterns import detect_patterns  # noqa: F401
+from .levels import compute_levels  # noqa: F401
+from .charts import render_chart  # noqa: F401
+from .ssid import SsidInfo, parse_ssid  # noqa: F401
+from .cookies import ssid_from_browser  # noqa: F401
 
-__all__ = __pocket_all__ + ['tracing', 'validator']
+__all__ = __pocket_all__ + ['tracing', 'validator', 'service', 'windows_service', 'control', 'protocol', 'streams', 'merge_streams', 'ProtocolConsole', 'benchmarks', 'replay', 'CandleReplay', 'storage', 'storage_dirs', 'set_storage_dirs', 'patterns', 'detect_patterns', 'levels', 'compute_levels', 'charts', 'render_chart', 'ssid', 'SsidInfo', 'parse_ssid', 'cookies', 'ssid_from_browser']
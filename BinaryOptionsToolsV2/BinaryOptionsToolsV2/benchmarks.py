@@ -0,0 +1,61 @@
+from __future__ import annotations
+
+import asyncio
+import json
+
+from BinaryOptionsToolsV2 import run_benchmarks as _run_benchmarks
+from BinaryOptionsToolsV2 import json_backend as _json_backend
+
+
+async def run_benchmarks_async(iterations: int = 10000) -> list[dict]:
+    """
+    Run the benchmarks of the hot paths of the client, no connection is needed so the results of
+    different releases (or machines) can be compared to spot performance regressions.
+
+    The benchmarks are the parsing of the most frequent messages (`parse_update_stream`, `parse_open_order`,
+    `parse_history`) with the backend returned by `json_backend`, the same stream payload parsed with
+    serde_json (`parse_update_stream_serde_json`) to compare it with simd-json, the serialization of an
+    order (`serialize_open_order`), the path of an order from the risk checks to the outgoing queue
+    (`order_send_path`), the price updates delivered per second by a subscription (`stream_throughput`) and
+    the round trip of an order frame through a local websocket with the default socket options
+    (`round_trip_default`) and the `Config.low_latency()` ones (`round_trip_low_latency`).
+
+    Args:
+        iterations (int): Number of iterations of each benchmark. Defaults to 10000
+
+    Returns:
+        list[dict]: One result per benchmark with its `name`, `iterations`, `total_ms`, `mean_us`, the
+            `p50_us` and `p99_us` percentiles (None for the throughput benchmarks) and the `throughput`
+            in operations per second.
+    """
+    return json.loads(await _run_benchmarks(iterations))
+
+
+def run_benchmarks(iterations: int = 10000) -> list[dict]:
+    "Runs the benchmarks synchronously, see `run_benchmarks_async`"
+    return asyncio.run(run_benchmarks_async(iterations))
+
+
+def compare_low_latency(iterations: int = 1000) -> dict:
+    """
+    Measures the round trip of an order frame with the default socket options and with the ones of
+    `Config.low_latency()` on this machine, to decide whether the preset is worth its trade-offs (more syscalls
+    and packets, one more thread per client).
+
+    Returns:
+        dict: The `default` and `low_latency` benchmark results, and the `p50_gain_us` / `p99_gain_us` saved on
+            every round trip by the preset (negative if it's slower on this machine).
+    """
+    results = {r["name"]: r for r in run_benchmarks(iterations) if r["name"].startswith("round_trip_")}
+    default, low_latency = results["round_trip_default"], results["round_trip_low_latency"]
+    return {
+        "default": default,
+        "low_latency": low_latency,
+        "p50_gain_us": default["p50_us"] - low_latency["p50_us"],
+        "p99_gain_us": default["p99_us"] - low_latency["p99_us"],
+    }
+
+
+def json_backend() -> str:
+    "Returns the JSON backend the messages are parsed with, 'simd-json' if the library was built with the `simd-json` feature, 'serde_json' otherwise"
+    return _json_backend()
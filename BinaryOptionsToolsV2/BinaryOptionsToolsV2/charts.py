@@ -0,0 +1,46 @@
+"""
+Candle charts rendered in Rust to PNG images, with the entries and exits of trades marked on them, to attach to the
+alerts of a bot (Telegram, Discord, emails...). `render_chart` works on candles already downloaded,
+`PocketOptionAsync.chart_trade` and `PocketOptionAsync.chart_range` download them.
+
+The charts are an optional feature of the library, build it with `maturin build --release --features charts` to enable
+them.
+
+Example:
+    ```python
+    candles = await api.get_candles("EURUSD_otc", 60, 3600)
+    png = render_chart(candles, markers=[(candles[-10]["time"], candles[-10]["close"], "call")], title="EURUSD_otc")
+    with open("chart.png", "wb") as file:
+        file.write(png)
+    ```
+"""
+from __future__ import annotations
+
+from .pocketoption.asyncronous import _candles_to_json, _chart_markers, _chart_options, _require_native
+from . import BinaryOptionsToolsV2 as _native
+from BinaryOptionsToolsV2 import Candle
+
+
+def render_chart(
+    candles: list[dict | Candle],
+    markers: list[dict | tuple] | None = None,
+    width: int = 1000,
+    height: int = 500,
+    title: str | None = None,
+) -> bytes:
+    """
+    Renders a history of candles to a PNG image.
+
+    Args:
+        candles (list[dict | Candle]): Candles in chronological order, like the ones returned by `get_candles`
+        markers (list[dict | tuple], optional): Points drawn on the chart, as `(time, price, kind)` tuples or dicts
+            with these keys, `kind` being "call", "put", "win", "loss" or "draw"
+        width (int): Width of the image in pixels. Defaults to 1000
+        height (int): Height of the image in pixels. Defaults to 500
+        title (str, optional): Title drawn above the chart
+
+    Returns:
+        bytes: The PNG image
+    """
+    _require_native(_native, "render_chart", "charts")
+    return _native.render_chart(_candles_to_json(candles), _chart_markers(markers), _chart_options(width, height, title))
@@ -1,8 +1,13 @@
+from __future__ import annotations
+
 from BinaryOptionsToolsV2 import PyConfig
-from typing import Dict, Any, List
+from typing import Dict, Any, List, Optional
 from dataclasses import dataclass
 
 import json
+import os
+
+ENV_PREFIX = "BOT_"
 
 @dataclass
 class Config:
@@ -16,6 +21,18 @@ class Config:
     connection_initialization_timeout_secs: int = 30
     timeout_secs: int = 30
     urls: List[str] = None
+    # How the missing values (None fields and NaN numbers) of the results are written: "null" keeps
+    # them, "omit" removes the fields and "default" replaces them with 0
+    null_policy: str = "null"
+
+    # Socket and reader options of the connection, see `Config.low_latency`
+    tcp_nodelay: bool = False
+    read_buffer_size: int = 128 * 1024
+    write_buffer_size: int = 128 * 1024
+    dedicated_reader: bool = False
+    reduced_logging: bool = False
+    # Rejects the orders on the assets whose market is closed with an `AssetClosedError`
+    check_market_hours: bool = False
     
     # Extra duration, used by functions like `check_win`
     extra_duration: int = 5
@@ -51,6 +68,40 @@ class Config:
         self._pyconfig.connection_initialization_timeout_secs = self.connection_initialization_timeout_secs
         self._pyconfig.timeout_secs = self.timeout_secs
         self._pyconfig.urls = self.urls.copy()
+        self._pyconfig.null_policy = self.null_policy
+        self._pyconfig.tcp_nodelay = self.tcp_nodelay
+        self._pyconfig.read_buffer_size = self.read_buffer_size
+        self._pyconfig.write_buffer_size = self.write_buffer_size
+        self._pyconfig.dedicated_reader = self.dedicated_reader
+        self._pyconfig.reduced_logging = self.reduced_logging
+        self._pyconfig.check_market_hours = self.check_market_hours
+
+    @classmethod
+    def low_latency(cls, **overrides) -> 'Config':
+        """
+        Preset for the short expiries (5 to 30 seconds), every frame is sent and read as soon as possible:
+        TCP_NODELAY, smaller buffers flushed on every frame, a dedicated reader thread with a raised priority
+        and no debug log per message.
+
+        The trade-offs are more syscalls and packets (a lower throughput when subscribing to many assets at
+        once) and one more thread per client. Run `BinaryOptionsToolsV2.benchmarks.compare_low_latency()` to
+        measure the gain on your machine.
+
+        Args:
+            **overrides: Other fields of the config (e.g. `timeout_secs=10`)
+
+        Returns:
+            Config instance
+        """
+        preset = PyConfig.low_latency()
+        return cls(**{
+            'tcp_nodelay': preset.tcp_nodelay,
+            'read_buffer_size': preset.read_buffer_size,
+            'write_buffer_size': preset.write_buffer_size,
+            'dedicated_reader': preset.dedicated_reader,
+            'reduced_logging': preset.reduced_logging,
+            **overrides,
+        })
 
     @classmethod
     def from_dict(cls, config_dict: Dict[str, Any]) -> 'Config':
@@ -81,6 +132,80 @@ class Config:
         """
         return cls.from_dict(json.loads(json_str))
 
+    @classmethod
+    def from_env(cls, prefix: str = ENV_PREFIX) -> 'Config':
+        """
+        Creates a Config instance from environment variables, every field is read from the
+        upper-case variable with the prefix (e.g. ``BOT_TIMEOUT_SECS``), ``BOT_URLS`` is a comma
+        separated list. Missing variables keep their default value.
+        
+        Args:
+            prefix: Prefix of the environment variables
+            
+        Returns:
+            Config instance
+            
+        Raises:
+            ValueError: If a variable can't be converted to the type of its field
+        """
+        config = {}
+        for name in cls.__dataclass_fields__:
+            value = os.environ.get(f"{prefix}{name.upper()}")
+            if value is None:
+                continue
+            if name == 'urls':
+                config[name] = [url.strip() for url in value.split(',') if url.strip()]
+            elif name == 'null_policy':
+                config[name] = value.strip().lower()
+            elif cls.__dataclass_fields__[name].type in (bool, 'bool'):
+                config[name] = value.strip().lower() in ('1', 'true', 'yes', 'on')
+            else:
+                try:
+                    config[name] = int(value)
+                except ValueError:
+                    raise ValueError(f"Invalid value '{value}' for ${prefix}{name.upper()}, expected an integer") from None
+        return cls.from_dict(config)
+
+    @classmethod
+    def from_profile(cls, name: str, path: Optional[str] = None) -> 'Config':
+        """
+        Creates a Config instance from a named profile.
+        
+        Profiles are stored in a single JSON file (``profiles.json`` by default) where each
+        profile can extend another one using the ``"inherits"`` key:
+        
+        ```json
+        {
+            "demo": {"timeout_secs": 30},
+            "prod": {"inherits": "demo", "reconnect_time": 2},
+            "research": {"inherits": "prod", "max_allowed_loops": 500}
+        }
+        ```
+        
+        Args:
+            name: Name of the profile to load
+            path: Path of the profiles file
+            
+        Returns:
+            Config instance
+        """
+        profile = PyConfig.profile(name, path)
+        return cls(
+            max_allowed_loops=profile.max_allowed_loops,
+            sleep_interval=profile.sleep_interval,
+            reconnect_time=profile.reconnect_time,
+            connection_initialization_timeout_secs=profile.connection_initialization_timeout_secs,
+            timeout_secs=profile.timeout_secs,
+            urls=list(profile.urls),
+            null_policy=profile.null_policy,
+            tcp_nodelay=profile.tcp_nodelay,
+            read_buffer_size=profile.read_buffer_size,
+            write_buffer_size=profile.write_buffer_size,
+            dedicated_reader=profile.dedicated_reader,
+            reduced_logging=profile.reduced_logging,
+            check_market_hours=profile.check_market_hours
+        )
+
     def to_dict(self) -> Dict[str, Any]:
         """
         Converts the configuration to a dictionary.
@@ -94,7 +219,14 @@ class Config:
             'reconnect_time': self.reconnect_time,
             'connection_initialization_timeout_secs': self.connection_initialization_timeout_secs,
             'timeout_secs': self.timeout_secs,
-            'urls': self.urls
+            'urls': self.urls,
+            'null_policy': self.null_policy,
+            'tcp_nodelay': self.tcp_nodelay,
+            'read_buffer_size': self.read_buffer_size,
+            'write_buffer_size': self.write_buffer_size,
+            'dedicated_reader': self.dedicated_reader,
+            'reduced_logging': self.reduced_logging,
+            'check_market_hours': self.check_market_hours
         }
 
     def to_json(self) -> str:
@@ -0,0 +1,311 @@
+"""
+Authenticated remote control of a running `Service`, so the bots hosted on a VPS can be managed from a laptop
+without SSH-ing in: start / stop the strategy, update the risk limits and fetch the status.
+
+The API is served over HTTPS (TLS certificate and key files) and every request must carry the token of the service
+in an `Authorization: Bearer <token>` header. Plain HTTP is only allowed with `insecure=True`, for local testing.
+
+Endpoints (JSON bodies and responses):
+    * `GET /status`: State of the service and the strategy, balance, health, risk rules and journal statistics
+    * `POST /strategy/start`, `POST /strategy/stop`, `POST /strategy/restart`: Control the strategy, the client
+      stays connected while the strategy is stopped
+    * `POST /risk`: Updates the risk limits, body with any of `{"clear": true}`, `{"cluster_guard": {"max_trades": 1,
+      "window": 60, "aligned": true}}`, `{"drawdown": {"max_drawdown": 0.2, "demo_ssid": null}}`, `{"reset_drawdown": true}`
+    * `POST /shutdown`: Stops the service
+
+Example:
+    ```
+    # On the VPS
+    BOT_CONTROL_TOKEN="..." python -m BinaryOptionsToolsV2.service --strategy my_bot:run \\
+        --control-port 8443 --control-cert cert.pem --control-key key.pem
+
+    # On the laptop
+    BOT_CONTROL_TOKEN="..." python -m BinaryOptionsToolsV2.control https://my-vps:8443 status
+    BOT_CONTROL_TOKEN="..." python -m BinaryOptionsToolsV2.control https://my-vps:8443 strategy/stop
+    ```
+"""
+from __future__ import annotations
+from BinaryOptionsToolsV2.tracing import Logger
+
+from datetime import timedelta
+from typing import Any, Optional
+
+import argparse
+import asyncio
+import hmac
+import json
+import os
+import ssl
+import sys
+import urllib.error
+import urllib.request
+
+TOKEN_ENV = "BOT_CONTROL_TOKEN"
+MAX_BODY_SIZE = 64 * 1024
+# Seconds a client has to send the head of its request, then its body
+REQUEST_TIMEOUT = 10
+
+STATUS_TEXT = {
+    200: "OK",
+    400: "Bad Request",
+    401: "Unauthorized",
+    404: "Not Found",
+    405: "Method Not Allowed",
+    413: "Payload Too Large",
+    500: "Internal Server Error",
+}
+
+
+class ControlError(Exception):
+    def __init__(self, status: int, message: str):
+        super().__init__(message)
+        self.status = status
+
+
+class ControlServer:
+    """
+    HTTPS server exposing the control API of a `Service`, see the module documentation for the endpoints.
+    """
+
+    def __init__(
+        self,
+        service,
+        token: str,
+        port: int,
+        host: str = "0.0.0.0",
+        certfile: Optional[str] = None,
+        keyfile: Optional[str] = None,
+        insecure: bool = False,
+    ):
+        """
+        Args:
+            service (Service): Service to control
+            token (str): Secret token every request must send in its `Authorization: Bearer` header
+            port (int): Port to listen on
+            host (str): Interface to listen on. Defaults to all the interfaces
+            certfile (str, optional): TLS certificate (PEM), required unless `insecure` is True
+            keyfile (str, optional): Private key of the certificate, if it's not in `certfile`
+            insecure (bool): Serve plain HTTP without TLS, only meant for local testing
+        """
+        if not token:
+            raise ValueError("A token is required to expose the control API")
+        if certfile is None and not insecure:
+            raise ValueError("A TLS certificate is required to expose the control API, use `insecure=True` to serve plain HTTP")
+        self.service = service
+        self.token = token
+        self.port = port
+        self.host = host
+        self.logger = Logger()
+        self._ssl = None
+        if certfile is not None:
+            self._ssl = ssl.create_default_context(ssl.Purpose.CLIENT_AUTH)
+            self._ssl.minimum_version = ssl.TLSVersion.TLSv1_2
+            self._ssl.load_cert_chain(certfile, keyfile)
+        self._server: Optional[asyncio.AbstractServer] = None
+        self._routes = {
+            ("GET", "/status"): self._status,
+            ("POST", "/strategy/start"): self._start_strategy,
+            ("POST", "/strategy/stop"): self._stop_strategy,
+            ("POST", "/strategy/restart"): self._restart_strategy,
+            ("POST", "/risk"): self._update_risk,
+            ("POST", "/shutdown"): self._shutdown,
+        }
+
+    async def start(self) -> None:
+        self._server = await asyncio.start_server(self._handle, self.host, self.port, ssl=self._ssl)
+        # The port picked by the system if 0 was given
+        self.port = self._server.sockets[0].getsockname()[1]
+        scheme = "https" if self._ssl is not None else "http"
+        self.logger.info(f"Control API listening on {scheme}://{self.host}:{self.port}")
+        if self._ssl is None:
+            self.logger.warn("Control API served without TLS, the token is sent in clear text")
+
+    async def stop(self) -> None:
+        if self._server is not None:
+            self._server.close()
+            await self._server.wait_closed()
+            self._server = None
+
+    def _authorized(self, headers: dict[str, str]) -> bool:
+        scheme, _, token = headers.get("authorization", "").partition(" ")
+        return scheme.lower() == "bearer" and hmac.compare_digest(token.strip().encode(), self.token.encode())
+
+    async def _read_head(self, reader: asyncio.StreamReader) -> tuple[str, str, dict[str, str]]:
+        request_line = (await reader.readline()).decode("latin-1").strip()
+        try:
+            method, path, _ = request_line.split(" ", 2)
+        except ValueError:
+            raise ControlError(400, "Malformed request line") from None
+        headers = {}
+        while True:
+            line = (await reader.readline()).decode("latin-1")
+            if line in ("\r\n", "\n", ""):
+                break
+            name, _, value = line.partition(":")
+            headers[name.strip().lower()] = value.strip()
+        return method.upper(), path.split("?", 1)[0].rstrip("/") or "/", headers
+
+    async def _read_body(self, reader: asyncio.StreamReader, headers: dict[str, str]) -> bytes:
+        length = headers.get("content-length") or "0"
+        if not (length.isascii() and length.isdigit()):
+            raise ControlError(400, "Invalid Content-Length header")
+        length = int(length)
+        if length > MAX_BODY_SIZE:
+            raise ControlError(413, "Request body too large")
+        return await reader.readexactly(length) if length else b""
+
+    async def _handle(self, reader: asyncio.StreamReader, writer: asyncio.StreamWriter):
+        peer = writer.get_extra_info("peername")
+        try:
+            method, path, headers = await asyncio.wait_for(self._read_head(reader), timeout=REQUEST_TIMEOUT)
+            # The body of an unauthorized request is never read
+            if not self._authorized(headers):
+                self.logger.warn(f"Rejected unauthorized control request from {peer}")
+                raise ControlError(401, "Invalid or missing token")
+            body = await asyncio.wait_for(self._read_body(reader, headers), timeout=REQUEST_TIMEOUT)
+            route = self._routes.get((method, path))
+            if route is None:
+                allowed = any(p == path for _, p in self._routes)
+                raise ControlError(405 if allowed else 404, f"No route for {method} {path}")
+            try:
+                payload = json.loads(body) if body else {}
+            except ValueError:
+                raise ControlError(400, "Body must be valid JSON") from None
+            self.logger.info(f"Control request {method} {path} from {peer}")
+            status, response = 200, await route(payload)
+        except ControlError as e:
+            status, response = e.status, {"error": str(e)}
+        except (asyncio.TimeoutError, asyncio.IncompleteReadError, ConnectionError):
+            writer.close()
+            return
+        except Exception as e:
+            self.logger.error(f"Error handling control request, {e}")
+            status, response = 500, {"error": str(e)}
+        data = json.dumps(response, default=str).encode()
+        writer.write(
+            (
+                f"HTTP/1.1 {status} {STATUS_TEXT.get(status, '')}\r\n"
+                f"Content-Type: application/json\r\nContent-Length: {len(data)}\r\nConnection: close\r\n\r\n"
+            ).encode()
+            + data
+        )
+        try:
+            await writer.drain()
+        finally:
+            writer.close()
+
+    async def _status(self, _: dict) -> dict:
+        return await self.service.status()
+
+    async def _start_strategy(self, _: dict) -> dict:
+        await self.service.start_strategy()
+        return await self.service.status()
+
+    async def _stop_strategy(self, _: dict) -> dict:
+        await self.service.stop_strategy()
+        return await self.service.status()
+
+    async def _restart_strategy(self, _: dict) -> dict:
+        self.service.request_reload()
+        return {"reloading": True}
+
+    async def _update_risk(self, payload: dict) -> dict:
+        client = self.service.client
+        if client is None:
+            raise ControlError(400, "The client isn't connected")
+        if payload.get("clear"):
+            client.clear_risk_rules()
+        if "cluster_guard" in payload:
+            guard = payload["cluster_guard"]
+            client.add_cluster_guard(
+                int(guard.get("max_trades", 1)), timedelta(seconds=float(guard.get("window", 60))), bool(guard.get("aligned", True))
+            )
+        if "drawdown" in payload:
+            drawdown = payload["drawdown"]
+            await client.set_drawdown_breaker(float(drawdown["max_drawdown"]), drawdown.get("demo_ssid"))
+        if payload.get("reset_drawdown"):
+            client.reset_drawdown_breaker()
+        return {"risk_rules": client.risk_rules(), "drawdown": client.drawdown_state()}
+
+    async def _shutdown(self, _: dict) -> dict:
+        self.service.request_stop()
+        return {"stopping": True}
+
+
+class ControlClient:
+    """Client of the control API, to manage a remote service from python"""
+
+    def __init__(self, url: str, token: Optional[str] = None, cafile: Optional[str] = None, verify: bool = True):
+        """
+        Args:
+            url (str): Base url of the control API (e.g. "https://my-vps:8443")
+            token (str, optional): Token of the service, defaults to the `BOT_CONTROL_TOKEN` environment variable
+            cafile (str, optional): CA (or self-signed certificate) used to verify the server
+            verify (bool): Verify the certificate of the server. Defaults to True
+        """
+        self.url = url.rstrip("/")
+        self.token = token or os.environ.get(TOKEN_ENV)
+        if not self.token:
+            raise ValueError(f"A token is required, pass it or set ${TOKEN_ENV}")
+        self._ssl = ssl.create_default_context(cafile=cafile)
+        if not verify:
+            self._ssl.check_hostname = False
+            self._ssl.verify_mode = ssl.CERT_NONE
+
+    def request(self, method: str, path: str, payload: Optional[dict] = None) -> Any:
+        data = json.dumps(payload).encode() if payload is not None else None
+        request = urllib.request.Request(
+            f"{self.url}/{path.lstrip('/')}",
+            data=data,
+            method=method,
+            headers={"Authorization": f"Bearer {self.token}", "Content-Type": "application/json"},
+        )
+        context = self._ssl if self.url.startswith("https") else None
+        try:
+            with urllib.request.urlopen(request, context=context, timeout=30) as response:
+                return json.loads(response.read())
+        except urllib.error.HTTPError as e:
+            raise ControlError(e.code, json.loads(e.read() or b"{}").get("error", e.reason)) from None
+
+    def status(self) -> dict:
+        return self.request("GET", "status")
+
+    def start_strategy(self) -> dict:
+        return self.request("POST", "strategy/start")
+
+    def stop_strategy(self) -> dict:
+        return self.request("POST", "strategy/stop")
+
+    def restart_strategy(self) -> dict:
+        return self.request("POST", "strategy/restart")
+
+    def update_risk(self, **limits) -> dict:
+        """Updates the risk limits, see the `POST /risk` endpoint for the accepted keys"""
+        return self.request("POST", "risk", limits)
+
+    def shutdown(self) -> dict:
+        return self.request("POST", "shutdown")
+
+
+def main(argv: Optional[list[str]] = None):
+    """Command line client of the control API."""
+    parser = argparse.ArgumentParser(description="Manage a remote BinaryOptionsToolsV2 service")
+    parser.add_argument("url", help="Base url of the control API, e.g. https://my-vps:8443")
+    parser.add_argument("command", help="status, strategy/start, strategy/stop, strategy/restart, risk or shutdown")
+    parser.add_argument("body", nargs="?", default=None, help="JSON body of the `risk` command")
+    parser.add_argument("--token", default=None, help=f"Token of the service (defaults to ${TOKEN_ENV})")
+    parser.add_argument("--cafile", default=None, help="CA or self-signed certificate of the server")
+    parser.add_argument("--no-verify", action="store_true", help="Don't verify the certificate of the server")
+    args = parser.parse_args(argv)
+    client = ControlClient(args.url, args.token, args.cafile, not args.no_verify)
+    method = "GET" if args.command == "status" else "POST"
+    try:
+        result = client.request(method, args.command, json.loads(args.body) if args.body else None)
+    except ControlError as e:
+        print(f"Error {e.status}: {e}", file=sys.stderr)
+        sys.exit(1)
+    print(json.dumps(result, indent=2))
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,220 @@
+"""
+Reads the Pocket Option session from the cookie store of a local browser to build the SSID, instead of copying it from
+the devtools. Nothing is read unless `ssid_from_browser` is called, and the cookie file can be given explicitly.
+
+Firefox stores its cookies in plain text. Chrome (and Chromium) encrypts them, decrypting them requires the
+`cryptography` package (`pip install cryptography`) and works with the keys of Linux (without a keyring), macOS (the
+keychain asks for permission) and Windows before the app-bound encryption of Chrome 127.
+
+Only the sessions of the real accounts are stored in the cookies, the uid of the account is shown in its profile.
+
+Example:
+    ```python
+    info = ssid_from_browser(87742848, browser="firefox")
+    api = PocketOptionAsync(info.ssid)
+    ```
+"""
+from __future__ import annotations
+
+from .ssid import SsidInfo, parse_ssid
+
+from pathlib import Path
+from urllib.parse import quote
+
+import base64
+import hashlib
+import os
+import shutil
+import sqlite3
+import subprocess
+import sys
+import tempfile
+
+SESSION_COOKIE = "ci_session"
+DEFAULT_DOMAIN = "pocketoption.com"
+
+
+def _home(*parts: str) -> Path:
+    return Path.home().joinpath(*parts)
+
+
+def _firefox_cookie_files() -> list[Path]:
+    if sys.platform == "win32":
+        roots = [Path(os.environ.get("APPDATA", "")) / "Mozilla" / "Firefox" / "Profiles"]
+    elif sys.platform == "darwin":
+        roots = [_home("Library", "Application Support", "Firefox", "Profiles")]
+    else:
+        roots = [_home(".mozilla", "firefox"), _home("snap", "firefox", "common", ".mozilla", "firefox")]
+    return [file for root in roots if root.is_dir() for file in root.glob("*/cookies.sqlite")]
+
+
+def _chrome_roots() -> list[Path]:
+    if sys.platform == "win32":
+        local = Path(os.environ.get("LOCALAPPDATA", ""))
+        return [local / "Google" / "Chrome" / "User Data", local / "Chromium" / "User Data"]
+    if sys.platform == "darwin":
+        return [_home("Library", "Application Support", "Google", "Chrome"), _home("Library", "Application Support", "Chromium")]
+    return [_home(".config", "google-chrome"), _home(".config", "chromium")]
+
+
+def _chrome_cookie_files() -> list[Path]:
+    files = []
+    for root in _chrome_roots():
+        if root.is_dir():
+            files.extend(root.glob("*/Network/Cookies"))
+            files.extend(root.glob("*/Cookies"))
+    return files
+
+
+def _query(cookie_file: Path, sql: str, params: tuple) -> list[tuple]:
+    "Runs the query on a copy of the database, the browser keeps it locked while it's running"
+    with tempfile.TemporaryDirectory() as directory:
+        copy = Path(directory) / "cookies.sqlite"
+        shutil.copyfile(cookie_file, copy)
+        connection = sqlite3.connect(copy)
+        try:
+            return connection.execute(sql, params).fetchall()
+        finally:
+            connection.close()
+
+
+def _cipher():
+    try:
+        from cryptography.hazmat.primitives.ciphers import Cipher, algorithms, modes
+        from cryptography.hazmat.primitives.ciphers.aead import AESGCM
+    except ImportError as e:
+        raise RuntimeError("the cookies of Chrome are encrypted, install the 'cryptography' package to decrypt them") from e
+    return Cipher, algorithms, modes, AESGCM
+
+
+def _windows_key(root: Path) -> bytes:
+    "Decrypts the key of the cookies stored in the `Local State` file with DPAPI"
+    import ctypes
+    import ctypes.wintypes
+    import json
+
+    local_state = json.loads((root / "Local State").read_text(encoding="utf-8"))
+    encrypted = base64.b64decode(local_state["os_crypt"]["encrypted_key"])[len(b"DPAPI"):]
+
+    class Blob(ctypes.Structure):
+        _fields_ = [("cbData", ctypes.wintypes.DWORD), ("pbData", ctypes.POINTER(ctypes.c_char))]
+
+    source = Blob(len(encrypted), ctypes.create_string_buffer(encrypted, len(encrypted)))
+    output = Blob()
+    if not ctypes.windll.crypt32.CryptUnprotectData(ctypes.byref(source), None, None, None, None, 0, ctypes.byref(output)):
+        raise RuntimeError("failed to decrypt the key of the Chrome cookies with DPAPI")
+    key = ctypes.string_at(output.pbData, output.cbData)
+    ctypes.windll.kernel32.LocalFree(output.pbData)
+    return key
+
+
+def _decrypt_chrome(cookie_file: Path, value: bytes, host_hash: bool) -> str:
+    Cipher, algorithms, modes, AESGCM = _cipher()
+    version, payload = value[:3], value[3:]
+    if version == b"v20":
+        raise RuntimeError("the cookie uses the app-bound encryption of Chrome 127+, use Firefox or copy the SSID from the devtools")
+    if sys.platform == "win32":
+        # `cookie_file` is `<User Data>/<profile>/Network/Cookies` or `<User Data>/<profile>/Cookies`
+        root = next(parent for parent in cookie_file.parents if (parent / "Local State").exists())
+        decrypted = AESGCM(_windows_key(root)).decrypt(payload[:12], payload[12:], None)
+    else:
+        if version == b"v11":
+            raise RuntimeError("the cookie is encrypted with the password of the system keyring, which isn't supported")
+        if sys.platform == "darwin":
+            password = subprocess.run(
+                ["security", "find-generic-password", "-w", "-s", "Chrome Safe Storage"],
+                capture_output=True, check=True, text=True,
+            ).stdout.strip().encode()
+            iterations = 1003
+        else:
+            password, iterations = b"peanuts", 1
+        key = hashlib.pbkdf2_hmac("sha1", password, b"saltysalt", iterations, 16)
+        decryptor = Cipher(algorithms.AES(key), modes.CBC(b" " * 16)).decryptor()
+        padded = decryptor.update(payload) + decryptor.finalize()
+        decrypted = padded[:-padded[-1]]
+    # Since version 24 of the database the value is prefixed with the hash of the domain
+    return (decrypted[32:] if host_hash else decrypted).decode()
+
+
+def _firefox_session(cookie_file: Path, domain: str) -> tuple[int, str] | None:
+    rows = _query(
+        cookie_file,
+        "SELECT lastAccessed, value FROM moz_cookies WHERE name = ? AND host LIKE ? ORDER BY lastAccessed DESC LIMIT 1",
+        (SESSION_COOKIE, f"%{domain}"),
+    )
+    return (rows[0][0] // 1_000_000, rows[0][1]) if rows else None
+
+
+def _chrome_session(cookie_file: Path, domain: str) -> tuple[int, str] | None:
+    rows = _query(
+        cookie_file,
+        "SELECT last_access_utc, value, encrypted_value FROM cookies WHERE name = ? AND host_key LIKE ? "
+        "ORDER BY last_access_utc DESC LIMIT 1",
+        (SESSION_COOKIE, f"%{domain}"),
+    )
+    if not rows:
+        return None
+    version = _query(cookie_file, "SELECT value FROM meta WHERE key = 'version'", ())
+    last_access, value, encrypted = rows[0]
+    if not value and encrypted:
+        value = _decrypt_chrome(cookie_file, encrypted, bool(version) and int(version[0][0]) >= 24)
+    # Chrome counts the microseconds since 1601
+    return (last_access // 1_000_000 - 11_644_473_600, value)
+
+
+def read_session_cookie(browser: str = "firefox", cookie_file: str | Path | None = None, domain: str = DEFAULT_DOMAIN) -> str:
+    """
+    Returns the url-encoded value of the `ci_session` cookie of the site, from the most recently used profile.
+
+    Args:
+        browser (str): "firefox" or "chrome" (also for Chromium). Defaults to "firefox"
+        cookie_file (str | Path, optional): Cookie database to read (`cookies.sqlite` for Firefox, `Cookies` for
+            Chrome), every profile of the browser is searched if not given
+        domain (str): Domain of the site. Defaults to "pocketoption.com"
+
+    Raises:
+        ValueError: If the browser isn't supported
+        FileNotFoundError: If no cookie database was found
+        LookupError: If no session cookie of the site was found, log in the site with the browser first
+    """
+    browser = browser.lower()
+    if browser == "firefox":
+        files, read = _firefox_cookie_files(), _firefox_session
+    elif browser in ("chrome", "chromium"):
+        files, read = _chrome_cookie_files(), _chrome_session
+    else:
+        raise ValueError(f"unsupported browser '{browser}', expected 'firefox' or 'chrome'")
+    if cookie_file is not None:
+        files = [Path(cookie_file)]
+    files = [file for file in files if file.is_file()]
+    if not files:
+        raise FileNotFoundError(f"no cookie database of {browser} was found, give its path with `cookie_file`")
+    sessions = [session for session in (read(file, domain) for file in files) if session is not None]
+    if not sessions:
+        raise LookupError(f"no '{SESSION_COOKIE}' cookie of {domain} in {browser}, log in the site with this browser first")
+    return max(sessions)[1]
+
+
+def ssid_from_browser(
+    uid: int,
+    browser: str = "firefox",
+    cookie_file: str | Path | None = None,
+    domain: str = DEFAULT_DOMAIN,
+) -> SsidInfo:
+    """
+    Builds the SSID of a real account from the session cookie stored by a local browser logged in the site.
+
+    Args:
+        uid (int): Id of the account, shown in its profile
+        browser (str): "firefox" or "chrome" (also for Chromium). Defaults to "firefox"
+        cookie_file (str | Path, optional): Cookie database to read, every profile of the browser is searched if not given
+        domain (str): Domain of the site. Defaults to "pocketoption.com"
+
+    Returns:
+        SsidInfo: The account, its session and the normalized `ssid` to create the clients with, see `parse_ssid`
+    """
+    session = read_session_cookie(browser, cookie_file, domain)
+    # Chrome stores the decoded value, the cookie is encoded again so both are parsed the same way
+    if "%" not in session:
+        session = quote(session, safe="")
+    return parse_ssid(f"{SESSION_COOKIE}={session}", uid, demo=False)
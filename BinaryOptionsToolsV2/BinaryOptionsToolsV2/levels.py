@@ -0,0 +1,35 @@
+"""
+Price levels of a history of candles, computed in Rust: the classic floor pivots, the highs and lows of the sessions and
+support / resistance zones clustering the swing highs and lows. `compute_levels` works on candles already downloaded,
+`PocketOptionAsync.levels` downloads them and the `levels` method of the candle subscriptions annotates a stream.
+
+Example:
+    ```python
+    candles = await api.get_candles("EURUSD_otc", 300, 3 * 86400)
+    levels = compute_levels(candles, zone_width=0.3)
+    print(levels["pivots"]["pivot"], [zone["price"] for zone in levels["zones"]])
+    ```
+"""
+from __future__ import annotations
+
+from .pocketoption.asyncronous import _candles_to_json, _levels_config
+from BinaryOptionsToolsV2 import Candle
+from BinaryOptionsToolsV2 import compute_levels as _compute_levels
+
+import json
+
+
+def compute_levels(candles: list[dict | Candle], **config) -> dict:
+    """
+    Computes the price levels of a history of candles, relative to its last close.
+
+    Args:
+        candles (list[dict | Candle]): Candles in chronological order, like the ones returned by `get_candles`
+        **config: Settings of the levels (`session`, `swing_window`, `zone_width`, `min_touches` and `max_zones`), see
+            `PocketOptionAsync.levels`
+
+    Returns:
+        dict: The `pivots`, `sessions`, `zones`, `nearest_support` and `nearest_resistance`, see
+            `PocketOptionAsync.levels`
+    """
+    return json.loads(_compute_levels(_candles_to_json(candles), _levels_config(**config)))
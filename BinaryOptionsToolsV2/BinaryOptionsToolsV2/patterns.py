@@ -0,0 +1,63 @@
+"""
+Candlestick pattern detection, running in Rust: engulfing, doji, pin bars, inside bars, three white soldiers / black
+crows and morning / evening stars. `detect_patterns` scans a history of candles and the `patterns` method of the candle
+subscriptions annotates a stream.
+
+Every pattern is reported as a dict with:
+    - pattern: Name of the pattern, one of `PATTERNS`
+    - bias: "bullish", "bearish" or "neutral" (doji and inside bar)
+    - start: Time of the first candle of the pattern
+    - time: Time of the candle completing the pattern
+    - confidence: How clearly the candles match the pattern, from 0 to 1 (not a probability of the price moving)
+
+Example:
+    ```python
+    candles = await api.get_candles("EURUSD_otc", 60, 3600)
+    for event in detect_patterns(candles, min_confidence=0.5):
+        print(event["time"], event["pattern"], event["confidence"])
+    ```
+"""
+from __future__ import annotations
+
+from .pocketoption.asyncronous import _candles_to_json, _pattern_config
+from BinaryOptionsToolsV2 import Candle
+from BinaryOptionsToolsV2 import detect_patterns as _detect_patterns
+
+import json
+
+PATTERNS = [
+    "bullish_engulfing",
+    "bearish_engulfing",
+    "doji",
+    "bullish_pin_bar",
+    "bearish_pin_bar",
+    "inside_bar",
+    "three_white_soldiers",
+    "three_black_crows",
+    "morning_star",
+    "evening_star",
+]
+
+
+def detect_patterns(
+    candles: list[dict | Candle], patterns: list[str] | None = None, min_confidence: float = 0.0, **thresholds: float
+) -> list[dict]:
+    """
+    Finds the candlestick patterns of a history of closed candles, in chronological order.
+
+    Args:
+        candles (list[dict | Candle]): Candles in chronological order, like the ones returned by `get_candles`
+        patterns (list[str], optional): Patterns to look for, see `PATTERNS`. Defaults to all of them
+        min_confidence (float): Patterns with a lower confidence (0 to 1) aren't reported. Defaults to 0
+        **thresholds: Thresholds of the patterns, as a share of the range of a candle:
+            - doji_body: Largest body of a doji. Defaults to 0.1
+            - pin_wick: Smallest wick of a pin bar. Defaults to 0.6
+            - star_body: Largest body of the middle candle of a star, relative to the first one. Defaults to 0.3
+            - soldier_wick: Largest wick against the trend of three soldiers / crows, relative to their body.
+              Defaults to 0.5
+
+    Returns:
+        list[dict]: The patterns found, see the module documentation
+    """
+    config = _pattern_config(patterns, min_confidence, **thresholds)
+    return json.loads(_detect_patterns(_candles_to_json(candles), config))
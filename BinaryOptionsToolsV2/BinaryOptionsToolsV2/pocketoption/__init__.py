@@ -5,9 +5,15 @@ Contains asynchronous and synchronous clients,
 as well as specific classes for Pocket Option trading.
 """
 
-__all__ = ['asyncronous', 'syncronous', 'PocketOptionAsync', 'PocketOption']
+__all__ = ['asyncronous', 'syncronous', 'ipc', 'pool', 'accounts', 'templates', 'timeframe', 'orders', 'experiment', 'PocketOptionAsync', 'PocketOption', 'PocketOptionIpcAsync', 'PocketOptionIpc', 'ClientPool', 'AccountPair', 'OrderTemplate', 'Timeframe', 'Direction', 'ABExperiment', 'Arm']
 
-from . import asyncronous, syncronous
+from . import asyncronous, syncronous, ipc, pool, accounts, templates, timeframe, orders, experiment
 from .asyncronous import PocketOptionAsync
 from .syncronous import PocketOption
-
+from .ipc import PocketOptionIpcAsync, PocketOptionIpc
+from .pool import ClientPool
+from .accounts import AccountPair
+from .templates import OrderTemplate
+from .timeframe import Timeframe
+from .orders import Direction
+from .experiment import ABExperiment, Arm
@@ -0,0 +1,153 @@
+"""
+Demo and real views of the same user behind a single object, for strategies switching between both accounts.
+
+The demo and real accounts are served by different servers (the demo account has its own endpoint), so the
+protocol doesn't allow authenticating both on one websocket. `AccountPair` keeps the cost as close as possible
+to a single client: only the active account is connected at first, the other one is connected the first time
+it's used, switching between them afterwards is free, and the market data (candles, payouts, subscriptions)
+is always read from a connection that is already open instead of opening one just for it.
+
+Example:
+    ```python
+    pair = AccountPair(real_ssid, demo_ssid, mode="demo")
+
+    await pair.buy("EURUSD_otc", 1.0, 60)          # Placed on the demo account
+    candles = await pair.market().get_candles("EURUSD_otc", 60, 3600)
+
+    await pair.switch("real")                      # Connects the real account the first time
+    await pair.buy("EURUSD_otc", 1.0, 60)          # Placed on the real account
+    print(pair.connected())                        # {"demo": True, "real": True}
+    ```
+"""
+from __future__ import annotations
+from .asyncronous import PocketOptionAsync
+from BinaryOptionsToolsV2.config import Config
+
+import asyncio
+
+MODES = ("real", "demo")
+
+
+class AccountPair:
+    """
+    Lazily connected demo and real clients of the same user, trading on the active one.
+    """
+
+    def __init__(
+        self,
+        real_ssid: str,
+        demo_ssid: str,
+        mode: str = "demo",
+        config: Config | dict | str | None = None,
+        url: str | None = None,
+    ):
+        """
+        Args:
+            real_ssid (str): Session id of the real account
+            demo_ssid (str): Session id of the demo account
+            mode (str): Account trading starts on, "real" or "demo". Defaults to "demo"
+            config (Config | dict | str, optional): Configuration of both clients
+            url (str, optional): Custom websocket url, only used by the real account (the demo account has its own server)
+        """
+        self._ssids = {"real": real_ssid, "demo": demo_ssid}
+        self._clients: dict[str, PocketOptionAsync] = {}
+        self._locks = {m: asyncio.Lock() for m in MODES}
+        self._mode = self._check_mode(mode)
+        self.config = config
+        self.url = url
+
+    @staticmethod
+    def _check_mode(mode: str) -> str:
+        mode = mode.lower()
+        if mode not in MODES:
+            raise ValueError(f"Invalid account mode '{mode}', expected 'real' or 'demo'")
+        return mode
+
+    def _config(self):
+        if isinstance(self.config, Config):
+            # A config is locked once it's used by a client, every client needs its own copy
+            return Config.from_dict(self.config.to_dict())
+        return self.config
+
+    @property
+    def mode(self) -> str:
+        """Account the trades are placed on, 'real' or 'demo'"""
+        return self._mode
+
+    def connected(self) -> dict[str, bool]:
+        """Returns which accounts currently have an open connection"""
+        return {m: m in self._clients for m in MODES}
+
+    async def client(self, mode: str) -> PocketOptionAsync:
+        """Returns the client of an account ('real' or 'demo'), connecting it if it's the first time it's used"""
+        mode = self._check_mode(mode)
+        async with self._locks[mode]:
+            if mode not in self._clients:
+                url = self.url if mode == "real" else None
+                # The client connects in its constructor, so it's created outside of the event loop
+                self._clients[mode] = await asyncio.get_running_loop().run_in_executor(
+                    None, PocketOptionAsync, self._ssids[mode], url, self._config()
+                )
+            return self._clients[mode]
+
+    async def switch(self, mode: str) -> PocketOptionAsync:
+        """
+        Makes `mode` the active account, connecting it if needed, and returns its client.
+        Switching to an account that was already used doesn't open a new connection.
+        """
+        client = await self.client(mode)
+        self._mode = self._check_mode(mode)
+        return client
+
+    async def active(self) -> PocketOptionAsync:
+        """Returns the client of the active account"""
+        return await self.client(self._mode)
+
+    async def real(self) -> PocketOptionAsync:
+        return await self.client("real")
+
+    async def demo(self) -> PocketOptionAsync:
+        return await self.client("demo")
+
+    def market(self) -> PocketOptionAsync:
+        """
+        Returns a client to read market data from (candles, payouts, subscriptions), the prices are the same on
+        both accounts so the active one is preferred and no connection is opened for it.
+        """
+        client = self._clients.get(self._mode) or next(iter(self._clients.values()), None)
+        if client is None:
+            raise RuntimeError("No account is connected yet, call `switch` or `active` first")
+        return client
+
+    async def buy(
+        self, asset: str, amount: float, time: int, check_win: bool = False, min_payout: int | None = None
+    ) -> tuple[str, dict]:
+        """Places a buy trade on the active account, see `PocketOptionAsync.buy`"""
+        return await (await self.active()).buy(asset, amount, time, check_win, min_payout)
+
+    async def sell(
+        self, asset: str, amount: float, time: int, check_win: bool = False, min_payout: int | None = None
+    ) -> tuple[str, dict]:
+        """Places a sell trade on the active account, see `PocketOptionAsync.sell`"""
+        return await (await self.active()).sell(asset, amount, time, check_win, min_payout)
+
+    async def check_win(self, id: str, mode: str | None = None, timeout: float | None = None) -> dict:
+        """Returns the result of a trade, placed on the active account unless `mode` is given, see `PocketOptionAsync.check_win`"""
+        return await (await self.client(mode or self._mode)).check_win(id, timeout)
+
+    async def balance(self, mode: str | None = None) -> float:
+        """Returns the balance of the active account unless `mode` is given"""
+        return await (await self.client(mode or self._mode)).balance()
+
+    async def close(self, mode: str | None = None) -> None:
+        """Closes the connection of an account (or of both), it will be reconnected the next time it's used"""
+        for m in [self._check_mode(mode)] if mode is not None else list(MODES):
+            async with self._locks[m]:
+                self._clients.pop(m, None)
+
+    async def __aenter__(self):
+        await self.active()
+        return self
+
+    async def __aexit__(self, *_):
+        await self.close()
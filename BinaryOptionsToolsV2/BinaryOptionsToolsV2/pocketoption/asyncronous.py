@@ -1,29 +1,260 @@
+from __future__ import annotations
+
 from BinaryOptionsToolsV2.validator import Validator
 from BinaryOptionsToolsV2.config import Config
-from BinaryOptionsToolsV2 import RawPocketOption, Logger
-from datetime import timedelta
+from BinaryOptionsToolsV2.ssid import SsidInfo, _ssid_info, parse_ssid
+from BinaryOptionsToolsV2.tracing import StreamLogsLayer
+from .templates import OrderTemplate
+from .timeframe import Timeframe
+from .orders import Direction
+from BinaryOptionsToolsV2 import RawPocketOption, Logger, AccessControl, Candle, OrderDirection, Deal, Asset
+from datetime import datetime, timedelta, timezone
+from typing import Awaitable, Callable
 
 
 import asyncio
+import inspect
 import json
 import time 
 import sys 
 
 
+def _candle_predicate(predicate: Validator | Callable[[dict], bool]):
+    "Returns the predicate of `filter` as expected by the iterators: a `RawValidator` or a function of the json string"
+    if isinstance(predicate, Validator):
+        return predicate.raw_validator
+    if callable(predicate):
+        return lambda candle: bool(predicate(json.loads(candle)))
+    raise TypeError(f"Expected a Validator or a function to filter the candles, got {type(predicate).__name__}")
+
+
+def _utc(timestamp: datetime | float) -> datetime:
+    "Returns `timestamp` (a unix timestamp or a datetime, naive ones are in UTC) as an aware UTC datetime"
+    if isinstance(timestamp, datetime):
+        return timestamp.replace(tzinfo=timezone.utc) if timestamp.tzinfo is None else timestamp.astimezone(timezone.utc)
+    return datetime.fromtimestamp(timestamp, timezone.utc)
+
+
+def _seconds(duration: timedelta | float) -> float:
+    "Returns a duration as seconds, the numbers are already seconds"
+    return duration.total_seconds() if isinstance(duration, timedelta) else float(duration)
+
+
+def _pattern_config(patterns: list[str] | None = None, min_confidence: float = 0.0, **thresholds: float) -> str:
+    "Returns the json of the `PatternConfig` of the pattern detection functions"
+    return json.dumps({"patterns": list(patterns or []), "min_confidence": min_confidence, **thresholds})
+
+
+def _levels_config(**config) -> str | None:
+    "Returns the json of the `LevelsConfig` of the level functions, None for the defaults"
+    return json.dumps(config) if config else None
+
+
+def _chart_options(width: int = 1000, height: int = 500, title: str | None = None) -> str:
+    "Returns the json of the `ChartOptions` of the chart functions"
+    return json.dumps({"width": width, "height": height, "title": title})
+
+
+def _chart_markers(markers: list[dict | tuple] | None) -> str | None:
+    "Returns the json of the markers of a chart, given as `(time, price, kind)` tuples or dicts with these keys"
+    if not markers:
+        return None
+    markers = [marker if isinstance(marker, dict) else dict(zip(("time", "price", "kind"), marker)) for marker in markers]
+    return json.dumps([
+        {"time": _utc(marker["time"]).isoformat(), "price": float(marker["price"]), "kind": str(marker["kind"]).lower()}
+        for marker in markers
+    ])
+
+
+def _require_native(client, method: str, feature: str):
+    "Raises a `RuntimeError` if the extension module was built without the feature of `method`"
+    if not hasattr(client, method):
+        raise RuntimeError(
+            f"BinaryOptionsToolsV2 was built without the `{feature}` feature, build it with `maturin build --release --features {feature}`"
+        )
+
+
+def _record_batch(stream: bytes):
+    "Reads the Arrow IPC stream returned by the `*_arrow` native methods as a `pyarrow.RecordBatch`, without a copy"
+    try:
+        import pyarrow.ipc
+    except ImportError as e:
+        raise ImportError("the Arrow methods require pyarrow, install it with `pip install BinaryOptionsToolsV2[arrow]`") from e
+    return pyarrow.ipc.open_stream(stream).read_next_batch()
+
+
+def _trade_filter(
+    asset: str | None = None,
+    tag: str | None = None,
+    hours: tuple[int, int] | None = None,
+    since: datetime | float | None = None,
+    until: datetime | float | None = None,
+) -> str:
+    "Returns the json of the `TradeFilter` selecting the journal trades of the analytics functions"
+    return json.dumps({
+        "asset": asset,
+        "tag": tag,
+        "from_hour": hours[0] if hours else None,
+        "to_hour": hours[1] if hours else None,
+        "since": _utc(since).isoformat() if since is not None else None,
+        "until": _utc(until).isoformat() if until is not None else None,
+    })
+
+
+def _item(item: str | Candle) -> dict | Candle:
+    "Parses the items of the iterators, the candle subscriptions yield `Candle` objects and the others json strings"
+    return json.loads(item) if isinstance(item, str) else item
+
+
 class AsyncSubscription:
     def __init__(self, subscription):
-        """Asyncronous Iterator over json objects"""
+        """Asyncronous Iterator over candles (as `Candle`) or json objects"""
         self.subscription = subscription
         
     def __aiter__(self):
         return self
         
     async def __anext__(self):
-        return json.loads(await anext(self.subscription))
+        return _item(await self.subscription.__anext__())
+
+    async def close(self) -> None:
+        """
+        Unsubscribes from the asset and ends the subscription immediately, even while it's waiting for a candle. The
+        other subscriptions of the asset stop receiving candles too. The subscriptions of events don't unsubscribe
+        anything, they only end.
+
+        Example:
+            ```python
+            async with await api.subscribe_symbol("EURUSD_otc") as stream:
+                async for candle in stream:
+                    if candle.close > target:
+                        break
+            # Unsubscribed here
+            ```
+        """
+        if hasattr(self.subscription, "aclose"):
+            await self.subscription.aclose()
+
+    async def __aenter__(self) -> AsyncSubscription:
+        return self
+
+    async def __aexit__(self, exc_type, exc_value, traceback) -> bool:
+        await self.close()
+        return False
+
+    def recent(self, n: int = 10) -> list[dict | Candle]:
+        """Returns the last `n` items delivered by the subscription (oldest first), useful to inspect what a strategy just saw"""
+        return [_item(item) for item in self.subscription.recent(n)]
+
+    def filter(self, predicate: Validator | Callable[[dict], bool]) -> AsyncSubscription:
+        """
+        Returns a subscription over the candles accepted by `predicate`, the filtering runs in Rust for a `Validator`
+        (checked against the candle as a json string). The combinators read the candles of this subscription, so only
+        the returned one should be iterated.
+
+        Args:
+            predicate (Validator | Callable[[dict], bool]): Validator, or function taking the candle and returning True
+                to keep it
+
+        Example:
+            ```python
+            stream = await api.subscribe_symbol("EURUSD_otc")
+            async for candle in stream.filter(lambda c: c["close"] > c["open"]).take(10):
+                print(candle)
+            ```
+        """
+        return AsyncSubscription(self.subscription.filter(_candle_predicate(predicate)))
+
+    def map_json(self, path: str) -> AsyncSubscription:
+        """
+        Returns a subscription over the value at `path` in each candle, e.g. `"close"`. The path is a json pointer
+        (`"/close"`) or keys and list indexes separated by dots, the candles without a value at `path` are skipped.
+        """
+        return AsyncSubscription(self.subscription.map_json(path))
+
+    def take(self, n: int) -> AsyncSubscription:
+        "Returns a subscription ending after `n` candles"
+        return AsyncSubscription(self.subscription.take(n))
+
+    def record(
+        self, path: str, compression: str | None = None, level: int | None = None, chunk_size: int | None = None
+    ) -> AsyncSubscription:
+        """
+        Returns a subscription appending every candle it yields to the recording at `path` (one json candle per line),
+        which can be replayed with `BinaryOptionsToolsV2.replay.CandleReplay`.
+
+        Args:
+            path (str): Recording to append the candles to
+            compression (str, optional): "none", "gzip" or "zstd". Defaults to the one of the extension of `path`
+                (".gz" or ".zst"), none otherwise
+            level (int, optional): Compression level, 0 to 9 for gzip and 1 to 22 for zstd. Defaults to 6 and 3
+            chunk_size (int, optional): Candles written at once, each chunk is compressed on its own so an interrupted
+                recording only loses its last chunk. Defaults to 512 for the compressed recordings and 1 otherwise
+
+        Example:
+            ```python
+            stream = await api.subscribe_symbol("EURUSD_otc")
+            async for candle in stream.record("eurusd.jsonl.zst", level=19):
+                ...
+            ```
+        """
+        return AsyncSubscription(self.subscription.record(path, compression, level, chunk_size))
+
+    def until(self, timestamp: datetime | float) -> AsyncSubscription:
+        """
+        Returns a subscription ending at the first candle at or after `timestamp` (a unix timestamp or a datetime,
+        naive datetimes are in UTC), which isn't yielded.
+        """
+        return AsyncSubscription(self.subscription.until(_utc(timestamp)))
+
+    def patterns(
+        self, patterns: list[str] | None = None, min_confidence: float = 0.0, **thresholds: float
+    ) -> AsyncSubscription:
+        """
+        Returns a subscription over the candlestick patterns completed by the candles, detected in Rust. Each item is a
+        dict with the `asset`, the `candle` and its `patterns`, the candles completing no pattern are skipped. The
+        candles must be closed candles, like the ones of `subscribe_symbol_timed`.
+
+        Args:
+            patterns (list[str], optional): Patterns to look for, see `BinaryOptionsToolsV2.patterns.PATTERNS`.
+                Defaults to all of them
+            min_confidence (float): Patterns with a lower confidence (0 to 1) aren't reported. Defaults to 0
+            **thresholds: Thresholds of the patterns (`doji_body`, `pin_wick`, `star_body`, `soldier_wick`), see
+                `BinaryOptionsToolsV2.patterns.detect_patterns`
+
+        Example:
+            ```python
+            stream = await api.subscribe_symbol_timed("EURUSD_otc", timedelta(minutes=1))
+            async for event in stream.patterns(["bullish_engulfing", "bullish_pin_bar"], min_confidence=0.6):
+                print(event["candle"]["close"], [p["pattern"] for p in event["patterns"]])
+            ```
+        """
+        return AsyncSubscription(self.subscription.patterns(_pattern_config(patterns, min_confidence, **thresholds)))
+
+    def levels(self, lookback: int = 100, **config) -> AsyncSubscription:
+        """
+        Returns a subscription yielding every candle with the levels of the last `lookback` candles, as a dict with the
+        `asset`, the `candle` and its `levels` (see `PocketOptionAsync.levels`). The candles must be closed candles, like
+        the ones of `subscribe_symbol_timed`.
+
+        Args:
+            lookback (int): Number of candles the levels are computed from. Defaults to 100
+            **config: Settings of the levels, see `PocketOptionAsync.levels`
+
+        Example:
+            ```python
+            stream = await api.subscribe_symbol_timed("EURUSD_otc", timedelta(minutes=1))
+            async for event in stream.levels(200):
+                support = event["levels"]["nearest_support"]
+                if support and event["candle"]["low"] <= support["high"]:
+                    print(f"Testing the support at {support['price']}")
+            ```
+        """
+        return AsyncSubscription(self.subscription.levels(lookback, _levels_config(**config)))
     
 # This file contains all the async code for the PocketOption Module
 class PocketOptionAsync:
-    def __init__(self, ssid: str, url: str | None = None, config: Config | dict | str = None, **_):
+    def __init__(self, ssid: str, url: str | None = None, config: Config | dict | str = None, observer: bool = False, otc_fallback: bool = False, **_):
         """
         Initializes a new PocketOptionAsync instance.
 
@@ -44,14 +275,38 @@ class PocketOptionAsync:
                     - connection_initialization_timeout_secs (int): Connection initialization timeout
                     - timeout_secs (int): General operation timeout
                     - urls (List[str]): List of fallback WebSocket URLs
+                    - tcp_nodelay, read_buffer_size, write_buffer_size, dedicated_reader, reduced_logging: Socket
+                      and reader options, `Config.low_latency()` sets them for the short expiries
+                    - check_market_hours (bool): Rejects the orders on closed assets with `AssetClosedError`
+            observer (bool, optional): Read-only observer mode, the client receives data and events but every method
+                sending an order raises `ObserverModeError`. For analytics deployments where trading must be
+                impossible. Defaults to False.
+            otc_fallback (bool, optional): While the market of a regular asset (e.g. "EURUSD") is closed, on weekends
+                for instance, `buy`, `sell` and `subscribe_symbol` switch to its OTC version ("EURUSD_otc") if it's open.
+                Defaults to False, see `set_otc_fallback`.
             **_: Additional keyword arguments (ignored)
 
+        Raises:
+            AuthenticationError: If the server rejects the session, right away instead of letting the requests time
+                out. Its message tells whether the session expired (copy a fresh one from the browser) or is malformed
+                (e.g. truncated while copying it)
+            RegionMismatchError: If the server reports that the client connects from another IP or region than the one
+                of the session (e.g. after moving the bot to another VPS), its message lists the steps to fix it. It's
+                a subclass of `AuthenticationError`
+
         Examples:
             Basic usage:
             ```python
             client = PocketOptionAsync("your-session-id")
             ```
 
+            In observer mode:
+            ```python
+            client = PocketOptionAsync("your-session-id", observer=True)
+            stream = await client.subscribe_symbol("EURUSD_otc")
+            await client.buy("EURUSD_otc", 1.0, 60)  # raises ObserverModeError
+            ```
+
             With custom WebSocket URL:
             ```python
             client = PocketOptionAsync("your-session-id", url="wss://custom-server.com/ws")
@@ -102,19 +357,23 @@ class PocketOptionAsync:
                 raise ValueError("Config must be either a Config object, dictionary, or JSON string")
 
             if url is not None:
-                self.client = RawPocketOption.new_with_url(ssid, url, self.config.pyconfig)
+                self.client = RawPocketOption.new_with_url(ssid, url, self.config.pyconfig, observer)
             else:
-                self.client = RawPocketOption(ssid, config, self.config.pyconfig)
+                self.client = RawPocketOption(ssid, self.config.pyconfig, observer)
         else: 
             self.config = Config()
             if url is not None:
-                self.client = RawPocketOption.new_with_url(ssid, url)
+                self.client = RawPocketOption.new_with_url(ssid, url, observer=observer)
             else:
-                self.client = RawPocketOption(ssid)
+                self.client = RawPocketOption(ssid, observer=observer)
+        if otc_fallback:
+            self.client.set_otc_fallback(True)
         self.logger = Logger()
     
     
-    async def buy(self, asset: str, amount: float, time: int, check_win: bool = False) -> tuple[str, dict]:
+    async def buy(
+        self, asset: str, amount: float, time: int, check_win: bool = False, min_payout: int | None = None
+    ) -> tuple[str, dict]:
         """
         Places a buy (call) order for the specified asset.
 
@@ -123,6 +382,8 @@ class PocketOptionAsync:
             amount (float): Trade amount in account currency
             time (int): Expiry time in seconds (e.g., 60 for 1 minute)
             check_win (bool): If True, waits for trade result. Defaults to True.
+            min_payout (int, optional): Refuses to place the trade if the payout of the asset is under this percentage
+                (or unknown), checked by the Rust client right before the order is sent
 
         Returns:
             tuple[str, dict]: Tuple containing (trade_id, trade_details)
@@ -138,15 +399,14 @@ class PocketOptionAsync:
             ConnectionError: If connection to platform fails
             ValueError: If invalid parameters are provided
             TimeoutError: If trade confirmation times out
+            PayoutTooLowError: If the payout of the asset is under `min_payout`
+            AssetClosedError: If the market of the asset is closed and `Config.check_market_hours` is set
         """
-        (trade_id, trade) = await self.client.buy(asset, amount, time)
-        if check_win:
-            return trade_id, await self.check_win(trade_id) 
-        else:
-            trade = json.loads(trade)
-            return trade_id, trade 
+        return await self.place_order(asset, Direction.CALL, amount, time, check_win=check_win, min_payout=min_payout)
        
-    async def sell(self, asset: str, amount: float, time: int, check_win: bool = False) -> tuple[str, dict]:
+    async def sell(
+        self, asset: str, amount: float, time: int, check_win: bool = False, min_payout: int | None = None
+    ) -> tuple[str, dict]:
         """
         Places a sell (put) order for the specified asset.
 
@@ -155,6 +415,8 @@ class PocketOptionAsync:
             amount (float): Trade amount in account currency
             time (int): Expiry time in seconds (e.g., 60 for 1 minute)
             check_win (bool): If True, waits for trade result. Defaults to True.
+            min_payout (int, optional): Refuses to place the trade if the payout of the asset is under this percentage
+                (or unknown), checked by the Rust client right before the order is sent
 
         Returns:
             tuple[str, dict]: Tuple containing (trade_id, trade_details)
@@ -170,20 +432,97 @@ class PocketOptionAsync:
             ConnectionError: If connection to platform fails
             ValueError: If invalid parameters are provided
             TimeoutError: If trade confirmation times out
+            PayoutTooLowError: If the payout of the asset is under `min_payout`
+            AssetClosedError: If the market of the asset is closed and `Config.check_market_hours` is set
+        """
+        return await self.place_order(asset, Direction.PUT, amount, time, check_win=check_win, min_payout=min_payout)
+
+    async def trade(self, asset: str, direction: OrderDirection | Direction | str, amount: float, time: int) -> Deal:
+        """
+        Places a trade in `direction`, so strategies don't have to choose between `buy` and `sell`.
+
+        Args:
+            asset (str): Trading asset (e.g., "EURUSD_otc", "EURUSD")
+            direction (OrderDirection | Direction | str): `OrderDirection.CALL` / `OrderDirection.PUT`, a `Direction` or "call" / "put" / "buy" / "sell"
+            amount (float): Trade amount in account currency
+            time (int): Expiry time in seconds (e.g., 60 for 1 minute)
+
+        Returns:
+            Deal: The placed trade, with the attributes `id`, `asset`, `direction`, `amount`, `open_price`,
+            `open_time`, `close_time`, `percent_profit`... Its result is given by `check_win(deal.id)`.
+
+        Raises:
+            ValueError: If the direction is invalid
+            Exception: If the trade is rejected by a risk rule or can't be placed
+
+        Example:
+            ```python
+            direction = OrderDirection.CALL if fast > slow else OrderDirection.PUT
+            deal = await client.trade("EURUSD_otc", direction, 1.0, 60)
+            result = await client.check_win(deal.id)
+            ```
+        """
+        return await self.client.trade(asset, direction, amount, time)
+
+    async def place_order(
+        self,
+        asset: str,
+        direction: Direction | str,
+        amount: float,
+        expiry: int,
+        *,
+        check_win: bool = False,
+        tags: list[str] | None = None,
+        dry_run: bool = False,
+        min_payout: int | None = None,
+    ) -> tuple[str | None, dict]:
+        """
+        Places a trade, the canonical entry point `buy` and `sell` are shortcuts of. Every trade goes through the
+        risk rules and the journal, and the options apply the same way to both directions.
+
+        Args:
+            asset (str): Trading asset (e.g., "EURUSD_otc", "EURUSD")
+            direction (Direction | str): Direction of the trade, `Direction.CALL` / "call" / "buy" or `Direction.PUT` / "put" / "sell"
+            amount (float): Trade amount in account currency
+            expiry (int): Expiry time in seconds (e.g., 60 for 1 minute)
+            check_win (bool): If True, waits for the trade result. Defaults to False.
+            tags (list[str], optional): Tags recorded in the journal with the trade (see `journal_stats`)
+            dry_run (bool): Runs every check (minimum payout, risk rules) without placing the trade. Defaults to False.
+            min_payout (int, optional): Rejects the trade with a `PayoutTooLowError` if the payout of the asset is under
+                this percentage, or unknown. The payout is checked right before the order is sent
+
+        Returns:
+            tuple[str | None, dict]: Tuple containing (trade_id, trade_details), same as `buy` / `sell`.
+            For a dry run the trade_id is None and the details contain the checked `asset`, `direction`,
+            `amount`, `expiry` and current `payout`.
+
+        Raises:
+            ValueError: If the direction is invalid
+            Exception: If the trade is rejected by the minimum payout or a risk rule, or can't be placed
+
+        Example:
+            ```python
+            trade_id, deal = await client.place_order("EURUSD_otc", "call", 1.0, 60, tags=["breakout"], min_payout=80)
+            _, order = await client.place_order("EURUSD_otc", Direction.PUT, 1.0, 60, dry_run=True)
+            ```
         """
-        (trade_id, trade) = await self.client.sell(asset, amount, time)
+        direction = Direction.parse(direction)
+        placement = await self.client.place_order(asset, direction.value, amount, expiry, tags, dry_run, min_payout)
+        if placement.pop("status") == "dry_run":
+            return None, placement
+        trade_id = placement["id"]
         if check_win:
-            return trade_id, await self.check_win(trade_id)   
-        else:
-            trade = json.loads(trade)
-            return trade_id, trade 
+            return trade_id, await self.check_win(trade_id)
+        return trade_id, placement["deal"]
  
-    async def check_win(self, id: str) -> dict:
+    async def check_win(self, id: str, timeout: timedelta | float | None = None) -> dict:
         """
         Checks the result of a specific trade.
 
         Args:
             trade_id (str): ID of the trade to check
+            timeout (timedelta | float, optional): Maximum time to wait for the result (seconds if a number).
+                Defaults to the time left before the expiry of the trade plus `config.extra_duration`.
 
         Returns:
             dict: Trade result containing:
@@ -194,8 +533,11 @@ class PocketOptionAsync:
 
         Raises:
             ValueError: If trade_id is invalid
+            TradeTimeoutError: If the result isn't received within `timeout`
             TimeoutError: If result check times out
         """
+        if timeout is not None:
+            return _with_result(await self.client.check_win(id, _seconds(timeout)))
         end_time = await self.client.get_deal_end_time(id)
         
         if end_time is not None:
@@ -208,295 +550,2225 @@ class PocketOptionAsync:
         
         self.logger.debug(f"Timeout set to: {duration} (6 extra seconds)")
         async def check(id):
-            trade = await self.client.check_win(id)
-            trade = json.loads(trade)
-            win = trade["profit"]
-            if win > 0:
-                trade["result"] = "win"
-            elif win == 0:
-                trade["result"] = "draw"
-            else:
-                trade["result"] = "loss"
-            return trade
+            return _with_result(await self.client.check_win(id))
         return await _timeout(check(id), duration)
-        
-        
-    async def get_candles(self, asset: str, period: int, offset: int) -> list[dict]:  
+
+    async def poll_result(self, id: str) -> dict | None:
         """
-        Retrieves historical candle data for an asset.
+        Returns the result of a trade without waiting, for loops checking several trades.
 
         Args:
-            asset (str): Trading asset (e.g., "EURUSD_otc")
-            timeframe (int): Candle timeframe in seconds (e.g., 60 for 1-minute candles)
-            period (int): Historical period in seconds to fetch
+            id (str): ID of the trade to check
 
         Returns:
-            list[dict]: List of candles, each containing:
-                - time: Candle timestamp
-                - open: Opening price
-                - high: Highest price
-                - low: Lowest price
-                - close: Closing price
+            dict | None: The trade with its "result" ("win", "loss" or "draw") like `check_win`, None
+            while the trade is still open. None is also returned for an id the client doesn't know (a
+            typo, or a trade of another session), use `opened_deals` to tell it apart from an open
+            trade, or `check_win`, which fails for an unknown id.
 
-        Note:
-            Available timeframes: 1, 5, 15, 30, 60, 300 seconds
-            Maximum period depends on the timeframe
+        Example:
+            ```python
+            while (trade := await client.poll_result(trade_id)) is None:
+                await asyncio.sleep(1)
+            ```
         """
-        candles = await self.client.get_candles(asset, period, offset)
-        return json.loads(candles)
-    
-    async def get_candles_advanced(self, asset: str, period: int, offset: int, time: int) -> list[dict]:  
+        trade = await self.client.poll_result(id)
+        return None if trade is None else _with_result(trade)
+
+    async def check_win_many(
+        self, trade_ids: list[str], timeout: timedelta | float | None = None, return_exceptions: bool = False
+    ) -> dict[str, dict | Exception]:
         """
-        Retrieves historical candle data for an asset.
+        Waits for the results of several trades concurrently in Rust, instead of one task per `check_win`.
 
         Args:
-            asset (str): Trading asset (e.g., "EURUSD_otc")
-            timeframe (int): Candle timeframe in seconds (e.g., 60 for 1-minute candles)
-            period (int): Historical period in seconds to fetch
-            time (int): Time to fetch candles from
+            trade_ids (list[str]): IDs of the trades to check
+            timeout (timedelta | float, optional): Maximum time to wait for each result (seconds if a number)
+            return_exceptions (bool): Returns the exception of a failed check in the dict instead of raising it,
+                like `asyncio.gather`. Defaults to False.
 
         Returns:
-            list[dict]: List of candles, each containing:
-                - time: Candle timestamp
-                - open: Opening price
-                - high: Highest price
-                - low: Lowest price
-                - close: Closing price
+            dict[str, dict | Exception]: The results by trade id, in the order of `trade_ids`, each with its
+            "result" ("win", "loss" or "draw") like `check_win`.
 
-        Note:
-            Available timeframes: 1, 5, 15, 30, 60, 300 seconds
-            Maximum period depends on the timeframe
-        """
-        candles = await self.client.get_candles_advanced(asset, period, offset, time)
-        return json.loads(candles)
+        Raises:
+            ValueError: If a trade id is invalid
+            TradeTimeoutError: If a result isn't received within `timeout` (without `return_exceptions`)
 
+        Example:
+            ```python
+            results = await client.check_win_many(trade_ids, timeout=120, return_exceptions=True)
+            wins = [id for id, trade in results.items() if isinstance(trade, dict) and trade["result"] == "win"]
+            ```
+        """
+        timeout = None if timeout is None else _seconds(timeout)
+        results = await self.client.check_win_many(list(trade_ids), timeout, return_exceptions)
+        return {id: trade if isinstance(trade, Exception) else _with_result(trade) for id, trade in results.items()}
 
-    
-    async def balance(self) -> float:
+    async def close_deal(self, id: str) -> dict:
         """
-        Retrieves current account balance.
+        Closes an open trade before its expiry (sell-out) for a partial payout, set by the server from the price
+        and the time left when it closes the trade.
+
+        Args:
+            id (str): ID of the open trade to close
 
         Returns:
-            float: Account balance in account currency
+            dict: The closed trade with its realized `profit` and its `result` ("win", "loss" or "draw"), also
+                recorded in the journal
 
-        Note:
-            Updates in real-time as trades are completed
+        Raises:
+            ValueError: If the id is invalid
+            Exception: If the trade isn't open or the server refused to close it (e.g. too close to its expiry)
+
+        Example:
+            ```python
+            trade_id, _ = await client.buy("EURUSD_otc", 1, 300)
+            trade = await client.close_deal(trade_id)
+            print(f"Closed early: {trade['result']} ({trade['profit']})")
+            ```
         """
-        return json.loads(await self.client.balance())["balance"]
-    
-    async def opened_deals(self) -> list[dict]:
-        "Returns a list of all the opened deals as dictionaries"
-        return json.loads(await self.client.opened_deals())
-    
-    async def closed_deals(self) -> list[dict]:
-        "Returns a list of all the closed deals as dictionaries"
-        return json.loads(await self.client.closed_deals())
-    
-    async def clear_closed_deals(self) -> None:
-        "Removes all the closed deals from memory, this function doesn't return anything"
-        await self.client.clear_closed_deals()
+        trade = await self.client.close_deal(id)
+        win = trade["profit"]
+        if win > 0:
+            trade["result"] = "win"
+        elif win == 0:
+            trade["result"] = "draw"
+        else:
+            trade["result"] = "loss"
+        return trade
 
-    async def payout(self, asset: None | str | list[str] = None) -> dict | list[int] | int:
+    async def buy_at_price(
+        self, asset: str, amount: float, time: int, price: float, valid_for: timedelta | float | None = None
+    ) -> dict:
         """
-        Retrieves current payout percentages for all assets.
+        Registers a buy (call) trade placed once the quote of the asset crosses `price`, see `order_at_price`.
 
-        Returns:
-            dict: Asset payouts mapping:
-                {
-                    "EURUSD_otc": 85,  # 85% payout
-                    "GBPUSD": 82,      # 82% payout
-                    ...
-                }
-            list: If asset is a list, returns a list of payouts for each asset in the same order
-            int: If asset is a string, returns the payout for that specific asset
-            none: If asset didn't match and valid asset none will be returned
-        """        
-        payout = json.loads(await self.client.payout())
-        if isinstance(asset, str):
-            return payout.get(asset)
-        elif isinstance(asset, list):
-            return [payout.get(ast) for ast in asset]
-        return payout
-    
-    async def history(self, asset: str, period: int) -> list[dict]:
-        "Returns a list of dictionaries containing the latest data available for the specified asset starting from 'period', the data is in the same format as the returned data of the 'get_candles' function."
-        return json.loads(await self.client.history(asset, period))
-    
-    async def _subscribe_symbol_inner(self, asset: str) :
-        return await self.client.subscribe_symbol(asset)
-    
-    async def _subscribe_symbol_chuncked_inner(self, asset: str, chunck_size: int):
-        return await self.client.subscribe_symbol_chuncked(asset, chunck_size)
-    
-    async def _subscribe_symbol_timed_inner(self, asset: str, time: timedelta):
-        return await self.client.subscribe_symbol_timed(asset, time)
-    
-    async def subscribe_symbol(self, asset: str) -> AsyncSubscription:
+        Example:
+            ```python
+            order = await client.buy_at_price("EURUSD_otc", 1.0, 60, 1.0850, valid_for=timedelta(minutes=10))
+            ```
         """
-        Creates a real-time data subscription for an asset.
+        return await self.order_at_price(asset, Direction.CALL, amount, time, price, valid_for)
+
+    async def sell_at_price(
+        self, asset: str, amount: float, time: int, price: float, valid_for: timedelta | float | None = None
+    ) -> dict:
+        "Registers a sell (put) trade placed once the quote of the asset crosses `price`, see `order_at_price`"
+        return await self.order_at_price(asset, Direction.PUT, amount, time, price, valid_for)
+
+    async def order_at_price(
+        self,
+        asset: str,
+        direction: Direction | str,
+        amount: float,
+        time: int,
+        price: float,
+        valid_for: timedelta | float | None = None,
+    ) -> dict:
+        """
+        Registers a trade placed once the quote of the asset crosses `price`, coming from above or below depending on
+        the quote when the order is registered. The ticks are watched by the Rust client, which places the trade
+        (through the risk rules and the journal like any other trade) as soon as the tick crossing the price is
+        received, so the latency doesn't depend on Python.
 
         Args:
-            asset (str): Trading asset to subscribe to
+            asset (str): Trading asset (e.g., "EURUSD_otc"), the client subscribes to it
+            direction (Direction | str): Direction of the trade ("call" / "put")
+            amount (float): Trade amount in account currency
+            time (int): Expiry time of the trade in seconds
+            price (float): Quote triggering the trade
+            valid_for (timedelta | float, optional): The order expires if the price isn't crossed in time (seconds),
+                it waits until it's cancelled by default
 
         Returns:
-            AsyncSubscription: Async iterator yielding real-time price updates
+            dict: The order, with its `id`, and its `status`: "waiting", "sending", "triggered" (with the `trade_id`
+                and the `quote` it was triggered at), "failed" (with the `error`), "cancelled" or "expired"
 
         Example:
             ```python
-            async with api.subscribe_symbol("EURUSD_otc") as subscription:
-                async for update in subscription:
-                    print(f"Price update: {update}")
+            order = await client.order_at_price("EURUSD_otc", "put", 1.0, 60, 1.0900)
+            ...
+            for order in client.price_orders():
+                if order["status"] == "triggered":
+                    print(await client.check_win(order["trade_id"]))
             ```
         """
-        return AsyncSubscription(await self._subscribe_symbol_inner(asset))
-    
-    async def subscribe_symbol_chuncked(self, asset: str, chunck_size: int) -> AsyncSubscription:
-        """Returns an async iterator over the associated asset, it will return real time candles formed with the specified amount of raw candles and will return new candles while the 'PocketOptionAsync' class is loaded if the class is droped then the iterator will fail"""
-        return AsyncSubscription(await self._subscribe_symbol_chuncked_inner(asset, chunck_size))
-    
-    async def subscribe_symbol_timed(self, asset: str, time: timedelta) -> AsyncSubscription:
-        """
-        Creates a timed real-time data subscription for an asset.
+        direction = Direction.parse(direction)
+        valid_for = None if valid_for is None else _seconds(valid_for)
+        return await self.client.order_at_price(asset, direction.value, amount, time, price, valid_for)
 
-        Args:
-            asset (str): Trading asset to subscribe to
-            interval (int): Update interval in seconds
+    def price_orders(self) -> list[dict]:
+        "Returns the orders registered with `order_at_price` (and `buy_at_price` / `sell_at_price`) with their status, oldest first"
+        return self.client.price_orders()
 
-        Returns:
-            AsyncSubscription: Async iterator yielding price updates at specified intervals
+    def cancel_price_order(self, id: str) -> dict:
+        "Cancels an order waiting for its price, raises if the price was already crossed. Returns the cancelled order"
+        return self.client.cancel_price_order(id)
+
+    async def buy_at_time(self, asset: str, amount: float, time: int, execute_at: datetime | float) -> dict:
+        """
+        Queues a buy (call) trade placed at `execute_at` on the server clock, see `order_at_time`.
 
         Example:
             ```python
-            # Get updates every 5 seconds
-            async with api.subscribe_symbol_timed("EURUSD_otc", 5) as subscription:
-                async for update in subscription:
-                    print(f"Timed update: {update}")
+            # Signal: CALL EURUSD_otc at 14:05:00, 1 minute
+            order = await client.buy_at_time("EURUSD_otc", 1.0, 60, datetime(2025, 6, 2, 14, 5, tzinfo=timezone.utc))
             ```
         """
-        return AsyncSubscription(await self._subscribe_symbol_timed_inner(asset, time))
-    
-    async def send_raw_message(self, message: str) -> None:
+        return await self.order_at_time(asset, Direction.CALL, amount, time, execute_at)
+
+    async def sell_at_time(self, asset: str, amount: float, time: int, execute_at: datetime | float) -> dict:
+        "Queues a sell (put) trade placed at `execute_at` on the server clock, see `order_at_time`"
+        return await self.order_at_time(asset, Direction.PUT, amount, time, execute_at)
+
+    async def order_at_time(
+        self, asset: str, direction: Direction | str, amount: float, time: int, execute_at: datetime | float
+    ) -> dict:
         """
-        Sends a raw WebSocket message without waiting for a response.
-        
+        Queues a trade placed by the Rust client at `execute_at` on the server clock, with a millisecond timer
+        instead of a sleep in Python. The server clock is estimated from the latency of the ticks received (see
+        `latency_stats`), subscribe to an asset beforehand for an accurate entry: the local clock is used until a
+        tick is received. The trade goes through the risk rules and the journal like any other trade.
+
         Args:
-            message: Raw WebSocket message to send (e.g., '42["ping"]')
+            asset (str): Trading asset (e.g., "EURUSD_otc")
+            direction (Direction | str): Direction of the trade ("call" / "put")
+            amount (float): Trade amount in account currency
+            time (int): Expiry time of the trade in seconds
+            execute_at (datetime | float): Server time of the entry, as a datetime (naive ones are in UTC) or a unix
+                timestamp. It must be in the future.
+
+        Returns:
+            dict: The order, with its `id`, and its `status`: "scheduled", "sending", "placed" (with the `trade_id`
+                and the `delay` in milliseconds after `execute_at` it was placed with), "failed" (with the `error`)
+                or "cancelled"
+
+        Raises:
+            Exception: If `execute_at` is already past
         """
-        await self.client.send_raw_message(message)
-        
-    async def create_raw_order(self, message: str, validator: Validator) -> str:
+        direction = Direction.parse(direction)
+        return await self.client.order_at_time(asset, direction.value, amount, time, _utc(execute_at))
+
+    def scheduled_orders(self) -> list[dict]:
+        "Returns the orders queued with `order_at_time` (and `buy_at_time` / `sell_at_time`) with their status, by execution time"
+        return self.client.scheduled_orders()
+
+    def cancel_scheduled_order(self, id: str) -> dict:
+        "Cancels an order waiting for its execution time, raises if it was already sent. Returns the cancelled order"
+        return self.client.cancel_scheduled_order(id)
+
+    async def get_candles(self, asset: str, period: Timeframe | int | str | timedelta, offset: int, allow_partial: bool = False) -> list[Candle]:  
+        """
+        Retrieves historical candle data for an asset.
+
+        Args:
+            asset (str): Trading asset (e.g., "EURUSD_otc")
+            period (Timeframe | int | str | timedelta): Candle timeframe, a `Timeframe`, seconds (e.g., 60 for 1-minute candles)
+                or a duration like "5m"
+            offset (int): Historical period in seconds to fetch
+            allow_partial (bool): Returns the received candles instead of raising if some are missing. Defaults to False
+
+        Returns:
+            list[Candle]: List of candles, each with the attributes:
+                - asset: Trading asset
+                - time: Start of the candle (datetime), `timestamp` is the same as a unix timestamp
+                - open: Opening price
+                - high: Highest price
+                - low: Lowest price
+                - close: Closing price
+            The candles can also be read like the dicts of the previous versions (`candle["close"]`), `to_dict`
+            returns them as a dict
+
+        Raises:
+            ValueError: If the timeframe isn't supported by the server
+
+        Note:
+            Available timeframes: the members of `Timeframe` (5s, 10s, 15s, 30s, 1m, 2m, 3m, 5m, 10m, 15m, 30m, 1h, 4h, 1d)
+            Maximum period depends on the timeframe
+            Missing segments are requested again with a backoff, if some candles are still missing
+            an exception with the missing ranges is raised unless `allow_partial` is True
+        """
+        return await self.client.get_candles(asset, int(Timeframe.parse(period)), offset, allow_partial)
+
+    async def get_candles_np(self, asset: str, period: Timeframe | int | str | timedelta, offset: int, allow_partial: bool = False) -> dict:
+        """
+        Retrieves historical candle data for an asset as numpy arrays, for the analysis of large histories.
+
+        The Rust client writes every column of the candles to a buffer of `float64` read by numpy without a copy,
+        no `Candle` (nor json string) is built for the rows.
+
+        Args:
+            asset (str): Trading asset (e.g., "EURUSD_otc")
+            period (Timeframe | int | str | timedelta): Candle timeframe, a `Timeframe`, seconds or a duration like "5m"
+            offset (int): Historical period in seconds to fetch
+            allow_partial (bool): Returns the received candles instead of raising if some are missing. Defaults to False
+
+        Returns:
+            dict: The `float64` arrays `timestamps` (unix timestamps of the start of the candles, in seconds), `open`,
+                `high`, `low` and `close`, sorted by time like `get_candles`
+
+        Raises:
+            ImportError: If numpy isn't installed
+
+        Example:
+            ```python
+            candles = await client.get_candles_np("EURUSD_otc", "1m", 86400)
+            returns = numpy.diff(numpy.log(candles["close"]))
+            ```
+        """
+        try:
+            import numpy
+        except ImportError as e:
+            raise ImportError("get_candles_np requires numpy, install it with `pip install BinaryOptionsToolsV2[numpy]`") from e
+        columns = await self.client.get_candles_columns(asset, int(Timeframe.parse(period)), offset, allow_partial)
+        return {name: numpy.frombuffer(column, dtype=numpy.float64) for name, column in columns.items()}
+
+    async def get_candles_arrow(self, asset: str, period: Timeframe | int | str | timedelta, offset: int, allow_partial: bool = False):
+        """
+        Retrieves historical candle data for an asset as an Apache Arrow record batch, converted to a pandas or polars
+        dataframe without copying the columns.
+
+        Args:
+            asset (str): Trading asset (e.g., "EURUSD_otc")
+            period (Timeframe | int | str | timedelta): Candle timeframe, a `Timeframe`, seconds or a duration like "5m"
+            offset (int): Historical period in seconds to fetch
+            allow_partial (bool): Returns the received candles instead of raising if some are missing. Defaults to False
+
+        Returns:
+            pyarrow.RecordBatch: The columns `time` (UTC timestamps in milliseconds), `open`, `high`, `low` and `close`,
+                sorted by time like `get_candles`
+
+        Raises:
+            ImportError: If pyarrow isn't installed
+            RuntimeError: If the extension module was built without the `arrow` feature
+
+        Example:
+            ```python
+            batch = await client.get_candles_arrow("EURUSD_otc", "1m", 86400)
+            df = batch.to_pandas()  # or polars.from_arrow(batch)
+            ```
+        """
+        _require_native(self.client, "get_candles_arrow", "arrow")
+        return _record_batch(await self.client.get_candles_arrow(asset, int(Timeframe.parse(period)), offset, allow_partial))
+
+    async def get_candles_range(self, asset: str, period: Timeframe | int | str | timedelta, start: datetime | float, end: datetime | float) -> list[Candle]:
+        """
+        Retrieves the candles of an asset between two dates, without computing the offsets by hand.
+
+        The range is loaded page by page by the Rust client, the pages are merged into a single list sorted by time
+        without duplicates. The candles the server doesn't have (e.g. while the market was closed) are skipped like
+        with `allow_partial`, use `audit_candles_data` to find them.
+
+        Args:
+            asset (str): Trading asset (e.g., "EURUSD_otc")
+            period (Timeframe | int | str | timedelta): Candle timeframe, a `Timeframe`, seconds or a duration like "5m"
+            start (datetime | float): Start of the range (server time), a datetime or a unix timestamp, naive
+                datetimes are in UTC
+            end (datetime | float): End of the range, excluded. The candle still being formed is never returned
+
+        Returns:
+            list[Candle]: List of candles, like `get_candles`
+
+        Example:
+            ```python
+            candles = await client.get_candles_range("EURUSD_otc", "1m", datetime(2025, 1, 6), datetime(2025, 1, 11))
+            ```
+        """
+        return await self.client.get_candles_range(asset, int(Timeframe.parse(period)), _utc(start), _utc(end))
+
+    def get_recent_candles(self, asset: str, count: int) -> list[Candle]:
+        """
+        Returns the last candles of a subscribed asset from the ones kept in memory by the Rust client, without a
+        request to the server.
+
+        The ticks of every subscribed asset are aggregated into candles of the period of the store (1 minute by
+        default, see `set_candle_store`), the candles are kept across the reconnections and the resubscriptions.
+
+        Args:
+            asset (str): Subscribed asset (e.g., "EURUSD_otc")
+            count (int): Number of candles to return
+
+        Returns:
+            list[Candle]: Up to `count` candles sorted by time, like `get_candles`. The last one is the candle of the
+                current period, still open, and fewer candles are returned while the store fills up
+
+        Example:
+            ```python
+            async for tick in await client.subscribe_symbol("EURUSD_otc"):
+                candles = client.get_recent_candles("EURUSD_otc", 50)
+            ```
+        """
+        return self.client.get_recent_candles(asset, count)
+
+    def get_candles_between(self, asset: str, start: datetime | float, end: datetime | float) -> list[Candle]:
+        """
+        Returns the candles kept in memory for an asset starting between `start` (included) and `end` (excluded),
+        datetimes or unix timestamps, without a request to the server. See `get_recent_candles`, and
+        `get_candles_range` for the candles older than the store.
+        """
+        return self.client.get_candles_between(asset, _utc(start), _utc(end))
+
+    def set_candle_store(self, period: Timeframe | int | str | timedelta, capacity: int = 500) -> None:
+        """
+        Sets the period of the candles kept in memory for the subscribed assets and how many are kept per asset.
+        Changing the period drops the candles kept so far.
+
+        Args:
+            period (Timeframe | int | str | timedelta): Period of the candles, 1 minute by default
+            capacity (int): Number of candles kept per asset. Defaults to 500
+        """
+        self.client.set_candle_store(int(Timeframe.parse(period)), capacity)
+
+    async def persist_candle_store(self, path: str | None = None, interval: timedelta | float = timedelta(minutes=1)) -> int:
+        """
+        Keeps the candles of `get_recent_candles` on disk, so they survive the restarts of the bot.
+
+        The candles saved in the file are loaded right away, before the ones recorded since, and the store is saved
+        every `interval`, on `shutdown` and once the client is dropped. A file saved with another period of the store
+        (see `set_candle_store`) isn't loaded.
+
+        Args:
+            path (str | None): File the candles are saved to, `candle_store.json` in the cache directory (see
+                `set_storage_dirs`) by default
+            interval (timedelta | float): Delay (seconds for a number) between two saves, one minute by default
+
+        Returns:
+            int: Number of candles loaded from the file
+
+        Example:
+            ```python
+            client.set_candle_store(Timeframe.M5, 1000)
+            loaded = await client.persist_candle_store()
+            ```
+        """
+        return await self.client.persist_candle_store(None if path is None else str(path), _seconds(interval))
+
+    async def download_history(self, asset: str, period: Timeframe | int | str | timedelta, days: int, path: str, format: str | None = None, progress: Callable[[dict], None] | None = None) -> dict:
+        """
+        Downloads months of candles of an asset to a file, without holding them in memory.
+
+        The Rust client requests the candles page by page (oldest first) with a minimum delay between the requests so
+        the server doesn't throttle the download, requests a page again if it fails (e.g. while reconnecting) and
+        writes every page to the file before requesting the next one, the candles shared by two pages are only
+        written once. The file is created, or truncated if it exists.
+
+        Args:
+            asset (str): Trading asset (e.g., "EURUSD_otc")
+            period (Timeframe | int | str | timedelta): Candle timeframe, a `Timeframe`, seconds or a duration like "5m"
+            days (int): Number of days to download, up to the last closed candle
+            path (str): File to write the candles to
+            format (str | None): "csv" (`time,open,high,low,close`), "jsonl" (one json candle per line, readable with
+                `CandleReplay` and compressed for a `.gz` or `.zst` path) or "parquet" (requires the `export-parquet`
+                feature). Defaults to the format of the extension of `path`, CSV otherwise
+            progress (Callable[[dict], None] | None): Function called after every page with a dictionary of the
+                `asset`, `pages_done`, `pages`, the `candles` written so far, the `last_candle` time and the `retries`
+
+        Returns:
+            dict: Summary of the download with the `asset`, `period`, `path`, `format`, `start` and `end` of the range,
+                the number of `candles` written, the `missing_candles` (e.g. while the market was closed) and the
+                number of `retries`.
+
+        Example:
+            ```python
+            summary = await client.download_history(
+                "EURUSD_otc", "1m", 90, "EURUSD_otc.parquet",
+                progress=lambda p: print(f"{p['pages_done']}/{p['pages']} pages"),
+            )
+            ```
+        """
+        return await self.client.download_history(asset, int(Timeframe.parse(period)), days, path, format, progress)
+    
+    async def get_candles_advanced(self, asset: str, period: Timeframe | int | str | timedelta, offset: int, time: int, allow_partial: bool = False) -> list[Candle]:  
+        """
+        Retrieves historical candle data for an asset.
+
+        Args:
+            asset (str): Trading asset (e.g., "EURUSD_otc")
+            period (Timeframe | int | str | timedelta): Candle timeframe, a `Timeframe`, seconds (e.g., 60 for 1-minute candles)
+                or a duration like "5m"
+            offset (int): Historical period in seconds to fetch
+            time (int): Time to fetch candles from
+            allow_partial (bool): Returns the received candles instead of raising if some are missing. Defaults to False
+
+        Returns:
+            list[Candle]: List of candles, each with the attributes:
+                - asset: Trading asset
+                - time: Start of the candle (datetime), `timestamp` is the same as a unix timestamp
+                - open: Opening price
+                - high: Highest price
+                - low: Lowest price
+                - close: Closing price
+            The candles can also be read like the dicts of the previous versions (`candle["close"]`), `to_dict`
+            returns them as a dict
+
+        Raises:
+            ValueError: If the timeframe isn't supported by the server
+
+        Note:
+            Available timeframes: the members of `Timeframe` (5s, 10s, 15s, 30s, 1m, 2m, 3m, 5m, 10m, 15m, 30m, 1h, 4h, 1d)
+            Maximum period depends on the timeframe
+            Missing segments are requested again with a backoff, if some candles are still missing
+            an exception with the missing ranges is raised unless `allow_partial` is True
+        """
+        return await self.client.get_candles_advanced(asset, int(Timeframe.parse(period)), offset, time, allow_partial)
+
+
+    
+    async def balance(self) -> float:
+        """
+        Retrieves current account balance.
+
+        Returns:
+            float: Account balance in account currency
+
+        Note:
+            Updates in real-time as trades are completed
+        """
+        return (await self.client.balance())["balance"]
+    
+    async def opened_deals(self) -> list[dict]:
+        "Returns a list of all the opened deals as dictionaries"
+        return await self.client.opened_deals()
+    
+    async def closed_deals(self) -> list[dict]:
+        "Returns a list of all the closed deals as dictionaries"
+        return await self.client.closed_deals()
+
+    async def closed_deals_arrow(self):
+        """
+        Returns the closed deals as an Apache Arrow record batch, one row per deal with its `id`, `asset`, `command`
+        (0 for call, 1 for put), `amount`, `profit`, `percent_profit`, `percent_loss`, `open_price`, `close_price`,
+        `open_timestamp`, `close_timestamp`, `uid`, `is_demo` and `currency`. See `get_candles_arrow`.
+        """
+        _require_native(self.client, "closed_deals_arrow", "arrow")
+        return _record_batch(await self.client.closed_deals_arrow())
+    
+    async def clear_closed_deals(self) -> None:
+        "Removes all the closed deals from memory, this function doesn't return anything"
+        await self.client.clear_closed_deals()
+
+    async def payout(self, asset: None | str | list[str] = None) -> dict | list[int] | int:
+        """
+        Retrieves current payout percentages for all assets.
+
+        Returns:
+            dict: Asset payouts mapping:
+                {
+                    "EURUSD_otc": 85,  # 85% payout
+                    "GBPUSD": 82,      # 82% payout
+                    ...
+                }
+            list: If asset is a list, returns a list of payouts for each asset in the same order
+            int: If asset is a string, returns the payout for that specific asset
+            none: If asset didn't match and valid asset none will be returned
+        """        
+        payout = await self.client.payout()
+        if isinstance(asset, str):
+            return payout.get(asset)
+        elif isinstance(asset, list):
+            return [payout.get(ast) for ast in asset]
+        return payout
+
+    async def get_payout_for(self, asset: str) -> int:
+        """
+        Returns the current payout of an asset in percent, without converting the payouts of every asset.
+
+        Raises:
+            ValueError: If the server didn't send the payout of the asset
+
+        Example:
+            ```python
+            if await api.get_payout_for("EURUSD_otc") >= 85:
+                await api.buy("EURUSD_otc", 1.0, 60)
+            ```
+        """
+        return await self.client.get_payout_for(asset)
+
+    async def is_open(self, asset: str) -> bool:
+        """
+        Returns whether the market of an asset is currently open, the orders on a closed asset are
+        dropped by the server (or rejected with an `AssetClosedError` with `Config.check_market_hours`).
+
+        Raises:
+            ValueError: If the server didn't send the asset
+
+        Example:
+            ```python
+            if await api.is_open("EURUSD"):
+                await api.buy("EURUSD", 1.0, 60)
+            ```
+        """
+        return await self.client.is_open(asset)
+
+    async def get_assets(self, open_only: bool = False, otc: bool | None = None) -> list[Asset]:
+        """
+        Returns the catalog of the assets sent by the server, sorted by symbol.
+
+        Args:
+            open_only (bool): Only returns the assets that can be traded right now. Defaults to False.
+            otc (bool, optional): Only returns the OTC assets if True, or the regular ones if False.
+
+        Returns:
+            list[Asset]: The assets, with the attributes `symbol`, `name`, `asset_type`, `payout`, `is_otc`,
+            `is_open` and `expiries` (the expiry times in seconds the server accepts)
+
+        Example:
+            ```python
+            assets = await api.get_assets(open_only=True)
+            best = max(assets, key=lambda asset: asset.payout)
+            await api.buy(best.symbol, 1.0, best.expiries[0])
+            ```
+        """
+        assets = await self.client.get_assets()
+        return [
+            asset for asset in assets
+            if (not open_only or asset.is_open) and (otc is None or asset.is_otc == otc)
+        ]
+    
+    async def history(self, asset: str, period: Timeframe | int | str | timedelta) -> list[Candle]:
+        "Returns a list of candles containing the latest data available for the specified asset with the timeframe 'period' (see `Timeframe`), the data is in the same format as the returned data of the 'get_candles' function."
+        return await self.client.history(asset, int(Timeframe.parse(period)))
+
+    async def history_arrow(self, asset: str, period: Timeframe | int | str | timedelta):
+        "Returns the latest candles of `history` as an Apache Arrow record batch with the columns of `get_candles_arrow`"
+        _require_native(self.client, "history_arrow", "arrow")
+        return _record_batch(await self.client.history_arrow(asset, int(Timeframe.parse(period))))
+    
+    async def _subscribe_symbol_inner(self, asset: str) :
+        return await self.client.subscribe_symbol(asset)
+    
+    async def _subscribe_symbol_batch_inner(self, assets: list[str]):
+        return await self.client.subscribe_symbol_batch(assets)
+
+    async def _subscribe_symbols_inner(self, assets: list[str]):
+        return await self.client.subscribe_symbols(assets)
+
+    async def _subscribe_symbol_chuncked_inner(self, asset: str, chunck_size: int):
+        return await self.client.subscribe_symbol_chuncked(asset, chunck_size)
+    
+    async def _subscribe_symbol_timed_inner(self, asset: str, time: timedelta):
+        return await self.client.subscribe_symbol_timed(asset, time)
+    
+    async def subscribe_symbol(self, asset: str) -> AsyncSubscription:
+        """
+        Creates a real-time data subscription for an asset.
+
+        Args:
+            asset (str): Trading asset to subscribe to
+
+        Returns:
+            AsyncSubscription: Async iterator yielding real-time price updates
+
+        Example:
+            ```python
+            async with api.subscribe_symbol("EURUSD_otc") as subscription:
+                async for update in subscription:
+                    print(f"Price update: {update}")
+            ```
+        """
+        return AsyncSubscription(await self._subscribe_symbol_inner(asset))
+    
+    async def subscribe_symbol_batch(self, assets: list[str]) -> list[AsyncSubscription]:
+        """
+        Creates real-time data subscriptions for several assets at once.
+        The subscription frames of all the assets are sent together instead of one asset at a time,
+        so subscribing to a large watchlist takes a fraction of a second.
+
+        Args:
+            assets (list[str]): Trading assets to subscribe to
+
+        Returns:
+            list[AsyncSubscription]: One async iterator per asset, in the same order as `assets`
+
+        Example:
+            ```python
+            watchlist = ["EURUSD_otc", "GBPUSD_otc", "#AAPL_otc"]
+            subscriptions = await api.subscribe_symbol_batch(watchlist)
+            for asset, subscription in zip(watchlist, subscriptions):
+                print(asset, await subscription.__anext__())
+            ```
+        """
+        return [AsyncSubscription(s) for s in await self._subscribe_symbol_batch_inner(assets)]
+
+    async def subscribe_symbols(self, symbols: list[str]) -> AsyncSubscription:
+        """
+        Creates a single real-time data subscription merging the candles of several assets.
+        The candles are yielded in the order they arrive, each one carries the asset it belongs to
+        (the `asset` attribute of the candles, or the `"asset"` key of the json strings).
+
+        Args:
+            symbols (list[str]): Trading assets to subscribe to
+
+        Returns:
+            AsyncSubscription: Async iterator yielding the candles of all the assets, closing it unsubscribes every asset
+
+        Example:
+            ```python
+            async with await api.subscribe_symbols(["EURUSD_otc", "GBPUSD_otc"]) as subscription:
+                async for candle in subscription:
+                    print(candle.asset, candle.close)
+            ```
+        """
+        return AsyncSubscription(await self._subscribe_symbols_inner(symbols))
+
+    async def subscribe_symbol_chuncked(self, asset: str, chunck_size: int) -> AsyncSubscription:
+        """Returns an async iterator over the associated asset, it will return real time candles formed with the specified amount of raw candles and will return new candles while the 'PocketOptionAsync' class is loaded if the class is droped then the iterator will fail"""
+        return AsyncSubscription(await self._subscribe_symbol_chuncked_inner(asset, chunck_size))
+    
+    async def subscribe_symbol_timed(self, asset: str, time: timedelta) -> AsyncSubscription:
+        """
+        Creates a timed real-time data subscription for an asset.
+
+        Args:
+            asset (str): Trading asset to subscribe to
+            interval (int): Update interval in seconds
+
+        Returns:
+            AsyncSubscription: Async iterator yielding price updates at specified intervals
+
+        Example:
+            ```python
+            # Get updates every 5 seconds
+            async with api.subscribe_symbol_timed("EURUSD_otc", 5) as subscription:
+                async for update in subscription:
+                    print(f"Timed update: {update}")
+            ```
+        """
+        return AsyncSubscription(await self._subscribe_symbol_timed_inner(asset, time))
+    
+    async def _subscribe_candle_close_inner(self, asset: str, period: timedelta | int, timeout: timedelta | None = None):
+        if isinstance(period, (int, float)):
+            period = timedelta(seconds=period)
+        return await self.client.subscribe_candle_close(asset, period, timeout)
+
+    async def subscribe_candle_close(self, asset: str, period: timedelta | int, timeout: timedelta | None = None) -> AsyncSubscription:
+        """
+        Subscribes to the close of the candles of an asset. A candle is sent when each period ends on the synced server clock,
+        even if the final tick of the period is late or never arrives, so strategies waiting for a candle never hang.
+
+        Args:
+            asset (str): Trading asset (e.g., "EURUSD_otc")
+            period (timedelta | int): Duration of the candles (seconds if it's a number), at least 1 second
+            timeout (timedelta | None): Optional maximum time to wait for each candle
+
+        Returns:
+            AsyncSubscription: Async iterator yielding dictionaries with the `asset`, the `period` in seconds, the `open_time`
+                and `close_time` of the period, the best known `candle` (flat at the last known price if no tick arrived
+                during the period, None if no price is known yet) and the number of `ticks` recieved.
+
+        Example:
+            ```python
+            async for closed in await api.subscribe_candle_close("EURUSD_otc", 60):
+                if closed["candle"] is not None:
+                    print(f"Candle closed at {closed['close_time']}: {closed['candle']['close']}")
+            ```
+        """
+        return AsyncSubscription(await self._subscribe_candle_close_inner(asset, period, timeout))
+
+    async def _subscribe_account_events_inner(self, timeout: timedelta | None = None):
+        return await self.client.subscribe_account_events(timeout)
+
+    async def subscribe_account_events(self, timeout: timedelta | None = None) -> AsyncSubscription:
+        """
+        Subscribes to the administrative messages about the account: bonuses credited, margin calls,
+        account restrictions and maintenance or other server notices.
+
+        Args:
+            timeout (timedelta | None): Optional maximum time to wait for each event, it only applies to this subscription
+
+        Returns:
+            AsyncSubscription: Async iterator yielding the events as dictionaries with the `kind` ("bonus", "margin_call",
+                "restriction", "maintenance", "notice", "circuit_breaker" or "feed_anomaly"), the `event` name sent by the
+                server, its `message` and `amount` (if any), the full `data` and the `received_at` time.
+
+        Example:
+            ```python
+            async for event in await api.subscribe_account_events():
+                if event["kind"] == "restriction":
+                    print(f"Account restricted: {event['message']}")
+            ```
+        """
+        return AsyncSubscription(await self._subscribe_account_events_inner(timeout))
+
+    async def _subscribe_trade_results_inner(self, timeout: timedelta | None = None):
+        return await self.client.subscribe_trade_results(timeout)
+
+    async def subscribe_trade_results(self, timeout: timedelta | None = None) -> AsyncSubscription:
+        """
+        Subscribes to the results of the trades, a deal is yielded every time a trade of the account closes, so the
+        trades don't need to be polled one by one with `check_win`.
+
+        Args:
+            timeout (timedelta | None): Optional maximum time to wait for each result, it only applies to this subscription
+
+        Returns:
+            AsyncSubscription: Async iterator yielding the closed deals as dictionaries, in the same format as the deals
+                returned by `check_win`. The `profit` is positive for a win, negative for a loss and 0 for a draw.
+
+        Example:
+            ```python
+            async for deal in await api.subscribe_trade_results():
+                result = "win" if deal["profit"] > 0 else "loss" if deal["profit"] < 0 else "draw"
+                print(f"Trade {deal['id']} on {deal['asset']}: {result}")
+            ```
+        """
+        return AsyncSubscription(await self._subscribe_trade_results_inner(timeout))
+
+    def _subscribe_unparsed_inner(self, timeout: timedelta | None = None):
+        return self.client.subscribe_unparsed(timeout)
+
+    async def subscribe_unparsed(self, timeout: timedelta | None = None) -> AsyncSubscription:
+        """
+        Subscribes to the messages recieved from the server that failed to parse, instead of only logging them,
+        allowing to detect changes in the protocol of the broker as soon as they happen.
+
+        Args:
+            timeout (timedelta | None): Optional maximum time to wait for each message, it only applies to this subscription
+
+        Returns:
+            AsyncSubscription: Async iterator yielding the messages as dictionaries with the `event` they were expected to be
+                (None if unknown), the raw `payload`, the parsing `error` and the `received_at` time.
+
+        Example:
+            ```python
+            async for message in await api.subscribe_unparsed():
+                print(f"Unparsed '{message['event']}' message: {message['error']}")
+            ```
+        """
+        return AsyncSubscription(self._subscribe_unparsed_inner(timeout))
+
+    async def unsubscribe_symbol(self, asset: str) -> None:
+        """
+        Unsubscribes from an asset, releasing its slot for the next queued subscription. The subscriptions of the asset
+        stop recieving updates.
+
+        Args:
+            asset (str): Trading asset to unsubscribe from
+        """
+        await self.client.unsubscribe_symbol(asset)
+
+    async def set_subscription_policy(
+        self, max_subscriptions: int | None = None, rotate: bool = True, detection_delay: timedelta | None = None
+    ) -> None:
+        """
+        Sets how the limit of simultaneous chart subscriptions of the server is handled. Past that limit the server
+        accepts new subscriptions but never streams them, so once the limit is known the surplus subscriptions are
+        either queued until a slot is released or replace the least recently used subscription.
+
+        Args:
+            max_subscriptions (int | None): Maximum number of simultaneous subscriptions. If None the limit is detected
+                from the new subscriptions that never recieve a tick while the older ones keep ticking
+            rotate (bool): Unsubscribes the least recently used asset to make room for a new one once the limit is
+                reached, instead of queuing the new one. Defaults to True
+            detection_delay (timedelta | None): Time without any tick after which a new subscription is considered
+                rejected by the server. Defaults to 10 seconds
+
+        Example:
+            ```python
+            await api.set_subscription_policy(max_subscriptions=10, rotate=False)
+            ```
+        """
+        await self.client.set_subscription_policy(max_subscriptions, rotate, detection_delay)
+
+    def subscription_status(self) -> dict:
+        """
+        Returns the state of the chart subscriptions.
+
+        Returns:
+            dict: Dictionary with the `active` assets (from the least to the most recently used), the `queued` ones
+                waiting for a slot, the `limit` in use (None if unknown), whether it was `detected` and the assets
+                `suspended` by the power saving mode.
+        """
+        return self.client.subscription_status()
+
+    def set_stream_priority(self, asset: str, priority: str) -> None:
+        """
+        Sets the lane the ticks of an asset go through. When the streams don't keep up with the feed the oldest
+        "best_effort" ticks are dropped first, the "critical" ones (e.g. the asset currently traded) are never shed.
+        Every asset is "best_effort" by default.
+
+        Args:
+            asset (str): Asset of the ticks
+            priority (str): "critical" or "best_effort"
+
+        Example:
+            ```python
+            api.set_stream_priority("EURUSD_otc", "critical")
+            stream = await api.subscribe_symbol("EURUSD_otc")
+            ```
+        """
+        self.client.set_stream_priority(asset, priority)
+
+    def stream_lane_stats(self) -> dict:
+        """
+        Returns the state of the stream lanes.
+
+        Returns:
+            dict: Dictionary with the `critical` assets, the number of best-effort ticks `shed` (and `shed_by_asset`)
+                and the ticks waiting in each lane (`pending_critical` and `pending_best_effort`).
+        """
+        return self.client.stream_lane_stats()
+
+    async def set_power_saving(self, idle: timedelta | float | None) -> None:
+        """
+        Enables the power saving mode, for the bots idling most of the time on small servers: once no stream (like the
+        ones of `subscribe_symbol`) was read for `idle`, every chart subscription is dropped. The connection and the
+        session stay alive so trades can still be placed, and the assets are subscribed again when a stream is
+        requested for them. The dropped assets are listed as `suspended` by `subscription_status`.
+
+        Args:
+            idle (timedelta | float | None): Time without any stream (seconds for a number) before the subscriptions
+                are dropped. None disables the power saving mode
+
+        Example:
+            ```python
+            await api.set_power_saving(timedelta(minutes=10))
+            # Later, when a signal arrives the asset is subscribed again
+            stream = await api.subscribe_symbol("EURUSD_otc")
+            ```
+        """
+        await self.client.set_power_saving(None if idle is None else _seconds(idle))
+
+    def _subscribe_subscription_events_inner(self, timeout: timedelta | None = None):
+        return self.client.subscribe_subscription_events(timeout)
+
+    async def subscribe_subscription_events(self, timeout: timedelta | None = None) -> AsyncSubscription:
+        """
+        Subscribes to the changes of the chart subscriptions, so a subscription rotated out or queued past the limit of
+        the server doesn't go silently quiet.
+
+        Args:
+            timeout (timedelta | None): Optional maximum time to wait for each event, it only applies to this subscription
+
+        Returns:
+            AsyncSubscription: Async iterator yielding the events as dictionaries with their `event` ("subscribed",
+                "queued", "activated", "evicted", "unsubscribed" or "limit_detected") and details: the `asset`, its
+                `position` in the queue, the asset it was `replaced_by`, or the detected `limit` and the `silent` assets.
+
+        Example:
+            ```python
+            async for event in await api.subscribe_subscription_events():
+                if event["event"] == "evicted":
+                    print(f"No more updates for {event['asset']}, replaced by {event['replaced_by']}")
+            ```
+        """
+        return AsyncSubscription(self._subscribe_subscription_events_inner(timeout))
+
+    async def send_raw_message(self, message: str) -> None:
+        """
+        Sends a raw WebSocket message without waiting for a response.
+        
+        Args:
+            message: Raw WebSocket message to send (e.g., '42["ping"]')
+        """
+        await self.client.send_raw_message(message)
+        
+    async def create_raw_order(self, message: str, validator: Validator) -> str:
         """
         Sends a raw WebSocket message and waits for a validated response.
         
         Args:
-            message: Raw WebSocket message to send
-            validator: Validator instance to validate the response
-            
+            message: Raw WebSocket message to send
+            validator: Validator instance to validate the response
+            
+        Returns:
+            str: The first message that matches the validator's conditions
+            
+        Example:
+            ```python
+            validator = Validator.starts_with('451-["signals/load"')
+            response = await client.create_raw_order(
+                '42["signals/subscribe"]',
+                validator
+            )
+            ```
+        """
+        return await self.client.create_raw_order(message, validator.raw_validator)
+        
+    async def create_raw_order_with_timout(self, message: str, validator: Validator, timeout: timedelta) -> str:
+        """
+        Similar to create_raw_order but with a timeout.
+        
+        Args:
+            message: Raw WebSocket message to send
+            validator: Validator instance to validate the response
+            timeout: Maximum time to wait for a valid response
+            
+        Returns:
+            str: The first message that matches the validator's conditions
+            
+        Raises:
+            TimeoutError: If no valid response is received within the timeout period
+        """
+
+        return await self.client.create_raw_order_with_timeout(message, validator.raw_validator, timeout)
+    
+    async def create_raw_order_with_timeout_and_retry(self, message: str, validator: Validator, timeout: timedelta) -> str:
+        """
+        Similar to create_raw_order_with_timout but with automatic retry on failure.
+        
+        Args:
+            message: Raw WebSocket message to send
+            validator: Validator instance to validate the response
+            timeout: Maximum time to wait for each attempt
+            
+        Returns:
+            str: The first message that matches the validator's conditions
+        """
+
+        return await self.client.create_raw_order_with_timeout_and_retry(message, validator.raw_validator, timeout)
+ 
+    async def create_raw_iterator(self, message: str, validator: Validator, timeout: timedelta | None = None):
+        """
+        Creates an async iterator that yields validated WebSocket messages.
+        
+        Args:
+            message: Initial WebSocket message to send
+            validator: Validator instance to filter incoming messages
+            timeout: Optional maximum time to wait for each message, it only applies to this iterator and can be changed
+                later with `set_timeout`, `reset_timeout` and `extend`. When it's reached the iteration stops but the
+                iterator can be iterated again
+            
+        Returns:
+            AsyncIterator yielding validated messages
+            
+        Example:
+            ```python
+            validator = Validator.starts_with('{"signals":')
+            async for message in client.create_raw_iterator(
+                '42["signals/subscribe"]',
+                validator,
+                timeout=timedelta(minutes=5)
+            ):
+                print(f"Received: {message}")
+            ```
+        """
+        return await self.client.create_raw_iterator(message, validator, timeout)
+
+    async def register_raw_iterator(self, iterator_id: str, message: str, validator: Validator, timeout: timedelta | None = None, path: str = "raw_iterators.json"):
+        """
+        Creates a raw iterator like `create_raw_iterator` and persists its definition (message + validator)
+        under a stable id, so it can be re-created after a restart with `restore_raw_iterators`.
+        
+        Args:
+            iterator_id: Stable id of the iterator
+            message: Initial WebSocket message to send
+            validator: Validator instance to filter incoming messages (custom validators can't be persisted)
+            timeout: Optional maximum time to wait for each message, it only applies to this iterator and can be changed
+                later with `set_timeout`, `reset_timeout` and `extend`. When it's reached the iteration stops but the
+                iterator can be iterated again
+            path: File where the iterator definitions are stored
+            
+        Returns:
+            AsyncIterator yielding validated messages, its `id` attribute is `iterator_id`
+        """
+        return await self.client.register_raw_iterator(iterator_id, message, validator.raw_validator, timeout, path)
+
+    async def restore_raw_iterators(self, path: str = "raw_iterators.json") -> dict:
+        """
+        Re-creates every raw iterator registered with `register_raw_iterator`, sending their initial message again.
+        
+        Returns:
+            dict: Mapping of iterator id to its new iterator
+            
+        Example:
+            ```python
+            iterators = await client.restore_raw_iterators()
+            signals = iterators.get("signals") or await client.register_raw_iterator(
+                "signals", '42["signals/subscribe"]', Validator.starts_with('451-["signals')
+            )
+            ```
+        """
+        return await self.client.restore_raw_iterators(path)
+
+    async def unregister_raw_iterator(self, iterator_id: str, path: str = "raw_iterators.json") -> bool:
+        """Removes a persisted raw iterator definition, returns True if it existed"""
+        return self.client.unregister_raw_iterator(iterator_id, path)
+
+    def _tap_raw_inner(self, validator: Validator | None = None, timeout: timedelta | None = None):
+        return self.client.tap_raw((validator or Validator()).raw_validator, timeout)
+
+    async def tap_raw(self, validator: Validator | None = None, timeout: timedelta | None = None):
+        """
+        Taps the raw messages recieved from the server, the frames of the events the library doesn't know (their
+        `451-[...]` headers and payloads), without sending anything. Useful to explore undocumented messages.
+
+        Args:
+            validator (Validator | None): Optional validator to filter the messages, every message is yielded by default
+            timeout (timedelta | None): Optional maximum time to wait for each message
+
+        Returns:
+            AsyncIterator yielding the raw messages as strings
+
+        Example:
+            ```python
+            async for message in await client.tap_raw(Validator.contains("signals")):
+                print(f"Received: {message}")
+            ```
+        """
+        return self._tap_raw_inner(validator, timeout)
+    
+    async def get_server_time(self) -> int:
+        """Returns the current server time as a UNIX timestamp"""
+        return await self.client.get_server_time()
+    
+    async def is_demo(self) -> bool:
+        """
+        Checks if the current account is a demo account.
+
+        Returns:
+            bool: True if using a demo account, False if using a real account
+
+        Examples:
+            ```python
+            # Basic account type check
+            async with PocketOptionAsync(ssid) as client:
+                is_demo = await client.is_demo()
+                print("Using", "demo" if is_demo else "real", "account")
+
+            # Example with balance check
+            async def check_account():
+                is_demo = await client.is_demo()
+                balance = await client.balance()
+                print(f"{'Demo' if is_demo else 'Real'} account balance: {balance}")
+
+            # Example with trade validation
+            async def safe_trade(asset: str, amount: float, duration: int):
+                is_demo = await client.is_demo()
+                if not is_demo and amount > 100:
+                    raise ValueError("Large trades should be tested in demo first")
+                return await client.buy(asset, amount, duration)
+            ```
+        """
+        return await self.client.is_demo()
+
+    def is_observer(self) -> bool:
+        "Returns True if the client is in observer mode, where every method sending an order raises `ObserverModeError`"
+        return self.client.is_observer()
+
+    def set_otc_fallback(self, enabled: bool) -> None:
+        """
+        Enables or disables the OTC fallback: while the market of a regular asset is closed and the market of
+        its OTC version is open, the trades and the subscriptions on the regular asset use the OTC one.
+        The switch is logged by the Rust client, `resolve_asset` returns the symbol that will be used.
+
+        Example:
+            ```python
+            client.set_otc_fallback(True)
+            # On a Saturday
+            trade_id, deal = await client.buy("EURUSD", 1.0, 60)
+            print(deal["asset"])  # EURUSD_otc
+            ```
+        """
+        self.client.set_otc_fallback(enabled)
+
+    def otc_fallback(self) -> bool:
+        "Returns True if the OTC fallback is enabled, see `set_otc_fallback`"
+        return self.client.otc_fallback()
+
+    async def resolve_asset(self, asset: str) -> str:
+        "Returns the symbol the trades and the subscriptions on `asset` use, its OTC version while it's closed with the OTC fallback"
+        return await self.client.resolve_asset(asset)
+
+    @classmethod
+    async def from_session_provider(
+        cls,
+        provider: Callable[[], Awaitable[str] | str],
+        uid: int | None = None,
+        demo: bool | None = None,
+        url: str | None = None,
+        config: Config | dict | str = None,
+        observer: bool = False,
+    ) -> "PocketOptionAsync":
+        """
+        Creates a client with the session returned by `provider` and registers it with `set_session_provider`, so the
+        session is renewed without restarting the bot when it expires.
+
+        Args:
+            provider (Callable): Coroutine function or function returning the session, in any format accepted by
+                `parse_ssid` (the `42["auth",...]` message, the cookies of the site...)
+            uid (int, optional): Id of the account, required if the provider returns the cookies or the session token
+            demo (bool, optional): Whether it's a demo account, inferred from the session if not given
+            url, config, observer: As in the constructor
+
+        Example:
+            ```python
+            async def login() -> str:
+                # e.g. log in the site with a headless browser and return its `ci_session` cookie
+                ...
+
+            client = await PocketOptionAsync.from_session_provider(login, uid=87742848)
+            ```
+        """
+        session = provider()
+        if inspect.isawaitable(session):
+            session = await session
+        client = cls(parse_ssid(session, uid, demo).ssid, url=url, config=config, observer=observer)
+        client.set_session_provider(provider)
+        return client
+
+    def set_session_provider(self, provider: Callable[[], Awaitable[str] | str]) -> None:
+        """
+        Registers the function called for a fresh session once the server rejects the current one (the session copied
+        from the browser expired), the client then reconnects with it. Its session is cached until it's rejected too,
+        and concurrent refreshes call it only once.
+
+        Must be called from a coroutine: a coroutine function runs on the running event loop, so the loop must keep
+        running while the client is used, a plain function is called directly from the client's threads.
+
+        Args:
+            provider (Callable): Coroutine function or function taking no arguments and returning the session of the
+                same account, in any format accepted by `parse_ssid` (sessions without the uid, like the cookies, get
+                the one of the current session). Its exceptions are logged and the current session is kept.
+        """
+        self.client.set_session_provider(provider, asyncio.get_running_loop())
+
+    def remove_session_provider(self) -> None:
+        "Removes the session provider, an expired session is then kept until the client is recreated"
+        self.client.remove_session_provider()
+
+    async def refresh_session(self) -> SsidInfo:
+        """
+        Asks the session provider for a fresh session right away and caches it, it's used from the next connection.
+
+        Raises:
+            Exception: If no provider is set, it failed or it returned the session of another account
+        """
+        return _ssid_info(await self.client.refresh_session())
+
+    def session_status(self) -> dict:
+        """
+        Returns the state of the session:
+            - uid, demo: Account of the session
+            - expired: True once the server rejected the session, until the provider returned a fresh one
+            - provider: Whether a session provider is set
+            - refreshes: Number of fresh sessions returned by the provider
+            - refreshed_at: When the last one was returned (None if never)
+            - rejection: Last rejection of the session by the server with its `failure` ("expired" or "malformed"), the
+              `reason` and its `time`, None once a session is accepted
+        """
+        return self.client.session_status()
+
+    async def profile(self) -> dict:
+        """
+        Returns the profile of the account:
+            - uid, demo, balance: Account of the session and its current balance
+            - session_ip, user_agent: IP and browser the session was created from (`session_ip` is None for the demo
+              accounts)
+            - server: Host of the websocket server the client is connected to
+            - region: Region reported by the server, None until it sends one
+            - region_mismatch: Last region or IP mismatch reported by the server with its `kind` ("ip" or "region"),
+              `reason`, `session_ip`, `current_ip` and the `hints` to fix it, None if it never reported one
+
+        Example:
+            ```python
+            profile = await client.profile()
+            if profile["region_mismatch"]:
+                print("\n".join(profile["region_mismatch"]["hints"]))
+            ```
+        """
+        return await self.client.profile()
+
+    async def health(self) -> dict:
+        """
+        Returns the current health of the client.
+
+        Returns:
+            dict: Health report containing:
+                - connected: Whether the websocket connection is currently open
+                - last_message_age: Seconds since the last message was received (None if no message was received)
+                - open_trades: Number of currently opened trades
+                - reconnections: Number of reconnections since the client was created
+                - shed_ticks: Number of best-effort ticks dropped because the streams didn't keep up
+        """
+        return await self.client.health()
+
+    async def start_healthcheck(self, port: int, host: str = "0.0.0.0", access: AccessControl | None = None) -> None:
+        """
+        Starts an embedded HTTP healthcheck endpoint, useful for docker / kubernetes liveness probes.
+        Every GET request is answered with the `health` report as JSON, with status code 200 if the
+        client is connected and 503 otherwise.
+
+        Args:
+            port (int): Port to listen on
+            host (str): Host to bind to. Defaults to "0.0.0.0"
+            access (AccessControl, optional): API keys allowed to read the endpoint, sent in an
+                `Authorization: Bearer <key>` or `X-Api-Key: <key>` header. Requests without a valid key are answered
+                with 401. Defaults to None (no key required).
+
+        Example:
+            ```python
+            client = PocketOptionAsync(ssid)
+            await client.start_healthcheck(8080)
+            # curl http://localhost:8080/health
+
+            access = AccessControl()
+            access.add_key("dashboard-key", "read_only")
+            await client.start_healthcheck(8081, access=access)
+            # curl -H "X-Api-Key: dashboard-key" http://localhost:8081/health
+            ```
+        """
+        await self.client.start_healthcheck(port, host, access)
+
+    async def start_dashboard(
+        self,
+        port: int = 8000,
+        host: str = "127.0.0.1",
+        logs: StreamLogsLayer | None = None,
+        access: AccessControl | None = None,
+    ) -> None:
+        """
+        Starts a dashboard of the client, a web page showing the balance, the open and closed trades, the subscribed
+        streams, the pending price and scheduled orders and the logs, updated every second. The page is served by
+        the client itself, nothing else needs to be installed.
+
+        The dashboard is an optional feature of the library, the wheels built without it (the default) raise a
+        `RuntimeError`, build the library with `maturin build --release --features dashboard` to enable it.
+
+        Args:
+            port (int): Port to listen on. Defaults to 8000
+            host (str): Host to bind to. Defaults to "127.0.0.1" (only reachable from this machine), use "0.0.0.0"
+                to open it to the network, preferably with `access`
+            logs (StreamLogsLayer, optional): Installed layer whose logs are shown. Defaults to None (no logs)
+            access (AccessControl, optional): API keys allowed to open the dashboard, given in the `key` parameter
+                of the url (`http://host:port/?key=<key>`). Defaults to None (no key required).
+
+        Example:
+            ```python
+            layer = StreamLogsLayer("INFO")
+            start_logs(".", "INFO", terminal=False, layers=[layer])
+            client = PocketOptionAsync(ssid)
+            await client.start_dashboard(8000, logs=layer)
+            # open http://127.0.0.1:8000 in a browser
+            ```
+        """
+        _require_native(self.client, "start_dashboard", "dashboard")
+        await self.client.start_dashboard(port, host, logs.layer if logs is not None else None, access)
+
+    async def shutdown(self) -> None:
+        """
+        Closes the connection with the server for good, it isn't reconnected afterwards.
+
+        The background tasks of the client (like the healthcheck endpoint) are stopped and every open stream
+        (`subscribe_symbol`, `subscribe_account_events`...) ends with `StopAsyncIteration`. The requests sent
+        afterwards fail. Calling it again does nothing.
+
+        Example:
+            ```python
+            client = PocketOptionAsync(ssid)
+            stream = await client.subscribe_symbol("EURUSD_otc")
+            ...
+            await client.shutdown()
+            async for candle in stream:  # Ends right away
+                ...
+            ```
+        """
+        await self.client.shutdown()
+
+    def is_shut_down(self) -> bool:
+        "Returns True once `shutdown` was called"
+        return self.client.is_shut_down()
+
+    def is_connected(self) -> bool:
+        "Returns True while the websocket is connected, False while reconnecting and after `shutdown`"
+        return self.client.is_connected()
+
+    def _connection_events_inner(self, timeout: timedelta | None = None):
+        return self.client.connection_events(timeout)
+
+    async def connection_events(self, timeout: timedelta | None = None) -> AsyncSubscription:
+        """
+        Subscribes to the changes of the state of the connection, to pause the order placement during the reconnection
+        windows. The subscription ends when the client is shut down.
+
+        Args:
+            timeout (timedelta | None): Optional maximum time to wait for each event, it only applies to this subscription
+
+        Returns:
+            AsyncSubscription: Async iterator yielding the events as dictionaries with the `kind` ("connected",
+                "disconnected", "reconnecting", "reconnect_failed", "authentication_failed" or "region_mismatch"), the
+                `time` of the event, the `attempt` number of the reconnections and the `reason` of the failure (if
+                known). The "authentication_failed" events tell whether the session expired or is malformed, to refresh
+                it without a session provider. Without one, the requests then fail with an `AuthenticationError`. The
+                "region_mismatch" events are sent when the server reports that the client connects from another IP or
+                region than the one of the session, the requests then fail with a `RegionMismatchError`.
+
+        Example:
+            ```python
+            async for event in await api.connection_events():
+                if event["kind"] == "connected":
+                    resume_trading()
+                else:
+                    pause_trading()
+            ```
+        """
+        return AsyncSubscription(self._connection_events_inner(timeout))
+
+    def pending_orders(self) -> int:
+        "Returns the number of orders sent whose confirmation wasn't received yet"
+        return self.client.pending_orders()
+
+    async def flush_orders(self, timeout: float | None = None) -> None:
+        """
+        Waits for the pending orders to be confirmed by the server (or to fail), so `shutdown` doesn't drop an order
+        that was already requested.
+
+        Args:
+            timeout (float, optional): Seconds to wait for. Defaults to the timeout of the orders
+
+        Raises:
+            Exception: If some orders are still pending after `timeout`
+        """
+        await self.client.flush_orders(timeout)
+
+    async def __aenter__(self) -> "PocketOptionAsync":
+        return self
+
+    async def __aexit__(self, exc_type, exc_value, traceback):
+        """
+        Flushes the pending orders and shuts the client down, so the connection isn't leaked:
+
+            ```python
+            async with PocketOptionAsync(ssid) as client:
+                await client.buy("EURUSD_otc", 1.0, 60)
+            # The connection is closed here, even if the block raised
+            ```
+        """
+        await self.client.__aexit__(exc_type, exc_value, traceback)
+
+    async def valid_expiries(self, asset: str) -> list[int]:
+        """
+        Returns the expiry times (in seconds) accepted by the server for an asset, sorted from shortest to longest.
+
+        Args:
+            asset (str): Trading asset (e.g., "EURUSD_otc")
+
+        Raises:
+            Exception: If the asset isn't in the list of assets sent by the server
+        """
+        return await self.client.valid_expiries(asset)
+
+    async def nearest_expiry(self, asset: str, desired_secs: int) -> int:
+        """
+        Returns the valid expiry closest to `desired_secs` for an asset, use it to correct the expiry
+        before placing a trade instead of letting the broker silently adjust it.
+
+        Args:
+            asset (str): Trading asset (e.g., "EURUSD_otc")
+            desired_secs (int): Desired expiry in seconds
+
+        Example:
+            ```python
+            time = await client.nearest_expiry("EURUSD_otc", 250)  # 300
+            trade_id, _ = await client.buy("EURUSD_otc", 1.0, time)
+            ```
+        """
+        return await self.client.nearest_expiry(asset, desired_secs)
+
+    async def suggest_expiry(self, asset: str, target_move_pips: float) -> dict:
+        """
+        Suggests the shortest valid expiry of an asset likely to see a move of `target_move_pips`, from the
+        realized volatility of its one minute candles of the last hour. The prices are modeled as a random walk,
+        so the suggestion is a starting point for adaptive strategies rather than a forecast.
+
+        Args:
+            asset (str): Trading asset (e.g., "EURUSD_otc")
+            target_move_pips (float): Move the trade needs, in pips
+
+        Returns:
+            dict: Dictionary with the suggested `expiry` (in seconds), the `expected_move_pips` over it, the
+            `volatility_pips` of one candle, `reaches_target` (False when even the longest expiry is too short,
+            the longest one is suggested) and the number of `candles` measured.
+
+        Example:
+            ```python
+            suggestion = await client.suggest_expiry("EURUSD_otc", 5.0)
+            if suggestion["reaches_target"]:
+                await client.buy("EURUSD_otc", 1.0, suggestion["expiry"])
+            ```
+        """
+        return await self.client.suggest_expiry(asset, target_move_pips)
+
+    async def precision(self, asset: str) -> dict:
+        """
+        Returns the precision of the prices of an asset.
+
+        Args:
+            asset (str): Trading asset (e.g., "EURUSD_otc")
+
+        Returns:
+            dict: Dictionary with the number of `digits` of the prices and the `pip_size` (0.0001 for EURUSD, 0.01 for JPY pairs).
+
+        Raises:
+            Exception: If the asset isn't in the list of assets sent by the server
+        """
+        return await self.client.precision(asset)
+
+    async def round_price(self, asset: str, price: float) -> float:
+        """
+        Rounds a price to the number of digits of an asset.
+
+        Example:
+            ```python
+            price = await client.round_price("EURUSD_otc", 1.085434999)  # 1.08543
+            ```
+        """
+        return await self.client.round_price(asset, price)
+
+    async def pips_between(self, asset: str, a: float, b: float) -> float:
+        """
+        Returns the signed number of pips from price `a` to price `b` of an asset, rounded to a tenth of a pip.
+
+        Example:
+            ```python
+            pips = await client.pips_between("EURUSD_otc", 1.08543, 1.08553)  # 1.0
+            ```
+        """
+        return await self.client.pips_between(asset, a, b)
+
+    async def hedge(self, trade_id: str, hedge_asset: str, ratio: float = 1.0, negative_correlation: bool = True) -> tuple[str, str, dict]:
+        """
+        Places an offsetting trade for an already placed trade on a correlated asset, both trades are
+        linked in the journal as a hedge group.
+
+        Args:
+            trade_id (str): ID of the primary trade, it must have been placed by this client
+            hedge_asset (str): Asset correlated to the asset of the primary trade (e.g. "USDCHF_otc" for "EURUSD_otc")
+            ratio (float): Amount of the hedge trade relative to the amount of the primary trade. Defaults to 1.0
+            negative_correlation (bool): If True (default) the hedge trade is placed in the same direction as the
+                primary trade since both assets move in opposite directions, otherwise in the opposite direction
+
+        Returns:
+            tuple[str, str, dict]: Tuple containing (group_id, hedge_trade_id, hedge_trade_details)
+
+        Example:
+            ```python
+            trade_id, _ = await client.buy("EURUSD_otc", 10, 60)
+            group_id, hedge_id, hedge = await client.hedge(trade_id, "USDCHF_otc", ratio=0.5)
+            print(client.hedge_group(group_id))
+            ```
+        """
+        (group, hedge_id, hedge) = await self.client.hedge(trade_id, hedge_asset, ratio, negative_correlation)
+        return group, hedge_id, hedge
+
+    async def straddle(self, asset: str, amount: float, expiry: int) -> dict:
+        """
+        Places a call and a put of the same amount and expiry on an asset, sent together by the Rust client so both
+        legs open at the same price (two separate `buy` / `sell` awaits can open at different prices). Both legs
+        go through the risk rules, are tagged "straddle" and grouped in the journal.
+
+        Args:
+            asset (str): Trading asset (e.g., "EURUSD")
+            amount (float): Amount of each leg
+            expiry (int): Expiry of both legs in seconds
+
+        Returns:
+            dict: Dictionary with the journal `group` of the straddle, the `asset`, `amount`, `expiry` and the `call`
+                and `put` deals. The combined result is sent to `subscribe_straddle_results` once both legs closed.
+
+        Raises:
+            ValueError: If only one leg was placed, the message contains the id of the placed leg (left open)
+
+        Example:
+            ```python
+            results = await client.subscribe_straddle_results()
+            straddle = await client.straddle("EURUSD", 10.0, 300)
+            async for result in results:
+                if result["group"] == straddle["group"]:
+                    print(f"Straddle closed: {result['result']} ({result['profit']})")
+                    break
+            ```
+        """
+        return await self.client.straddle(asset, amount, expiry)
+
+    async def _subscribe_straddle_results_inner(self, timeout: timedelta | None = None):
+        return await self.client.subscribe_straddle_results(timeout)
+
+    async def subscribe_straddle_results(self, timeout: timedelta | None = None) -> AsyncSubscription:
+        """
+        Subscribes to the combined results of the straddles, one result is yielded once both legs of a straddle closed.
+
+        Args:
+            timeout (timedelta | None): Optional maximum time to wait for each result
+
+        Returns:
+            AsyncSubscription: Async iterator yielding dictionaries with the `group` and `asset` of the straddle, the
+                closed `call` and `put` deals, their combined `profit` and the `result` ("win", "loss" or "draw").
+        """
+        return AsyncSubscription(await self._subscribe_straddle_results_inner(timeout))
+
+    def debug_stats(self) -> dict:
+        """
+        Returns internal counters of the library, useful to diagnose performance issues.
+
+        Returns:
+            dict: Dictionary with the `json_backend` the messages are parsed with ("serde_json" or "simd-json")
+                and the `parser_buffers` pool statistics: buffers `acquired`, `reused` from the pool, `allocated`,
+                `discarded` and currently `pooled` (only used by the simd-json backend).
+        """
+        return self.client.debug_stats()
+
+    async def latency_stats(self) -> dict:
+        """
+        Returns rolling statistics of the latency of the last streamed messages, the time in milliseconds between
+        their server timestamp and the moment they were recieved.
+
+        Returns:
+            dict: Dictionary with the number of `samples`, the `last`, `mean`, `p50`, `p90`, `p99` and `max` latencies,
+                the estimated `clock_offset` between the local and server clocks (minimum latency) and the
+                `transport_p50` and `transport_p99` latencies without that offset. A growing `transport_p50` means
+                the machine is falling behind the feed.
+
+        Note:
+            Each streamed candle also includes its `received_at` time and `latency`.
+        """
+        return await self.client.latency_stats()
+
+    async def feed_health(self) -> dict:
+        """
+        Returns the message rate of every subscribed feed compared to the rate it usually streams at. A feed whose
+        rate collapses while the connection is up (possible throttling or ban of the account by the server) is logged
+        and sent as a `feed_anomaly` account event to `subscribe_account_events`, so is its recovery.
+
+        Returns:
+            dict: Dictionary with the `feeds` (their `asset`, `expected_rate` and `observed_rate` in ticks per second
+                and `collapsed_since`), the `collapsed` assets and `suspected_throttling`, true when every feed with
+                an expected rate collapsed at once.
+
+        Example:
+            ```python
+            report = await client.feed_health()
+            if report["suspected_throttling"]:
+                print(f"Every feed collapsed: {report['collapsed']}")
+            ```
+        """
+        return await self.client.feed_health()
+
+    async def set_metadata_cache(self, path: str, ttl: timedelta | float = timedelta(days=1)) -> dict:
+        """
+        Caches the metadata of the assets (payouts, expiries and open markets) in a file, so a bot restarted while the
+        server is slow to send them can still validate its orders. The cache is loaded right away if it isn't older
+        than `ttl` and the server didn't send the assets yet, the assets sent by the server replace it and are saved
+        to the file in the background.
+
+        Args:
+            path (str): Cache file, created if it doesn't exist
+            ttl (timedelta | float): Age (seconds for a number) after which the cached assets aren't used anymore,
+                one day by default
+
         Returns:
-            str: The first message that matches the validator's conditions
-            
+            dict: The `metadata_status` once the cache is loaded
+
         Example:
             ```python
-            validator = Validator.starts_with('451-["signals/load"')
-            response = await client.create_raw_order(
-                '42["signals/subscribe"]',
-                validator
-            )
+            status = await client.set_metadata_cache("assets.json", timedelta(hours=6))
+            print(f"{status['assets']} assets from the {status['source']}")
             ```
         """
-        return await self.client.create_raw_order(message, validator.raw_validator)
-        
-    async def create_raw_order_with_timout(self, message: str, validator: Validator, timeout: timedelta) -> str:
+        return await self.client.set_metadata_cache(str(path), _seconds(ttl))
+
+    async def metadata_status(self) -> dict:
         """
-        Similar to create_raw_order but with a timeout.
-        
+        Returns where the metadata of the assets used by the client come from.
+
+        Returns:
+            dict: Dictionary with the `source` ("server", "cache" or "none" until either provides them), `updated_at`
+                (when the server sent them), the number of `assets`, the `path` of the cache file and `stale`, true
+                when the cached assets are older than the TTL of the cache and the server still didn't send them
+        """
+        return await self.client.metadata_status()
+
+    async def server_ids(self, trade_id: str) -> dict:
+        """
+        Returns the identifiers the broker uses for a trade, the ones its support needs when opening a ticket.
+
         Args:
-            message: Raw WebSocket message to send
-            validator: Validator instance to validate the response
-            timeout: Maximum time to wait for a valid response
-            
+            trade_id (str): ID of the trade, as returned by `buy` or `sell`
+
         Returns:
-            str: The first message that matches the validator's conditions
-            
+            dict: Dictionary with the `ticket` (id of the deal on the trade server), the `request_id`
+                of the order (None if unknown) and the `uid` of the account that placed it.
+
         Raises:
-            TimeoutError: If no valid response is received within the timeout period
+            Exception: If the trade isn't in the opened deals, the closed deals nor the journal
         """
+        return await self.client.server_ids(trade_id)
 
-        return await self.client.create_raw_order_with_timeout(message, validator.raw_validator, timeout)
-    
-    async def create_raw_order_with_timeout_and_retry(self, message: str, validator: Validator, timeout: timedelta) -> str:
+    def journal(self) -> list[dict]:
+        "Returns the journal of all the trades placed by the client, including their hedge group and result once closed"
+        return self.client.journal()
+
+    def hedge_group(self, group_id: str) -> list[dict]:
+        "Returns the journal entries of the trades linked in a hedge group"
+        return self.client.hedge_group(group_id)
+
+    def persist_journal(self, path: str = "journal.json") -> None:
         """
-        Similar to create_raw_order_with_timout but with automatic retry on failure.
-        
+        Loads the journal stored in `path` (if it exists) and saves every new trade and result to it from now on.
+
         Args:
-            message: Raw WebSocket message to send
-            validator: Validator instance to validate the response
-            timeout: Maximum time to wait for each attempt
-            
+            path (str): Path of the journal file. Defaults to "journal.json"
+        """
+        self.client.persist_journal(path)
+
+    def capture_journal_context(
+        self, period: int | str | Timeframe | None = 60, before: int = 20, after: int = 10
+    ) -> None:
+        """
+        Stores the candles around every trade in its journal entry (under `context`) once it closes, so a post-mortem
+        can look at the market the bot traded in without recording the streams. The candles are downloaded once the
+        last one closed, `after` candles after the close of the trade.
+
+        Args:
+            period (int | str | Timeframe | None): Timeframe of the candles, None stops storing them. Defaults to 60
+            before (int): Candles before the one the trade opened in. Defaults to 20
+            after (int): Candles after the one the trade closed in. Defaults to 10
+
+        Example:
+            ```python
+            client.persist_journal("journal.json")
+            client.capture_journal_context("1m", before=30, after=5)
+            trade_id, _ = await client.buy("EURUSD_otc", 1.0, 60, check_win=True)
+            # a few minutes later
+            context = next(e["context"] for e in client.journal() if e["trade_id"] == trade_id)
+            ```
+        """
+        self.client.capture_journal_context(
+            int(Timeframe.parse(period)) if period is not None else None, before, after
+        )
+
+    def import_trades(self, path: str) -> int:
+        """
+        Loads the trades of a history exported from the Pocket Option website in the journal, so its statistics
+        include the trades placed before using the library. The imported trades are tagged "import" and the ones
+        already in the journal are skipped, so the same file can be imported again safely.
+
+        Args:
+            path (str): Path of the export, CSV files are always supported, XLSX files require the `xlsx` feature
+
         Returns:
-            str: The first message that matches the validator's conditions
+            int: Number of trades added to the journal
+
+        Example:
+            ```python
+            client.persist_journal("journal.json")
+            added = client.import_trades("trades_export.csv")
+            print(client.journal_stats())
+            ```
         """
+        return self.client.import_trades(path)
 
-        return await self.client.create_raw_order_with_timeout_and_retry(message, validator.raw_validator, timeout)
- 
-    async def create_raw_iterator(self, message: str, validator: Validator, timeout: timedelta | None = None):
+    def journal_stats(self, tag: str | None = None) -> dict:
         """
-        Creates an async iterator that yields validated WebSocket messages.
-        
+        Returns the statistics of the closed trades of the journal (only of the trades with `tag` if it's given):
+        the number of `trades`, `wins`, `losses` and `draws`, the `win_rate`, `total_amount`, `total_profit`,
+        `roi` and the dates of the `first_trade` and `last_trade`.
+        """
+        return self.client.journal_stats(tag)
+
+    def expectancy(
+        self,
+        asset: str | None = None,
+        tag: str | None = None,
+        hours: tuple[int, int] | None = None,
+        since: datetime | float | None = None,
+        until: datetime | float | None = None,
+        confidence: float = 0.95,
+    ) -> dict:
+        """
+        Measures the edge of the closed trades of the journal matching the filter, so the stake can be adapted to it.
+
         Args:
-            message: Initial WebSocket message to send
-            validator: Validator instance to filter incoming messages
-            timeout: Optional timeout for the entire stream
-            
+            asset (str, optional): Only the trades of this asset
+            tag (str, optional): Only the trades with this tag (e.g. "template:<name>" or a strategy tag)
+            hours (tuple[int, int], optional): Only the trades opened between these hours (UTC), the first one is
+                included and the last one excluded. A range ending before it starts wraps around midnight, e.g. (22, 2)
+            since (datetime | float, optional): Only the trades opened at or after this time
+            until (datetime | float, optional): Only the trades opened before this time
+            confidence (float): Confidence level of the interval. Defaults to 0.95
+
         Returns:
-            AsyncIterator yielding validated messages
-            
+            dict: Edge containing:
+                - trades: Number of closed trades matching the filter
+                - win_rate, average_win, average_loss (as a positive amount)
+                - expectancy: Mean profit per trade, in the account currency
+                - return_per_unit: Mean profit per unit staked
+                - std_dev: Standard deviation of the returns per unit staked (None with less than 2 trades)
+                - sharpe: Return per unit over its standard deviation (None without a standard deviation)
+                - confidence / interval: Confidence level and [low, high] interval of the return per unit (None with
+                  less than 2 trades). A low bound above 0 means the edge is unlikely to be luck
+
         Example:
             ```python
-            validator = Validator.starts_with('{"signals":')
-            async for message in client.create_raw_iterator(
-                '42["signals/subscribe"]',
-                validator,
-                timeout=timedelta(minutes=5)
-            ):
-                print(f"Received: {message}")
+            edge = api.expectancy(tag="strategy:trend", hours=(8, 12))
+            if edge["interval"] and edge["interval"][0] > 0:
+                amount *= 1.5
             ```
         """
-        return await self.client.create_raw_iterator(message, validator, timeout)
-    
-    async def get_server_time(self) -> int:
-        """Returns the current server time as a UNIX timestamp"""
-        return await self.client.get_server_time()
-    
-    async def is_demo(self) -> bool:
+        return self.client.expectancy(_trade_filter(asset, tag, hours, since, until), confidence)
+
+    def performance_by_hour(
+        self,
+        by_asset: bool = False,
+        asset: str | None = None,
+        tag: str | None = None,
+        since: datetime | float | None = None,
+        until: datetime | float | None = None,
+    ) -> dict:
         """
-        Checks if the current account is a demo account.
+        Aggregates the closed trades of the journal by the hour of the day (UTC) they were opened at, as matrices ready
+        to be drawn as a heatmap.
+
+        Args:
+            by_asset (bool): One row per asset instead of a single "all" row. Defaults to False
+            asset (str, optional): Only the trades of this asset
+            tag (str, optional): Only the trades with this tag (e.g. "template:<name>" or a strategy tag)
+            since (datetime | float, optional): Only the trades opened at or after this time
+            until (datetime | float, optional): Only the trades opened before this time
 
         Returns:
-            bool: True if using a demo account, False if using a real account
+            dict: Performance containing:
+                - axis: "hour"
+                - rows: Assets of the rows (["all"] if not `by_asset`)
+                - columns: Labels of the 24 columns, "00" to "23"
+                - trades, profit: Matrices (one list per row) of the number of trades and the total profit
+                - win_rate, return_per_unit: Matrices of the win rate and of the profit per unit staked, None in the
+                  cells without trades
 
-        Examples:
+        Example:
             ```python
-            # Basic account type check
-            async with PocketOptionAsync(ssid) as client:
-                is_demo = await client.is_demo()
-                print("Using", "demo" if is_demo else "real", "account")
+            import matplotlib.pyplot as plt
 
-            # Example with balance check
-            async def check_account():
-                is_demo = await client.is_demo()
-                balance = await client.balance()
-                print(f"{'Demo' if is_demo else 'Real'} account balance: {balance}")
+            performance = api.performance_by_hour(by_asset=True)
+            plt.imshow([[r or 0 for r in row] for row in performance["return_per_unit"]], cmap="RdYlGn")
+            plt.xticks(range(24), performance["columns"])
+            plt.yticks(range(len(performance["rows"])), performance["rows"])
+            ```
+        """
+        return self.client.performance_by_hour(_trade_filter(asset, tag, None, since, until), by_asset)
 
-            # Example with trade validation
-            async def safe_trade(asset: str, amount: float, duration: int):
-                is_demo = await client.is_demo()
-                if not is_demo and amount > 100:
-                    raise ValueError("Large trades should be tested in demo first")
-                return await client.buy(asset, amount, duration)
+    def performance_by_weekday(
+        self,
+        by_asset: bool = False,
+        asset: str | None = None,
+        tag: str | None = None,
+        hours: tuple[int, int] | None = None,
+        since: datetime | float | None = None,
+        until: datetime | float | None = None,
+    ) -> dict:
+        """
+        Aggregates the closed trades of the journal by the day of the week (UTC) they were opened on, with the same
+        matrices as `performance_by_hour` and the 7 columns "Mon" to "Sun".
+
+        Args:
+            by_asset (bool): One row per asset instead of a single "all" row. Defaults to False
+            asset (str, optional): Only the trades of this asset
+            tag (str, optional): Only the trades with this tag
+            hours (tuple[int, int], optional): Only the trades opened between these hours (UTC), see `expectancy`
+            since (datetime | float, optional): Only the trades opened at or after this time
+            until (datetime | float, optional): Only the trades opened before this time
+        """
+        return self.client.performance_by_weekday(_trade_filter(asset, tag, hours, since, until), by_asset)
+
+    def save_template(self, template: OrderTemplate | dict, path: str = "order_templates.json") -> None:
+        """
+        Saves an order template so it can be executed by name with `execute_template`, it replaces the template
+        with the same name if any.
+
+        Args:
+            template (OrderTemplate | dict): Template to save
+            path (str): Path of the templates file. Defaults to "order_templates.json"
+        """
+        if not isinstance(template, OrderTemplate):
+            template = OrderTemplate.from_dict(template)
+        self.client.save_template(template.to_json(), path)
+
+    def list_templates(self, path: str = "order_templates.json") -> dict[str, OrderTemplate]:
+        """Returns the saved order templates, by name"""
+        return {name: OrderTemplate.from_dict(t) for name, t in self.client.list_templates(path).items()}
+
+    def remove_template(self, name: str, path: str = "order_templates.json") -> bool:
+        """Removes a saved order template, returns True if it existed"""
+        return self.client.remove_template(name, path)
+
+    async def execute_template(self, name: str, path: str = "order_templates.json") -> dict:
+        """
+        Executes a saved order template: its stake and expiry are resolved against the current state of the account,
+        its checks are run and its trades are placed (through the risk rules like any other trade).
+        The trades are tagged in the journal with `template:<name>` and the risk tags of the template.
+
+        Args:
+            name (str): Name of the template
+            path (str): Path of the templates file. Defaults to "order_templates.json"
+
+        Returns:
+            dict: The `template` name, the journal `group` of the trades (None for a single trade) and the placed `trades`
+
+        Raises:
+            Exception: If the template doesn't exist or one of its checks failed
+
+        Example:
+            ```python
+            execution = await client.execute_template("news_straddle_eurusd")
+            for trade in execution["trades"]:
+                print(f"Placed trade {trade['id']} of {trade['amount']}")
             ```
         """
-        return await self.client.is_demo()
+        return await self.client.execute_template(name, path)
+
+    def add_cluster_guard(self, max_trades: int = 1, window: timedelta = timedelta(minutes=1), aligned: bool = True) -> None:
+        """
+        Limits the number of trades placed on the same asset in a time window (anti-overtrading).
+        Trades over the limit are rejected before being sent with an error describing the rule that rejected them,
+        preventing duplicate entries when a signal bounces.
+
+        Args:
+            max_trades (int): Maximum number of trades per asset in the window. Defaults to 1
+            window (timedelta): Duration of the window. Defaults to 1 minute
+            aligned (bool): If True the windows are aligned to the candle boundaries (max trades per candle),
+                otherwise it's a sliding window ending at the time of the trade. Defaults to True
+
+        Example:
+            ```python
+            client.add_cluster_guard(1, timedelta(minutes=5))  # Max 1 trade per symbol per 5m candle
+            await client.buy("EURUSD_otc", 1.0, 300)
+            await client.buy("EURUSD_otc", 1.0, 300)  # Raises an error
+            ```
+        """
+        self.client.add_cluster_guard(max_trades, window.total_seconds(), aligned)
+
+    async def set_drawdown_breaker(self, max_drawdown: float, demo_ssid: str | None = None) -> None:
+        """
+        Adds a circuit breaker that stops trading on the real account once the balance drops more than `max_drawdown`
+        from its peak. When it trips an error is logged, an audit event is written and a `circuit_breaker` event is sent
+        to the subscribers of `subscribe_account_events`.
+
+        Args:
+            max_drawdown (float): Maximum drawdown allowed, as a fraction of the peak balance (0.2 means 20%)
+            demo_ssid (str, optional): Session id of the demo account, if provided the trades placed after the breaker
+                tripped are placed on the demo account instead of being rejected, so the strategy keeps running for
+                diagnostics. Their results must be checked with the demo account
+
+        Raises:
+            Error: If `max_drawdown` isn't between 0 and 1 or `demo_ssid` isn't a demo session
+
+        Example:
+            ```python
+            await client.set_drawdown_breaker(0.2, demo_ssid)
+            print(client.drawdown_state())
+            ```
+        """
+        await self.client.set_drawdown_breaker(max_drawdown, demo_ssid)
+
+    def drawdown_state(self) -> dict | None:
+        """
+        Returns the state of the drawdown breaker, None if it isn't set.
+
+        Returns:
+            dict | None: Dictionary containing `max_drawdown`, `peak`, `balance`, `drawdown` and `tripped_at`
+                (None if the breaker didn't trip)
+        """
+        return self.client.drawdown_state()
+
+    def reset_drawdown_breaker(self) -> None:
+        "Re-arms the drawdown breaker after it tripped, the current balance becomes the new peak"
+        self.client.reset_drawdown_breaker()
+
+    def set_trading_hours(self, hours: list[int] | None = None, weekdays: list[int] | None = None) -> None:
+        """
+        Only allows the trades placed in some hours of the day and days of the week, in UTC. The trades placed outside
+        of them are rejected before being sent with an error, it replaces the previous trading hours if any.
+
+        Args:
+            hours (list[int], optional): Allowed hours, from 0 to 23. Every hour if None
+            weekdays (list[int], optional): Allowed days of the week, from 0 (Monday) to 6 (Sunday). Every day if None
+
+        Example:
+            ```python
+            api.set_trading_hours(hours=range(8, 12), weekdays=range(5))  # 8h to 12h UTC on weekdays
+            ```
+        """
+        self.client.set_trading_hours(
+            list(hours) if hours is not None else None,
+            list(weekdays) if weekdays is not None else None,
+        )
+
+    def trade_profitable_hours(
+        self,
+        min_trades: int = 10,
+        asset: str | None = None,
+        tag: str | None = None,
+        since: datetime | float | None = None,
+    ) -> list[int]:
+        """
+        Only allows the trades placed in the hours (UTC) the closed trades of the journal were profitable at, using the
+        matrix of `performance_by_hour`. It replaces the trading hours set with `set_trading_hours`.
+
+        Args:
+            min_trades (int): Minimum number of trades of an hour to allow it. Defaults to 10
+            asset (str, optional): Only measure the trades of this asset
+            tag (str, optional): Only measure the trades with this tag (e.g. a strategy tag)
+            since (datetime | float, optional): Only measure the trades opened at or after this time
+
+        Returns:
+            list[int]: The allowed hours, empty if no hour was profitable (every trade is then rejected)
+        """
+        return self.client.trade_profitable_hours(_trade_filter(asset, tag, None, since), min_trades)
+
+    def set_market_guard(
+        self,
+        max_latency: float | None = None,
+        max_gap: timedelta | float | None = None,
+        max_volatility: float | None = None,
+        window: int = 20,
+        resume_after: timedelta | float = 30,
+    ) -> None:
+        """
+        Pauses the order placement while the market conditions are abnormal, trading resumes on its own once they
+        stayed normal for `resume_after`. The trades placed during a pause are rejected before being sent with an
+        error, and every pause and resume is sent as a `circuit_breaker` event (named "marketGuardPaused" or
+        "marketGuardResumed") to the subscribers of `subscribe_account_events`. It replaces the previous guard if any.
+
+        Args:
+            max_latency (float, optional): Maximum mean latency of the last ticks in milliseconds, without the offset
+                between the local and the server clocks. A high latency pauses every asset
+            max_gap (timedelta | float, optional): Maximum time without a tick of an asset (seconds if a number), only
+                the subscribed assets are checked
+            max_volatility (float, optional): Maximum standard deviation of the tick to tick returns of an asset,
+                e.g. 0.001 for 0.1%
+            window (int): Number of ticks the latency and the volatility are measured over. Defaults to 20
+            resume_after (timedelta | float): Time the conditions must stay normal before trading resumes (seconds if
+                a number). Defaults to 30 seconds
+
+        Example:
+            ```python
+            api.set_market_guard(max_latency=800, max_gap=timedelta(seconds=10))
+            async for event in await api.subscribe_account_events():
+                print(event["event"], event["message"])
+            ```
+        """
+        self.client.set_market_guard(json.dumps({
+            "max_latency": max_latency,
+            "max_gap": _seconds(max_gap) if max_gap is not None else None,
+            "max_volatility": max_volatility,
+            "window": window,
+            "resume_after": _seconds(resume_after),
+        }))
+
+    def remove_market_guard(self) -> None:
+        "Removes the market guard, the trades are no longer paused on abnormal market conditions"
+        self.client.remove_market_guard()
+
+    def market_guard_status(self) -> list[dict]:
+        """
+        Returns the pauses of the market guard, empty if the trading isn't paused or the guard isn't set.
+        Each pause has the `asset` it applies to (None for every asset), the `reasons` and the time it started (`since`)
+        """
+        return self.client.market_guard_status()
+
+    def risk_rules(self) -> list[str]:
+        "Returns the names of the risk rules checked before every trade"
+        return self.client.risk_rules()
+
+    def clear_risk_rules(self) -> None:
+        "Removes all the risk rules"
+        self.client.clear_risk_rules()
+
+    def set_stake_rules(
+        self,
+        step: float = 0.01,
+        min: float | None = None,
+        max: float | None = None,
+        rounding: str = "nearest",
+        currency: str | None = None,
+        account_currency: str | None = None,
+    ) -> None:
+        """
+        Sets the rounding rule of the stakes, applied before every order: the amount is rounded to a multiple of
+        `step` and rejected with a clear error if it's out of the limits, instead of being sent to the broker which
+        rejects the odd amounts with an opaque error. Each currency can have its own rule.
+
+        Args:
+            step (float): Stakes are rounded to a multiple of the step (0.01 for cents, 1 for whole units). Defaults to 0.01
+            min (float, optional): Minimum stake after rounding
+            max (float, optional): Maximum stake after rounding
+            rounding (str): "nearest" (default), "down" (never stakes more than requested) or "up"
+            currency (str, optional): Currency the rule applies to (e.g. "USD"), the rule of every currency without
+                its own rule if it's not given
+            account_currency (str, optional): Currency of the account, learnt from its deals if it's not set
+
+        Raises:
+            ValueError: If the rule is invalid (e.g. the step isn't positive)
+
+        Example:
+            ```python
+            client.set_stake_rules(step=0.01, min=1, max=20000, currency="USD")
+            client.set_stake_rules(step=1, min=50, rounding="down", currency="RUB")
+            await client.buy("EURUSD_otc", 1.005, 60)  # Placed with an amount of 1.0 (or 1.01) USD
+            ```
+        """
+        rules = self.stake_rules()
+        rule = {"step": step, "min": min, "max": max, "rounding": rounding}
+        if currency is None:
+            rules["default"] = rule
+        else:
+            rules["currencies"][currency.upper()] = rule
+        if account_currency is not None:
+            rules["currency"] = account_currency.upper()
+        self.client.set_stake_rules(json.dumps(rules))
+
+    def stake_rules(self) -> dict:
+        "Returns the stake rules: the account `currency` if it's set, the rule of each of the `currencies` and the `default` rule"
+        return self.client.stake_rules()
+
+    async def account_currency(self) -> str | None:
+        "Returns the currency of the account, None if it isn't set in the stake rules and the account has no deal yet"
+        return await self.client.account_currency()
+
+    async def round_stake(self, amount: float) -> float:
+        "Returns the amount an order of `amount` would be placed with, raises an error if it's out of the limits of the stake rules"
+        return await self.client.round_stake(amount)
+
+    async def audit_candles(self, data_or_range: list[dict] | tuple[str, int, int], period: int | None = None) -> dict:
+        """
+        Returns a data quality report of a list of candles, so they can be checked (or cleaned) before being used.
+
+        Args:
+            data_or_range (list[dict] | tuple[str, int, int]): Candles to audit (as returned by `get_candles`, the time
+                can also be a datetime or a unix timestamp), or a range `(asset, period, offset)` to download and audit
+            period (int, optional): Period of the candles in seconds, inferred from the candles if not provided
+
+        Returns:
+            dict: Report containing:
+                - period: Period used to audit the candles
+                - candles: Number of candles
+                - start / end: Time of the first and last candle
+                - gaps: List of [start, end) ranges without candles
+                - missing_candles: Number of missing candles
+                - duplicates: Timestamps shared by more than one candle
+                - zero_range: Timestamps of the candles where the high is equal to the low
+                - misaligned: Timestamps of the candles not aligned to the period
+        """
+        if isinstance(data_or_range, tuple):
+            asset, range_period, offset = data_or_range
+            return await self.client.audit_candles(asset, int(Timeframe.parse(range_period)), offset)
+        return json.loads(RawPocketOption.audit_candles_data(_candles_to_json(data_or_range), period))
+
+    async def levels(self, asset: str, period: int | str | Timeframe, lookback: int, **config) -> dict:
+        """
+        Downloads the last `lookback` seconds of candles of `asset` and computes their price levels.
+
+        Args:
+            asset (str): Trading asset
+            period (int | str | Timeframe): Timeframe of the candles
+            lookback (int): Seconds of history the levels are computed from
+            **config: Settings of the levels:
+                - session (int): Length of the sessions in seconds, they start at multiples of it. Defaults to 86400
+                - swing_window (int): Candles on each side of a swing high / low. Defaults to 2
+                - zone_width (float): Largest width of a zone, relative to the mean range of the candles. Defaults to 0.5
+                - min_touches (int): Smallest number of swings of a zone. Defaults to 2
+                - max_zones (int): Largest number of zones, the most touched are kept. Defaults to 10
+
+        Returns:
+            dict: Levels containing:
+                - time / close: Time and close of the last candle
+                - pivots: Classic pivots (`pivot`, `r1` to `r3`, `s1` to `s3`) of the previous session, or of the whole
+                  history if it covers a single session
+                - sessions: `start`, `open`, `high`, `low`, `close` and number of `candles` of every session
+                - zones: Support and resistance zones sorted by price, with their `kind` ("support" below the last
+                  close, "resistance" otherwise), mean `price`, `low`, `high`, number of `touches` and `last_touch`
+                - nearest_support / nearest_resistance: Closest zones to the last close, None if there isn't any
+        """
+        return await self.client.levels(asset, int(Timeframe.parse(period)), lookback, _levels_config(**config))
+
+    async def chart_trade(
+        self,
+        trade_id: str,
+        period: int | str | Timeframe = 60,
+        width: int = 1000,
+        height: int = 500,
+        title: str | None = None,
+    ) -> bytes:
+        """
+        Renders a trade to a PNG image: the candles of its asset around it, with its entry (a triangle pointing up
+        for a call and down for a put) and, once it's closed, its exit (a square, green for a win and red for a loss).
+        Meant to be attached to the alerts of a bot, e.g. with the `sendPhoto` method of the Telegram bot API.
+
+        The charts are an optional feature of the library, build it with `maturin build --release --features charts`
+        to enable them. The labels are drawn with the fonts of the system, slim docker images need one installed
+        (e.g. `fonts-dejavu-core`).
+
+        Args:
+            trade_id (str): ID of an open or closed trade
+            period (int | str | Timeframe): Timeframe of the candles. Defaults to 60
+            width (int): Width of the image in pixels. Defaults to 1000
+            height (int): Height of the image in pixels. Defaults to 500
+            title (str, optional): Title drawn above the chart
+
+        Returns:
+            bytes: The PNG image
+
+        Example:
+            ```python
+            trade_id, _ = await client.buy("EURUSD_otc", 1.0, 60)
+            result = await client.check_win(trade_id)
+            png = await client.chart_trade(trade_id, title=f"EURUSD_otc {result['result']}")
+            with open("trade.png", "wb") as file:
+                file.write(png)
+            ```
+        """
+        _require_native(self.client, "chart_trade", "charts")
+        return await self.client.chart_trade(trade_id, int(Timeframe.parse(period)), _chart_options(width, height, title))
+
+    async def chart_range(
+        self,
+        asset: str,
+        period: int | str | Timeframe,
+        start: datetime | float,
+        end: datetime | float,
+        markers: list[dict | tuple] | None = None,
+        width: int = 1000,
+        height: int = 500,
+        title: str | None = None,
+    ) -> bytes:
+        """
+        Renders the candles of `asset` between `start` and `end` (server time) to a PNG image, see `chart_trade`.
+
+        Args:
+            asset (str): Trading asset
+            period (int | str | Timeframe): Timeframe of the candles
+            start (datetime | float): Start of the chart, a datetime or a unix timestamp
+            end (datetime | float): End of the chart
+            markers (list[dict | tuple], optional): Points drawn on the chart, as `(time, price, kind)` tuples or
+                dicts with these keys, `kind` being "call", "put", "win", "loss" or "draw"
+            width (int): Width of the image in pixels. Defaults to 1000
+            height (int): Height of the image in pixels. Defaults to 500
+            title (str, optional): Title drawn above the chart
+
+        Returns:
+            bytes: The PNG image
+        """
+        _require_native(self.client, "chart_range", "charts")
+        return await self.client.chart_range(
+            asset,
+            int(Timeframe.parse(period)),
+            _utc(start),
+            _utc(end),
+            _chart_markers(markers),
+            _chart_options(width, height, title),
+        )
+
+def _with_result(trade: dict) -> dict:
+    "Adds the \"result\" of a closed trade from its profit"
+    win = trade["profit"]
+    if win > 0:
+        trade["result"] = "win"
+    elif win == 0:
+        trade["result"] = "draw"
+    else:
+        trade["result"] = "loss"
+    return trade
 
 async def _timeout(future, timeout: int):
     if sys.version_info[:3] >= (3,11): 
@@ -504,3 +2776,14 @@ async def _timeout(future, timeout: int):
             return await future
     else:
         return await asyncio.wait_for(future, timeout)
+
+def _candles_to_json(candles: list[dict | Candle]) -> str:
+    def normalize(candle: dict | Candle) -> dict:
+        candle = candle.to_dict() if isinstance(candle, Candle) else dict(candle)
+        if isinstance(candle.get("time"), datetime):
+            time = candle["time"]
+            candle["time"] = (time if time.tzinfo else time.replace(tzinfo=timezone.utc)).isoformat()
+        elif isinstance(candle.get("time"), (int, float)):
+            candle["time"] = datetime.fromtimestamp(candle["time"], timezone.utc).isoformat()
+        return candle
+    return json.dumps([normalize(candle) for candle in candles])
@@ -0,0 +1,172 @@
+"""
+A/B comparison of the execution of the same signals on two accounts with different settings (e.g. a different expiry
+or stake plan), to measure which settings perform better on identical entries.
+
+Every signal is mirrored to both accounts at the same time, their trades are recorded in the journal of each client
+tagged with the experiment, their arm and the signal, and `report` compares the results of both arms signal by signal.
+
+Example:
+    ```python
+    pool = ClientPool({"alice": ssid_alice, "bob": ssid_bob})
+    experiment = await ABExperiment.from_pool(
+        pool, "expiry", "alice", "bob",
+        a=Arm("60s", expiry=60),
+        b=Arm("120s", expiry=120, stake=lambda amount: amount / 2),
+    )
+
+    await experiment.signal("EURUSD_otc", "call", 1.0, 60)
+    ...
+    report = experiment.report()
+    print(report["paired"]["mean_difference"], report["paired"]["standard_error"])
+    ```
+"""
+from __future__ import annotations
+from .asyncronous import PocketOptionAsync
+from .orders import Direction
+from .pool import ClientPool
+from BinaryOptionsToolsV2 import compare_experiment
+from BinaryOptionsToolsV2.tracing import Logger
+from typing import Callable, Optional, Union
+
+import asyncio
+import json
+import uuid
+
+StakePlan = Union[float, Callable[[float], float]]
+
+
+class Arm:
+    """Settings of one side of the experiment, the settings left to `None` are the ones of the signal"""
+
+    def __init__(self, name: str, expiry: Optional[int] = None, stake: Optional[StakePlan] = None):
+        """
+        Args:
+            name (str): Name of the arm in the journal tags and the report
+            expiry (int, optional): Expiry in seconds of the trades of the arm, instead of the one of the signal
+            stake (float | Callable, optional): Fixed amount of the trades of the arm, or function returning it from
+                the amount of the signal
+        """
+        self.name = name
+        self.expiry = expiry
+        self.stake = stake
+
+    def amount(self, amount: float) -> float:
+        if self.stake is None:
+            return amount
+        if callable(self.stake):
+            return self.stake(amount)
+        return self.stake
+
+    def __repr__(self) -> str:
+        return f"Arm(name={self.name!r}, expiry={self.expiry!r}, stake={self.stake!r})"
+
+
+class ABExperiment:
+    """
+    Mirrors signals to two clients, each one trading with the settings of its arm.
+    """
+
+    def __init__(
+        self,
+        name: str,
+        a_client: PocketOptionAsync,
+        b_client: PocketOptionAsync,
+        a: Arm | None = None,
+        b: Arm | None = None,
+    ):
+        """
+        Args:
+            name (str): Name of the experiment, the trades of other experiments are left out of the report
+            a_client (PocketOptionAsync): Client of the account of the arm `a`
+            b_client (PocketOptionAsync): Client of the account of the arm `b`
+            a (Arm, optional): Settings of the first arm. Defaults to an arm named "a" trading the signals as they are
+            b (Arm, optional): Settings of the second arm. Defaults to an arm named "b" trading the signals as they are
+        """
+        self.name = name
+        self.a = a or Arm("a")
+        self.b = b or Arm("b")
+        if self.a.name == self.b.name:
+            raise ValueError(f"Both arms of the experiment are named '{self.a.name}'")
+        self._clients = {self.a.name: a_client, self.b.name: b_client}
+        self.logger = Logger()
+
+    @classmethod
+    async def from_pool(
+        cls,
+        pool: ClientPool,
+        name: str,
+        account_a: str,
+        account_b: str,
+        a: Arm | None = None,
+        b: Arm | None = None,
+    ) -> "ABExperiment":
+        """Creates an experiment between two accounts of a `ClientPool`, connecting them if needed"""
+        a_client, b_client = await asyncio.gather(pool.get(account_a), pool.get(account_b))
+        return cls(name, a_client, b_client, a, b)
+
+    def tags(self, arm: Arm, signal_id: str) -> list[str]:
+        "Returns the journal tags of the trade placed by `arm` for the signal `signal_id`"
+        return [f"experiment:{self.name}", f"arm:{arm.name}", f"signal:{signal_id}"]
+
+    async def _place(self, arm: Arm, signal_id: str, asset: str, direction: Direction, amount: float, expiry: int):
+        client = self._clients[arm.name]
+        return await client.place_order(
+            asset, direction, arm.amount(amount), arm.expiry or expiry, tags=self.tags(arm, signal_id)
+        )
+
+    async def signal(
+        self,
+        asset: str,
+        direction: Direction | str,
+        amount: float,
+        expiry: int,
+        signal_id: str | None = None,
+    ) -> dict:
+        """
+        Places the signal on both accounts at the same time, with the expiry and stake of each arm.
+
+        A trade rejected on one account (e.g. by its risk rules) doesn't cancel the trade of the other one, the
+        signal is then reported in the `unmatched_signals` of the report.
+
+        Args:
+            asset (str): Trading asset
+            direction (Direction | str): Direction of the trade
+            amount (float): Amount of the signal, the arms without a stake plan trade it as is
+            expiry (int): Expiry of the signal in seconds, the arms without an expiry trade it as is
+            signal_id (str, optional): Id pairing the trades of both arms. Defaults to a random id
+
+        Returns:
+            dict: The `signal_id` and, for each arm name, a dict with the `trade_id` and `deal` of its trade or
+            the `error` that prevented it
+        """
+        direction = Direction.parse(direction)
+        signal_id = signal_id or str(uuid.uuid4())
+        arms = (self.a, self.b)
+        results = await asyncio.gather(
+            *(self._place(arm, signal_id, asset, direction, amount, expiry) for arm in arms), return_exceptions=True
+        )
+        placed = {"signal_id": signal_id}
+        for arm, result in zip(arms, results):
+            if isinstance(result, BaseException):
+                self.logger.warn(f"Experiment '{self.name}': arm '{arm.name}' failed to place signal '{signal_id}', {result}")
+                placed[arm.name] = {"error": str(result)}
+            else:
+                trade_id, deal = result
+                placed[arm.name] = {"trade_id": trade_id, "deal": deal}
+        return placed
+
+    def report(self) -> dict:
+        """
+        Compares the results of both arms from the journals of their clients:
+            - experiment: Name of the experiment
+            - a, b: For each arm, its `arm` name, the journal statistics of its closed trades (`stats`), the number
+              of `signals` it traded and of `open_trades`
+            - paired: Comparison of the signals both arms have a closed trade for: the number of `signals`, where
+              `a_better`, `b_better` or `ties`, the `profit_difference` (a minus b), its `mean_difference` per signal
+              and the `standard_error` of the mean (None with less than 2 signals)
+            - unmatched_signals: Signals only one of the arms traded
+        """
+        journals = {name: json.dumps(client.journal()) for name, client in self._clients.items()}
+        return json.loads(
+            compare_experiment(self.name, self.a.name, journals[self.a.name], self.b.name, journals[self.b.name])
+        )
@@ -0,0 +1,389 @@
+"""
+Shared connection between processes.
+
+One process owns the broker connection and serves it with `IpcServer` on a local Unix domain socket
+(or a localhost TCP port on platforms without Unix sockets), the other processes (e.g. a pool of workers)
+use `PocketOptionIpcAsync` / `PocketOptionIpc`, which expose the same API as `PocketOptionAsync` /
+`PocketOption`, so only one session is opened per account.
+
+Example:
+    ```python
+    # Owner process
+    client = PocketOptionAsync(ssid)
+    server = IpcServer(client, "/tmp/pocket.sock")
+    await server.serve_forever()
+
+    # Worker processes
+    client = PocketOptionIpcAsync("/tmp/pocket.sock")
+    trade_id, deal = await client.buy("EURUSD_otc", 1.0, 60)
+    async for candle in await client.subscribe_symbol("EURUSD_otc"):
+        print(candle)
+    ```
+
+To share the connection beyond trusted processes (a dashboard, a teammate) give the server an `AccessControl`, every
+request must then carry an API key whose role allows the method: `read_only` keys can only read the account and the
+market data, `trade_limited` keys can also trade within their limits and `admin` keys can call every method. The
+checks run in Rust, a denied request raises `IpcError` with the `PermissionDeniedError` kind in the caller.
+
+Example:
+    ```python
+    access = AccessControl()
+    access.add_key("dashboard-key", "read_only")
+    access.add_key("teammate-key", "trade_limited", max_amount=5.0, max_trades=10)
+    server = IpcServer(client, "127.0.0.1:7000", access=access)
+
+    client = PocketOptionIpcAsync("127.0.0.1:7000", api_key="teammate-key")
+    ```
+"""
+from __future__ import annotations
+from .asyncronous import PocketOptionAsync
+from BinaryOptionsToolsV2.tracing import Logger
+from BinaryOptionsToolsV2.validator import Validator
+from BinaryOptionsToolsV2 import AccessControl
+from datetime import timedelta
+from typing import Any, Optional
+
+import asyncio
+import inspect
+import itertools
+import json
+import os
+import socket
+
+DEFAULT_SOCKET_PATH = "/tmp/binary-options-tools.sock"
+
+# Methods returning iterators, their items are streamed to the caller
+_STREAMING_METHODS = {
+    "subscribe_symbol",
+    "subscribe_symbol_chuncked",
+    "subscribe_symbol_timed",
+    "subscribe_symbols",
+    "subscribe_trade_results",
+    "create_raw_iterator",
+    "register_raw_iterator",
+}
+
+
+def _encode(value: Any) -> Any:
+    if isinstance(value, timedelta):
+        return {"__timedelta__": value.total_seconds()}
+    if isinstance(value, Validator):
+        return {"__validator__": value.to_json()}
+    if isinstance(value, (list, tuple)):
+        return [_encode(v) for v in value]
+    if isinstance(value, dict):
+        return {k: _encode(v) for k, v in value.items()}
+    return value
+
+
+def _json_default(value: Any) -> Any:
+    "Serializes the results json doesn't know, the `Candle` and `Deal` objects are sent as their dicts"
+    to_dict = getattr(value, "to_dict", None)
+    if callable(to_dict):
+        return to_dict()
+    raise TypeError(f"Object of type {type(value).__name__} is not JSON serializable")
+
+
+def _dumps(message: dict) -> bytes:
+    return json.dumps(message, default=_json_default).encode() + b"\n"
+
+
+def _decode(value: Any) -> Any:
+    if isinstance(value, dict):
+        if "__timedelta__" in value:
+            return timedelta(seconds=value["__timedelta__"])
+        if "__validator__" in value:
+            return Validator.from_json(value["__validator__"])
+        return {k: _decode(v) for k, v in value.items()}
+    if isinstance(value, list):
+        return [_decode(v) for v in value]
+    return value
+
+
+def _order_amount(function, args: list, kwargs: dict) -> Optional[float]:
+    "Amount of the order placed by a call, checked against the limits of the `trade_limited` API keys"
+    try:
+        amount = inspect.signature(function).bind_partial(*args, **kwargs).arguments.get("amount")
+    except (TypeError, ValueError):
+        return None
+    return float(amount) if isinstance(amount, (int, float)) else None
+
+
+def _is_tcp(address: str) -> bool:
+    return not hasattr(socket, "AF_UNIX") or (":" in address and os.path.sep not in address)
+
+
+def _split_tcp(address: str) -> tuple[str, int]:
+    host, _, port = address.rpartition(":")
+    return host or "127.0.0.1", int(port)
+
+
+class IpcError(Exception):
+    """Error raised in the process owning the connection, forwarded to the caller."""
+
+    def __init__(self, kind: str, message: str):
+        super().__init__(f"{kind}: {message}")
+        self.kind = kind
+
+
+class IpcServer:
+    """
+    Serves a `PocketOptionAsync` client to other processes.
+
+    Every request is a JSON line `{"id": int, "method": str, "args": list, "kwargs": dict}`, answered with
+    `{"id": int, "result": ...}` or `{"id": int, "error": str, "kind": str}`. For iterators every item is sent
+    as `{"id": int, "item": ...}` followed by `{"id": int, "done": true}`, the caller can stop an iterator
+    by sending `{"id": int, "cancel": true}`. The `Candle` and `Deal` objects of the results are sent as their
+    `to_dict()` dicts. With an `AccessControl` the requests also carry the API key of the caller
+    in a `"key"` field.
+    """
+
+    def __init__(self, client: PocketOptionAsync, address: str = DEFAULT_SOCKET_PATH, access: Optional[AccessControl] = None):
+        """
+        Args:
+            client (PocketOptionAsync): Client to serve
+            address (str): Unix socket path, or `host:port` for TCP
+            access (AccessControl, optional): API keys allowed to call the client and their roles. Defaults to None
+                (every caller has full access).
+        """
+        self.client = client
+        self.address = address
+        self.access = access
+        self.logger = Logger()
+        self._server: Optional[asyncio.AbstractServer] = None
+
+    async def start(self) -> None:
+        if _is_tcp(self.address):
+            host, port = _split_tcp(self.address)
+            self._server = await asyncio.start_server(self._handle, host, port)
+        else:
+            if os.path.exists(self.address):
+                os.remove(self.address)
+            self._server = await asyncio.start_unix_server(self._handle, self.address)
+        self.logger.info(f"IPC server listening on {self.address}")
+
+    async def serve_forever(self) -> None:
+        if self._server is None:
+            await self.start()
+        async with self._server:
+            await self._server.serve_forever()
+
+    async def close(self) -> None:
+        if self._server is not None:
+            self._server.close()
+            await self._server.wait_closed()
+            self._server = None
+        if not _is_tcp(self.address) and os.path.exists(self.address):
+            os.remove(self.address)
+
+    async def _handle(self, reader: asyncio.StreamReader, writer: asyncio.StreamWriter):
+        lock = asyncio.Lock()
+        streams: dict[int, asyncio.Task] = {}
+
+        async def send(message: dict):
+            async with lock:
+                writer.write(_dumps(message))
+                await writer.drain()
+
+        try:
+            while line := await reader.readline():
+                request = json.loads(line)
+                request_id = request.get("id")
+                if request.get("cancel"):
+                    task = streams.pop(request_id, None)
+                    if task is not None:
+                        task.cancel()
+                    continue
+                task = asyncio.create_task(self._call(request, send))
+                if request.get("method") in _STREAMING_METHODS:
+                    streams[request_id] = task
+        except (ConnectionError, json.JSONDecodeError) as e:
+            self.logger.warn(f"IPC connection closed, {e}")
+        finally:
+            for task in streams.values():
+                task.cancel()
+            writer.close()
+
+    async def _call(self, request: dict, send):
+        request_id = request.get("id")
+        method = request.get("method", "")
+        try:
+            if method.startswith("_") or not hasattr(self.client, method):
+                raise AttributeError(f"unknown method '{method}'")
+            function = getattr(self.client, method)
+            args, kwargs = _decode(request.get("args", [])), _decode(request.get("kwargs", {}))
+            if self.access is not None:
+                self.access.authorize(request.get("key"), method, _order_amount(function, args, kwargs))
+            result = function(*args, **kwargs)
+            if asyncio.iscoroutine(result):
+                result = await result
+            if method in _STREAMING_METHODS:
+                async for item in result:
+                    await send({"id": request_id, "item": item})
+                await send({"id": request_id, "done": True})
+            else:
+                await send({"id": request_id, "result": result})
+        except asyncio.CancelledError:
+            pass
+        except Exception as e:
+            await send({"id": request_id, "error": str(e), "kind": type(e).__name__})
+
+
+class IpcSubscription:
+    """Async iterator over the items of an iterator living in the process owning the connection."""
+
+    def __init__(self, client: "PocketOptionIpcAsync", request_id: int, queue: asyncio.Queue):
+        self._client = client
+        self._id = request_id
+        self._queue = queue
+
+    def __aiter__(self):
+        return self
+
+    async def __anext__(self):
+        message = await self._queue.get()
+        if "error" in message:
+            raise IpcError(message["kind"], message["error"])
+        if message.get("done"):
+            raise StopAsyncIteration
+        return message["item"]
+
+    async def close(self):
+        """Stops the iterator in the process owning the connection"""
+        await self._client._send({"id": self._id, "cancel": True})
+        self._client._pending.pop(self._id, None)
+
+
+class PocketOptionIpcAsync:
+    """
+    Async client with the same API as `PocketOptionAsync`, forwarding every call to the process
+    owning the connection through `IpcServer`.
+    """
+
+    def __init__(self, address: str = DEFAULT_SOCKET_PATH, api_key: Optional[str] = None):
+        """
+        Args:
+            address (str): Address of the `IpcServer`, Unix socket path or `host:port` for TCP
+            api_key (str, optional): API key sent with every request, required if the server has an `AccessControl`
+        """
+        self.address = address
+        self.api_key = api_key
+        self._ids = itertools.count()
+        self._pending: dict[int, asyncio.Queue] = {}
+        self._reader: Optional[asyncio.StreamReader] = None
+        self._writer: Optional[asyncio.StreamWriter] = None
+        self._listener: Optional[asyncio.Task] = None
+        self._lock = asyncio.Lock()
+
+    async def connect(self) -> None:
+        if _is_tcp(self.address):
+            self._reader, self._writer = await asyncio.open_connection(*_split_tcp(self.address))
+        else:
+            self._reader, self._writer = await asyncio.open_unix_connection(self.address)
+        self._listener = asyncio.create_task(self._listen())
+
+    async def close(self) -> None:
+        if self._listener is not None:
+            self._listener.cancel()
+        if self._writer is not None:
+            self._writer.close()
+        self._reader, self._writer, self._listener = None, None, None
+
+    async def _listen(self):
+        while line := await self._reader.readline():
+            message = json.loads(line)
+            queue = self._pending.get(message.get("id"))
+            if queue is not None:
+                queue.put_nowait(message)
+        for queue in self._pending.values():
+            queue.put_nowait({"error": "IPC connection closed", "kind": "ConnectionError"})
+
+    async def _send(self, message: dict):
+        async with self._lock:
+            if self._writer is None:
+                await self.connect()
+            self._writer.write(_dumps(message))
+            await self._writer.drain()
+
+    async def call(self, method: str, *args, **kwargs) -> Any:
+        """Calls a method of the `PocketOptionAsync` client owned by the server process"""
+        request_id = next(self._ids)
+        queue = asyncio.Queue()
+        self._pending[request_id] = queue
+        request = {"id": request_id, "method": method, "args": _encode(list(args)), "kwargs": _encode(kwargs)}
+        if self.api_key is not None:
+            request["key"] = self.api_key
+        await self._send(request)
+        if method in _STREAMING_METHODS:
+            return IpcSubscription(self, request_id, queue)
+        try:
+            message = await queue.get()
+        finally:
+            self._pending.pop(request_id, None)
+        if "error" in message:
+            raise IpcError(message["kind"], message["error"])
+        result = message["result"]
+        # JSON has no tuples, keep the same return types as `PocketOptionAsync`
+        return tuple(result) if method in ("buy", "sell", "hedge") else result
+
+    def __getattr__(self, method: str):
+        if method.startswith("_"):
+            raise AttributeError(method)
+
+        async def proxy(*args, **kwargs):
+            return await self.call(method, *args, **kwargs)
+
+        proxy.__name__ = method
+        return proxy
+
+
+class PocketOptionIpc:
+    """Sync client with the same API as `PocketOption`, forwarding every call to the process owning the connection."""
+
+    def __init__(self, address: str = DEFAULT_SOCKET_PATH, api_key: Optional[str] = None):
+        self.loop = asyncio.new_event_loop()
+        self._client = PocketOptionIpcAsync(address, api_key)
+
+    def __del__(self):
+        self.loop.run_until_complete(self._client.close())
+        self.loop.close()
+
+    def __getattr__(self, method: str):
+        if method.startswith("_"):
+            raise AttributeError(method)
+
+        def proxy(*args, **kwargs):
+            result = self.loop.run_until_complete(self._client.call(method, *args, **kwargs))
+            if isinstance(result, IpcSubscription):
+                return _SyncIpcSubscription(self.loop, result)
+            return result
+
+        proxy.__name__ = method
+        return proxy
+
+
+class _SyncIpcSubscription:
+    def __init__(self, loop: asyncio.AbstractEventLoop, subscription: IpcSubscription):
+        self.loop = loop
+        self.subscription = subscription
+
+    def __iter__(self):
+        return self
+
+    def __next__(self):
+        try:
+            return self.loop.run_until_complete(self.subscription.__anext__())
+        except StopAsyncIteration:
+            raise StopIteration
+
+    def close(self):
+        self.loop.run_until_complete(self.subscription.close())
+
+
+async def serve_ipc(client: PocketOptionAsync, address: str = DEFAULT_SOCKET_PATH, access: Optional[AccessControl] = None) -> None:
+    """Shares the connection of `client` with other processes until cancelled, see `IpcServer` for `access`"""
+    server = IpcServer(client, address, access)
+    try:
+        await server.serve_forever()
+    finally:
+        await server.close()
@@ -0,0 +1,28 @@
+"""
+Direction of the trades placed with `place_order`.
+"""
+from __future__ import annotations
+
+from enum import Enum
+
+
+class Direction(str, Enum):
+    CALL = "call"
+    PUT = "put"
+
+    @classmethod
+    def parse(cls, direction: Direction | str) -> Direction:
+        """
+        Returns the direction of `direction`: a `Direction`, "call" / "buy" or "put" / "sell" (case insensitive).
+
+        Raises:
+            ValueError: If the direction is invalid
+        """
+        if isinstance(direction, cls):
+            return direction
+        value = str(direction).strip().lower()
+        if value in ("call", "buy"):
+            return cls.CALL
+        if value in ("put", "sell"):
+            return cls.PUT
+        raise ValueError(f"Invalid direction {direction!r}, expected 'call', 'buy', 'put' or 'sell'")
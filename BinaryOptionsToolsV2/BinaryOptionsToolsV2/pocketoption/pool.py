@@ -0,0 +1,265 @@
+"""
+Connection pool for services handling many accounts (e.g. signal distribution services).
+
+`ClientPool` connects to an account the first time it's requested, caps the number of open connections
+(closing the least recently used idle connection when the cap is reached), closes connections that
+have been idle for too long and keeps track of the health of every account.
+
+Example:
+    ```python
+    pool = ClientPool({"alice": ssid_alice, "bob": ssid_bob}, max_connections=100)
+
+    async with pool.lease("alice") as client:
+        await client.buy("EURUSD_otc", 1.0, 60)
+
+    client = await pool.get("bob")  # Without lease, the connection may be recycled once it's idle
+    print(pool.health())
+    await pool.close_all()
+    ```
+"""
+from __future__ import annotations
+from .asyncronous import PocketOptionAsync
+from BinaryOptionsToolsV2.config import Config
+from BinaryOptionsToolsV2.tracing import Logger
+from contextlib import asynccontextmanager
+from datetime import timedelta
+from typing import Awaitable, Callable, Optional, Union
+
+import asyncio
+import inspect
+import time
+
+SsidProvider = Callable[[str], Union[str, Awaitable[str]]]
+
+
+class PoolExhaustedError(Exception):
+    """Raised when every connection of the pool is leased and no connection became free in time."""
+
+
+class AccountUnavailableError(Exception):
+    """Raised when an account failed to connect too many times in a row and is cooling down."""
+
+
+class _Entry:
+    def __init__(self):
+        self.client: Optional[PocketOptionAsync] = None
+        self.leases = 0
+        self.last_used = 0.0
+        self.connections = 0
+        self.failures = 0
+        self.consecutive_failures = 0
+        self.last_error: Optional[str] = None
+        self.cooldown_until = 0.0
+        self.connecting = False
+        self.lock = asyncio.Lock()
+
+
+class ClientPool:
+    """
+    Lazily connected pool of `PocketOptionAsync` clients indexed by account id.
+    """
+
+    def __init__(
+        self,
+        accounts: dict[str, str] | SsidProvider,
+        max_connections: int = 50,
+        idle_timeout: timedelta | None = timedelta(minutes=10),
+        acquire_timeout: timedelta = timedelta(seconds=30),
+        max_failures: int = 3,
+        failure_cooldown: timedelta = timedelta(minutes=1),
+        config: Config | dict | str | None = None,
+        url: str | None = None,
+    ):
+        """
+        Args:
+            accounts (dict[str, str] | Callable): Mapping of account id to ssid, or function (sync or async)
+                returning the ssid of an account id
+            max_connections (int): Maximum number of open connections. Defaults to 50
+            idle_timeout (timedelta | None): Connections not used for this long are closed. Defaults to 10 minutes,
+                `None` keeps them open until the cap is reached
+            acquire_timeout (timedelta): Maximum time to wait for a free connection slot when every connection
+                is leased. Defaults to 30 seconds
+            max_failures (int): Consecutive connection failures before an account starts cooling down. Defaults to 3
+            failure_cooldown (timedelta): Time during which an account isn't retried after `max_failures`
+            config (Config | dict | str, optional): Config used by every client
+            url (str, optional): Custom websocket url used by every client
+        """
+        self._accounts = accounts
+        self.max_connections = max_connections
+        self.idle_timeout = idle_timeout
+        self.acquire_timeout = acquire_timeout
+        self.max_failures = max_failures
+        self.failure_cooldown = failure_cooldown
+        self.config = config
+        self.url = url
+        self.logger = Logger()
+        self._entries: dict[str, _Entry] = {}
+        self._slots = asyncio.Condition()
+        self._reaper: Optional[asyncio.Task] = None
+
+    def _entry(self, account_id: str) -> _Entry:
+        if account_id not in self._entries:
+            self._entries[account_id] = _Entry()
+        return self._entries[account_id]
+
+    def _connected(self) -> int:
+        return sum(1 for e in self._entries.values() if e.client is not None or e.connecting)
+
+    async def _ssid(self, account_id: str) -> str:
+        if callable(self._accounts):
+            ssid = self._accounts(account_id)
+            return await ssid if inspect.isawaitable(ssid) else ssid
+        if account_id not in self._accounts:
+            raise KeyError(f"unknown account '{account_id}'")
+        return self._accounts[account_id]
+
+    def _config(self):
+        if isinstance(self.config, Config):
+            # A config is locked once it's used by a client, every client needs its own copy
+            return Config.from_dict(self.config.to_dict())
+        return self.config
+
+    async def _evict_idle(self) -> bool:
+        idle = [(e.last_used, account_id) for account_id, e in self._entries.items() if e.client is not None and e.leases == 0]
+        if not idle:
+            return False
+        _, account_id = min(idle)
+        self.logger.debug(f"Connection pool is full, closing least recently used connection of '{account_id}'")
+        await self._shutdown(self._entries[account_id])
+        return True
+
+    async def _shutdown(self, entry: _Entry):
+        client, entry.client = entry.client, None
+        if client is not None:
+            await client.shutdown()
+
+    async def _reserve_slot(self, entry: _Entry):
+        async with self._slots:
+            deadline = time.monotonic() + self.acquire_timeout.total_seconds()
+            while self._connected() >= self.max_connections and not await self._evict_idle():
+                remaining = deadline - time.monotonic()
+                if remaining <= 0:
+                    raise PoolExhaustedError(f"all the {self.max_connections} connections of the pool are leased")
+                try:
+                    await asyncio.wait_for(self._slots.wait(), remaining)
+                except asyncio.TimeoutError:
+                    pass
+            entry.connecting = True
+
+    async def get(self, account_id: str) -> PocketOptionAsync:
+        """
+        Returns the client of an account, connecting it if needed.
+        The connection may be recycled once it's idle, use `lease` to keep it open while it's in use.
+        """
+        self._start_reaper()
+        entry = self._entry(account_id)
+        async with entry.lock:
+            if entry.client is None:
+                if entry.cooldown_until > time.monotonic():
+                    raise AccountUnavailableError(
+                        f"account '{account_id}' failed to connect {entry.consecutive_failures} times, last error: {entry.last_error}"
+                    )
+                await self._reserve_slot(entry)
+                try:
+                    ssid = await self._ssid(account_id)
+                    # The client connects in its constructor, so it's created outside of the event loop
+                    entry.client = await asyncio.get_running_loop().run_in_executor(
+                        None, PocketOptionAsync, ssid, self.url, self._config()
+                    )
+                except Exception as e:
+                    entry.failures += 1
+                    entry.consecutive_failures += 1
+                    entry.last_error = str(e)
+                    if entry.consecutive_failures >= self.max_failures:
+                        entry.cooldown_until = time.monotonic() + self.failure_cooldown.total_seconds()
+                    self.logger.warn(f"Failed to connect account '{account_id}', {e}")
+                    await self._notify()
+                    raise
+                finally:
+                    entry.connecting = False
+                entry.connections += 1
+                entry.consecutive_failures = 0
+                self.logger.debug(f"Connected account '{account_id}' ({self._connected()}/{self.max_connections} connections)")
+            entry.last_used = time.monotonic()
+            return entry.client
+
+    @asynccontextmanager
+    async def lease(self, account_id: str):
+        """Async context manager returning the client of an account, which won't be recycled while it's leased"""
+        client = await self.get(account_id)
+        entry = self._entry(account_id)
+        entry.leases += 1
+        try:
+            yield client
+        finally:
+            entry.leases -= 1
+            entry.last_used = time.monotonic()
+            await self._notify()
+
+    async def _notify(self):
+        async with self._slots:
+            self._slots.notify_all()
+
+    async def close(self, account_id: str) -> None:
+        """Closes the connection of an account, it will be reconnected the next time it's requested"""
+        entry = self._entries.get(account_id)
+        if entry is not None and entry.client is not None:
+            await self._shutdown(entry)
+            await self._notify()
+
+    async def close_all(self) -> None:
+        """Closes every connection and stops recycling idle connections"""
+        if self._reaper is not None:
+            self._reaper.cancel()
+            self._reaper = None
+        await asyncio.gather(*(self._shutdown(entry) for entry in list(self._entries.values())))
+        await self._notify()
+
+    def health(self) -> dict[str, dict]:
+        """
+        Returns the health of every account known by the pool:
+            - connected: Whether the account currently has an open connection
+            - leases: Number of active leases
+            - idle_for: Seconds since the connection was last used
+            - connections: Number of times the account was connected
+            - failures: Total number of failed connections
+            - consecutive_failures: Failed connections since the last successful one
+            - last_error: Last connection error
+            - cooling_down: Whether the account is waiting before being retried
+        """
+        now = time.monotonic()
+        return {
+            account_id: {
+                "connected": e.client is not None,
+                "leases": e.leases,
+                "idle_for": now - e.last_used if e.last_used else None,
+                "connections": e.connections,
+                "failures": e.failures,
+                "consecutive_failures": e.consecutive_failures,
+                "last_error": e.last_error,
+                "cooling_down": e.cooldown_until > now,
+            }
+            for account_id, e in self._entries.items()
+        }
+
+    def _start_reaper(self):
+        if self.idle_timeout is None or (self._reaper is not None and not self._reaper.done()):
+            return
+        self._reaper = asyncio.create_task(self._reap())
+
+    async def _reap(self):
+        timeout = self.idle_timeout.total_seconds()
+        while True:
+            await asyncio.sleep(max(timeout / 4, 1))
+            now = time.monotonic()
+            for account_id, entry in list(self._entries.items()):
+                if entry.client is not None and entry.leases == 0 and now - entry.last_used > timeout:
+                    self.logger.debug(f"Closing idle connection of '{account_id}'")
+                    await self._shutdown(entry)
+            await self._notify()
+
+    async def __aenter__(self):
+        return self
+
+    async def __aexit__(self, *_):
+        await self.close_all()
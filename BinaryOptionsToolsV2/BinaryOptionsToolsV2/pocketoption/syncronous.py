@@ -1,10 +1,20 @@
-from .asyncronous import PocketOptionAsync
+from __future__ import annotations
+
+from .asyncronous import PocketOptionAsync, _candle_predicate, _item, _levels_config, _pattern_config, _utc
+from .templates import OrderTemplate
+from .timeframe import Timeframe
+from .orders import Direction
 from BinaryOptionsToolsV2.config import Config
+from BinaryOptionsToolsV2.ssid import SsidInfo
+from BinaryOptionsToolsV2.tracing import StreamLogsLayer
 from BinaryOptionsToolsV2.validator import Validator
-from datetime import timedelta
+from BinaryOptionsToolsV2 import AccessControl, Candle, OrderDirection, Deal, Asset
+from datetime import datetime, timedelta
+from typing import Callable
 
 import asyncio
 import json
+import threading
 
 
 class SyncSubscription:
@@ -15,11 +25,71 @@ class SyncSubscription:
         return self
         
     def __next__(self):
-        return json.loads(next(self.subscription))        
+        return _item(next(self.subscription))
+
+    def close(self) -> None:
+        "Unsubscribes from the asset and ends the subscription immediately, see `AsyncSubscription.close`"
+        if hasattr(self.subscription, "close"):
+            self.subscription.close()
+
+    def __enter__(self) -> SyncSubscription:
+        return self
+
+    def __exit__(self, exc_type, exc_value, traceback) -> bool:
+        self.close()
+        return False
+
+    def recent(self, n: int = 10) -> list[dict | Candle]:
+        """Returns the last `n` items delivered by the subscription (oldest first), useful to inspect what a strategy just saw"""
+        return [_item(item) for item in self.subscription.recent(n)]
+
+    def filter(self, predicate: Validator | Callable[[dict], bool]) -> SyncSubscription:
+        "Returns a subscription over the candles accepted by `predicate`, see `AsyncSubscription.filter`"
+        return SyncSubscription(self.subscription.filter(_candle_predicate(predicate)))
+
+    def map_json(self, path: str) -> SyncSubscription:
+        "Returns a subscription over the value at `path` (e.g. `close`) in each candle, see `AsyncSubscription.map_json`"
+        return SyncSubscription(self.subscription.map_json(path))
+
+    def take(self, n: int) -> SyncSubscription:
+        "Returns a subscription ending after `n` candles"
+        return SyncSubscription(self.subscription.take(n))
+
+    def record(
+        self, path: str, compression: str | None = None, level: int | None = None, chunk_size: int | None = None
+    ) -> SyncSubscription:
+        "Returns a subscription appending every candle it yields to the recording at `path`, see `AsyncSubscription.record`"
+        return SyncSubscription(self.subscription.record(path, compression, level, chunk_size))
+
+    def until(self, timestamp: datetime | float) -> SyncSubscription:
+        "Returns a subscription ending at the first candle at or after `timestamp` (unix timestamp or datetime, naive in UTC)"
+        return SyncSubscription(self.subscription.until(_utc(timestamp)))
+
+    def patterns(
+        self, patterns: list[str] | None = None, min_confidence: float = 0.0, **thresholds: float
+    ) -> SyncSubscription:
+        "Returns a subscription over the candlestick patterns completed by the candles, see `AsyncSubscription.patterns`"
+        return SyncSubscription(self.subscription.patterns(_pattern_config(patterns, min_confidence, **thresholds)))
+
+    def levels(self, lookback: int = 100, **config) -> SyncSubscription:
+        "Returns a subscription yielding every candle with the levels of the last `lookback` candles, see `AsyncSubscription.levels`"
+        return SyncSubscription(self.subscription.levels(lookback, _levels_config(**config)))
+
+    def set_timeout(self, timeout: timedelta | None = None):
+        "Changes the timeout of a raw iterator (`None` disables it) and restarts its deadline, other iterators aren't affected"
+        self.subscription.set_timeout(timeout)
+
+    def reset_timeout(self):
+        "Restarts the deadline of a raw iterator, it has a full timeout to receive the next message"
+        self.subscription.reset_timeout()
+
+    def extend(self, timeout: timedelta):
+        "Gives a raw iterator `timeout` more to receive the next message, without changing its timeout"
+        self.subscription.extend(timeout)
     
 
 class PocketOption:
-    def __init__(self, ssid: str, config: Config | dict | str = None, **_):
+    def __init__(self, ssid: str, config: Config | dict | str = None, observer: bool = False, otc_fallback: bool = False, **_):
         """
         Initializes a new PocketOption instance.
 
@@ -41,8 +111,20 @@ class PocketOption:
                     - connection_initialization_timeout_secs (int): Connection initialization timeout
                     - timeout_secs (int): General operation timeout
                     - urls (List[str]): List of fallback WebSocket URLs
+                    - tcp_nodelay, read_buffer_size, write_buffer_size, dedicated_reader, reduced_logging: Socket
+                      and reader options, `Config.low_latency()` sets them for the short expiries
+                    - check_market_hours (bool): Rejects the orders on closed assets with `AssetClosedError`
+            observer (bool, optional): Read-only observer mode, every method sending an order raises
+                `ObserverModeError`. Defaults to False.
+            otc_fallback (bool, optional): Trades and subscribes to the OTC version of the regular assets (e.g.
+                "EURUSD_otc" for "EURUSD") while their market is closed. Defaults to False.
             **_: Additional keyword arguments (ignored)
 
+        Raises:
+            AuthenticationError: If the server rejects the session, telling whether it expired or is malformed
+            RegionMismatchError: If the server reports that the client connects from another IP or region than the one
+                of the session, with the steps to fix it
+
         Examples:
             Basic usage:
             ```python
@@ -98,75 +180,232 @@ class PocketOption:
             ```
 
         Note:
-            - Creates a new event loop for handling async operations synchronously
+            - Creates a new event loop per thread for handling async operations synchronously, so the client
+              can be shared between threads (including free-threaded Python builds)
             - The configuration becomes locked once initialized and cannot be modified afterwards
             - Custom URLs provided in the `url` parameter take precedence over URLs in the configuration
             - Invalid configuration values will raise appropriate exceptions
-            - The event loop is automatically closed when the instance is deleted
+            - The event loops are automatically closed when the instance is deleted
             - All async operations are wrapped to provide a synchronous interface
         """        
-        self.loop = asyncio.new_event_loop()
-        self._client = PocketOptionAsync(ssid, config)
-    
+        self._local = threading.local()
+        self._loops: list[asyncio.AbstractEventLoop] = []
+        self._loops_lock = threading.Lock()
+        self._client = PocketOptionAsync(ssid, config=config, observer=observer, otc_fallback=otc_fallback)
+
+    @property
+    def loop(self) -> asyncio.AbstractEventLoop:
+        """Event loop of the current thread, an event loop can only run one call at a time so each thread gets its own"""
+        loop = getattr(self._local, "loop", None)
+        if loop is None:
+            loop = asyncio.new_event_loop()
+            self._local.loop = loop
+            with self._loops_lock:
+                self._loops.append(loop)
+        return loop
+
     def __del__(self):
-        self.loop.close()
+        for loop in getattr(self, "_loops", []):
+            if not loop.is_running():
+                loop.close()
 
-    def buy(self, asset: str, amount: float, time: int, check_win: bool = False) -> tuple[str, dict]:
+    def buy(
+        self, asset: str, amount: float, time: int, check_win: bool = False, min_payout: int | None = None
+    ) -> tuple[str, dict]:
         """
         Takes the asset, and amount to place a buy trade that will expire in time (in seconds).
         If check_win is True then the function will return a tuple containing the trade id and a dictionary containing the trade data and the result of the trade ("win", "draw", "loss)
         If check_win is False then the function will return a tuple with the id of the trade and the trade as a dict
+        With min_payout the trade is refused with a `PayoutTooLowError` if the payout of the asset is lower
+        With `Config.check_market_hours` the trade is refused with an `AssetClosedError` if the market of the asset is closed
         """
-        return self.loop.run_until_complete(self._client.buy(asset, amount, time, check_win))
+        return self.loop.run_until_complete(self._client.buy(asset, amount, time, check_win, min_payout))
        
-    def sell(self, asset: str, amount: float, time: int, check_win: bool = False) -> tuple[str, dict]:
+    def sell(
+        self, asset: str, amount: float, time: int, check_win: bool = False, min_payout: int | None = None
+    ) -> tuple[str, dict]:
         """
         Takes the asset, and amount to place a sell trade that will expire in time (in seconds).
         If check_win is True then the function will return a tuple containing the trade id and a dictionary containing the trade data and the result of the trade ("win", "draw", "loss)
         If check_win is False then the function will return a tuple with the id of the trade and the trade as a dict
+        With min_payout the trade is refused with a `PayoutTooLowError` if the payout of the asset is lower
+        With `Config.check_market_hours` the trade is refused with an `AssetClosedError` if the market of the asset is closed
         """
-        return self.loop.run_until_complete(self._client.sell(asset, amount, time, check_win))
+        return self.loop.run_until_complete(self._client.sell(asset, amount, time, check_win, min_payout))
+
+    def trade(self, asset: str, direction: OrderDirection | Direction | str, amount: float, time: int) -> Deal:
+        "Places a trade in `direction` (`OrderDirection.CALL` / `OrderDirection.PUT` or \"call\" / \"put\") and returns the typed `Deal`, see `PocketOptionAsync.trade`"
+        return self.loop.run_until_complete(self._client.trade(asset, direction, amount, time))
+
+    def place_order(
+        self,
+        asset: str,
+        direction: Direction | str,
+        amount: float,
+        expiry: int,
+        *,
+        check_win: bool = False,
+        tags: list[str] | None = None,
+        dry_run: bool = False,
+        min_payout: int | None = None,
+    ) -> tuple[str | None, dict]:
+        "Places a trade in `direction` (\"call\" / \"put\"), the canonical entry point `buy` and `sell` are shortcuts of. `tags` are recorded in the journal, `dry_run` runs every check without placing the trade (the trade id is None) and `min_payout` rejects the trade if the payout of the asset is lower"
+        return self.loop.run_until_complete(
+            self._client.place_order(
+                asset, direction, amount, expiry, check_win=check_win, tags=tags, dry_run=dry_run, min_payout=min_payout
+            )
+        )
     
-    def check_win(self, id: str) -> dict:
-        """Returns a dictionary containing the trade data and the result of the trade ("win", "draw", "loss)"""
-        return self.loop.run_until_complete(self._client.check_win(id))
+    def check_win(self, id: str, timeout: timedelta | float | None = None) -> dict:
+        """Returns a dictionary containing the trade data and the result of the trade ("win", "draw", "loss), raises `TradeTimeoutError` if it isn't received within `timeout`"""
+        return self.loop.run_until_complete(self._client.check_win(id, timeout))
+
+    def check_win_many(
+        self, trade_ids: list[str], timeout: timedelta | float | None = None, return_exceptions: bool = False
+    ) -> dict[str, dict | Exception]:
+        "Waits for the results of several trades concurrently, returns them by trade id, see `PocketOptionAsync.check_win_many`"
+        return self.loop.run_until_complete(self._client.check_win_many(trade_ids, timeout, return_exceptions))
+
+    def poll_result(self, id: str) -> dict | None:
+        "Returns the result of a trade without waiting, None while the trade is still open (or unknown), see `PocketOptionAsync.poll_result`"
+        return self.loop.run_until_complete(self._client.poll_result(id))
+
+    def buy_at_price(self, asset: str, amount: float, time: int, price: float, valid_for: timedelta | float | None = None) -> dict:
+        "Registers a buy (call) trade placed once the quote of the asset crosses `price`, see `PocketOptionAsync.order_at_price`"
+        return self.loop.run_until_complete(self._client.buy_at_price(asset, amount, time, price, valid_for))
+
+    def sell_at_price(self, asset: str, amount: float, time: int, price: float, valid_for: timedelta | float | None = None) -> dict:
+        "Registers a sell (put) trade placed once the quote of the asset crosses `price`, see `PocketOptionAsync.order_at_price`"
+        return self.loop.run_until_complete(self._client.sell_at_price(asset, amount, time, price, valid_for))
+
+    def order_at_price(
+        self,
+        asset: str,
+        direction: Direction | str,
+        amount: float,
+        time: int,
+        price: float,
+        valid_for: timedelta | float | None = None,
+    ) -> dict:
+        "Registers a trade placed by the Rust client once the quote of the asset crosses `price`, see `PocketOptionAsync.order_at_price`"
+        return self.loop.run_until_complete(self._client.order_at_price(asset, direction, amount, time, price, valid_for))
+
+    def price_orders(self) -> list[dict]:
+        "Returns the orders registered with `order_at_price` with their status, oldest first"
+        return self._client.price_orders()
+
+    def cancel_price_order(self, id: str) -> dict:
+        "Cancels an order waiting for its price, raises if the price was already crossed"
+        return self._client.cancel_price_order(id)
 
-    def get_candles(self, asset: str, period: int, offset: int) -> list[dict]:
+    def buy_at_time(self, asset: str, amount: float, time: int, execute_at: datetime | float) -> dict:
+        "Queues a buy (call) trade placed at `execute_at` on the server clock, see `PocketOptionAsync.order_at_time`"
+        return self.loop.run_until_complete(self._client.buy_at_time(asset, amount, time, execute_at))
+
+    def sell_at_time(self, asset: str, amount: float, time: int, execute_at: datetime | float) -> dict:
+        "Queues a sell (put) trade placed at `execute_at` on the server clock, see `PocketOptionAsync.order_at_time`"
+        return self.loop.run_until_complete(self._client.sell_at_time(asset, amount, time, execute_at))
+
+    def order_at_time(self, asset: str, direction: Direction | str, amount: float, time: int, execute_at: datetime | float) -> dict:
+        "Queues a trade placed by the Rust client at `execute_at` on the server clock, see `PocketOptionAsync.order_at_time`"
+        return self.loop.run_until_complete(self._client.order_at_time(asset, direction, amount, time, execute_at))
+
+    def scheduled_orders(self) -> list[dict]:
+        "Returns the orders queued with `order_at_time` with their status, by execution time"
+        return self._client.scheduled_orders()
+
+    def cancel_scheduled_order(self, id: str) -> dict:
+        "Cancels an order waiting for its execution time, raises if it was already sent"
+        return self._client.cancel_scheduled_order(id)
+
+    def close_deal(self, id: str) -> dict:
         """
-        Takes the asset you want to get the candles and return a list of raw candles in dictionary format
+        Closes an open trade before its expiry (sell-out) for a partial payout, returns the closed trade with its
+        realized `profit` and its `result` ("win", "draw", "loss"). Raises if the trade isn't open or the server
+        refused to close it.
+        """
+        return self.loop.run_until_complete(self._client.close_deal(id))
+
+    def get_candles(self, asset: str, period: Timeframe | int | str | timedelta, offset: int, allow_partial: bool = False) -> list[Candle]:
+        """
+        Takes the asset you want to get the candles and return a list of `Candle` (which can also be read like a dict)
         Each candle contains:
-            * time: using the iso format
+            * asset: the asset of the candle
+            * time: start of the candle as a datetime (`timestamp` as a unix timestamp)
             * open: open price
             * close: close price
             * high: highest price
             * low: lowest price
+        If some candles are still missing after retrying an exception is raised, unless `allow_partial` is True
         """
-        return self.loop.run_until_complete(self._client.get_candles(asset, period, offset))
+        return self.loop.run_until_complete(self._client.get_candles(asset, period, offset, allow_partial))
+
+    def get_candles_np(self, asset: str, period: Timeframe | int | str | timedelta, offset: int, allow_partial: bool = False) -> dict:
+        "Returns the candles as a dict of numpy `float64` arrays (`timestamps`, `open`, `high`, `low`, `close`) read from the buffers of the Rust client, see `PocketOptionAsync.get_candles_np`"
+        return self.loop.run_until_complete(self._client.get_candles_np(asset, period, offset, allow_partial))
+
+    def get_candles_arrow(self, asset: str, period: Timeframe | int | str | timedelta, offset: int, allow_partial: bool = False):
+        "Returns the candles as a `pyarrow.RecordBatch` (`time`, `open`, `high`, `low`, `close`) for pandas or polars, see `PocketOptionAsync.get_candles_arrow`"
+        return self.loop.run_until_complete(self._client.get_candles_arrow(asset, period, offset, allow_partial))
+
+    def get_candles_range(self, asset: str, period: Timeframe | int | str | timedelta, start: datetime | float, end: datetime | float) -> list[Candle]:
+        "Returns the candles of the asset between `start` and `end` (datetimes or unix timestamps), loaded page by page and merged without duplicates, see `PocketOptionAsync.get_candles_range`"
+        return self.loop.run_until_complete(self._client.get_candles_range(asset, period, start, end))
+
+    def get_recent_candles(self, asset: str, count: int) -> list[Candle]:
+        "Returns the last `count` candles of a subscribed asset kept in memory, without a request to the server, see `PocketOptionAsync.get_recent_candles`"
+        return self._client.get_recent_candles(asset, count)
+
+    def get_candles_between(self, asset: str, start: datetime | float, end: datetime | float) -> list[Candle]:
+        "Returns the candles kept in memory for the asset between `start` and `end` (datetimes or unix timestamps), without a request to the server"
+        return self._client.get_candles_between(asset, start, end)
+
+    def set_candle_store(self, period: Timeframe | int | str | timedelta, capacity: int = 500) -> None:
+        "Sets the period of the candles kept in memory for the subscribed assets (1 minute by default) and how many are kept per asset"
+        self._client.set_candle_store(period, capacity)
+
+    def persist_candle_store(self, path: str | None = None, interval: timedelta | float = timedelta(minutes=1)) -> int:
+        "Saves the candles kept in memory to a file every `interval` and loads them back, see `PocketOptionAsync.persist_candle_store`"
+        return self.loop.run_until_complete(self._client.persist_candle_store(path, interval))
+
+    def download_history(self, asset: str, period: Timeframe | int | str | timedelta, days: int, path: str, format: str | None = None, progress: Callable[[dict], None] | None = None) -> dict:
+        "Downloads the candles of the last `days` days to a CSV, JSON lines or Parquet file page by page, see `PocketOptionAsync.download_history`"
+        return self.loop.run_until_complete(self._client.download_history(asset, period, days, path, format, progress))
     
-    def get_candles_advanced(self, asset: str, period: int, offset: int, time: int) -> list[dict]:  
+    def get_candles_advanced(self, asset: str, period: Timeframe | int | str | timedelta, offset: int, time: int, allow_partial: bool = False) -> list[Candle]:  
         """
         Retrieves historical candle data for an asset.
 
         Args:
             asset (str): Trading asset (e.g., "EURUSD_otc")
-            timeframe (int): Candle timeframe in seconds (e.g., 60 for 1-minute candles)
-            period (int): Historical period in seconds to fetch
+            period (Timeframe | int | str | timedelta): Candle timeframe, a `Timeframe`, seconds (e.g., 60 for 1-minute candles)
+                or a duration like "5m"
+            offset (int): Historical period in seconds to fetch
             time (int): Time to fetch candles from
+            allow_partial (bool): Returns the received candles instead of raising if some are missing. Defaults to False
 
         Returns:
-            list[dict]: List of candles, each containing:
-                - time: Candle timestamp
+            list[Candle]: List of candles, each with the attributes:
+                - asset: Trading asset
+                - time: Start of the candle (datetime), `timestamp` is the same as a unix timestamp
                 - open: Opening price
                 - high: Highest price
                 - low: Lowest price
                 - close: Closing price
+            The candles can also be read like the dicts of the previous versions (`candle["close"]`), `to_dict`
+            returns them as a dict
+
+        Raises:
+            ValueError: If the timeframe isn't supported by the server
 
         Note:
-            Available timeframes: 1, 5, 15, 30, 60, 300 seconds
+            Available timeframes: the members of `Timeframe` (5s, 10s, 15s, 30s, 1m, 2m, 3m, 5m, 10m, 15m, 30m, 1h, 4h, 1d)
             Maximum period depends on the timeframe
+            Missing segments are requested again with a backoff, if some candles are still missing
+            an exception with the missing ranges is raised unless `allow_partial` is True
         """
         
-        return self.loop.run_until_complete(self._client.get_candles_advanced(asset, period, offset, time))
+        return self.loop.run_until_complete(self._client.get_candles_advanced(asset, period, offset, time, allow_partial))
 
 
     def balance(self) -> float:
@@ -180,6 +419,10 @@ class PocketOption:
     def closed_deals(self) -> list[dict]:
         "Returns a list of all the closed deals as dictionaries"
         return self.loop.run_until_complete(self._client.closed_deals())      
+
+    def closed_deals_arrow(self):
+        "Returns the closed deals as a `pyarrow.RecordBatch`, one row per deal"
+        return self.loop.run_until_complete(self._client.closed_deals_arrow())
     
     def clear_closed_deals(self) -> None:
         "Removes all the closed deals from memory, this function doesn't return anything"
@@ -188,15 +431,39 @@ class PocketOption:
     def payout(self, asset: None | str | list[str] = None) -> dict | list[str] | int:
         "Returns a dict of asset | payout for each asset, if 'asset' is not None then it will return the payout of the asset or a list of the payouts for each asset it was passed"
         return self.loop.run_until_complete(self._client.payout(asset))
+
+    def get_payout_for(self, asset: str) -> int:
+        "Returns the current payout of the asset in percent, raises a `ValueError` if the server didn't send it"
+        return self.loop.run_until_complete(self._client.get_payout_for(asset))
+
+    def is_open(self, asset: str) -> bool:
+        "Returns whether the market of the asset is currently open, raises a `ValueError` if the server didn't send it"
+        return self.loop.run_until_complete(self._client.is_open(asset))
+
+    def get_assets(self, open_only: bool = False, otc: bool | None = None) -> list[Asset]:
+        "Returns the assets sent by the server sorted by symbol, only the tradable ones with `open_only` and only the OTC (or regular) ones with `otc`"
+        return self.loop.run_until_complete(self._client.get_assets(open_only, otc))
     
-    def history(self, asset: str, period: int) -> list[dict]:
-        "Returns a list of dictionaries containing the latest data available for the specified asset starting from 'period', the data is in the same format as the returned data of the 'get_candles' function."
+    def history(self, asset: str, period: Timeframe | int | str | timedelta) -> list[Candle]:
+        "Returns a list of candles containing the latest data available for the specified asset with the timeframe 'period' (see `Timeframe`), the data is in the same format as the returned data of the 'get_candles' function."
         return self.loop.run_until_complete(self._client.history(asset, period))
 
+    def history_arrow(self, asset: str, period: Timeframe | int | str | timedelta):
+        "Returns the latest candles of `history` as a `pyarrow.RecordBatch`"
+        return self.loop.run_until_complete(self._client.history_arrow(asset, period))
+
     def subscribe_symbol(self, asset: str) -> SyncSubscription:
         """Returns a sync iterator over the associated asset, it will return real time raw candles and will return new candles while the 'PocketOption' class is loaded if the class is droped then the iterator will fail"""
         return SyncSubscription(self.loop.run_until_complete(self._client._subscribe_symbol_inner(asset)))
 
+    def subscribe_symbol_batch(self, assets: list[str]) -> list[SyncSubscription]:
+        """Returns one sync iterator per asset (in the same order), the subscriptions of all the assets are sent together which is much faster than subscribing one by one"""
+        return [SyncSubscription(s) for s in self.loop.run_until_complete(self._client._subscribe_symbol_batch_inner(assets))]
+
+    def subscribe_symbols(self, symbols: list[str]) -> SyncSubscription:
+        """Returns one sync iterator merging the candles of all the assets in the order they arrive, each candle carries its `asset`, see `PocketOptionAsync.subscribe_symbols`"""
+        return SyncSubscription(self.loop.run_until_complete(self._client._subscribe_symbols_inner(symbols)))
+
     def subscribe_symbol_chuncked(self, asset: str, chunck_size: int) -> SyncSubscription:
         """Returns a sync iterator over the associated asset, it will return real time candles formed with the specified amount of raw candles and will return new candles while the 'PocketOption' class is loaded if the class is droped then the iterator will fail"""
         return SyncSubscription(self.loop.run_until_complete(self._client._subscribe_symbol_chuncked_inner(asset, chunck_size)))
@@ -208,6 +475,67 @@ class PocketOption:
         """
         return SyncSubscription(self.loop.run_until_complete(self._client._subscribe_symbol_timed_inner(asset, time)))
     
+    def subscribe_candle_close(self, asset: str, period: timedelta | int, timeout: timedelta | None = None) -> SyncSubscription:
+        """
+        Returns a sync iterator over the candles of an asset sent when each period closes on the server clock, even if the
+        last tick of the period never arrives, see `PocketOptionAsync.subscribe_candle_close`
+        """
+        return SyncSubscription(self.loop.run_until_complete(self._client._subscribe_candle_close_inner(asset, period, timeout)))
+
+    def subscribe_account_events(self, timeout: timedelta | None = None) -> SyncSubscription:
+        """
+        Returns a sync iterator over the administrative messages about the account (bonuses, margin calls,
+        restrictions, maintenance notices...), see `PocketOptionAsync.subscribe_account_events`
+        """
+        return SyncSubscription(self.loop.run_until_complete(self._client._subscribe_account_events_inner(timeout)))
+
+    def subscribe_trade_results(self, timeout: timedelta | None = None) -> SyncSubscription:
+        """
+        Returns a sync iterator yielding a deal every time a trade of the account closes (the `profit` is positive for
+        a win, negative for a loss and 0 for a draw), see `PocketOptionAsync.subscribe_trade_results`
+        """
+        return SyncSubscription(self.loop.run_until_complete(self._client._subscribe_trade_results_inner(timeout)))
+
+    def subscribe_unparsed(self, timeout: timedelta | None = None) -> SyncSubscription:
+        """
+        Returns a sync iterator over the messages recieved from the server that failed to parse, with their raw payload
+        and parsing error, see `PocketOptionAsync.subscribe_unparsed`
+        """
+        return SyncSubscription(self._client._subscribe_unparsed_inner(timeout))
+
+    def unsubscribe_symbol(self, asset: str) -> None:
+        "Unsubscribes from an asset, releasing its slot for the next queued subscription"
+        self.loop.run_until_complete(self._client.unsubscribe_symbol(asset))
+
+    def set_subscription_policy(
+        self, max_subscriptions: int | None = None, rotate: bool = True, detection_delay: timedelta | None = None
+    ) -> None:
+        "Sets how the subscription limit of the server is handled, see `PocketOptionAsync.set_subscription_policy`"
+        self.loop.run_until_complete(self._client.set_subscription_policy(max_subscriptions, rotate, detection_delay))
+
+    def subscription_status(self) -> dict:
+        "Returns the `active` and `queued` subscriptions, the `limit` in use and whether it was `detected`"
+        return self._client.subscription_status()
+
+    def set_stream_priority(self, asset: str, priority: str) -> None:
+        "Sets the lane of the ticks of the asset, \"critical\" (never shed) or \"best_effort\", see `PocketOptionAsync.set_stream_priority`"
+        self._client.set_stream_priority(asset, priority)
+
+    def stream_lane_stats(self) -> dict:
+        "Returns the `critical` assets, the best-effort ticks `shed` (and `shed_by_asset`) and the ticks pending in each lane"
+        return self._client.stream_lane_stats()
+
+    def set_power_saving(self, idle: timedelta | float | None) -> None:
+        "Drops the chart subscriptions after `idle` without a stream, see `PocketOptionAsync.set_power_saving`"
+        self.loop.run_until_complete(self._client.set_power_saving(idle))
+
+    def subscribe_subscription_events(self, timeout: timedelta | None = None) -> SyncSubscription:
+        """
+        Returns a sync iterator over the changes of the chart subscriptions (subscribed, queued, activated, evicted,
+        limit detected...), see `PocketOptionAsync.subscribe_subscription_events`
+        """
+        return SyncSubscription(self._client._subscribe_subscription_events_inner(timeout))
+
     def send_raw_message(self, message: str) -> None:
         """
         Sends a raw WebSocket message without waiting for a response.
@@ -326,7 +654,9 @@ class PocketOption:
         Args:
             message: Initial WebSocket message to send
             validator: Validator instance to filter incoming messages
-            timeout: Optional timeout for the entire stream
+            timeout: Optional maximum time to wait for each message, it only applies to this iterator and can be changed
+                later with `set_timeout`, `reset_timeout` and `extend`. When it's reached the iteration stops but the
+                iterator can be iterated again
             
         Returns:
             SyncSubscription yielding validated messages
@@ -360,6 +690,26 @@ class PocketOption:
         """
         return SyncSubscription(self.loop.run_until_complete(self._client.create_raw_iterator(message, validator, timeout)))
 
+    def register_raw_iterator(self, iterator_id: str, message: str, validator: Validator, timeout: timedelta | None = None, path: str = "raw_iterators.json"):
+        """
+        Creates a raw iterator like `create_raw_iterator` and persists its definition (message + validator)
+        under a stable id, so it can be re-created after a restart with `restore_raw_iterators`.
+        Custom validators can't be persisted.
+        """
+        return self.loop.run_until_complete(self._client.register_raw_iterator(iterator_id, message, validator, timeout, path))
+
+    def restore_raw_iterators(self, path: str = "raw_iterators.json") -> dict:
+        """Re-creates every persisted raw iterator, returns a dict mapping each iterator id to its new iterator"""
+        return self.loop.run_until_complete(self._client.restore_raw_iterators(path))
+
+    def unregister_raw_iterator(self, iterator_id: str, path: str = "raw_iterators.json") -> bool:
+        """Removes a persisted raw iterator definition, returns True if it existed"""
+        return self.loop.run_until_complete(self._client.unregister_raw_iterator(iterator_id, path))
+
+    def tap_raw(self, validator: Validator | None = None, timeout: timedelta | None = None):
+        """Returns a sync iterator over the raw messages recieved from the server without sending anything, see `PocketOptionAsync.tap_raw`"""
+        return self._client._tap_raw_inner(validator, timeout)
+
     def get_server_time(self) -> int:
         """Returns the current server time as a UNIX timestamp"""
         return self.loop.run_until_complete(self._client.get_server_time())
@@ -393,3 +743,363 @@ class PocketOption:
             ```
         """
         return self.loop.run_until_complete(self._client.is_demo())
+
+    def is_observer(self) -> bool:
+        "Returns True if the client is in observer mode, where every method sending an order raises `ObserverModeError`"
+        return self._client.is_observer()
+
+    def set_otc_fallback(self, enabled: bool) -> None:
+        "Enables or disables the OTC fallback, the trades and subscriptions on a closed regular asset use its OTC version if it's open"
+        self._client.set_otc_fallback(enabled)
+
+    def otc_fallback(self) -> bool:
+        "Returns True if the OTC fallback is enabled"
+        return self._client.otc_fallback()
+
+    def resolve_asset(self, asset: str) -> str:
+        "Returns the symbol the trades and the subscriptions on `asset` use, see `PocketOptionAsync.set_otc_fallback`"
+        return self.loop.run_until_complete(self._client.resolve_asset(asset))
+
+    def set_session_provider(self, provider: Callable[[], str]) -> None:
+        """
+        Registers the function called for a fresh session once the server rejects the current one, the client then
+        reconnects with it. The function is called from the client's threads, so it should be a plain function: the
+        event loops of this client only run during its calls. See `PocketOptionAsync.set_session_provider`.
+        """
+        self._client.client.set_session_provider(provider, self.loop)
+
+    def remove_session_provider(self) -> None:
+        "Removes the session provider, an expired session is then kept until the client is recreated"
+        self._client.remove_session_provider()
+
+    def refresh_session(self) -> SsidInfo:
+        "Asks the session provider for a fresh session right away and caches it, it's used from the next connection"
+        return self.loop.run_until_complete(self._client.refresh_session())
+
+    def session_status(self) -> dict:
+        "Returns the state of the session: its account, whether it expired, whether a provider is set, the number of refreshes and the last `rejection` (expired or malformed)"
+        return self._client.session_status()
+
+    def profile(self) -> dict:
+        "Returns the account of the session with its balance, the server the client is connected to, the region reported by the server and its last `region_mismatch` (with the `hints` to fix it)"
+        return self.loop.run_until_complete(self._client.profile())
+
+    def health(self) -> dict:
+        """
+        Returns the current health of the client as a dictionary containing:
+            - connected: Whether the websocket connection is currently open
+            - last_message_age: Seconds since the last message was received (None if no message was received)
+            - open_trades: Number of currently opened trades
+            - reconnections: Number of reconnections since the client was created
+            - shed_ticks: Number of best-effort ticks dropped because the streams didn't keep up
+        """
+        return self.loop.run_until_complete(self._client.health())
+
+    def start_healthcheck(self, port: int, host: str = "0.0.0.0", access: AccessControl | None = None) -> None:
+        """
+        Starts an embedded HTTP healthcheck endpoint, useful for docker / kubernetes liveness probes.
+        Every GET request is answered with the `health` report as JSON, with status code 200 if the
+        client is connected and 503 otherwise. With `access` only the requests carrying one of its API keys are answered.
+        """
+        self.loop.run_until_complete(self._client.start_healthcheck(port, host, access))
+
+    def start_dashboard(
+        self,
+        port: int = 8000,
+        host: str = "127.0.0.1",
+        logs: StreamLogsLayer | None = None,
+        access: AccessControl | None = None,
+    ) -> None:
+        """
+        Starts a dashboard of the client, a web page showing its balance, trades, streams, pending orders and logs
+        (the ones of the `logs` layer). Requires a build with the `dashboard` feature, see
+        `PocketOptionAsync.start_dashboard`.
+        """
+        self.loop.run_until_complete(self._client.start_dashboard(port, host, logs, access))
+
+    def shutdown(self) -> None:
+        """
+        Closes the connection with the server for good: the background tasks of the client are stopped, every open
+        stream ends with `StopIteration` and the requests sent afterwards fail.
+        """
+        self.loop.run_until_complete(self._client.shutdown())
+
+    def is_shut_down(self) -> bool:
+        "Returns True once `shutdown` was called"
+        return self._client.is_shut_down()
+
+    def is_connected(self) -> bool:
+        "Returns True while the websocket is connected, False while reconnecting and after `shutdown`"
+        return self._client.is_connected()
+
+    def connection_events(self, timeout: timedelta | None = None) -> SyncSubscription:
+        "Returns a sync iterator over the changes of the state of the connection, see `PocketOptionAsync.connection_events`"
+        return SyncSubscription(self._client._connection_events_inner(timeout))
+
+    def pending_orders(self) -> int:
+        "Returns the number of orders sent whose confirmation wasn't received yet"
+        return self._client.pending_orders()
+
+    def flush_orders(self, timeout: float | None = None) -> None:
+        "Waits up to `timeout` seconds (the timeout of the orders by default) for the pending orders to be confirmed"
+        self.loop.run_until_complete(self._client.flush_orders(timeout))
+
+    def __enter__(self) -> "PocketOption":
+        return self
+
+    def __exit__(self, exc_type, exc_value, traceback):
+        "Flushes the pending orders and shuts the client down, see `PocketOptionAsync.__aexit__`"
+        self.loop.run_until_complete(self._client.__aexit__(exc_type, exc_value, traceback))
+
+    def valid_expiries(self, asset: str) -> list[int]:
+        "Returns the expiry times (in seconds) accepted by the server for an asset, sorted from shortest to longest."
+        return self.loop.run_until_complete(self._client.valid_expiries(asset))
+
+    def nearest_expiry(self, asset: str, desired_secs: int) -> int:
+        "Returns the valid expiry closest to `desired_secs` for an asset, use it to correct the expiry before placing a trade."
+        return self.loop.run_until_complete(self._client.nearest_expiry(asset, desired_secs))
+
+    def suggest_expiry(self, asset: str, target_move_pips: float) -> dict:
+        "Suggests the shortest valid expiry of an asset likely to see a move of `target_move_pips` from its recent realized volatility, see `PocketOptionAsync.suggest_expiry`"
+        return self.loop.run_until_complete(self._client.suggest_expiry(asset, target_move_pips))
+
+    def precision(self, asset: str) -> dict:
+        "Returns the precision (`digits` and `pip_size`) of the prices of an asset"
+        return self.loop.run_until_complete(self._client.precision(asset))
+
+    def round_price(self, asset: str, price: float) -> float:
+        "Rounds a price to the number of digits of an asset"
+        return self.loop.run_until_complete(self._client.round_price(asset, price))
+
+    def pips_between(self, asset: str, a: float, b: float) -> float:
+        "Returns the signed number of pips from price `a` to price `b` of an asset"
+        return self.loop.run_until_complete(self._client.pips_between(asset, a, b))
+
+    def add_cluster_guard(self, max_trades: int = 1, window: timedelta = timedelta(minutes=1), aligned: bool = True) -> None:
+        "Limits the number of trades placed on the same asset in a time window (per candle if `aligned` is True), trades over the limit are rejected with an error before being sent."
+        self._client.add_cluster_guard(max_trades, window, aligned)
+
+    def set_drawdown_breaker(self, max_drawdown: float, demo_ssid: str | None = None) -> None:
+        "Stops trading on the real account once the balance drops more than `max_drawdown` (fraction) from its peak, the trades continue on the demo account if `demo_ssid` is provided."
+        self.loop.run_until_complete(self._client.set_drawdown_breaker(max_drawdown, demo_ssid))
+
+    def drawdown_state(self) -> dict | None:
+        "Returns the state of the drawdown breaker, None if it isn't set"
+        return self._client.drawdown_state()
+
+    def reset_drawdown_breaker(self) -> None:
+        "Re-arms the drawdown breaker after it tripped, the current balance becomes the new peak"
+        self._client.reset_drawdown_breaker()
+
+    def set_trading_hours(self, hours: list[int] | None = None, weekdays: list[int] | None = None) -> None:
+        "Only allows the trades placed in `hours` (0 to 23) and on `weekdays` (0 for Monday to 6), in UTC, the others are rejected with an error"
+        self._client.set_trading_hours(hours, weekdays)
+
+    def trade_profitable_hours(
+        self,
+        min_trades: int = 10,
+        asset: str | None = None,
+        tag: str | None = None,
+        since: datetime | float | None = None,
+    ) -> list[int]:
+        "Only allows the trades placed in the hours the closed trades of the journal were profitable at, returns the allowed hours, see `PocketOptionAsync.trade_profitable_hours`"
+        return self._client.trade_profitable_hours(min_trades, asset, tag, since)
+
+    def set_market_guard(
+        self,
+        max_latency: float | None = None,
+        max_gap: timedelta | float | None = None,
+        max_volatility: float | None = None,
+        window: int = 20,
+        resume_after: timedelta | float = 30,
+    ) -> None:
+        "Pauses the order placement while the latency, the gaps between the ticks or the volatility are over the thresholds and resumes it once they stayed normal for `resume_after`, see `PocketOptionAsync.set_market_guard`"
+        self._client.set_market_guard(max_latency, max_gap, max_volatility, window, resume_after)
+
+    def remove_market_guard(self) -> None:
+        "Removes the market guard"
+        self._client.remove_market_guard()
+
+    def market_guard_status(self) -> list[dict]:
+        "Returns the pauses of the market guard (asset, reasons, since), empty if the trading isn't paused"
+        return self._client.market_guard_status()
+
+    def risk_rules(self) -> list[str]:
+        "Returns the names of the risk rules checked before every trade"
+        return self._client.risk_rules()
+
+    def clear_risk_rules(self) -> None:
+        "Removes all the risk rules"
+        self._client.clear_risk_rules()
+
+    def set_stake_rules(
+        self,
+        step: float = 0.01,
+        min: float | None = None,
+        max: float | None = None,
+        rounding: str = "nearest",
+        currency: str | None = None,
+        account_currency: str | None = None,
+    ) -> None:
+        "Sets the rounding rule of the stakes (of `currency`, or of every currency without its own rule), applied before every order, see `PocketOptionAsync.set_stake_rules`"
+        self._client.set_stake_rules(step, min, max, rounding, currency, account_currency)
+
+    def stake_rules(self) -> dict:
+        "Returns the stake rules: the account `currency` if it's set, the rule of each of the `currencies` and the `default` rule"
+        return self._client.stake_rules()
+
+    def account_currency(self) -> str | None:
+        "Returns the currency of the account, None if it isn't set in the stake rules and the account has no deal yet"
+        return self.loop.run_until_complete(self._client.account_currency())
+
+    def round_stake(self, amount: float) -> float:
+        "Returns the amount an order of `amount` would be placed with, raises an error if it's out of the limits of the stake rules"
+        return self.loop.run_until_complete(self._client.round_stake(amount))
+
+    def hedge(self, trade_id: str, hedge_asset: str, ratio: float = 1.0, negative_correlation: bool = True) -> tuple[str, str, dict]:
+        "Places an offsetting trade on a correlated asset for an already placed trade, returns (group_id, hedge_trade_id, hedge_trade_details) and links both trades in the journal as a hedge group"
+        return self.loop.run_until_complete(self._client.hedge(trade_id, hedge_asset, ratio, negative_correlation))
+
+    def straddle(self, asset: str, amount: float, expiry: int) -> dict:
+        "Places a call and a put of `amount` on the asset together, grouped in the journal, see `PocketOptionAsync.straddle`"
+        return self.loop.run_until_complete(self._client.straddle(asset, amount, expiry))
+
+    def subscribe_straddle_results(self, timeout: timedelta | None = None) -> SyncSubscription:
+        "Returns a sync iterator yielding the combined result (`profit` and `result` of both legs) of every straddle once it closed"
+        return SyncSubscription(self.loop.run_until_complete(self._client._subscribe_straddle_results_inner(timeout)))
+
+    def debug_stats(self) -> dict:
+        "Returns internal counters of the library (JSON backend, buffer pool statistics) to diagnose performance issues"
+        return self._client.debug_stats()
+
+    def latency_stats(self) -> dict:
+        "Returns rolling statistics (percentiles, clock offset) of the latency of the last streamed messages in milliseconds"
+        return self.loop.run_until_complete(self._client.latency_stats())
+
+    def feed_health(self) -> dict:
+        "Returns the observed vs expected message rate of every subscribed feed and the feeds whose rate collapsed"
+        return self.loop.run_until_complete(self._client.feed_health())
+
+    def set_metadata_cache(self, path: str, ttl: timedelta | float = timedelta(days=1)) -> dict:
+        "Caches the metadata of the assets in a file used until the server sends them, see `PocketOptionAsync.set_metadata_cache`"
+        return self.loop.run_until_complete(self._client.set_metadata_cache(path, ttl))
+
+    def metadata_status(self) -> dict:
+        "Returns the `source` of the metadata of the assets (server, cache or none), when the server sent them and whether the cached ones are `stale`"
+        return self.loop.run_until_complete(self._client.metadata_status())
+
+    def server_ids(self, trade_id: str) -> dict:
+        "Returns the ids the broker uses for a trade (`ticket`, `request_id` and `uid`), the ones its support needs"
+        return self.loop.run_until_complete(self._client.server_ids(trade_id))
+
+    def journal(self) -> list[dict]:
+        "Returns the journal of all the trades placed by the client, including their hedge group and result once closed"
+        return self._client.journal()
+
+    def hedge_group(self, group_id: str) -> list[dict]:
+        "Returns the journal entries of the trades linked in a hedge group"
+        return self._client.hedge_group(group_id)
+
+    def persist_journal(self, path: str = "journal.json") -> None:
+        "Loads the journal stored in `path` (if it exists) and saves every new trade and result to it from now on"
+        self._client.persist_journal(path)
+
+    def capture_journal_context(
+        self, period: int | str | Timeframe | None = 60, before: int = 20, after: int = 10
+    ) -> None:
+        "Stores the candles around every trade in its journal entry once it closes, see `PocketOptionAsync.capture_journal_context`"
+        self._client.capture_journal_context(period, before, after)
+
+    def import_trades(self, path: str) -> int:
+        "Loads the trades of a history exported from the broker (CSV or XLSX) in the journal, returns the number of trades added"
+        return self._client.import_trades(path)
+
+    def journal_stats(self, tag: str | None = None) -> dict:
+        "Returns the statistics (win rate, profit, roi...) of the closed trades of the journal, see `PocketOptionAsync.journal_stats`"
+        return self._client.journal_stats(tag)
+
+    def expectancy(
+        self,
+        asset: str | None = None,
+        tag: str | None = None,
+        hours: tuple[int, int] | None = None,
+        since: datetime | float | None = None,
+        until: datetime | float | None = None,
+        confidence: float = 0.95,
+    ) -> dict:
+        "Returns the expectancy, Sharpe-like ratio and confidence interval of the closed trades of the journal matching the filter, see `PocketOptionAsync.expectancy`"
+        return self._client.expectancy(asset, tag, hours, since, until, confidence)
+
+    def performance_by_hour(
+        self,
+        by_asset: bool = False,
+        asset: str | None = None,
+        tag: str | None = None,
+        since: datetime | float | None = None,
+        until: datetime | float | None = None,
+    ) -> dict:
+        "Returns the performance matrices (trades, win rate, profit, return per unit) of the closed trades of the journal by hour of the day, see `PocketOptionAsync.performance_by_hour`"
+        return self._client.performance_by_hour(by_asset, asset, tag, since, until)
+
+    def performance_by_weekday(
+        self,
+        by_asset: bool = False,
+        asset: str | None = None,
+        tag: str | None = None,
+        hours: tuple[int, int] | None = None,
+        since: datetime | float | None = None,
+        until: datetime | float | None = None,
+    ) -> dict:
+        "Returns the performance matrices of the closed trades of the journal by day of the week, see `PocketOptionAsync.performance_by_weekday`"
+        return self._client.performance_by_weekday(by_asset, asset, tag, hours, since, until)
+
+    def save_template(self, template: OrderTemplate | dict, path: str = "order_templates.json") -> None:
+        "Saves an order template so it can be executed by name with `execute_template`, see `PocketOptionAsync.save_template`"
+        self._client.save_template(template, path)
+
+    def list_templates(self, path: str = "order_templates.json") -> dict[str, OrderTemplate]:
+        "Returns the saved order templates, by name"
+        return self._client.list_templates(path)
+
+    def remove_template(self, name: str, path: str = "order_templates.json") -> bool:
+        "Removes a saved order template, returns True if it existed"
+        return self._client.remove_template(name, path)
+
+    def execute_template(self, name: str, path: str = "order_templates.json") -> dict:
+        "Executes a saved order template and returns the placed trades, see `PocketOptionAsync.execute_template`"
+        return self.loop.run_until_complete(self._client.execute_template(name, path))
+
+    def audit_candles(self, data_or_range: list[dict] | tuple[str, int, int], period: int | None = None) -> dict:
+        "Returns a data quality report (gaps, duplicate timestamps, zero-range candles and misaligned candles) of a list of candles or of the candles of a range `(asset, period, offset)`, the period is inferred from the candles if not provided"
+        return self.loop.run_until_complete(self._client.audit_candles(data_or_range, period))
+
+    def levels(self, asset: str, period: int | str | Timeframe, lookback: int, **config) -> dict:
+        "Returns the pivots, session highs / lows and support / resistance zones of the last `lookback` seconds of `asset`, see `PocketOptionAsync.levels`"
+        return self.loop.run_until_complete(self._client.levels(asset, period, lookback, **config))
+
+    def chart_trade(
+        self,
+        trade_id: str,
+        period: int | str | Timeframe = 60,
+        width: int = 1000,
+        height: int = 500,
+        title: str | None = None,
+    ) -> bytes:
+        "Renders a trade and the candles around it to a PNG image, see `PocketOptionAsync.chart_trade`"
+        return self.loop.run_until_complete(self._client.chart_trade(trade_id, period, width, height, title))
+
+    def chart_range(
+        self,
+        asset: str,
+        period: int | str | Timeframe,
+        start: datetime | float,
+        end: datetime | float,
+        markers: list[dict | tuple] | None = None,
+        width: int = 1000,
+        height: int = 500,
+        title: str | None = None,
+    ) -> bytes:
+        "Renders the candles of `asset` between `start` and `end` to a PNG image, see `PocketOptionAsync.chart_range`"
+        return self.loop.run_until_complete(
+            self._client.chart_range(asset, period, start, end, markers, width, height, title)
+        )
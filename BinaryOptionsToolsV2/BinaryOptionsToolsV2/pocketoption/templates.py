@@ -0,0 +1,131 @@
+"""
+Order templates: predefined trades saved under a name and executed later with `execute_template`, so operational
+tooling can trigger them safely without building the orders by hand.
+
+Example:
+    ```python
+    straddle = OrderTemplate(
+        "news_straddle_eurusd",
+        "EURUSD_otc",
+        ["call", "put"],
+        amount=OrderTemplate.balance_percent(1.0, max=20.0),
+        expiry=OrderTemplate.candle_close(60, min_seconds=15),
+        checks=[OrderTemplate.min_payout(80), OrderTemplate.hours(12, 16)],
+        tags=["news"],
+    )
+    await client.save_template(straddle)
+    execution = await client.execute_template("news_straddle_eurusd")
+    ```
+"""
+from __future__ import annotations
+
+import json
+
+ACTIONS = {"call": "call", "buy": "call", "put": "put", "sell": "put"}
+
+
+class OrderTemplate:
+    def __init__(
+        self,
+        name: str,
+        asset: str,
+        actions: str | list[str],
+        amount: float | dict,
+        expiry: int | dict,
+        checks: list[dict] | None = None,
+        tags: list[str] | None = None,
+        description: str | None = None,
+    ):
+        """
+        A predefined trade, or group of trades placed together (e.g. a straddle with a call and a put).
+
+        Args:
+            name (str): Name the template is saved and executed with
+            asset (str): Asset of the trades
+            actions (str | list[str]): Direction of every trade placed, "call" / "buy" or "put" / "sell"
+            amount (float | dict): Stake of every trade, a fixed amount or a rule (see `balance_percent`)
+            expiry (int | dict): Expiry of the trades, fixed seconds or a rule (see `nearest` and `candle_close`)
+            checks (list[dict], optional): Conditions checked before the execution (see `min_payout`, `max_amount`,
+                `demo_only` and `hours`), the execution is rejected if any fails
+            tags (list[str], optional): Risk tags recorded in the journal with every trade of the template
+            description (str, optional): Description of the template
+        """
+        if isinstance(actions, str):
+            actions = [actions]
+        try:
+            self.actions = [ACTIONS[action.lower()] for action in actions]
+        except KeyError as e:
+            raise ValueError(f"Invalid action {e}, expected 'call', 'buy', 'put' or 'sell'") from None
+        self.name = name
+        self.asset = asset
+        self.amount = amount if isinstance(amount, dict) else {"kind": "fixed", "amount": float(amount)}
+        self.expiry = expiry if isinstance(expiry, dict) else {"kind": "fixed", "seconds": int(expiry)}
+        self.checks = list(checks or [])
+        self.tags = list(tags or [])
+        self.description = description
+
+    @staticmethod
+    def balance_percent(percent: float, min: float | None = None, max: float | None = None) -> dict:
+        """Stake of `percent`% of the balance at the time of the execution, clamped to `min` and `max`"""
+        return {"kind": "balance_percent", "percent": percent, "min": min, "max": max}
+
+    @staticmethod
+    def nearest(seconds: int) -> dict:
+        """Expiry accepted by the server for the asset closest to `seconds`"""
+        return {"kind": "nearest", "seconds": seconds}
+
+    @staticmethod
+    def candle_close(period: int, min_seconds: int = 0) -> dict:
+        """Expiry at the close of the current candle of `period` seconds, or of the next one if less than `min_seconds` remain"""
+        return {"kind": "candle_close", "period": period, "min_seconds": min_seconds}
+
+    @staticmethod
+    def min_payout(percent: int) -> dict:
+        """Check: the payout of the asset must be at least `percent`"""
+        return {"kind": "min_payout", "percent": percent}
+
+    @staticmethod
+    def max_amount(amount: float) -> dict:
+        """Check: the stake of every trade must not exceed `amount`"""
+        return {"kind": "max_amount", "amount": amount}
+
+    @staticmethod
+    def demo_only() -> dict:
+        """Check: the template can only be executed on a demo account"""
+        return {"kind": "demo_only"}
+
+    @staticmethod
+    def hours(start: int, end: int) -> dict:
+        """Check: the template can only be executed between the `start` and `end` hours (UTC), `end` excluded"""
+        return {"kind": "hours", "start": start, "end": end}
+
+    def to_dict(self) -> dict:
+        return {
+            "name": self.name,
+            "description": self.description,
+            "asset": self.asset,
+            "actions": self.actions,
+            "amount": self.amount,
+            "expiry": self.expiry,
+            "checks": self.checks,
+            "tags": self.tags,
+        }
+
+    def to_json(self) -> str:
+        return json.dumps(self.to_dict())
+
+    @staticmethod
+    def from_dict(data: dict) -> OrderTemplate:
+        return OrderTemplate(
+            data["name"],
+            data["asset"],
+            data["actions"],
+            data["amount"],
+            data["expiry"],
+            data.get("checks"),
+            data.get("tags"),
+            data.get("description"),
+        )
+
+    def __repr__(self) -> str:
+        return f"OrderTemplate({self.to_dict()!r})"
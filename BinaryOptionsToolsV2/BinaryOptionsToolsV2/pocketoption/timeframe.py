@@ -0,0 +1,67 @@
+"""
+Candle periods supported by Pocket Option. A `Timeframe` is an `int` (its number of seconds), so it can be passed
+everywhere a `period` is taken, e.g. `await client.get_candles("EURUSD_otc", Timeframe.M5, 3600)`.
+"""
+from __future__ import annotations
+
+from datetime import timedelta
+from enum import IntEnum
+
+UNITS = {"": 1, "s": 1, "m": 60, "h": 3600, "d": 86400}
+
+
+class Timeframe(IntEnum):
+    S5 = 5
+    S10 = 10
+    S15 = 15
+    S30 = 30
+    M1 = 60
+    M2 = 120
+    M3 = 180
+    M5 = 300
+    M10 = 600
+    M15 = 900
+    M30 = 1800
+    H1 = 3600
+    H4 = 14400
+    D1 = 86400
+
+    @property
+    def seconds(self) -> int:
+        return int(self)
+
+    @property
+    def duration(self) -> timedelta:
+        return timedelta(seconds=int(self))
+
+    @classmethod
+    def parse(cls, period: Timeframe | int | str | timedelta) -> Timeframe:
+        """
+        Returns the timeframe of `period`: a `Timeframe`, a number of seconds, a `timedelta`, the name of the
+        timeframe ("M1") or a duration ("1m", "30s", "4h", "1d").
+
+        Raises:
+            ValueError: If the period isn't supported by the server
+        """
+        if isinstance(period, cls):
+            return period
+        seconds = None
+        if isinstance(period, timedelta):
+            seconds = period.total_seconds()
+        elif isinstance(period, str):
+            value = period.strip()
+            if value.upper() in cls.__members__:
+                return cls[value.upper()]
+            amount = value.rstrip("smhdSMHD")
+            unit = value[len(amount):].lower()
+            if amount.isdigit() and unit in UNITS:
+                seconds = int(amount) * UNITS[unit]
+        elif isinstance(period, (int, float)) and not isinstance(period, bool):
+            seconds = period
+        if seconds is not None and float(seconds).is_integer():
+            try:
+                return cls(int(seconds))
+            except ValueError:
+                pass
+        supported = ", ".join(timeframe.name for timeframe in cls)
+        raise ValueError(f"Invalid timeframe {period!r}, the supported timeframes are {supported}")
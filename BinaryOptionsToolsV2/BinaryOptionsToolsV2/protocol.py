@@ -0,0 +1,256 @@
+from __future__ import annotations
+
+from collections import deque
+from datetime import datetime, timedelta, timezone
+
+import asyncio
+import json
+import re
+
+from BinaryOptionsToolsV2 import parse_frame as _parse_frame
+from BinaryOptionsToolsV2 import load_fixtures as _load_fixtures
+from BinaryOptionsToolsV2 import check_fixtures as _check_fixtures
+from BinaryOptionsToolsV2.validator import Validator
+
+
+def parse_frame(frame: str, context: str | None = None) -> dict:
+    """
+    Parse a frame as sent by the server, without a connection, to triage new or unknown messages.
+
+    Args:
+        frame (str): A `451-["event",...]` header, a `42["event",data]` message or a bare payload.
+        context (str | None): Event of the previous header, used to parse bare payloads (e.g. "successopenOrder").
+
+    Returns:
+        dict: Report with the `event`, the `info` (type) the payload was parsed as, `awaiting_payload` for headers,
+            `parsed` (False if the models kept it as raw data), the parsed `message` and the parsing `error` if any.
+    """
+    return json.loads(_parse_frame(frame, context))
+
+
+def load_fixtures(path: str) -> list[dict]:
+    """
+    Load the captured frames of a corpus directory, each `.json` file contains `{"event": ..., "payload": ...}`.
+
+    Args:
+        path (str): Directory of the corpus.
+
+    Returns:
+        list[dict]: The fixtures with their `name`, `event` and `payload`, sorted by name.
+    """
+    return json.loads(_load_fixtures(path))
+
+
+def check_fixtures(path: str) -> dict[str, dict]:
+    """
+    Parse every fixture of a corpus directory with the models of its event.
+
+    Args:
+        path (str): Directory of the corpus.
+
+    Returns:
+        dict[str, dict]: The report (see `parse_frame`) of each fixture, by name.
+    """
+    return {name: report for name, report in json.loads(_check_fixtures(path))}
+
+
+def _loads(payload: str):
+    try:
+        return json.loads(payload)
+    except ValueError:
+        return payload
+
+
+class ProtocolConsole:
+    """
+    Interactive helper to explore undocumented messages of the broker from a REPL (e.g. `python -m asyncio`):
+    it taps the raw messages of the server (and optionally the ones that failed to parse), pretty-prints the
+    ones matching the filters as they arrive, keeps them in a history and sends raw messages.
+
+    Example:
+        ```python
+        client = PocketOptionAsync(ssid)
+        console = ProtocolConsole(client, include=["signals"])
+        await console.start()
+        await console.send('42["signals/subscribe"]')    # Matching responses are printed as they arrive
+        console.filter(exclude=["signals/load"])
+        console.history(5)                               # Last 5 messages, sent ones included
+        await console.stop()
+        ```
+    """
+
+    def __init__(
+        self,
+        client,
+        include: list[str] | None = None,
+        exclude: list[str] | None = None,
+        unparsed: bool = True,
+        pretty: bool = True,
+        max_length: int | None = 2000,
+        history_size: int = 1000,
+        printer=print,
+    ):
+        """
+        Args:
+            client (PocketOptionAsync): Connected client to explore the messages of
+            include (list[str] | None): Regex patterns, only the messages whose event (or frame) matches one of them are shown
+            exclude (list[str] | None): Regex patterns, the messages whose event (or frame) matches one of them are hidden
+            unparsed (bool): Also tap the messages of known events that failed to parse. Defaults to True
+            pretty (bool): Indent json payloads. Defaults to True
+            max_length (int | None): Payloads longer than this are truncated when printed, None disables it
+            history_size (int): Number of messages kept in the history (filtered ones included)
+            printer (callable): Function called with every formatted message. Defaults to `print`
+        """
+        self.client = client
+        self.unparsed = unparsed
+        self.pretty = pretty
+        self.max_length = max_length
+        self.printer = printer
+        self._history = deque(maxlen=history_size)
+        self._context: str | None = None
+        self._tasks: list[asyncio.Task] = []
+        self.filter(include, exclude)
+
+    def filter(self, include: list[str] | None = None, exclude: list[str] | None = None) -> None:
+        """Replaces the filters of the printed messages, calling it without arguments shows every message"""
+        self._include = [re.compile(p) for p in include or []]
+        self._exclude = [re.compile(p) for p in exclude or []]
+
+    def matches(self, message: dict) -> bool:
+        """Returns True if the message passes the filters"""
+        target = message["event"] or message["frame"]
+        if self._include and not any(p.search(target) for p in self._include):
+            return False
+        return not any(p.search(target) for p in self._exclude)
+
+    def format(self, message: dict) -> str:
+        """Formats a message of the history like it's printed"""
+        payload = message["payload"]
+        if not isinstance(payload, str):
+            payload = json.dumps(payload, indent=2 if self.pretty else None)
+        if self.max_length is not None and len(payload) > self.max_length:
+            payload = f"{payload[:self.max_length]}... ({len(payload)} chars)"
+        arrow = ">>" if message["direction"] == "out" else "<<"
+        header = f"[{message['time'].strftime('%H:%M:%S.%f')[:-3]}] {arrow} {message['event'] or '?'}"
+        if message.get("error"):
+            header += f" (unparsed: {message['error']})"
+        return f"{header}\n{payload}"
+
+    def _record(self, direction: str, frame: str, event: str | None = None, error: str | None = None) -> dict:
+        message = {
+            "direction": direction,
+            "event": event,
+            "frame": frame,
+            "payload": _loads(frame),
+            "error": error,
+            "time": datetime.now(timezone.utc),
+        }
+        self._history.append(message)
+        if self.matches(message):
+            self.printer(self.format(message))
+        return message
+
+    def _on_frame(self, frame: str) -> None:
+        report = parse_frame(frame, self._context)
+        if report["awaiting_payload"]:
+            # The payload of a `451-` header is sent in the next frame, both are shown as a single message
+            self._context = report["event"]
+            return
+        context, self._context = self._context, None
+        if context is None and report["event"] is None and self.unparsed:
+            # Payloads of known events are only tapped when they failed to parse, the unparsed
+            # subscription records them with their event and error
+            return
+        self._record("in", frame, report["event"])
+
+    async def _tap(self, timeout: timedelta | None):
+        async for frame in await self.client.tap_raw(Validator(), timeout):
+            self._on_frame(frame)
+
+    async def _tap_unparsed(self, timeout: timedelta | None):
+        async for message in await self.client.subscribe_unparsed(timeout):
+            self._record("in", message["payload"], message["event"], message["error"])
+
+    @property
+    def running(self) -> bool:
+        """Whether the console is tapping the messages of the server"""
+        return any(not task.done() for task in self._tasks)
+
+    async def start(self, timeout: timedelta | None = None) -> None:
+        """Starts tapping (and printing) the messages of the server in the background"""
+        if self.running:
+            return
+        self._tasks = [asyncio.create_task(self._tap(timeout))]
+        if self.unparsed:
+            self._tasks.append(asyncio.create_task(self._tap_unparsed(timeout)))
+
+    async def stop(self) -> None:
+        """Stops tapping the messages of the server, the history is kept"""
+        for task in self._tasks:
+            task.cancel()
+        await asyncio.gather(*self._tasks, return_exceptions=True)
+        self._tasks = []
+        self._context = None
+
+    async def watch(self, seconds: float) -> list[dict]:
+        """Taps the messages of the server for `seconds` and returns the ones matching the filters"""
+        start = datetime.now(timezone.utc)
+        running = self.running
+        await self.start()
+        try:
+            await asyncio.sleep(seconds)
+        finally:
+            if not running:
+                await self.stop()
+        return [m for m in self._history if m["time"] >= start and self.matches(m)]
+
+    async def send(self, message: str) -> None:
+        """Sends a raw message (e.g. `42["signals/subscribe"]`) without waiting for a response"""
+        await self.client.send_raw_message(message)
+        self._record("out", message, self._event(message))
+
+    async def request(self, message: str, validator: Validator, timeout: timedelta = timedelta(seconds=10)) -> str:
+        """Sends a raw message and waits for the first message accepted by the validator, both are recorded"""
+        self._record("out", message, self._event(message))
+        response = await self.client.create_raw_order_with_timout(message, validator, timeout)
+        self._record("in", response)
+        return response
+
+    @staticmethod
+    def _event(message: str) -> str | None:
+        payload = _loads(message[2:]) if message.startswith("42") else None
+        return payload[0] if isinstance(payload, list) and payload and isinstance(payload[0], str) else None
+
+    def history(self, n: int | None = 20, event: str | None = None, filtered: bool = True) -> list[dict]:
+        """
+        Returns the last `n` messages (all of them if None), oldest first.
+
+        Args:
+            n (int | None): Number of messages to return
+            event (str | None): Regex pattern, only the messages whose event matches it are returned
+            filtered (bool): Only return the messages passing the filters of the console. Defaults to True
+        """
+        messages = [
+            m for m in self._history
+            if (not filtered or self.matches(m)) and (event is None or re.search(event, m["event"] or ""))
+        ]
+        return messages if n is None else messages[-n:]
+
+    def events(self) -> dict[str, int]:
+        """Returns how many messages of each event were seen, to spot the undocumented ones"""
+        counts: dict[str, int] = {}
+        for message in self._history:
+            key = message["event"] or "?"
+            counts[key] = counts.get(key, 0) + 1
+        return counts
+
+    def clear(self) -> None:
+        """Clears the history"""
+        self._history.clear()
+
+    async def __aenter__(self):
+        await self.start()
+        return self
+
+    async def __aexit__(self, *_):
+        await self.stop()
@@ -0,0 +1,89 @@
+"""
+Replay of candle recordings, made with the `record` method of the candle subscriptions, with checkpoints so a long
+job over a big recording (e.g. feature extraction) can resume where it stopped after an interruption. Recordings
+compressed with gzip or zstd are decompressed on the fly, `seek` reads them from the start.
+
+Example:
+    ```python
+    # Recording
+    stream = await api.subscribe_symbol("EURUSD_otc")
+    async for candle in stream.record("eurusd.jsonl"):
+        ...
+
+    # Research job, resumes from the last saved checkpoint if it was interrupted
+    replay = CandleReplay("eurusd.jsonl", checkpoint_file="eurusd.checkpoint.json")
+    for i, candle in enumerate(replay):
+        process(candle)
+        if i % 10_000 == 0:
+            replay.save_checkpoint()
+    ```
+"""
+from __future__ import annotations
+
+from datetime import datetime, timezone
+from typing import Optional
+
+import json
+import os
+
+from BinaryOptionsToolsV2 import CandleReplay as _CandleReplay
+
+
+class CandleReplay:
+    """Iterator over the candles (as dictionaries) of a recording, in the order they were recorded"""
+
+    def __init__(self, path: str, checkpoint: Optional[dict] = None, checkpoint_file: Optional[str] = None):
+        """
+        Args:
+            path (str): Recording to replay, one json candle per line, optionally compressed
+            checkpoint (dict, optional): Position returned by `checkpoint` to resume from
+            checkpoint_file (str, optional): File `save_checkpoint` writes the position to, the replay resumes from
+                it if it exists and no `checkpoint` is given
+        """
+        self.checkpoint_file = checkpoint_file
+        if checkpoint is None and checkpoint_file is not None and os.path.exists(checkpoint_file):
+            with open(checkpoint_file) as f:
+                checkpoint = json.load(f)
+        self._replay = _CandleReplay(path, json.dumps(checkpoint) if checkpoint is not None else None)
+
+    @property
+    def path(self) -> str:
+        return self._replay.path
+
+    @property
+    def compression(self) -> str:
+        "Compression of the recording: none, gzip or zstd"
+        return self._replay.compression
+
+    def __iter__(self):
+        return self
+
+    def __next__(self) -> dict:
+        return json.loads(next(self._replay))
+
+    def checkpoint(self) -> dict:
+        "Returns the position of the replay: the byte `offset` of the next candle and the `time` of the last one read"
+        return json.loads(self._replay.checkpoint())
+
+    def save_checkpoint(self, checkpoint_file: Optional[str] = None) -> dict:
+        "Writes the position of the replay to `checkpoint_file` (defaults to the one given to the constructor)"
+        checkpoint_file = checkpoint_file or self.checkpoint_file
+        if checkpoint_file is None:
+            raise ValueError("No checkpoint file to save the position of the replay to")
+        checkpoint = self.checkpoint()
+        tmp = f"{checkpoint_file}.tmp"
+        with open(tmp, "w") as f:
+            json.dump(checkpoint, f)
+        os.replace(tmp, checkpoint_file)
+        return checkpoint
+
+    def seek(self, timestamp: datetime | float) -> None:
+        """
+        Moves to the first candle at or after `timestamp` (a unix timestamp or a datetime, naive datetimes are in UTC),
+        without reading the candles before it. The candles of the recording must be in chronological order.
+        """
+        if isinstance(timestamp, datetime):
+            timestamp = timestamp.replace(tzinfo=timezone.utc) if timestamp.tzinfo is None else timestamp
+        else:
+            timestamp = datetime.fromtimestamp(timestamp, timezone.utc)
+        self._replay.seek(timestamp)
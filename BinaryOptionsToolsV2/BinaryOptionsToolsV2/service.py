@@ -0,0 +1,384 @@
+"""
+Long-lived service runner.
+
+Runs a `PocketOptionAsync` client together with a strategy as a daemon-friendly service:
+    * SIGTERM / SIGINT stop the strategy and close the client cleanly
+    * SIGHUP reloads the configuration (profile or JSON file) and restarts the strategy with a new client
+    * Optionally, an authenticated HTTPS control API to start / stop the strategy, update the risk limits and fetch
+      the status remotely (see `BinaryOptionsToolsV2.control`)
+
+It can be used from python with `serve(...)` or from the command line:
+    ```
+    POCKET_OPTION_SSID="..." python -m BinaryOptionsToolsV2.service --strategy my_bot:run --profile prod
+    ```
+
+Every option of the command line can also be set with a `BOT_` environment variable (e.g. `BOT_STRATEGY`,
+`BOT_CONTROL_PORT`). With `--twelve-factor` (or `BOT_TWELVE_FACTOR=1`) the service runs as a "12-factor" app:
+the logs are written to stdout as JSON (see `start_logs_from_env`), the client config is read from the environment
+(see `Config.from_env`) unless a config file or profile is given, and no file is written unless configured.
+    ```
+    docker run -e POCKET_OPTION_SSID="..." -e BOT_STRATEGY=my_bot:run -e BOT_TWELVE_FACTOR=1 my-bot
+    ```
+
+On Windows, `BinaryOptionsToolsV2.windows_service` installs the same service in the Service Control Manager.
+"""
+from __future__ import annotations
+from BinaryOptionsToolsV2.config import Config
+from BinaryOptionsToolsV2.pocketoption.asyncronous import PocketOptionAsync
+from BinaryOptionsToolsV2.tracing import Logger, start_logs_from_env
+from BinaryOptionsToolsV2.control import ControlServer, TOKEN_ENV
+
+from typing import Any, Awaitable, Callable, Optional
+
+import argparse
+import asyncio
+import importlib
+import inspect
+import os
+import signal
+import sys
+import time
+
+SSID_ENV = "POCKET_OPTION_SSID"
+ENV_PREFIX = "BOT_"
+
+Strategy = Callable[[PocketOptionAsync], Awaitable[Any]]
+
+
+class Service:
+    """
+    Keeps a client and a strategy running until a termination signal is received.
+
+    The strategy can either be an async function taking the client as its only argument, or an object
+    with an async `run(client)` method and optional `on_reload(config)` / `on_shutdown()` hooks.
+    """
+
+    def __init__(
+        self,
+        ssid: str,
+        strategy: Strategy | Any,
+        config: Config | dict | str | None = None,
+        profile: Optional[str] = None,
+        profiles_path: Optional[str] = None,
+        url: Optional[str] = None,
+        healthcheck_port: Optional[int] = None,
+        control: Optional[dict] = None,
+    ):
+        self.ssid = ssid
+        self.strategy = strategy
+        self.profile = profile
+        self.profiles_path = profiles_path
+        self.url = url
+        self.healthcheck_port = healthcheck_port
+        self.logger = Logger()
+        self._config = config
+        self._client: Optional[PocketOptionAsync] = None
+        self._task: Optional[asyncio.Task] = None
+        self._stop = asyncio.Event()
+        self._reload = asyncio.Event()
+        self._changed = asyncio.Event()
+        self._paused = False
+        self._started_at: Optional[float] = None
+        self.error: Optional[BaseException] = None
+        self._control = ControlServer(self, **control) if control is not None else None
+
+    def _load_config(self) -> Config | dict | str | None:
+        if self.profile is not None:
+            return Config.from_profile(self.profile, self.profiles_path)
+        if isinstance(self._config, str) and os.path.isfile(self._config):
+            with open(self._config) as f:
+                return Config.from_json(f.read())
+        if isinstance(self._config, Config):
+            # A locked config can't be reused by a new client
+            return Config.from_dict(self._config.to_dict())
+        return self._config
+
+    def _run_strategy(self, client: PocketOptionAsync) -> Awaitable[Any]:
+        run = getattr(self.strategy, "run", self.strategy)
+        return run(client)
+
+    async def _call_hook(self, name: str, *args):
+        hook = getattr(self.strategy, name, None)
+        if hook is None:
+            return
+        result = hook(*args)
+        if inspect.isawaitable(result):
+            await result
+
+    def request_stop(self):
+        self.logger.info("Received termination signal, shutting down service")
+        self._stop.set()
+
+    def request_reload(self):
+        self.logger.info("Received reload signal, reloading configuration")
+        self._reload.set()
+
+    @property
+    def client(self) -> Optional[PocketOptionAsync]:
+        """Client used by the strategy, None while the service is restarting"""
+        return self._client
+
+    @property
+    def strategy_running(self) -> bool:
+        return self._task is not None and not self._task.done()
+
+    async def start_strategy(self):
+        """Starts the strategy again after it was stopped with `stop_strategy`, the client is reused"""
+        if self.strategy_running:
+            return
+        self._paused = False
+        if self._client is None:
+            await self._start()
+        else:
+            self._task = asyncio.create_task(self._run_strategy(self._client))
+            self._started_at = time.time()
+        self.logger.info("Strategy started")
+        self._changed.set()
+
+    async def stop_strategy(self):
+        """Stops the strategy without stopping the service, the client stays connected"""
+        self._paused = True
+        task, self._task = self._task, None
+        self._changed.set()
+        if task is not None and not task.done():
+            task.cancel()
+            try:
+                await task
+            except asyncio.CancelledError:
+                pass
+        self._started_at = None
+        self.logger.info("Strategy stopped")
+
+    async def status(self) -> dict:
+        """Returns the state of the service, the strategy and the client"""
+        status = {
+            "strategy_running": self.strategy_running,
+            "paused": self._paused,
+            "strategy_uptime": time.time() - self._started_at if self._started_at and self.strategy_running else None,
+            "profile": self.profile,
+            "client": None,
+        }
+        client = self._client
+        if client is not None:
+            status["client"] = {
+                "balance": await client.balance(),
+                "opened_deals": len(await client.opened_deals()),
+                "risk_rules": client.risk_rules(),
+                "drawdown": client.drawdown_state(),
+                "journal": client.journal_stats(),
+                "health": await client.health(),
+            }
+        return status
+
+    def _install_signal_handlers(self):
+        loop = asyncio.get_running_loop()
+        handlers = {signal.SIGTERM: self.request_stop, signal.SIGINT: self.request_stop}
+        if hasattr(signal, "SIGHUP"):
+            handlers[signal.SIGHUP] = self.request_reload
+        for sig, handler in handlers.items():
+            try:
+                loop.add_signal_handler(sig, handler)
+            except (NotImplementedError, RuntimeError):
+                # Windows event loops don't support `add_signal_handler`
+                try:
+                    signal.signal(sig, lambda *_, h=handler: loop.call_soon_threadsafe(h))
+                except ValueError:
+                    # Only the main thread handles the signals, e.g. a Windows service is stopped with `request_stop`
+                    return
+
+    async def _start(self):
+        config = self._load_config()
+        self._client = PocketOptionAsync(self.ssid, url=self.url, config=config)
+        if self.healthcheck_port is not None:
+            await self._client.start_healthcheck(self.healthcheck_port)
+        self._task = asyncio.create_task(self._run_strategy(self._client))
+        self._started_at = time.time()
+        return config
+
+    async def _stop_strategy(self):
+        if self._task is not None and not self._task.done():
+            self._task.cancel()
+            try:
+                await self._task
+            except asyncio.CancelledError:
+                pass
+        self._task = None
+        self._started_at = None
+        client, self._client = self._client, None
+        if client is not None:
+            # Closes the websocket and stops the healthcheck endpoint, so the next client can bind its port
+            await client.shutdown()
+
+    async def run(self):
+        """Runs the service until SIGTERM / SIGINT is received or the strategy finishes."""
+        self._install_signal_handlers()
+        await self._start()
+        if self._control is not None:
+            await self._control.start()
+        try:
+            while not self._stop.is_set():
+                waiters = [
+                    asyncio.create_task(self._stop.wait()),
+                    asyncio.create_task(self._reload.wait()),
+                    asyncio.create_task(self._changed.wait()),
+                ]
+                strategy = self._task
+                done, pending = await asyncio.wait(
+                    waiters + ([strategy] if strategy is not None else []), return_when=asyncio.FIRST_COMPLETED
+                )
+                for task in pending:
+                    if task is not strategy:
+                        task.cancel()
+                if self._changed.is_set():
+                    # The strategy was started or stopped through the control API
+                    self._changed.clear()
+                    continue
+                if strategy is not None and strategy in done:
+                    if strategy.exception() is not None:
+                        self.error = strategy.exception()
+                        self.logger.error(f"Strategy stopped with an error, {strategy.exception()}")
+                    else:
+                        self.logger.info("Strategy finished, stopping service")
+                    break
+                if self._reload.is_set():
+                    self._reload.clear()
+                    await self._stop_strategy()
+                    self._paused = False
+                    config = await self._start()
+                    await self._call_hook("on_reload", config)
+        finally:
+            if self._control is not None:
+                await self._control.stop()
+            await self._stop_strategy()
+            await self._call_hook("on_shutdown")
+            self.logger.info("Service stopped")
+
+
+def serve(
+    ssid: str,
+    strategy: Strategy | Any,
+    config: Config | dict | str | None = None,
+    profile: Optional[str] = None,
+    profiles_path: Optional[str] = None,
+    url: Optional[str] = None,
+    healthcheck_port: Optional[int] = None,
+    control: Optional[dict] = None,
+) -> None:
+    """
+    Runs the client and the strategy as a long-lived service, blocking until SIGTERM / SIGINT is received.
+
+    Args:
+        ssid (str): Session ID used to connect to Pocket Option
+        strategy: Async function taking the client, or object with an async `run(client)` method and optional
+            `on_reload(config)` / `on_shutdown()` hooks
+        config (Config | dict | str, optional): Config object, dict, JSON string or path to a JSON file,
+            reloaded on SIGHUP when it's a path
+        profile (str, optional): Name of the config profile to load (takes precedence over `config`), reloaded on SIGHUP
+        profiles_path (str, optional): Path of the profiles file
+        url (str, optional): Custom websocket url
+        healthcheck_port (int, optional): If set, starts the HTTP healthcheck endpoint on this port
+        control (dict, optional): If set, exposes the control API, with the arguments of `ControlServer`
+            (`token`, `port`, `host`, `certfile`, `keyfile`, `insecure`)
+
+    Example:
+        ```python
+        from BinaryOptionsToolsV2.service import serve
+
+        async def strategy(client):
+            while True:
+                await client.buy("EURUSD_otc", 1.0, 60, check_win=True)
+
+        serve(ssid, strategy, profile="prod", healthcheck_port=8080)
+        ```
+    """
+    service = Service(ssid, strategy, config, profile, profiles_path, url, healthcheck_port, control)
+    asyncio.run(service.run())
+
+
+def _load_strategy(path: str):
+    module, _, attr = path.partition(":")
+    strategy = importlib.import_module(module)
+    for part in (attr or "run").split("."):
+        strategy = getattr(strategy, part)
+    return strategy() if inspect.isclass(strategy) else strategy
+
+
+def _env(name: str, default: Any = None) -> Any:
+    return os.environ.get(f"{ENV_PREFIX}{name}", default)
+
+
+def _env_flag(name: str) -> bool:
+    return _env(name, "").strip().lower() in ("1", "true", "yes", "on")
+
+
+def _parser() -> argparse.ArgumentParser:
+    parser = argparse.ArgumentParser(description="Run a BinaryOptionsToolsV2 strategy as a service")
+    parser.add_argument("--strategy", default=_env("STRATEGY"), help="Strategy to run, as 'module:attribute' ($BOT_STRATEGY)")
+    parser.add_argument("--ssid", default=os.environ.get(SSID_ENV), help=f"Session ID (defaults to ${SSID_ENV})")
+    parser.add_argument("--config", default=_env("CONFIG"), help="Path to a JSON config file ($BOT_CONFIG)")
+    parser.add_argument("--profile", default=_env("PROFILE"), help="Name of the config profile to load ($BOT_PROFILE)")
+    parser.add_argument("--profiles-file", default=_env("PROFILES_FILE"), help="Path of the profiles file ($BOT_PROFILES_FILE)")
+    parser.add_argument("--url", default=_env("URL"), help="Custom websocket url ($BOT_URL)")
+    parser.add_argument("--healthcheck-port", type=int, default=_env("HEALTHCHECK_PORT"), help="Port of the healthcheck endpoint ($BOT_HEALTHCHECK_PORT)")
+    parser.add_argument("--control-port", type=int, default=_env("CONTROL_PORT"), help="Port of the control API ($BOT_CONTROL_PORT)")
+    parser.add_argument("--control-host", default=_env("CONTROL_HOST", "0.0.0.0"), help="Interface of the control API ($BOT_CONTROL_HOST)")
+    parser.add_argument("--control-token", default=os.environ.get(TOKEN_ENV), help=f"Token of the control API (defaults to ${TOKEN_ENV})")
+    parser.add_argument("--control-cert", default=_env("CONTROL_CERT"), help="TLS certificate of the control API ($BOT_CONTROL_CERT)")
+    parser.add_argument("--control-key", default=_env("CONTROL_KEY"), help="Private key of the TLS certificate ($BOT_CONTROL_KEY)")
+    parser.add_argument("--control-insecure", action="store_true", default=_env_flag("CONTROL_INSECURE"), help="Serve the control API without TLS, local testing only ($BOT_CONTROL_INSECURE)")
+    parser.add_argument("--twelve-factor", action="store_true", default=_env_flag("TWELVE_FACTOR"), help="JSON logs to stdout and config from the environment ($BOT_TWELVE_FACTOR)")
+    return parser
+
+
+def parse_args(argv: Optional[list[str]] = None) -> argparse.Namespace:
+    """Parses the command line options of `main`, exits with the usage if they're invalid."""
+    parser = _parser()
+    args = parser.parse_args(argv)
+    if args.strategy is None:
+        parser.error("a strategy is required, use --strategy or set $BOT_STRATEGY")
+    if args.ssid is None:
+        parser.error(f"an ssid is required, use --ssid or set ${SSID_ENV}")
+    if args.control_port is not None and not args.control_token:
+        parser.error(f"the control API requires a token, use --control-token or set ${TOKEN_ENV}")
+    return args
+
+
+def build_service(argv: Optional[list[str]] = None) -> Service:
+    """Creates the `Service` of the command line options, the strategy is imported from the working directory."""
+    args = parse_args(argv)
+    config = args.config
+    if args.twelve_factor:
+        start_logs_from_env()
+        if config is None and args.profile is None:
+            config = Config.from_env(ENV_PREFIX)
+    control = None
+    if args.control_port is not None:
+        control = {
+            "token": args.control_token,
+            "port": args.control_port,
+            "host": args.control_host,
+            "certfile": args.control_cert,
+            "keyfile": args.control_key,
+            "insecure": args.control_insecure,
+        }
+    sys.path.insert(0, os.getcwd())
+    return Service(
+        args.ssid,
+        _load_strategy(args.strategy),
+        config=config,
+        profile=args.profile,
+        profiles_path=args.profiles_file,
+        url=args.url,
+        healthcheck_port=args.healthcheck_port,
+        control=control,
+    )
+
+
+def main(argv: Optional[list[str]] = None):
+    """Command line entry point for `serve`, every option defaults to its `BOT_` environment variable."""
+    service = build_service(argv)
+    asyncio.run(service.run())
+
+
+if __name__ == "__main__":
+    main()
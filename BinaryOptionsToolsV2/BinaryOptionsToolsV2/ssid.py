@@ -0,0 +1,62 @@
+"""
+Validation of the session id (SSID) the clients log in with, the most common reason a client fails to connect. The
+session can be pasted the way it was copied from the browser: the whole `42["auth",...]` message sent by the site
+(devtools > Network > WS > Messages), even quoted as a string, its json object, the cookies of the site or just the
+session token, and `parse_ssid` returns the normalized SSID with a clear error if something is missing.
+
+Example:
+    ```python
+    info = parse_ssid(input("Paste your SSID: "))
+    print(f"{'Demo' if info.demo else 'Real'} account {info.uid}")
+    api = PocketOptionAsync(info.ssid)
+    ```
+"""
+from __future__ import annotations
+
+from BinaryOptionsToolsV2 import parse_ssid as _parse_ssid
+
+from dataclasses import dataclass
+from datetime import datetime
+
+import json
+
+
+@dataclass(frozen=True)
+class SsidInfo:
+    "Account and session of an SSID, `ssid` is the normalized `42[\"auth\",...]` message accepted by the clients"
+    demo: bool
+    uid: int
+    platform: int
+    session_id: str
+    ip_address: str | None
+    user_agent: str
+    last_activity: datetime | None
+    ssid: str
+
+    def __str__(self) -> str:
+        return self.ssid
+
+
+def parse_ssid(raw: str, uid: int | None = None, demo: bool | None = None) -> SsidInfo:
+    """
+    Validates and normalizes a session string copied from the browser.
+
+    Args:
+        raw (str): The `42["auth",...]` message, its json object, the cookies of the site (with the `ci_session`
+            cookie) or the session token
+        uid (int, optional): Id of the account, required for the cookies and the tokens which don't have it
+        demo (bool, optional): Whether it's the session of a demo account, inferred from the session if not given
+
+    Returns:
+        SsidInfo: The account, its session and the normalized `ssid` to create the clients with
+
+    Raises:
+        Exception: With the reason the session is invalid (uid missing, wrong message copied, cookie missing...)
+    """
+    return _ssid_info(json.loads(_parse_ssid(raw, uid, demo)))
+
+
+def _ssid_info(info: dict) -> SsidInfo:
+    if info["last_activity"] is not None:
+        info["last_activity"] = datetime.fromisoformat(info["last_activity"].replace("Z", "+00:00"))
+    return SsidInfo(**info)
@@ -0,0 +1,40 @@
+"""
+Directories the library writes its files to: data (journals, recordings...), logs and cache. They default to the
+directories of the platform (e.g. `~/.local/share/binaryoptionstools` on Linux), each one can be overridden with the
+`BOT_DATA_DIR`, `BOT_LOG_DIR` and `BOT_CACHE_DIR` environment variables or `set_storage_dirs`, and they are created
+when a file is written to them.
+"""
+from __future__ import annotations
+
+from pathlib import Path
+from typing import Optional
+
+import json
+
+from BinaryOptionsToolsV2 import storage_dirs as _storage_dirs
+from BinaryOptionsToolsV2 import set_storage_dirs as _set_storage_dirs
+
+
+def storage_dirs() -> dict[str, Path]:
+    "Returns the `data_dir`, `log_dir` and `cache_dir` used by the library"
+    return {name: Path(path) for name, path in json.loads(_storage_dirs()).items()}
+
+
+def set_storage_dirs(
+    root: Optional[str | Path] = None,
+    data_dir: Optional[str | Path] = None,
+    log_dir: Optional[str | Path] = None,
+    cache_dir: Optional[str | Path] = None,
+) -> None:
+    """
+    Changes the directories used by the library.
+
+    Args:
+        root (str | Path, optional): Directory holding the data, with the `logs` and `cache` directories inside it
+        data_dir (str | Path, optional): Data directory, defaults to `root` (or the current data directory)
+        log_dir (str | Path, optional): Log directory, defaults to `root/logs` (or the current log directory)
+        cache_dir (str | Path, optional): Cache directory, defaults to `root/cache` (or the current cache directory)
+    """
+    _set_storage_dirs(
+        *(str(path) if path is not None else None for path in (root, data_dir, log_dir, cache_dir))
+    )
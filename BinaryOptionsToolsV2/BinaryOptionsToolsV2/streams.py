@@ -0,0 +1,69 @@
+from __future__ import annotations
+
+import json
+
+from BinaryOptionsToolsV2 import merge_streams as _merge_streams
+
+
+def _unwrap(iterator):
+    # Subscriptions of the clients and log iterators wrap the iterators of the library
+    return getattr(iterator, "subscription", iterator)
+
+
+def _parse(item: str):
+    try:
+        return json.loads(item)
+    except ValueError:
+        return item
+
+
+class MergedSubscription:
+    def __init__(self, subscription):
+        """Iterator over `(tag, item)` tuples of multiple subscriptions, supports both `async for` and `for`"""
+        self.subscription = subscription
+
+    def __aiter__(self):
+        return self
+
+    async def __anext__(self):
+        tag, item = await self.subscription.__anext__()
+        return tag, _parse(item)
+
+    def __iter__(self):
+        return self
+
+    def __next__(self):
+        tag, item = next(self.subscription)
+        return tag, _parse(item)
+
+
+def merge_streams(iterators: list | dict) -> MergedSubscription:
+    """
+    Merges multiple subscriptions (candles, raw iterators, account events, logs...) into a single iterator
+    yielding their items in arrival order, so they can be consumed in one loop.
+
+    Args:
+        iterators (list | dict): Subscriptions to merge, if it's a dictionary its keys are used as tags,
+            otherwise the tag of each subscription is its position (as a string).
+
+    Returns:
+        MergedSubscription: Iterator yielding `(tag, item)` tuples, items are parsed from json when possible.
+            It ends once every subscription ended, a subscription ends at its first error (e.g. a timeout).
+
+    Example:
+        ```python
+        candles = await api.subscribe_symbol("EURUSD_otc")
+        events = await api.subscribe_account_events()
+        async for tag, item in merge_streams({"candles": candles, "events": events}):
+            if tag == "candles":
+                print(f"Candle: {item}")
+            else:
+                print(f"Account event: {item['kind']}")
+        ```
+    """
+    if isinstance(iterators, dict):
+        tags = [str(tag) for tag in iterators.keys()]
+        iterators = list(iterators.values())
+    else:
+        tags = None
+    return MergedSubscription(_merge_streams([_unwrap(iterator) for iterator in iterators], tags))
@@ -1,10 +1,21 @@
+from __future__ import annotations
+
 import json
+import os
 from BinaryOptionsToolsV2 import start_tracing
+from BinaryOptionsToolsV2 import redact_secrets as _redact_secrets
+from BinaryOptionsToolsV2 import add_redaction_pattern as _add_redaction_pattern
+from BinaryOptionsToolsV2 import verify_audit_file as _verify_audit_file
 from BinaryOptionsToolsV2 import Logger as RustLogger
 from BinaryOptionsToolsV2 import LogBuilder as RustLogBuilder
+from BinaryOptionsToolsV2 import StreamLogsLayer as RustStreamLogsLayer
 
 from datetime import timedelta
 
+LOG_LEVEL_ENV = "BOT_LOG_LEVEL"
+LOG_FORMAT_ENV = "BOT_LOG_FORMAT"
+LOG_DIR_ENV = "BOT_LOG_DIR"
+
 class LogSubscription:
     def __init__(self, subscription):
         self.subscription = subscription
@@ -13,23 +24,66 @@ class LogSubscription:
         return self
         
     async def __anext__(self):
-        return json.loads(await anext(self.subscription))
+        return json.loads(await self.subscription.__anext__())
     
     def __iter__(self):
         return self
         
     def __next__(self):
-        return json.loads(next(self.subscription))        
+        return json.loads(next(self.subscription))
+
+    @property
+    def timeout(self) -> timedelta | None:
+        "Timeout of this iterator, it only applies to this iterator"
+        return self.subscription.timeout
+
+    def set_timeout(self, timeout: timedelta | None = None):
+        "Changes the timeout of this iterator (`None` disables it) and restarts its deadline"
+        self.subscription.set_timeout(timeout)
 
+    def reset_timeout(self):
+        "Restarts the deadline of this iterator, it has a full timeout to receive the next log"
+        self.subscription.reset_timeout()
 
-def start_logs(path: str, level: str = "DEBUG", terminal: bool = True, layers: list = None):
+    def extend(self, timeout: timedelta):
+        "Gives this iterator `timeout` more to receive the next log, without changing its timeout"
+        self.subscription.extend(timeout)
+
+
+class StreamLogsLayer:
+    """
+    A stream of logs that can feed any number of iterators, each one with its own timeout and filters.
+    Install it with `start_logs(..., layers=[layer])` or `LogBuilder.add_stream_layer(layer)`,
+    iterators can be created before or after that.
+    """
+    def __init__(self, level: str = "DEBUG"):
+        self.layer = RustStreamLogsLayer(level)
+
+    def create_iterator(
+        self,
+        timeout: timedelta | None = None,
+        targets: list[str] | None = None,
+        filters: list[str] | None = None,
+    ) -> LogSubscription:
+        "Creates a new iterator over the logs of this layer, see `LogBuilder.create_logs_iterator` for the arguments"
+        return LogSubscription(self.layer.create_iterator(timeout, targets, filters))
+
+    @property
+    def consumers(self) -> int:
+        "Number of iterators currently receiving the logs of this layer"
+        return self.layer.consumers()
+
+
+def start_logs(path: str | None, level: str = "DEBUG", terminal: bool = True, layers: list = None, json: bool = False):
     """
     Initialize logging system for the application.
 
     Args:
-        path (str): Path where log files will be stored.
+        path (str | None): Path where log files will be stored, `None` doesn't write any file (e.g. read-only containers).
         level (str): Logging level (default is "DEBUG").
         terminal (bool): Whether to display logs in the terminal (default is True).
+        layers (list[StreamLogsLayer]): Stream layers to install, all of them are installed or an exception is raised.
+        json (bool): Write the terminal logs to stdout as one JSON object per line (default is False).
 
     Returns:
         None
@@ -39,12 +93,81 @@ def start_logs(path: str, level: str = "DEBUG", terminal: bool = True, layers: l
     """
     if layers is None:
         layers = []
+    layers = [getattr(layer, "layer", layer) for layer in layers]
     try:
-        start_tracing(path, level, terminal, layers)
+        start_tracing(path, level, terminal, layers, json)
     except Exception as e:
         print(f"Error starting logs, {e}")
-        
-        
+
+
+def start_logs_from_env(layers: list = None):
+    """
+    Initialize the logs of a "12-factor" app (e.g. a docker container) from the environment:
+    logs are written to stdout, as JSON by default, and no file is written unless `$BOT_LOG_DIR` is set.
+
+    Environment variables:
+        BOT_LOG_LEVEL: Logging level (default is "INFO").
+        BOT_LOG_FORMAT: "json" (default) or "text".
+        BOT_LOG_DIR: Directory of the log files, no file is written if it's not set.
+
+    Args:
+        layers (list[StreamLogsLayer]): Stream layers to install.
+    """
+    log_format = os.environ.get(LOG_FORMAT_ENV, "json").lower()
+    if log_format not in ("json", "text"):
+        raise ValueError(f"Invalid ${LOG_FORMAT_ENV} '{log_format}', expected 'json' or 'text'")
+    start_logs(
+        os.environ.get(LOG_DIR_ENV) or None,
+        os.environ.get(LOG_LEVEL_ENV, "INFO"),
+        terminal=True,
+        layers=layers,
+        json=log_format == "json",
+    )
+
+
+
+def redact_secrets(enabled: bool = True):
+    """
+    Enable or disable the redaction of secrets (SSID, auth frames, tokens) in all the logs,
+    stream logs and raw messages. Redaction is enabled by default, disable it only for deep debugging.
+
+    Args:
+        enabled (bool): Whether secrets should be redacted (default is True).
+    """
+    _redact_secrets(enabled)
+
+
+def add_redaction_pattern(pattern: str):
+    """
+    Register an extra regex pattern whose matches will be replaced by "[REDACTED]" in the logs. The whole match is
+    replaced, capture groups included, except a group named `prefix` which is kept:
+    `(?P<prefix>apikey=)\\w+` turns `apikey=abc` into `apikey=[REDACTED]`.
+
+    Args:
+        pattern (str): Regex pattern to redact.
+
+    Raises:
+        Exception: If the pattern is not a valid regex.
+    """
+    _add_redaction_pattern(pattern)
+
+
+def verify_audit_file(path: str = "audit.log") -> int:
+    """
+    Verify the hash chain of an audit file created with `LogBuilder.audit_file`.
+
+    Args:
+        path (str): Path of the audit file (default is "audit.log").
+
+    Returns:
+        int: The number of records in the file.
+
+    Raises:
+        Exception: If any record was modified, removed or reordered.
+    """
+    return _verify_audit_file(path)
+
+
 class Logger:
     """
     A logger class wrapping the RustLogger functionality.
@@ -102,18 +225,37 @@ class LogBuilder:
     def __init__(self):
         self.builder = RustLogBuilder()
 
-    def create_logs_iterator(self, level: str = "DEBUG", timeout: None | timedelta = None) -> LogSubscription:
+    def create_logs_iterator(
+        self,
+        level: str = "DEBUG",
+        timeout: None | timedelta = None,
+        targets: list[str] | None = None,
+        filters: list[str] | None = None,
+    ) -> LogSubscription:
         """
         Create a new logs iterator with the specified level and timeout.
 
         Args:
             level (str): The logging level (default is "DEBUG").
             timeout (None | timedelta): Optional timeout for the iterator.
+            targets (list[str] | None): Only the logs whose target starts with one of the targets are sent (default is all of them).
+            filters (list[str] | None): Conditions on the fields of the logs, all of them must match.
+                Supported conditions are `field == value`, `field != value` and `field` (the field exists),
+                e.g. `['asset == "EURUSD"']`. Invalid conditions raise an exception.
 
         Returns:
             StreamLogsIterator: A new StreamLogsIterator instance that supports both asyncronous and syncronous iterators.
         """
-        return LogSubscription(self.builder.create_logs_iterator(level, timeout))
+        return LogSubscription(self.builder.create_logs_iterator(level, timeout, targets, filters))
+
+    def add_stream_layer(self, layer: StreamLogsLayer):
+        """
+        Install a `StreamLogsLayer`, all of its iterators receive the logs once the builder is built.
+
+        Args:
+            layer (StreamLogsLayer): The layer to install.
+        """
+        self.builder.add_stream_layer(getattr(layer, "layer", layer))
 
     def log_file(self, path: str = "logs.log", level: str = "DEBUG"):
         """
@@ -125,14 +267,25 @@ class LogBuilder:
         """
         self.builder.log_file(path, level)
 
-    def terminal(self, level: str = "DEBUG"):
+    def audit_file(self, path: str = "audit.log"):
+        """
+        Configure a tamper-evident audit log. Every order request / response and config change
+        is appended to this file as a hash-chained record, separate from the operational logs.
+
+        Args:
+            path (str): The path of the audit file (default is "audit.log").
+        """
+        self.builder.audit_file(path)
+
+    def terminal(self, level: str = "DEBUG", json: bool = False):
         """
         Configure logging to the terminal.
 
         Args:
             level (str): The minimum log level for this terminal handler.
+            json (bool): Write the logs to stdout as one JSON object per line (default is False).
         """
-        self.builder.terminal(level)
+        self.builder.terminal(level, json)
 
     def build(self):
         """
@@ -1,3 +1,5 @@
+from __future__ import annotations
+
 from typing import List
 
 class Validator:
@@ -256,6 +258,25 @@ class Validator:
             True if message matches the validator's conditions, False otherwise
         """
         return self._validator.check(message)
+
+    def to_json(self) -> str:
+        """
+        Serializes the validator to JSON so it can be stored and rebuilt later with `from_json`.
+        
+        Raises:
+            Exception: If the validator is (or contains) a custom validator
+        """
+        return self._validator.to_json()
+
+    @staticmethod
+    def from_json(json: str) -> 'Validator':
+        """
+        Rebuilds a validator serialized with `to_json`.
+        """
+        from BinaryOptionsToolsV2 import RawValidator
+        v = Validator()
+        v._validator = RawValidator.from_json(json)
+        return v
         
     @property
     def raw_validator(self):
@@ -0,0 +1,316 @@
+"""
+Windows service integration.
+
+Installs a strategy as a Windows service run by the Service Control Manager, instead of a scheduled task: it starts
+with Windows (before any login), is restarted when it fails, and is stopped cleanly like with SIGTERM on Linux. The
+warnings and errors of the client are written to the Windows event log (Event Viewer > Windows Logs > Application).
+
+It requires pywin32 (`pip install BinaryOptionsToolsV2[windows]`) and an administrator prompt to install the service:
+    ```
+    set POCKET_OPTION_SSID=...
+    python -m BinaryOptionsToolsV2.windows_service install --name PocketBot -- --strategy my_bot:run --profile prod
+    python -m BinaryOptionsToolsV2.windows_service start --name PocketBot
+    ```
+
+The options after `--` are the ones of `python -m BinaryOptionsToolsV2.service`, the strategy is imported from the
+directory the service was installed from. The `POCKET_OPTION_SSID`, `BOT_CONTROL_TOKEN` and `BOT_` environment
+variables set at install time are given to the service, they're stored in its registry key which only the
+administrators can read.
+"""
+from __future__ import annotations
+from BinaryOptionsToolsV2.control import TOKEN_ENV
+from BinaryOptionsToolsV2.service import ENV_PREFIX, SSID_ENV, Service, build_service, parse_args
+from BinaryOptionsToolsV2.tracing import StreamLogsLayer, start_logs
+
+from typing import Optional
+
+import argparse
+import asyncio
+import json
+import os
+import threading
+
+try:
+    import servicemanager
+    import win32service
+    import win32serviceutil
+    import winreg
+except ImportError:
+    servicemanager = win32service = win32serviceutil = winreg = None
+
+# Options of the service stored in its registry key, read when the service starts
+ARGV_OPTION = "ServeArgs"
+CWD_OPTION = "WorkingDirectory"
+LOGS_OPTION = "LogsDirectory"
+EVENT_LEVEL_OPTION = "EventLogLevel"
+
+STARTUP_TYPES = ("auto", "delayed", "manual")
+
+
+def _require_pywin32():
+    if win32serviceutil is None:
+        raise ImportError(
+            "The Windows service requires pywin32, install it with `pip install BinaryOptionsToolsV2[windows]`"
+        )
+
+
+class EventLogSink:
+    """
+    Writes the logs of the client to the Windows event log, under the name of the running service.
+
+    Install its layer with the other logs, then start it:
+        ```python
+        sink = EventLogSink("WARN")
+        start_logs("logs", "INFO", terminal=False, layers=[sink.layer])
+        sink.start()
+        ```
+    """
+
+    def __init__(self, level: str = "WARN", targets: Optional[list[str]] = None):
+        _require_pywin32()
+        self.layer = StreamLogsLayer(level)
+        self._targets = targets
+        self._thread: Optional[threading.Thread] = None
+
+    def start(self):
+        "Forwards the logs from a background thread, until the logs stop"
+        if self._thread is not None:
+            return
+        subscription = self.layer.create_iterator(targets=self._targets)
+        self._thread = threading.Thread(target=self._forward, args=(subscription,), name="EventLogSink", daemon=True)
+        self._thread.start()
+
+    def _forward(self, subscription):
+        for record in subscription:
+            write_event(record)
+
+
+def write_event(record: dict):
+    "Writes a log record (as streamed by a `StreamLogsLayer`) to the event log, with a type matching its level"
+    fields = " ".join(
+        f"{key}={value}" for key, value in record.items() if key not in ("timestamp", "level", "target", "message")
+    )
+    text = f"[{record.get('target', '')}] {record.get('message', '')} {fields}".strip()
+    level = str(record.get("level", "INFO")).upper()
+    if level == "ERROR":
+        servicemanager.LogErrorMsg(text)
+    elif level == "WARN":
+        servicemanager.LogWarningMsg(text)
+    else:
+        servicemanager.LogInfoMsg(text)
+
+
+class WindowsService(win32serviceutil.ServiceFramework if win32serviceutil is not None else object):
+    """
+    Service run by the Service Control Manager, it runs the `Service` of the options stored by `install_service`.
+
+    A strategy stopping with an error stops the service as failed, so the recovery actions restart it.
+    """
+
+    _svc_name_ = "BinaryOptionsToolsV2"
+    _svc_display_name_ = "BinaryOptionsToolsV2 strategy"
+
+    def __init__(self, args):
+        super().__init__(args)
+        # The class is registered under the name given to `install_service`
+        self.name = args[0]
+        self._service: Optional[Service] = None
+        self._loop: Optional[asyncio.AbstractEventLoop] = None
+
+    def SvcStop(self):
+        self.ReportServiceStatus(win32service.SERVICE_STOP_PENDING)
+        service, loop = self._service, self._loop
+        if service is not None and loop is not None and not loop.is_closed():
+            loop.call_soon_threadsafe(service.request_stop)
+
+    def SvcDoRun(self):
+        servicemanager.LogMsg(
+            servicemanager.EVENTLOG_INFORMATION_TYPE, servicemanager.PYS_SERVICE_STARTED, (self.name, "")
+        )
+        os.chdir(self._option(CWD_OPTION, os.getcwd()))
+        sink = EventLogSink(self._option(EVENT_LEVEL_OPTION, "WARN"))
+        start_logs(self._option(LOGS_OPTION, None), "INFO", terminal=False, layers=[sink.layer])
+        sink.start()
+
+        # The service runs outside of the main thread, it has its own loop
+        self._loop = asyncio.new_event_loop()
+        asyncio.set_event_loop(self._loop)
+        try:
+            self._service = build_service(json.loads(self._option(ARGV_OPTION, "[]")))
+            self._loop.run_until_complete(self._service.run())
+        finally:
+            self._loop.close()
+        if self._service.error is not None:
+            raise RuntimeError(f"Strategy stopped with an error, {self._service.error}")
+        servicemanager.LogMsg(
+            servicemanager.EVENTLOG_INFORMATION_TYPE, servicemanager.PYS_SERVICE_STOPPED, (self.name, "")
+        )
+
+    def _option(self, option: str, default):
+        return win32serviceutil.GetServiceCustomOption(self.name, option, default)
+
+
+def _service_environment(env: Optional[dict]) -> list[str]:
+    if env is None:
+        env = {
+            key: value
+            for key, value in os.environ.items()
+            if key in (SSID_ENV, TOKEN_ENV) or key.startswith(ENV_PREFIX)
+        }
+    return [f"{key}={value}" for key, value in env.items()]
+
+
+def install_service(
+    name: str,
+    serve_args: list[str],
+    display_name: Optional[str] = None,
+    description: Optional[str] = None,
+    startup: str = "auto",
+    restart_delay: Optional[int] = 60,
+    logs_dir: Optional[str] = None,
+    event_log_level: str = "WARN",
+    env: Optional[dict] = None,
+) -> None:
+    """
+    Installs a strategy as a Windows service, requires an administrator prompt.
+
+    Args:
+        name (str): Name of the service, used to start, stop and remove it
+        serve_args (list[str]): Options of `python -m BinaryOptionsToolsV2.service` (`--strategy`, `--profile`...),
+            checked before the service is installed
+        display_name (str, optional): Name shown in the services manager. Defaults to `name`
+        description (str, optional): Description shown in the services manager
+        startup (str): "auto" (starts with Windows), "delayed" (shortly after the other automatic services) or
+            "manual". Defaults to "auto"
+        restart_delay (int, optional): Seconds before the service is restarted when it fails, None disables the
+            restarts. Defaults to 60
+        logs_dir (str, optional): Directory of the log files, no file is written if it's not set
+        event_log_level (str): Lowest level of the logs written to the event log. Defaults to "WARN"
+        env (dict, optional): Environment variables of the service. Defaults to the `POCKET_OPTION_SSID`,
+            `BOT_CONTROL_TOKEN` and `BOT_` variables of the current environment
+
+    Raises:
+        ValueError: If `startup` isn't a known startup type
+        SystemExit: If the options of the service are invalid
+    """
+    _require_pywin32()
+    if startup not in STARTUP_TYPES:
+        raise ValueError(f"Unknown startup type '{startup}', expected one of {', '.join(STARTUP_TYPES)}")
+    # The ssid and the strategy can come from the environment given to the service
+    environment = _service_environment(env)
+    saved = dict(os.environ)
+    os.environ.update(item.split("=", 1) for item in environment)
+    try:
+        parse_args(serve_args)
+    finally:
+        os.environ.clear()
+        os.environ.update(saved)
+
+    win32serviceutil.InstallService(
+        win32serviceutil.GetServiceClassString(WindowsService),
+        name,
+        display_name or name,
+        startType=win32service.SERVICE_DEMAND_START if startup == "manual" else win32service.SERVICE_AUTO_START,
+        description=description,
+    )
+    win32serviceutil.SetServiceCustomOption(name, ARGV_OPTION, json.dumps(serve_args))
+    win32serviceutil.SetServiceCustomOption(name, CWD_OPTION, os.getcwd())
+    win32serviceutil.SetServiceCustomOption(name, EVENT_LEVEL_OPTION, event_log_level)
+    if logs_dir is not None:
+        win32serviceutil.SetServiceCustomOption(name, LOGS_OPTION, os.path.abspath(logs_dir))
+    with winreg.OpenKey(
+        winreg.HKEY_LOCAL_MACHINE, rf"SYSTEM\CurrentControlSet\Services\{name}", 0, winreg.KEY_SET_VALUE
+    ) as key:
+        # Read by the Service Control Manager when it starts the process
+        winreg.SetValueEx(key, "Environment", 0, winreg.REG_MULTI_SZ, environment)
+
+    manager = win32service.OpenSCManager(None, None, win32service.SC_MANAGER_ALL_ACCESS)
+    try:
+        handle = win32service.OpenService(manager, name, win32service.SERVICE_ALL_ACCESS)
+        try:
+            if startup == "delayed":
+                win32service.ChangeServiceConfig2(handle, win32service.SERVICE_CONFIG_DELAYED_AUTO_START_INFO, True)
+            if restart_delay is not None:
+                action = (win32service.SC_ACTION_RESTART, int(restart_delay * 1000))
+                win32service.ChangeServiceConfig2(
+                    handle,
+                    win32service.SERVICE_CONFIG_FAILURE_ACTIONS,
+                    {"ResetPeriod": 86400, "RebootMsg": "", "Command": "", "Actions": [action] * 3},
+                )
+                # A service stopping with an error counts as a failure, not only a crash
+                win32service.ChangeServiceConfig2(handle, win32service.SERVICE_CONFIG_FAILURE_ACTIONS_FLAG, True)
+        finally:
+            win32service.CloseServiceHandle(handle)
+    finally:
+        win32service.CloseServiceHandle(manager)
+
+
+def remove_service(name: str) -> None:
+    "Removes a service installed with `install_service`, it's stopped first if it's running"
+    _require_pywin32()
+    try:
+        win32serviceutil.StopService(name)
+    except Exception:
+        # Not running
+        pass
+    win32serviceutil.RemoveService(name)
+
+
+def start_service(name: str) -> None:
+    _require_pywin32()
+    win32serviceutil.StartService(name)
+
+
+def stop_service(name: str) -> None:
+    "Stops the service like a SIGTERM, the strategy is cancelled and the client closed"
+    _require_pywin32()
+    win32serviceutil.StopService(name)
+
+
+def main(argv: Optional[list[str]] = None):
+    """Command line to install, remove, start and stop the Windows services of the strategies."""
+    parser = argparse.ArgumentParser(description="Run a BinaryOptionsToolsV2 strategy as a Windows service")
+    commands = parser.add_subparsers(dest="command", required=True)
+    install = commands.add_parser(
+        "install", help="Install a service, the options after `--` are the ones of BinaryOptionsToolsV2.service"
+    )
+    install.add_argument("--name", required=True, help="Name of the service")
+    install.add_argument("--display-name", help="Name shown in the services manager")
+    install.add_argument("--description", help="Description shown in the services manager")
+    install.add_argument("--startup", choices=STARTUP_TYPES, default="auto", help="Startup type of the service")
+    install.add_argument("--restart-delay", type=int, default=60, help="Seconds before restarting a failed service")
+    install.add_argument("--no-restart", action="store_true", help="Don't restart the service when it fails")
+    install.add_argument("--logs-dir", help="Directory of the log files")
+    install.add_argument("--event-log-level", default="WARN", help="Lowest level written to the event log")
+    for command in ("remove", "start", "stop"):
+        commands.add_parser(command, help=f"{command.capitalize()} a service").add_argument(
+            "--name", required=True, help="Name of the service"
+        )
+    args, serve_args = parser.parse_known_args(argv)
+    if serve_args[:1] == ["--"]:
+        serve_args = serve_args[1:]
+    if args.command != "install" and serve_args:
+        parser.error(f"unrecognized arguments: {' '.join(serve_args)}")
+
+    if args.command == "install":
+        install_service(
+            args.name,
+            serve_args,
+            display_name=args.display_name,
+            description=args.description,
+            startup=args.startup,
+            restart_delay=None if args.no_restart else args.restart_delay,
+            logs_dir=args.logs_dir,
+            event_log_level=args.event_log_level,
+        )
+        print(f"Service '{args.name}' installed, start it with `start --name {args.name}`")
+    elif args.command == "remove":
+        remove_service(args.name)
+    elif args.command == "start":
+        start_service(args.name)
+    else:
+        stop_service(args.name)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,74 @@
+use std::{sync::Arc, time::Duration};
+
+use binary_options_tools::pocketoption::access::{AccessControl, Operation, Role, TradeLimits};
+use pyo3::{pyclass, pymethods, PyResult};
+
+use crate::error::BinaryErrorPy;
+
+/// API keys allowed to use the IPC bridge or the healthcheck gateway, with their role
+/// (`read_only`, `trade_limited` or `admin`). The checks run in Rust, only the SHA-256 digests of
+/// the keys are kept.
+#[pyclass(name = "AccessControl")]
+#[derive(Clone, Default)]
+pub struct PyAccessControl {
+    pub(crate) access: Arc<AccessControl>,
+}
+
+#[pymethods]
+impl PyAccessControl {
+    #[new]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allows `key` with `role`, the limits only apply to the `trade_limited` role: maximum amount
+    /// of a trade and maximum number of trades every `window` (one hour by default).
+    #[pyo3(signature = (key, role, name = None, max_amount = None, max_trades = None, window = None))]
+    pub fn add_key(
+        &self,
+        key: String,
+        role: String,
+        name: Option<String>,
+        max_amount: Option<f64>,
+        max_trades: Option<usize>,
+        window: Option<Duration>,
+    ) -> PyResult<()> {
+        let role: Role = role.parse().map_err(BinaryErrorPy::from)?;
+        let limits = TradeLimits {
+            max_amount,
+            max_trades,
+            window: window.unwrap_or(TradeLimits::default().window),
+        };
+        let name = name.unwrap_or_else(|| role.to_string());
+        Ok(self
+            .access
+            .add_key(&key, name, role, limits)
+            .map_err(BinaryErrorPy::from)?)
+    }
+
+    /// Revokes `key`, returns False if it wasn't allowed.
+    pub fn remove_key(&self, key: String) -> bool {
+        self.access.remove_key(&key)
+    }
+
+    /// Returns the role of `key`, `None` if it isn't allowed.
+    pub fn role(&self, key: String) -> Option<String> {
+        self.access.role(&key).map(|role| role.to_string())
+    }
+
+    /// Checks that `key` can call `method` of the client (placing an order of `amount`), returns
+    /// its role or raises `PermissionDeniedError`.
+    #[pyo3(signature = (key, method, amount = None))]
+    pub fn authorize(
+        &self,
+        key: Option<String>,
+        method: String,
+        amount: Option<f64>,
+    ) -> PyResult<String> {
+        let role = self
+            .access
+            .authorize(key.as_deref(), &Operation::of_method(&method, amount))
+            .map_err(BinaryErrorPy::from)?;
+        Ok(role.to_string())
+    }
+}
@@ -0,0 +1,72 @@
+use binary_options_tools::pocketoption::types::update::{AssetInfo, AssetType};
+use pyo3::{pyclass, pymethods, Bound, PyAny, PyResult, Python};
+use pythonize::pythonize;
+
+/// Asset of the catalog returned by `get_assets`.
+#[pyclass(name = "Asset", frozen)]
+#[derive(Debug, Clone)]
+pub struct PyAsset {
+    #[pyo3(get)]
+    pub id: i32,
+    #[pyo3(get)]
+    pub symbol: String,
+    /// Name shown by the platform (e.g. "Apple OTC").
+    #[pyo3(get)]
+    pub name: String,
+    /// "currency", "cryptocurrency", "commodity", "index" or "stock".
+    #[pyo3(get)]
+    pub asset_type: &'static str,
+    /// Payout of the asset in percent.
+    #[pyo3(get)]
+    pub payout: i32,
+    #[pyo3(get)]
+    pub is_otc: bool,
+    /// False while the market of the asset is closed, the trades on it are rejected.
+    #[pyo3(get)]
+    pub is_open: bool,
+    /// Expiry times (in seconds) the server accepts.
+    #[pyo3(get)]
+    pub expiries: Vec<u32>,
+    asset: AssetInfo,
+}
+
+impl From<AssetInfo> for PyAsset {
+    fn from(asset: AssetInfo) -> Self {
+        Self {
+            id: asset.id,
+            symbol: asset.symbol.clone(),
+            name: asset.name.clone(),
+            asset_type: match asset.asset_type {
+                AssetType::Currency => "currency",
+                AssetType::Cryptocurrency => "cryptocurrency",
+                AssetType::Commodity => "commodity",
+                AssetType::Index => "index",
+                AssetType::Stock => "stock",
+            },
+            payout: asset.payout,
+            is_otc: asset.is_otc,
+            is_open: asset.is_open,
+            expiries: asset.expiries.clone(),
+            asset,
+        }
+    }
+}
+
+#[pymethods]
+impl PyAsset {
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        Ok(pythonize(py, &self.asset)?)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Asset(symbol='{}', name='{}', type='{}', payout={}, is_otc={}, is_open={})",
+            self.symbol,
+            self.name,
+            self.asset_type,
+            self.payout,
+            if self.is_otc { "True" } else { "False" },
+            if self.is_open { "True" } else { "False" }
+        )
+    }
+}
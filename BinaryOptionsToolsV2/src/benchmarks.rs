@@ -0,0 +1,29 @@
+use binary_options_tools::pocketoption::benchmarks::run_benchmarks;
+use binary_options_tools::pocketoption::parser::json::JSON_BACKEND;
+use pyo3::{pyfunction, Bound, IntoPyObjectExt, PyAny, PyResult, Python};
+use pyo3_async_runtimes::tokio::future_into_py;
+
+use crate::error::BinaryErrorPy;
+
+/// Runs the benchmarks of the hot paths of the client (parsing, serialization, order send path and
+/// stream throughput) and returns the json list of the results.
+#[pyfunction(name = "run_benchmarks")]
+#[pyo3(signature = (iterations = 10000))]
+pub fn run_benchmarks_py(py: Python<'_>, iterations: usize) -> PyResult<Bound<'_, PyAny>> {
+    future_into_py(py, async move {
+        let results = run_benchmarks(iterations)
+            .await
+            .map_err(BinaryErrorPy::from)?;
+        Python::with_gil(|py| {
+            serde_json::to_string(&results)
+                .map_err(BinaryErrorPy::from)?
+                .into_py_any(py)
+        })
+    })
+}
+
+/// Name of the JSON backend the messages are parsed with (`serde_json` or `simd-json`).
+#[pyfunction]
+pub fn json_backend() -> &'static str {
+    JSON_BACKEND
+}
@@ -0,0 +1,195 @@
+use binary_options_tools::pocketoption::types::update::DataCandle;
+use chrono::{DateTime, Utc};
+use pyo3::types::{PyAnyMethods, PyByteArray, PyDict, PyDictMethods};
+use pyo3::{pyclass, pymethods, Bound, IntoPyObjectExt, Py, PyAny, PyResult, Python};
+use pythonize::pythonize;
+
+use crate::error::BinaryErrorPy;
+use crate::output::{NullPolicy, OutputFormat};
+
+/// Candle yielded by the candle subscriptions and returned by `get_candles`, with typed fields
+/// instead of a json string.
+#[pyclass(name = "Candle", frozen)]
+#[derive(Debug, Clone)]
+pub struct PyCandle {
+    #[pyo3(get)]
+    pub asset: String,
+    /// Start of the candle.
+    #[pyo3(get)]
+    pub time: DateTime<Utc>,
+    #[pyo3(get)]
+    pub open: f64,
+    #[pyo3(get)]
+    pub high: f64,
+    #[pyo3(get)]
+    pub low: f64,
+    #[pyo3(get)]
+    pub close: f64,
+    /// Local time the last update of a streamed candle was received at.
+    #[pyo3(get)]
+    pub received_at: Option<DateTime<Utc>>,
+    /// Latency in milliseconds of the last update of a streamed candle.
+    #[pyo3(get)]
+    pub latency: Option<f64>,
+    /// Policy of the missing values of `to_dict` and `to_json`.
+    nulls: NullPolicy,
+}
+
+impl PyCandle {
+    pub fn new(asset: &str, candle: &DataCandle, nulls: NullPolicy) -> Self {
+        Self {
+            asset: asset.to_string(),
+            time: candle.time,
+            open: candle.open,
+            high: candle.high,
+            low: candle.low,
+            close: candle.close,
+            received_at: candle.received_at,
+            latency: nulls.number(candle.latency),
+            nulls,
+        }
+    }
+
+    fn data(&self) -> DataCandle {
+        DataCandle {
+            time: self.time,
+            open: self.open,
+            close: self.close,
+            high: self.high,
+            low: self.low,
+            received_at: self.received_at,
+            latency: self.latency,
+        }
+    }
+}
+
+#[pymethods]
+impl PyCandle {
+    /// Unix timestamp of the start of the candle, in seconds.
+    #[getter]
+    fn timestamp(&self) -> f64 {
+        self.time.timestamp_millis() as f64 / 1000.0
+    }
+
+    /// Returns the candle as the dict the previous versions returned (the `time` is an ISO 8601
+    /// string), with its `asset`.
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let mut value = OutputFormat::new(false, self.nulls).to_value(&self.data())?;
+        value["asset"] = self.asset.clone().into();
+        Ok(pythonize(py, &value)?)
+    }
+
+    /// Returns the candle as a json string, in the format of the recordings.
+    fn to_json(&self) -> PyResult<String> {
+        Ok(OutputFormat::new(true, self.nulls).to_json(&self.data())?)
+    }
+
+    /// Reads a candle written by `to_json` (a line of the recordings), of the `asset`.
+    #[staticmethod]
+    #[pyo3(signature = (json, asset = String::new()))]
+    fn from_json(json: &str, asset: String) -> PyResult<Self> {
+        let candle: DataCandle = serde_json::from_str(json).map_err(BinaryErrorPy::from)?;
+        Ok(Self::new(&asset, &candle, NullPolicy::default()))
+    }
+
+    /// Dict-like access (`candle["close"]`) for the code written for the dicts of the previous versions.
+    fn __getitem__<'py>(&self, py: Python<'py>, key: &str) -> PyResult<Bound<'py, PyAny>> {
+        self.to_dict(py)?.get_item(key)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Candle(asset='{}', time='{}', open={}, high={}, low={}, close={})",
+            self.asset,
+            self.time.to_rfc3339(),
+            self.open,
+            self.high,
+            self.low,
+            self.close
+        )
+    }
+}
+
+/// Candle of a `StreamIterator` with its asset, the iterators of `subscribe_symbols` yield the
+/// candles of several assets.
+#[derive(Debug, Clone)]
+pub struct AssetCandle {
+    pub asset: String,
+    pub candle: DataCandle,
+}
+
+impl AssetCandle {
+    pub fn new(asset: impl ToString, candle: DataCandle) -> Self {
+        Self {
+            asset: asset.to_string(),
+            candle,
+        }
+    }
+
+    /// Converts the candle to a `Candle`, or to its json string with `json_strings`. The json
+    /// string only has the `asset` of the candle if `tagged`.
+    pub fn to_py(&self, py: Python<'_>, output: OutputFormat, tagged: bool) -> PyResult<Py<PyAny>> {
+        if output.json_strings && tagged {
+            let mut value = output.to_value(&self.candle)?;
+            value["asset"] = self.asset.clone().into();
+            value.to_string().into_py_any(py)
+        } else {
+            candle_to_py(py, &self.asset, &self.candle, output)
+        }
+    }
+}
+
+/// Converts a candle to a `Candle`, or to its json string with `json_strings`.
+pub fn candle_to_py(
+    py: Python<'_>,
+    asset: &str,
+    candle: &DataCandle,
+    output: OutputFormat,
+) -> PyResult<Py<PyAny>> {
+    if output.json_strings {
+        output.to_json(candle)?.into_py_any(py)
+    } else {
+        PyCandle::new(asset, candle, output.nulls).into_py_any(py)
+    }
+}
+
+/// Converts candles to a list of `Candle`, or to the json string of the list with `json_strings`.
+pub fn candles_to_py(
+    py: Python<'_>,
+    asset: &str,
+    candles: &[DataCandle],
+    output: OutputFormat,
+) -> PyResult<Py<PyAny>> {
+    if output.json_strings {
+        output.to_json(candles)?.into_py_any(py)
+    } else {
+        candles
+            .iter()
+            .map(|candle| PyCandle::new(asset, candle, output.nulls))
+            .collect::<Vec<_>>()
+            .into_py_any(py)
+    }
+}
+
+/// Converts candles to a dict of columns (`timestamps` in unix seconds, `open`, `high`, `low` and
+/// `close`), each a `bytearray` of native endian `float64` read by `numpy.frombuffer` without a copy.
+pub fn candles_to_columns(py: Python<'_>, candles: &[DataCandle]) -> PyResult<Py<PyAny>> {
+    let columns: [(&str, fn(&DataCandle) -> f64); 5] = [
+        ("timestamps", |c| c.time.timestamp_millis() as f64 / 1000.0),
+        ("open", |c| c.open),
+        ("high", |c| c.high),
+        ("low", |c| c.low),
+        ("close", |c| c.close),
+    ];
+    let dict = PyDict::new(py);
+    for (name, value) in columns {
+        let column = PyByteArray::new_with(py, candles.len() * size_of::<f64>(), |bytes| {
+            for (chunk, candle) in bytes.chunks_exact_mut(size_of::<f64>()).zip(candles) {
+                chunk.copy_from_slice(&value(candle).to_ne_bytes());
+            }
+            Ok(())
+        })?;
+        dict.set_item(name, column)?;
+    }
+    dict.into_py_any(py)
+}
@@ -0,0 +1,40 @@
+use binary_options_tools::pocketoption::{
+    chart::{self, ChartMarker, ChartOptions},
+    types::update::DataCandle,
+};
+use pyo3::{pyfunction, types::PyBytes, Bound, PyResult, Python};
+
+use crate::error::BinaryErrorPy;
+
+/// Parses the json of the `ChartOptions`, the default options if `None`.
+pub fn chart_options(options: Option<String>) -> PyResult<ChartOptions> {
+    let options: ChartOptions = match options {
+        Some(options) => serde_json::from_str(&options).map_err(BinaryErrorPy::from)?,
+        None => ChartOptions::default(),
+    };
+    options.validate().map_err(BinaryErrorPy::from)?;
+    Ok(options)
+}
+
+/// Parses the json of a list of `ChartMarker`, no marker if `None`.
+pub fn chart_markers(markers: Option<String>) -> PyResult<Vec<ChartMarker>> {
+    match markers {
+        Some(markers) => Ok(serde_json::from_str(&markers).map_err(BinaryErrorPy::from)?),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Renders a history of candles (as json) and the markers drawn on them to a PNG image.
+#[pyfunction]
+#[pyo3(signature = (candles, markers = None, options = None))]
+pub fn render_chart<'py>(
+    py: Python<'py>,
+    candles: String,
+    markers: Option<String>,
+    options: Option<String>,
+) -> PyResult<Bound<'py, PyBytes>> {
+    let candles: Vec<DataCandle> = serde_json::from_str(&candles).map_err(BinaryErrorPy::from)?;
+    let (markers, options) = (chart_markers(markers)?, chart_options(options)?);
+    let png = chart::render_chart(&candles, &markers, &options).map_err(BinaryErrorPy::from)?;
+    Ok(PyBytes::new(py, &png))
+}
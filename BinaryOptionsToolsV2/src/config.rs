@@ -1,15 +1,25 @@
 use binary_options_tools::{error::BinaryOptionsToolsError, pocketoption::parser::message::WebSocketMessage};
 use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::collections::HashSet;
+use std::path::PathBuf;
 use std::time::Duration;
 use url::Url;
-use binary_options_tools::reimports::ConfigBuilder;
+use binary_options_tools::reimports::{_Config, ConfigBuilder, ConnectionTuning};
 use binary_options_tools::pocketoption::types::data::PocketData;
 
-use crate::error::BinaryResultPy;
+use crate::error::{BinaryErrorPy, BinaryResultPy};
+use crate::output::NullPolicy;
+
+/// Default file used by `PyConfig.profile` when no path is given.
+const DEFAULT_PROFILES_FILE: &str = "profiles.json";
+/// Key used inside a profile to name the profile it extends.
+const INHERITS_KEY: &str = "inherits";
 
 #[pyclass]
-#[derive(Clone, Default)]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct PyConfig {
     #[pyo3(get, set)]
     pub max_allowed_loops: u32,
@@ -23,12 +33,35 @@ pub struct PyConfig {
     pub timeout_secs: u64,
     #[pyo3(get, set)]
     pub urls: Vec<String>,
+    /// How the missing values of the results are written, see `null_policy`.
+    pub null_policy: NullPolicy,
+    /// Sets TCP_NODELAY on the socket, the orders are sent without waiting for the previous frames
+    /// to be acknowledged.
+    #[pyo3(get, set)]
+    pub tcp_nodelay: bool,
+    /// Capacity of the read buffer of the websocket, in bytes.
+    #[pyo3(get, set)]
+    pub read_buffer_size: usize,
+    /// Bytes buffered before they're written to the socket, 0 writes every frame right away.
+    #[pyo3(get, set)]
+    pub write_buffer_size: usize,
+    /// Reads the websocket on a thread of its own with a raised priority.
+    #[pyo3(get, set)]
+    pub dedicated_reader: bool,
+    /// Skips the debug logs written for every message received.
+    #[pyo3(get, set)]
+    pub reduced_logging: bool,
+    /// Rejects the orders on the assets whose market is closed with `AssetClosedError` instead of
+    /// sending them.
+    #[pyo3(get, set)]
+    pub check_market_hours: bool,
 }
 
 #[pymethods]
 impl PyConfig {
     #[new]
     pub fn new() -> Self {
+        let tuning = ConnectionTuning::default();
         Self {
             max_allowed_loops: 100,
             sleep_interval: 100,
@@ -36,12 +69,103 @@ impl PyConfig {
             connection_initialization_timeout_secs: 30,
             timeout_secs: 30,
             urls: Vec::new(),
+            null_policy: NullPolicy::default(),
+            tcp_nodelay: tuning.tcp_nodelay,
+            read_buffer_size: tuning.read_buffer_size,
+            write_buffer_size: tuning.write_buffer_size,
+            dedicated_reader: tuning.dedicated_reader,
+            reduced_logging: tuning.reduced_logging,
+            check_market_hours: false,
         }
     }
 
+    /// Preset for the short expiries (5 to 30 seconds), every frame is sent and read as soon as
+    /// possible: TCP_NODELAY, smaller buffers flushed on every frame, a dedicated reader thread with
+    /// a raised priority and no debug log per message.
+    /// The trade-offs are more syscalls and packets (a lower throughput when subscribing to many
+    /// assets at once) and a thread per client, the round trip of both settings on the current
+    /// machine is measured by the `round_trip_default` and `round_trip_low_latency` benchmarks.
+    #[staticmethod]
+    pub fn low_latency() -> Self {
+        let mut config = Self::new();
+        config.set_tuning(ConnectionTuning::low_latency());
+        config
+    }
+
+    /// Policy of the missing values (`None` fields and NaN numbers) of the results: "null" keeps
+    /// them, "omit" removes the fields and "default" replaces them with 0.
+    #[getter(null_policy)]
+    pub fn get_null_policy(&self) -> &'static str {
+        self.null_policy.as_str()
+    }
+
+    #[setter(null_policy)]
+    pub fn set_null_policy(&mut self, policy: &str) -> PyResult<()> {
+        self.null_policy = NullPolicy::parse(policy)?;
+        Ok(())
+    }
+
+    /// Loads the named profile from a profiles file.
+    /// The file is a JSON object mapping profile names to partial configs, a profile can extend
+    /// another one with the `"inherits"` key and only needs to declare the fields it overrides:
+    /// `{"demo": {"timeout_secs": 30}, "prod": {"inherits": "demo", "reconnect_time": 2}}`
+    #[staticmethod]
+    #[pyo3(signature = (name, path = None))]
+    pub fn profile(name: String, path: Option<PathBuf>) -> PyResult<Self> {
+        let path = path.unwrap_or_else(|| PathBuf::from(DEFAULT_PROFILES_FILE));
+        let content = std::fs::read_to_string(&path).map_err(BinaryErrorPy::from)?;
+        let profiles: Map<String, Value> =
+            serde_json::from_str(&content).map_err(BinaryErrorPy::from)?;
+        Ok(Self::from_profiles(&profiles, &name)?)
+    }
+
+    /// Returns the names of all the profiles declared in a profiles file.
+    #[staticmethod]
+    #[pyo3(signature = (path = None))]
+    pub fn profiles(path: Option<PathBuf>) -> PyResult<Vec<String>> {
+        let path = path.unwrap_or_else(|| PathBuf::from(DEFAULT_PROFILES_FILE));
+        let content = std::fs::read_to_string(&path).map_err(BinaryErrorPy::from)?;
+        let profiles: Map<String, Value> =
+            serde_json::from_str(&content).map_err(BinaryErrorPy::from)?;
+        Ok(profiles.keys().cloned().collect())
+    }
+}
+
+impl Default for PyConfig {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl PyConfig {
+    /// Null policy of an optional config, the default one without config.
+    pub fn null_policy_of(config: Option<&Self>) -> NullPolicy {
+        config.map(|config| config.null_policy).unwrap_or_default()
+    }
+
+    /// Whether an optional config enables the market hours check, disabled without config.
+    pub fn market_hours_of(config: Option<&Self>) -> bool {
+        config.is_some_and(|config| config.check_market_hours)
+    }
+
+    pub fn tuning(&self) -> ConnectionTuning {
+        ConnectionTuning {
+            tcp_nodelay: self.tcp_nodelay,
+            read_buffer_size: self.read_buffer_size,
+            write_buffer_size: self.write_buffer_size,
+            dedicated_reader: self.dedicated_reader,
+            reduced_logging: self.reduced_logging,
+        }
+    }
+
+    fn set_tuning(&mut self, tuning: ConnectionTuning) {
+        self.tcp_nodelay = tuning.tcp_nodelay;
+        self.read_buffer_size = tuning.read_buffer_size;
+        self.write_buffer_size = tuning.write_buffer_size;
+        self.dedicated_reader = tuning.dedicated_reader;
+        self.reduced_logging = tuning.reduced_logging;
+    }
+
     pub fn build(&self) -> BinaryResultPy<ConfigBuilder<PocketData, WebSocketMessage, ()>> {
         let urls: Result<Vec<Url>, url::ParseError> = self
             .urls
@@ -49,7 +173,10 @@ impl PyConfig {
             .map(|url| Url::parse(url))
             .collect();
 
-        let config = ConfigBuilder::new()
+        // Starts from the config of `PocketOption::new`, so the fields without a python equivalent are set
+        let config = _Config::new(Duration::from_millis(500), vec![], ())
+        .builder()
+        .connection_tuning(self.tuning())
         .max_allowed_loops(self.max_allowed_loops)
         .sleep_interval(self.sleep_interval)
         .reconnect_time(self.reconnect_time)
@@ -60,4 +187,84 @@ impl PyConfig {
         Ok(config)
     }
 
-}
\ No newline at end of file
+    /// Resolves `name` against the profiles map, applying each parent profile before the child
+    /// so the child's values take precedence.
+    pub fn from_profiles(profiles: &Map<String, Value>, name: &str) -> BinaryResultPy<Self> {
+        let mut chain: Vec<&Map<String, Value>> = Vec::new();
+        let mut visited: Vec<&str> = Vec::new();
+        let mut current = Some(name);
+        while let Some(profile_name) = current {
+            if visited.contains(&profile_name) {
+                return Err(BinaryErrorPy::ProfileError(format!(
+                    "circular inheritance detected: {} -> {profile_name}",
+                    visited.join(" -> ")
+                )));
+            }
+            visited.push(profile_name);
+            let profile = profiles
+                .get(profile_name)
+                .and_then(Value::as_object)
+                .ok_or_else(|| {
+                    BinaryErrorPy::ProfileError(format!("profile '{profile_name}' not found"))
+                })?;
+            chain.push(profile);
+            current = profile.get(INHERITS_KEY).and_then(Value::as_str);
+        }
+
+        let mut merged = Map::new();
+        for profile in chain.into_iter().rev() {
+            for (key, value) in profile {
+                if key != INHERITS_KEY {
+                    merged.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        Ok(serde_json::from_value(Value::Object(merged))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_inheritance() {
+        let profiles: Map<String, Value> = serde_json::from_str(
+            r#"{
+                "demo": {"timeout_secs": 10, "urls": ["wss://demo.example.com"]},
+                "prod": {"inherits": "demo", "timeout_secs": 60},
+                "research": {"inherits": "prod", "reconnect_time": 1}
+            }"#,
+        )
+        .unwrap();
+        let config = PyConfig::from_profiles(&profiles, "research").unwrap();
+        assert_eq!(config.timeout_secs, 60);
+        assert_eq!(config.reconnect_time, 1);
+        assert_eq!(config.urls, vec!["wss://demo.example.com".to_string()]);
+        assert_eq!(config.max_allowed_loops, PyConfig::new().max_allowed_loops);
+    }
+
+    #[test]
+    fn test_low_latency_preset() {
+        let config = PyConfig::low_latency();
+        assert_eq!(config.tuning(), ConnectionTuning::low_latency());
+        assert_eq!(config.timeout_secs, PyConfig::new().timeout_secs);
+        assert_eq!(PyConfig::new().tuning(), ConnectionTuning::default());
+        // A profile only overrides the options it declares
+        let profiles: Map<String, Value> =
+            serde_json::from_str(r#"{"fast": {"tcp_nodelay": true}}"#).unwrap();
+        let config = PyConfig::from_profiles(&profiles, "fast").unwrap();
+        assert!(config.tcp_nodelay && !config.dedicated_reader);
+        assert!(config.build().unwrap().build().is_ok());
+    }
+
+    #[test]
+    fn test_profile_errors() {
+        let profiles: Map<String, Value> = serde_json::from_str(
+            r#"{"a": {"inherits": "b"}, "b": {"inherits": "a"}}"#,
+        )
+        .unwrap();
+        assert!(PyConfig::from_profiles(&profiles, "a").is_err());
+        assert!(PyConfig::from_profiles(&profiles, "missing").is_err());
+    }
+}
@@ -0,0 +1,200 @@
+use binary_options_tools::pocketoption::types::order::{Action, Deal};
+use chrono::{DateTime, Utc};
+use pyo3::types::PyAnyMethods;
+use pyo3::{pyclass, pymethods, Bound, PyAny, PyResult, Python};
+use pythonize::pythonize;
+
+use crate::error::BinaryErrorPy;
+use crate::output::{NullPolicy, OutputFormat};
+
+/// Direction of a trade, given to `trade` instead of choosing between `buy` and `sell`.
+#[pyclass(name = "OrderDirection", eq, eq_int, frozen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PyOrderDirection {
+    #[pyo3(name = "CALL")]
+    Call,
+    #[pyo3(name = "PUT")]
+    Put,
+}
+
+impl From<Action> for PyOrderDirection {
+    fn from(action: Action) -> Self {
+        match action {
+            Action::Call => Self::Call,
+            Action::Put => Self::Put,
+        }
+    }
+}
+
+impl From<PyOrderDirection> for Action {
+    fn from(direction: PyOrderDirection) -> Self {
+        match direction {
+            PyOrderDirection::Call => Self::Call,
+            PyOrderDirection::Put => Self::Put,
+        }
+    }
+}
+
+#[pymethods]
+impl PyOrderDirection {
+    /// Parses "call" / "buy" or "put" / "sell", case insensitive.
+    #[staticmethod]
+    fn parse(direction: &str) -> PyResult<Self> {
+        let action: Action = direction.parse().map_err(BinaryErrorPy::from)?;
+        Ok(action.into())
+    }
+
+    /// Name of the direction the other methods take, "call" or "put".
+    #[getter]
+    fn value(&self) -> &'static str {
+        match self {
+            Self::Call => "call",
+            Self::Put => "put",
+        }
+    }
+
+    fn opposite(&self) -> Self {
+        match self {
+            Self::Call => Self::Put,
+            Self::Put => Self::Call,
+        }
+    }
+
+    fn __str__(&self) -> &'static str {
+        self.value()
+    }
+}
+
+/// Reads a direction given as an `OrderDirection` or as a string ("call", "put", "buy", "sell" or a
+/// `Direction` of the python package).
+pub fn extract_direction(direction: &Bound<'_, PyAny>) -> PyResult<Action> {
+    if let Ok(direction) = direction.extract::<PyOrderDirection>() {
+        return Ok(direction.into());
+    }
+    let direction: String = direction.extract()?;
+    let action = direction.parse().map_err(BinaryErrorPy::from)?;
+    Ok(action)
+}
+
+/// Trade returned by `trade`, with typed fields instead of a json string.
+#[pyclass(name = "Deal", frozen)]
+#[derive(Debug, Clone)]
+pub struct PyDeal {
+    #[pyo3(get)]
+    pub id: String,
+    #[pyo3(get)]
+    pub asset: String,
+    #[pyo3(get)]
+    pub direction: PyOrderDirection,
+    #[pyo3(get)]
+    pub amount: f64,
+    /// Profit of the trade once closed, negative for a loss.
+    #[pyo3(get)]
+    pub profit: f64,
+    /// Payout of the asset when the trade was placed, in percent.
+    #[pyo3(get)]
+    pub percent_profit: i32,
+    #[pyo3(get)]
+    pub open_price: f64,
+    /// Price the trade closed at, zero while it's open.
+    #[pyo3(get)]
+    pub close_price: f64,
+    #[pyo3(get)]
+    pub open_time: DateTime<Utc>,
+    /// Expiry of the trade.
+    #[pyo3(get)]
+    pub close_time: DateTime<Utc>,
+    #[pyo3(get)]
+    pub currency: String,
+    #[pyo3(get)]
+    pub is_demo: bool,
+    deal: Deal,
+    /// Policy of the missing values of `to_dict` and `to_json`.
+    nulls: NullPolicy,
+}
+
+impl PyDeal {
+    pub fn new(deal: Deal, nulls: NullPolicy) -> Self {
+        Self {
+            id: deal.id.to_string(),
+            asset: deal.asset.clone(),
+            direction: if deal.command == 0 {
+                PyOrderDirection::Call
+            } else {
+                PyOrderDirection::Put
+            },
+            amount: nulls.finite(deal.amount),
+            profit: nulls.finite(deal.profit),
+            percent_profit: deal.percent_profit,
+            open_price: nulls.finite(deal.open_price),
+            close_price: nulls.finite(deal.close_price),
+            open_time: deal.open_timestamp,
+            close_time: deal.close_timestamp,
+            currency: deal.currency.clone(),
+            is_demo: deal.is_demo != 0,
+            deal,
+            nulls,
+        }
+    }
+}
+
+#[pymethods]
+impl PyDeal {
+    /// True once the result of the trade is known.
+    #[getter]
+    fn closed(&self) -> bool {
+        self.close_price > 0.0
+    }
+
+    /// "win", "loss" or "draw" once the trade is closed, None while it's open.
+    #[getter]
+    fn result(&self) -> Option<&'static str> {
+        if !self.closed() {
+            None
+        } else if self.profit > 0.0 {
+            Some("win")
+        } else if self.profit < 0.0 {
+            Some("loss")
+        } else {
+            Some("draw")
+        }
+    }
+
+    /// Returns the trade as the dict `buy` and `sell` return.
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let value = OutputFormat::new(false, self.nulls).to_value(&self.deal)?;
+        Ok(pythonize(py, &value)?)
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        Ok(OutputFormat::new(true, self.nulls).to_json(&self.deal)?)
+    }
+
+    /// Reads a trade written by `to_json` or sent by the broker.
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        let deal: Deal = serde_json::from_str(json).map_err(BinaryErrorPy::from)?;
+        Ok(Self::new(deal, NullPolicy::default()))
+    }
+
+    /// Dict-like access (`deal["openPrice"]`) for the code written for the dicts of `buy` and `sell`.
+    fn __getitem__<'py>(&self, py: Python<'py>, key: &str) -> PyResult<Bound<'py, PyAny>> {
+        self.to_dict(py)?.get_item(key)
+    }
+
+    fn __repr__(&self) -> String {
+        let direction = match self.direction {
+            PyOrderDirection::Call => "CALL",
+            PyOrderDirection::Put => "PUT",
+        };
+        format!(
+            "Deal(id='{}', asset='{}', direction=OrderDirection.{}, amount={}, open_price={}, profit={})",
+            self.id,
+            self.asset,
+            direction,
+            self.amount,
+            self.open_price,
+            self.profit
+        )
+    }
+}
@@ -1,8 +1,59 @@
-use binary_options_tools::{error::BinaryOptionsToolsError, pocketoption::error::PocketOptionError};
-use pyo3::{exceptions::PyValueError, PyErr};
+use binary_options_tools::{
+    error::BinaryOptionsToolsError,
+    locale::{locale, Locale},
+    pocketoption::error::PocketOptionError,
+};
+use pyo3::{
+    create_exception,
+    exceptions::{PyPermissionError, PyTimeoutError, PyValueError},
+    PyErr,
+};
 use thiserror::Error;
 use uuid::Uuid;
 
+create_exception!(
+    BinaryOptionsToolsV2,
+    ObserverModeError,
+    PyValueError,
+    "Raised when an order is sent through a client in observer mode."
+);
+create_exception!(
+    BinaryOptionsToolsV2,
+    PermissionDeniedError,
+    PyPermissionError,
+    "Raised when an API key is missing, invalid or its role doesn't allow the operation."
+);
+create_exception!(
+    BinaryOptionsToolsV2,
+    AuthenticationError,
+    PyPermissionError,
+    "Raised when the server rejects the session, its message tells whether it expired or is malformed."
+);
+create_exception!(
+    BinaryOptionsToolsV2,
+    RegionMismatchError,
+    AuthenticationError,
+    "Raised when the server reports that the client connects from another IP or region than the one of the session, its message lists the steps to fix it."
+);
+create_exception!(
+    BinaryOptionsToolsV2,
+    TradeTimeoutError,
+    PyTimeoutError,
+    "Raised when the result of a trade isn't received before the timeout of `check_win`."
+);
+create_exception!(
+    BinaryOptionsToolsV2,
+    PayoutTooLowError,
+    PyValueError,
+    "Raised when a trade is rejected because the payout of the asset is under its `min_payout`."
+);
+create_exception!(
+    BinaryOptionsToolsV2,
+    AssetClosedError,
+    PyValueError,
+    "Raised when a trade is rejected because the market of the asset is closed."
+);
+
 #[derive(Error, Debug)]
 pub enum BinaryErrorPy {
     #[error("BinaryOptionsError, {0}")]
@@ -19,13 +70,58 @@ pub enum BinaryErrorPy {
     TradeNotFound(Uuid),
     #[error("Operation not allowed")]
     NotAllowed(String),
+    #[cfg(feature = "validators-regex")]
     #[error("Invalid Regex pattern, {0}")]
-    InvalidRegexError(#[from] regex::Error)
+    InvalidRegexError(#[from] regex::Error),
+    #[error("IO error, {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Config profile error, {0}")]
+    ProfileError(String),
+    #[error("Invalid socket address, {0}")]
+    AddrParseError(#[from] std::net::AddrParseError),
+}
+
+impl BinaryErrorPy {
+    /// Message of the error in the locale of the library, followed by the English message in the
+    /// other locales so the reported errors stay readable by the maintainers.
+    pub fn message(&self) -> String {
+        let locale = locale();
+        let localized = match self {
+            Self::BinaryOptionsError(e) if locale != Locale::En => e.localized(locale),
+            Self::PocketOptionError(e) if locale != Locale::En => e.localized(locale),
+            _ => return self.to_string(),
+        };
+        format!("{localized}\n(en) {self}")
+    }
 }
 
 impl From<BinaryErrorPy> for PyErr {
     fn from(value: BinaryErrorPy) -> Self {
-        PyValueError::new_err(value.to_string())
+        let message = value.message();
+        match value {
+            BinaryErrorPy::PocketOptionError(PocketOptionError::ObserverMode(_)) => {
+                ObserverModeError::new_err(message)
+            }
+            BinaryErrorPy::PocketOptionError(
+                PocketOptionError::Unauthorized(_) | PocketOptionError::PermissionDenied { .. },
+            ) => PermissionDeniedError::new_err(message),
+            BinaryErrorPy::PocketOptionError(PocketOptionError::AuthenticationError { .. }) => {
+                AuthenticationError::new_err(message)
+            }
+            BinaryErrorPy::PocketOptionError(PocketOptionError::RegionMismatch { .. }) => {
+                RegionMismatchError::new_err(message)
+            }
+            BinaryErrorPy::PocketOptionError(PocketOptionError::TradeTimeout { .. }) => {
+                TradeTimeoutError::new_err(message)
+            }
+            BinaryErrorPy::PocketOptionError(PocketOptionError::PayoutTooLow { .. }) => {
+                PayoutTooLowError::new_err(message)
+            }
+            BinaryErrorPy::PocketOptionError(PocketOptionError::AssetClosed(_)) => {
+                AssetClosedError::new_err(message)
+            }
+            _ => PyValueError::new_err(message),
+        }
     }
 }
 
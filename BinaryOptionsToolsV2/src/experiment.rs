@@ -0,0 +1,26 @@
+use binary_options_tools::pocketoption::{experiment::ComparisonReport, journal::JournalEntry};
+use pyo3::{pyfunction, PyResult};
+
+use crate::error::BinaryErrorPy;
+
+/// Compares the arms `arm_a` and `arm_b` of the experiment `name` from the journals (as json) of
+/// their accounts, returns the comparison report as json.
+#[pyfunction]
+pub fn compare_experiment(
+    name: String,
+    arm_a: String,
+    journal_a: String,
+    arm_b: String,
+    journal_b: String,
+) -> PyResult<String> {
+    let journal_a: Vec<JournalEntry> =
+        serde_json::from_str(&journal_a).map_err(BinaryErrorPy::from)?;
+    let journal_b: Vec<JournalEntry> =
+        serde_json::from_str(&journal_b).map_err(BinaryErrorPy::from)?;
+    let arms = [
+        (arm_a.as_str(), journal_a.as_slice()),
+        (arm_b.as_str(), journal_b.as_slice()),
+    ];
+    let report = ComparisonReport::new(&name, arms);
+    Ok(serde_json::to_string(&report).map_err(BinaryErrorPy::from)?)
+}
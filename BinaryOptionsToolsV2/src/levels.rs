@@ -0,0 +1,36 @@
+use binary_options_tools::pocketoption::{
+    levels::{Levels, LevelsConfig},
+    types::update::DataCandle,
+};
+use pyo3::{pyfunction, PyResult};
+use serde::Serialize;
+
+use crate::error::BinaryErrorPy;
+
+/// Candle of a stream with the levels of the last candles, yielded by `StreamIterator.levels`.
+#[derive(Serialize)]
+pub struct CandleLevels {
+    pub asset: String,
+    pub candle: DataCandle,
+    pub levels: Levels,
+}
+
+/// Parses the json of a `LevelsConfig`, the default config if `None`.
+pub fn levels_config(config: Option<String>) -> PyResult<LevelsConfig> {
+    let config = match config {
+        Some(config) => serde_json::from_str(&config).map_err(BinaryErrorPy::from)?,
+        None => LevelsConfig::default(),
+    };
+    config.validate().map_err(BinaryErrorPy::from)?;
+    Ok(config)
+}
+
+/// Computes the pivots, session highs / lows and support / resistance zones of a history of
+/// candles (as json), returns the json of the levels.
+#[pyfunction]
+#[pyo3(signature = (candles, config = None))]
+pub fn compute_levels(candles: String, config: Option<String>) -> PyResult<String> {
+    let candles: Vec<DataCandle> = serde_json::from_str(&candles).map_err(BinaryErrorPy::from)?;
+    let levels = Levels::compute(&candles, &levels_config(config)?).map_err(BinaryErrorPy::from)?;
+    Ok(serde_json::to_string(&levels).map_err(BinaryErrorPy::from)?)
+}
@@ -1,20 +1,61 @@
 #![allow(non_snake_case)]
 
+mod access;
+mod asset;
+mod benchmarks;
+mod candle;
+#[cfg(feature = "charts")]
+mod chart;
 mod error;
+mod experiment;
+mod levels;
+mod locale;
 mod logs;
+mod output;
+mod patterns;
 mod pocketoption;
+mod protocol;
+mod registry;
+mod replay;
 mod runtime;
+mod ssid;
+mod storage;
 mod stream;
 mod validator;
 mod config;
+mod deal;
 
+use access::PyAccessControl;
+use asset::PyAsset;
+use benchmarks::{json_backend, run_benchmarks_py};
+use candle::PyCandle;
+#[cfg(feature = "charts")]
+use chart::render_chart;
 use config::PyConfig;
-use logs::{start_tracing, LogBuilder, Logger, StreamLogsIterator, StreamLogsLayer};
-use pocketoption::{RawPocketOption, RawStreamIterator, StreamIterator};
+use deal::{PyDeal, PyOrderDirection};
+use error::{
+    AssetClosedError, AuthenticationError, ObserverModeError, PayoutTooLowError,
+    PermissionDeniedError, RegionMismatchError, TradeTimeoutError,
+};
+use experiment::compare_experiment;
+use levels::compute_levels;
+use locale::{get_locale, set_locale_py};
+use logs::{
+    add_redaction_pattern, redact_secrets, start_tracing, verify_audit_file, LogBuilder, Logger,
+    StreamLogsIterator, StreamLogsLayer,
+};
+use patterns::detect_patterns;
+use pocketoption::{EventIterator, RawPocketOption, RawStreamIterator, StreamIterator};
+use protocol::{check_fixtures_py, load_fixtures_py, parse_frame_py};
 use pyo3::prelude::*;
+use replay::CandleReplay;
+use ssid::parse_ssid;
+use storage::{set_storage_dirs, storage_dirs};
+use stream::{merge_streams, MergedStreamIterator};
 use validator::RawValidator;
 
-#[pymodule]
+// The module doesn't rely on the GIL for its own state, so it can run on free-threaded builds
+#[pymodule(gil_used = false)]
 #[pyo3(name = "BinaryOptionsToolsV2")]
 fn BinaryOptionsTools(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<StreamLogsIterator>()?;
@@ -23,10 +64,47 @@ fn BinaryOptionsTools(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<Logger>()?;
     m.add_class::<LogBuilder>()?;
     m.add_class::<StreamIterator>()?;
+    m.add_class::<PyCandle>()?;
+    m.add_class::<PyOrderDirection>()?;
+    m.add_class::<PyDeal>()?;
+    m.add_class::<PyAsset>()?;
     m.add_class::<RawStreamIterator>()?;
+    m.add_class::<EventIterator>()?;
+    m.add_class::<MergedStreamIterator>()?;
+    m.add_class::<CandleReplay>()?;
     m.add_class::<RawValidator>()?;
     m.add_class::<PyConfig>()?;
+    m.add_class::<PyAccessControl>()?;
+    m.add("ObserverModeError", m.py().get_type::<ObserverModeError>())?;
+    m.add(
+        "PermissionDeniedError",
+        m.py().get_type::<PermissionDeniedError>(),
+    )?;
+    m.add("AuthenticationError", m.py().get_type::<AuthenticationError>())?;
+    m.add("RegionMismatchError", m.py().get_type::<RegionMismatchError>())?;
+    m.add("TradeTimeoutError", m.py().get_type::<TradeTimeoutError>())?;
+    m.add("PayoutTooLowError", m.py().get_type::<PayoutTooLowError>())?;
+    m.add("AssetClosedError", m.py().get_type::<AssetClosedError>())?;
 
     m.add_function(wrap_pyfunction!(start_tracing, m)?)?;
+    m.add_function(wrap_pyfunction!(redact_secrets, m)?)?;
+    m.add_function(wrap_pyfunction!(add_redaction_pattern, m)?)?;
+    m.add_function(wrap_pyfunction!(verify_audit_file, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_frame_py, m)?)?;
+    m.add_function(wrap_pyfunction!(load_fixtures_py, m)?)?;
+    m.add_function(wrap_pyfunction!(check_fixtures_py, m)?)?;
+    m.add_function(wrap_pyfunction!(merge_streams, m)?)?;
+    m.add_function(wrap_pyfunction!(run_benchmarks_py, m)?)?;
+    m.add_function(wrap_pyfunction!(json_backend, m)?)?;
+    m.add_function(wrap_pyfunction!(storage_dirs, m)?)?;
+    m.add_function(wrap_pyfunction!(set_storage_dirs, m)?)?;
+    m.add_function(wrap_pyfunction!(get_locale, m)?)?;
+    m.add_function(wrap_pyfunction!(set_locale_py, m)?)?;
+    m.add_function(wrap_pyfunction!(compare_experiment, m)?)?;
+    m.add_function(wrap_pyfunction!(detect_patterns, m)?)?;
+    m.add_function(wrap_pyfunction!(compute_levels, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_ssid, m)?)?;
+    #[cfg(feature = "charts")]
+    m.add_function(wrap_pyfunction!(render_chart, m)?)?;
     Ok(())
 }
@@ -0,0 +1,19 @@
+use binary_options_tools::locale::{locale, set_locale, Locale};
+use pyo3::{pyfunction, PyResult};
+
+use crate::error::BinaryErrorPy;
+
+/// Returns the language of the error messages (`en`, `es`, `pt` or `ru`).
+#[pyfunction]
+pub fn get_locale() -> String {
+    locale().to_string()
+}
+
+/// Changes the language of the error messages, `locale` is a language code like `es` or `pt_BR`.
+/// It defaults to the `BOT_LOCALE` environment variable, or to the language of the system.
+#[pyfunction(name = "set_locale")]
+pub fn set_locale_py(locale: String) -> PyResult<()> {
+    let locale: Locale = locale.parse().map_err(BinaryErrorPy::from)?;
+    set_locale(locale);
+    Ok(())
+}
@@ -1,18 +1,30 @@
-use std::{fs::OpenOptions, io::Write, sync::Arc};
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use binary_options_tools::{
+    audit::{audit_layer, verify_audit_log},
     error::BinaryOptionsResult,
-    stream::{stream_logs_layer, RecieverStream},
+    storage::create_parent_dirs,
+    stream::{add_secret_pattern, set_redaction, RedactingWriter},
 };
-use chrono::Duration;
-use futures_util::{
-    stream::{BoxStream, Fuse},
-    StreamExt,
+#[cfg(feature = "logging-stream")]
+use binary_options_tools::stream::{
+    stream_logs_broadcast_layer, stream_logs_layer_filtered, LogBroadcast, LogFilter,
+    RecieverStream, StreamDeadline,
 };
+#[cfg(feature = "logging-stream")]
+use chrono::Duration;
+#[cfg(feature = "logging-stream")]
+use futures_util::StreamExt;
+use futures_util::stream::{BoxStream, Fuse};
 use pyo3::{pyclass, pyfunction, pymethods, Bound, Py, PyAny, PyResult, Python};
 use pyo3_async_runtimes::tokio::future_into_py;
 use tokio::sync::Mutex;
-use tracing::{debug, instrument, level_filters::LevelFilter, warn, Level};
+use tracing::{debug, instrument, level_filters::LevelFilter, Level};
 use tracing_subscriber::{
     fmt::{self, MakeWriter},
     layer::SubscriberExt,
@@ -20,66 +32,203 @@ use tracing_subscriber::{
     Layer, Registry,
 };
 
-use crate::{error::BinaryErrorPy, runtime::get_runtime, stream::next_stream};
+use crate::{
+    error::BinaryErrorPy,
+    runtime::get_runtime,
+    stream::{next_stream, text_stream, TextStream},
+};
 
 const TARGET: &str = "Python";
 
+/// Installs the logs. Without `path` no file is written, so it can run in read-only containers,
+/// and with `json` the terminal records are written to stdout as one JSON object per line.
+/// A log file that can't be created isn't fatal, the logs keep going to the other outputs and a
+/// warning is logged.
 #[pyfunction]
+#[pyo3(signature = (path, level, terminal, layers, json = false))]
 pub fn start_tracing(
-    path: String,
+    path: Option<String>,
     level: String,
     terminal: bool,
     layers: Vec<StreamLogsLayer>,
+    json: bool,
 ) -> PyResult<()> {
     let level: LevelFilter = level.parse().unwrap_or(Level::DEBUG.into());
-    let error_logs = OpenOptions::new()
-        .append(true)
-        .create(true)
-        .open(format!("{}/error.log", &path))?;
-    let logs = OpenOptions::new()
-        .append(true)
-        .create(true)
-        .open(format!("{}/logs.log", &path))?;
     let default = fmt::Layer::default().with_writer(NoneWriter).boxed();
     let mut layers = layers
-        .into_iter()
-        .flat_map(|l| Arc::try_unwrap(l.layer))
-        .collect::<Vec<Box<dyn Layer<Registry> + Send + Sync>>>();
+        .iter()
+        .map(StreamLogsLayer::layer)
+        .collect::<PyResult<Vec<Box<dyn Layer<Registry> + Send + Sync>>>>()?;
     layers.push(default);
-    println!("Length of layers: {}", layers.len());
-    let subscriber = tracing_subscriber::registry()
-        // .with(filtered_layer)
-        .with(layers)
-        .with(
-            // log-error file, to log the errors that arise
-            fmt::layer()
-                .with_ansi(false)
-                .with_writer(error_logs)
-                .with_filter(LevelFilter::WARN),
-        )
-        .with(
-            // log-debug file, to log the debug
+    let mut warnings = Vec::new();
+    if let Some(path) = path {
+        // log-error file, to log the errors that arise
+        let path = Path::new(&path);
+        layers.extend(file_layer(
+            path.join("error.log"),
+            LevelFilter::WARN,
+            &mut warnings,
+        ));
+        // log-debug file, to log the debug
+        layers.extend(file_layer(path.join("logs.log"), level, &mut warnings));
+    }
+    if terminal {
+        layers.push(stdout_layer(level, json));
+    }
+    install(layers, warnings);
+
+    Ok(())
+}
+
+/// Layer writing the records of `level` to the file at `path`, its missing directories are created.
+/// If the file can't be opened (permissions, read-only filesystem) the reason is added to `warnings`
+/// and no layer is returned.
+fn file_layer(
+    path: PathBuf,
+    level: LevelFilter,
+    warnings: &mut Vec<String>,
+) -> Option<Box<dyn Layer<Registry> + Send + Sync>> {
+    let opened = create_parent_dirs(&path)
+        .and_then(|_| OpenOptions::new().append(true).create(true).open(&path));
+    match opened {
+        Ok(logs) => Some(
             fmt::layer()
                 .with_ansi(false)
-                .with_writer(logs)
-                .with_filter(level),
-        );
+                .with_writer(RedactingWriter::new(logs))
+                .with_filter(level)
+                .boxed(),
+        ),
+        Err(e) => {
+            warnings.push(format!(
+                "Couldn't open the log file '{}', skipping it: {e}",
+                path.display()
+            ));
+            None
+        }
+    }
+}
 
-    if terminal {
-        subscriber
-            .with(fmt::Layer::default().with_filter(level))
-            .init();
+/// Installs the layers as the global subscriber and logs the `warnings` raised while creating them.
+/// If a subscriber is already installed the new layers are dropped with a warning instead of panicking.
+fn install(layers: Vec<Box<dyn Layer<Registry> + Send + Sync>>, mut warnings: Vec<String>) {
+    if let Err(e) = tracing_subscriber::registry().with(layers).try_init() {
+        warnings.push(format!(
+            "Couldn't install the logs, keeping the current ones: {e}"
+        ));
+    }
+    for warning in warnings {
+        tracing::warn!(target: TARGET, "{warning}");
+    }
+}
+
+fn stdout_layer(level: LevelFilter, json: bool) -> Box<dyn Layer<Registry> + Send + Sync> {
+    if json {
+        fmt::layer()
+            .json()
+            .flatten_event(true)
+            .with_writer(RedactingWriter::new(std::io::stdout))
+            .with_filter(level)
+            .boxed()
     } else {
-        subscriber.init()
+        fmt::Layer::default()
+            .with_writer(RedactingWriter::new(std::io::stdout))
+            .with_filter(level)
+            .boxed()
     }
+}
 
+/// Enables or disables the redaction of secrets (SSID, auth frames, tokens) in every log output
+/// and in raw messages captured by `create_raw_order` / `create_raw_iterator`.
+/// Redaction is enabled by default, only disable it for deep debugging.
+#[pyfunction]
+pub fn redact_secrets(enabled: bool) {
+    set_redaction(enabled);
+}
+
+/// Adds an extra regex pattern whose matches will be redacted from the logs, only a group named
+/// `prefix` is kept.
+#[pyfunction]
+pub fn add_redaction_pattern(pattern: String) -> PyResult<()> {
+    add_secret_pattern(&pattern).map_err(BinaryErrorPy::from)?;
     Ok(())
 }
 
+/// Verifies the hash chain of an audit file, returning the number of records.
+/// Raises an error if any record was modified, removed or reordered.
+#[pyfunction]
+pub fn verify_audit_file(path: String) -> PyResult<u64> {
+    Ok(verify_audit_log(path).map_err(BinaryErrorPy::from)?)
+}
+
+/// Stream of logs that can be installed with `start_tracing` and feed any number of iterators,
+/// created before or after the layer is installed.
 #[pyclass]
 #[derive(Clone)]
 pub struct StreamLogsLayer {
-    layer: Arc<Box<dyn Layer<Registry> + Send + Sync>>,
+    level: LevelFilter,
+    #[cfg(feature = "logging-stream")]
+    broadcast: LogBroadcast,
+}
+
+impl StreamLogsLayer {
+    fn layer(&self) -> PyResult<Box<dyn Layer<Registry> + Send + Sync>> {
+        #[cfg(feature = "logging-stream")]
+        {
+            Ok(stream_logs_broadcast_layer(
+                self.level,
+                self.broadcast.clone(),
+            ))
+        }
+        #[cfg(not(feature = "logging-stream"))]
+        {
+            Err(BinaryErrorPy::NotAllowed(
+                "Streaming logs requires the 'logging-stream' feature".to_string(),
+            )
+            .into())
+        }
+    }
+
+    /// Broadcast of the records of the layer, so the dashboard can stream them.
+    #[cfg(feature = "dashboard")]
+    pub(crate) fn broadcast(&self) -> LogBroadcast {
+        self.broadcast.clone()
+    }
+}
+
+#[pymethods]
+impl StreamLogsLayer {
+    #[new]
+    #[pyo3(signature = (level = "DEBUG".to_string()))]
+    pub fn new(level: String) -> Self {
+        Self {
+            level: level.parse().unwrap_or(Level::DEBUG.into()),
+            #[cfg(feature = "logging-stream")]
+            broadcast: LogBroadcast::new(),
+        }
+    }
+
+    /// Creates a new iterator over the logs of this layer, with its own timeout and filters
+    /// (see `LogBuilder.create_logs_iterator`).
+    #[cfg(feature = "logging-stream")]
+    #[pyo3(signature = (timeout = None, targets = None, filters = None))]
+    pub fn create_iterator(
+        &self,
+        timeout: Option<Duration>,
+        targets: Option<Vec<String>>,
+        filters: Option<Vec<String>>,
+    ) -> PyResult<StreamLogsIterator> {
+        let filter = log_filter(targets, filters)?;
+        let timeout = timeout.map(to_std_duration).transpose()?;
+        Ok(StreamLogsIterator::new(
+            self.broadcast.subscribe(filter, timeout),
+        ))
+    }
+
+    /// Number of iterators currently receiving the logs of this layer.
+    #[cfg(feature = "logging-stream")]
+    pub fn consumers(&self) -> usize {
+        self.broadcast.consumers()
+    }
 }
 
 struct NoneWriter;
@@ -106,6 +255,40 @@ type LogStream = Fuse<BoxStream<'static, BinaryOptionsResult<String>>>;
 #[pyclass]
 pub struct StreamLogsIterator {
     stream: Arc<Mutex<LogStream>>,
+    #[cfg(feature = "logging-stream")]
+    deadline: Arc<StreamDeadline>,
+}
+
+#[cfg(feature = "logging-stream")]
+impl StreamLogsIterator {
+    fn new(receiver: RecieverStream<String>) -> Self {
+        let deadline = receiver.deadline();
+        let stream = RecieverStream::to_stream_static(Arc::new(receiver))
+            .boxed()
+            .fuse();
+        Self {
+            stream: Arc::new(Mutex::new(stream)),
+            deadline,
+        }
+    }
+}
+
+impl StreamLogsIterator {
+    pub(crate) fn text_stream(&self) -> TextStream {
+        text_stream(self.stream.clone(), |log| log)
+    }
+}
+
+#[cfg(feature = "logging-stream")]
+fn log_filter(targets: Option<Vec<String>>, filters: Option<Vec<String>>) -> PyResult<LogFilter> {
+    let mut filter = LogFilter::new();
+    for target in targets.unwrap_or_default() {
+        filter = filter.target(target);
+    }
+    for predicate in filters.unwrap_or_default() {
+        filter = filter.predicate(predicate.parse().map_err(BinaryErrorPy::from)?);
+    }
+    Ok(filter)
 }
 
 #[pymethods]
@@ -118,7 +301,7 @@ impl StreamLogsIterator {
         slf
     }
 
-    fn __anext__<'py>(&'py mut self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+    fn __anext__<'py>(&'py self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
         let stream = self.stream.clone();
         future_into_py(py, next_stream(stream, false))
     }
@@ -128,12 +311,48 @@ impl StreamLogsIterator {
         let stream = self.stream.clone();
         runtime.block_on(next_stream(stream, true))
     }
+
+    /// Timeout of this iterator only, other iterators and layers aren't affected.
+    #[cfg(feature = "logging-stream")]
+    #[getter]
+    fn timeout(&self) -> Option<std::time::Duration> {
+        self.deadline.timeout()
+    }
+
+    #[cfg(feature = "logging-stream")]
+    #[pyo3(signature = (timeout = None))]
+    fn set_timeout(&self, timeout: Option<Duration>) -> PyResult<()> {
+        let timeout = timeout.map(to_std_duration).transpose()?;
+        self.deadline.set_timeout(timeout);
+        Ok(())
+    }
+
+    #[cfg(feature = "logging-stream")]
+    fn reset_timeout(&self) {
+        self.deadline.reset();
+    }
+
+    #[cfg(feature = "logging-stream")]
+    fn extend(&self, timeout: Duration) -> PyResult<()> {
+        self.deadline.extend(to_std_duration(timeout)?);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "logging-stream")]
+fn to_std_duration(duration: Duration) -> PyResult<std::time::Duration> {
+    Ok(duration.to_std().map_err(|e| {
+        BinaryErrorPy::from(binary_options_tools::error::BinaryOptionsToolsError::from(
+            e,
+        ))
+    })?)
 }
 
 #[pyclass]
 #[derive(Default)]
 pub struct LogBuilder {
     layers: Vec<Box<dyn Layer<Registry> + Send + Sync>>,
+    warnings: Vec<String>,
     build: bool,
 }
 
@@ -144,52 +363,72 @@ impl LogBuilder {
         Self::default()
     }
 
-    #[pyo3(signature = (level = "DEBUG".to_string(), timeout = None))]
+    /// `targets` keeps only the records whose target starts with one of them, `filters` are conditions on the
+    /// fields of the records (`field == value`, `field != value` or `field` to require the field), both are
+    /// applied before the records are sent to Python.
+    #[cfg(feature = "logging-stream")]
+    #[pyo3(signature = (level = "DEBUG".to_string(), timeout = None, targets = None, filters = None))]
     pub fn create_logs_iterator(
         &mut self,
         level: String,
         timeout: Option<Duration>,
-    ) -> StreamLogsIterator {
+        targets: Option<Vec<String>>,
+        filters: Option<Vec<String>>,
+    ) -> PyResult<StreamLogsIterator> {
+        let filter = log_filter(targets, filters)?;
         let timeout = match timeout {
             Some(timeout) => match timeout.to_std() {
                 Ok(timeout) => Some(timeout),
                 Err(e) => {
-                    warn!("Error converting duration to std, {e}");
+                    tracing::warn!("Error converting duration to std, {e}");
                     None
                 }
             },
             None => None,
         };
-        let (layer, inner_iter) =
-            stream_logs_layer(level.parse().unwrap_or(Level::DEBUG.into()), timeout);
-        let stream = RecieverStream::to_stream_static(Arc::new(inner_iter))
-            .boxed()
-            .fuse();
-        let iter = StreamLogsIterator {
-            stream: Arc::new(Mutex::new(stream)),
-        };
+        let (layer, inner_iter) = stream_logs_layer_filtered(
+            level.parse().unwrap_or(Level::DEBUG.into()),
+            timeout,
+            filter,
+        );
         self.layers.push(layer);
-        iter
+        Ok(StreamLogsIterator::new(inner_iter))
     }
 
+    /// Installs a `StreamLogsLayer`, its iterators (existing or created later) receive the logs once
+    /// the builder is built.
+    pub fn add_stream_layer(&mut self, layer: &StreamLogsLayer) -> PyResult<()> {
+        self.layers.push(layer.layer()?);
+        Ok(())
+    }
+
+    /// If the file can't be created it's skipped, and a warning is logged once the builder is built.
     #[pyo3(signature = (path = "logs.log".to_string(), level = "DEBUG".to_string()))]
     pub fn log_file(&mut self, path: String, level: String) -> PyResult<()> {
-        let logs = OpenOptions::new().append(true).create(true).open(path)?;
-        let layer = fmt::layer()
-            .with_ansi(false)
-            .with_writer(logs)
-            .with_filter(level.parse().unwrap_or(LevelFilter::DEBUG))
-            .boxed();
-        self.layers.push(layer);
+        self.layers.extend(file_layer(
+            PathBuf::from(path),
+            level.parse().unwrap_or(LevelFilter::DEBUG),
+            &mut self.warnings,
+        ));
         Ok(())
     }
 
-    #[pyo3(signature = (level = "DEBUG".to_string()))]
-    pub fn terminal(&mut self, level: String) {
-        let layer = fmt::Layer::default()
-            .with_filter(level.parse().unwrap_or(LevelFilter::DEBUG))
-            .boxed();
+    /// Records every order request / response and config change into an append-only,
+    /// hash-chained audit file, separate from the operational logs.
+    #[pyo3(signature = (path = "audit.log".to_string()))]
+    pub fn audit_file(&mut self, path: String) -> PyResult<()> {
+        let layer = audit_layer(path).map_err(BinaryErrorPy::from)?;
         self.layers.push(layer);
+        Ok(())
+    }
+
+    /// With `json` every record is written to stdout as one JSON object per line.
+    #[pyo3(signature = (level = "DEBUG".to_string(), json = false))]
+    pub fn terminal(&mut self, level: String, json: bool) {
+        self.layers.push(stdout_layer(
+            level.parse().unwrap_or(LevelFilter::DEBUG),
+            json,
+        ));
     }
 
     pub fn build(&mut self) -> PyResult<()> {
@@ -206,7 +445,7 @@ impl LogBuilder {
             .layers
             .drain(..)
             .collect::<Vec<Box<dyn Layer<Registry> + Send + Sync>>>();
-        tracing_subscriber::registry().with(layers).init();
+        install(layers, std::mem::take(&mut self.warnings));
         Ok(())
     }
 }
@@ -255,30 +494,57 @@ mod tests {
 
     #[test]
     fn test_start_tracing() {
-        start_tracing(".".to_string(), "DEBUG".to_string(), true, vec![]).unwrap();
+        start_tracing(
+            Some(".".to_string()),
+            "DEBUG".to_string(),
+            true,
+            vec![],
+            false,
+        )
+        .unwrap();
 
         info!("Test")
     }
 
+    #[test]
+    fn test_start_tracing_unwritable_path() {
+        // The missing directories are created, a path under a file can't be
+        let file = std::env::temp_dir().join(format!("not-a-dir-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&file, "").unwrap();
+        let mut warnings = Vec::new();
+        assert!(file_layer(file.join("logs.log"), LevelFilter::DEBUG, &mut warnings).is_none());
+        assert_eq!(warnings.len(), 1);
+        // The missing files are skipped and the other outputs are still installed
+        start_tracing(
+            Some(file.join("logs").to_string_lossy().to_string()),
+            "DEBUG".to_string(),
+            true,
+            vec![],
+            false,
+        )
+        .unwrap();
+        std::fs::remove_file(&file).unwrap();
+    }
+
+    #[cfg(feature = "logging-stream")]
     fn create_logs_iterator_test(level: String) -> (StreamLogsLayer, StreamLogsIterator) {
-        let (inner_layer, inner_iter) =
-            stream_logs_layer(level.parse().unwrap_or(Level::DEBUG.into()), None);
-        let layer = StreamLogsLayer {
-            layer: Arc::new(inner_layer),
-        };
-        let stream = RecieverStream::to_stream_static(Arc::new(inner_iter))
-            .boxed()
-            .fuse();
-        let iter = StreamLogsIterator {
-            stream: Arc::new(Mutex::new(stream)),
-        };
+        let layer = StreamLogsLayer::new(level);
+        let iter = layer.create_iterator(None, None, None).unwrap();
         (layer, iter)
     }
 
+    #[cfg(feature = "logging-stream")]
     #[tokio::test]
     async fn test_start_tracing_stream() {
         let (layer, receiver) = create_logs_iterator_test("ERROR".to_string());
-        start_tracing(".".to_string(), "DEBUG".to_string(), false, vec![layer]).unwrap();
+        start_tracing(
+            Some(".".to_string()),
+            "DEBUG".to_string(),
+            false,
+            vec![layer],
+            false,
+        )
+        .unwrap();
 
         async fn log() {
             let mut num = 0;
@@ -0,0 +1,167 @@
+use pyo3::{IntoPyObjectExt, Py, PyAny, PyResult, Python};
+use pythonize::pythonize;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::BinaryErrorPy;
+
+/// How the missing values of the results (the `None` fields and the NaN numbers) are written, some
+/// strict json parsers reject the nulls.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NullPolicy {
+    /// Written as they come, `None` / `null` (NaN numbers are `null` in the json strings).
+    #[default]
+    Null,
+    /// The fields are removed from the objects, the typed accessors return `None`.
+    Omit,
+    /// Replaced by `0` (the missing fields of the deals and candles are numbers), the timestamps of
+    /// the typed accessors stay `None`.
+    Default,
+}
+
+impl NullPolicy {
+    pub fn parse(policy: &str) -> Result<Self, BinaryErrorPy> {
+        match policy.trim().to_ascii_lowercase().as_str() {
+            "null" | "none" => Ok(Self::Null),
+            "omit" => Ok(Self::Omit),
+            "default" => Ok(Self::Default),
+            _ => Err(BinaryErrorPy::NotAllowed(format!(
+                "unknown null policy '{policy}', expected 'null', 'omit' or 'default'"
+            ))),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Null => "null",
+            Self::Omit => "omit",
+            Self::Default => "default",
+        }
+    }
+
+    /// Applies the policy to a json value, NaN numbers are already `null` in a `Value`.
+    pub fn apply(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                if *self == Self::Omit {
+                    map.retain(|_, value| !value.is_null());
+                }
+                map.values_mut().for_each(|value| self.apply(value));
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.apply(item)),
+            Value::Null if *self == Self::Default => *value = Value::from(0),
+            _ => {}
+        }
+    }
+
+    /// Value of an optional number for a typed accessor.
+    pub fn number(&self, value: Option<f64>) -> Option<f64> {
+        match value.filter(|value| !value.is_nan()) {
+            None if *self == Self::Default => Some(0.0),
+            value => value,
+        }
+    }
+
+    /// Value of a number for a typed accessor, NaN is only replaced with the `Default` policy.
+    pub fn finite(&self, value: f64) -> f64 {
+        if value.is_nan() && *self == Self::Default {
+            0.0
+        } else {
+            value
+        }
+    }
+}
+
+/// Format of the results returned to python.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OutputFormat {
+    /// Returns the results as json strings instead of dicts and lists, like the previous versions.
+    pub json_strings: bool,
+    pub nulls: NullPolicy,
+}
+
+impl OutputFormat {
+    pub fn new(json_strings: bool, nulls: NullPolicy) -> Self {
+        Self {
+            json_strings,
+            nulls,
+        }
+    }
+
+    /// Converts a value to its json with the null policy applied.
+    pub fn to_value<T: Serialize + ?Sized>(&self, value: &T) -> Result<Value, BinaryErrorPy> {
+        let mut value = serde_json::to_value(value)?;
+        self.nulls.apply(&mut value);
+        Ok(value)
+    }
+
+    /// Converts a value to its json string with the null policy applied.
+    pub fn to_json<T: Serialize + ?Sized>(&self, value: &T) -> Result<String, BinaryErrorPy> {
+        match self.nulls {
+            NullPolicy::Null => Ok(serde_json::to_string(value)?),
+            _ => Ok(self.to_value(value)?.to_string()),
+        }
+    }
+
+    /// Converts a value to native python objects (dicts, lists...) with the null policy applied.
+    pub fn to_object<T: Serialize + ?Sized>(
+        &self,
+        py: Python<'_>,
+        value: &T,
+    ) -> PyResult<Py<PyAny>> {
+        match self.nulls {
+            NullPolicy::Null => Ok(pythonize(py, value)?.unbind()),
+            _ => Ok(pythonize(py, &self.to_value(value)?)?.unbind()),
+        }
+    }
+
+    /// Converts a result to native python objects, or to its json string with `json_strings`.
+    pub fn to_py<T: Serialize + ?Sized>(&self, py: Python<'_>, value: &T) -> PyResult<Py<PyAny>> {
+        if self.json_strings {
+            self.to_json(value)?.into_py_any(py)
+        } else {
+            self.to_object(py, value)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_null_policy() {
+        let value = serde_json::json!({
+            "profit": f64::NAN,
+            "closeMs": null,
+            "amount": 1.5,
+            "candles": [{"latency": null}, null],
+        });
+        // serde_json writes the NaN numbers as null
+        assert!(value["profit"].is_null());
+
+        let mut omitted = value.clone();
+        NullPolicy::Omit.apply(&mut omitted);
+        assert_eq!(
+            omitted,
+            serde_json::json!({"amount": 1.5, "candles": [{}, null]})
+        );
+
+        let mut defaulted = value.clone();
+        NullPolicy::Default.apply(&mut defaulted);
+        assert_eq!(defaulted["profit"], 0);
+        assert_eq!(defaulted["candles"][0]["latency"], 0);
+        assert_eq!(defaulted["candles"][1], 0);
+
+        let mut unchanged = value.clone();
+        NullPolicy::Null.apply(&mut unchanged);
+        assert_eq!(unchanged, value);
+
+        assert_eq!(NullPolicy::parse("OMIT").unwrap(), NullPolicy::Omit);
+        assert!(NullPolicy::parse("zero").is_err());
+        assert_eq!(NullPolicy::Default.number(Some(f64::NAN)), Some(0.0));
+        assert_eq!(NullPolicy::Omit.number(None), None);
+        assert!(NullPolicy::Null.finite(f64::NAN).is_nan());
+    }
+}
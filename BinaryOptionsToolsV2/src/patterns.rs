@@ -0,0 +1,36 @@
+use binary_options_tools::pocketoption::{
+    patterns::{scan, PatternConfig, PatternEvent},
+    types::update::DataCandle,
+};
+use pyo3::{pyfunction, PyResult};
+use serde::Serialize;
+
+use crate::error::BinaryErrorPy;
+
+/// Candle of a stream with the patterns it completes, yielded by `StreamIterator.patterns`.
+#[derive(Serialize)]
+pub struct CandlePatterns {
+    pub asset: String,
+    pub candle: DataCandle,
+    pub patterns: Vec<PatternEvent>,
+}
+
+/// Parses the json of a `PatternConfig`, the default config if `None`.
+pub fn pattern_config(config: Option<String>) -> PyResult<PatternConfig> {
+    let config = match config {
+        Some(config) => serde_json::from_str(&config).map_err(BinaryErrorPy::from)?,
+        None => PatternConfig::default(),
+    };
+    config.validate().map_err(BinaryErrorPy::from)?;
+    Ok(config)
+}
+
+/// Finds the candlestick patterns of a history of closed candles (as json), returns the json list
+/// of the pattern events in chronological order.
+#[pyfunction]
+#[pyo3(signature = (candles, config = None))]
+pub fn detect_patterns(candles: String, config: Option<String>) -> PyResult<String> {
+    let candles: Vec<DataCandle> = serde_json::from_str(&candles).map_err(BinaryErrorPy::from)?;
+    let events = scan(&candles, pattern_config(config)?).map_err(BinaryErrorPy::from)?;
+    Ok(serde_json::to_string(&events).map_err(BinaryErrorPy::from)?)
+}
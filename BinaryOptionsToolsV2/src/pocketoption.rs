@@ -1,50 +1,362 @@
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+#[cfg(feature = "http-gateway")]
+use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::str;
 use std::sync::Arc;
 use std::time::Duration;
 
 use binary_options_tools::error::{BinaryOptionsResult, BinaryOptionsToolsError};
-use binary_options_tools::pocketoption::error::PocketResult;
+use binary_options_tools::pocketoption::error::{PocketOptionError, PocketResult};
+use binary_options_tools::pocketoption::expectancy::{TradeFilter, DEFAULT_CONFIDENCE};
+#[cfg(feature = "arrow")]
+use binary_options_tools::pocketoption::interop::to_ipc_stream;
+use binary_options_tools::pocketoption::journal::ContextCapture;
+use binary_options_tools::pocketoption::levels::LevelTracker;
+use binary_options_tools::pocketoption::market_guard::MarketGuardConfig;
+use binary_options_tools::pocketoption::orders::{OrderOptions, OrderPlacement};
+use binary_options_tools::pocketoption::patterns::PatternDetector;
 use binary_options_tools::pocketoption::pocket_client::PocketOption;
+use binary_options_tools::pocketoption::risk::TradingHours;
+use binary_options_tools::pocketoption::stake::StakeRules;
+use binary_options_tools::pocketoption::templates::{OrderTemplate, TemplateStore};
 use binary_options_tools::pocketoption::types::base::RawWebsocketMessage;
+use binary_options_tools::pocketoption::types::order::Action;
+use binary_options_tools::pocketoption::types::subscriptions::SubscriptionPolicy;
 use binary_options_tools::pocketoption::types::update::DataCandle;
+use binary_options_tools::pocketoption::utils::download::{DownloadProgress, HistoryFormat};
+use binary_options_tools::pocketoption::utils::history::CandleAudit;
+use binary_options_tools::pocketoption::utils::recording::{CandleRecorder, Compression};
+use binary_options_tools::pocketoption::ws::candle_close::CandleCloseStream;
 use binary_options_tools::pocketoption::ws::stream::StreamAsset;
-use binary_options_tools::reimports::FilteredRecieverStream;
-use futures_util::stream::{BoxStream, Fuse};
-use futures_util::StreamExt;
-use pyo3::{pyclass, pymethods, Bound, IntoPyObjectExt, Py, PyAny, PyResult, Python};
+use binary_options_tools::reimports::{FilteredRecieverStream, ValidatorTrait};
+use binary_options_tools::stream::{redact, RecieverStream, StreamDeadline};
+use chrono::{DateTime, Utc};
+use futures_util::stream::{select_all, unfold, AbortHandle, Abortable, BoxStream, Fuse};
+use futures_util::{future, Stream, StreamExt};
+#[cfg(any(feature = "charts", feature = "arrow"))]
+use pyo3::types::PyBytes;
+use pyo3::types::{PyAnyMethods, PyDict, PyDictMethods};
+use pyo3::{pyclass, pymethods, Bound, IntoPyObjectExt, Py, PyAny, PyErr, PyResult, Python};
 use pyo3_async_runtimes::tokio::future_into_py;
+use serde::Serialize;
+use tracing::warn;
 use url::Url;
 use uuid::Uuid;
 
+#[cfg(feature = "http-gateway")]
+use crate::access::PyAccessControl;
+use crate::asset::PyAsset;
+use crate::candle::{candles_to_columns, candles_to_py, AssetCandle};
+use crate::deal::{extract_direction, PyDeal};
+#[cfg(feature = "charts")]
+use crate::chart::{chart_markers, chart_options};
 use crate::error::BinaryErrorPy;
+use crate::levels::{levels_config, CandleLevels};
+#[cfg(feature = "dashboard")]
+use crate::logs::StreamLogsLayer;
+use crate::output::OutputFormat;
+use crate::patterns::{pattern_config, CandlePatterns};
+use crate::registry::{IteratorRegistry, RawIteratorSpec};
 use crate::runtime::get_runtime;
-use crate::stream::next_stream;
+use crate::ssid::PySessionProvider;
+use crate::stream::{json_path, next_stream, text_stream, TextStream};
 use crate::validator::RawValidator;
 use crate::config::PyConfig;
 use tokio::sync::Mutex;
 
+/// Default file used to persist the raw iterators registered with `register_raw_iterator`.
+const DEFAULT_REGISTRY_FILE: &str = "raw_iterators.json";
+/// Default file used to store the order templates.
+const DEFAULT_TEMPLATES_FILE: &str = "order_templates.json";
+
 #[pyclass]
 #[derive(Clone)]
 pub struct RawPocketOption {
     client: PocketOption,
+    /// Json strings or python objects, and the policy of the missing values of the results.
+    output: OutputFormat,
+}
+
+/// Parses the json of a `TradeFilter`, every trade matches if `None`.
+fn trade_filter(filter: Option<String>) -> PyResult<TradeFilter> {
+    match filter {
+        Some(filter) => Ok(serde_json::from_str(&filter).map_err(BinaryErrorPy::from)?),
+        None => Ok(TradeFilter::default()),
+    }
 }
 
+/// Number of items kept by each `StreamIterator` for `recent`.
+const RECENT_BUFFER_SIZE: usize = 256;
+
+/// Iterator over the candles of an asset, or of several assets for `subscribe_symbols`, yields
+/// them as `Candle` (or as json strings if the client was created with `json_strings`).
 #[pyclass]
 pub struct StreamIterator {
-    stream: Arc<Mutex<Fuse<BoxStream<'static, PocketResult<DataCandle>>>>>,
+    /// Asset of the candles, the assets separated by commas for `subscribe_symbols`.
+    #[pyo3(get)]
+    asset: String,
+    #[pyo3(get)]
+    assets: Vec<String>,
+    output: OutputFormat,
+    /// Adds the asset to the json strings, for the iterators of several assets.
+    tagged: bool,
+    stream: Arc<Mutex<Fuse<BoxStream<'static, PocketResult<AssetCandle>>>>>,
+    recent: Arc<std::sync::Mutex<VecDeque<AssetCandle>>>,
+    /// Client of the subscription, used to unsubscribe on `close`.
+    client: PocketOption,
+    /// Ends the stream of this iterator and of the iterators it was derived from, the first one is
+    /// the handle of this iterator.
+    closers: Vec<AbortHandle>,
+}
+
+impl StreamIterator {
+    fn new(
+        assets: Vec<String>,
+        client: PocketOption,
+        stream: impl Stream<Item = PocketResult<AssetCandle>> + Send + 'static,
+        output: OutputFormat,
+        tagged: bool,
+    ) -> Self {
+        let (closer, registration) = AbortHandle::new_pair();
+        let stream = Abortable::new(stream, registration).boxed().fuse();
+        Self {
+            asset: assets.join(","),
+            assets,
+            output,
+            tagged,
+            stream: Arc::new(Mutex::new(stream)),
+            recent: Arc::new(std::sync::Mutex::new(VecDeque::with_capacity(
+                RECENT_BUFFER_SIZE,
+            ))),
+            client,
+            closers: vec![closer],
+        }
+    }
+
+    fn from_asset(stream_asset: StreamAsset, client: PocketOption, output: OutputFormat) -> Self {
+        let asset = stream_asset.asset().to_string();
+        let stream = Self::asset_candles(stream_asset);
+        Self::new(vec![asset], client, stream, output, false)
+    }
+
+    /// Merges the candles of several assets in arrival order, the candles are tagged with their
+    /// asset.
+    fn from_assets(
+        stream_assets: Vec<StreamAsset>,
+        client: PocketOption,
+        output: OutputFormat,
+    ) -> Self {
+        let assets = stream_assets
+            .iter()
+            .map(|stream_asset| stream_asset.asset().to_string())
+            .collect();
+        let stream = select_all(stream_assets.into_iter().map(Self::asset_candles));
+        Self::new(assets, client, stream, output, true)
+    }
+
+    fn asset_candles(stream_asset: StreamAsset) -> BoxStream<'static, PocketResult<AssetCandle>> {
+        let asset = stream_asset.asset().to_string();
+        StreamAsset::to_stream_static(Arc::new(stream_asset))
+            .map(move |candle| candle.map(|candle| AssetCandle::new(&asset, candle)))
+            .boxed()
+    }
+
+    /// Ends the stream of the iterator immediately, even while it's waiting for a candle, and
+    /// returns the future unsubscribing from the asset (doing nothing if it was already closed).
+    fn close_future(&self) -> impl Future<Output = PyResult<()>> + Send + 'static {
+        let open = !self.closers[0].is_aborted();
+        self.closers.iter().for_each(AbortHandle::abort);
+        let (client, assets) = (self.client.clone(), self.assets.clone());
+        async move {
+            if open {
+                for asset in assets {
+                    client
+                        .unsubscribe_symbol(asset)
+                        .await
+                        .map_err(BinaryErrorPy::from)?;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    pub(crate) fn text_stream(&self) -> TextStream {
+        text_stream(self.stream.clone(), |item| item.candle.to_string())
+    }
+
+    /// Candles of this iterator, the iterator and the stream share the candles.
+    fn candles(&self) -> BoxStream<'static, PocketResult<AssetCandle>> {
+        unfold(self.stream.clone(), |stream| async move {
+            let item = stream.lock().await.next().await?;
+            Some((item, stream))
+        })
+        .boxed()
+    }
+
+    fn derive(
+        &self,
+        stream: impl Stream<Item = PocketResult<AssetCandle>> + Send + 'static,
+    ) -> Self {
+        let mut derived = Self::new(
+            self.assets.clone(),
+            self.client.clone(),
+            stream,
+            self.output,
+            self.tagged,
+        );
+        derived.closers.extend(self.closers.iter().cloned());
+        derived
+    }
+
+    fn record(recent: &std::sync::Mutex<VecDeque<AssetCandle>>, item: &AssetCandle) {
+        if let Ok(mut recent) = recent.lock() {
+            if recent.len() == RECENT_BUFFER_SIZE {
+                recent.pop_front();
+            }
+            recent.push_back(item.clone());
+        }
+    }
 }
 
 #[pyclass]
 pub struct RawStreamIterator {
     stream: Arc<Mutex<Fuse<BoxStream<'static, BinaryOptionsResult<RawWebsocketMessage>>>>>,
+    deadline: Arc<StreamDeadline>,
+    /// Client the iterator reads the messages of, used to send the unsubscribe message on `close`.
+    client: PocketOption,
+    closer: AbortHandle,
+    /// Stable id of the iterator if it was registered with `register_raw_iterator`.
+    #[pyo3(get)]
+    id: Option<String>,
+}
+
+impl RawStreamIterator {
+    async fn create(
+        client: &PocketOption,
+        message: String,
+        validator: RawValidator,
+        timeout: Option<Duration>,
+        id: Option<String>,
+    ) -> PocketResult<Self> {
+        let raw_stream = client
+            .create_raw_iterator(message, Box::new(validator), timeout)
+            .await?;
+        Ok(Self::from_stream(raw_stream, client.clone(), id))
+    }
+
+    fn from_stream(
+        raw_stream: FilteredRecieverStream<RawWebsocketMessage>,
+        client: PocketOption,
+        id: Option<String>,
+    ) -> Self {
+        let deadline = raw_stream.deadline();
+        let (closer, registration) = AbortHandle::new_pair();
+        // Clone the stream_asset and convert it to a BoxStream
+        let boxed_stream = Abortable::new(
+            FilteredRecieverStream::to_stream_static(Arc::new(raw_stream)),
+            registration,
+        )
+        .boxed()
+        .fuse();
+
+        // Wrap the BoxStream in an Arc and Mutex
+        let stream = Arc::new(Mutex::new(boxed_stream));
+        Self {
+            stream,
+            deadline,
+            client,
+            closer,
+            id,
+        }
+    }
+
+    /// Ends the stream of the iterator immediately and sends `unsubscribe` if the iterator was open.
+    async fn close_with(
+        client: PocketOption,
+        closer: AbortHandle,
+        unsubscribe: Option<String>,
+    ) -> PyResult<()> {
+        let open = !closer.is_aborted();
+        closer.abort();
+        if let Some(message) = unsubscribe.filter(|_| open) {
+            client
+                .send_raw_message(message)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn text_stream(&self) -> TextStream {
+        text_stream(self.stream.clone(), |raw| {
+            redact(&raw.to_string()).into_owned()
+        })
+    }
+}
+
+/// Iterator over events of the client (account events, unparsed messages...), yields them as json strings.
+#[pyclass]
+pub struct EventIterator {
+    stream: Arc<Mutex<Fuse<BoxStream<'static, BinaryOptionsResult<String>>>>>,
+    deadline: Arc<StreamDeadline>,
+}
+
+impl EventIterator {
+    fn new<T: Serialize + Send + Sync + 'static>(events: RecieverStream<T>) -> Self {
+        let deadline = events.deadline();
+        Self::from_stream(RecieverStream::to_stream_static(Arc::new(events)), deadline)
+    }
+
+    fn from_stream<T, E>(
+        events: impl Stream<Item = Result<T, E>> + Send + 'static,
+        deadline: Arc<StreamDeadline>,
+    ) -> Self
+    where
+        T: Serialize,
+        BinaryOptionsToolsError: From<E>,
+    {
+        let stream = events
+            .map(|event| -> BinaryOptionsResult<String> { Ok(serde_json::to_string(&event?)?) })
+            .boxed()
+            .fuse();
+        Self {
+            stream: Arc::new(Mutex::new(stream)),
+            deadline,
+        }
+    }
+
+    pub(crate) fn text_stream(&self) -> TextStream {
+        text_stream(self.stream.clone(), |event| event)
+    }
+}
+
+impl RawPocketOption {
+    fn with_mode(client: PocketOption, observer: bool, output: OutputFormat) -> Self {
+        let client = if observer { client.observer() } else { client };
+        Self { client, output }
+    }
+
+    fn to_py<T: Serialize + ?Sized>(&self, py: Python<'_>, value: &T) -> PyResult<Py<PyAny>> {
+        self.output.to_py(py, value)
+    }
 }
 
 #[pymethods]
 impl RawPocketOption {
     #[new]
-    #[pyo3(signature = (ssid, config = None))]
-    pub fn new(ssid: String, config: Option<PyConfig>, py: Python<'_>) -> PyResult<Self> {
+    #[pyo3(signature = (ssid, config = None, observer = false, json_strings = false))]
+    pub fn new(
+        ssid: String,
+        config: Option<PyConfig>,
+        observer: bool,
+        json_strings: bool,
+        py: Python<'_>,
+    ) -> PyResult<Self> {
         let runtime = get_runtime(py)?;
+        let output = OutputFormat::new(json_strings, PyConfig::null_policy_of(config.as_ref()));
+        let market_hours = PyConfig::market_hours_of(config.as_ref());
         runtime.block_on(async move {
             let client = if let Some(config) = config {
                 let builder = config.build()?;
@@ -55,14 +367,17 @@ impl RawPocketOption {
             } else {
                 PocketOption::new(ssid).await.map_err(BinaryErrorPy::from)?
             };
-            Ok(Self { client })
+            client.set_market_hours_check(market_hours);
+            Ok(Self::with_mode(client, observer, output))
         })
     }
 
     #[staticmethod]
-    #[pyo3(signature = (ssid, url, config = None))]
-    pub fn new_with_url(py: Python<'_>, ssid: String, url: String, config: Option<PyConfig>) -> PyResult<Self> {
+    #[pyo3(signature = (ssid, url, config = None, observer = false, json_strings = false))]
+    pub fn new_with_url(py: Python<'_>, ssid: String, url: String, config: Option<PyConfig>, observer: bool, json_strings: bool) -> PyResult<Self> {
         let runtime = get_runtime(py)?;
+        let output = OutputFormat::new(json_strings, PyConfig::null_policy_of(config.as_ref()));
+        let market_hours = PyConfig::market_hours_of(config.as_ref());
         runtime.block_on(async move {
             let parsed_url = Url::parse(&url)
                 .map_err(|e| BinaryErrorPy::from(BinaryOptionsToolsError::from(e)))?;
@@ -78,7 +393,8 @@ impl RawPocketOption {
                     .await
                     .map_err(BinaryErrorPy::from)?
             };
-            Ok(Self { client })
+            client.set_market_hours_check(market_hours);
+            Ok(Self::with_mode(client, observer, output))
         })
     }
 
@@ -88,325 +404,1738 @@ impl RawPocketOption {
         self.client.is_demo().await
     }
 
+    /// Returns true if the client is in observer mode, where sending an order raises `ObserverModeError`.
+    pub fn is_observer(&self) -> bool {
+        self.client.is_observer()
+    }
+
+    /// Trades and subscribes to the OTC version of the regular assets while their market is closed.
+    pub fn set_otc_fallback(&self, enabled: bool) {
+        self.client.set_otc_fallback(enabled)
+    }
+
+    pub fn otc_fallback(&self) -> bool {
+        self.client.otc_fallback()
+    }
+
+    /// Symbol the trades and the subscriptions on `asset` use with the OTC fallback.
+    pub async fn resolve_asset(&self, asset: String) -> String {
+        self.client.resolve_asset(asset).await
+    }
+
+    /// Returns the results as json strings instead of dicts and lists, like the previous versions.
+    #[getter]
+    pub fn json_strings(&self) -> bool {
+        self.output.json_strings
+    }
+
+    /// Policy of the missing values of the results, "null", "omit" or "default".
+    #[getter]
+    pub fn null_policy(&self) -> &'static str {
+        self.output.nulls.as_str()
+    }
+
+    #[pyo3(signature = (asset, amount, time, min_payout = None))]
     pub fn buy<'py>(
         &self,
         py: Python<'py>,
         asset: String,
         amount: f64,
         time: u32,
+        min_payout: Option<i32>,
     ) -> PyResult<Bound<'py, PyAny>> {
         let client = self.client.clone();
+        let output = self.output;
         future_into_py(py, async move {
             let res = client
-                .buy(asset, amount, time)
+                .trade_with_min_payout(asset, Action::Call, amount, time, min_payout)
                 .await
                 .map_err(BinaryErrorPy::from)?;
-            let deal = serde_json::to_string(&res.1).map_err(BinaryErrorPy::from)?;
-            let result = vec![res.0.to_string(), deal];
-            Python::with_gil(|py| result.into_py_any(py))
+            Python::with_gil(|py| {
+                let deal = output.to_py(py, &res.1)?;
+                vec![res.0.to_string().into_py_any(py)?, deal].into_py_any(py)
+            })
         })
     }
 
+    #[pyo3(signature = (asset, amount, time, min_payout = None))]
     pub fn sell<'py>(
         &self,
         py: Python<'py>,
         asset: String,
         amount: f64,
         time: u32,
+        min_payout: Option<i32>,
     ) -> PyResult<Bound<'py, PyAny>> {
         let client = self.client.clone();
+        let output = self.output;
         future_into_py(py, async move {
             let res = client
-                .sell(asset, amount, time)
+                .trade_with_min_payout(asset, Action::Put, amount, time, min_payout)
                 .await
                 .map_err(BinaryErrorPy::from)?;
-            let deal = serde_json::to_string(&res.1).map_err(BinaryErrorPy::from)?;
-            let result = vec![res.0.to_string(), deal];
-            Python::with_gil(|py| result.into_py_any(py))
+            Python::with_gil(|py| {
+                let deal = output.to_py(py, &res.1)?;
+                vec![res.0.to_string().into_py_any(py)?, deal].into_py_any(py)
+            })
         })
     }
 
-    pub fn check_win<'py>(&self, py: Python<'py>, trade_id: String) -> PyResult<Bound<'py, PyAny>> {
+    /// Places a trade in `direction` (an `OrderDirection` or "call" / "put"), returns the `Deal`.
+    pub fn trade<'py>(
+        &self,
+        py: Python<'py>,
+        asset: String,
+        direction: &Bound<'py, PyAny>,
+        amount: f64,
+        time: u32,
+    ) -> PyResult<Bound<'py, PyAny>> {
         let client = self.client.clone();
+        let nulls = self.output.nulls;
+        let direction = extract_direction(direction)?;
         future_into_py(py, async move {
-            let res = client
-                .check_results(Uuid::parse_str(&trade_id).map_err(BinaryErrorPy::from)?)
+            let (_, deal) = client
+                .trade(asset, direction, amount, time)
                 .await
                 .map_err(BinaryErrorPy::from)?;
-            Python::with_gil(|py| {
-                serde_json::to_string(&res)
-                    .map_err(BinaryErrorPy::from)?
-                    .into_py_any(py)
-            })
+            Ok(PyDeal::new(deal, nulls))
         })
     }
 
-    pub async fn get_deal_end_time(&self, trade_id: String) -> PyResult<Option<i64>> {
-        Ok(self
-            .client
-            .get_deal_end_time(Uuid::parse_str(&trade_id).map_err(BinaryErrorPy::from)?)
-            .await
-            .map(|d| d.timestamp()))
-    }
-
-    pub fn get_candles<'py>(
+    /// Places a trade with the unified options, returns the placement (`status` is
+    /// "placed" with the `id` and `deal`, or "dry_run" with the checked order).
+    #[pyo3(signature = (asset, direction, amount, expiry, tags = None, dry_run = false, min_payout = None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn place_order<'py>(
         &self,
         py: Python<'py>,
         asset: String,
-        period: i64,
-        offset: i64,
+        direction: String,
+        amount: f64,
+        expiry: u32,
+        tags: Option<Vec<String>>,
+        dry_run: bool,
+        min_payout: Option<i32>,
     ) -> PyResult<Bound<'py, PyAny>> {
         let client = self.client.clone();
+        let output = self.output;
+        let direction = direction.parse().map_err(BinaryErrorPy::from)?;
+        let options = OrderOptions {
+            tags: tags.unwrap_or_default(),
+            dry_run,
+            min_payout,
+        };
         future_into_py(py, async move {
-            let res = client
-                .get_candles(asset, period, offset)
+            let placement: OrderPlacement = client
+                .place_order(asset, direction, amount, expiry, options)
                 .await
                 .map_err(BinaryErrorPy::from)?;
-            Python::with_gil(|py| {
-                serde_json::to_string(&res)
-                    .map_err(BinaryErrorPy::from)?
-                    .into_py_any(py)
-            })
+            Python::with_gil(|py| output.to_py(py, &placement))
         })
     }
 
-    pub fn get_candles_advanced<'py>(&self, py: Python<'py>, asset: String, period: i64, offset: i64, time: i64) -> PyResult<Bound<'py, PyAny>> {
+    /// Registers a trade placed once the quote of the asset crosses `price`, returns the order.
+    /// The order expires after `valid_for` seconds if given.
+    #[pyo3(signature = (asset, direction, amount, time, price, valid_for = None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn order_at_price<'py>(
+        &self,
+        py: Python<'py>,
+        asset: String,
+        direction: String,
+        amount: f64,
+        time: u32,
+        price: f64,
+        valid_for: Option<f64>,
+    ) -> PyResult<Bound<'py, PyAny>> {
         let client = self.client.clone();
-
+        let output = self.output;
+        let direction = direction.parse().map_err(BinaryErrorPy::from)?;
+        let valid_for = valid_for
+            .map(Duration::try_from_secs_f64)
+            .transpose()
+            .map_err(|e| BinaryErrorPy::NotAllowed(e.to_string()))?;
         future_into_py(py, async move {
-            let res = client
-                .get_candles_advanced(asset, period, offset, time)
+            let order = client
+                .order_at_price(asset, direction, amount, time, price, valid_for)
                 .await
                 .map_err(BinaryErrorPy::from)?;
-            Python::with_gil(|py| {
-                serde_json::to_string(&res)
-                    .map_err(BinaryErrorPy::from)?
-                    .into_py_any(py)
-            })
-        })    
+            Python::with_gil(|py| output.to_py(py, &order))
+        })
     }
 
-    pub async fn balance(&self) -> PyResult<String> {
-        let res = self.client.get_balance().await;
-        Ok(serde_json::to_string(&res).map_err(BinaryErrorPy::from)?)
+    pub fn price_orders(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        self.to_py(py, &self.client.price_orders())
     }
 
-    pub async fn closed_deals(&self) -> PyResult<String> {
-        let res = self.client.get_closed_deals().await;
-        Ok(serde_json::to_string(&res).map_err(BinaryErrorPy::from)?)
+    pub fn cancel_price_order(&self, py: Python<'_>, order_id: String) -> PyResult<Py<PyAny>> {
+        let order = self
+            .client
+            .cancel_price_order(Uuid::parse_str(&order_id).map_err(BinaryErrorPy::from)?)
+            .map_err(BinaryErrorPy::from)?;
+        self.to_py(py, &order)
     }
 
-    pub async fn clear_closed_deals(&self) {
-        self.client.clear_closed_deals().await
+    /// Queues a trade placed at `execute_at` on the server clock, returns the order.
+    pub fn order_at_time<'py>(
+        &self,
+        py: Python<'py>,
+        asset: String,
+        direction: String,
+        amount: f64,
+        time: u32,
+        execute_at: DateTime<Utc>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let output = self.output;
+        let direction = direction.parse().map_err(BinaryErrorPy::from)?;
+        future_into_py(py, async move {
+            let order = client
+                .order_at_time(asset, direction, amount, time, execute_at)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Python::with_gil(|py| output.to_py(py, &order))
+        })
     }
 
-    pub async fn opened_deals(&self) -> PyResult<String> {
-        let res = self.client.get_opened_deals().await;
-        Ok(serde_json::to_string(&res).map_err(BinaryErrorPy::from)?)
+    pub fn scheduled_orders(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        self.to_py(py, &self.client.scheduled_orders())
     }
 
-    pub async fn payout(&self) -> PyResult<String> {
-        let res = self.client.get_payout().await;
-        Ok(serde_json::to_string(&res).map_err(BinaryErrorPy::from)?)
+    pub fn cancel_scheduled_order(&self, py: Python<'_>, order_id: String) -> PyResult<Py<PyAny>> {
+        let order = self
+            .client
+            .cancel_scheduled_order(Uuid::parse_str(&order_id).map_err(BinaryErrorPy::from)?)
+            .map_err(BinaryErrorPy::from)?;
+        self.to_py(py, &order)
     }
 
-    pub fn history<'py>(
+    /// Waits for the result of a trade, raises `TradeTimeoutError` if it isn't received within
+    /// `timeout` seconds.
+    #[pyo3(signature = (trade_id, timeout = None))]
+    pub fn check_win<'py>(
         &self,
         py: Python<'py>,
-        asset: String,
-        period: i64,
+        trade_id: String,
+        timeout: Option<f64>,
     ) -> PyResult<Bound<'py, PyAny>> {
         let client = self.client.clone();
+        let output = self.output;
+        let trade_id = Uuid::parse_str(&trade_id).map_err(BinaryErrorPy::from)?;
+        let timeout = timeout
+            .map(Duration::try_from_secs_f64)
+            .transpose()
+            .map_err(|e| BinaryErrorPy::NotAllowed(e.to_string()))?;
         future_into_py(py, async move {
-            let res = client
-                .history(asset, period)
-                .await
-                .map_err(BinaryErrorPy::from)?;
-            Python::with_gil(|py| {
-                serde_json::to_string(&res)
-                    .map_err(BinaryErrorPy::from)?
-                    .into_py_any(py)
-            })
+            let res = match timeout {
+                Some(timeout) => client.check_results_within(trade_id, timeout).await,
+                None => client.check_results(trade_id).await,
+            }
+            .map_err(BinaryErrorPy::from)?;
+            Python::with_gil(|py| output.to_py(py, &res))
         })
     }
 
-    pub fn subscribe_symbol<'py>(
+    /// Waits for the results of several trades concurrently, returns a dict mapping the ids to the
+    /// deals. The first failed check is raised once every trade was checked, or with
+    /// `return_exceptions` the exceptions are returned in the dict.
+    #[pyo3(signature = (trade_ids, timeout = None, return_exceptions = false))]
+    pub fn check_win_many<'py>(
         &self,
         py: Python<'py>,
-        symbol: String,
+        trade_ids: Vec<String>,
+        timeout: Option<f64>,
+        return_exceptions: bool,
     ) -> PyResult<Bound<'py, PyAny>> {
         let client = self.client.clone();
+        let output = self.output;
+        let trade_ids = trade_ids
+            .iter()
+            .map(|id| Uuid::parse_str(id))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(BinaryErrorPy::from)?;
+        let timeout = timeout
+            .map(Duration::try_from_secs_f64)
+            .transpose()
+            .map_err(|e| BinaryErrorPy::NotAllowed(e.to_string()))?;
         future_into_py(py, async move {
-            let stream_asset = client
-                .subscribe_symbol(symbol)
-                .await
-                .map_err(BinaryErrorPy::from)?;
-
-            // Clone the stream_asset and convert it to a BoxStream
-            let boxed_stream = StreamAsset::to_stream_static(Arc::new(stream_asset))
-                .boxed()
-                .fuse();
-
-            // Wrap the BoxStream in an Arc and Mutex
-            let stream = Arc::new(Mutex::new(boxed_stream));
-
-            Python::with_gil(|py| StreamIterator { stream }.into_py_any(py))
+            let results = client.check_results_many(&trade_ids, timeout).await;
+            Python::with_gil(|py| {
+                let dict = PyDict::new(py);
+                for (trade_id, result) in results {
+                    let value = match result {
+                        Ok(deal) => output.to_py(py, &deal)?,
+                        Err(e) if return_exceptions => PyErr::from(BinaryErrorPy::from(e))
+                            .into_value(py)
+                            .into_any(),
+                        Err(e) => return Err(BinaryErrorPy::from(e).into()),
+                    };
+                    dict.set_item(trade_id.to_string(), value)?;
+                }
+                Ok(dict.unbind())
+            })
         })
     }
 
-    pub fn subscribe_symbol_chuncked<'py>(
+    /// Returns the result of a trade without waiting, `None` while the trade is open (or unknown).
+    pub fn poll_result<'py>(
         &self,
         py: Python<'py>,
-        symbol: String,
-        chunck_size: usize,
+        trade_id: String,
     ) -> PyResult<Bound<'py, PyAny>> {
         let client = self.client.clone();
+        let output = self.output;
+        let trade_id = Uuid::parse_str(&trade_id).map_err(BinaryErrorPy::from)?;
         future_into_py(py, async move {
-            let stream_asset = client
-                .subscribe_symbol_chuncked(symbol, chunck_size)
+            let res = client.poll_result(trade_id).await;
+            Python::with_gil(|py| output.to_py(py, &res))
+        })
+    }
+
+    pub fn close_deal<'py>(&self, py: Python<'py>, trade_id: String) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let output = self.output;
+        future_into_py(py, async move {
+            let res = client
+                .close_deal(Uuid::parse_str(&trade_id).map_err(BinaryErrorPy::from)?)
                 .await
                 .map_err(BinaryErrorPy::from)?;
-
-            // Clone the stream_asset and convert it to a BoxStream
-            let boxed_stream = StreamAsset::to_stream_static(Arc::new(stream_asset))
-                .boxed()
-                .fuse();
-
-            // Wrap the BoxStream in an Arc and Mutex
-            let stream = Arc::new(Mutex::new(boxed_stream));
-
-            Python::with_gil(|py| StreamIterator { stream }.into_py_any(py))
+            Python::with_gil(|py| output.to_py(py, &res))
         })
     }
 
-    pub fn subscribe_symbol_timed<'py>(
+    /// Registers the callable returning a fresh ssid once the server rejects the current one,
+    /// coroutine functions run on `event_loop` (the running loop by default).
+    #[pyo3(signature = (provider, event_loop = None))]
+    pub fn set_session_provider(
         &self,
-        py: Python<'py>,
-        symbol: String,
-        time: Duration,
-    ) -> PyResult<Bound<'py, PyAny>> {
+        py: Python<'_>,
+        provider: Py<PyAny>,
+        event_loop: Option<Bound<'_, PyAny>>,
+    ) -> PyResult<()> {
+        let provider = PySessionProvider::new(py, provider, event_loop)?;
+        self.client.set_session_provider(provider);
+        Ok(())
+    }
+
+    pub fn remove_session_provider(&self) {
+        self.client.remove_session_provider();
+    }
+
+    pub fn refresh_session<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
         let client = self.client.clone();
+        let output = self.output;
         future_into_py(py, async move {
-            let stream_asset = client
-                .subscribe_symbol_timed(symbol, time)
+            let res = client
+                .refresh_session()
                 .await
                 .map_err(BinaryErrorPy::from)?;
+            Python::with_gil(|py| output.to_py(py, &res))
+        })
+    }
 
-            // Clone the stream_asset and convert it to a BoxStream
-            let boxed_stream = StreamAsset::to_stream_static(Arc::new(stream_asset))
-                .boxed()
-                .fuse();
+    pub fn session_status(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        self.to_py(py, &self.client.session_status())
+    }
+
+    pub fn profile<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let output = self.output;
+        future_into_py(py, async move {
+            let res = client.profile().await;
+            Python::with_gil(|py| output.to_py(py, &res))
+        })
+    }
 
-            // Wrap the BoxStream in an Arc and Mutex
-            let stream = Arc::new(Mutex::new(boxed_stream));
+    pub fn debug_stats(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        self.to_py(py, &self.client.debug_stats())
+    }
 
-            Python::with_gil(|py| StreamIterator { stream }.into_py_any(py))
+    pub fn latency_stats<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let output = self.output;
+        future_into_py(py, async move {
+            let res = client.latency_stats().await;
+            Python::with_gil(|py| output.to_py(py, &res))
         })
     }
 
-    pub fn send_raw_message<'py>(
-        &self,
-        py: Python<'py>,
-        message: String,
-    ) -> PyResult<Bound<'py, PyAny>> {
+    pub fn feed_health<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
         let client = self.client.clone();
+        let output = self.output;
         future_into_py(py, async move {
-            client
-                .send_raw_message(message)
-                .await
-                .map_err(BinaryErrorPy::from)?;
-            // Clone the stream_asset and convert it to a BoxStream
-            Ok(())
+            let res = client.feed_health().await;
+            Python::with_gil(|py| output.to_py(py, &res))
         })
     }
 
-    pub fn create_raw_order<'py>(
+    pub fn set_metadata_cache<'py>(
         &self,
         py: Python<'py>,
-        message: String,
-        validator: Bound<'py, RawValidator>,
+        path: String,
+        ttl: f64,
     ) -> PyResult<Bound<'py, PyAny>> {
         let client = self.client.clone();
-        let validator = validator.get().clone();
+        let output = self.output;
+        let ttl = Duration::try_from_secs_f64(ttl)
+            .map_err(|e| BinaryErrorPy::NotAllowed(e.to_string()))?;
         future_into_py(py, async move {
             let res = client
-                .create_raw_order(message, Box::new(validator))
+                .set_metadata_cache(path, ttl)
                 .await
                 .map_err(BinaryErrorPy::from)?;
-            Ok(res.to_string())
+            Python::with_gil(|py| output.to_py(py, &res))
         })
     }
 
-    pub fn create_raw_order_with_timeout<'py>(
-        &self,
-        py: Python<'py>,
-        message: String,
-        validator: Bound<'py, RawValidator>,
-        timeout: Duration,
-    ) -> PyResult<Bound<'py, PyAny>> {
+    pub fn metadata_status<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
         let client = self.client.clone();
-        let validator = validator.get().clone();
+        let output = self.output;
         future_into_py(py, async move {
-            let res = client
-                .create_raw_order_with_timeout(message, Box::new(validator), timeout)
-                .await
-                .map_err(BinaryErrorPy::from)?;
-            Ok(res.to_string())
+            let res = client.metadata_status().await;
+            Python::with_gil(|py| output.to_py(py, &res))
         })
     }
 
-    pub fn create_raw_order_with_timeout_and_retry<'py>(
+    pub fn set_candle_store(&self, period: i64, capacity: usize) -> PyResult<()> {
+        self.client
+            .set_candle_store(period, capacity)
+            .map_err(BinaryErrorPy::from)?;
+        Ok(())
+    }
+
+    #[pyo3(signature = (path, interval))]
+    pub fn persist_candle_store<'py>(
         &self,
         py: Python<'py>,
-        message: String,
-        validator: Bound<'py, RawValidator>,
-        timeout: Duration,
+        path: Option<String>,
+        interval: f64,
     ) -> PyResult<Bound<'py, PyAny>> {
         let client = self.client.clone();
-        let validator = validator.get().clone();
+        let interval = Duration::try_from_secs_f64(interval)
+            .map_err(|e| BinaryErrorPy::NotAllowed(e.to_string()))?;
         future_into_py(py, async move {
-            let res = client
-                .create_raw_order_with_timeout_and_retry(message, Box::new(validator), timeout)
+            let loaded = client
+                .persist_candle_store(path.map(PathBuf::from), interval)
                 .await
                 .map_err(BinaryErrorPy::from)?;
-            Ok(res.to_string())
+            Ok(loaded)
         })
     }
 
-    #[pyo3(signature = (message, validator, timeout=None))]
-    pub fn create_raw_iterator<'py>(
+    pub fn get_recent_candles(
+        &self,
+        py: Python<'_>,
+        asset: String,
+        count: usize,
+    ) -> PyResult<Py<PyAny>> {
+        let candles = self.client.get_recent_candles(&asset, count);
+        candles_to_py(py, &asset, &candles, self.output)
+    }
+
+    pub fn get_candles_between(
+        &self,
+        py: Python<'_>,
+        asset: String,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> PyResult<Py<PyAny>> {
+        let candles = self.client.get_candles_between(&asset, start, end);
+        candles_to_py(py, &asset, &candles, self.output)
+    }
+
+    pub fn server_ids<'py>(
         &self,
         py: Python<'py>,
-        message: String,
-        validator: Bound<'py, RawValidator>,
-        timeout: Option<Duration>,
+        trade_id: String,
     ) -> PyResult<Bound<'py, PyAny>> {
         let client = self.client.clone();
-        let validator = validator.get().clone();
+        let output = self.output;
         future_into_py(py, async move {
-            let raw_stream = client
-                .create_raw_iterator(message, Box::new(validator), timeout)
+            let res = client
+                .server_ids(Uuid::parse_str(&trade_id).map_err(BinaryErrorPy::from)?)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Python::with_gil(|py| output.to_py(py, &res))
+        })
+    }
+
+    pub async fn get_deal_end_time(&self, trade_id: String) -> PyResult<Option<i64>> {
+        Ok(self
+            .client
+            .get_deal_end_time(Uuid::parse_str(&trade_id).map_err(BinaryErrorPy::from)?)
+            .await
+            .map(|d| d.timestamp()))
+    }
+
+    #[pyo3(signature = (asset, period, offset, allow_partial = false))]
+    pub fn get_candles<'py>(
+        &self,
+        py: Python<'py>,
+        asset: String,
+        period: i64,
+        offset: i64,
+        allow_partial: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let output = self.output;
+        future_into_py(py, async move {
+            let candles = client
+                .get_candles(asset.clone(), period, offset, allow_partial)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Python::with_gil(|py| candles_to_py(py, &asset, &candles, output))
+        })
+    }
+
+    /// Returns the candles as columns of `float64` buffers, the numpy arrays of `get_candles_np`.
+    pub fn get_candles_columns<'py>(
+        &self,
+        py: Python<'py>,
+        asset: String,
+        period: i64,
+        offset: i64,
+        allow_partial: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let candles = client
+                .get_candles(asset, period, offset, allow_partial)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Python::with_gil(|py| candles_to_columns(py, &candles))
+        })
+    }
+
+    /// Returns the candles as an Arrow IPC stream, the record batch of `get_candles_arrow`.
+    #[cfg(feature = "arrow")]
+    pub fn get_candles_arrow<'py>(
+        &self,
+        py: Python<'py>,
+        asset: String,
+        period: i64,
+        offset: i64,
+        allow_partial: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let batch = client
+                .get_candles_arrow(asset, period, offset, allow_partial)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            let stream = to_ipc_stream(&batch).map_err(BinaryErrorPy::from)?;
+            Ok(Python::with_gil(|py| PyBytes::new(py, &stream).unbind()))
+        })
+    }
+
+    pub fn get_candles_range<'py>(
+        &self,
+        py: Python<'py>,
+        asset: String,
+        period: i64,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let output = self.output;
+        future_into_py(py, async move {
+            let candles = client
+                .get_candles_range(asset.clone(), period, start, end)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Python::with_gil(|py| candles_to_py(py, &asset, &candles, output))
+        })
+    }
+
+    /// `format` is "csv", "jsonl" or "parquet", from the extension of `path` if not given. The
+    /// `progress` callable is called with the progress of the download after every page.
+    #[pyo3(signature = (asset, period, days, path, format = None, progress = None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn download_history<'py>(
+        &self,
+        py: Python<'py>,
+        asset: String,
+        period: i64,
+        days: u32,
+        path: String,
+        format: Option<String>,
+        progress: Option<Py<PyAny>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let format = match format {
+            Some(format) => format.parse().map_err(BinaryErrorPy::from)?,
+            None => HistoryFormat::from_path(&path),
+        };
+        let client = self.client.clone();
+        let output = self.output;
+        future_into_py(py, async move {
+            let callback = move |report: &DownloadProgress| {
+                let Some(progress) = &progress else {
+                    return;
+                };
+                Python::with_gil(|py| {
+                    let result = output
+                        .to_py(py, report)
+                        .and_then(|report| progress.call1(py, (report,)));
+                    if let Err(e) = result {
+                        warn!("The progress callback of the history download failed, {e}");
+                    }
+                })
+            };
+            let summary = client
+                .download_history(asset, period, days, path, format, callback)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Python::with_gil(|py| output.to_py(py, &summary))
+        })
+    }
+
+    #[pyo3(signature = (asset, period, offset, time, allow_partial = false))]
+    pub fn get_candles_advanced<'py>(&self, py: Python<'py>, asset: String, period: i64, offset: i64, time: i64, allow_partial: bool) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let output = self.output;
+
+        future_into_py(py, async move {
+            let candles = client
+                .get_candles_advanced(asset.clone(), time, period, offset, allow_partial)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Python::with_gil(|py| candles_to_py(py, &asset, &candles, output))
+        })    
+    }
+
+    pub fn audit_candles<'py>(
+        &self,
+        py: Python<'py>,
+        asset: String,
+        period: i64,
+        offset: i64,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let output = self.output;
+        future_into_py(py, async move {
+            let res = client
+                .audit_candles(asset, period, offset)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Python::with_gil(|py| output.to_py(py, &res))
+        })
+    }
+
+    #[cfg(feature = "charts")]
+    #[pyo3(signature = (asset, period, start, end, markers = None, options = None))]
+    pub fn chart_range<'py>(
+        &self,
+        py: Python<'py>,
+        asset: String,
+        period: i64,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        markers: Option<String>,
+        options: Option<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let (markers, options) = (chart_markers(markers)?, chart_options(options)?);
+        future_into_py(py, async move {
+            let png = client
+                .chart_range(asset, period, start, end, &markers, &options)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Ok(Python::with_gil(|py| PyBytes::new(py, &png).unbind()))
+        })
+    }
+
+    #[cfg(feature = "charts")]
+    #[pyo3(signature = (trade_id, period, options = None))]
+    pub fn chart_trade<'py>(
+        &self,
+        py: Python<'py>,
+        trade_id: String,
+        period: i64,
+        options: Option<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let trade_id = Uuid::parse_str(&trade_id).map_err(BinaryErrorPy::from)?;
+        let options = chart_options(options)?;
+        future_into_py(py, async move {
+            let png = client
+                .chart_trade(trade_id, period, &options)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Ok(Python::with_gil(|py| PyBytes::new(py, &png).unbind()))
+        })
+    }
+
+    #[pyo3(signature = (asset, period, lookback, config = None))]
+    pub fn levels<'py>(
+        &self,
+        py: Python<'py>,
+        asset: String,
+        period: i64,
+        lookback: i64,
+        config: Option<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let output = self.output;
+        let config = levels_config(config)?;
+        future_into_py(py, async move {
+            let res = client
+                .levels_with(asset, period, lookback, &config)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Python::with_gil(|py| output.to_py(py, &res))
+        })
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (candles, period = None))]
+    pub fn audit_candles_data(candles: String, period: Option<i64>) -> PyResult<String> {
+        let candles: Vec<DataCandle> =
+            serde_json::from_str(&candles).map_err(BinaryErrorPy::from)?;
+        let audit = match period {
+            Some(period) => CandleAudit::new(&candles, period),
+            None => CandleAudit::infer(&candles),
+        };
+        Ok(serde_json::to_string(&audit).map_err(BinaryErrorPy::from)?)
+    }
+
+    pub async fn balance(&self) -> PyResult<Py<PyAny>> {
+        let res = self.client.get_balance().await;
+        Python::with_gil(|py| self.to_py(py, &res))
+    }
+
+    pub async fn closed_deals(&self) -> PyResult<Py<PyAny>> {
+        let res = self.client.get_closed_deals().await;
+        Python::with_gil(|py| self.to_py(py, &res))
+    }
+
+    #[cfg(feature = "arrow")]
+    pub fn closed_deals_arrow<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let batch = client
+                .closed_deals_arrow()
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            let stream = to_ipc_stream(&batch).map_err(BinaryErrorPy::from)?;
+            Ok(Python::with_gil(|py| PyBytes::new(py, &stream).unbind()))
+        })
+    }
+
+    pub async fn clear_closed_deals(&self) {
+        self.client.clear_closed_deals().await
+    }
+
+    pub async fn opened_deals(&self) -> PyResult<Py<PyAny>> {
+        let res = self.client.get_opened_deals().await;
+        Python::with_gil(|py| self.to_py(py, &res))
+    }
+
+    pub async fn payout(&self) -> PyResult<Py<PyAny>> {
+        let res = self.client.get_payout().await;
+        Python::with_gil(|py| self.to_py(py, &res))
+    }
+
+    /// Payout of an asset in percent, raises if the server didn't send it.
+    pub async fn get_payout_for(&self, asset: String) -> PyResult<i32> {
+        match self.client.get_payout_for(&asset).await {
+            Some(payout) => Ok(payout),
+            None => Err(BinaryErrorPy::from(PocketOptionError::AssetNotFound(asset)).into()),
+        }
+    }
+
+    /// Whether the market of an asset is currently open, raises if the server didn't send it.
+    pub async fn is_open(&self, asset: String) -> PyResult<bool> {
+        match self.client.is_open(&asset).await {
+            Some(open) => Ok(open),
+            None => Err(BinaryErrorPy::from(PocketOptionError::AssetNotFound(asset)).into()),
+        }
+    }
+
+    /// Catalog of the assets sent by the server, sorted by symbol.
+    pub async fn get_assets(&self) -> Vec<PyAsset> {
+        let assets = self.client.get_assets().await;
+        assets.into_iter().map(PyAsset::from).collect()
+    }
+
+    pub fn history<'py>(
+        &self,
+        py: Python<'py>,
+        asset: String,
+        period: i64,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let output = self.output;
+        future_into_py(py, async move {
+            let candles = client
+                .history(asset.clone(), period)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Python::with_gil(|py| candles_to_py(py, &asset, &candles, output))
+        })
+    }
+
+    #[cfg(feature = "arrow")]
+    pub fn history_arrow<'py>(
+        &self,
+        py: Python<'py>,
+        asset: String,
+        period: i64,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let batch = client
+                .history_arrow(asset, period)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            let stream = to_ipc_stream(&batch).map_err(BinaryErrorPy::from)?;
+            Ok(Python::with_gil(|py| PyBytes::new(py, &stream).unbind()))
+        })
+    }
+
+    pub fn subscribe_symbol<'py>(
+        &self,
+        py: Python<'py>,
+        symbol: String,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let output = self.output;
+        future_into_py(py, async move {
+            let stream_asset = client
+                .subscribe_symbol(symbol)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Python::with_gil(|py| {
+                StreamIterator::from_asset(stream_asset, client.clone(), output).into_py_any(py)
+            })
+        })
+    }
+
+    pub fn subscribe_symbol_batch<'py>(
+        &self,
+        py: Python<'py>,
+        symbols: Vec<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let output = self.output;
+        future_into_py(py, async move {
+            let streams = client
+                .subscribe_symbol_batch(symbols)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Python::with_gil(|py| {
+                streams
+                    .into_iter()
+                    .map(|stream_asset| {
+                        StreamIterator::from_asset(stream_asset, client.clone(), output)
+                    })
+                    .collect::<Vec<_>>()
+                    .into_py_any(py)
+            })
+        })
+    }
+
+    /// Subscribes to several assets at once and merges their candles in a single iterator, the
+    /// candles are tagged with their asset.
+    pub fn subscribe_symbols<'py>(
+        &self,
+        py: Python<'py>,
+        symbols: Vec<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        if symbols.is_empty() {
+            return Err(BinaryErrorPy::NotAllowed("no symbols to subscribe to".into()).into());
+        }
+        let client = self.client.clone();
+        let output = self.output;
+        future_into_py(py, async move {
+            let streams = client
+                .subscribe_symbol_batch(symbols)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Python::with_gil(|py| {
+                StreamIterator::from_assets(streams, client.clone(), output).into_py_any(py)
+            })
+        })
+    }
+
+    pub fn subscribe_symbol_chuncked<'py>(
+        &self,
+        py: Python<'py>,
+        symbol: String,
+        chunck_size: usize,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let output = self.output;
+        future_into_py(py, async move {
+            let stream_asset = client
+                .subscribe_symbol_chuncked(symbol, chunck_size)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Python::with_gil(|py| {
+                StreamIterator::from_asset(stream_asset, client.clone(), output).into_py_any(py)
+            })
+        })
+    }
+
+    pub fn subscribe_symbol_timed<'py>(
+        &self,
+        py: Python<'py>,
+        symbol: String,
+        time: Duration,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let output = self.output;
+        future_into_py(py, async move {
+            let stream_asset = client
+                .subscribe_symbol_timed(symbol, time)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Python::with_gil(|py| {
+                StreamIterator::from_asset(stream_asset, client.clone(), output).into_py_any(py)
+            })
+        })
+    }
+
+    #[pyo3(signature = (symbol, period, timeout = None))]
+    pub fn subscribe_candle_close<'py>(
+        &self,
+        py: Python<'py>,
+        symbol: String,
+        period: Duration,
+        timeout: Option<Duration>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let closes = client
+                .subscribe_candle_close(symbol, period, timeout)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            let deadline = closes.deadline();
+            let stream = CandleCloseStream::to_stream_static(Arc::new(closes));
+            Python::with_gil(|py| EventIterator::from_stream(stream, deadline).into_py_any(py))
+        })
+    }
+
+    #[pyo3(signature = (timeout = None))]
+    pub fn subscribe_account_events<'py>(
+        &self,
+        py: Python<'py>,
+        timeout: Option<Duration>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let events = client.subscribe_account_events(timeout).await;
+            Python::with_gil(|py| EventIterator::new(events).into_py_any(py))
+        })
+    }
+
+    #[pyo3(signature = (timeout = None))]
+    pub fn subscribe_trade_results<'py>(
+        &self,
+        py: Python<'py>,
+        timeout: Option<Duration>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let results = client.subscribe_trade_results(timeout).await;
+            Python::with_gil(|py| EventIterator::new(results).into_py_any(py))
+        })
+    }
+
+    #[pyo3(signature = (timeout = None))]
+    pub fn subscribe_unparsed(&self, timeout: Option<Duration>) -> EventIterator {
+        EventIterator::new(self.client.subscribe_unparsed(timeout))
+    }
+
+    pub fn unsubscribe_symbol<'py>(
+        &self,
+        py: Python<'py>,
+        symbol: String,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            client
+                .unsubscribe_symbol(symbol)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Ok(())
+        })
+    }
+
+    #[pyo3(signature = (max_subscriptions = None, rotate = true, detection_delay = None))]
+    pub fn set_subscription_policy<'py>(
+        &self,
+        py: Python<'py>,
+        max_subscriptions: Option<usize>,
+        rotate: bool,
+        detection_delay: Option<Duration>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let default = SubscriptionPolicy::default();
+        let policy = SubscriptionPolicy {
+            max_subscriptions,
+            rotate,
+            detection_delay: detection_delay.unwrap_or(default.detection_delay),
+        };
+        future_into_py(py, async move {
+            client
+                .set_subscription_policy(policy)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Ok(())
+        })
+    }
+
+    pub fn subscription_status(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        self.to_py(py, &self.client.subscription_status())
+    }
+
+    /// Sets the lane of the ticks of `asset`, "critical" (never shed) or "best_effort".
+    pub fn set_stream_priority(&self, asset: String, priority: String) -> PyResult<()> {
+        let priority = priority.parse().map_err(BinaryErrorPy::from)?;
+        self.client.set_stream_priority(asset, priority);
+        Ok(())
+    }
+
+    pub fn stream_lane_stats(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        self.to_py(py, &self.client.stream_lane_stats())
+    }
+
+    /// Unsubscribes from every asset once no stream read the ticks for `idle` seconds, `None`
+    /// disables the power saving mode.
+    #[pyo3(signature = (idle = None))]
+    pub fn set_power_saving<'py>(
+        &self,
+        py: Python<'py>,
+        idle: Option<f64>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let idle = idle
+            .map(Duration::try_from_secs_f64)
+            .transpose()
+            .map_err(|e| BinaryErrorPy::NotAllowed(e.to_string()))?;
+        future_into_py(py, async move {
+            client.set_power_saving(idle).map_err(BinaryErrorPy::from)?;
+            Ok(())
+        })
+    }
+
+    #[pyo3(signature = (timeout = None))]
+    pub fn subscribe_subscription_events(&self, timeout: Option<Duration>) -> EventIterator {
+        EventIterator::new(self.client.subscribe_subscription_events(timeout))
+    }
+
+    pub fn send_raw_message<'py>(
+        &self,
+        py: Python<'py>,
+        message: String,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            client
+                .send_raw_message(message)
                 .await
                 .map_err(BinaryErrorPy::from)?;
-
             // Clone the stream_asset and convert it to a BoxStream
-            let boxed_stream = FilteredRecieverStream::to_stream_static(Arc::new(raw_stream))
-                .boxed()
-                .fuse();
+            Ok(())
+        })
+    }
+
+    pub fn create_raw_order<'py>(
+        &self,
+        py: Python<'py>,
+        message: String,
+        validator: Bound<'py, RawValidator>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let validator = validator.get().clone();
+        future_into_py(py, async move {
+            let res = client
+                .create_raw_order(message, Box::new(validator))
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Ok(redact(&res.to_string()).into_owned())
+        })
+    }
+
+    pub fn create_raw_order_with_timeout<'py>(
+        &self,
+        py: Python<'py>,
+        message: String,
+        validator: Bound<'py, RawValidator>,
+        timeout: Duration,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let validator = validator.get().clone();
+        future_into_py(py, async move {
+            let res = client
+                .create_raw_order_with_timeout(message, Box::new(validator), timeout)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Ok(redact(&res.to_string()).into_owned())
+        })
+    }
+
+    pub fn create_raw_order_with_timeout_and_retry<'py>(
+        &self,
+        py: Python<'py>,
+        message: String,
+        validator: Bound<'py, RawValidator>,
+        timeout: Duration,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let validator = validator.get().clone();
+        future_into_py(py, async move {
+            let res = client
+                .create_raw_order_with_timeout_and_retry(message, Box::new(validator), timeout)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Ok(redact(&res.to_string()).into_owned())
+        })
+    }
+
+    #[pyo3(signature = (message, validator, timeout=None))]
+    pub fn create_raw_iterator<'py>(
+        &self,
+        py: Python<'py>,
+        message: String,
+        validator: Bound<'py, RawValidator>,
+        timeout: Option<Duration>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let validator = validator.get().clone();
+        future_into_py(py, async move {
+            let iterator = RawStreamIterator::create(&client, message, validator, timeout, None)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Python::with_gil(|py| iterator.into_py_any(py))
+        })
+    }
+
+    /// Iterator over the raw messages of the server (events the models don't know), nothing is sent.
+    #[pyo3(signature = (validator, timeout=None))]
+    pub fn tap_raw(
+        &self,
+        validator: Bound<'_, RawValidator>,
+        timeout: Option<Duration>,
+    ) -> RawStreamIterator {
+        let validator = validator.get().clone();
+        RawStreamIterator::from_stream(
+            self.client.tap_raw(Box::new(validator), timeout),
+            self.client.clone(),
+            None,
+        )
+    }
+
+    /// Creates a raw iterator like `create_raw_iterator` and persists its definition under
+    /// `iterator_id` in the registry file so it can be re-created with `restore_raw_iterators`
+    /// after the process restarts. Custom validators can't be persisted.
+    #[pyo3(signature = (iterator_id, message, validator, timeout=None, path=DEFAULT_REGISTRY_FILE.to_string()))]
+    pub fn register_raw_iterator<'py>(
+        &self,
+        py: Python<'py>,
+        iterator_id: String,
+        message: String,
+        validator: Bound<'py, RawValidator>,
+        timeout: Option<Duration>,
+        path: String,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let validator = validator.get().clone();
+        let spec = RawIteratorSpec::new(message.clone(), &validator, timeout)?;
+        future_into_py(py, async move {
+            IteratorRegistry::register(&path, iterator_id.clone(), spec)?;
+            let iterator =
+                RawStreamIterator::create(&client, message, validator, timeout, Some(iterator_id))
+                    .await
+                    .map_err(BinaryErrorPy::from)?;
+            Python::with_gil(|py| iterator.into_py_any(py))
+        })
+    }
+
+    /// Re-creates every raw iterator persisted in the registry file, returning a dict mapping
+    /// each iterator id to its new iterator.
+    #[pyo3(signature = (path=DEFAULT_REGISTRY_FILE.to_string()))]
+    pub fn restore_raw_iterators<'py>(
+        &self,
+        py: Python<'py>,
+        path: String,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let registry = IteratorRegistry::load(&path)?;
+            let mut iterators = HashMap::new();
+            for (id, spec) in registry.iterators() {
+                let iterator = RawStreamIterator::create(
+                    &client,
+                    spec.message.clone(),
+                    spec.validator()?,
+                    spec.timeout(),
+                    Some(id.clone()),
+                )
+                .await
+                .map_err(BinaryErrorPy::from)?;
+                iterators.insert(id.clone(), iterator);
+            }
+            Python::with_gil(|py| iterators.into_py_any(py))
+        })
+    }
+
+    /// Removes a raw iterator from the registry file so it's not restored anymore.
+    #[pyo3(signature = (iterator_id, path=DEFAULT_REGISTRY_FILE.to_string()))]
+    pub fn unregister_raw_iterator(&self, iterator_id: String, path: String) -> PyResult<bool> {
+        Ok(IteratorRegistry::remove(&path, &iterator_id)?.is_some())
+    }
+
+    pub fn get_server_time<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(
+            py,
+            async move { Ok(client.get_server_time().await.timestamp()) },
+        )
+    }
+
+    #[cfg(feature = "metrics")]
+    pub fn health<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let output = self.output;
+        future_into_py(py, async move {
+            let res = client.health().await;
+            Python::with_gil(|py| output.to_py(py, &res))
+        })
+    }
+
+    #[pyo3(signature = (trade_id, hedge_asset, ratio = 1.0, negative_correlation = true))]
+    pub fn hedge<'py>(
+        &self,
+        py: Python<'py>,
+        trade_id: String,
+        hedge_asset: String,
+        ratio: f64,
+        negative_correlation: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let output = self.output;
+        future_into_py(py, async move {
+            let trade_id = Uuid::parse_str(&trade_id).map_err(BinaryErrorPy::from)?;
+            let (group, hedge_id, hedge) = client
+                .hedge(trade_id, hedge_asset, ratio, negative_correlation)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Python::with_gil(|py| {
+                let deal = output.to_py(py, &hedge)?;
+                vec![
+                    group.to_string().into_py_any(py)?,
+                    hedge_id.to_string().into_py_any(py)?,
+                    deal,
+                ]
+                .into_py_any(py)
+            })
+        })
+    }
+
+    pub fn straddle<'py>(
+        &self,
+        py: Python<'py>,
+        asset: String,
+        amount: f64,
+        expiry: u32,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let output = self.output;
+        future_into_py(py, async move {
+            let straddle = client
+                .straddle(asset, amount, expiry)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Python::with_gil(|py| output.to_py(py, &straddle))
+        })
+    }
+
+    #[pyo3(signature = (timeout = None))]
+    pub fn subscribe_straddle_results<'py>(
+        &self,
+        py: Python<'py>,
+        timeout: Option<Duration>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let results = client.subscribe_straddle_results(timeout).await;
+            Python::with_gil(|py| EventIterator::new(results).into_py_any(py))
+        })
+    }
+
+    pub fn journal(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        self.to_py(py, &self.client.journal().entries())
+    }
+
+    pub fn hedge_group(&self, py: Python<'_>, group: String) -> PyResult<Py<PyAny>> {
+        let group = Uuid::parse_str(&group).map_err(BinaryErrorPy::from)?;
+        self.to_py(py, &self.client.journal().group_entries(group))
+    }
+
+    /// Stores the `before` candles before the opening and `after` candles after the close of the
+    /// trades in their journal entry once they close, `period = None` stops it.
+    #[pyo3(signature = (period = None, before = 20, after = 10))]
+    pub fn capture_journal_context(
+        &self,
+        period: Option<u32>,
+        before: u32,
+        after: u32,
+    ) -> PyResult<()> {
+        let capture = period.map(|period| ContextCapture {
+            period,
+            before,
+            after,
+        });
+        Ok(self
+            .client
+            .journal()
+            .capture_context(capture)
+            .map_err(BinaryErrorPy::from)?)
+    }
+
+    pub fn persist_journal(&self, path: String) -> PyResult<()> {
+        Ok(self
+            .client
+            .journal()
+            .persist_to(path)
+            .map_err(BinaryErrorPy::from)?)
+    }
+
+    /// Loads the trades of a history exported from the broker (CSV or XLSX) in the journal,
+    /// returns the number of trades added.
+    pub fn import_trades(&self, path: String) -> PyResult<usize> {
+        Ok(self
+            .client
+            .import_trades(path)
+            .map_err(BinaryErrorPy::from)?)
+    }
+
+    #[pyo3(signature = (tag = None))]
+    pub fn journal_stats(&self, py: Python<'_>, tag: Option<String>) -> PyResult<Py<PyAny>> {
+        let stats = self.client.journal().stats(tag.as_deref());
+        self.to_py(py, &stats)
+    }
+
+    /// Returns the expectancy of the closed trades of the journal matching `filter` (json of a
+    /// `TradeFilter`), every closed trade if `None`.
+    #[pyo3(signature = (filter = None, confidence = DEFAULT_CONFIDENCE))]
+    pub fn expectancy(
+        &self,
+        py: Python<'_>,
+        filter: Option<String>,
+        confidence: f64,
+    ) -> PyResult<Py<PyAny>> {
+        let expectancy = self
+            .client
+            .journal()
+            .expectancy(&trade_filter(filter)?, confidence)
+            .map_err(BinaryErrorPy::from)?;
+        self.to_py(py, &expectancy)
+    }
+
+    /// Returns the performance of the closed trades of the journal matching `filter` (json of a
+    /// `TradeFilter`) by hour of the day, with one row per asset if `by_asset`.
+    #[pyo3(signature = (filter = None, by_asset = false))]
+    pub fn performance_by_hour(
+        &self,
+        py: Python<'_>,
+        filter: Option<String>,
+        by_asset: bool,
+    ) -> PyResult<Py<PyAny>> {
+        let performance = self
+            .client
+            .journal()
+            .performance_by_hour(&trade_filter(filter)?, by_asset)
+            .map_err(BinaryErrorPy::from)?;
+        self.to_py(py, &performance)
+    }
+
+    /// Returns the performance of the closed trades of the journal matching `filter` (json of a
+    /// `TradeFilter`) by day of the week, with one row per asset if `by_asset`.
+    #[pyo3(signature = (filter = None, by_asset = false))]
+    pub fn performance_by_weekday(
+        &self,
+        py: Python<'_>,
+        filter: Option<String>,
+        by_asset: bool,
+    ) -> PyResult<Py<PyAny>> {
+        let performance = self
+            .client
+            .journal()
+            .performance_by_weekday(&trade_filter(filter)?, by_asset)
+            .map_err(BinaryErrorPy::from)?;
+        self.to_py(py, &performance)
+    }
+
+    /// Saves an order template (as json) in the templates file, replacing the one with the same name.
+    #[pyo3(signature = (template, path=DEFAULT_TEMPLATES_FILE.to_string()))]
+    pub fn save_template(&self, template: String, path: String) -> PyResult<()> {
+        let template: OrderTemplate =
+            serde_json::from_str(&template).map_err(BinaryErrorPy::from)?;
+        Ok(TemplateStore::load(&path)
+            .and_then(|mut store| store.insert(template))
+            .map_err(BinaryErrorPy::from)?)
+    }
+
+    #[pyo3(signature = (path=DEFAULT_TEMPLATES_FILE.to_string()))]
+    pub fn list_templates(&self, py: Python<'_>, path: String) -> PyResult<Py<PyAny>> {
+        let store = TemplateStore::load(&path).map_err(BinaryErrorPy::from)?;
+        self.to_py(py, store.templates())
+    }
+
+    #[pyo3(signature = (name, path=DEFAULT_TEMPLATES_FILE.to_string()))]
+    pub fn remove_template(&self, name: String, path: String) -> PyResult<bool> {
+        Ok(TemplateStore::load(&path)
+            .and_then(|mut store| store.remove(&name))
+            .map_err(BinaryErrorPy::from)?
+            .is_some())
+    }
+
+    /// Executes the order template saved under `name` in the templates file.
+    #[pyo3(signature = (name, path=DEFAULT_TEMPLATES_FILE.to_string()))]
+    pub fn execute_template<'py>(
+        &self,
+        py: Python<'py>,
+        name: String,
+        path: String,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let output = self.output;
+        future_into_py(py, async move {
+            let template = TemplateStore::load(&path)
+                .and_then(|store| store.get(&name).cloned())
+                .map_err(BinaryErrorPy::from)?;
+            let execution = client
+                .execute_template(&template)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Python::with_gil(|py| output.to_py(py, &execution))
+        })
+    }
+
+    #[pyo3(signature = (max_trades, window, aligned = true))]
+    pub fn add_cluster_guard(&self, max_trades: usize, window: f64, aligned: bool) {
+        self.client.add_cluster_guard(max_trades, Duration::from_secs_f64(window), aligned);
+    }
+
+    #[pyo3(signature = (max_drawdown, demo_ssid = None))]
+    pub fn set_drawdown_breaker<'py>(
+        &self,
+        py: Python<'py>,
+        max_drawdown: f64,
+        demo_ssid: Option<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            Ok(client
+                .set_drawdown_breaker(max_drawdown, demo_ssid)
+                .await
+                .map_err(BinaryErrorPy::from)?)
+        })
+    }
+
+    pub fn drawdown_state(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        self.to_py(py, &self.client.drawdown_state())
+    }
+
+    pub fn reset_drawdown_breaker(&self) {
+        self.client.reset_drawdown_breaker();
+    }
+
+    #[pyo3(signature = (hours = None, weekdays = None))]
+    pub fn set_trading_hours(
+        &self,
+        hours: Option<Vec<u32>>,
+        weekdays: Option<Vec<u32>>,
+    ) -> PyResult<()> {
+        let mut trading_hours = TradingHours::new();
+        if let Some(hours) = hours {
+            trading_hours = trading_hours.hours(hours).map_err(BinaryErrorPy::from)?;
+        }
+        if let Some(weekdays) = weekdays {
+            trading_hours = trading_hours
+                .weekdays(weekdays)
+                .map_err(BinaryErrorPy::from)?;
+        }
+        self.client.set_trading_hours(trading_hours);
+        Ok(())
+    }
+
+    #[pyo3(signature = (filter = None, min_trades = 10))]
+    pub fn trade_profitable_hours(
+        &self,
+        filter: Option<String>,
+        min_trades: usize,
+    ) -> PyResult<Vec<u32>> {
+        Ok(self
+            .client
+            .trade_profitable_hours(&trade_filter(filter)?, min_trades)
+            .map_err(BinaryErrorPy::from)?)
+    }
+
+    /// Sets the market guard with `config` (json of a `MarketGuardConfig`).
+    pub fn set_market_guard(&self, config: String) -> PyResult<()> {
+        let config: MarketGuardConfig =
+            serde_json::from_str(&config).map_err(BinaryErrorPy::from)?;
+        Ok(self
+            .client
+            .set_market_guard(config)
+            .map_err(BinaryErrorPy::from)?)
+    }
+
+    pub fn remove_market_guard(&self) {
+        self.client.remove_market_guard();
+    }
+
+    pub fn market_guard_status(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        self.to_py(py, &self.client.market_guard_status())
+    }
+
+    pub fn risk_rules(&self) -> Vec<String> {
+        self.client.risk().rules()
+    }
+
+    pub fn clear_risk_rules(&self) {
+        self.client.risk().clear_rules();
+    }
+
+    /// Sets the stake rules (as json) rounding the amount of every order to the rule of the
+    /// account currency.
+    pub fn set_stake_rules(&self, rules: String) -> PyResult<()> {
+        let rules: StakeRules = serde_json::from_str(&rules).map_err(BinaryErrorPy::from)?;
+        Ok(self
+            .client
+            .set_stake_rules(rules)
+            .map_err(BinaryErrorPy::from)?)
+    }
+
+    pub fn stake_rules(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        self.to_py(py, &self.client.stake_rules())
+    }
 
-            // Wrap the BoxStream in an Arc and Mutex
-            let stream = Arc::new(Mutex::new(boxed_stream));
+    pub async fn account_currency(&self) -> Option<String> {
+        self.client.account_currency().await
+    }
 
-            Python::with_gil(|py| RawStreamIterator { stream }.into_py_any(py))
+    pub fn round_stake<'py>(&self, py: Python<'py>, amount: f64) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            Ok(client
+                .round_stake(amount)
+                .await
+                .map_err(BinaryErrorPy::from)?)
         })
     }
 
-    pub fn get_server_time<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+    pub fn valid_expiries<'py>(&self, py: Python<'py>, asset: String) -> PyResult<Bound<'py, PyAny>> {
         let client = self.client.clone();
-        future_into_py(
-            py,
-            async move { Ok(client.get_server_time().await.timestamp()) },
-        )
+        future_into_py(py, async move {
+            Ok(client
+                .valid_expiries(asset)
+                .await
+                .map_err(BinaryErrorPy::from)?)
+        })
+    }
+
+    pub fn nearest_expiry<'py>(
+        &self,
+        py: Python<'py>,
+        asset: String,
+        desired_secs: u32,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            Ok(client
+                .nearest_expiry(asset, desired_secs)
+                .await
+                .map_err(BinaryErrorPy::from)?)
+        })
+    }
+
+    pub fn suggest_expiry<'py>(
+        &self,
+        py: Python<'py>,
+        asset: String,
+        target_move_pips: f64,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let output = self.output;
+        future_into_py(py, async move {
+            let res = client
+                .suggest_expiry(asset, target_move_pips)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Python::with_gil(|py| output.to_py(py, &res))
+        })
+    }
+
+    pub fn precision<'py>(&self, py: Python<'py>, asset: String) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let output = self.output;
+        future_into_py(py, async move {
+            let res = client.precision(asset).await.map_err(BinaryErrorPy::from)?;
+            Python::with_gil(|py| output.to_py(py, &res))
+        })
+    }
+
+    pub fn round_price<'py>(
+        &self,
+        py: Python<'py>,
+        asset: String,
+        price: f64,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            Ok(client
+                .round_price(asset, price)
+                .await
+                .map_err(BinaryErrorPy::from)?)
+        })
+    }
+
+    pub fn pips_between<'py>(
+        &self,
+        py: Python<'py>,
+        asset: String,
+        a: f64,
+        b: f64,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            Ok(client
+                .pips_between(asset, a, b)
+                .await
+                .map_err(BinaryErrorPy::from)?)
+        })
+    }
+
+    /// Closes the connection, stops the background tasks of the client and ends every open
+    /// `StreamIterator` with `StopAsyncIteration`.
+    pub fn shutdown<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            client.shutdown().await;
+            Ok(())
+        })
+    }
+
+    pub fn is_shut_down(&self) -> bool {
+        self.client.is_shut_down()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.client.is_connected()
+    }
+
+    #[pyo3(signature = (timeout = None))]
+    pub fn connection_events(&self, timeout: Option<Duration>) -> EventIterator {
+        EventIterator::new(self.client.connection_events(timeout))
+    }
+
+    /// Number of orders sent whose confirmation wasn't recieved yet.
+    pub fn pending_orders(&self) -> usize {
+        self.client.pending_orders()
+    }
+
+    /// Waits for the pending orders to be confirmed, up to `timeout` seconds (the timeout of the
+    /// orders by default).
+    #[pyo3(signature = (timeout = None))]
+    pub fn flush_orders<'py>(
+        &self,
+        py: Python<'py>,
+        timeout: Option<f64>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let timeout = match timeout {
+            Some(secs) => Duration::try_from_secs_f64(secs)
+                .map_err(|e| BinaryErrorPy::NotAllowed(e.to_string()))?,
+            None => client.get_timeout().map_err(BinaryErrorPy::from)?,
+        };
+        future_into_py(py, async move {
+            client
+                .flush_orders(timeout)
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Ok(())
+        })
+    }
+
+    fn __aenter__<'py>(slf: Bound<'py, Self>, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let slf = slf.unbind();
+        future_into_py(py, async move { Ok(slf) })
+    }
+
+    /// Flushes the pending orders and shuts the client down, the exceptions raised in the block
+    /// are propagated.
+    #[pyo3(signature = (_exc_type = None, _exc_value = None, _traceback = None))]
+    fn __aexit__<'py>(
+        &self,
+        py: Python<'py>,
+        _exc_type: Option<Bound<'py, PyAny>>,
+        _exc_value: Option<Bound<'py, PyAny>>,
+        _traceback: Option<Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let timeout = client.get_timeout().map_err(BinaryErrorPy::from)?;
+        future_into_py(py, async move {
+            if let Err(e) = client.flush_orders(timeout).await {
+                warn!("Shutting down with pending orders, {e}");
+            }
+            client.shutdown().await;
+            Ok(false)
+        })
+    }
+
+    #[cfg(feature = "http-gateway")]
+    #[pyo3(signature = (port, host = "0.0.0.0".to_string(), access = None))]
+    pub fn start_healthcheck<'py>(
+        &self,
+        py: Python<'py>,
+        port: u16,
+        host: String,
+        access: Option<PyAccessControl>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let addr: SocketAddr = format!("{host}:{port}")
+                .parse()
+                .map_err(BinaryErrorPy::from)?;
+            match access {
+                Some(access) => {
+                    client
+                        .start_healthcheck_with_access(addr, access.access)
+                        .await
+                }
+                None => client.start_healthcheck(addr).await,
+            }
+            .map_err(BinaryErrorPy::from)?;
+            Ok(())
+        })
+    }
+
+    #[cfg(feature = "dashboard")]
+    #[pyo3(signature = (port, host = "127.0.0.1".to_string(), logs = None, access = None))]
+    pub fn start_dashboard<'py>(
+        &self,
+        py: Python<'py>,
+        port: u16,
+        host: String,
+        logs: Option<StreamLogsLayer>,
+        access: Option<PyAccessControl>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        future_into_py(py, async move {
+            let addr: SocketAddr = format!("{host}:{port}")
+                .parse()
+                .map_err(BinaryErrorPy::from)?;
+            let logs = logs.as_ref().map(StreamLogsLayer::broadcast);
+            client
+                .start_dashboard(addr, logs, access.map(|access| access.access))
+                .await
+                .map_err(BinaryErrorPy::from)?;
+            Ok(())
+        })
     }
 }
 
@@ -420,22 +2149,238 @@ impl StreamIterator {
         slf
     }
 
-    fn __anext__<'py>(&'py mut self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+    fn __anext__<'py>(&'py self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
         let stream = self.stream.clone();
+        let recent = self.recent.clone();
+        let (output, tagged) = (self.output, self.tagged);
         future_into_py(py, async move {
-            let res = next_stream(stream, false).await;
-            res.map(|res| res.to_string())
+            let item = next_stream(stream, false).await?;
+            Self::record(&recent, &item);
+            Python::with_gil(|py| item.to_py(py, output, tagged))
         })
     }
 
-    fn __next__<'py>(&'py self, py: Python<'py>) -> PyResult<String> {
+    fn __next__<'py>(&'py self, py: Python<'py>) -> PyResult<Py<PyAny>> {
         let runtime = get_runtime(py)?;
         let stream = self.stream.clone();
-        runtime.block_on(async move {
-            let res = next_stream(stream, true).await;
-            res.map(|res| res.to_string())
+        let item = runtime.block_on(async move { next_stream(stream, true).await })?;
+        Self::record(&self.recent, &item);
+        item.to_py(py, self.output, self.tagged)
+    }
+
+    /// Unsubscribes from the asset and ends the iterator immediately, even while it's waiting for a
+    /// candle. The other iterators of the asset stop receiving candles too, closing a closed
+    /// iterator does nothing.
+    fn close(&self, py: Python<'_>) -> PyResult<()> {
+        let runtime = get_runtime(py)?;
+        runtime.block_on(self.close_future())
+    }
+
+    /// Same as `close`, without blocking the event loop.
+    fn aclose<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        future_into_py(py, self.close_future())
+    }
+
+    #[getter]
+    fn closed(&self) -> bool {
+        self.closers[0].is_aborted()
+    }
+
+    fn __enter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    #[pyo3(signature = (_exc_type = None, _exc_value = None, _traceback = None))]
+    fn __exit__(
+        &self,
+        py: Python<'_>,
+        _exc_type: Option<Bound<'_, PyAny>>,
+        _exc_value: Option<Bound<'_, PyAny>>,
+        _traceback: Option<Bound<'_, PyAny>>,
+    ) -> PyResult<bool> {
+        self.close(py)?;
+        Ok(false)
+    }
+
+    fn __aenter__<'py>(slf: Bound<'py, Self>, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let slf = slf.unbind();
+        future_into_py(py, async move { Ok(slf) })
+    }
+
+    /// Closes the iterator, the exceptions raised in the block are propagated.
+    #[pyo3(signature = (_exc_type = None, _exc_value = None, _traceback = None))]
+    fn __aexit__<'py>(
+        &self,
+        py: Python<'py>,
+        _exc_type: Option<Bound<'py, PyAny>>,
+        _exc_value: Option<Bound<'py, PyAny>>,
+        _traceback: Option<Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let close = self.close_future();
+        future_into_py(py, async move {
+            close.await?;
+            Ok(false)
         })
     }
+
+    /// Returns the last `n` items delivered by the iterator, oldest first.
+    /// Only the last 256 items are kept.
+    #[pyo3(signature = (n = 10))]
+    fn recent(&self, py: Python<'_>, n: usize) -> PyResult<Vec<Py<PyAny>>> {
+        let items: Vec<AssetCandle> = match self.recent.lock() {
+            Ok(recent) => recent
+                .iter()
+                .skip(recent.len().saturating_sub(n))
+                .cloned()
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        items
+            .iter()
+            .map(|item| item.to_py(py, self.output, self.tagged))
+            .collect()
+    }
+
+    /// Returns an iterator over the candles accepted by `predicate`, a `RawValidator` or a function
+    /// taking the candle as a json string and returning a boolean. The combinators read the candles of
+    /// this iterator, they shouldn't be read from both.
+    fn filter(&self, predicate: Bound<'_, PyAny>) -> PyResult<Self> {
+        let validator = if let Ok(validator) = predicate.downcast::<RawValidator>() {
+            validator.get().clone()
+        } else if predicate.is_callable() {
+            RawValidator::custom(predicate.unbind())
+        } else {
+            return Err(BinaryErrorPy::NotAllowed(format!(
+                "Expected a 'RawValidator' or a function to filter the candles, got '{}'",
+                predicate.get_type()
+            ))
+            .into());
+        };
+        Ok(self.derive(self.candles().filter(move |item| {
+            let keep = match item {
+                Ok(item) => validator.validate(&RawWebsocketMessage::from(item.candle.to_string())),
+                Err(_) => true,
+            };
+            future::ready(keep)
+        })))
+    }
+
+    /// Returns an iterator ending after `n` candles.
+    fn take(&self, n: usize) -> Self {
+        self.derive(self.candles().take(n))
+    }
+
+    /// Returns an iterator ending at the first candle at or after `timestamp`, which isn't yielded.
+    fn until(&self, timestamp: DateTime<Utc>) -> Self {
+        self.derive(self.candles().take_while(move |item| {
+            let before = item.as_ref().map_or(true, |i| i.candle.time < timestamp);
+            future::ready(before)
+        }))
+    }
+
+    /// Returns an iterator appending every candle it yields to the recording at `path` (one json
+    /// candle per line), which can be read again with `CandleReplay`.
+    ///
+    /// `compression` ("none", "gzip" or "zstd", from the extension of `path` if not given) is
+    /// applied every `chunk_size` candles, the candles of the last chunk are written once the
+    /// iterator is dropped.
+    #[pyo3(signature = (path, compression = None, level = None, chunk_size = None))]
+    fn record(
+        &self,
+        path: String,
+        compression: Option<String>,
+        level: Option<i32>,
+        chunk_size: Option<usize>,
+    ) -> PyResult<Self> {
+        let compression = match compression {
+            Some(compression) => compression.parse().map_err(BinaryErrorPy::from)?,
+            None => Compression::from_path(&path),
+        };
+        let compression = match level {
+            Some(level) => compression.level(level).map_err(BinaryErrorPy::from)?,
+            None => compression,
+        };
+        let mut recorder = CandleRecorder::create_with(&path, compression, chunk_size)
+            .map_err(BinaryErrorPy::from)?;
+        Ok(self.derive(self.candles().inspect(move |item| {
+            if let Ok(item) = item {
+                if let Err(e) = recorder.record(&item.candle) {
+                    warn!("Failed to record candle to '{path}', {e}");
+                }
+            }
+        })))
+    }
+
+    /// Returns an iterator over the value at `path` in each candle (e.g. `"close"`), as json, see
+    /// `json_path` for the syntax. The candles without a value at `path` are skipped.
+    fn map_json(&self, path: String) -> EventIterator {
+        let values = self.candles().filter_map(move |item| {
+            let value = item.and_then(|item| {
+                let value = serde_json::to_value(&item.candle)?;
+                Ok(json_path(&value, &path).cloned())
+            });
+            future::ready(value.transpose())
+        });
+        EventIterator::from_stream(values, Arc::new(StreamDeadline::new(None)))
+    }
+
+    /// Returns an iterator over the candlestick patterns completed by the candles, as json with
+    /// the `asset`, the `candle` and its `patterns`, the candles completing none are skipped. The
+    /// candles are expected closed, like the ones of `subscribe_symbol_timed`.
+    #[pyo3(signature = (config = None))]
+    fn patterns(&self, config: Option<String>) -> PyResult<EventIterator> {
+        let detector =
+            PatternDetector::new(pattern_config(config)?).map_err(BinaryErrorPy::from)?;
+        // One detector per asset, the candles of `subscribe_symbols` mix several assets
+        let mut detectors: HashMap<String, PatternDetector> = HashMap::new();
+        let events = self.candles().filter_map(move |item| {
+            let annotated = item.map(|AssetCandle { asset, candle }| {
+                let patterns = detectors
+                    .entry(asset.clone())
+                    .or_insert_with(|| detector.clone())
+                    .update(&candle);
+                (!patterns.is_empty()).then_some(CandlePatterns {
+                    asset,
+                    candle,
+                    patterns,
+                })
+            });
+            future::ready(annotated.transpose())
+        });
+        Ok(EventIterator::from_stream(
+            events,
+            Arc::new(StreamDeadline::new(None)),
+        ))
+    }
+
+    /// Returns an iterator yielding every candle with the levels (pivots, session highs / lows and
+    /// support / resistance zones) of the last `lookback` candles, as json of the `asset`, the
+    /// `candle` and its `levels`. The candles are expected closed, like the ones of
+    /// `subscribe_symbol_timed`.
+    #[pyo3(signature = (lookback, config = None))]
+    fn levels(&self, lookback: usize, config: Option<String>) -> PyResult<EventIterator> {
+        let tracker =
+            LevelTracker::new(levels_config(config)?, lookback).map_err(BinaryErrorPy::from)?;
+        let mut trackers: HashMap<String, LevelTracker> = HashMap::new();
+        let events = self
+            .candles()
+            .map(move |item| -> PocketResult<CandleLevels> {
+                let AssetCandle { asset, candle } = item?;
+                let levels = trackers
+                    .entry(asset.clone())
+                    .or_insert_with(|| tracker.clone())
+                    .update(&candle)?;
+                Ok(CandleLevels {
+                    asset,
+                    candle,
+                    levels,
+                })
+            });
+        Ok(EventIterator::from_stream(
+            events,
+            Arc::new(StreamDeadline::new(None)),
+        ))
+    }
 }
 
 #[pymethods]
@@ -448,11 +2393,11 @@ impl RawStreamIterator {
         slf
     }
 
-    fn __anext__<'py>(&'py mut self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+    fn __anext__<'py>(&'py self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
         let stream = self.stream.clone();
         future_into_py(py, async move {
             let res = next_stream(stream, false).await;
-            res.map(|res| res.to_string())
+            res.map(|res| redact(&res.to_string()).into_owned())
         })
     }
 
@@ -461,8 +2406,128 @@ impl RawStreamIterator {
         let stream = self.stream.clone();
         runtime.block_on(async move {
             let res = next_stream(stream, true).await;
-            res.map(|res| res.to_string())
+            res.map(|res| redact(&res.to_string()).into_owned())
         })
     }
+
+    /// Timeout of this iterator only, other iterators receiving the same messages aren't affected.
+    #[getter]
+    fn timeout(&self) -> Option<Duration> {
+        self.deadline.timeout()
+    }
+
+    #[pyo3(signature = (timeout = None))]
+    fn set_timeout(&self, timeout: Option<Duration>) {
+        self.deadline.set_timeout(timeout);
+    }
+
+    fn reset_timeout(&self) {
+        self.deadline.reset();
+    }
+
+    fn extend(&self, timeout: Duration) {
+        self.deadline.extend(timeout);
+    }
+
+    /// Ends the iterator immediately, even while it's waiting for a message, and sends the raw
+    /// `unsubscribe` message (if any) to stop the subscription started by the iterator. Closing a
+    /// closed iterator does nothing.
+    #[pyo3(signature = (unsubscribe = None))]
+    fn close(&self, py: Python<'_>, unsubscribe: Option<String>) -> PyResult<()> {
+        let runtime = get_runtime(py)?;
+        runtime.block_on(Self::close_with(
+            self.client.clone(),
+            self.closer.clone(),
+            unsubscribe,
+        ))
+    }
+
+    /// Same as `close`, without blocking the event loop.
+    #[pyo3(signature = (unsubscribe = None))]
+    fn aclose<'py>(
+        &self,
+        py: Python<'py>,
+        unsubscribe: Option<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let (client, closer) = (self.client.clone(), self.closer.clone());
+        future_into_py(py, Self::close_with(client, closer, unsubscribe))
+    }
+
+    #[getter]
+    fn closed(&self) -> bool {
+        self.closer.is_aborted()
+    }
+
+    fn __enter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    #[pyo3(signature = (_exc_type = None, _exc_value = None, _traceback = None))]
+    fn __exit__(
+        &self,
+        _exc_type: Option<Bound<'_, PyAny>>,
+        _exc_value: Option<Bound<'_, PyAny>>,
+        _traceback: Option<Bound<'_, PyAny>>,
+    ) -> bool {
+        self.closer.abort();
+        false
+    }
+
+    fn __aenter__<'py>(slf: Bound<'py, Self>, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let slf = slf.unbind();
+        future_into_py(py, async move { Ok(slf) })
+    }
+
+    /// Closes the iterator without sending a message, use `aclose` to unsubscribe.
+    #[pyo3(signature = (_exc_type = None, _exc_value = None, _traceback = None))]
+    fn __aexit__<'py>(
+        &self,
+        py: Python<'py>,
+        _exc_type: Option<Bound<'py, PyAny>>,
+        _exc_value: Option<Bound<'py, PyAny>>,
+        _traceback: Option<Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.closer.abort();
+        future_into_py(py, async move { Ok(false) })
+    }
 }
 
+#[pymethods]
+impl EventIterator {
+    fn __aiter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    fn __iter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    fn __anext__<'py>(&'py self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let stream = self.stream.clone();
+        future_into_py(py, async move { next_stream(stream, false).await })
+    }
+
+    fn __next__<'py>(&'py self, py: Python<'py>) -> PyResult<String> {
+        let runtime = get_runtime(py)?;
+        let stream = self.stream.clone();
+        runtime.block_on(async move { next_stream(stream, true).await })
+    }
+
+    #[getter]
+    fn timeout(&self) -> Option<Duration> {
+        self.deadline.timeout()
+    }
+
+    #[pyo3(signature = (timeout = None))]
+    fn set_timeout(&self, timeout: Option<Duration>) {
+        self.deadline.set_timeout(timeout);
+    }
+
+    fn reset_timeout(&self) {
+        self.deadline.reset();
+    }
+
+    fn extend(&self, timeout: Duration) {
+        self.deadline.extend(timeout);
+    }
+}
@@ -0,0 +1,27 @@
+use binary_options_tools::pocketoption::parser::frame::{
+    check_fixtures, load_fixtures, parse_frame,
+};
+use pyo3::{pyfunction, PyResult};
+
+use crate::error::BinaryErrorPy;
+
+/// Parses a frame as sent by the server (`451-` header, `42[...]` message or bare payload) and
+/// returns a json report of how the models handle it.
+#[pyfunction(name = "parse_frame")]
+#[pyo3(signature = (frame, context = None))]
+pub fn parse_frame_py(frame: String, context: Option<String>) -> PyResult<String> {
+    let report = parse_frame(&frame, context.as_deref());
+    Ok(serde_json::to_string(&report).map_err(BinaryErrorPy::from)?)
+}
+
+#[pyfunction(name = "load_fixtures")]
+pub fn load_fixtures_py(path: String) -> PyResult<String> {
+    let fixtures = load_fixtures(path).map_err(BinaryErrorPy::from)?;
+    Ok(serde_json::to_string(&fixtures).map_err(BinaryErrorPy::from)?)
+}
+
+#[pyfunction(name = "check_fixtures")]
+pub fn check_fixtures_py(path: String) -> PyResult<String> {
+    let reports = check_fixtures(path).map_err(BinaryErrorPy::from)?;
+    Ok(serde_json::to_string(&reports).map_err(BinaryErrorPy::from)?)
+}
@@ -0,0 +1,166 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    error::BinaryResultPy,
+    validator::{RawValidator, ValidatorSpec},
+};
+
+/// Definition of a raw iterator, enough to register it again after a restart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RawIteratorSpec {
+    pub message: String,
+    pub validator: ValidatorSpec,
+    pub timeout_secs: Option<f64>,
+}
+
+impl RawIteratorSpec {
+    pub fn new(
+        message: String,
+        validator: &RawValidator,
+        timeout: Option<Duration>,
+    ) -> BinaryResultPy<Self> {
+        Ok(Self {
+            message,
+            validator: ValidatorSpec::try_from(validator)?,
+            timeout_secs: timeout.map(|t| t.as_secs_f64()),
+        })
+    }
+
+    pub fn validator(&self) -> BinaryResultPy<RawValidator> {
+        RawValidator::try_from(self.validator.clone())
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout_secs.map(Duration::from_secs_f64)
+    }
+}
+
+/// Serializes the updates of the registry files, so two iterators registered at the same time
+/// don't overwrite each other.
+static REGISTRY_LOCK: Mutex<()> = Mutex::new(());
+
+/// File backed registry of raw iterators, indexed by a user provided stable id.
+/// The file is rewritten atomically so a crash never leaves it half written.
+pub struct IteratorRegistry {
+    path: PathBuf,
+    iterators: BTreeMap<String, RawIteratorSpec>,
+}
+
+impl IteratorRegistry {
+    pub fn load(path: impl AsRef<Path>) -> BinaryResultPy<Self> {
+        let path = path.as_ref().to_path_buf();
+        let iterators = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self { path, iterators })
+    }
+
+    /// Adds the iterator to the registry file at `path`, replacing the one with the same id.
+    pub fn register(
+        path: impl AsRef<Path>,
+        id: String,
+        spec: RawIteratorSpec,
+    ) -> BinaryResultPy<()> {
+        let _guard = REGISTRY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut registry = Self::load(path)?;
+        registry.iterators.insert(id, spec);
+        registry.save()
+    }
+
+    /// Removes the iterator from the registry file at `path`, returning it if it was registered.
+    pub fn remove(path: impl AsRef<Path>, id: &str) -> BinaryResultPy<Option<RawIteratorSpec>> {
+        let _guard = REGISTRY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut registry = Self::load(path)?;
+        let spec = registry.iterators.remove(id);
+        registry.save()?;
+        Ok(spec)
+    }
+
+    pub fn iterators(&self) -> &BTreeMap<String, RawIteratorSpec> {
+        &self.iterators
+    }
+
+    fn save(&self) -> BinaryResultPy<()> {
+        // Unique per save, so another process saving the same registry doesn't write to it
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(format!(".{}.tmp", Uuid::new_v4()));
+        std::fs::write(&tmp, serde_json::to_string_pretty(&self.iterators)?)?;
+        std::fs::rename(tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_roundtrip() -> BinaryResultPy<()> {
+        let path = std::env::temp_dir().join(format!("iterators-{}.json", uuid::Uuid::new_v4()));
+        let validator = RawValidator::new_all(vec![
+            RawValidator::new_starts_with("451-".to_string()),
+            RawValidator::new_not(RawValidator::new_contains("signals".to_string())),
+        ]);
+        IteratorRegistry::register(
+            &path,
+            "signals".to_string(),
+            RawIteratorSpec::new(
+                r#"42["signals/subscribe"]"#.to_string(),
+                &validator,
+                Some(Duration::from_secs(5)),
+            )?,
+        )?;
+
+        let registry = IteratorRegistry::load(&path)?;
+        let spec = registry
+            .iterators()
+            .get("signals")
+            .expect("Iterator persisted");
+        assert_eq!(spec.timeout(), Some(Duration::from_secs(5)));
+        assert!(spec
+            .validator()?
+            .check(r#"451-["updateStream"]"#.to_string()));
+        assert!(!spec
+            .validator()?
+            .check(r#"451-["signals/load"]"#.to_string()));
+
+        assert!(IteratorRegistry::remove(&path, "signals")?.is_some());
+        assert!(IteratorRegistry::remove(&path, "signals")?.is_none());
+        assert!(IteratorRegistry::load(&path)?.iterators().is_empty());
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_registry_concurrent_registrations() -> BinaryResultPy<()> {
+        let path = std::env::temp_dir().join(format!("iterators-{}.json", uuid::Uuid::new_v4()));
+        let validator = RawValidator::new_starts_with("451-".to_string());
+        let threads: Vec<_> = (0..16)
+            .map(|i| {
+                let (path, validator) = (path.clone(), validator.clone());
+                std::thread::spawn(move || {
+                    let spec = RawIteratorSpec::new(format!("42[{i}]"), &validator, None)?;
+                    IteratorRegistry::register(&path, format!("iterator-{i}"), spec)
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().expect("Registration thread panicked")?;
+        }
+
+        // No registration was lost
+        assert_eq!(IteratorRegistry::load(&path)?.iterators().len(), 16);
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+}
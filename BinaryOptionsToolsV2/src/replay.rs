@@ -0,0 +1,81 @@
+use std::sync::Mutex;
+
+use binary_options_tools::pocketoption::utils::recording::{self, ReplayCheckpoint};
+use chrono::{DateTime, Utc};
+use pyo3::{exceptions::PyStopIteration, pyclass, pymethods, Py, PyResult};
+
+use crate::error::BinaryErrorPy;
+
+/// Replay of a candle recording (see `StreamIterator.record`), yields the candles as json strings.
+/// `checkpoint` returns its position as json, to resume it later without reading the recording again.
+/// Compressed recordings are recognized and decompressed on the fly.
+#[pyclass]
+pub struct CandleReplay {
+    replay: Mutex<recording::CandleReplay>,
+    #[pyo3(get)]
+    path: String,
+}
+
+impl CandleReplay {
+    fn with_replay<T>(
+        &self,
+        f: impl FnOnce(&mut recording::CandleReplay) -> PyResult<T>,
+    ) -> PyResult<T> {
+        let mut replay = self
+            .replay
+            .lock()
+            .map_err(|e| BinaryErrorPy::NotAllowed(e.to_string()))?;
+        f(&mut replay)
+    }
+}
+
+#[pymethods]
+impl CandleReplay {
+    #[new]
+    #[pyo3(signature = (path, checkpoint = None))]
+    pub fn new(path: String, checkpoint: Option<String>) -> PyResult<Self> {
+        let replay = match checkpoint {
+            Some(checkpoint) => {
+                let checkpoint: ReplayCheckpoint =
+                    serde_json::from_str(&checkpoint).map_err(BinaryErrorPy::from)?;
+                recording::CandleReplay::resume(&path, &checkpoint)
+            }
+            None => recording::CandleReplay::open(&path),
+        }
+        .map_err(BinaryErrorPy::from)?;
+        Ok(Self {
+            replay: Mutex::new(replay),
+            path,
+        })
+    }
+
+    fn __iter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    fn __next__(&self) -> PyResult<String> {
+        self.with_replay(|replay| match replay.next_candle() {
+            Ok(Some(candle)) => Ok(candle.to_string()),
+            Ok(None) => Err(PyStopIteration::new_err("Recording exhausted")),
+            Err(e) => Err(BinaryErrorPy::from(e).into()),
+        })
+    }
+
+    /// Returns the position of the replay as json, pass it to the constructor to resume from it.
+    fn checkpoint(&self) -> PyResult<String> {
+        self.with_replay(|replay| {
+            Ok(serde_json::to_string(&replay.checkpoint()).map_err(BinaryErrorPy::from)?)
+        })
+    }
+
+    /// Compression of the recording: "none", "gzip" or "zstd".
+    #[getter]
+    fn compression(&self) -> PyResult<String> {
+        self.with_replay(|replay| Ok(replay.compression().to_string()))
+    }
+
+    /// Moves to the first candle at or after `timestamp`.
+    fn seek(&self, timestamp: DateTime<Utc>) -> PyResult<()> {
+        self.with_replay(|replay| Ok(replay.seek(timestamp).map_err(BinaryErrorPy::from)?))
+    }
+}
@@ -1,18 +1,21 @@
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
-use pyo3::sync::GILOnceCell;
 use tokio::runtime::Runtime;
 
-static RUNTIME: GILOnceCell<Arc<Runtime>> = GILOnceCell::new();
+/// The runtime is shared by the whole process instead of being tied to the GIL (`GILOnceCell`),
+/// so it stays valid for every interpreter and thread that uses the module.
+static RUNTIME: OnceLock<Arc<Runtime>> = OnceLock::new();
 
 /// Get the tokio runtime for sync requests
-pub(crate) fn get_runtime(py: Python<'_>) -> PyResult<Arc<Runtime>> {
-    let runtime = RUNTIME.get_or_try_init(py, || {
-        Ok::<_, PyErr>(Arc::new(Runtime::new().map_err(|err| {
-            PyValueError::new_err(format!("Could not create tokio runtime. {}", err))
-        })?))
-    })?;
-    Ok(runtime.clone())
+pub(crate) fn get_runtime(_py: Python<'_>) -> PyResult<Arc<Runtime>> {
+    if let Some(runtime) = RUNTIME.get() {
+        return Ok(runtime.clone());
+    }
+    let runtime = Arc::new(Runtime::new().map_err(|err| {
+        PyValueError::new_err(format!("Could not create tokio runtime. {}", err))
+    })?);
+    // If another thread initialized it first the new runtime is dropped and the existing one is used
+    Ok(RUNTIME.get_or_init(|| runtime).clone())
 }
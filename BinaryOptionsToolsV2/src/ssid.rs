@@ -0,0 +1,77 @@
+use async_trait::async_trait;
+use binary_options_tools::pocketoption::error::{PocketOptionError, PocketResult};
+use binary_options_tools::pocketoption::ws::session::SessionProvider;
+use binary_options_tools::pocketoption::ws::ssid::Ssid;
+use futures_util::future::Either;
+use pyo3::exceptions::PyTypeError;
+use pyo3::types::PyAnyMethods;
+use pyo3::{pyfunction, Bound, Py, PyAny, PyErr, PyResult, Python};
+use pyo3_async_runtimes::TaskLocals;
+
+use crate::error::BinaryErrorPy;
+
+/// Validates and normalizes a session string copied from the browser (the `42["auth",...]`
+/// message, its json object, the cookies or the session token), returns the json of its
+/// `SsidInfo`.
+#[pyfunction]
+#[pyo3(signature = (raw, uid = None, demo = None))]
+pub fn parse_ssid(raw: String, uid: Option<u32>, demo: Option<bool>) -> PyResult<String> {
+    let ssid = Ssid::parse_lenient(&raw, uid, demo).map_err(BinaryErrorPy::from)?;
+    Ok(serde_json::to_string(&ssid.info()).map_err(BinaryErrorPy::from)?)
+}
+
+/// Session provider calling a Python callable, a coroutine function runs on the event loop it was
+/// registered from and a plain function is called directly.
+pub struct PySessionProvider {
+    provider: Py<PyAny>,
+    locals: TaskLocals,
+}
+
+impl PySessionProvider {
+    /// `event_loop` defaults to the running event loop.
+    pub fn new(
+        py: Python<'_>,
+        provider: Py<PyAny>,
+        event_loop: Option<Bound<'_, PyAny>>,
+    ) -> PyResult<Self> {
+        if !provider.bind(py).is_callable() {
+            return Err(PyTypeError::new_err(
+                "the session provider must be a function returning the ssid",
+            ));
+        }
+        let event_loop = match event_loop {
+            Some(event_loop) => event_loop,
+            None => pyo3_async_runtimes::get_running_loop(py)?,
+        };
+        Ok(Self {
+            provider,
+            locals: TaskLocals::new(event_loop).copy_context(py)?,
+        })
+    }
+}
+
+fn provider_error(e: PyErr) -> PocketOptionError {
+    PocketOptionError::Unauthorized(format!("the session provider failed, {e}"))
+}
+
+#[async_trait]
+impl SessionProvider for PySessionProvider {
+    async fn fresh_ssid(&self) -> PocketResult<String> {
+        let result = Python::with_gil(|py| {
+            let result = self.provider.call0(py)?.into_bound(py);
+            if result.hasattr("__await__")? {
+                pyo3_async_runtimes::into_future_with_locals(&self.locals, result).map(Either::Left)
+            } else {
+                result.extract::<String>().map(Either::Right)
+            }
+        })
+        .map_err(provider_error)?;
+        match result {
+            Either::Left(future) => {
+                let ssid = future.await.map_err(provider_error)?;
+                Python::with_gil(|py| ssid.extract::<String>(py)).map_err(provider_error)
+            }
+            Either::Right(ssid) => Ok(ssid),
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use std::path::PathBuf;
+
+use binary_options_tools::storage::{set_storage, storage, Storage};
+use pyo3::{pyfunction, PyResult};
+
+use crate::error::BinaryErrorPy;
+
+/// Returns the data, log and cache directories used by the library as json.
+#[pyfunction]
+pub fn storage_dirs() -> PyResult<String> {
+    Ok(serde_json::to_string(&storage()).map_err(BinaryErrorPy::from)?)
+}
+
+/// Changes the directories used by the library, the ones not given are under `root` if it's set
+/// (`root/logs` and `root/cache` for the logs and the cache) and unchanged otherwise.
+#[pyfunction]
+#[pyo3(signature = (root = None, data_dir = None, log_dir = None, cache_dir = None))]
+pub fn set_storage_dirs(
+    root: Option<PathBuf>,
+    data_dir: Option<PathBuf>,
+    log_dir: Option<PathBuf>,
+    cache_dir: Option<PathBuf>,
+) {
+    let defaults = root.map(Storage::in_dir).unwrap_or_else(storage);
+    set_storage(Storage {
+        data_dir: data_dir.unwrap_or(defaults.data_dir),
+        log_dir: log_dir.unwrap_or(defaults.log_dir),
+        cache_dir: cache_dir.unwrap_or(defaults.cache_dir),
+    });
+}
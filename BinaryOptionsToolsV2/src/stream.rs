@@ -1,17 +1,30 @@
 use std::sync::Arc;
 
+use binary_options_tools::error::BinaryOptionsToolsError;
 use futures_util::{
-    stream::{BoxStream, Fuse},
+    stream::{select_all, unfold, BoxStream, Fuse},
     StreamExt,
 };
 use pyo3::{
     exceptions::{PyStopAsyncIteration, PyStopIteration},
-    PyResult,
+    pyclass, pyfunction, pymethods, Bound, Py, PyAny, PyResult, Python,
 };
+use pyo3_async_runtimes::tokio::future_into_py;
+use serde_json::Value;
 use tokio::sync::Mutex;
 
+use crate::error::BinaryErrorPy;
+use crate::logs::StreamLogsIterator;
+use crate::pocketoption::{EventIterator, RawStreamIterator, StreamIterator};
+use crate::runtime::get_runtime;
+
 pub type PyStream<T, E> = Fuse<BoxStream<'static, Result<T, E>>>;
 
+/// Items of an iterator as the strings it yields to python, ends at the first error.
+pub type TextStream = BoxStream<'static, String>;
+
+type MergedStream = PyStream<(String, String), BinaryOptionsToolsError>;
+
 pub async fn next_stream<T, E>(stream: Arc<Mutex<PyStream<T, E>>>, sync: bool) -> PyResult<T>
 where
     E: std::error::Error,
@@ -34,3 +47,127 @@ where
         },
     }
 }
+
+/// Reads the items of the stream of an iterator, the iterator and the stream share the items.
+pub fn text_stream<T, E>(stream: Arc<Mutex<PyStream<T, E>>>, to_text: fn(T) -> String) -> TextStream
+where
+    T: Send + 'static,
+    E: Send + 'static,
+{
+    unfold(stream, move |stream| async move {
+        let item = stream.lock().await.next().await?.ok()?;
+        Some((to_text(item), stream))
+    })
+    .boxed()
+}
+
+/// Returns the value at `path` in `value`: a json pointer (`"/candle/close"`) or keys and array
+/// indexes separated by dots (`"candle.close"`, `"prices.0"`), an empty path returns `value`.
+pub fn json_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    if path.starts_with('/') {
+        return value.pointer(path);
+    }
+    path.split('.')
+        .filter(|key| !key.is_empty())
+        .try_fold(value, |value, key| match value {
+            Value::Array(items) => items.get(key.parse::<usize>().ok()?),
+            Value::Object(map) => map.get(key),
+            _ => None,
+        })
+}
+
+/// Iterator over the items of multiple iterators in arrival order, yields `(tag, item)` tuples.
+#[pyclass]
+pub struct MergedStreamIterator {
+    stream: Arc<Mutex<MergedStream>>,
+}
+
+fn iterator_stream(iterator: &Bound<'_, PyAny>) -> PyResult<TextStream> {
+    if let Ok(iterator) = iterator.downcast::<StreamIterator>() {
+        Ok(iterator.borrow().text_stream())
+    } else if let Ok(iterator) = iterator.downcast::<RawStreamIterator>() {
+        Ok(iterator.borrow().text_stream())
+    } else if let Ok(iterator) = iterator.downcast::<EventIterator>() {
+        Ok(iterator.borrow().text_stream())
+    } else if let Ok(iterator) = iterator.downcast::<StreamLogsIterator>() {
+        Ok(iterator.borrow().text_stream())
+    } else {
+        Err(BinaryErrorPy::NotAllowed(format!(
+            "Can't merge objects of type '{}', only the iterators returned by the library can be merged",
+            iterator.get_type()
+        ))
+        .into())
+    }
+}
+
+/// Merges multiple iterators (candles, raw, events or logs iterators) into one yielding `(tag, item)`
+/// tuples in arrival order, `tags` defaults to the position of each iterator. The merged iterator
+/// ends once all the iterators ended, an iterator ends at its first error (e.g. a timeout).
+#[pyfunction]
+#[pyo3(signature = (iterators, tags = None))]
+pub fn merge_streams(
+    iterators: Vec<Bound<'_, PyAny>>,
+    tags: Option<Vec<String>>,
+) -> PyResult<MergedStreamIterator> {
+    let tags = tags.unwrap_or_else(|| (0..iterators.len()).map(|i| i.to_string()).collect());
+    if tags.len() != iterators.len() {
+        return Err(BinaryErrorPy::NotAllowed(format!(
+            "Expected {} tags, one for each iterator, got {}",
+            iterators.len(),
+            tags.len()
+        ))
+        .into());
+    }
+    let streams = iterators
+        .iter()
+        .zip(tags)
+        .map(|(iterator, tag)| {
+            Ok(iterator_stream(iterator)?
+                .map(move |item| Ok::<_, BinaryOptionsToolsError>((tag.clone(), item)))
+                .boxed())
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    Ok(MergedStreamIterator {
+        stream: Arc::new(Mutex::new(select_all(streams).boxed().fuse())),
+    })
+}
+
+#[pymethods]
+impl MergedStreamIterator {
+    fn __aiter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    fn __iter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    fn __anext__<'py>(&'py self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let stream = self.stream.clone();
+        future_into_py(py, next_stream(stream, false))
+    }
+
+    fn __next__<'py>(&'py self, py: Python<'py>) -> PyResult<(String, String)> {
+        let runtime = get_runtime(py)?;
+        let stream = self.stream.clone();
+        runtime.block_on(next_stream(stream, true))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_json_path() {
+        let value = json!({"close": 1.5, "prices": [1, 2], "meta": {"asset": "EURUSD_otc"}});
+        assert_eq!(json_path(&value, "close"), Some(&json!(1.5)));
+        assert_eq!(json_path(&value, "prices.1"), Some(&json!(2)));
+        assert_eq!(json_path(&value, "/meta/asset"), Some(&json!("EURUSD_otc")));
+        assert_eq!(json_path(&value, ""), Some(&value));
+        assert_eq!(json_path(&value, "prices.x"), None);
+        assert_eq!(json_path(&value, "close.value"), None);
+    }
+}
@@ -5,9 +5,11 @@ use pyo3::{
     types::{PyAnyMethods, PyList},
     Bound, PyObject, PyResult, Python,
 };
+#[cfg(feature = "validators-regex")]
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 
-use crate::error::BinaryResultPy;
+use crate::error::{BinaryErrorPy, BinaryResultPy};
 use binary_options_tools::{
     pocketoption::types::base::RawWebsocketMessage, reimports::ValidatorTrait,
 };
@@ -20,12 +22,21 @@ pub struct ArrayValidator(Vec<RawValidator>);
 #[derive(Clone)]
 pub struct BoxedValidator(Box<RawValidator>);
 
+#[cfg(feature = "validators-regex")]
 #[pyclass]
 #[derive(Clone)]
 pub struct RegexValidator {
     regex: Regex,
 }
 
+/// Placeholder when built without the `validators-regex` feature, it can't be constructed.
+#[cfg(not(feature = "validators-regex"))]
+#[pyclass]
+#[derive(Clone)]
+pub struct RegexValidator {
+    pattern: String,
+}
+
 #[pyclass]
 #[derive(Clone)]
 pub struct PyCustom {
@@ -46,12 +57,86 @@ pub enum RawValidator {
     Custom(PyCustom),
 }
 
+/// Serializable representation of a `RawValidator`, used to persist validators.
+/// Custom validators wrap python functions and can't be serialized.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum ValidatorSpec {
+    None,
+    Regex(String),
+    StartsWith(String),
+    EndsWith(String),
+    Contains(String),
+    All(Vec<ValidatorSpec>),
+    Any(Vec<ValidatorSpec>),
+    Not(Box<ValidatorSpec>),
+}
+
+impl TryFrom<&RawValidator> for ValidatorSpec {
+    type Error = BinaryErrorPy;
+
+    fn try_from(value: &RawValidator) -> Result<Self, Self::Error> {
+        Ok(match value {
+            RawValidator::None() => Self::None,
+            RawValidator::Regex(val) => Self::Regex(val.as_str().to_string()),
+            RawValidator::StartsWith(pat) => Self::StartsWith(pat.clone()),
+            RawValidator::EndsWith(pat) => Self::EndsWith(pat.clone()),
+            RawValidator::Contains(pat) => Self::Contains(pat.clone()),
+            RawValidator::All(val) => {
+                Self::All(val.0.iter().map(Self::try_from).collect::<Result<_, _>>()?)
+            }
+            RawValidator::Any(val) => {
+                Self::Any(val.0.iter().map(Self::try_from).collect::<Result<_, _>>()?)
+            }
+            RawValidator::Not(val) => Self::Not(Box::new(Self::try_from(val.0.as_ref())?)),
+            RawValidator::Custom(_) => {
+                return Err(BinaryErrorPy::NotAllowed(
+                    "Custom validators can't be serialized".to_string(),
+                ))
+            }
+        })
+    }
+}
+
+impl TryFrom<ValidatorSpec> for RawValidator {
+    type Error = BinaryErrorPy;
+
+    fn try_from(value: ValidatorSpec) -> Result<Self, Self::Error> {
+        Ok(match value {
+            ValidatorSpec::None => Self::None(),
+            ValidatorSpec::Regex(pattern) => Self::new_regex(pattern)?,
+            ValidatorSpec::StartsWith(pat) => Self::new_starts_with(pat),
+            ValidatorSpec::EndsWith(pat) => Self::new_ends_with(pat),
+            ValidatorSpec::Contains(pat) => Self::new_contains(pat),
+            ValidatorSpec::All(val) => Self::new_all(
+                val.into_iter()
+                    .map(Self::try_from)
+                    .collect::<Result<_, _>>()?,
+            ),
+            ValidatorSpec::Any(val) => Self::new_any(
+                val.into_iter()
+                    .map(Self::try_from)
+                    .collect::<Result<_, _>>()?,
+            ),
+            ValidatorSpec::Not(val) => Self::new_not(Self::try_from(*val)?),
+        })
+    }
+}
+
 impl RawValidator {
+    #[cfg(feature = "validators-regex")]
     pub fn new_regex(regex: String) -> BinaryResultPy<Self> {
         let regex = Regex::new(&regex)?;
         Ok(Self::Regex(RegexValidator { regex }))
     }
 
+    #[cfg(not(feature = "validators-regex"))]
+    pub fn new_regex(_regex: String) -> BinaryResultPy<Self> {
+        Err(BinaryErrorPy::NotAllowed(
+            "Regex validators require the 'validators-regex' feature".to_string(),
+        ))
+    }
+
     pub fn new_all(validators: Vec<RawValidator>) -> Self {
         Self::All(ArrayValidator(validators))
     }
@@ -128,10 +213,28 @@ impl ValidatorTrait<RawWebsocketMessage> for BoxedValidator {
     }
 }
 
+impl RegexValidator {
+    #[cfg(feature = "validators-regex")]
+    fn as_str(&self) -> &str {
+        self.regex.as_str()
+    }
+
+    #[cfg(not(feature = "validators-regex"))]
+    fn as_str(&self) -> &str {
+        &self.pattern
+    }
+}
+
 impl ValidatorTrait<RawWebsocketMessage> for RegexValidator {
+    #[cfg(feature = "validators-regex")]
     fn validate(&self, message: &RawWebsocketMessage) -> bool {
         self.regex.is_match(&message.to_string())
     }
+
+    #[cfg(not(feature = "validators-regex"))]
+    fn validate(&self, _message: &RawWebsocketMessage) -> bool {
+        false
+    }
 }
 
 #[pymethods]
@@ -190,4 +293,16 @@ impl RawValidator {
         let raw = RawWebsocketMessage::from(msg);
         self.validate(&raw)
     }
+
+    /// Serializes the validator to JSON, fails for custom validators.
+    pub fn to_json(&self) -> PyResult<String> {
+        let spec = ValidatorSpec::try_from(self)?;
+        Ok(serde_json::to_string(&spec).map_err(BinaryErrorPy::from)?)
+    }
+
+    #[staticmethod]
+    pub fn from_json(json: String) -> PyResult<Self> {
+        let spec: ValidatorSpec = serde_json::from_str(&json).map_err(BinaryErrorPy::from)?;
+        Ok(Self::try_from(spec)?)
+    }
 }
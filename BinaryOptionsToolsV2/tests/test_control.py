@@ -0,0 +1,102 @@
+import asyncio
+import json
+import unittest
+
+from BinaryOptionsToolsV2.control import MAX_BODY_SIZE, ControlServer
+
+TOKEN = "secret-token"
+
+
+class FakeService:
+    """Service whose strategy is only a flag, enough for the routes of the control API"""
+
+    def __init__(self):
+        self.client = None
+        self.running = False
+        self.stopping = False
+
+    async def status(self) -> dict:
+        return {"strategy": "running" if self.running else "stopped"}
+
+    async def start_strategy(self):
+        self.running = True
+
+    async def stop_strategy(self):
+        self.running = False
+
+    def request_reload(self):
+        pass
+
+    def request_stop(self):
+        self.stopping = True
+
+
+class ControlServerTest(unittest.IsolatedAsyncioTestCase):
+    async def asyncSetUp(self):
+        self.service = FakeService()
+        self.server = ControlServer(self.service, TOKEN, 0, host="127.0.0.1", insecure=True)
+        await self.server.start()
+
+    async def asyncTearDown(self):
+        await self.server.stop()
+
+    async def request(self, head: str, body: bytes = b"") -> tuple:
+        reader, writer = await asyncio.open_connection("127.0.0.1", self.server.port)
+        writer.write(head.encode("latin-1") + b"\r\n" + body)
+        await writer.drain()
+        response = await asyncio.wait_for(reader.read(), timeout=5)
+        writer.close()
+        status_line, _, rest = response.partition(b"\r\n")
+        _, _, content = rest.partition(b"\r\n\r\n")
+        return int(status_line.split(b" ")[1]), json.loads(content)
+
+    def head(self, method: str, path: str, token: str = TOKEN, **headers) -> str:
+        lines = [f"{method} {path} HTTP/1.1", "Host: localhost"]
+        if token is not None:
+            lines.append(f"Authorization: Bearer {token}")
+        lines += [f"{name.replace('_', '-')}: {value}" for name, value in headers.items()]
+        return "\r\n".join(lines) + "\r\n"
+
+    async def test_rejects_unauthorized_requests(self):
+        for token in (None, "wrong-token"):
+            status, response = await self.request(self.head("GET", "/status", token))
+            self.assertEqual(status, 401)
+            self.assertIn("token", response["error"])
+
+        # The body isn't read before the token is checked, the response doesn't wait for it
+        head = self.head("POST", "/shutdown", "wrong-token", Content_Length=MAX_BODY_SIZE)
+        status, _ = await self.request(head)
+        self.assertEqual(status, 401)
+        self.assertFalse(self.service.stopping)
+
+    async def test_routes_requests(self):
+        status, response = await self.request(self.head("GET", "/status"))
+        self.assertEqual((status, response), (200, {"strategy": "stopped"}))
+        status, response = await self.request(self.head("POST", "/strategy/start/"))
+        self.assertEqual((status, response), (200, {"strategy": "running"}))
+        status, response = await self.request(self.head("POST", "/shutdown"))
+        self.assertEqual((status, response), (200, {"stopping": True}))
+        self.assertTrue(self.service.stopping)
+
+        status, _ = await self.request(self.head("POST", "/status"))
+        self.assertEqual(status, 405)
+        status, _ = await self.request(self.head("GET", "/unknown"))
+        self.assertEqual(status, 404)
+
+    async def test_rejects_bad_requests(self):
+        status, _ = await self.request("GARBAGE\r\n")
+        self.assertEqual(status, 400)
+        for length in ("-1", "abc", "1_0"):
+            status, response = await self.request(self.head("POST", "/risk", Content_Length=length))
+            self.assertEqual(status, 400)
+            self.assertIn("Content-Length", response["error"])
+        status, _ = await self.request(self.head("POST", "/risk", Content_Length=MAX_BODY_SIZE + 1))
+        self.assertEqual(status, 413)
+        body = b"{not json"
+        status, response = await self.request(self.head("POST", "/risk", Content_Length=len(body)), body)
+        self.assertEqual(status, 400)
+        self.assertIn("JSON", response["error"])
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,86 @@
+import asyncio
+import json
+import unittest
+
+from BinaryOptionsToolsV2 import Candle, Deal
+from BinaryOptionsToolsV2.pocketoption.ipc import IpcServer, PocketOptionIpcAsync
+
+CANDLE = {"time": "2024-12-04T23:58:00Z", "open": 1.1, "close": 1.2, "high": 1.3, "low": 1.0}
+DEAL = {
+    "id": "0f8a7c3e-6c1b-4a35-9d1e-2b7c4f9a5e10",
+    "openTime": "2024-12-04 23:58:48",
+    "closeTime": "2024-12-04 23:59:48",
+    "openTimestamp": 1733356728,
+    "closeTimestamp": 1733356788,
+    "uid": 1,
+    "isDemo": 1,
+    "amount": 10.0,
+    "profit": 9.2,
+    "percentProfit": 92,
+    "percentLoss": 100,
+    "openPrice": 1.1,
+    "copyTicket": "",
+    "closePrice": 1.2,
+    "command": 0,
+    "asset": "EURUSD_otc",
+    "openMs": 601,
+    "optionType": 100,
+    "currency": "USD",
+}
+
+
+class FakeClient:
+    """Client returning the pyclasses of the bindings, as `PocketOptionAsync` does"""
+
+    async def get_candles(self, asset: str, period: int, offset: int) -> list:
+        return [Candle.from_json(json.dumps(CANDLE), asset)]
+
+    async def trade(self, asset: str, direction: str, amount: float, time: int):
+        return Deal.from_json(json.dumps(DEAL))
+
+    async def subscribe_symbol(self, asset: str):
+        async def candles():
+            for _ in range(2):
+                yield Candle.from_json(json.dumps(CANDLE), asset)
+
+        return candles()
+
+
+class IpcServerTest(unittest.IsolatedAsyncioTestCase):
+    async def asyncSetUp(self):
+        self.server = IpcServer(FakeClient(), "127.0.0.1:0")
+        await self.server.start()
+        port = self.server._server.sockets[0].getsockname()[1]
+        self.client = PocketOptionIpcAsync(f"127.0.0.1:{port}")
+
+    async def asyncTearDown(self):
+        await self.client.close()
+        # Lets the server read the end of the connection before the loop is closed
+        await asyncio.sleep(0.05)
+        await self.server.close()
+
+    def assertCandle(self, candle: dict):
+        self.assertEqual(candle["asset"], "EURUSD_otc")
+        self.assertEqual(candle["time"], CANDLE["time"])
+        for key in ("open", "high", "low", "close"):
+            self.assertEqual(candle[key], CANDLE[key])
+
+    async def test_sends_candles(self):
+        candles = await self.client.get_candles("EURUSD_otc", 60, 3600)
+        self.assertEqual(len(candles), 1)
+        self.assertCandle(candles[0])
+
+        subscription = await self.client.subscribe_symbol("EURUSD_otc")
+        streamed = [candle async for candle in subscription]
+        self.assertEqual(len(streamed), 2)
+        for candle in streamed:
+            self.assertCandle(candle)
+
+    async def test_sends_deals(self):
+        deal = await self.client.trade("EURUSD_otc", "call", 10.0, 60)
+        for key in ("id", "asset", "amount", "profit", "openPrice", "closePrice", "currency"):
+            self.assertEqual(deal[key], DEAL[key])
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,61 @@
+import asyncio
+import socket
+import unittest
+from unittest import mock
+
+from BinaryOptionsToolsV2.service import Service
+
+
+def free_port() -> int:
+    with socket.socket() as s:
+        s.bind(("127.0.0.1", 0))
+        return s.getsockname()[1]
+
+
+class FakeClient:
+    """Client whose healthcheck binds its port like the embedded endpoint does"""
+
+    clients: list = []
+
+    def __init__(self, ssid, url=None, config=None):
+        self.server = None
+        self.shut_down = False
+        FakeClient.clients.append(self)
+
+    async def start_healthcheck(self, port: int):
+        self.server = await asyncio.start_server(lambda r, w: w.close(), "127.0.0.1", port)
+
+    async def shutdown(self):
+        self.shut_down = True
+        self.server.close()
+        await self.server.wait_closed()
+
+
+async def strategy(client):
+    await asyncio.Event().wait()
+
+
+class ServiceTest(unittest.IsolatedAsyncioTestCase):
+    async def test_reload_shuts_down_the_client(self):
+        FakeClient.clients.clear()
+        with mock.patch("BinaryOptionsToolsV2.service.PocketOptionAsync", FakeClient), mock.patch.object(
+            Service, "_install_signal_handlers"
+        ):
+            service = Service("ssid", strategy, healthcheck_port=free_port())
+            task = asyncio.create_task(service.run())
+            await asyncio.sleep(0.05)
+            for _ in range(2):
+                service.request_reload()
+                await asyncio.sleep(0.05)
+            self.assertIsNone(service.error)
+            self.assertFalse(task.done())
+            self.assertEqual([c.shut_down for c in FakeClient.clients], [True, True, False])
+
+            service.request_stop()
+            await asyncio.wait_for(task, timeout=5)
+            self.assertTrue(all(c.shut_down for c in FakeClient.clients))
+            self.assertIsNone(service.client)
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -2,8 +2,32 @@ pub mod pocketoption;
 pub mod reimports;
 
 pub mod stream {
-    pub use binary_options_tools_core::general::stream::RecieverStream;
-    pub use binary_options_tools_core::utils::tracing::stream_logs_layer;
+    pub use binary_options_tools_core::general::stream::{RecieverStream, StreamDeadline};
+    pub use binary_options_tools_core::utils::redact::{
+        RedactingWriter, add_secret_pattern, is_redaction_enabled, redact, set_redaction,
+    };
+    #[cfg(feature = "logging-stream")]
+    pub use binary_options_tools_core::utils::tracing::{
+        FieldPredicate, LogBroadcast, LogFilter, stream_logs_broadcast_layer, stream_logs_layer,
+        stream_logs_layer_filtered,
+    };
+}
+
+pub mod audit {
+    pub use binary_options_tools_core::utils::audit::{
+        AUDIT_TARGET, AuditRecord, audit_layer, verify_audit_log,
+    };
+}
+
+pub mod storage {
+    pub use binary_options_tools_core::utils::storage::{
+        CACHE_DIR_ENV, DATA_DIR_ENV, LOG_DIR_ENV, Storage, create_parent_dirs, file_in,
+        set_storage, storage,
+    };
+}
+
+pub mod locale {
+    pub use binary_options_tools_core::utils::locale::{LOCALE_ENV, Locale, locale, set_locale};
 }
 
 pub mod error {
@@ -0,0 +1,385 @@
+//! API-key roles of the gateway and the IPC bridge, so a dashboard or a teammate can be given a key
+//! that reads the account or trades within limits without having full control of the client.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    str::FromStr,
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant},
+};
+
+use binary_options_tools_core::utils::audit::AUDIT_TARGET;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+
+use super::error::{PocketOptionError, PocketResult};
+
+/// Methods only reading the account or the market data, allowed for every role.
+const READ_METHODS: &[&str] = &[
+    "account_currency",
+    "audit_candles",
+    "balance",
+    "chart_range",
+    "chart_trade",
+    "check_results",
+    "check_results_many",
+    "check_results_within",
+    "check_win",
+    "check_win_many",
+    "closed_deals",
+    "connection_events",
+    "debug_stats",
+    "drawdown_state",
+    "expectancy",
+    "feed_health",
+    "get_assets",
+    "get_balance",
+    "get_candles",
+    "get_candles_advanced",
+    "get_candles_between",
+    "get_candles_range",
+    "get_closed_deals",
+    "get_opened_deals",
+    "get_payout",
+    "get_payout_for",
+    "get_recent_candles",
+    "get_server_time",
+    "health",
+    "history",
+    "is_connected",
+    "is_demo",
+    "is_observer",
+    "is_open",
+    "is_shut_down",
+    "journal",
+    "journal_stats",
+    "latency_stats",
+    "levels",
+    "list_templates",
+    "market_guard_status",
+    "market_hours_check",
+    "metadata_status",
+    "nearest_expiry",
+    "normalize",
+    "opened_deals",
+    "otc_fallback",
+    "payout",
+    "pending_orders",
+    "performance_by_hour",
+    "performance_by_weekday",
+    "pips_between",
+    "poll_result",
+    "precision",
+    "price_orders",
+    "profile",
+    "resolve_asset",
+    "risk_rules",
+    "round_price",
+    "round_stake",
+    "scheduled_orders",
+    "server_ids",
+    "session_status",
+    "stake_rules",
+    "stream_lane_stats",
+    "subscribe_account_events",
+    "subscribe_candle_close",
+    "subscribe_straddle_results",
+    "subscribe_subscription_events",
+    "subscribe_symbol",
+    "subscribe_symbol_batch",
+    "subscribe_symbol_chuncked",
+    "subscribe_symbol_timed",
+    "subscribe_symbols",
+    "subscribe_trade_results",
+    "subscribe_unparsed",
+    "subscription_policy",
+    "subscription_status",
+    "suggest_expiry",
+    "tap_raw",
+    "unsubscribe_symbol",
+    "valid_expiries",
+];
+
+/// Methods placing trades, allowed for the `TradeLimited` role within its limits.
+const TRADE_METHODS: &[&str] = &[
+    "buy",
+    "buy_at_price",
+    "buy_at_time",
+    "execute_template",
+    "hedge",
+    "order_at_price",
+    "order_at_time",
+    "place_order",
+    "sell",
+    "sell_at_price",
+    "sell_at_time",
+    "straddle",
+    "trade",
+    "trade_with_min_payout",
+];
+
+/// Role granted by an API key, each role can do everything the roles before it can.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Market data, account state and statistics.
+    ReadOnly,
+    /// Trades within the `TradeLimits` of the key.
+    TradeLimited,
+    /// Every method, including the risk rules, the raw messages and the configuration.
+    Admin,
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ReadOnly => write!(f, "read_only"),
+            Self::TradeLimited => write!(f, "trade_limited"),
+            Self::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = PocketOptionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "read_only" | "readonly" | "read" => Ok(Self::ReadOnly),
+            "trade_limited" | "trade" => Ok(Self::TradeLimited),
+            "admin" => Ok(Self::Admin),
+            _ => Err(PocketOptionError::GeneralParsingError(format!(
+                "Invalid role '{s}', expected 'read_only', 'trade_limited' or 'admin'"
+            ))),
+        }
+    }
+}
+
+/// What a request does, the methods that aren't known to only read or trade require `Admin`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operation {
+    Read,
+    /// Places a trade of `amount`, if it's known.
+    Trade {
+        amount: Option<f64>,
+    },
+    Admin,
+}
+
+impl Operation {
+    /// Classifies a method of the client, `amount` is the amount of the order it places if any.
+    pub fn of_method(method: &str, amount: Option<f64>) -> Self {
+        if READ_METHODS.contains(&method) {
+            Self::Read
+        } else if TRADE_METHODS.contains(&method) {
+            Self::Trade { amount }
+        } else {
+            Self::Admin
+        }
+    }
+
+    pub fn required_role(&self) -> Role {
+        match self {
+            Self::Read => Role::ReadOnly,
+            Self::Trade { .. } => Role::TradeLimited,
+            Self::Admin => Role::Admin,
+        }
+    }
+}
+
+/// Limits of the trades placed with a `TradeLimited` key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradeLimits {
+    /// Maximum amount of a single trade, the trades of unknown amount are rejected if it's set.
+    pub max_amount: Option<f64>,
+    /// Maximum number of trades in `window`.
+    pub max_trades: Option<usize>,
+    pub window: Duration,
+}
+
+impl Default for TradeLimits {
+    fn default() -> Self {
+        Self {
+            max_amount: None,
+            max_trades: None,
+            window: Duration::from_secs(3600),
+        }
+    }
+}
+
+struct Grant {
+    name: String,
+    role: Role,
+    limits: TradeLimits,
+    trades: Mutex<VecDeque<Instant>>,
+}
+
+impl Grant {
+    fn check_limits(&self, amount: Option<f64>) -> Result<(), String> {
+        if let Some(max_amount) = self.limits.max_amount {
+            match amount {
+                Some(amount) if amount > max_amount => {
+                    return Err(format!(
+                        "the amount {amount} is over the limit of {max_amount} per trade"
+                    ));
+                }
+                None => {
+                    return Err(format!(
+                        "the amount of the trade isn't known, it can't be checked against the limit of {max_amount}"
+                    ));
+                }
+                _ => {}
+            }
+        }
+        if let Some(max_trades) = self.limits.max_trades {
+            let mut trades = self
+                .trades
+                .lock()
+                .map_err(|e| format!("the trade limits can't be checked, {e}"))?;
+            let now = Instant::now();
+            while trades
+                .front()
+                .is_some_and(|t| now.duration_since(*t) >= self.limits.window)
+            {
+                trades.pop_front();
+            }
+            if trades.len() >= max_trades {
+                return Err(format!(
+                    "the limit of {max_trades} trades every {:?} was reached",
+                    self.limits.window
+                ));
+            }
+            trades.push_back(now);
+        }
+        Ok(())
+    }
+}
+
+/// API keys allowed to use the gateway or the IPC bridge, with their role. Only the SHA-256
+/// digests of the keys are kept.
+#[derive(Default)]
+pub struct AccessControl {
+    keys: RwLock<HashMap<[u8; 32], Arc<Grant>>>,
+}
+
+impl AccessControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allows `key` with `role`, replacing its previous role if it was already allowed. The
+    /// `limits` only apply to the `TradeLimited` role.
+    pub fn add_key(
+        &self,
+        key: &str,
+        name: impl ToString,
+        role: Role,
+        limits: TradeLimits,
+    ) -> PocketResult<()> {
+        if key.is_empty() {
+            return Err(PocketOptionError::Unallowed(
+                "API keys can't be empty".into(),
+            ));
+        }
+        let grant = Grant {
+            name: name.to_string(),
+            role,
+            limits,
+            trades: Mutex::new(VecDeque::new()),
+        };
+        info!(target: AUDIT_TARGET, event = "api_key_added", name = %grant.name, %role);
+        self.keys
+            .write()
+            .map_err(|e| PocketOptionError::UnreachableError(e.to_string()))?
+            .insert(digest(key), Arc::new(grant));
+        Ok(())
+    }
+
+    /// Revokes `key`, returns false if it wasn't allowed.
+    pub fn remove_key(&self, key: &str) -> bool {
+        self.keys
+            .write()
+            .is_ok_and(|mut keys| keys.remove(&digest(key)).is_some())
+    }
+
+    /// Returns the role of `key`, `None` if it isn't allowed.
+    pub fn role(&self, key: &str) -> Option<Role> {
+        self.grant(key).map(|g| g.role)
+    }
+
+    /// Checks that `key` is allowed to do `operation`, returning its role. A trade allowed for a
+    /// `TradeLimited` key counts against its limits.
+    pub fn authorize(&self, key: Option<&str>, operation: &Operation) -> PocketResult<Role> {
+        let grant = key.and_then(|k| self.grant(k)).ok_or_else(|| {
+            warn!(target: AUDIT_TARGET, event = "access_denied", reason = "invalid_key", ?operation);
+            PocketOptionError::Unauthorized("missing or invalid API key".into())
+        })?;
+        let denied = |reason: String| {
+            warn!(target: AUDIT_TARGET, event = "access_denied", name = %grant.name, role = %grant.role, ?operation, %reason);
+            PocketOptionError::PermissionDenied {
+                role: grant.role.to_string(),
+                reason,
+            }
+        };
+        if grant.role < operation.required_role() {
+            return Err(denied(format!(
+                "the operation requires the '{}' role",
+                operation.required_role()
+            )));
+        }
+        if let (Role::TradeLimited, Operation::Trade { amount }) = (grant.role, operation) {
+            grant.check_limits(*amount).map_err(denied)?;
+        }
+        Ok(grant.role)
+    }
+
+    fn grant(&self, key: &str) -> Option<Arc<Grant>> {
+        self.keys.read().ok()?.get(&digest(key)).cloned()
+    }
+}
+
+fn digest(key: &str) -> [u8; 32] {
+    Sha256::digest(key.as_bytes()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roles_and_trade_limits() -> anyhow::Result<()> {
+        let access = AccessControl::new();
+        let limits = TradeLimits {
+            max_amount: Some(5.0),
+            max_trades: Some(2),
+            ..Default::default()
+        };
+        access.add_key("dashboard", "dashboard", Role::ReadOnly, limits.clone())?;
+        access.add_key("teammate", "teammate", Role::TradeLimited, limits.clone())?;
+        access.add_key("owner", "owner", Role::Admin, limits)?;
+
+        let call = |key, method| access.authorize(Some(key), &Operation::of_method(method, None));
+        let buy =
+            |key, amount| access.authorize(Some(key), &Operation::of_method("buy", Some(amount)));
+        assert!(access.authorize(None, &Operation::Read).is_err());
+        assert!(call("unknown", "get_candles").is_err());
+        assert_eq!(call("dashboard", "get_candles")?, Role::ReadOnly);
+        assert!(buy("dashboard", 1.0).is_err());
+
+        assert!(buy("teammate", 10.0).is_err());
+        assert!(call("teammate", "hedge").is_err());
+        buy("teammate", 1.0)?;
+        buy("teammate", 1.0)?;
+        assert!(buy("teammate", 1.0).is_err());
+        assert!(call("teammate", "set_drawdown_breaker").is_err());
+
+        buy("owner", 100.0)?;
+        call("owner", "send_raw_message")?;
+        assert!(access.remove_key("owner"));
+        assert_eq!(access.role("owner"), None);
+        assert_eq!("read-only".parse::<Role>()?, Role::ReadOnly);
+        Ok(())
+    }
+}
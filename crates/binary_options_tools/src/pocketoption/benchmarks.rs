@@ -0,0 +1,301 @@
+//! Instrumented benchmarks of the hot paths of the client (message parsing, order serialization,
+//! order send path, stream throughput and websocket round trip), they don't need a connection so
+//! they can be run on any release to compare its performance with the previous ones.
+
+use std::time::{Duration, Instant};
+
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::net::{TcpListener, TcpStream};
+
+use binary_options_tools_core::{
+    constants::MAX_CHANNEL_CAPACITY,
+    error::{BinaryOptionsResult, BinaryOptionsToolsError},
+    general::{config::ConnectionTuning, send::SenderMessage},
+    reimports::{Message, accept_async, client_async_with_config},
+};
+
+use super::{
+    error::PocketResult,
+    parser::message::WebSocketMessage,
+    risk::{ClusterGuard, RiskManager, TradeIntent},
+    types::{
+        info::MessageInfo,
+        order::{Action, OpenOrder},
+        update::UpdateStream,
+    },
+    ws::stream::StreamAsset,
+};
+
+const SUCCESS_OPEN_ORDER: &str = include_str!("../../tests/corpus/success_open_order.json");
+const UPDATE_HISTORY_NEW_FAST: &str =
+    include_str!("../../tests/corpus/update_history_new_fast.json");
+const UPDATE_STREAM: &str = r#"[["EURUSD_otc",1732830108.205,1.08543]]"#;
+
+/// Timing of a single benchmark, the durations are in microseconds.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BenchmarkResult {
+    pub name: String,
+    pub iterations: usize,
+    pub total_ms: f64,
+    pub mean_us: f64,
+    /// Percentiles of the iterations, `None` for the benchmarks only timed as a whole (throughput).
+    pub p50_us: Option<f64>,
+    pub p99_us: Option<f64>,
+    /// Operations per second.
+    pub throughput: f64,
+}
+
+impl BenchmarkResult {
+    fn from_samples(name: &str, mut samples: Vec<Duration>) -> Self {
+        samples.sort();
+        let total: Duration = samples.iter().sum();
+        let percentile = |p: f64| {
+            samples
+                .get(((samples.len().max(1) - 1) as f64 * p).round() as usize)
+                .map(|d| d.as_secs_f64() * 1e6)
+        };
+        Self {
+            p50_us: percentile(0.5),
+            p99_us: percentile(0.99),
+            ..Self::from_total(name, samples.len(), total)
+        }
+    }
+
+    fn from_total(name: &str, iterations: usize, total: Duration) -> Self {
+        let secs = total.as_secs_f64();
+        Self {
+            name: name.to_string(),
+            iterations,
+            total_ms: secs * 1e3,
+            mean_us: secs * 1e6 / iterations.max(1) as f64,
+            p50_us: None,
+            p99_us: None,
+            throughput: if secs > 0.0 {
+                iterations as f64 / secs
+            } else {
+                0.0
+            },
+        }
+    }
+}
+
+fn payload(fixture: &str) -> PocketResult<String> {
+    let fixture: Value = serde_json::from_str(fixture)?;
+    Ok(fixture["payload"].to_string())
+}
+
+fn bench_sync<T>(
+    name: &str,
+    iterations: usize,
+    mut f: impl FnMut() -> PocketResult<T>,
+) -> PocketResult<BenchmarkResult> {
+    let mut samples = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        let start = Instant::now();
+        std::hint::black_box(f()?);
+        samples.push(start.elapsed());
+    }
+    Ok(BenchmarkResult::from_samples(name, samples))
+}
+
+/// Time spent parsing the payloads of the most frequent messages with the configured backend
+/// (`parser::json::JSON_BACKEND`).
+pub fn bench_parsing(iterations: usize) -> PocketResult<Vec<BenchmarkResult>> {
+    let deal = payload(SUCCESS_OPEN_ORDER)?;
+    let history = payload(UPDATE_HISTORY_NEW_FAST)?;
+    Ok(vec![
+        bench_sync("parse_update_stream", iterations, || {
+            Ok(WebSocketMessage::try_parse_with_context(
+                UPDATE_STREAM,
+                &MessageInfo::UpdateStream,
+            )?)
+        })?,
+        // Same payload parsed with serde_json, to compare the backends when `simd-json` is enabled
+        bench_sync("parse_update_stream_serde_json", iterations, || {
+            Ok(WebSocketMessage::UpdateStream(serde_json::from_str(
+                UPDATE_STREAM,
+            )?))
+        })?,
+        bench_sync("parse_open_order", iterations, || {
+            Ok(WebSocketMessage::try_parse_with_context(
+                &deal,
+                &MessageInfo::SuccessopenOrder,
+            )?)
+        })?,
+        bench_sync("parse_history", iterations, || {
+            Ok(WebSocketMessage::try_parse_with_context(
+                &history,
+                &MessageInfo::UpdateHistoryNewFast,
+            )?)
+        })?,
+    ])
+}
+
+/// Time spent serializing an order into the frame sent to the server.
+pub fn bench_serialization(iterations: usize) -> PocketResult<BenchmarkResult> {
+    bench_sync("serialize_open_order", iterations, || {
+        let order = OpenOrder::new(1.0, "EURUSD_otc".into(), Action::Call, 60, 1)?;
+        Ok(Message::from(WebSocketMessage::OpenOrder(order)))
+    })
+}
+
+/// Time spent between a trade request and its frame leaving the outgoing queue: risk checks,
+/// serialization and the prioritized queue of the websocket writer.
+pub async fn bench_order_path(iterations: usize) -> PocketResult<BenchmarkResult> {
+    let risk = RiskManager::default();
+    risk.add_rule(ClusterGuard::new(usize::MAX, Duration::from_secs(60), true));
+    let (sender, (reciever, _)) = SenderMessage::new(MAX_CHANNEL_CAPACITY);
+    let mut samples = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        let start = Instant::now();
+        let intent = TradeIntent::new("EURUSD_otc", Action::Call, 1.0, 60);
+        risk.check(&intent)?;
+        let order = OpenOrder::new(intent.amount, intent.asset, intent.action, intent.time, 1)?;
+        sender.send(WebSocketMessage::OpenOrder(order)).await?;
+        std::hint::black_box(
+            reciever
+                .recv()
+                .await
+                .map_err(BinaryOptionsToolsError::from)?,
+        );
+        samples.push(start.elapsed());
+    }
+    Ok(BenchmarkResult::from_samples("order_send_path", samples))
+}
+
+/// Number of price updates per second a `StreamAsset` can deliver, from the parsed message to the
+/// candle recieved by the user.
+pub async fn bench_stream_throughput(iterations: usize) -> PocketResult<BenchmarkResult> {
+    let (sender, reciever) = async_channel::bounded(MAX_CHANNEL_CAPACITY);
+    let stream = StreamAsset::new(reciever, "EURUSD_otc".into());
+    let update: UpdateStream = serde_json::from_str(UPDATE_STREAM)?;
+    let producer = tokio::spawn(async move {
+        for _ in 0..iterations {
+            if sender
+                .send(WebSocketMessage::UpdateStream(update.clone()))
+                .await
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+    let start = Instant::now();
+    for _ in 0..iterations {
+        std::hint::black_box(stream.recieve().await?);
+    }
+    let total = start.elapsed();
+    producer.await.map_err(BinaryOptionsToolsError::from)?;
+    Ok(BenchmarkResult::from_total(
+        "stream_throughput",
+        iterations,
+        total,
+    ))
+}
+
+/// Round trip of an order frame through a local websocket echo server, with the default socket
+/// options (`round_trip_default`) and with the `ConnectionTuning::low_latency` ones
+/// (`round_trip_low_latency`), to weigh the preset on the current machine.
+pub async fn bench_connection_tuning(iterations: usize) -> PocketResult<Vec<BenchmarkResult>> {
+    let order = OpenOrder::new(1.0, "EURUSD_otc".into(), Action::Call, 5, 1)?;
+    let frame = Message::from(WebSocketMessage::OpenOrder(order));
+    let mut results = Vec::new();
+    for (name, tuning) in [
+        ("round_trip_default", ConnectionTuning::default()),
+        ("round_trip_low_latency", ConnectionTuning::low_latency()),
+    ] {
+        let samples = round_trips(iterations, tuning, &frame).await?;
+        results.push(BenchmarkResult::from_samples(name, samples));
+    }
+    Ok(results)
+}
+
+async fn round_trips(
+    iterations: usize,
+    tuning: ConnectionTuning,
+    frame: &Message,
+) -> BinaryOptionsResult<Vec<Duration>> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let address = listener.local_addr()?;
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await?;
+        stream.set_nodelay(tuning.tcp_nodelay)?;
+        let mut ws = accept_async(stream).await?;
+        while let Some(msg) = ws.next().await {
+            let msg = msg?;
+            if msg.is_close() {
+                break;
+            }
+            ws.send(msg).await?;
+        }
+        Ok::<_, BinaryOptionsToolsError>(())
+    });
+
+    let stream = TcpStream::connect(address).await?;
+    stream.set_nodelay(tuning.tcp_nodelay)?;
+    let (mut ws, _) = client_async_with_config(
+        format!("ws://{address}"),
+        stream,
+        Some(tuning.websocket_config()),
+    )
+    .await?;
+    let mut samples = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        let start = Instant::now();
+        ws.send(frame.clone()).await?;
+        std::hint::black_box(ws.next().await.transpose()?);
+        samples.push(start.elapsed());
+    }
+    ws.close(None).await?;
+    server.await??;
+    Ok(samples)
+}
+
+/// Runs every benchmark with `iterations` iterations each.
+///
+/// # Examples
+/// ```rust
+/// for result in run_benchmarks(10_000).await? {
+///     println!("{}: {:.2}us ({:.0}/s)", result.name, result.mean_us, result.throughput);
+/// }
+/// ```
+pub async fn run_benchmarks(iterations: usize) -> PocketResult<Vec<BenchmarkResult>> {
+    let mut results = bench_parsing(iterations)?;
+    results.push(bench_serialization(iterations)?);
+    results.push(bench_order_path(iterations).await?);
+    results.push(bench_stream_throughput(iterations).await?);
+    results.extend(bench_connection_tuning(iterations).await?);
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_run_benchmarks() -> anyhow::Result<()> {
+        let results = run_benchmarks(100).await?;
+        let names: Vec<&str> = results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "parse_update_stream",
+                "parse_update_stream_serde_json",
+                "parse_open_order",
+                "parse_history",
+                "serialize_open_order",
+                "order_send_path",
+                "stream_throughput",
+                "round_trip_default",
+                "round_trip_low_latency"
+            ]
+        );
+        for result in results {
+            assert_eq!(result.iterations, 100);
+            assert!(result.throughput > 0.0, "{result:?}");
+        }
+        Ok(())
+    }
+}
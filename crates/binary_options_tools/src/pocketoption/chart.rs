@@ -0,0 +1,306 @@
+//! Candle charts rendered to PNG images, with the entries and exits of trades marked on them, to
+//! attach to the alerts sent by a bot (Telegram, Discord, emails...).
+
+use std::str::FromStr;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use plotters::{coord::Shift, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    error::{PocketOptionError, PocketResult},
+    types::{order::Deal, update::DataCandle},
+};
+
+/// Number of candles shown before the entry and after the exit of a trade by `PocketOption::chart_trade`.
+pub const CHART_TRADE_PADDING: i64 = 20;
+
+const BACKGROUND: RGBColor = RGBColor(17, 20, 24);
+const GRID: RGBColor = RGBColor(42, 48, 56);
+const TEXT: RGBColor = RGBColor(228, 230, 235);
+const GAIN: RGBColor = RGBColor(76, 195, 138);
+const LOSS: RGBColor = RGBColor(240, 97, 109);
+const DRAW: RGBColor = RGBColor(154, 164, 178);
+
+/// Kind of a `ChartMarker`, entries are drawn as triangles and exits as squares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarkerKind {
+    Call,
+    Put,
+    Win,
+    Loss,
+    Draw,
+}
+
+impl MarkerKind {
+    fn color(&self) -> RGBColor {
+        match self {
+            Self::Call | Self::Win => GAIN,
+            Self::Put | Self::Loss => LOSS,
+            Self::Draw => DRAW,
+        }
+    }
+
+    /// Vertices of the marker, in pixels around its point.
+    fn shape(&self) -> Vec<(i32, i32)> {
+        match self {
+            Self::Call => vec![(0, -7), (7, 6), (-7, 6)],
+            Self::Put => vec![(0, 7), (7, -6), (-7, -6)],
+            Self::Win | Self::Loss | Self::Draw => vec![(-5, -5), (5, -5), (5, 5), (-5, 5)],
+        }
+    }
+}
+
+impl FromStr for MarkerKind {
+    type Err = PocketOptionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "call" | "buy" => Ok(Self::Call),
+            "put" | "sell" => Ok(Self::Put),
+            "win" => Ok(Self::Win),
+            "loss" => Ok(Self::Loss),
+            "draw" => Ok(Self::Draw),
+            _ => Err(PocketOptionError::GeneralParsingError(format!(
+                "unknown marker '{s}', expected call, put, win, loss or draw"
+            ))),
+        }
+    }
+}
+
+/// Point marked on a chart.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChartMarker {
+    pub time: DateTime<Utc>,
+    pub price: f64,
+    pub kind: MarkerKind,
+}
+
+impl ChartMarker {
+    pub fn new(time: DateTime<Utc>, price: f64, kind: MarkerKind) -> Self {
+        Self { time, price, kind }
+    }
+
+    /// Entry of a deal, and its exit once it's closed.
+    pub fn from_deal(deal: &Deal) -> Vec<Self> {
+        let entry = if deal.command == 0 {
+            MarkerKind::Call
+        } else {
+            MarkerKind::Put
+        };
+        let mut markers = vec![Self::new(deal.open_timestamp, deal.open_price, entry)];
+        if deal.close_price > 0.0 {
+            let exit = if deal.profit > 0.0 {
+                MarkerKind::Win
+            } else if deal.profit < 0.0 {
+                MarkerKind::Loss
+            } else {
+                MarkerKind::Draw
+            };
+            markers.push(Self::new(deal.close_timestamp, deal.close_price, exit));
+        }
+        markers
+    }
+}
+
+/// Size and title of a chart.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChartOptions {
+    pub width: u32,
+    pub height: u32,
+    pub title: Option<String>,
+}
+
+impl Default for ChartOptions {
+    fn default() -> Self {
+        Self {
+            width: 1000,
+            height: 500,
+            title: None,
+        }
+    }
+}
+
+impl ChartOptions {
+    pub fn validate(&self) -> PocketResult<()> {
+        if !(100..=4000).contains(&self.width) || !(100..=4000).contains(&self.height) {
+            return Err(PocketOptionError::Unallowed(format!(
+                "the size of the chart must be between 100 and 4000 pixels, got {}x{}",
+                self.width, self.height
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn chart_error(e: impl std::fmt::Display) -> PocketOptionError {
+    PocketOptionError::ChartError(e.to_string())
+}
+
+/// Renders `candles` (sorted by time) and `markers` to a PNG image.
+///
+/// The labels are drawn with the sans-serif font of the system, so a font must be installed
+/// (e.g. `fonts-dejavu-core` on slim docker images).
+pub fn render_chart(
+    candles: &[DataCandle],
+    markers: &[ChartMarker],
+    options: &ChartOptions,
+) -> PocketResult<Vec<u8>> {
+    options.validate()?;
+    let (Some(first), Some(last)) = (candles.first(), candles.last()) else {
+        return Err(PocketOptionError::EmptyArrayError("DataCandle".into()));
+    };
+    let mut pixels = vec![0u8; options.width as usize * options.height as usize * 3];
+    let root = BitMapBackend::with_buffer(&mut pixels, (options.width, options.height))
+        .into_drawing_area();
+    draw(&root, candles, markers, options, (first.time, last.time))?;
+    root.present().map_err(chart_error)?;
+    drop(root);
+    encode_png(&pixels, options.width, options.height)
+}
+
+fn draw(
+    root: &DrawingArea<BitMapBackend<'_>, Shift>,
+    candles: &[DataCandle],
+    markers: &[ChartMarker],
+    options: &ChartOptions,
+    (first, last): (DateTime<Utc>, DateTime<Utc>),
+) -> PocketResult<()> {
+    // Half a candle of margin on both sides, so the first and last candles are drawn whole
+    let step = candles
+        .windows(2)
+        .map(|pair| pair[1].time - pair[0].time)
+        .min()
+        .unwrap_or(TimeDelta::seconds(60));
+    let start = markers.iter().map(|m| m.time).fold(first, |a, b| a.min(b)) - step / 2;
+    let end = markers.iter().map(|m| m.time).fold(last, |a, b| a.max(b)) + step / 2;
+    let prices = candles
+        .iter()
+        .flat_map(|c| [c.low, c.high])
+        .chain(markers.iter().map(|m| m.price));
+    let (low, high) = prices.fold((f64::MAX, f64::MIN), |(low, high), p| {
+        (low.min(p), high.max(p))
+    });
+    let margin = ((high - low) * 0.05).max(high.abs() * 1e-5);
+    let decimals = price_decimals(candles);
+
+    root.fill(&BACKGROUND).map_err(chart_error)?;
+    let mut builder = ChartBuilder::on(root);
+    builder
+        .margin(12)
+        .x_label_area_size(30)
+        .y_label_area_size(70);
+    if let Some(title) = &options.title {
+        builder.caption(title, ("sans-serif", 20).into_font().color(&TEXT));
+    }
+    let mut chart = builder
+        .build_cartesian_2d(start..end, (low - margin)..(high + margin))
+        .map_err(chart_error)?;
+    chart
+        .configure_mesh()
+        .bold_line_style(GRID)
+        .light_line_style(BACKGROUND)
+        .axis_style(GRID)
+        .label_style(("sans-serif", 13).into_font().color(&TEXT))
+        .x_labels(8)
+        .y_labels(8)
+        .x_label_formatter(&|time| time.format("%H:%M").to_string())
+        .y_label_formatter(&|price| format!("{price:.decimals$}"))
+        .draw()
+        .map_err(chart_error)?;
+
+    let plot_width = options.width.saturating_sub(100) as f64;
+    let candle_width = (plot_width / candles.len() as f64 * 0.7).clamp(1.0, 20.0) as u32;
+    chart
+        .draw_series(candles.iter().map(|c| {
+            CandleStick::new(
+                c.time,
+                c.open,
+                c.high,
+                c.low,
+                c.close,
+                GAIN.filled(),
+                LOSS.filled(),
+                candle_width,
+            )
+        }))
+        .map_err(chart_error)?;
+    chart
+        .draw_series(markers.iter().map(|m| {
+            EmptyElement::at((m.time, m.price))
+                + Polygon::new(m.kind.shape(), m.kind.color().filled())
+        }))
+        .map_err(chart_error)?;
+    Ok(())
+}
+
+/// Number of decimals of the prices of the candles, at most 6.
+fn price_decimals(candles: &[DataCandle]) -> usize {
+    candles
+        .iter()
+        .map(|c| {
+            let text = c.close.to_string();
+            text.split_once('.').map_or(0, |(_, d)| d.len())
+        })
+        .max()
+        .unwrap_or(0)
+        .min(6)
+}
+
+fn encode_png(pixels: &[u8], width: u32, height: u32) -> PocketResult<Vec<u8>> {
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(chart_error)?;
+    writer.write_image_data(pixels).map_err(chart_error)?;
+    writer.finish().map_err(chart_error)?;
+    Ok(png)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chart_markers() -> anyhow::Result<()> {
+        assert_eq!("BUY".parse::<MarkerKind>()?, MarkerKind::Call);
+        assert!("exit".parse::<MarkerKind>().is_err());
+        assert!(
+            ChartOptions {
+                width: 10,
+                ..Default::default()
+            }
+            .validate()
+            .is_err()
+        );
+
+        let time = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let candle = |minutes: i64, close: f64| DataCandle {
+            time: time + TimeDelta::minutes(minutes),
+            open: 1.1,
+            close,
+            high: close.max(1.1) + 0.0002,
+            low: close.min(1.1) - 0.0002,
+            received_at: None,
+            latency: None,
+        };
+        let candles = vec![candle(0, 1.1012), candle(1, 1.09871), candle(2, 1.1003)];
+        assert_eq!(price_decimals(&candles), 5);
+        assert!(render_chart(&[], &[], &ChartOptions::default()).is_err());
+
+        let markers = [ChartMarker::new(time, 1.1, MarkerKind::Put)];
+        let options = ChartOptions {
+            width: 200,
+            height: 100,
+            title: None,
+        };
+        // The candles are drawn even without the system fonts, the labels need them
+        if let Ok(png) = render_chart(&candles, &markers, &options) {
+            assert_eq!(&png[1..4], b"PNG");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,222 @@
+//! Dashboard of a running client: a static page served by the client showing its balance, open
+//! trades, subscriptions, queued orders and logs, updated over a websocket. The page has no
+//! external dependency, it works offline and without a frontend build.
+
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+use binary_options_tools_core::{
+    error::{BinaryOptionsResult, BinaryOptionsToolsError},
+    utils::tracing::{LogBroadcast, LogFilter},
+};
+use chrono::{DateTime, Utc};
+use futures_util::{SinkExt, Stream, StreamExt};
+use serde::Serialize;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    task::JoinHandle,
+};
+use tokio_tungstenite::{accept_async, tungstenite::Message};
+use tracing::{debug, info, warn};
+
+use super::{
+    access::{AccessControl, Operation},
+    error::{PocketOptionError, PocketResult},
+    health::{HealthReport, api_key},
+    pocket_client::PocketOption,
+    price_orders::PriceOrder,
+    scheduled_orders::ScheduledOrder,
+    types::{order::Deal, subscriptions::SubscriptionStatus},
+};
+
+const PAGE: &str = include_str!("dashboard.html");
+
+/// How often the state of the client is pushed to the open dashboards.
+pub const DASHBOARD_REFRESH: Duration = Duration::from_secs(1);
+/// Number of closed trades shown, the most recent ones.
+pub const DASHBOARD_CLOSED_DEALS: usize = 20;
+
+/// State of the client shown by the dashboard, also served as JSON on `/api/state`.
+#[derive(Debug, Clone, Serialize)]
+pub struct DashboardState {
+    pub time: DateTime<Utc>,
+    pub demo: bool,
+    pub balance: f64,
+    pub currency: Option<String>,
+    pub health: HealthReport,
+    pub opened_deals: Vec<Deal>,
+    /// Last closed trades, the most recent first.
+    pub closed_deals: Vec<Deal>,
+    pub subscriptions: SubscriptionStatus,
+    pub price_orders: Vec<PriceOrder>,
+    pub scheduled_orders: Vec<ScheduledOrder>,
+}
+
+impl DashboardState {
+    pub async fn collect(client: &PocketOption) -> Self {
+        let mut closed_deals = client.get_closed_deals().await;
+        closed_deals.sort_by(|a, b| b.close_timestamp.cmp(&a.close_timestamp));
+        closed_deals.truncate(DASHBOARD_CLOSED_DEALS);
+        Self {
+            time: Utc::now(),
+            demo: client.is_demo().await,
+            balance: client.get_balance().await.balance,
+            currency: client.account_currency().await,
+            health: client.health().await,
+            opened_deals: client.get_opened_deals().await,
+            closed_deals,
+            subscriptions: client.subscription_status(),
+            price_orders: client.price_orders(),
+            scheduled_orders: client.scheduled_orders(),
+        }
+    }
+}
+
+/// Starts the dashboard on `addr`: `GET /` serves the page, `GET /api/state` the `DashboardState`
+/// as JSON and `/ws` the websocket pushing the state every second and the records of `logs` as they
+/// are logged. With `access` every request must carry an API key allowed to read, in an
+/// `Authorization: Bearer <key>` or `X-Api-Key: <key>` header or in the `key` query parameter (the
+/// one the page forwards to its requests).
+pub async fn serve_dashboard(
+    client: PocketOption,
+    addr: SocketAddr,
+    logs: Option<LogBroadcast>,
+    access: Option<Arc<AccessControl>>,
+) -> PocketResult<JoinHandle<()>> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .map_err(BinaryOptionsToolsError::from)?;
+    info!(target: "Dashboard", "Dashboard listening on http://{addr}");
+    Ok(tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, peer)) => {
+                    debug!(target: "Dashboard", "Dashboard request from {peer}");
+                    let client = client.clone();
+                    let logs = logs.clone();
+                    let access = access.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = respond(stream, client, logs, access).await {
+                            warn!(target: "Dashboard", "Error answering dashboard request, {e}");
+                        }
+                    });
+                }
+                Err(e) => warn!(target: "Dashboard", "Error accepting connection, {e}"),
+            }
+        }
+    }))
+}
+
+/// Path and `key` query parameter of the request line.
+fn request_target(request: &str) -> (&str, Option<&str>) {
+    let target = request.split_whitespace().nth(1).unwrap_or("/");
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let key = query
+        .split('&')
+        .find_map(|param| param.strip_prefix("key="))
+        .filter(|key| !key.is_empty());
+    (path, key)
+}
+
+async fn respond(
+    mut stream: TcpStream,
+    client: PocketOption,
+    logs: Option<LogBroadcast>,
+    access: Option<Arc<AccessControl>>,
+) -> std::io::Result<()> {
+    let mut buf = [0u8; 4096];
+    // Only peeked, the websocket handshake reads the request again
+    let read = stream.peek(&mut buf).await?;
+    let request = String::from_utf8_lossy(&buf[..read]).into_owned();
+    let (path, key) = request_target(&request);
+    let key = api_key(&request).or(key);
+    let denied = match access.map(|a| a.authorize(key, &Operation::Read)) {
+        Some(Err(PocketOptionError::Unauthorized(_))) => Some("401 Unauthorized"),
+        Some(Err(_)) => Some("403 Forbidden"),
+        _ => None,
+    };
+    if denied.is_none() && request.starts_with("GET") && path == "/ws" {
+        let ws = accept_async(stream).await.map_err(std::io::Error::other)?;
+        stream_updates(ws, client, logs).await;
+        return Ok(());
+    }
+    stream.read_exact(&mut buf[..read]).await?;
+    let (status, content_type, body) = match (denied, path) {
+        (Some(status), _) => (status, "text/plain", String::new()),
+        _ if !request.starts_with("GET") => ("405 Method Not Allowed", "text/plain", String::new()),
+        (None, "/" | "/index.html") => ("200 OK", "text/html; charset=utf-8", PAGE.to_string()),
+        (None, "/api/state") => {
+            let state = DashboardState::collect(&client).await;
+            ("200 OK", "application/json", serde_json::to_string(&state)?)
+        }
+        (None, _) => ("404 Not Found", "text/plain", String::new()),
+    };
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+async fn next_log(
+    logs: &mut Option<impl Stream<Item = BinaryOptionsResult<String>> + Unpin>,
+) -> Option<String> {
+    match logs {
+        Some(logs) => logs.next().await.and_then(Result::ok),
+        None => std::future::pending().await,
+    }
+}
+
+/// Pushes the state of the client and its logs to a dashboard until it disconnects.
+async fn stream_updates(
+    ws: tokio_tungstenite::WebSocketStream<TcpStream>,
+    client: PocketOption,
+    logs: Option<LogBroadcast>,
+) {
+    let (mut sink, mut incoming) = ws.split();
+    let logs = logs.map(|logs| logs.subscribe(LogFilter::default(), None));
+    let mut records = logs.as_ref().map(|logs| logs.to_stream());
+    let mut refresh = tokio::time::interval(DASHBOARD_REFRESH);
+    loop {
+        let message = tokio::select! {
+            _ = refresh.tick() => {
+                let state = DashboardState::collect(&client).await;
+                match serde_json::to_string(&state) {
+                    Ok(state) => format!(r#"{{"type":"state","data":{state}}}"#),
+                    Err(e) => {
+                        warn!(target: "Dashboard", "Failed to serialize the state of the client, {e}");
+                        continue;
+                    }
+                }
+            }
+            Some(record) = next_log(&mut records) => format!(r#"{{"type":"log","data":{record}}}"#),
+            message = incoming.next() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => continue,
+            },
+        };
+        if sink.send(Message::text(message)).await.is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_target() {
+        assert_eq!(request_target("GET / HTTP/1.1\r\n"), ("/", None));
+        assert_eq!(
+            request_target("GET /ws?key=abc HTTP/1.1\r\nHost: localhost\r\n"),
+            ("/ws", Some("abc"))
+        );
+        assert_eq!(
+            request_target("GET /api/state?theme=dark&key= HTTP/1.1\r\n"),
+            ("/api/state", None)
+        );
+        assert!(PAGE.contains("/api/state") && PAGE.contains("/ws"));
+    }
+}
@@ -2,9 +2,17 @@ use std::error::Error;
 use std::string::FromUtf8Error;
 
 use super::types::order::PocketMessageFail;
+use super::types::region::MismatchKind;
+use super::utils::history::format_ranges;
+use super::ws::session::AuthFailure;
 use super::{parser::message::WebSocketMessage, types::info::MessageInfo};
-use binary_options_tools_core::error::BinaryOptionsToolsError;
+use binary_options_tools_core::{
+    error::BinaryOptionsToolsError,
+    utils::locale::{Catalog, Locale, translate},
+};
+use chrono::{DateTime, Utc};
 use thiserror::Error;
+use uuid::Uuid;
 // use tokio_tungstenite::tungstenite::Error as TungsteniteError;
 // use tokio_tungstenite::tungstenite::{http, Message};
 
@@ -54,6 +62,73 @@ pub enum PocketOptionError {
     Unallowed(String),
     #[error("Error sending request, {0}")]
     WebsocketMessageSendingError(#[from] PocketMessageFail),
+    #[error("Trade rejected by risk rule '{rule}', {reason}")]
+    RiskRuleViolation { rule: String, reason: String },
+    #[error("Invalid stake of {amount} in {currency}, {reason}")]
+    InvalidStake {
+        amount: f64,
+        currency: String,
+        reason: String,
+    },
+    #[error("Template '{template}' can't be executed, {reason}")]
+    TemplateRejected { template: String, reason: String },
+    #[error(
+        "Invalid timeframe '{0}', the supported periods are 5s, 10s, 15s, 30s, 1m, 2m, 3m, 5m, 10m, 15m, 30m, 1h, 4h and 1d"
+    )]
+    InvalidTimeframe(String),
+    #[error("Client is in observer mode, {0} is disabled")]
+    ObserverMode(String),
+    #[error("Unauthorized, {0}")]
+    Unauthorized(String),
+    #[error("Authentication failed, the session is {failure}, {reason}")]
+    AuthenticationError {
+        failure: AuthFailure,
+        reason: String,
+    },
+    #[error("{kind} mismatch, {reason}, hints: {}", .hints.join("; "))]
+    RegionMismatch {
+        kind: MismatchKind,
+        reason: String,
+        hints: Vec<String>,
+    },
+    #[error("Permission denied for the '{role}' role, {reason}")]
+    PermissionDenied { role: String, reason: String },
+    #[error("Asset '{0}' was not found in the list of assets sent by the server")]
+    AssetNotFound(String),
+    #[error(
+        "Received {received} of the {expected} candles requested for '{asset}', missing ranges: {}",
+        format_ranges(.missing)
+    )]
+    IncompleteCandles {
+        asset: String,
+        expected: usize,
+        received: usize,
+        missing: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+    },
+    #[error("The result of trade '{trade_id}' wasn't received within {seconds} seconds")]
+    TradeTimeout { trade_id: Uuid, seconds: f64 },
+    #[error(
+        "Trade on '{asset}' rejected, its payout ({}) is under the minimum of {min_payout}%",
+        format_payout(.payout)
+    )]
+    PayoutTooLow {
+        asset: String,
+        /// Payout of the asset when the order was about to be sent, `None` if unknown.
+        payout: Option<i32>,
+        min_payout: i32,
+    },
+    #[error("Trade on '{0}' rejected, the market of the asset is closed")]
+    AssetClosed(String),
+    #[error(
+        "Only one leg of the straddle was placed (trade '{placed}'), the other failed: {reason}"
+    )]
+    StraddleIncomplete { placed: Uuid, reason: String },
+    #[error("Failed to render the chart, {0}")]
+    ChartError(String),
+    #[error("Failed to export the candles, {0}")]
+    ExportError(String),
+    #[error("Failed to convert the results to Arrow, {0}")]
+    ArrowError(String),
     #[error("Expected the data to be non-empty for type '{0}'")]
     EmptyArrayError(String),
     #[error("General compiling error: {0}")]
@@ -62,6 +137,258 @@ pub enum PocketOptionError {
 
 pub type PocketResult<T> = Result<T, PocketOptionError>;
 
+fn format_payout(payout: &Option<i32>) -> String {
+    payout.map_or_else(|| "unknown".to_string(), |payout| format!("{payout}%"))
+}
+
+/// Translations of the most common messages of `PocketOptionError`, the English ones are the
+/// messages of the variants.
+const CATALOG: &Catalog = &[
+    (
+        "ssid",
+        [
+            "Failed to parse SSID: {0}",
+            "No se pudo leer el SSID: {0}",
+            "Falha ao ler o SSID: {0}",
+            "Не удалось разобрать SSID: {0}",
+        ],
+    ),
+    (
+        "connection_closed",
+        [
+            "Websocket connection was closed by the server, {0}",
+            "El servidor cerró la conexión websocket, {0}",
+            "A conexão websocket foi encerrada pelo servidor, {0}",
+            "Сервер закрыл websocket-соединение, {0}",
+        ],
+    ),
+    (
+        "connection_attempts",
+        [
+            "Failed to connect to websocket server after multiple attempts, {0}",
+            "No se pudo conectar al servidor websocket tras varios intentos, {0}",
+            "Falha ao conectar ao servidor websocket após várias tentativas, {0}",
+            "Не удалось подключиться к websocket-серверу после нескольких попыток, {0}",
+        ],
+    ),
+    (
+        "unallowed",
+        [
+            "Unallowed operation, {0}",
+            "Operación no permitida, {0}",
+            "Operação não permitida, {0}",
+            "Недопустимая операция, {0}",
+        ],
+    ),
+    (
+        "request_failed",
+        [
+            "Error sending request, {0}",
+            "Error al enviar la solicitud, {0}",
+            "Erro ao enviar a solicitação, {0}",
+            "Ошибка при отправке запроса, {0}",
+        ],
+    ),
+    (
+        "risk_rule",
+        [
+            "Trade rejected by risk rule '{0}', {1}",
+            "Operación rechazada por la regla de riesgo '{0}', {1}",
+            "Operação rejeitada pela regra de risco '{0}', {1}",
+            "Сделка отклонена правилом риска '{0}', {1}",
+        ],
+    ),
+    (
+        "invalid_stake",
+        [
+            "Invalid stake of {0} in {1}, {2}",
+            "Monto de inversión no válido de {0} en {1}, {2}",
+            "Valor de investimento inválido de {0} em {1}, {2}",
+            "Недопустимая ставка {0} в {1}, {2}",
+        ],
+    ),
+    (
+        "template_rejected",
+        [
+            "Template '{0}' can't be executed, {1}",
+            "La plantilla '{0}' no se puede ejecutar, {1}",
+            "O modelo '{0}' não pode ser executado, {1}",
+            "Шаблон '{0}' не может быть выполнен, {1}",
+        ],
+    ),
+    (
+        "invalid_timeframe",
+        [
+            "Invalid timeframe '{0}', the supported periods are 5s, 10s, 15s, 30s, 1m, 2m, 3m, 5m, 10m, 15m, 30m, 1h, 4h and 1d",
+            "Periodo '{0}' no válido, los periodos admitidos son 5s, 10s, 15s, 30s, 1m, 2m, 3m, 5m, 10m, 15m, 30m, 1h, 4h y 1d",
+            "Período '{0}' inválido, os períodos suportados são 5s, 10s, 15s, 30s, 1m, 2m, 3m, 5m, 10m, 15m, 30m, 1h, 4h e 1d",
+            "Недопустимый таймфрейм '{0}', поддерживаются периоды 5s, 10s, 15s, 30s, 1m, 2m, 3m, 5m, 10m, 15m, 30m, 1h, 4h и 1d",
+        ],
+    ),
+    (
+        "asset_not_found",
+        [
+            "Asset '{0}' was not found in the list of assets sent by the server",
+            "El activo '{0}' no está en la lista de activos enviada por el servidor",
+            "O ativo '{0}' não está na lista de ativos enviada pelo servidor",
+            "Актив '{0}' не найден в списке активов, присланном сервером",
+        ],
+    ),
+    (
+        "incomplete_candles",
+        [
+            "Received {0} of the {1} candles requested for '{2}', missing ranges: {3}",
+            "Se recibieron {0} de las {1} velas solicitadas para '{2}', rangos faltantes: {3}",
+            "Foram recebidas {0} das {1} velas solicitadas para '{2}', intervalos ausentes: {3}",
+            "Получено {0} из {1} запрошенных свечей для '{2}', отсутствующие интервалы: {3}",
+        ],
+    ),
+    (
+        "trade_timeout",
+        [
+            "The result of trade '{0}' wasn't received within {1} seconds",
+            "El resultado de la operación '{0}' no se recibió en {1} segundos",
+            "O resultado da operação '{0}' não foi recebido em {1} segundos",
+            "Результат сделки '{0}' не получен за {1} секунд",
+        ],
+    ),
+    (
+        "payout_too_low",
+        [
+            "Trade on '{0}' rejected, its payout ({1}) is under the minimum of {2}%",
+            "Operación en '{0}' rechazada, su pago ({1}) es inferior al mínimo de {2}%",
+            "Operação em '{0}' rejeitada, o pagamento ({1}) está abaixo do mínimo de {2}%",
+            "Сделка по '{0}' отклонена, выплата ({1}) ниже минимума {2}%",
+        ],
+    ),
+    (
+        "asset_closed",
+        [
+            "Trade on '{0}' rejected, the market of the asset is closed",
+            "Operación en '{0}' rechazada, el mercado del activo está cerrado",
+            "Operação em '{0}' rejeitada, o mercado do ativo está fechado",
+            "Сделка по '{0}' отклонена, рынок актива закрыт",
+        ],
+    ),
+    (
+        "straddle_incomplete",
+        [
+            "Only one leg of the straddle was placed (trade '{0}'), the other failed: {1}",
+            "Solo se colocó una pata del straddle (operación '{0}'), la otra falló: {1}",
+            "Apenas uma perna do straddle foi colocada (operação '{0}'), a outra falhou: {1}",
+            "Размещена только одна нога стрэддла (сделка '{0}'), другая не удалась: {1}",
+        ],
+    ),
+    (
+        "observer_mode",
+        [
+            "Client is in observer mode, {0} is disabled",
+            "El cliente está en modo observador, {0} está deshabilitado",
+            "O cliente está no modo observador, {0} está desativado",
+            "Клиент находится в режиме наблюдателя, {0} отключено",
+        ],
+    ),
+    (
+        "unauthorized",
+        [
+            "Unauthorized, {0}",
+            "No autorizado, {0}",
+            "Não autorizado, {0}",
+            "Нет доступа, {0}",
+        ],
+    ),
+    (
+        "permission_denied",
+        [
+            "Permission denied for the '{0}' role, {1}",
+            "Permiso denegado para el rol '{0}', {1}",
+            "Permissão negada para a função '{0}', {1}",
+            "Доступ запрещён для роли '{0}', {1}",
+        ],
+    ),
+];
+
+impl PocketOptionError {
+    /// Message of the error in `locale`, the messages missing from the catalog are in English.
+    /// The details coming from the server or from other libraries aren't translated.
+    pub fn localized(&self, locale: Locale) -> String {
+        if locale == Locale::En {
+            return self.to_string();
+        }
+        let translated = match self {
+            Self::BinaryOptionsToolsError(e) => return e.localized(locale),
+            Self::SsidParsingError(e) => translate(CATALOG, "ssid", locale, &[e]),
+            Self::WebsocketConnectionClosed(e) => {
+                translate(CATALOG, "connection_closed", locale, &[e])
+            }
+            Self::WebsocketMultipleAttemptsConnectionError(e) => {
+                translate(CATALOG, "connection_attempts", locale, &[e])
+            }
+            Self::Unallowed(reason) => translate(CATALOG, "unallowed", locale, &[reason]),
+            Self::WebsocketMessageSendingError(fail) => {
+                translate(CATALOG, "request_failed", locale, &[fail])
+            }
+            Self::RiskRuleViolation { rule, reason } => {
+                translate(CATALOG, "risk_rule", locale, &[rule, reason])
+            }
+            Self::InvalidStake {
+                amount,
+                currency,
+                reason,
+            } => translate(
+                CATALOG,
+                "invalid_stake",
+                locale,
+                &[amount, currency, reason],
+            ),
+            Self::TemplateRejected { template, reason } => {
+                translate(CATALOG, "template_rejected", locale, &[template, reason])
+            }
+            Self::InvalidTimeframe(period) => {
+                translate(CATALOG, "invalid_timeframe", locale, &[period])
+            }
+            Self::AssetNotFound(asset) => translate(CATALOG, "asset_not_found", locale, &[asset]),
+            Self::IncompleteCandles {
+                asset,
+                expected,
+                received,
+                missing,
+            } => translate(
+                CATALOG,
+                "incomplete_candles",
+                locale,
+                &[received, expected, asset, &format_ranges(missing)],
+            ),
+            Self::TradeTimeout { trade_id, seconds } => {
+                translate(CATALOG, "trade_timeout", locale, &[trade_id, seconds])
+            }
+            Self::PayoutTooLow {
+                asset,
+                payout,
+                min_payout,
+            } => translate(
+                CATALOG,
+                "payout_too_low",
+                locale,
+                &[asset, &format_payout(payout), min_payout],
+            ),
+            Self::AssetClosed(asset) => translate(CATALOG, "asset_closed", locale, &[asset]),
+            Self::StraddleIncomplete { placed, reason } => {
+                translate(CATALOG, "straddle_incomplete", locale, &[placed, reason])
+            }
+            Self::ObserverMode(operation) => {
+                translate(CATALOG, "observer_mode", locale, &[operation])
+            }
+            Self::Unauthorized(reason) => translate(CATALOG, "unauthorized", locale, &[reason]),
+            Self::PermissionDenied { role, reason } => {
+                translate(CATALOG, "permission_denied", locale, &[role, reason])
+            }
+            _ => None,
+        };
+        translated.unwrap_or_else(|| self.to_string())
+    }
+}
+
 impl Error for WebSocketMessage {}
 impl Error for MessageInfo {}
 impl Error for PocketMessageFail {}
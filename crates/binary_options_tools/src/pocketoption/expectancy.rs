@@ -0,0 +1,269 @@
+//! Measured edge of the closed trades of the journal: expectancy, Sharpe-like ratio and confidence
+//! interval of the return per unit staked, over the trades matching a `TradeFilter`, so the stake
+//! can be adapted to the edge a strategy actually showed.
+
+use chrono::{DateTime, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    error::{PocketOptionError, PocketResult},
+    journal::JournalEntry,
+};
+
+/// Confidence level of the intervals when none is given.
+pub const DEFAULT_CONFIDENCE: f64 = 0.95;
+
+/// Selection of the journal trades, every condition left to `None` matches all the trades.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TradeFilter {
+    pub asset: Option<String>,
+    /// Tag of the trades, e.g. the `template:<name>` tag of the trades of a template.
+    pub tag: Option<String>,
+    /// First hour (UTC, 0 to 23) of the time of day the trades were opened at.
+    pub from_hour: Option<u32>,
+    /// Hour (UTC, 1 to 24) the time of day ends at, excluded. A range ending before it starts
+    /// wraps around midnight (e.g. from 22 to 2).
+    pub to_hour: Option<u32>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl TradeFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn asset(mut self, asset: impl ToString) -> Self {
+        self.asset = Some(asset.to_string());
+        self
+    }
+
+    pub fn tag(mut self, tag: impl ToString) -> Self {
+        self.tag = Some(tag.to_string());
+        self
+    }
+
+    /// Only the trades opened between `from` (included) and `to` (excluded) hours, in UTC.
+    pub fn hours(mut self, from: u32, to: u32) -> Self {
+        self.from_hour = Some(from);
+        self.to_hour = Some(to);
+        self
+    }
+
+    pub fn since(mut self, since: DateTime<Utc>) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    pub fn until(mut self, until: DateTime<Utc>) -> Self {
+        self.until = Some(until);
+        self
+    }
+
+    pub fn validate(&self) -> PocketResult<()> {
+        if let Some(hour) = self.from_hour.filter(|h| *h > 23) {
+            return Err(PocketOptionError::Unallowed(format!(
+                "the first hour must be between 0 and 23, got {hour}"
+            )));
+        }
+        if let Some(hour) = self.to_hour.filter(|h| *h == 0 || *h > 24) {
+            return Err(PocketOptionError::Unallowed(format!(
+                "the last hour must be between 1 and 24, got {hour}"
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn matches(&self, entry: &JournalEntry) -> bool {
+        let hour = entry.opened_at.hour();
+        let in_hours = match (self.from_hour, self.to_hour) {
+            (None, None) => true,
+            (from, to) => {
+                let (from, to) = (from.unwrap_or(0), to.unwrap_or(24));
+                if from < to {
+                    (from..to).contains(&hour)
+                } else {
+                    hour >= from || hour < to
+                }
+            }
+        };
+        in_hours
+            && self.asset.as_ref().is_none_or(|a| *a == entry.asset)
+            && self.tag.as_ref().is_none_or(|tag| entry.tags.contains(tag))
+            && self.since.is_none_or(|since| entry.opened_at >= since)
+            && self.until.is_none_or(|until| entry.opened_at < until)
+    }
+}
+
+/// Edge measured over the closed trades matching a filter.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Expectancy {
+    pub trades: usize,
+    /// Wins over the trades that weren't a draw, 0 if there are none.
+    pub win_rate: f64,
+    /// Mean profit of the winning trades.
+    pub average_win: f64,
+    /// Mean loss of the losing trades, as a positive amount.
+    pub average_loss: f64,
+    /// Mean profit per trade, in the account currency.
+    pub expectancy: f64,
+    /// Mean profit per unit staked, e.g. 0.05 for 5 cents per dollar staked.
+    pub return_per_unit: f64,
+    /// Standard deviation of the returns per unit staked, `None` with less than 2 trades.
+    pub std_dev: Option<f64>,
+    /// Return per unit over its standard deviation, `None` without a standard deviation.
+    pub sharpe: Option<f64>,
+    /// Confidence level of the interval, e.g. 0.95.
+    pub confidence: f64,
+    /// Confidence interval of the return per unit staked (normal approximation), `None` with less
+    /// than 2 trades.
+    pub interval: Option<(f64, f64)>,
+}
+
+impl Expectancy {
+    /// Measures the edge of the closed trades of `entries` matching `filter`, the open trades are
+    /// ignored. `confidence` is the level of the interval, between 0 and 1 excluded.
+    pub fn new<'a>(
+        entries: impl IntoIterator<Item = &'a JournalEntry>,
+        filter: &TradeFilter,
+        confidence: f64,
+    ) -> PocketResult<Self> {
+        filter.validate()?;
+        if !(confidence > 0.0 && confidence < 1.0) {
+            return Err(PocketOptionError::Unallowed(format!(
+                "the confidence level must be between 0 and 1, got {confidence}"
+            )));
+        }
+        let trades: Vec<(f64, f64)> = entries
+            .into_iter()
+            .filter(|e| filter.matches(e))
+            .filter_map(|e| e.profit.map(|profit| (profit, e.amount)))
+            .collect();
+        let mut expectancy = Self {
+            trades: trades.len(),
+            confidence,
+            ..Default::default()
+        };
+        if trades.is_empty() {
+            return Ok(expectancy);
+        }
+
+        let wins: Vec<f64> = trades.iter().map(|t| t.0).filter(|p| *p > 0.0).collect();
+        let losses: Vec<f64> = trades.iter().map(|t| -t.0).filter(|l| *l > 0.0).collect();
+        if !wins.is_empty() || !losses.is_empty() {
+            expectancy.win_rate = wins.len() as f64 / (wins.len() + losses.len()) as f64;
+        }
+        expectancy.average_win = mean(&wins).unwrap_or_default();
+        expectancy.average_loss = mean(&losses).unwrap_or_default();
+        let profits: Vec<f64> = trades.iter().map(|t| t.0).collect();
+        expectancy.expectancy = mean(&profits).unwrap_or_default();
+
+        let returns: Vec<f64> = trades
+            .iter()
+            .filter(|(_, amount)| *amount > 0.0)
+            .map(|(profit, amount)| profit / amount)
+            .collect();
+        let Some(return_per_unit) = mean(&returns) else {
+            return Ok(expectancy);
+        };
+        expectancy.return_per_unit = return_per_unit;
+        if returns.len() > 1 {
+            let n = returns.len() as f64;
+            let variance = returns
+                .iter()
+                .map(|r| (r - return_per_unit).powi(2))
+                .sum::<f64>()
+                / (n - 1.0);
+            let std_dev = variance.sqrt();
+            let margin = normal_quantile(0.5 + confidence / 2.0) * std_dev / n.sqrt();
+            expectancy.std_dev = Some(std_dev);
+            expectancy.sharpe = (std_dev > 0.0).then(|| return_per_unit / std_dev);
+            expectancy.interval = Some((return_per_unit - margin, return_per_unit + margin));
+        }
+        Ok(expectancy)
+    }
+
+    /// Returns true if the whole confidence interval is above 0, so the edge is unlikely to be luck.
+    pub fn is_significant(&self) -> bool {
+        self.interval.is_some_and(|(low, _)| low > 0.0)
+    }
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+/// Quantile of the standard normal distribution for `p` in (0, 1), with the rational
+/// approximation 26.2.23 of Abramowitz and Stegun (absolute error under 4.5e-4).
+fn normal_quantile(p: f64) -> f64 {
+    let tail = |q: f64| {
+        let t = (-2.0 * q.ln()).sqrt();
+        t - (2.515517 + 0.802853 * t + 0.010328 * t * t)
+            / (1.0 + 1.432788 * t + 0.189269 * t * t + 0.001308 * t * t * t)
+    };
+    if p < 0.5 { -tail(p) } else { tail(1.0 - p) }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::pocketoption::types::order::Action;
+
+    fn entry(asset: &str, hour: u32, profit: Option<f64>) -> JournalEntry {
+        JournalEntry {
+            trade_id: Uuid::new_v4(),
+            request_id: None,
+            uid: 0,
+            asset: asset.into(),
+            action: Action::Call,
+            amount: 2.0,
+            time: 60,
+            open_price: 1.0,
+            opened_at: Utc.with_ymd_and_hms(2025, 1, 1, hour, 0, 0).unwrap(),
+            group: None,
+            tags: vec!["strategy:trend".into()],
+            profit,
+            closed_at: None,
+            context: None,
+        }
+    }
+
+    #[test]
+    fn test_expectancy() -> anyhow::Result<()> {
+        assert!((normal_quantile(0.975) - 1.96).abs() < 1e-3);
+
+        let mut entries: Vec<JournalEntry> = (0..30)
+            .map(|i| entry("EURUSD_otc", 10, Some(if i % 3 == 0 { -2.0 } else { 1.8 })))
+            .collect();
+        entries.push(entry("EURUSD_otc", 10, None));
+        entries.push(entry("EURUSD_otc", 23, Some(-2.0)));
+        entries.push(entry("GBPUSD_otc", 10, Some(-2.0)));
+
+        let filter = TradeFilter::new()
+            .asset("EURUSD_otc")
+            .tag("strategy:trend")
+            .hours(8, 12);
+        let measured = Expectancy::new(&entries, &filter, DEFAULT_CONFIDENCE)?;
+        assert_eq!(measured.trades, 30);
+        assert!((measured.win_rate - 2.0 / 3.0).abs() < 1e-9);
+        assert!((measured.expectancy - (1.8 * 20.0 - 2.0 * 10.0) / 30.0).abs() < 1e-9);
+        assert!((measured.return_per_unit - measured.expectancy / 2.0).abs() < 1e-9);
+        let (low, high) = measured.interval.unwrap();
+        assert!(low < measured.return_per_unit && measured.return_per_unit < high);
+        assert!(measured.sharpe.unwrap() > 0.0);
+
+        // The range wraps around midnight
+        let night = Expectancy::new(&entries, &TradeFilter::new().hours(22, 2), 0.9)?;
+        assert_eq!(night.trades, 1);
+        assert_eq!(night.interval, None);
+        assert!(!night.is_significant());
+
+        assert!(Expectancy::new(&entries, &TradeFilter::new(), 1.0).is_err());
+        assert!(Expectancy::new(&entries, &TradeFilter::new().hours(0, 25), 0.9).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,218 @@
+//! A/B comparison of the executions of the same signals on two accounts with different settings
+//! (expiry, stake plan...), built on the journals of both clients: the trades of an experiment are
+//! tagged with its name, their arm and the signal they were placed for.
+
+use std::collections::{BTreeSet, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use super::journal::{JournalEntry, JournalStats};
+
+/// Tag of the trades of the experiment `name`.
+pub fn experiment_tag(name: &str) -> String {
+    format!("experiment:{name}")
+}
+
+/// Tag of the trades of the arm `arm`.
+pub fn arm_tag(arm: &str) -> String {
+    format!("arm:{arm}")
+}
+
+/// Tag of the trades placed for the signal `signal`.
+pub fn signal_tag(signal: &str) -> String {
+    format!("signal:{signal}")
+}
+
+/// Tags of a trade placed for `signal` on the arm `arm` of the experiment `name`.
+pub fn experiment_tags(name: &str, arm: &str, signal: &str) -> Vec<String> {
+    vec![experiment_tag(name), arm_tag(arm), signal_tag(signal)]
+}
+
+/// Results of one arm of the experiment.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ArmReport {
+    pub arm: String,
+    /// Statistics of the closed trades of the arm.
+    pub stats: JournalStats,
+    /// Number of signals the arm placed a trade for.
+    pub signals: usize,
+    /// Trades of the arm that aren't closed yet.
+    pub open_trades: usize,
+}
+
+/// Comparison of the signals both arms have a closed trade for.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PairedComparison {
+    pub signals: usize,
+    /// Signals where the profit of `a` was higher than the one of `b`.
+    pub a_better: usize,
+    pub b_better: usize,
+    pub ties: usize,
+    /// Total profit of `a` minus the one of `b`, over the paired signals.
+    pub profit_difference: f64,
+    /// Mean of the profit differences (`a` minus `b`) per signal, 0 without paired signals.
+    pub mean_difference: f64,
+    /// Standard error of `mean_difference`, `None` with less than 2 paired signals.
+    pub standard_error: Option<f64>,
+}
+
+/// Comparative report of an A/B experiment.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ComparisonReport {
+    pub experiment: String,
+    pub a: ArmReport,
+    pub b: ArmReport,
+    pub paired: PairedComparison,
+    /// Signals only one of the arms placed a trade for (rejected or failed on the other one).
+    pub unmatched_signals: Vec<String>,
+}
+
+impl ComparisonReport {
+    /// Builds the report of the experiment `name` from the journal entries of both accounts, the
+    /// entries of other experiments and of other arms are ignored.
+    pub fn new(name: &str, arms: [(&str, &[JournalEntry]); 2]) -> Self {
+        let [(a_name, a_entries), (b_name, b_entries)] = arms;
+        let a = ArmTrades::new(name, a_name, a_entries);
+        let b = ArmTrades::new(name, b_name, b_entries);
+
+        let mut paired = PairedComparison::default();
+        let mut differences = Vec::new();
+        for (signal, a_profit) in &a.profits {
+            let Some(b_profit) = b.profits.get(signal) else {
+                continue;
+            };
+            let difference = a_profit - b_profit;
+            match difference {
+                d if d > 0.0 => paired.a_better += 1,
+                d if d < 0.0 => paired.b_better += 1,
+                _ => paired.ties += 1,
+            }
+            differences.push(difference);
+        }
+        paired.signals = differences.len();
+        paired.profit_difference = differences.iter().sum();
+        if !differences.is_empty() {
+            paired.mean_difference = paired.profit_difference / differences.len() as f64;
+        }
+        if differences.len() > 1 {
+            let n = differences.len() as f64;
+            let variance = differences
+                .iter()
+                .map(|d| (d - paired.mean_difference).powi(2))
+                .sum::<f64>()
+                / (n - 1.0);
+            paired.standard_error = Some((variance / n).sqrt());
+        }
+        let unmatched_signals = a
+            .signals
+            .symmetric_difference(&b.signals)
+            .cloned()
+            .collect();
+
+        Self {
+            experiment: name.to_string(),
+            a: a.report,
+            b: b.report,
+            paired,
+            unmatched_signals,
+        }
+    }
+}
+
+/// Trades of one arm, with the profit of each signal.
+struct ArmTrades {
+    report: ArmReport,
+    signals: BTreeSet<String>,
+    profits: HashMap<String, f64>,
+}
+
+impl ArmTrades {
+    fn new(experiment: &str, arm: &str, entries: &[JournalEntry]) -> Self {
+        let (experiment, arm_tag) = (experiment_tag(experiment), arm_tag(arm));
+        let entries: Vec<&JournalEntry> = entries
+            .iter()
+            .filter(|e| e.tags.contains(&experiment) && e.tags.contains(&arm_tag))
+            .collect();
+        let mut signals = BTreeSet::new();
+        let mut profits = HashMap::new();
+        for entry in &entries {
+            let Some(signal) = entry.tags.iter().find_map(|t| t.strip_prefix("signal:")) else {
+                continue;
+            };
+            signals.insert(signal.to_string());
+            if let Some(profit) = entry.profit {
+                *profits.entry(signal.to_string()).or_insert(0.0) += profit;
+            }
+        }
+        Self {
+            report: ArmReport {
+                arm: arm.to_string(),
+                stats: JournalStats::new(entries.iter().copied()),
+                signals: signals.len(),
+                open_trades: entries.iter().filter(|e| !e.is_closed()).count(),
+            },
+            signals,
+            profits,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::pocketoption::types::order::Action;
+
+    fn entry(arm: &str, signal: &str, amount: f64, profit: Option<f64>) -> JournalEntry {
+        JournalEntry {
+            trade_id: Uuid::new_v4(),
+            request_id: None,
+            uid: 0,
+            asset: "EURUSD_otc".into(),
+            action: Action::Call,
+            amount,
+            time: 60,
+            open_price: 1.0,
+            opened_at: Utc::now(),
+            group: None,
+            tags: experiment_tags("expiry", arm, signal),
+            profit,
+            closed_at: None,
+            context: None,
+        }
+    }
+
+    #[test]
+    fn test_comparison_report() {
+        let a = vec![
+            entry("60s", "1", 1.0, Some(0.9)),
+            entry("60s", "2", 1.0, Some(-1.0)),
+            entry("60s", "3", 1.0, Some(0.9)),
+            entry("60s", "4", 1.0, None),
+        ];
+        let mut b = vec![
+            entry("120s", "1", 1.0, Some(-1.0)),
+            entry("120s", "2", 1.0, Some(0.9)),
+            entry("120s", "3", 1.0, Some(0.9)),
+        ];
+        // Trades of another experiment are ignored
+        b.push(JournalEntry {
+            tags: experiment_tags("stake", "120s", "1"),
+            ..entry("120s", "1", 5.0, Some(4.5))
+        });
+
+        let report = ComparisonReport::new("expiry", [("60s", &a[..]), ("120s", &b[..])]);
+        assert_eq!(report.a.stats.trades, 3);
+        assert_eq!(report.a.open_trades, 1);
+        assert_eq!(report.a.signals, 4);
+        assert_eq!(report.b.stats.trades, 3);
+        assert_eq!(report.paired.signals, 3);
+        let paired = &report.paired;
+        assert_eq!((paired.a_better, paired.b_better, paired.ties), (1, 1, 1));
+        assert!(paired.profit_difference.abs() < 1e-9);
+        assert!(paired.standard_error.is_some());
+        assert_eq!(report.unmatched_signals, vec!["4".to_string()]);
+    }
+}
@@ -0,0 +1,212 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "dashboard")]
+pub(crate) use gateway::api_key;
+#[cfg(feature = "http-gateway")]
+pub use gateway::{serve_healthcheck, serve_healthcheck_with_access};
+
+/// Snapshot of the client state returned by the healthcheck endpoint.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HealthReport {
+    pub connected: bool,
+    /// Seconds since the last websocket message was recieved.
+    pub last_message_age: Option<f64>,
+    pub open_trades: usize,
+    pub reconnections: u64,
+    /// Best-effort ticks dropped because the streams didn't keep up, see `StreamLaneStats`.
+    #[serde(default)]
+    pub shed_ticks: u64,
+    /// Subscribed assets whose message rate collapsed, see `FeedHealth`.
+    #[serde(default)]
+    pub collapsed_feeds: Vec<String>,
+}
+
+impl HealthReport {
+    pub fn is_healthy(&self) -> bool {
+        self.connected
+    }
+}
+
+#[cfg(feature = "http-gateway")]
+mod gateway {
+    use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+    use binary_options_tools_core::error::BinaryOptionsToolsError;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+        task::JoinHandle,
+        time::timeout,
+    };
+    use tracing::{debug, info, warn};
+
+    use crate::pocketoption::{
+        access::{AccessControl, Operation},
+        error::{PocketOptionError, PocketResult},
+        pocket_client::{PocketOption, WeakPocketOption},
+    };
+
+    /// Starts a minimal HTTP server on `addr` that answers every `GET` request with the
+    /// `HealthReport` of the client as JSON, with a `200` status code if the client is connected
+    /// and `503` otherwise, so it can be used for docker / kubernetes liveness probes. The server
+    /// doesn't keep the client alive, it stops and frees its port once the client was dropped.
+    pub async fn serve_healthcheck(
+        client: PocketOption,
+        addr: SocketAddr,
+    ) -> PocketResult<JoinHandle<()>> {
+        Ok(serve(bind(addr).await?, client.downgrade(), None))
+    }
+
+    /// Same as `serve_healthcheck`, but every request must carry an API key of `access` (in an
+    /// `Authorization: Bearer <key>` or `X-Api-Key: <key>` header), answered with `401` otherwise.
+    pub async fn serve_healthcheck_with_access(
+        client: PocketOption,
+        addr: SocketAddr,
+        access: Arc<AccessControl>,
+    ) -> PocketResult<JoinHandle<()>> {
+        Ok(serve(bind(addr).await?, client.downgrade(), Some(access)))
+    }
+
+    /// How often the server checks if the client was dropped while no request comes in.
+    const CLIENT_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+    async fn bind(addr: SocketAddr) -> PocketResult<TcpListener> {
+        let listener = TcpListener::bind(addr)
+            .await
+            .map_err(BinaryOptionsToolsError::from)?;
+        info!(target: "Healthcheck", "Healthcheck endpoint listening on {addr}");
+        Ok(listener)
+    }
+
+    fn serve(
+        listener: TcpListener,
+        client: WeakPocketOption,
+        access: Option<Arc<AccessControl>>,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            // The listener is dropped with the task, which frees the port
+            while !client.is_dropped() {
+                let Ok(accepted) = timeout(CLIENT_CHECK_INTERVAL, listener.accept()).await else {
+                    continue;
+                };
+                match accepted {
+                    Ok((stream, peer)) => {
+                        debug!(target: "Healthcheck", "Healthcheck request from {peer}");
+                        let client = client.clone();
+                        let access = access.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) = respond(stream, client, access).await {
+                                warn!(target: "Healthcheck", "Error answering healthcheck request, {e}");
+                            }
+                        });
+                    }
+                    Err(e) => warn!(target: "Healthcheck", "Error accepting connection, {e}"),
+                }
+            }
+            info!(target: "Healthcheck", "Client dropped, healthcheck endpoint stopped");
+        })
+    }
+
+    /// Value of the `Authorization: Bearer` or `X-Api-Key` header of the request.
+    pub(crate) fn api_key(request: &str) -> Option<&str> {
+        request.lines().skip(1).find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            let value = value.trim();
+            match name.trim().to_ascii_lowercase().as_str() {
+                "authorization" => value.strip_prefix("Bearer "),
+                "x-api-key" => Some(value),
+                _ => None,
+            }
+        })
+    }
+
+    async fn respond(
+        mut stream: TcpStream,
+        client: WeakPocketOption,
+        access: Option<Arc<AccessControl>>,
+    ) -> std::io::Result<()> {
+        let mut buf = [0u8; 4096];
+        let read = stream.read(&mut buf).await?;
+        let request = String::from_utf8_lossy(&buf[..read]);
+        let denied = match access.map(|a| a.authorize(api_key(&request), &Operation::Read)) {
+            Some(Err(PocketOptionError::Unauthorized(_))) => Some("401 Unauthorized"),
+            Some(Err(_)) => Some("403 Forbidden"),
+            _ => None,
+        };
+        let (status, body) = if let Some(status) = denied {
+            (status, String::new())
+        } else if request.starts_with("GET") {
+            let Some(client) = client.upgrade() else {
+                return write_response(stream, "503 Service Unavailable", "").await;
+            };
+            let report = client.health().await;
+            let status = if report.is_healthy() {
+                "200 OK"
+            } else {
+                "503 Service Unavailable"
+            };
+            (status, serde_json::to_string(&report)?)
+        } else {
+            ("405 Method Not Allowed", String::new())
+        };
+        write_response(stream, status, &body).await
+    }
+
+    async fn write_response(
+        mut stream: TcpStream,
+        status: &str,
+        body: &str,
+    ) -> std::io::Result<()> {
+        let response = format!(
+            "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        stream.write_all(response.as_bytes()).await?;
+        stream.shutdown().await
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::pocketoption::pocket_client::tests::mock_client;
+
+        /// Status line and body of the answer to a `GET` request.
+        async fn get(addr: SocketAddr) -> anyhow::Result<(String, String)> {
+            let mut stream = TcpStream::connect(addr).await?;
+            stream
+                .write_all(b"GET /health HTTP/1.1\r\nHost: localhost\r\n\r\n")
+                .await?;
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await?;
+            let (head, body) = response.split_once("\r\n\r\n").unwrap_or((&response, ""));
+            let status = head.lines().next().unwrap_or_default();
+            Ok((status.to_string(), body.to_string()))
+        }
+
+        #[tokio::test]
+        async fn test_healthcheck_report() -> anyhow::Result<()> {
+            let client = mock_client().await?;
+            let listener = TcpListener::bind("127.0.0.1:0").await?;
+            let addr = listener.local_addr()?;
+            let handle = serve(listener, client.downgrade(), None);
+
+            let (status, body) = get(addr).await?;
+            assert_eq!(status, "HTTP/1.1 200 OK");
+            let report: serde_json::Value = serde_json::from_str(&body)?;
+            assert_eq!(report["connected"], true);
+            assert!(
+                report["last_message_age"]
+                    .as_f64()
+                    .is_some_and(|age| age >= 0.0)
+            );
+            assert_eq!(report["open_trades"], 0);
+
+            // The server doesn't keep the client alive, it stops and frees the port once the client
+            // is dropped
+            drop(client);
+            tokio::time::timeout(CLIENT_CHECK_INTERVAL * 2, handle).await??;
+            assert!(TcpStream::connect(addr).await.is_err());
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! Apache Arrow record batches of the candles and deals returned by the client (`arrow` feature),
+//! the columnar format read without a copy by pandas, polars and the other dataframe libraries.
+//!
+//! Every result has a fixed schema so the batches of several requests can be concatenated, the
+//! batches are sent to Python as an Arrow IPC stream.
+
+use std::sync::Arc;
+
+pub use arrow_array::RecordBatch;
+
+use arrow_array::{
+    ArrayRef, BooleanArray, Float64Array, Int32Array, StringArray, TimestampMillisecondArray,
+    UInt64Array,
+};
+use arrow_ipc::writer::StreamWriter;
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef, TimeUnit};
+use chrono::{DateTime, Utc};
+
+use super::{
+    error::{PocketOptionError, PocketResult},
+    types::{order::Deal, update::DataCandle},
+};
+
+/// Conversion of the results of the client to an Arrow record batch.
+pub trait ToRecordBatch {
+    /// Schema of the batches, the same for every result (even an empty one).
+    fn arrow_schema() -> SchemaRef;
+
+    fn to_record_batch(&self) -> PocketResult<RecordBatch>;
+}
+
+/// `time` (timestamp in milliseconds, UTC), `open`, `high`, `low` and `close`, like the Parquet
+/// downloads of `download_history`.
+impl ToRecordBatch for [DataCandle] {
+    fn arrow_schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            timestamp_field("time"),
+            Field::new("open", DataType::Float64, false),
+            Field::new("high", DataType::Float64, false),
+            Field::new("low", DataType::Float64, false),
+            Field::new("close", DataType::Float64, false),
+        ]))
+    }
+
+    fn to_record_batch(&self) -> PocketResult<RecordBatch> {
+        let prices = |price: fn(&DataCandle) -> f64| -> ArrayRef {
+            Arc::new(Float64Array::from_iter_values(self.iter().map(price)))
+        };
+        let columns = vec![
+            timestamps(self.iter().map(|candle| candle.time)),
+            prices(|candle| candle.open),
+            prices(|candle| candle.high),
+            prices(|candle| candle.low),
+            prices(|candle| candle.close),
+        ];
+        RecordBatch::try_new(Self::arrow_schema(), columns).map_err(arrow_error)
+    }
+}
+
+/// One row per deal with its `id`, `asset`, `command` (0 for call, 1 for put), stake, result, prices
+/// and times.
+impl ToRecordBatch for [Deal] {
+    fn arrow_schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Utf8, false),
+            Field::new("asset", DataType::Utf8, false),
+            Field::new("command", DataType::Int32, false),
+            Field::new("amount", DataType::Float64, false),
+            Field::new("profit", DataType::Float64, false),
+            Field::new("percent_profit", DataType::Int32, false),
+            Field::new("percent_loss", DataType::Int32, false),
+            Field::new("open_price", DataType::Float64, false),
+            Field::new("close_price", DataType::Float64, false),
+            timestamp_field("open_timestamp"),
+            timestamp_field("close_timestamp"),
+            Field::new("uid", DataType::UInt64, false),
+            Field::new("is_demo", DataType::Boolean, false),
+            Field::new("currency", DataType::Utf8, false),
+        ]))
+    }
+
+    fn to_record_batch(&self) -> PocketResult<RecordBatch> {
+        let floats = |value: fn(&Deal) -> f64| -> ArrayRef {
+            Arc::new(Float64Array::from_iter_values(self.iter().map(value)))
+        };
+        let integers = |value: fn(&Deal) -> i32| -> ArrayRef {
+            Arc::new(Int32Array::from_iter_values(self.iter().map(value)))
+        };
+        let columns = vec![
+            Arc::new(StringArray::from_iter_values(
+                self.iter().map(|deal| deal.id.to_string()),
+            )) as ArrayRef,
+            Arc::new(StringArray::from_iter_values(
+                self.iter().map(|deal| deal.asset.as_str()),
+            )),
+            integers(|deal| deal.command),
+            floats(|deal| deal.amount),
+            floats(|deal| deal.profit),
+            integers(|deal| deal.percent_profit),
+            integers(|deal| deal.percent_loss),
+            floats(|deal| deal.open_price),
+            floats(|deal| deal.close_price),
+            timestamps(self.iter().map(|deal| deal.open_timestamp)),
+            timestamps(self.iter().map(|deal| deal.close_timestamp)),
+            Arc::new(UInt64Array::from_iter_values(
+                self.iter().map(|deal| deal.uid),
+            )),
+            Arc::new(BooleanArray::from(
+                self.iter()
+                    .map(|deal| deal.is_demo != 0)
+                    .collect::<Vec<_>>(),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                self.iter().map(|deal| deal.currency.as_str()),
+            )),
+        ];
+        RecordBatch::try_new(Self::arrow_schema(), columns).map_err(arrow_error)
+    }
+}
+
+/// Writes the batch as an Arrow IPC stream, read by `pyarrow.ipc.open_stream` without copying the
+/// columns.
+pub fn to_ipc_stream(batch: &RecordBatch) -> PocketResult<Vec<u8>> {
+    let mut writer = StreamWriter::try_new(Vec::new(), &batch.schema()).map_err(arrow_error)?;
+    writer.write(batch).map_err(arrow_error)?;
+    writer.into_inner().map_err(arrow_error)
+}
+
+fn arrow_error(e: ArrowError) -> PocketOptionError {
+    PocketOptionError::ArrowError(e.to_string())
+}
+
+fn timestamp_field(name: &str) -> Field {
+    Field::new(
+        name,
+        DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())),
+        false,
+    )
+}
+
+fn timestamps(times: impl Iterator<Item = DateTime<Utc>>) -> ArrayRef {
+    Arc::new(
+        TimestampMillisecondArray::from_iter_values(times.map(|time| time.timestamp_millis()))
+            .with_timezone("UTC"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::{
+        Array,
+        cast::AsArray,
+        types::{Float64Type, Int32Type, TimestampMillisecondType},
+    };
+    use arrow_ipc::reader::StreamReader;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    fn test_record_batches() -> anyhow::Result<()> {
+        let candles: Vec<DataCandle> = (0..3)
+            .map(|i| DataCandle {
+                time: DateTime::from_timestamp(i * 60, 0).unwrap(),
+                open: 1.0 + i as f64,
+                high: 2.0 + i as f64,
+                low: 0.5,
+                close: 1.5,
+                ..Default::default()
+            })
+            .collect();
+        let batch = candles.to_record_batch()?;
+        assert_eq!((batch.num_rows(), batch.num_columns()), (3, 5));
+        let times = batch.column(0).as_primitive::<TimestampMillisecondType>();
+        assert_eq!(times.value(2), 120_000);
+        assert_eq!(batch.column(1).as_primitive::<Float64Type>().value(1), 2.0);
+
+        // The IPC stream is read back as the same batch
+        let stream = to_ipc_stream(&batch)?;
+        let read: Vec<RecordBatch> =
+            StreamReader::try_new(stream.as_slice(), None)?.collect::<Result<_, _>>()?;
+        assert_eq!(read, vec![batch]);
+        // An empty result keeps the schema
+        let empty = ([] as [DataCandle; 0]).to_record_batch()?;
+        assert_eq!(empty.schema(), <[DataCandle]>::arrow_schema());
+        assert_eq!(empty.num_rows(), 0);
+
+        let deal: Deal = serde_json::from_value(json!({
+            "id": Uuid::new_v4(),
+            "openTime": "2024-12-04 23:58:48",
+            "closeTime": "2024-12-04 23:59:48",
+            "openTimestamp": 1733356728,
+            "closeTimestamp": 1733356788,
+            "uid": 1,
+            "isDemo": 1,
+            "amount": 10.0,
+            "profit": 9.2,
+            "percentProfit": 92,
+            "percentLoss": 100,
+            "openPrice": 1.1,
+            "copyTicket": "",
+            "closePrice": 1.2,
+            "command": 1,
+            "asset": "EURUSD_otc",
+            "openMs": 601,
+            "optionType": 100,
+            "currency": "USD"
+        }))?;
+        let batch = std::slice::from_ref(&deal).to_record_batch()?;
+        assert_eq!(batch.num_columns(), 14);
+        assert_eq!(
+            batch.column(0).as_string::<i32>().value(0),
+            deal.id.to_string()
+        );
+        assert_eq!(batch.column(2).as_primitive::<Int32Type>().value(0), 1);
+        assert!(batch.column(12).as_boolean().value(0));
+        assert_eq!(batch.column(13).null_count(), 0);
+        Ok(())
+    }
+}
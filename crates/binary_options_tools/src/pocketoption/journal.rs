@@ -0,0 +1,560 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use binary_options_tools_core::error::BinaryOptionsToolsError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{
+    error::{PocketOptionError, PocketResult},
+    expectancy::{Expectancy, TradeFilter},
+    performance::{PerformanceMatrix, TimeAxis},
+    types::{
+        order::{Action, Deal},
+        timeframe::IntoTimeframe,
+        update::DataCandle,
+    },
+};
+
+/// Largest number of candles stored around a trade by `ContextCapture`.
+pub const MAX_CONTEXT_CANDLES: u32 = 1000;
+
+/// A trade placed by the client, along with its result once it's closed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub trade_id: Uuid,
+    /// Id of the order request as sent back by the trade server.
+    #[serde(default)]
+    pub request_id: Option<u64>,
+    /// Account that placed the trade.
+    #[serde(default)]
+    pub uid: u64,
+    pub asset: String,
+    pub action: Action,
+    pub amount: f64,
+    pub time: u32,
+    pub open_price: f64,
+    pub opened_at: DateTime<Utc>,
+    /// Id shared by trades placed together (e.g. a trade and its hedge).
+    pub group: Option<Uuid>,
+    /// Labels of the trade (e.g. the risk tags of the template that placed it).
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub profit: Option<f64>,
+    pub closed_at: Option<DateTime<Utc>>,
+    /// Candles around the trade, stored once it closed if the journal captures them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<MarketContext>,
+}
+
+impl JournalEntry {
+    pub fn new(action: Action, time: u32, deal: &Deal) -> Self {
+        Self {
+            trade_id: deal.id,
+            request_id: deal.request_id,
+            uid: deal.uid,
+            asset: deal.asset.clone(),
+            action,
+            amount: deal.amount,
+            time,
+            open_price: deal.open_price,
+            opened_at: deal.open_timestamp,
+            group: None,
+            tags: Vec::new(),
+            profit: None,
+            closed_at: None,
+            context: None,
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.profit.is_some()
+    }
+}
+
+/// Number of candles the journal stores around the trades once they close (see
+/// `Journal::capture_context`), so the market the bot traded in can be reviewed afterwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextCapture {
+    /// Timeframe of the candles in seconds.
+    pub period: u32,
+    /// Candles before the one the trade opened in.
+    pub before: u32,
+    /// Candles after the one the trade closed in.
+    pub after: u32,
+}
+
+impl ContextCapture {
+    pub fn validate(&self) -> PocketResult<()> {
+        self.period.into_timeframe()?;
+        if self.before + self.after > MAX_CONTEXT_CANDLES {
+            return Err(PocketOptionError::Unallowed(format!(
+                "at most {MAX_CONTEXT_CANDLES} candles can be stored around a trade, got {} before and {} after",
+                self.before, self.after
+            )));
+        }
+        Ok(())
+    }
+
+    /// Start and end (unix timestamps, end excluded) of the candles stored for a trade opened at
+    /// `opened_at` and closed at `closed_at`.
+    pub fn range(&self, opened_at: DateTime<Utc>, closed_at: DateTime<Utc>) -> (i64, i64) {
+        let period = self.period as i64;
+        let start = (opened_at.timestamp().div_euclid(period) - self.before as i64) * period;
+        let end = (closed_at.timestamp().div_euclid(period) + 1 + self.after as i64) * period;
+        (start, end)
+    }
+}
+
+/// Candles around a trade stored in its journal entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketContext {
+    pub period: u32,
+    pub before: u32,
+    pub after: u32,
+    /// Candles from `before` candles before the opening to `after` candles after the close, the
+    /// ones the server didn't send are missing.
+    pub candles: Vec<DataCandle>,
+    pub captured_at: DateTime<Utc>,
+}
+
+/// Statistics of the closed trades of the journal.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct JournalStats {
+    pub trades: usize,
+    pub wins: usize,
+    pub losses: usize,
+    pub draws: usize,
+    /// Wins over the trades that weren't a draw, 0 if there are none.
+    pub win_rate: f64,
+    pub total_amount: f64,
+    pub total_profit: f64,
+    /// Total profit over the total amount staked, 0 if nothing was staked.
+    pub roi: f64,
+    pub first_trade: Option<DateTime<Utc>>,
+    pub last_trade: Option<DateTime<Utc>>,
+}
+
+impl JournalStats {
+    pub(crate) fn new<'a>(entries: impl Iterator<Item = &'a JournalEntry>) -> Self {
+        let mut stats = Self::default();
+        for entry in entries {
+            let Some(profit) = entry.profit else {
+                continue;
+            };
+            stats.trades += 1;
+            match profit {
+                p if p > 0.0 => stats.wins += 1,
+                p if p < 0.0 => stats.losses += 1,
+                _ => stats.draws += 1,
+            }
+            stats.total_amount += entry.amount;
+            stats.total_profit += profit;
+            stats.first_trade = Some(
+                stats
+                    .first_trade
+                    .map_or(entry.opened_at, |t| t.min(entry.opened_at)),
+            );
+            stats.last_trade = stats.last_trade.max(Some(entry.opened_at));
+        }
+        if stats.wins + stats.losses > 0 {
+            stats.win_rate = stats.wins as f64 / (stats.wins + stats.losses) as f64;
+        }
+        if stats.total_amount > 0.0 {
+            stats.roi = stats.total_profit / stats.total_amount;
+        }
+        stats
+    }
+}
+
+/// Adds the entries that aren't already in `entries` (by trade id), returns how many were added.
+fn merge(entries: &mut Vec<JournalEntry>, new: Vec<JournalEntry>) -> usize {
+    let before = entries.len();
+    for entry in new {
+        if !entries.iter().any(|e| e.trade_id == entry.trade_id) {
+            entries.push(entry);
+        }
+    }
+    entries.sort_by_key(|e| e.opened_at);
+    entries.len() - before
+}
+
+/// Journal of every trade placed by the client, optionally persisted to a JSON file
+/// so it survives restarts.
+#[derive(Default)]
+pub struct Journal {
+    entries: Mutex<Vec<JournalEntry>>,
+    path: Mutex<Option<PathBuf>>,
+    context: Mutex<Option<ContextCapture>>,
+    /// Held from the serialization of the entries to the rename of the file, so an older snapshot
+    /// never replaces a newer one.
+    save_lock: Mutex<()>,
+}
+
+impl Journal {
+    /// Loads the entries of `path` (if it exists) and saves every change to it from now on.
+    pub fn persist_to(&self, path: impl AsRef<Path>) -> PocketResult<()> {
+        let path = path.as_ref().to_path_buf();
+        let loaded: Vec<JournalEntry> = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(BinaryOptionsToolsError::from(e).into()),
+        };
+        merge(&mut *self.lock_entries()?, loaded);
+        *self
+            .path
+            .lock()
+            .map_err(|e| PocketOptionError::UnreachableError(e.to_string()))? = Some(path);
+        self.save()
+    }
+
+    /// Adds trades placed outside of the client (e.g. loaded from a broker export with
+    /// `utils::trade_export::load_trade_export`), the trades already in the journal are skipped.
+    /// Returns the number of trades added.
+    pub fn import(&self, entries: Vec<JournalEntry>) -> PocketResult<usize> {
+        let added = merge(&mut *self.lock_entries()?, entries);
+        self.save()?;
+        Ok(added)
+    }
+
+    /// Returns the statistics of the closed trades, only of the ones with `tag` if it's given.
+    pub fn stats(&self, tag: Option<&str>) -> JournalStats {
+        self.lock_entries()
+            .map(|entries| {
+                JournalStats::new(
+                    entries
+                        .iter()
+                        .filter(|e| tag.is_none_or(|tag| e.tags.iter().any(|t| t == tag))),
+                )
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns the expectancy of the closed trades matching `filter`, with a confidence interval
+    /// at the `confidence` level (e.g. 0.95).
+    pub fn expectancy(&self, filter: &TradeFilter, confidence: f64) -> PocketResult<Expectancy> {
+        Expectancy::new(self.lock_entries()?.iter(), filter, confidence)
+    }
+
+    /// Returns the performance of the closed trades matching `filter` by hour of the day (UTC), with
+    /// one row per asset if `by_asset`.
+    pub fn performance_by_hour(
+        &self,
+        filter: &TradeFilter,
+        by_asset: bool,
+    ) -> PocketResult<PerformanceMatrix> {
+        PerformanceMatrix::new(
+            self.lock_entries()?.iter(),
+            filter,
+            TimeAxis::Hour,
+            by_asset,
+        )
+    }
+
+    /// Returns the performance of the closed trades matching `filter` by day of the week (UTC),
+    /// with one row per asset if `by_asset`.
+    pub fn performance_by_weekday(
+        &self,
+        filter: &TradeFilter,
+        by_asset: bool,
+    ) -> PocketResult<PerformanceMatrix> {
+        PerformanceMatrix::new(
+            self.lock_entries()?.iter(),
+            filter,
+            TimeAxis::Weekday,
+            by_asset,
+        )
+    }
+
+    /// Stores the candles around the trades of the client once they close (`None` stops it), the
+    /// client downloads them once the last candle closed.
+    pub fn capture_context(&self, capture: Option<ContextCapture>) -> PocketResult<()> {
+        if let Some(capture) = &capture {
+            capture.validate()?;
+        }
+        *self
+            .context
+            .lock()
+            .map_err(|e| PocketOptionError::UnreachableError(e.to_string()))? = capture;
+        Ok(())
+    }
+
+    pub fn context_capture(&self) -> Option<ContextCapture> {
+        self.context.lock().ok().and_then(|capture| *capture)
+    }
+
+    /// Stores the candles around a trade in its entry.
+    pub fn set_context(&self, trade_id: Uuid, context: MarketContext) -> PocketResult<()> {
+        let updated = self
+            .lock_entries()?
+            .iter_mut()
+            .find(|e| e.trade_id == trade_id)
+            .map(|e| e.context = Some(context))
+            .is_some();
+        if updated {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    pub fn record(&self, entry: JournalEntry) -> PocketResult<()> {
+        self.lock_entries()?.push(entry);
+        self.save()
+    }
+
+    /// Links the trades in the same group, returning the id of the group.
+    pub fn group(&self, trades: &[Uuid]) -> PocketResult<Uuid> {
+        let group = Uuid::new_v4();
+        self.lock_entries()?
+            .iter_mut()
+            .filter(|e| trades.contains(&e.trade_id))
+            .for_each(|e| e.group = Some(group));
+        self.save()?;
+        Ok(group)
+    }
+
+    /// Adds `tags` to the trades, the tags they already have are kept.
+    pub fn tag(&self, trades: &[Uuid], tags: &[String]) -> PocketResult<()> {
+        self.lock_entries()?
+            .iter_mut()
+            .filter(|e| trades.contains(&e.trade_id))
+            .for_each(|e| {
+                for tag in tags {
+                    if !e.tags.contains(tag) {
+                        e.tags.push(tag.clone());
+                    }
+                }
+            });
+        self.save()
+    }
+
+    /// Returns all the trades with a tag.
+    pub fn tagged_entries(&self, tag: &str) -> Vec<JournalEntry> {
+        self.lock_entries()
+            .map(|entries| {
+                entries
+                    .iter()
+                    .filter(|e| e.tags.iter().any(|t| t == tag))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Updates the entry of a closed deal with its result, returns false if the deal isn't in the
+    /// journal or was already closed.
+    pub fn close(&self, deal: &Deal) -> PocketResult<bool> {
+        let updated = self
+            .lock_entries()?
+            .iter_mut()
+            .find(|e| e.trade_id == deal.id && !e.is_closed())
+            .map(|e| {
+                e.profit = Some(deal.profit);
+                e.closed_at = Some(deal.close_timestamp);
+            })
+            .is_some();
+        if updated {
+            self.save()?;
+        }
+        Ok(updated)
+    }
+
+    pub fn entry(&self, trade_id: Uuid) -> Option<JournalEntry> {
+        self.lock_entries()
+            .ok()?
+            .iter()
+            .find(|e| e.trade_id == trade_id)
+            .cloned()
+    }
+
+    pub fn entries(&self) -> Vec<JournalEntry> {
+        self.lock_entries().map(|e| e.clone()).unwrap_or_default()
+    }
+
+    /// Returns all the trades of a group.
+    pub fn group_entries(&self, group: Uuid) -> Vec<JournalEntry> {
+        self.lock_entries()
+            .map(|entries| {
+                entries
+                    .iter()
+                    .filter(|e| e.group == Some(group))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn lock_entries(&self) -> PocketResult<std::sync::MutexGuard<'_, Vec<JournalEntry>>> {
+        self.entries
+            .lock()
+            .map_err(|e| PocketOptionError::UnreachableError(e.to_string()))
+    }
+
+    fn save(&self) -> PocketResult<()> {
+        let _guard = self.save_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let path = self
+            .path
+            .lock()
+            .map_err(|e| PocketOptionError::UnreachableError(e.to_string()))?
+            .clone();
+        if let Some(path) = path {
+            let content = serde_json::to_string_pretty(&*self.lock_entries()?)?;
+            // Unique per save, so another journal persisted to the same file doesn't write to it
+            let mut tmp = path.clone().into_os_string();
+            tmp.push(format!(".{}.tmp", Uuid::new_v4()));
+            std::fs::write(&tmp, content).map_err(BinaryOptionsToolsError::from)?;
+            std::fs::rename(tmp, &path).map_err(BinaryOptionsToolsError::from)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(asset: &str) -> JournalEntry {
+        JournalEntry {
+            trade_id: Uuid::new_v4(),
+            request_id: None,
+            uid: 0,
+            asset: asset.to_string(),
+            action: Action::Call,
+            amount: 1.0,
+            time: 60,
+            open_price: 1.1,
+            opened_at: Utc::now(),
+            group: None,
+            tags: Vec::new(),
+            profit: None,
+            closed_at: None,
+            context: None,
+        }
+    }
+
+    #[test]
+    fn test_journal_groups_and_persistence() -> anyhow::Result<()> {
+        let path = std::env::temp_dir().join(format!("journal-{}.json", Uuid::new_v4()));
+        let journal = Journal::default();
+        journal.persist_to(&path)?;
+        let (primary, hedge, other) = (entry("EURUSD"), entry("USDCHF"), entry("GBPUSD"));
+        journal.record(primary.clone())?;
+        journal.record(hedge.clone())?;
+        journal.record(other.clone())?;
+        let group = journal.group(&[primary.trade_id, hedge.trade_id])?;
+
+        let restored = Journal::default();
+        restored.persist_to(&path)?;
+        assert_eq!(restored.entries().len(), 3);
+        let grouped = restored.group_entries(group);
+        assert_eq!(grouped.len(), 2);
+        assert!(grouped.iter().all(|e| e.trade_id != other.trade_id));
+
+        journal.tag(&[other.trade_id], &["news".to_string()])?;
+        journal.tag(
+            &[other.trade_id],
+            &["news".to_string(), "eurusd".to_string()],
+        )?;
+        let restored = Journal::default();
+        restored.persist_to(&path)?;
+        let tagged = restored.tagged_entries("news");
+        assert_eq!(tagged.len(), 1);
+        assert_eq!(tagged[0].tags, ["news", "eurusd"]);
+
+        let mut imported = entry("EURUSD");
+        imported.profit = Some(-1.0);
+        assert_eq!(journal.import(vec![imported.clone(), primary.clone()])?, 1);
+        assert_eq!(journal.import(vec![imported])?, 0);
+        let stats = journal.stats(None);
+        assert_eq!(
+            (stats.trades, stats.losses, stats.total_profit),
+            (1, 1, -1.0)
+        );
+        assert_eq!(journal.stats(Some("news")).trades, 0);
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_journal_concurrent_saves() -> anyhow::Result<()> {
+        let path = std::env::temp_dir().join(format!("journal-{}.json", Uuid::new_v4()));
+        let journal = Journal::default();
+        journal.persist_to(&path)?;
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    for _ in 0..10 {
+                        journal.record(entry("EURUSD")).unwrap();
+                    }
+                });
+            }
+        });
+
+        // The last save has every entry and no temporary file is left behind
+        let restored = Journal::default();
+        restored.persist_to(&path)?;
+        assert_eq!(restored.entries().len(), 80);
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        let leftovers = std::fs::read_dir(std::env::temp_dir())?
+            .filter_map(|e| e.ok())
+            .filter(|e| {
+                let file = e.file_name().to_string_lossy().into_owned();
+                file.starts_with(&name) && file.ends_with(".tmp")
+            })
+            .count();
+        assert_eq!(leftovers, 0);
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_context_capture() -> anyhow::Result<()> {
+        let journal = Journal::default();
+        let invalid = ContextCapture {
+            period: 7,
+            before: 10,
+            after: 5,
+        };
+        assert!(journal.capture_context(Some(invalid)).is_err());
+        let capture = ContextCapture {
+            period: 60,
+            ..invalid
+        };
+        journal.capture_context(Some(capture))?;
+        assert_eq!(journal.context_capture(), Some(capture));
+
+        // Opened and closed in the middle of two consecutive candles, 10 candles before the first
+        // one and 5 after the second one
+        let opened_at = DateTime::from_timestamp(1_700_000_430, 0).unwrap();
+        let closed_at = opened_at + chrono::Duration::seconds(60);
+        let (start, end) = capture.range(opened_at, closed_at);
+        assert_eq!((start, end), (1_700_000_400 - 600, 1_700_000_460 + 360));
+
+        let trade = entry("EURUSD_otc");
+        journal.record(trade.clone())?;
+        let context = MarketContext {
+            period: 60,
+            before: 10,
+            after: 5,
+            candles: Vec::new(),
+            captured_at: Utc::now(),
+        };
+        journal.set_context(trade.trade_id, context)?;
+        let json = serde_json::to_string(&journal.entries())?;
+        let restored: Vec<JournalEntry> = serde_json::from_str(&json)?;
+        assert_eq!(restored[0].context.as_ref().map(|c| c.after), Some(5));
+
+        // The entries without context (saved before it was captured) are still read
+        let old = serde_json::to_value(entry("EURUSD_otc"))?;
+        assert!(old.get("context").is_none());
+        assert!(
+            serde_json::from_value::<JournalEntry>(old)?
+                .context
+                .is_none()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,407 @@
+//! Price levels computed from a history of candles: the classic floor pivots, the highs and lows
+//! of the sessions and support / resistance zones clustering the swing highs and lows, either
+//! once with `Levels::compute` or on the last candles of a stream with a `LevelTracker`.
+
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    error::{PocketOptionError, PocketResult},
+    types::update::DataCandle,
+};
+
+/// Classic floor pivots of a session, computed from its high, low and close.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PivotLevels {
+    pub pivot: f64,
+    pub r1: f64,
+    pub r2: f64,
+    pub r3: f64,
+    pub s1: f64,
+    pub s2: f64,
+    pub s3: f64,
+}
+
+impl PivotLevels {
+    pub fn new(high: f64, low: f64, close: f64) -> Self {
+        let pivot = (high + low + close) / 3.0;
+        let range = high - low;
+        Self {
+            pivot,
+            r1: 2.0 * pivot - low,
+            r2: pivot + range,
+            r3: high + 2.0 * (pivot - low),
+            s1: 2.0 * pivot - high,
+            s2: pivot - range,
+            s3: low - 2.0 * (high - pivot),
+        }
+    }
+}
+
+/// Open, high, low and close of a session.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionRange {
+    /// Start of the session.
+    pub start: DateTime<Utc>,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub candles: usize,
+}
+
+impl SessionRange {
+    fn new(start: DateTime<Utc>, candle: &DataCandle) -> Self {
+        Self {
+            start,
+            open: candle.open,
+            high: candle.high,
+            low: candle.low,
+            close: candle.close,
+            candles: 1,
+        }
+    }
+
+    fn update(&mut self, candle: &DataCandle) {
+        self.high = self.high.max(candle.high);
+        self.low = self.low.min(candle.low);
+        self.close = candle.close;
+        self.candles += 1;
+    }
+
+    pub fn pivots(&self) -> PivotLevels {
+        PivotLevels::new(self.high, self.low, self.close)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ZoneKind {
+    /// Zone below the last close.
+    Support,
+    /// Zone at or above the last close.
+    Resistance,
+}
+
+/// Price zone where the swing highs and lows of the history clustered.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LevelZone {
+    pub kind: ZoneKind,
+    /// Mean price of the swings of the zone.
+    pub price: f64,
+    pub low: f64,
+    pub high: f64,
+    /// Number of swing highs and lows in the zone.
+    pub touches: usize,
+    /// Time of the last swing in the zone.
+    pub last_touch: DateTime<Utc>,
+}
+
+/// Settings of the level computation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LevelsConfig {
+    /// Length of the sessions in seconds, the sessions start at multiples of it (UTC days by
+    /// default).
+    pub session: i64,
+    /// Candles on each side of a swing high (low) that must have a lower high (higher low).
+    pub swing_window: usize,
+    /// Largest width of a zone, relative to the mean range (high - low) of the candles.
+    pub zone_width: f64,
+    /// Zones with fewer swings aren't reported.
+    pub min_touches: usize,
+    /// Largest number of zones reported, the ones with the most touches are kept.
+    pub max_zones: usize,
+}
+
+impl Default for LevelsConfig {
+    fn default() -> Self {
+        Self {
+            session: 86400,
+            swing_window: 2,
+            zone_width: 0.5,
+            min_touches: 2,
+            max_zones: 10,
+        }
+    }
+}
+
+impl LevelsConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn session(mut self, session: i64) -> Self {
+        self.session = session;
+        self
+    }
+
+    pub fn zone_width(mut self, zone_width: f64) -> Self {
+        self.zone_width = zone_width;
+        self
+    }
+
+    pub fn min_touches(mut self, min_touches: usize) -> Self {
+        self.min_touches = min_touches;
+        self
+    }
+
+    pub fn validate(&self) -> PocketResult<()> {
+        let invalid = |reason: String| Err(PocketOptionError::Unallowed(reason));
+        if self.session <= 0 {
+            return invalid(format!(
+                "the session must be positive, got {}",
+                self.session
+            ));
+        }
+        if self.swing_window == 0 {
+            return invalid("the swing window must be at least 1 candle".to_string());
+        }
+        if !self.zone_width.is_finite() || self.zone_width <= 0.0 {
+            return invalid(format!(
+                "the zone width must be positive, got {}",
+                self.zone_width
+            ));
+        }
+        if self.min_touches == 0 {
+            return invalid("the zones need at least 1 touch".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Levels of a history of candles, relative to its last close.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Levels {
+    /// Time of the last candle.
+    pub time: DateTime<Utc>,
+    pub close: f64,
+    /// Pivots of the previous session, or of the whole history if it covers a single session.
+    pub pivots: PivotLevels,
+    /// Sessions of the history in chronological order, the last one can be incomplete.
+    pub sessions: Vec<SessionRange>,
+    /// Support and resistance zones, sorted by price.
+    pub zones: Vec<LevelZone>,
+    /// Highest support zone.
+    pub nearest_support: Option<LevelZone>,
+    /// Lowest resistance zone.
+    pub nearest_resistance: Option<LevelZone>,
+}
+
+/// Swing high or low of the history.
+struct Swing {
+    price: f64,
+    time: DateTime<Utc>,
+}
+
+impl Levels {
+    /// Computes the levels of `candles`, sorted in chronological order.
+    pub fn compute(candles: &[DataCandle], config: &LevelsConfig) -> PocketResult<Self> {
+        config.validate()?;
+        let last = candles.last().ok_or_else(|| {
+            PocketOptionError::Unallowed("no candles to compute the levels from".to_string())
+        })?;
+        let sessions = sessions(candles, config.session);
+        let pivots = match &sessions[..] {
+            [.., previous, _] => previous.pivots(),
+            _ => {
+                let high = candles.iter().map(|c| c.high).fold(f64::MIN, f64::max);
+                let low = candles.iter().map(|c| c.low).fold(f64::MAX, f64::min);
+                PivotLevels::new(high, low, last.close)
+            }
+        };
+        let zones = zones(candles, config, last.close);
+        let nearest_support = zones.iter().rfind(|z| z.kind == ZoneKind::Support).cloned();
+        let nearest_resistance = zones
+            .iter()
+            .find(|z| z.kind == ZoneKind::Resistance)
+            .cloned();
+        Ok(Self {
+            time: last.time,
+            close: last.close,
+            pivots,
+            sessions,
+            zones,
+            nearest_support,
+            nearest_resistance,
+        })
+    }
+}
+
+fn sessions(candles: &[DataCandle], session: i64) -> Vec<SessionRange> {
+    let mut sessions: Vec<SessionRange> = Vec::new();
+    for candle in candles {
+        let start = candle.time.timestamp().div_euclid(session) * session;
+        let start = DateTime::from_timestamp(start, 0).unwrap_or(candle.time);
+        match sessions.last_mut() {
+            Some(last) if last.start == start => last.update(candle),
+            _ => sessions.push(SessionRange::new(start, candle)),
+        }
+    }
+    sessions
+}
+
+fn zones(candles: &[DataCandle], config: &LevelsConfig, close: f64) -> Vec<LevelZone> {
+    let window = config.swing_window;
+    let mut swings = Vec::new();
+    for i in window..candles.len().saturating_sub(window) {
+        let (left, candle, right) = (
+            &candles[i - window..i],
+            &candles[i],
+            &candles[i + 1..=i + window],
+        );
+        // Flat tops and bottoms count once, on their first candle
+        if left.iter().all(|c| c.high <= candle.high) && right.iter().all(|c| c.high < candle.high)
+        {
+            swings.push(Swing {
+                price: candle.high,
+                time: candle.time,
+            });
+        }
+        if left.iter().all(|c| c.low >= candle.low) && right.iter().all(|c| c.low > candle.low) {
+            swings.push(Swing {
+                price: candle.low,
+                time: candle.time,
+            });
+        }
+    }
+    swings.sort_by(|a, b| a.price.total_cmp(&b.price));
+
+    let mean_range =
+        candles.iter().map(|c| c.high - c.low).sum::<f64>() / candles.len().max(1) as f64;
+    let width = config.zone_width * mean_range;
+    let mut clusters: Vec<Vec<Swing>> = Vec::new();
+    for swing in swings {
+        match clusters.last_mut() {
+            Some(cluster) if swing.price - cluster[0].price <= width => cluster.push(swing),
+            _ => clusters.push(vec![swing]),
+        }
+    }
+
+    let mut zones: Vec<LevelZone> = clusters
+        .into_iter()
+        .filter(|cluster| cluster.len() >= config.min_touches)
+        .map(|cluster| {
+            let price = cluster.iter().map(|s| s.price).sum::<f64>() / cluster.len() as f64;
+            LevelZone {
+                kind: if price < close {
+                    ZoneKind::Support
+                } else {
+                    ZoneKind::Resistance
+                },
+                price,
+                low: cluster[0].price,
+                high: cluster[cluster.len() - 1].price,
+                touches: cluster.len(),
+                last_touch: cluster.iter().map(|s| s.time).max().unwrap_or_default(),
+            }
+        })
+        .collect();
+    // Keeps the zones touched the most, then the most recent ones
+    zones.sort_by(|a, b| {
+        b.touches
+            .cmp(&a.touches)
+            .then(b.last_touch.cmp(&a.last_touch))
+    });
+    zones.truncate(config.max_zones);
+    zones.sort_by(|a, b| a.price.total_cmp(&b.price));
+    zones
+}
+
+/// Levels of the last `lookback` closed candles of a stream.
+#[derive(Debug, Clone)]
+pub struct LevelTracker {
+    config: LevelsConfig,
+    lookback: usize,
+    window: VecDeque<DataCandle>,
+}
+
+impl LevelTracker {
+    pub fn new(config: LevelsConfig, lookback: usize) -> PocketResult<Self> {
+        config.validate()?;
+        if lookback == 0 {
+            return Err(PocketOptionError::Unallowed(
+                "the lookback must be at least 1 candle".to_string(),
+            ));
+        }
+        Ok(Self {
+            config,
+            lookback,
+            window: VecDeque::with_capacity(lookback),
+        })
+    }
+
+    /// Adds a closed candle and returns the levels of the last `lookback` candles. The candles
+    /// must be fed in chronological order.
+    pub fn update(&mut self, candle: &DataCandle) -> PocketResult<Levels> {
+        if self.window.len() == self.lookback {
+            self.window.pop_front();
+        }
+        self.window.push_back(candle.clone());
+        Levels::compute(self.window.make_contiguous(), &self.config)
+    }
+
+    pub fn reset(&mut self) {
+        self.window.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+
+    use super::*;
+
+    fn candle(index: i64, high: f64, low: f64) -> DataCandle {
+        DataCandle {
+            time: DateTime::from_timestamp(index * 3600, 0).unwrap(),
+            open: (high + low) / 2.0,
+            close: (high + low) / 2.0,
+            high,
+            low,
+            received_at: None,
+            latency: None,
+        }
+    }
+
+    #[test]
+    fn test_levels() -> anyhow::Result<()> {
+        let pivots = PivotLevels::new(1.2, 1.0, 1.1);
+        assert!((pivots.pivot - 1.1).abs() < 1e-9);
+        assert!((pivots.r1 - 1.2).abs() < 1e-9 && (pivots.s1 - 1.0).abs() < 1e-9);
+
+        // Oscillates between 1.00 and 1.10 over two days, ending in the middle
+        let candles: Vec<DataCandle> = (0..48)
+            .map(|i| {
+                let mid = match i % 6 {
+                    0 => 1.0,
+                    3 => 1.1,
+                    n if n < 3 => 1.0 + 0.03 * n as f64,
+                    n => 1.1 - 0.03 * (n - 3) as f64,
+                };
+                candle(i, mid + 0.005, mid - 0.005)
+            })
+            .collect();
+        let levels = Levels::compute(&candles, &LevelsConfig::new())?;
+        assert_eq!(levels.sessions.len(), 2);
+        assert_eq!(
+            levels.sessions[1].start - levels.sessions[0].start,
+            TimeDelta::days(1)
+        );
+        assert_eq!(levels.zones.len(), 2);
+        let support = levels.nearest_support.as_ref().unwrap();
+        let resistance = levels.nearest_resistance.as_ref().unwrap();
+        assert!((support.price - 0.995).abs() < 1e-9);
+        assert!((resistance.price - 1.105).abs() < 1e-9);
+        assert!(support.touches >= 7);
+
+        let mut tracker = LevelTracker::new(LevelsConfig::new(), 20)?;
+        let last = candles.iter().map(|c| tracker.update(c)).last().unwrap()?;
+        assert_eq!(last.zones.len(), 2);
+        assert!(Levels::compute(&[], &LevelsConfig::new()).is_err());
+        assert!(LevelsConfig::new().zone_width(0.0).validate().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,396 @@
+//! Guard pausing the order placement while the market conditions are abnormal (quote latency, gaps
+//! in the ticks of an asset, volatility), trading resumes on its own once the conditions stayed
+//! normal for a while.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    error::{PocketOptionError, PocketResult},
+    risk::{RiskRule, TradeIntent},
+    types::update::UpdateStream,
+};
+
+/// Thresholds of the `MarketGuard`, the conditions left to `None` aren't checked.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MarketGuardConfig {
+    /// Maximum mean transport latency of the last ticks, in milliseconds, without the offset
+    /// between the local and the server clocks. A high latency pauses every asset.
+    pub max_latency: Option<f64>,
+    /// Maximum time without a tick of an asset, in seconds. Only the assets that recieved a tick
+    /// are checked, so the assets must be subscribed to.
+    pub max_gap: Option<f64>,
+    /// Maximum standard deviation of the tick to tick returns of an asset, e.g. 0.001 for 0.1%.
+    pub max_volatility: Option<f64>,
+    /// Number of ticks the latency and the volatility are measured over.
+    pub window: usize,
+    /// Seconds the conditions must stay normal before trading resumes.
+    pub resume_after: f64,
+}
+
+impl Default for MarketGuardConfig {
+    fn default() -> Self {
+        Self {
+            max_latency: None,
+            max_gap: None,
+            max_volatility: None,
+            window: 20,
+            resume_after: 30.0,
+        }
+    }
+}
+
+impl MarketGuardConfig {
+    pub fn validate(&self) -> PocketResult<()> {
+        let thresholds = [
+            ("max_latency", self.max_latency),
+            ("max_gap", self.max_gap),
+            ("max_volatility", self.max_volatility),
+        ];
+        for (name, threshold) in thresholds {
+            if let Some(value) = threshold.filter(|v| !(v.is_finite() && *v > 0.0)) {
+                return Err(PocketOptionError::Unallowed(format!(
+                    "{name} must be a positive number, got {value}"
+                )));
+            }
+        }
+        if self.window < 2 {
+            return Err(PocketOptionError::Unallowed(format!(
+                "the window must have at least 2 ticks, got {}",
+                self.window
+            )));
+        }
+        if !(self.resume_after.is_finite() && self.resume_after >= 0.0) {
+            return Err(PocketOptionError::Unallowed(format!(
+                "resume_after must be a positive number of seconds, got {}",
+                self.resume_after
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Transition of the guard, sent when an asset (or every asset if `asset` is `None`) is paused
+/// or resumed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarketGuardEvent {
+    pub asset: Option<String>,
+    pub paused: bool,
+    /// Conditions that paused the trading, empty when it resumes.
+    pub reasons: Vec<String>,
+    pub time: DateTime<Utc>,
+}
+
+/// Trading currently paused by the guard, on `asset` or on every asset if it's `None`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarketPause {
+    pub asset: Option<String>,
+    pub reasons: Vec<String>,
+    pub since: DateTime<Utc>,
+}
+
+#[derive(Debug, Default)]
+struct AssetTicks {
+    last_tick: Option<DateTime<Utc>>,
+    prices: VecDeque<f64>,
+}
+
+#[derive(Debug)]
+struct Pause {
+    reasons: Vec<String>,
+    since: DateTime<Utc>,
+    /// Time the conditions went back to normal at, trading resumes `resume_after` later.
+    normal_since: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default)]
+struct GuardState {
+    latencies: VecDeque<f64>,
+    /// Lowest latency seen, the estimation of the offset between the local and the server clocks.
+    clock_offset: Option<f64>,
+    assets: HashMap<String, AssetTicks>,
+    /// Pauses by asset, the `None` key pauses every asset.
+    pauses: HashMap<Option<String>, Pause>,
+}
+
+/// Risk rule rejecting the trades while the market conditions of their asset are abnormal, the
+/// guard is fed with the ticks of the `updateStream` messages.
+pub struct MarketGuard {
+    config: MarketGuardConfig,
+    state: Mutex<GuardState>,
+}
+
+impl MarketGuard {
+    pub fn new(config: MarketGuardConfig) -> PocketResult<Self> {
+        config.validate()?;
+        Ok(Self {
+            config,
+            state: Mutex::new(GuardState::default()),
+        })
+    }
+
+    pub fn config(&self) -> &MarketGuardConfig {
+        &self.config
+    }
+
+    /// Records a tick of `asset` recieved at `received_at` with a latency of `latency` milliseconds.
+    pub fn record_tick(&self, asset: &str, price: f64, received_at: DateTime<Utc>, latency: f64) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        if latency.is_finite() {
+            state.clock_offset = Some(state.clock_offset.map_or(latency, |o| o.min(latency)));
+            state.latencies.push_back(latency);
+            if state.latencies.len() > self.config.window {
+                state.latencies.pop_front();
+            }
+        }
+        let ticks = state.assets.entry(asset.to_string()).or_default();
+        ticks.last_tick = Some(received_at);
+        if price.is_finite() && price > 0.0 {
+            ticks.prices.push_back(price);
+            if ticks.prices.len() > self.config.window + 1 {
+                ticks.prices.pop_front();
+            }
+        }
+    }
+
+    /// Records the ticks of a stream message and returns the transitions it caused.
+    pub fn record_stream(&self, stream: &UpdateStream) -> Vec<MarketGuardEvent> {
+        for item in stream.0.iter() {
+            self.record_tick(&item.active, item.price, item.received_at, item.latency());
+        }
+        self.evaluate(Utc::now())
+    }
+
+    /// Checks the conditions of every asset at `now`, pausing or resuming the trading, and returns
+    /// the transitions.
+    pub fn evaluate(&self, now: DateTime<Utc>) -> Vec<MarketGuardEvent> {
+        let Ok(mut state) = self.state.lock() else {
+            return Vec::new();
+        };
+        let mut conditions = vec![(None, self.latency_violations(&state))];
+        conditions.extend(
+            state
+                .assets
+                .iter()
+                .map(|(asset, ticks)| (Some(asset.clone()), self.asset_violations(ticks, now))),
+        );
+        let resume_after = TimeDelta::milliseconds((self.config.resume_after * 1000.0) as i64);
+        let mut events = Vec::new();
+        for (asset, reasons) in conditions {
+            match state.pauses.get_mut(&asset) {
+                None if !reasons.is_empty() => {
+                    events.push(MarketGuardEvent {
+                        asset: asset.clone(),
+                        paused: true,
+                        reasons: reasons.clone(),
+                        time: now,
+                    });
+                    state.pauses.insert(
+                        asset,
+                        Pause {
+                            reasons,
+                            since: now,
+                            normal_since: None,
+                        },
+                    );
+                }
+                None => {}
+                Some(pause) if !reasons.is_empty() => {
+                    pause.reasons = reasons;
+                    pause.normal_since = None;
+                }
+                Some(pause) => {
+                    let normal_since = *pause.normal_since.get_or_insert(now);
+                    if now - normal_since >= resume_after {
+                        state.pauses.remove(&asset);
+                        events.push(MarketGuardEvent {
+                            asset,
+                            paused: false,
+                            reasons: Vec::new(),
+                            time: now,
+                        });
+                    }
+                }
+            }
+        }
+        events
+    }
+
+    fn latency_violations(&self, state: &GuardState) -> Vec<String> {
+        let (Some(max_latency), Some(clock_offset)) = (self.config.max_latency, state.clock_offset)
+        else {
+            return Vec::new();
+        };
+        if state.latencies.len() < self.config.window {
+            return Vec::new();
+        }
+        let latency =
+            state.latencies.iter().sum::<f64>() / state.latencies.len() as f64 - clock_offset;
+        if latency > max_latency {
+            vec![format!(
+                "quote latency of {latency:.0}ms over the maximum of {max_latency:.0}ms"
+            )]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn asset_violations(&self, ticks: &AssetTicks, now: DateTime<Utc>) -> Vec<String> {
+        let mut reasons = Vec::new();
+        let gap = ticks
+            .last_tick
+            .map(|last_tick| (now - last_tick).num_milliseconds() as f64 / 1000.0);
+        match (self.config.max_gap, gap) {
+            (Some(max_gap), Some(gap)) if gap > max_gap => reasons.push(format!(
+                "no tick for {gap:.1}s, over the maximum gap of {max_gap}s"
+            )),
+            _ => {}
+        }
+        match (
+            self.config.max_volatility,
+            volatility(&ticks.prices, self.config.window),
+        ) {
+            (Some(max_volatility), Some(volatility)) if volatility > max_volatility => reasons
+                .push(format!(
+                    "volatility of {volatility:.6} over the maximum of {max_volatility}"
+                )),
+            _ => {}
+        }
+        reasons
+    }
+
+    /// Returns the current pauses.
+    pub fn status(&self) -> Vec<MarketPause> {
+        self.state
+            .lock()
+            .map(|state| {
+                state
+                    .pauses
+                    .iter()
+                    .map(|(asset, pause)| MarketPause {
+                        asset: asset.clone(),
+                        reasons: pause.reasons.clone(),
+                        since: pause.since,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns the reasons trading is paused on `asset`, `None` if it isn't.
+    pub fn paused(&self, asset: &str) -> Option<Vec<String>> {
+        let state = self.state.lock().ok()?;
+        let reasons: Vec<String> = [None, Some(asset.to_string())]
+            .iter()
+            .filter_map(|key| state.pauses.get(key))
+            .flat_map(|pause| pause.reasons.clone())
+            .collect();
+        (!reasons.is_empty()).then_some(reasons)
+    }
+}
+
+/// Standard deviation of the tick to tick log returns of `prices`, `None` with less than
+/// `window` returns.
+fn volatility(prices: &VecDeque<f64>, window: usize) -> Option<f64> {
+    if prices.len() <= window {
+        return None;
+    }
+    let returns: Vec<f64> = prices
+        .iter()
+        .zip(prices.iter().skip(1))
+        .map(|(previous, price)| (price / previous).ln())
+        .collect();
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    let variance =
+        returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
+    Some(variance.sqrt())
+}
+
+impl RiskRule for MarketGuard {
+    fn name(&self) -> &str {
+        "MarketGuard"
+    }
+
+    fn check(&self, intent: &TradeIntent) -> Result<(), String> {
+        match self.paused(&intent.asset) {
+            Some(reasons) => Err(format!(
+                "trading is paused by abnormal market conditions, {}",
+                reasons.join(", ")
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pocketoption::types::order::Action;
+
+    #[test]
+    fn test_market_guard() -> anyhow::Result<()> {
+        assert!(
+            MarketGuard::new(MarketGuardConfig {
+                window: 1,
+                ..Default::default()
+            })
+            .is_err()
+        );
+
+        let guard = MarketGuard::new(MarketGuardConfig {
+            max_latency: Some(500.0),
+            max_gap: Some(5.0),
+            window: 4,
+            resume_after: 10.0,
+            ..Default::default()
+        })?;
+        let start = Utc::now();
+        let at = |secs: i64| start + TimeDelta::seconds(secs);
+        let intent = TradeIntent::new("EURUSD_otc", Action::Call, 1.0, 60);
+        for i in 0..4 {
+            guard.record_tick("EURUSD_otc", 1.1, at(i), 50.0);
+        }
+        assert!(guard.evaluate(at(4)).is_empty());
+        assert!(guard.check(&intent).is_ok());
+
+        // The ticks of the asset stop
+        let events = guard.evaluate(at(10));
+        assert_eq!(events.len(), 1);
+        assert!(events[0].paused);
+        assert_eq!(events[0].asset.as_deref(), Some("EURUSD_otc"));
+        assert!(guard.check(&intent).is_err());
+        assert_eq!(guard.status().len(), 1);
+
+        // The latency spikes, pausing every asset
+        for i in 11..15 {
+            guard.record_tick("EURUSD_otc", 1.1, at(i), 1050.0);
+        }
+        let events = guard.evaluate(at(15));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].asset, None);
+        assert!(guard.paused("GBPUSD_otc").is_some());
+
+        // Conditions back to normal, trading resumes after `resume_after`
+        for i in 15..19 {
+            guard.record_tick("EURUSD_otc", 1.1, at(i), 50.0);
+        }
+        assert!(guard.evaluate(at(19)).is_empty());
+        for i in 19..29 {
+            guard.record_tick("EURUSD_otc", 1.1, at(i), 50.0);
+        }
+        let events = guard.evaluate(at(29));
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|e| !e.paused));
+        assert!(guard.check(&intent).is_ok());
+        assert!(guard.status().is_empty());
+        Ok(())
+    }
+}
@@ -1,6 +1,30 @@
+pub mod access;
+pub mod benchmarks;
+#[cfg(feature = "charts")]
+pub mod chart;
+#[cfg(feature = "dashboard")]
+pub mod dashboard;
 pub mod error;
+pub mod expectancy;
+pub mod experiment;
+#[cfg(feature = "metrics")]
+pub mod health;
+#[cfg(feature = "arrow")]
+pub mod interop;
+pub mod journal;
+pub mod levels;
+pub mod market_guard;
+pub mod orders;
 pub mod parser;
+pub mod patterns;
+pub mod performance;
 pub mod pocket_client;
+pub mod price_orders;
+pub mod risk;
+pub mod scheduled_orders;
+pub mod stake;
+pub mod straddle;
+pub mod templates;
 pub mod types;
 pub mod utils;
 pub mod validators;
@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{
+    error::{PocketOptionError, PocketResult},
+    types::order::{Deal, Direction},
+};
+
+/// Options of `PocketOption::place_order`, the same for every trade whatever the way it's placed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OrderOptions {
+    /// Tags recorded in the journal with the trade.
+    pub tags: Vec<String>,
+    /// Runs every check (minimum payout, risk rules) without placing the trade.
+    pub dry_run: bool,
+    /// Rejects the trade if the payout of the asset is under this percentage, or unknown.
+    pub min_payout: Option<i32>,
+}
+
+impl OrderOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn tag(mut self, tag: impl ToString) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn min_payout(mut self, percent: i32) -> Self {
+        self.min_payout = Some(percent);
+        self
+    }
+
+    /// Checks the payout of `asset` against `min_payout`, an unknown payout is rejected.
+    pub fn check_payout(&self, asset: &str, payout: Option<i32>) -> PocketResult<()> {
+        check_min_payout(asset, payout, self.min_payout)
+    }
+}
+
+/// Rejects a trade on `asset` with a `PayoutTooLow` error if its payout is under `min_payout`, or
+/// unknown.
+pub fn check_min_payout(
+    asset: &str,
+    payout: Option<i32>,
+    min_payout: Option<i32>,
+) -> PocketResult<()> {
+    match (min_payout, payout) {
+        (None, _) => Ok(()),
+        (Some(min), Some(payout)) if payout >= min => Ok(()),
+        (Some(min_payout), payout) => Err(PocketOptionError::PayoutTooLow {
+            asset: asset.to_string(),
+            payout,
+            min_payout,
+        }),
+    }
+}
+
+/// Rejects a trade on `asset` with an `AssetClosed` error if its market is closed, an asset the
+/// server didn't send yet is let through.
+pub fn check_market_open(asset: &str, is_open: Option<bool>) -> PocketResult<()> {
+    match is_open {
+        Some(false) => Err(PocketOptionError::AssetClosed(asset.to_string())),
+        _ => Ok(()),
+    }
+}
+
+/// Result of `PocketOption::place_order`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum OrderPlacement {
+    /// The trade was placed.
+    Placed { id: Uuid, deal: Deal },
+    /// Dry run, the trade passed every check but wasn't placed.
+    DryRun {
+        asset: String,
+        direction: Direction,
+        amount: f64,
+        expiry: u32,
+        payout: Option<i32>,
+    },
+}
+
+impl OrderPlacement {
+    /// Id and details of the trade, `None` for a dry run.
+    pub fn placed(self) -> Option<(Uuid, Deal)> {
+        match self {
+            Self::Placed { id, deal } => Some((id, deal)),
+            Self::DryRun { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order_options() -> anyhow::Result<()> {
+        let options = OrderOptions::new().tag("breakout").min_payout(80);
+        assert!(options.check_payout("EURUSD_otc", Some(85)).is_ok());
+        assert!(matches!(
+            options.check_payout("EURUSD_otc", Some(79)),
+            Err(PocketOptionError::PayoutTooLow {
+                payout: Some(79),
+                min_payout: 80,
+                ..
+            })
+        ));
+        assert!(options.check_payout("EURUSD_otc", None).is_err());
+        assert!(OrderOptions::new().check_payout("EURUSD_otc", None).is_ok());
+
+        assert!(check_market_open("EURUSD", Some(true)).is_ok());
+        assert!(check_market_open("EURUSD", None).is_ok());
+        assert!(matches!(
+            check_market_open("EURUSD", Some(false)),
+            Err(PocketOptionError::AssetClosed(asset)) if asset == "EURUSD"
+        ));
+
+        let options: OrderOptions = serde_json::from_str(r#"{"tags": ["news"], "dry_run": true}"#)?;
+        assert_eq!(options.tags, vec!["news".to_string()]);
+        assert!(options.dry_run && options.min_payout.is_none());
+
+        assert_eq!("BUY".parse::<Direction>()?, Direction::Call);
+        assert_eq!("put".parse::<Direction>()?, Direction::Put);
+        assert!("long".parse::<Direction>().is_err());
+
+        let dry_run = OrderPlacement::DryRun {
+            asset: "EURUSD_otc".into(),
+            direction: Direction::Put,
+            amount: 1.0,
+            expiry: 60,
+            payout: Some(92),
+        };
+        assert_eq!(
+            serde_json::to_value(&dry_run)?["status"],
+            serde_json::json!("dry_run")
+        );
+        assert!(dry_run.placed().is_none());
+        Ok(())
+    }
+}
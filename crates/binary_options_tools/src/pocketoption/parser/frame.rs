@@ -0,0 +1,275 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use binary_options_tools_core::error::BinaryOptionsToolsError;
+
+use crate::pocketoption::{
+    error::PocketResult,
+    types::{account::AccountEvent, info::MessageInfo},
+};
+
+use super::message::WebSocketMessage;
+
+/// Result of parsing a single frame, used to triage unknown or changed messages.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrameReport {
+    /// Name of the event, if the frame contains one.
+    pub event: Option<String>,
+    /// Type the payload was parsed as.
+    pub info: MessageInfo,
+    /// `true` if the frame is a `451-` header, its payload is sent in the next frame.
+    pub awaiting_payload: bool,
+    /// `true` if the payload was parsed by one of the models (not kept as raw data).
+    pub parsed: bool,
+    /// Debug representation of the parsed message.
+    pub message: Option<String>,
+    pub error: Option<String>,
+}
+
+impl FrameReport {
+    fn header(event: String, info: MessageInfo) -> Self {
+        Self {
+            event: Some(event),
+            info,
+            awaiting_payload: true,
+            parsed: true,
+            message: None,
+            error: None,
+        }
+    }
+
+    fn payload(event: Option<String>, payload: &str, info: &MessageInfo) -> Self {
+        let account_event = event
+            .as_ref()
+            .filter(|_| info.get_raw().is_some())
+            .and_then(|e| AccountEvent::new(e, serde_json::from_str(payload).ok()?));
+        let result = match account_event {
+            Some(account_event) => Ok(WebSocketMessage::AccountEvent(account_event)),
+            None => WebSocketMessage::try_parse_with_context(payload, info),
+        };
+        match result {
+            Ok(message) => Self {
+                event,
+                info: message.information(),
+                awaiting_payload: false,
+                parsed: message.get_raw().is_none(),
+                message: Some(format!("{message:?}")),
+                error: None,
+            },
+            Err(e) => Self {
+                event,
+                info: info.clone(),
+                awaiting_payload: false,
+                parsed: false,
+                message: None,
+                error: Some(e.to_string()),
+            },
+        }
+    }
+}
+
+/// Parses a frame as sent by the server, without a connection, to check how the models handle it.
+///
+/// Supported frames are `451-["event",{"_placeholder":true,"num":0}]` headers, `42["event",data]`
+/// messages and bare payloads, parsed as the payload of `context` (the event of the previous header)
+/// if it's given.
+pub fn parse_frame(frame: &str, context: Option<&str>) -> FrameReport {
+    let frame = frame.trim();
+    if let Some(header) = frame.strip_prefix("451-") {
+        return match serde_json::from_str::<(String, Value)>(header) {
+            Ok((event, _)) => {
+                let info = event_info(&event);
+                FrameReport::header(event, info)
+            }
+            Err(e) => FrameReport {
+                event: None,
+                info: MessageInfo::None,
+                awaiting_payload: false,
+                parsed: false,
+                message: None,
+                error: Some(e.to_string()),
+            },
+        };
+    }
+    if let Some(Ok((event, data))) = frame
+        .strip_prefix("42")
+        .map(serde_json::from_str::<(String, Value)>)
+    {
+        let info = event_info(&event);
+        return FrameReport::payload(Some(event), &data.to_string(), &info);
+    }
+    match context {
+        Some(event) => FrameReport::payload(Some(event.to_string()), frame, &event_info(event)),
+        None => FrameReport::payload(None, frame, &MessageInfo::None),
+    }
+}
+
+/// Returns true if the frame sends an order (`openOrder`, `openPendingOrder` or `closeOrder`), used
+/// to keep the raw messages of an observer client from trading. The event is read from the first
+/// element of any `42` / `451-` frame (or other binary event) whatever its other elements, the
+/// frames whose event can't be read are treated as orders.
+pub fn is_order_frame(frame: &str) -> bool {
+    let frame = frame.trim();
+    let digits = |c: char| c.is_ascii_digit();
+    let message = match (frame.strip_prefix("42"), frame.strip_prefix("45")) {
+        // Skips the acknowledgement id of the socket.io events (`421["event",...]`)
+        (Some(message), _) => message.trim_start_matches(digits),
+        // Binary events, with their number of attachments (`451-["event",...]`)
+        (_, Some(message)) => message.trim_start_matches(digits).trim_start_matches('-'),
+        _ => return false,
+    };
+    match frame_event(message) {
+        Some(event) => matches!(
+            event_info(&event),
+            MessageInfo::OpenOrder | MessageInfo::OpenPendingOrder | MessageInfo::CloseOrder
+        ),
+        None => true,
+    }
+}
+
+/// Name of the event of a socket.io message, the first element of its array.
+fn frame_event(message: &str) -> Option<String> {
+    match serde_json::from_str::<Value>(message).ok()? {
+        Value::Array(items) => items.into_iter().next()?.as_str().map(str::to_string),
+        _ => None,
+    }
+}
+
+fn event_info(event: &str) -> MessageInfo {
+    serde_json::from_value(Value::String(event.to_string()))
+        .unwrap_or_else(|_| MessageInfo::Raw(event.to_string()))
+}
+
+/// Captured frame of the golden corpus, stored as `{"event": ..., "payload": ...}` json files.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fixture {
+    #[serde(default)]
+    pub name: String,
+    pub event: String,
+    pub payload: Value,
+}
+
+impl Fixture {
+    /// Parses the payload of the fixture with the models of its event.
+    pub fn check(&self) -> FrameReport {
+        parse_frame(&self.payload.to_string(), Some(&self.event))
+    }
+}
+
+/// Loads every `.json` fixture in `dir`, sorted by name (the name of the file without its extension).
+pub fn load_fixtures(dir: impl AsRef<Path>) -> PocketResult<Vec<Fixture>> {
+    let mut fixtures = Vec::new();
+    for entry in std::fs::read_dir(dir).map_err(BinaryOptionsToolsError::from)? {
+        let path = entry.map_err(BinaryOptionsToolsError::from)?.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let content = std::fs::read_to_string(&path).map_err(BinaryOptionsToolsError::from)?;
+        let mut fixture: Fixture = serde_json::from_str(&content)?;
+        if let Some(name) = path.file_stem() {
+            fixture.name = name.to_string_lossy().into_owned();
+        }
+        fixtures.push(fixture);
+    }
+    fixtures.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(fixtures)
+}
+
+/// Checks every fixture in `dir`, returning the report of each one.
+pub fn check_fixtures(dir: impl AsRef<Path>) -> PocketResult<Vec<(String, FrameReport)>> {
+    Ok(load_fixtures(dir)?
+        .into_iter()
+        .map(|f| {
+            let report = f.check();
+            (f.name, report)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_golden_corpus() -> anyhow::Result<()> {
+        let reports = check_fixtures("tests/corpus")?;
+        assert!(!reports.is_empty());
+        for (name, report) in reports {
+            assert!(
+                report.parsed && report.error.is_none(),
+                "Fixture '{name}' wasn't parsed: {report:?}"
+            );
+            assert_ne!(report.info, MessageInfo::None, "{name}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_frame() {
+        let header = parse_frame(
+            r#"451-["successopenOrder",{"_placeholder":true,"num":0}]"#,
+            None,
+        );
+        assert!(header.awaiting_payload);
+        assert_eq!(header.info, MessageInfo::SuccessopenOrder);
+
+        let unknown = parse_frame(
+            r#"451-["someNewEvent",{"_placeholder":true,"num":0}]"#,
+            None,
+        );
+        assert_eq!(unknown.info, MessageInfo::Raw("someNewEvent".into()));
+
+        let stream = parse_frame(
+            r#"[["EURUSD_otc",1732830108.205,1.08543]]"#,
+            Some("updateStream"),
+        );
+        assert!(stream.parsed);
+        assert_eq!(stream.info, MessageInfo::UpdateStream);
+
+        let invalid = parse_frame(r#"{"profit": "abc"}"#, Some("successcloseOrder"));
+        assert!(!invalid.parsed);
+        assert!(invalid.error.is_some());
+
+        let notice = parse_frame(r#"42["bonus/credited",{"amount":10}]"#, None);
+        assert!(notice.parsed);
+        assert_eq!(notice.info, MessageInfo::AccountEvent);
+    }
+
+    #[test]
+    fn test_is_order_frame() {
+        assert!(is_order_frame(
+            r#"42["openOrder",{"asset":"EURUSD_otc","amount":1.0,"action":"call","isDemo":1,"requestId":1,"optionType":100,"time":60}]"#
+        ));
+        assert!(is_order_frame(
+            r#"451-["openPendingOrder",{"_placeholder":true,"num":0}]"#
+        ));
+        assert!(is_order_frame(
+            r#"42["closeOrder",{"ticket":"0a251728-4c1c-4b37-b770-0b8d9fffa941"}]"#
+        ));
+        assert!(!is_order_frame(
+            r#"42["changeSymbol",{"asset":"EURUSD_otc","period":60}]"#
+        ));
+        assert!(!is_order_frame(r#"42["signals/subscribe"]"#));
+        assert!(!is_order_frame("2"));
+        assert!(!is_order_frame(r#"40{"sid":"abc"}"#));
+
+        // The event is read whatever the number of elements, with an acknowledgement id or not
+        assert!(is_order_frame(
+            r#"42["openOrder",{"asset":"EURUSD_otc","amount":1.0,"action":"call","isDemo":1,"requestId":1,"optionType":100,"time":60},1]"#
+        ));
+        assert!(is_order_frame(r#"42["closeOrder"]"#));
+        assert!(is_order_frame(r#"421["openOrder",{}]"#));
+        assert!(is_order_frame(
+            r#"452-["closeOrder",{"_placeholder":true,"num":0}]"#
+        ));
+        assert!(!is_order_frame(r#"42["changeSymbol",{},1]"#));
+
+        // Frames whose event can't be read are rejected
+        assert!(is_order_frame(r#"42["openOrder",{"asset":"#));
+        assert!(is_order_frame(r#"42{"event":"openOrder"}"#));
+        assert!(is_order_frame("42[]"));
+        assert!(is_order_frame(r#"42[1,"openOrder"]"#));
+    }
+}
@@ -0,0 +1,53 @@
+//! JSON backend of the message parsing, `serde_json` by default or `simd-json` with the `simd-json`
+//! feature. Both return a `serde_json::Error` so the callers don't depend on the backend.
+
+use serde::de::DeserializeOwned;
+
+use binary_options_tools_core::utils::pool::{BufferPool, PoolStats};
+
+/// Scratch buffers of the parser, simd-json parses in place so every message is copied into one.
+static BUFFERS: BufferPool = BufferPool::new(32, 1 << 20);
+
+/// Name of the backend the messages are parsed with.
+pub const JSON_BACKEND: &str = if cfg!(feature = "simd-json") {
+    "simd-json"
+} else {
+    "serde_json"
+};
+
+/// Parses `data` with the configured backend.
+#[cfg(feature = "simd-json")]
+pub fn from_str<T: DeserializeOwned>(data: &str) -> Result<T, serde_json::Error> {
+    // simd-json parses in place, on failure the payload is parsed again with serde_json to get the error
+    let mut bytes = BUFFERS.acquire();
+    bytes.extend_from_slice(data.as_bytes());
+    simd_json::serde::from_slice(&mut bytes).or_else(|_| serde_json::from_str(data))
+}
+
+/// Parses `data` with the configured backend.
+#[cfg(not(feature = "simd-json"))]
+pub fn from_str<T: DeserializeOwned>(data: &str) -> Result<T, serde_json::Error> {
+    serde_json::from_str(data)
+}
+
+/// Statistics of the scratch buffers of the parser, they are only used by the `simd-json` backend.
+pub fn buffer_stats() -> PoolStats {
+    BUFFERS.stats()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pocketoption::types::update::UpdateStream;
+
+    #[test]
+    fn test_backend_parsing() -> anyhow::Result<()> {
+        let data = r#"[["EURUSD_otc",1732830108.205,"1,08543"]]"#;
+        let stream: UpdateStream = from_str(data)?;
+        let expected: UpdateStream = serde_json::from_str(data)?;
+        assert_eq!(stream.0[0].price, expected.0[0].price);
+        assert_eq!(stream.0[0].time, expected.0[0].time);
+        assert!(from_str::<UpdateStream>(r#"[["EURUSD_otc"]]"#).is_err());
+        Ok(())
+    }
+}
@@ -2,33 +2,35 @@ use core::fmt;
 use std::vec;
 
 use serde::Deserialize;
-use serde_json::{Value, from_str};
+use serde_json::Value;
 use tracing::warn;
 
 use binary_options_tools_core::{
-    general::traits::MessageTransfer,
+    general::{priority::Priority, traits::MessageTransfer},
     reimports::{Bytes, Message},
 };
 
 use crate::pocketoption::{
     error::PocketResult,
     types::{
+        account::AccountEvent,
         base::{ChangeSymbol, RawWebsocketMessage, SubscribeSymbol},
         info::MessageInfo,
         order::{
-            Deal, FailOpenOrder, FailOpenPendingOrder, OpenOrder, OpenPendingOrder,
-            PocketMessageFail, SuccessCloseOrder, SuccessOpenPendingOrder, UpdateClosedDeals,
-            UpdateOpenedDeals,
+            CloseOrder, Deal, FailCloseOrder, FailOpenOrder, FailOpenPendingOrder, OpenOrder,
+            OpenPendingOrder, PocketMessageFail, SuccessCloseOrder, SuccessOpenPendingOrder,
+            UpdateClosedDeals, UpdateOpenedDeals,
         },
         success::SuccessAuth,
         update::{
-            LoadHistoryPeriodResult, UpdateAssets, UpdateBalance, UpdateHistoryNewFast, UpdateStream,
+            LoadHistoryPeriodResult, UpdateAssets, UpdateBalance, UpdateHistoryNewFast,
+            UpdateStream,
         },
     },
     ws::ssid::Ssid,
 };
 
-use super::basic::LoadHistoryPeriod;
+use super::{basic::LoadHistoryPeriod, json::from_str};
 
 #[derive(Debug, Deserialize, Clone)]
 #[serde(untagged)]
@@ -59,6 +61,10 @@ pub enum WebSocketMessage {
     SuccessupdatePending(Value),
     OpenPendingOrder(OpenPendingOrder),
     SuccessOpenPendingOrder(SuccessOpenPendingOrder),
+    FailCloseOrder(FailCloseOrder),
+    CloseOrder(CloseOrder),
+    #[serde(skip)]
+    AccountEvent(AccountEvent),
 
     Raw(RawWebsocketMessage),
     None,
@@ -90,91 +96,58 @@ impl WebSocketMessage {
 
     pub fn parse_with_context(data: impl ToString, previous: &MessageInfo) -> Self {
         let data = data.to_string();
-        match previous {
-            MessageInfo::OpenOrder => {
-                if let Ok(order) = from_str::<OpenOrder>(&data) {
-                    return Self::OpenOrder(order);
-                }
-            }
-            MessageInfo::UpdateStream => {
-                if let Ok(stream) = from_str::<UpdateStream>(&data) {
-                    return Self::UpdateStream(stream);
-                }
+        match Self::try_parse_with_context(&data, previous) {
+            Ok(message) => message,
+            Err(e) => {
+                warn!(
+                    "Failed to parse message of type '{previous}', {e}:\n {data}, parsing it as raw data"
+                );
+                WebSocketMessage::Raw(RawWebsocketMessage::from(data))
             }
+        }
+    }
+
+    /// Parses the payload of a message of type `previous`, returning the parsing error instead of
+    /// falling back to a raw message.
+    pub fn try_parse_with_context(
+        data: &str,
+        previous: &MessageInfo,
+    ) -> Result<Self, serde_json::Error> {
+        match previous {
+            MessageInfo::OpenOrder => from_str::<OpenOrder>(data).map(Self::OpenOrder),
+            MessageInfo::UpdateStream => from_str::<UpdateStream>(data).map(Self::UpdateStream),
             MessageInfo::UpdateHistoryNew => {
-                if let Ok(history) = from_str::<UpdateHistoryNewFast>(&data) {
-                    return Self::UpdateHistoryNew(history);
-                }
+                from_str::<UpdateHistoryNewFast>(data).map(Self::UpdateHistoryNew)
             }
             MessageInfo::UpdateHistoryNewFast => {
-                if let Ok(history) = from_str::<UpdateHistoryNewFast>(&data) {
-                    return Self::UpdateHistoryNewFast(history);
-                }
-            }
-            MessageInfo::UpdateAssets => {
-                if let Ok(assets) = from_str::<UpdateAssets>(&data) {
-                    return Self::UpdateAssets(assets);
-                }
-            }
-            MessageInfo::UpdateBalance => {
-                if let Ok(balance) = from_str::<UpdateBalance>(&data) {
-                    return Self::UpdateBalance(balance);
-                }
+                from_str::<UpdateHistoryNewFast>(data).map(Self::UpdateHistoryNewFast)
             }
+            MessageInfo::UpdateAssets => from_str::<UpdateAssets>(data).map(Self::UpdateAssets),
+            MessageInfo::UpdateBalance => from_str::<UpdateBalance>(data).map(Self::UpdateBalance),
             MessageInfo::SuccesscloseOrder => {
-                if let Ok(order) = from_str::<SuccessCloseOrder>(&data) {
-                    return Self::SuccesscloseOrder(order);
-                }
-            }
-            MessageInfo::Auth => {
-                if let Ok(auth) = from_str::<Ssid>(&data) {
-                    return Self::Auth(auth);
-                }
-            }
-            MessageInfo::ChangeSymbol => {
-                if let Ok(symbol) = from_str::<ChangeSymbol>(&data) {
-                    return Self::ChangeSymbol(symbol);
-                }
+                from_str::<SuccessCloseOrder>(data).map(Self::SuccesscloseOrder)
             }
+            MessageInfo::Auth => from_str::<Ssid>(data).map(Self::Auth),
+            MessageInfo::ChangeSymbol => from_str::<ChangeSymbol>(data).map(Self::ChangeSymbol),
             MessageInfo::SuccessupdateBalance => {
-                if let Ok(balance) = from_str::<UpdateBalance>(&data) {
-                    return Self::SuccessupdateBalance(balance);
-                }
+                from_str::<UpdateBalance>(data).map(Self::SuccessupdateBalance)
             }
             MessageInfo::SuccessupdatePending => {
-                if let Ok(pending) = from_str::<Value>(&data) {
-                    return Self::SuccessupdatePending(pending);
-                }
+                from_str::<Value>(data).map(Self::SuccessupdatePending)
             }
             MessageInfo::SubscribeSymbol => {
-                if let Ok(symbol) = from_str::<SubscribeSymbol>(&data) {
-                    return Self::SubscribeSymbol(symbol);
-                }
-            }
-            MessageInfo::Successauth => {
-                if let Ok(auth) = from_str::<SuccessAuth>(&data) {
-                    return Self::SuccessAuth(auth);
-                }
+                from_str::<SubscribeSymbol>(data).map(Self::SubscribeSymbol)
             }
+            MessageInfo::Successauth => from_str::<SuccessAuth>(data).map(Self::SuccessAuth),
             MessageInfo::UpdateOpenedDeals => {
-                if let Ok(deals) = from_str::<UpdateOpenedDeals>(&data) {
-                    return Self::UpdateOpenedDeals(deals);
-                }
+                from_str::<UpdateOpenedDeals>(data).map(Self::UpdateOpenedDeals)
             }
             MessageInfo::UpdateClosedDeals => {
-                if let Ok(deals) = from_str::<UpdateClosedDeals>(&data) {
-                    return Self::UpdateClosedDeals(deals);
-                }
-            }
-            MessageInfo::SuccessopenOrder => {
-                if let Ok(order) = from_str::<Deal>(&data) {
-                    return Self::SuccessopenOrder(order);
-                }
+                from_str::<UpdateClosedDeals>(data).map(Self::UpdateClosedDeals)
             }
+            MessageInfo::SuccessopenOrder => from_str::<Deal>(data).map(Self::SuccessopenOrder),
             MessageInfo::LoadHistoryPeriod => {
-                if let Ok(history) = from_str::<LoadHistoryPeriodResult>(&data) {
-                    return Self::LoadHistoryPeriod(history);
-                }
+                from_str::<LoadHistoryPeriodResult>(data).map(Self::LoadHistoryPeriod)
             }
             // MessageInfo::UpdateCharts => {
             //     return Err(PocketOptionError::GeneralParsingError(
@@ -183,42 +156,32 @@ impl WebSocketMessage {
             //     ));
             //     // TODO: Add this
             // }
-            MessageInfo::GetCandles => {
-                if let Ok(candles) = from_str::<LoadHistoryPeriod>(&data) {
-                    return Self::GetCandles(candles);
-                }
-            }
-            MessageInfo::FailopenOrder => {
-                if let Ok(fail) = from_str::<FailOpenOrder>(&data) {
-                    return Self::FailOpenOrder(fail);
-                }
-            }
+            MessageInfo::GetCandles => from_str::<LoadHistoryPeriod>(data).map(Self::GetCandles),
+            MessageInfo::FailopenOrder => from_str::<FailOpenOrder>(data).map(Self::FailOpenOrder),
             MessageInfo::FailopenPendingOrder => {
-                if let Ok(fail) = from_str::<FailOpenPendingOrder>(&data) {
-                    return Self::FailOpenPendingOrder(fail);
-                }
+                from_str::<FailOpenPendingOrder>(data).map(Self::FailOpenPendingOrder)
             }
             MessageInfo::OpenPendingOrder => {
-                if let Ok(order) = from_str::<OpenPendingOrder>(&data) {
-                    return Self::OpenPendingOrder(order);
-                }
+                from_str::<OpenPendingOrder>(data).map(Self::OpenPendingOrder)
             }
             MessageInfo::SuccessopenPendingOrder => {
-                if let Ok(order) = from_str::<SuccessOpenPendingOrder>(&data) {
-                    return Self::SuccessOpenPendingOrder(order);
-                }
-            }
-            MessageInfo::Raw(content) => {
-                return WebSocketMessage::Raw(RawWebsocketMessage::from(content.to_owned()));
-            }
+                from_str::<SuccessOpenPendingOrder>(data).map(Self::SuccessOpenPendingOrder)
+            }
+            MessageInfo::CloseOrder => from_str::<CloseOrder>(data).map(Self::CloseOrder),
+            MessageInfo::FailcloseOrder => {
+                from_str::<FailCloseOrder>(data).map(Self::FailCloseOrder)
+            }
+            // The name of the event isn't known here, `Handler` builds these messages
+            MessageInfo::AccountEvent => Err(serde::de::Error::custom(
+                "account events can only be parsed with the name of the event",
+            )),
+            MessageInfo::Raw(content) => Ok(WebSocketMessage::Raw(RawWebsocketMessage::from(
+                content.to_owned(),
+            ))),
             MessageInfo::None => {
-                if let Ok(message) = WebSocketMessage::parse(data.clone()) {
-                    return message;
-                }
+                WebSocketMessage::parse(data).map_err(|e| serde::de::Error::custom(e.to_string()))
             }
         }
-        warn!("Failed to parse message of type '{previous}':\n {data}, parsing it as raw data");
-        WebSocketMessage::Raw(RawWebsocketMessage::from(data))
     }
 
     pub fn information(&self) -> MessageInfo {
@@ -245,6 +208,9 @@ impl WebSocketMessage {
             Self::FailOpenPendingOrder(_) => MessageInfo::FailopenPendingOrder,
             Self::SuccessOpenPendingOrder(_) => MessageInfo::SuccessopenPendingOrder,
             Self::OpenPendingOrder(_) => MessageInfo::OpenPendingOrder,
+            Self::CloseOrder(_) => MessageInfo::CloseOrder,
+            Self::FailCloseOrder(_) => MessageInfo::FailcloseOrder,
+            Self::AccountEvent(_) => MessageInfo::AccountEvent,
             Self::Raw(_) => MessageInfo::None,
             Self::Subfor(_) => MessageInfo::None,
             Self::Unsubfor(_) => MessageInfo::None,
@@ -290,13 +256,22 @@ impl fmt::Display for WebSocketMessage {
                     serde_json::to_string(open_order).map_err(|_| fmt::Error)?
                 )
             }
+            WebSocketMessage::CloseOrder(close_order) => {
+                write!(
+                    f,
+                    "42[{},{}]",
+                    serde_json::to_string(&MessageInfo::CloseOrder).map_err(|_| fmt::Error)?,
+                    serde_json::to_string(close_order).map_err(|_| fmt::Error)?
+                )
+            }
             WebSocketMessage::SubscribeSymbol(subscribe_symbol) => {
                 write!(f, "{:?}", subscribe_symbol)
             }
             WebSocketMessage::Raw(text) => text.fmt(f),
 
             WebSocketMessage::UpdateStream(update_stream) => write!(f, "{:?}", update_stream),
-            WebSocketMessage::UpdateHistoryNewFast(update_history_new) | WebSocketMessage::UpdateHistoryNew(update_history_new)=> {
+            WebSocketMessage::UpdateHistoryNewFast(update_history_new)
+            | WebSocketMessage::UpdateHistoryNew(update_history_new) => {
                 write!(f, "{:?}", update_history_new)
             }
             WebSocketMessage::UpdateAssets(update_assets) => write!(f, "{:?}", update_assets),
@@ -320,6 +295,7 @@ impl fmt::Display for WebSocketMessage {
             WebSocketMessage::SuccessOpenPendingOrder(order) => write!(f, "{:?}", order),
             WebSocketMessage::FailOpenPendingOrder(order) => write!(f, "{:?}", order),
             WebSocketMessage::OpenPendingOrder(order) => write!(f, "{:?}", order),
+            WebSocketMessage::AccountEvent(event) => write!(f, "{:?}", event),
 
             WebSocketMessage::None => write!(f, "None"),
             // 42["loadHistoryPeriod",{"asset":"#AXP_otc","index":173384282247,"time":1733482800,"offset":540000,"period":3600}]
@@ -333,6 +309,7 @@ impl fmt::Display for WebSocketMessage {
                 )
             }
             WebSocketMessage::FailOpenOrder(order) => order.fmt(f),
+            WebSocketMessage::FailCloseOrder(order) => order.fmt(f),
             WebSocketMessage::SuccessupdatePending(pending) => pending.fmt(f),
             WebSocketMessage::Subfor(sub) => write!(f, "42[\"subfor\",{}]", sub),
             WebSocketMessage::Unsubfor(unsub) => write!(f, "42[\"unsubfor\",{}]", unsub),
@@ -369,29 +346,39 @@ impl MessageTransfer for WebSocketMessage {
     }
 
     fn error(&self) -> Option<Self::Error> {
-        if let Self::FailOpenOrder(fail) = self {
-            return Some(PocketMessageFail::Order(fail.to_owned()));
+        match self {
+            Self::FailOpenOrder(fail) => Some(PocketMessageFail::Order(fail.to_owned())),
+            Self::FailCloseOrder(fail) => Some(PocketMessageFail::Close(fail.to_owned())),
+            _ => None,
         }
-        None
     }
 
     fn to_error(&self) -> Self::TransferError {
-        if let Self::FailOpenOrder(fail) = self {
-            PocketMessageFail::Order(fail.to_owned())
-        } else {
-            PocketMessageFail::Order(FailOpenOrder::new(
+        match self {
+            Self::FailOpenOrder(fail) => PocketMessageFail::Order(fail.to_owned()),
+            Self::FailCloseOrder(fail) => PocketMessageFail::Close(fail.to_owned()),
+            _ => PocketMessageFail::Order(FailOpenOrder::new(
                 "This is unexpected and should never happend",
                 1.0,
                 "None",
-            ))
+            )),
         }
     }
 
     fn error_info(&self) -> Option<Vec<Self::Info>> {
-        if let Self::FailOpenOrder(_) = self {
-            Some(vec![MessageInfo::SuccessopenOrder])
-        } else {
-            None
+        match self {
+            Self::FailOpenOrder(_) => Some(vec![MessageInfo::SuccessopenOrder]),
+            // A failure to close a deal is sent by `PocketData` to the `close_deal` of its trade, not
+            // to the unrelated requests waiting for a closed deal like `check_win`
+            _ => None,
+        }
+    }
+
+    fn priority(&self) -> Priority {
+        match self {
+            Self::OpenOrder(_) | Self::OpenPendingOrder(_) | Self::CloseOrder(_) => Priority::Order,
+            Self::ChangeSymbol(_) | Self::Subfor(_) | Self::Unsubfor(_) => Priority::Subscription,
+            _ => Priority::Misc,
         }
     }
 }
@@ -417,8 +404,8 @@ mod tests {
                 let mut file_names = Vec::new();
 
                 for entry in entries {
-                    let file_name = entry?.file_name().to_string_lossy().to_string();
-                    file_names.push(format!("{path}/{file_name}"));
+                    let file_name = entry?.file_name();
+                    file_names.push(dir_path.join(file_name).to_string_lossy().to_string());
                 }
 
                 Ok(file_names)
@@ -481,4 +468,104 @@ mod tests {
         file.write_all(data.as_bytes())?;
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_fail_close_order_routing() -> anyhow::Result<()> {
+        use std::time::Duration;
+
+        use binary_options_tools_core::{
+            constants::MAX_CHANNEL_CAPACITY,
+            general::{send::SenderMessage, types::Data},
+        };
+        use serde_json::json;
+        use uuid::Uuid;
+
+        use crate::pocketoption::{types::data::PocketData, validators::order_result_validator};
+
+        let (closed, kept) = (Uuid::new_v4(), Uuid::new_v4());
+        let data = Data::new(PocketData::default());
+        let (sender, _queue) = SenderMessage::new(MAX_CHANNEL_CAPACITY);
+        // `check_win` on one trade while `close_deal` waits for the sell-out of another one
+        let check_win = {
+            let (sender, data) = (sender.clone(), data.clone());
+            tokio::spawn(async move {
+                sender
+                    .send_message_with_timout(
+                        Duration::from_secs(1),
+                        "CheckResult",
+                        &data,
+                        WebSocketMessage::None,
+                        MessageInfo::SuccesscloseOrder,
+                        &order_result_validator(kept),
+                    )
+                    .await
+            })
+        };
+        let mut close_deal = data.wait_close_refusal(closed);
+        // Another `close_deal` running at the same time, on a third trade
+        let mut other_close = data.wait_close_refusal(Uuid::new_v4());
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let fail: FailCloseOrder =
+            serde_json::from_value(json!({"ticket": closed, "error": "too late"}))?;
+        assert_eq!(fail.ticket(), Some(closed));
+        let deal: Deal = serde_json::from_value(json!({
+            "id": kept,
+            "openTime": "2024-12-04 23:58:48",
+            "closeTime": "2024-12-04 23:59:48",
+            "openTimestamp": 1733356728,
+            "closeTimestamp": 1733356788,
+            "uid": 1,
+            "isDemo": 1,
+            "amount": 10.0,
+            "profit": 9.2,
+            "percentProfit": 92,
+            "percentLoss": 100,
+            "openPrice": 1.1,
+            "copyTicket": "",
+            "closePrice": 1.2,
+            "command": 1,
+            "asset": "EURUSD_otc",
+            "openMs": 601,
+            "optionType": 100,
+            "currency": "USD"
+        }))?;
+        for message in [
+            WebSocketMessage::FailCloseOrder(fail),
+            WebSocketMessage::SuccesscloseOrder(SuccessCloseOrder {
+                profit: 9.2,
+                deals: vec![deal],
+            }),
+        ] {
+            for sender in data.update_data(message.clone()).await?.unwrap_or_default() {
+                sender.send(message.clone()).await?;
+            }
+        }
+
+        // The refused sell-out only reaches the `close_deal` of its trade, `check_win` still gets
+        // its result
+        assert_eq!(close_deal.recv().await.ticket(), Some(closed));
+        assert!(matches!(
+            check_win.await??,
+            WebSocketMessage::SuccesscloseOrder(order) if order.deals[0].id == kept
+        ));
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), other_close.recv())
+                .await
+                .is_err()
+        );
+
+        // A refusal without a ticket isn't attributed while several trades are closing, and the
+        // waits are removed once dropped
+        let unknown: FailCloseOrder = serde_json::from_value(json!({"error": "unknown"}))?;
+        let third = data.wait_close_refusal(Uuid::new_v4());
+        data.update_data(WebSocketMessage::FailCloseOrder(unknown.clone()))
+            .await?;
+        drop(close_deal);
+        drop(third);
+        data.update_data(WebSocketMessage::FailCloseOrder(unknown))
+            .await?;
+        assert_eq!(other_close.recv().await.ticket(), None);
+        Ok(())
+    }
 }
@@ -1,2 +1,4 @@
 pub mod basic;
+pub mod frame;
+pub mod json;
 pub mod message;
@@ -0,0 +1,424 @@
+//! Detection of the classic candlestick patterns (engulfing, doji, pin bars, inside bars, three
+//! soldiers / crows and stars) on closed candles, either on a stream candle by candle with a
+//! `PatternDetector` or on a history with `scan`.
+//!
+//! The confidence of a pattern (0 to 1) measures how clearly the candles match its textbook
+//! shape, not how likely the price is to move in the direction of its bias.
+
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    error::{PocketOptionError, PocketResult},
+    types::update::DataCandle,
+};
+
+/// Largest number of candles a pattern spans.
+const MAX_PATTERN_CANDLES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CandlePattern {
+    BullishEngulfing,
+    BearishEngulfing,
+    Doji,
+    /// Long lower wick rejecting lower prices (hammer).
+    BullishPinBar,
+    /// Long upper wick rejecting higher prices (shooting star).
+    BearishPinBar,
+    /// Range inside the range of the previous candle.
+    InsideBar,
+    ThreeWhiteSoldiers,
+    ThreeBlackCrows,
+    MorningStar,
+    EveningStar,
+}
+
+impl CandlePattern {
+    pub const ALL: [CandlePattern; 10] = [
+        Self::BullishEngulfing,
+        Self::BearishEngulfing,
+        Self::Doji,
+        Self::BullishPinBar,
+        Self::BearishPinBar,
+        Self::InsideBar,
+        Self::ThreeWhiteSoldiers,
+        Self::ThreeBlackCrows,
+        Self::MorningStar,
+        Self::EveningStar,
+    ];
+
+    pub fn bias(&self) -> PatternBias {
+        match self {
+            Self::BullishEngulfing
+            | Self::BullishPinBar
+            | Self::ThreeWhiteSoldiers
+            | Self::MorningStar => PatternBias::Bullish,
+            Self::BearishEngulfing
+            | Self::BearishPinBar
+            | Self::ThreeBlackCrows
+            | Self::EveningStar => PatternBias::Bearish,
+            Self::Doji | Self::InsideBar => PatternBias::Neutral,
+        }
+    }
+
+    /// Number of candles the pattern spans.
+    pub fn candles(&self) -> usize {
+        match self {
+            Self::Doji | Self::BullishPinBar | Self::BearishPinBar => 1,
+            Self::BullishEngulfing | Self::BearishEngulfing | Self::InsideBar => 2,
+            Self::ThreeWhiteSoldiers
+            | Self::ThreeBlackCrows
+            | Self::MorningStar
+            | Self::EveningStar => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PatternBias {
+    Bullish,
+    Bearish,
+    /// Indecision (doji) or consolidation (inside bar).
+    Neutral,
+}
+
+/// Pattern found on the last candles of a stream or a history.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PatternEvent {
+    pub pattern: CandlePattern,
+    pub bias: PatternBias,
+    /// Time of the first candle of the pattern.
+    pub start: DateTime<Utc>,
+    /// Time of the candle completing the pattern.
+    pub time: DateTime<Utc>,
+    /// How clearly the candles match the pattern, from 0 to 1.
+    pub confidence: f64,
+}
+
+/// Thresholds of the patterns, the ratios are relative to the range (high - low) of a candle.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PatternConfig {
+    /// Patterns to look for, every pattern if empty.
+    pub patterns: Vec<CandlePattern>,
+    /// Patterns with a lower confidence aren't reported.
+    pub min_confidence: f64,
+    /// Largest body of a doji.
+    pub doji_body: f64,
+    /// Smallest wick of a pin bar, its other wick is at most a third of it.
+    pub pin_wick: f64,
+    /// Largest body of the middle candle of a star, relative to the body of the first candle.
+    pub star_body: f64,
+    /// Largest wick against the direction of the candles of three soldiers / crows, relative to
+    /// their body.
+    pub soldier_wick: f64,
+}
+
+impl Default for PatternConfig {
+    fn default() -> Self {
+        Self {
+            patterns: Vec::new(),
+            min_confidence: 0.0,
+            doji_body: 0.1,
+            pin_wick: 0.6,
+            star_body: 0.3,
+            soldier_wick: 0.5,
+        }
+    }
+}
+
+impl PatternConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only looks for `patterns`.
+    pub fn patterns(mut self, patterns: impl IntoIterator<Item = CandlePattern>) -> Self {
+        self.patterns = patterns.into_iter().collect();
+        self
+    }
+
+    pub fn min_confidence(mut self, min_confidence: f64) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+
+    /// Checks that the thresholds are ratios between 0 and 1.
+    pub fn validate(&self) -> PocketResult<()> {
+        [
+            ("min_confidence", self.min_confidence),
+            ("doji_body", self.doji_body),
+            ("pin_wick", self.pin_wick),
+            ("star_body", self.star_body),
+            ("soldier_wick", self.soldier_wick),
+        ]
+        .into_iter()
+        .find(|(_, value)| !(0.0..=1.0).contains(value))
+        .map_or(Ok(()), |(name, value)| {
+            Err(PocketOptionError::Unallowed(format!(
+                "the pattern threshold '{name}' must be between 0 and 1, got {value}"
+            )))
+        })
+    }
+
+    fn enabled(&self, pattern: CandlePattern) -> bool {
+        self.patterns.is_empty() || self.patterns.contains(&pattern)
+    }
+}
+
+/// Shape of a candle, the wicks and body as a share of its range.
+struct Shape {
+    bullish: bool,
+    bearish: bool,
+    body: f64,
+    range: f64,
+    upper: f64,
+    lower: f64,
+}
+
+impl Shape {
+    fn new(candle: &DataCandle) -> Self {
+        let range = candle.high - candle.low;
+        let (top, bottom) = (candle.open.max(candle.close), candle.open.min(candle.close));
+        Self {
+            bullish: candle.close > candle.open,
+            bearish: candle.close < candle.open,
+            body: top - bottom,
+            range,
+            upper: candle.high - top,
+            lower: bottom - candle.low,
+        }
+    }
+
+    /// Share of the range taken by `length`, 0 for a candle without range.
+    fn share(&self, length: f64) -> f64 {
+        if self.range > 0.0 {
+            length / self.range
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Finds the patterns completed by the candles of a stream, one closed candle at a time.
+#[derive(Debug, Clone, Default)]
+pub struct PatternDetector {
+    config: PatternConfig,
+    window: VecDeque<DataCandle>,
+}
+
+impl PatternDetector {
+    pub fn new(config: PatternConfig) -> PocketResult<Self> {
+        config.validate()?;
+        Ok(Self {
+            config,
+            window: VecDeque::with_capacity(MAX_PATTERN_CANDLES),
+        })
+    }
+
+    pub fn config(&self) -> &PatternConfig {
+        &self.config
+    }
+
+    /// Adds a closed candle and returns the patterns it completes. The candles must be fed in
+    /// chronological order, the updates of a candle still open would count as separate candles.
+    pub fn update(&mut self, candle: &DataCandle) -> Vec<PatternEvent> {
+        if self.window.len() == MAX_PATTERN_CANDLES {
+            self.window.pop_front();
+        }
+        self.window.push_back(candle.clone());
+        let candles: Vec<&DataCandle> = self.window.iter().collect();
+        let mut events = Vec::new();
+        for pattern in CandlePattern::ALL {
+            let count = pattern.candles();
+            if !self.config.enabled(pattern) || candles.len() < count {
+                continue;
+            }
+            let candles = &candles[candles.len() - count..];
+            let confidence = self.confidence(pattern, candles);
+            if let Some(confidence) = confidence.filter(|c| *c >= self.config.min_confidence) {
+                events.push(PatternEvent {
+                    pattern,
+                    bias: pattern.bias(),
+                    start: candles[0].time,
+                    time: candle.time,
+                    confidence: (confidence.clamp(0.0, 1.0) * 1000.0).round() / 1000.0,
+                });
+            }
+        }
+        events
+    }
+
+    /// Forgets the previous candles, for example after a gap in the stream.
+    pub fn reset(&mut self) {
+        self.window.clear();
+    }
+
+    /// Confidence of `pattern` on `candles` (as many as the pattern spans), `None` if they don't
+    /// match it.
+    fn confidence(&self, pattern: CandlePattern, candles: &[&DataCandle]) -> Option<f64> {
+        let config = &self.config;
+        let shapes: Vec<Shape> = candles.iter().map(|c| Shape::new(c)).collect();
+        match (pattern, shapes.as_slice()) {
+            (CandlePattern::Doji, [c]) => {
+                let body = c.share(c.body);
+                (c.range > 0.0 && body <= config.doji_body)
+                    .then(|| 1.0 - body / config.doji_body.max(f64::EPSILON))
+            }
+            (CandlePattern::BullishPinBar, [c]) => pin_bar(c, c.lower, c.upper, config),
+            (CandlePattern::BearishPinBar, [c]) => pin_bar(c, c.upper, c.lower, config),
+            (CandlePattern::BullishEngulfing, [p, c]) => (p.bearish
+                && c.bullish
+                && candles[1].open <= candles[0].close
+                && candles[1].close >= candles[0].open)
+                .then(|| engulfing(p, c)),
+            (CandlePattern::BearishEngulfing, [p, c]) => (p.bullish
+                && c.bearish
+                && candles[1].open >= candles[0].close
+                && candles[1].close <= candles[0].open)
+                .then(|| engulfing(p, c)),
+            (CandlePattern::InsideBar, [p, c]) => (p.range > 0.0
+                && candles[1].high <= candles[0].high
+                && candles[1].low >= candles[0].low
+                && c.range < p.range)
+                .then(|| 1.0 - c.range / p.range),
+            (CandlePattern::ThreeWhiteSoldiers, [a, b, c]) => {
+                let rising = candles.windows(2).all(|w| {
+                    w[1].close > w[0].close && w[1].open >= w[0].open && w[1].open <= w[0].close
+                });
+                let strong = [a, b, c]
+                    .iter()
+                    .all(|s| s.bullish && s.upper <= config.soldier_wick * s.body);
+                (rising && strong).then(|| solidity([a, b, c]))
+            }
+            (CandlePattern::ThreeBlackCrows, [a, b, c]) => {
+                let falling = candles.windows(2).all(|w| {
+                    w[1].close < w[0].close && w[1].open <= w[0].open && w[1].open >= w[0].close
+                });
+                let strong = [a, b, c]
+                    .iter()
+                    .all(|s| s.bearish && s.lower <= config.soldier_wick * s.body);
+                (falling && strong).then(|| solidity([a, b, c]))
+            }
+            (CandlePattern::MorningStar, [a, b, c]) => {
+                let middle = (candles[0].open + candles[0].close) / 2.0;
+                (a.bearish && c.bullish && b.body <= config.star_body * a.body)
+                    .then(|| (candles[2].close - middle) / (candles[0].open - middle))
+                    .filter(|recovery| *recovery > 0.0)
+            }
+            (CandlePattern::EveningStar, [a, b, c]) => {
+                let middle = (candles[0].open + candles[0].close) / 2.0;
+                (a.bullish && c.bearish && b.body <= config.star_body * a.body)
+                    .then(|| (middle - candles[2].close) / (middle - candles[0].open))
+                    .filter(|recovery| *recovery > 0.0)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Pin bar with a `wick` against the rejected prices and the `nose` on the other side.
+fn pin_bar(shape: &Shape, wick: f64, nose: f64, config: &PatternConfig) -> Option<f64> {
+    let share = shape.share(wick);
+    (share >= config.pin_wick && nose <= wick / 3.0 && wick >= 2.0 * shape.body)
+        .then(|| (share - config.pin_wick) / (1.0 - config.pin_wick).max(f64::EPSILON))
+        .map(|excess| 0.5 + excess / 2.0)
+}
+
+/// The more the body of the second candle exceeds the first one, the clearer the engulfing.
+fn engulfing(previous: &Shape, current: &Shape) -> f64 {
+    if current.body <= 0.0 {
+        return 0.0;
+    }
+    0.5 + (1.0 - previous.body / current.body) / 2.0
+}
+
+/// Mean share of the range taken by the bodies.
+fn solidity(shapes: [&Shape; 3]) -> f64 {
+    shapes.iter().map(|s| s.share(s.body)).sum::<f64>() / 3.0
+}
+
+/// Finds every pattern of a history of closed candles, in chronological order.
+pub fn scan(candles: &[DataCandle], config: PatternConfig) -> PocketResult<Vec<PatternEvent>> {
+    let mut detector = PatternDetector::new(config)?;
+    Ok(candles
+        .iter()
+        .flat_map(|candle| detector.update(candle))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+
+    use super::*;
+
+    fn candle(i: i64, open: f64, high: f64, low: f64, close: f64) -> DataCandle {
+        DataCandle {
+            time: DateTime::from_timestamp(1_700_000_000 + i * 60, 0).unwrap(),
+            open,
+            high,
+            low,
+            close,
+            ..Default::default()
+        }
+    }
+
+    fn found(events: &[PatternEvent], pattern: CandlePattern) -> bool {
+        events.iter().any(|e| e.pattern == pattern)
+    }
+
+    #[test]
+    fn test_patterns() -> anyhow::Result<()> {
+        let candles = [
+            candle(0, 1.10, 1.11, 1.05, 1.06),
+            candle(1, 1.055, 1.12, 1.05, 1.115),
+            candle(2, 1.10, 1.1005, 1.08, 1.0995),
+            candle(3, 1.09, 1.095, 1.05, 1.094),
+            candle(4, 1.094, 1.0945, 1.09, 1.0942),
+        ];
+        let events = scan(&candles, PatternConfig::new())?;
+        let at = |i: usize| -> Vec<PatternEvent> {
+            events
+                .iter()
+                .filter(|e| e.time == candles[i].time)
+                .cloned()
+                .collect()
+        };
+        assert!(found(&at(1), CandlePattern::BullishEngulfing));
+        assert!(found(&at(2), CandlePattern::Doji));
+        assert!(found(&at(2), CandlePattern::InsideBar));
+        assert!(found(&at(3), CandlePattern::BullishPinBar));
+        assert!(found(&at(4), CandlePattern::InsideBar));
+        assert!(!found(&events, CandlePattern::BearishEngulfing));
+        assert!(events.iter().all(|e| (0.0..=1.0).contains(&e.confidence)));
+
+        let soldiers = [
+            candle(0, 1.00, 1.021, 0.999, 1.02),
+            candle(1, 1.01, 1.041, 1.009, 1.04),
+            candle(2, 1.03, 1.061, 1.029, 1.06),
+        ];
+        let events = scan(
+            &soldiers,
+            PatternConfig::new().patterns([CandlePattern::ThreeWhiteSoldiers]),
+        )?;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].start, soldiers[0].time);
+        assert_eq!(events[0].time, soldiers[0].time + TimeDelta::minutes(2));
+        assert_eq!(events[0].bias, PatternBias::Bullish);
+
+        let star = [
+            candle(0, 1.10, 1.101, 1.049, 1.05),
+            candle(1, 1.048, 1.05, 1.044, 1.046),
+            candle(2, 1.05, 1.096, 1.049, 1.095),
+        ];
+        let events = scan(&star, PatternConfig::new().min_confidence(0.5))?;
+        assert!(found(&events, CandlePattern::MorningStar));
+        assert!(PatternConfig::new().min_confidence(2.0).validate().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,223 @@
+//! Performance of the closed trades of the journal by time of day or day of the week, as matrices
+//! (one row per asset, one column per hour or weekday) ready to be drawn as a heatmap or used to
+//! only trade the profitable hours.
+
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{error::PocketResult, expectancy::TradeFilter, journal::JournalEntry};
+
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// Columns of a `PerformanceMatrix`, the times are in UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeAxis {
+    /// 24 columns, the hour the trades were opened at.
+    Hour,
+    /// 7 columns from Monday to Sunday, the day the trades were opened on.
+    Weekday,
+}
+
+impl TimeAxis {
+    /// Number of columns.
+    pub fn columns(&self) -> usize {
+        match self {
+            Self::Hour => 24,
+            Self::Weekday => 7,
+        }
+    }
+
+    pub fn labels(&self) -> Vec<String> {
+        match self {
+            Self::Hour => (0..24).map(|hour| format!("{hour:02}")).collect(),
+            Self::Weekday => WEEKDAYS.iter().map(|day| day.to_string()).collect(),
+        }
+    }
+
+    /// Column of `time`, the hour or the day of the week starting at 0 for Monday.
+    pub fn index(&self, time: DateTime<Utc>) -> usize {
+        match self {
+            Self::Hour => time.hour() as usize,
+            Self::Weekday => time.weekday().num_days_from_monday() as usize,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Cell {
+    trades: usize,
+    wins: usize,
+    losses: usize,
+    profit: f64,
+    staked: f64,
+}
+
+impl Cell {
+    fn add(&mut self, profit: f64, amount: f64) {
+        self.trades += 1;
+        match profit {
+            p if p > 0.0 => self.wins += 1,
+            p if p < 0.0 => self.losses += 1,
+            _ => {}
+        }
+        self.profit += profit;
+        self.staked += amount;
+    }
+
+    fn win_rate(&self) -> Option<f64> {
+        (self.wins + self.losses > 0).then(|| self.wins as f64 / (self.wins + self.losses) as f64)
+    }
+
+    fn return_per_unit(&self) -> Option<f64> {
+        (self.staked > 0.0).then(|| self.profit / self.staked)
+    }
+}
+
+/// Performance of the closed trades matching a filter, `matrix[row][column]` is the value of the
+/// trades of the asset `rows[row]` opened in the hour or weekday `columns[column]`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PerformanceMatrix {
+    pub axis: TimeAxis,
+    /// Assets of the rows, a single `"all"` row if the trades aren't split by asset.
+    pub rows: Vec<String>,
+    /// Labels of the columns, `"00"` to `"23"` or `"Mon"` to `"Sun"`.
+    pub columns: Vec<String>,
+    pub trades: Vec<Vec<usize>>,
+    /// Wins over the trades that weren't a draw, `None` without such trades.
+    pub win_rate: Vec<Vec<Option<f64>>>,
+    /// Total profit, in the account currency.
+    pub profit: Vec<Vec<f64>>,
+    /// Total profit over the total amount staked, `None` without trades.
+    pub return_per_unit: Vec<Vec<Option<f64>>>,
+}
+
+impl PerformanceMatrix {
+    /// Aggregates the closed trades of `entries` matching `filter`, with one row per asset (sorted
+    /// by name) if `by_asset`, the open trades are ignored.
+    pub fn new<'a>(
+        entries: impl IntoIterator<Item = &'a JournalEntry>,
+        filter: &TradeFilter,
+        axis: TimeAxis,
+        by_asset: bool,
+    ) -> PocketResult<Self> {
+        filter.validate()?;
+        let trades: Vec<(&str, usize, f64, f64)> = entries
+            .into_iter()
+            .filter(|e| filter.matches(e))
+            .filter_map(|e| {
+                let row = if by_asset { e.asset.as_str() } else { "all" };
+                e.profit
+                    .map(|profit| (row, axis.index(e.opened_at), profit, e.amount))
+            })
+            .collect();
+        let mut rows: Vec<String> = trades.iter().map(|t| t.0.to_string()).collect();
+        rows.sort();
+        rows.dedup();
+        if rows.is_empty() && !by_asset {
+            rows.push("all".into());
+        }
+
+        let mut cells = vec![vec![Cell::default(); axis.columns()]; rows.len()];
+        for (row, column, profit, amount) in trades {
+            if let Ok(row) = rows.binary_search_by(|r| r.as_str().cmp(row)) {
+                cells[row][column].add(profit, amount);
+            }
+        }
+        Ok(Self {
+            axis,
+            columns: axis.labels(),
+            trades: map_cells(&cells, |c| c.trades),
+            win_rate: map_cells(&cells, Cell::win_rate),
+            profit: map_cells(&cells, |c| c.profit),
+            return_per_unit: map_cells(&cells, Cell::return_per_unit),
+            rows,
+        })
+    }
+
+    /// Columns (hours or days of the week starting at 0 for Monday) with at least `min_trades`
+    /// trades over every row and a positive total profit.
+    pub fn profitable(&self, min_trades: usize) -> Vec<u32> {
+        (0..self.columns.len())
+            .filter(|column| {
+                let trades: usize = self.trades.iter().map(|row| row[*column]).sum();
+                let profit: f64 = self.profit.iter().map(|row| row[*column]).sum();
+                trades > 0 && trades >= min_trades && profit > 0.0
+            })
+            .map(|column| column as u32)
+            .collect()
+    }
+}
+
+fn map_cells<T>(cells: &[Vec<Cell>], value: impl Fn(&Cell) -> T) -> Vec<Vec<T>> {
+    cells
+        .iter()
+        .map(|row| row.iter().map(&value).collect())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::pocketoption::types::order::Action;
+
+    fn entry(asset: &str, day: u32, hour: u32, profit: Option<f64>) -> JournalEntry {
+        JournalEntry {
+            trade_id: Uuid::new_v4(),
+            request_id: None,
+            uid: 0,
+            asset: asset.into(),
+            action: Action::Put,
+            amount: 2.0,
+            time: 60,
+            open_price: 1.0,
+            // 2025-01-06 is a Monday
+            opened_at: Utc.with_ymd_and_hms(2025, 1, day, hour, 30, 0).unwrap(),
+            group: None,
+            tags: Vec::new(),
+            profit,
+            closed_at: None,
+            context: None,
+        }
+    }
+
+    #[test]
+    fn test_performance_matrix() -> anyhow::Result<()> {
+        let entries = vec![
+            entry("EURUSD_otc", 6, 9, Some(1.8)),
+            entry("EURUSD_otc", 6, 9, Some(-2.0)),
+            entry("EURUSD_otc", 7, 9, Some(1.8)),
+            entry("GBPUSD_otc", 7, 14, Some(-2.0)),
+            entry("GBPUSD_otc", 12, 14, Some(1.8)),
+            entry("GBPUSD_otc", 12, 15, None),
+        ];
+
+        let hourly = PerformanceMatrix::new(&entries, &TradeFilter::new(), TimeAxis::Hour, true)?;
+        assert_eq!(hourly.rows, vec!["EURUSD_otc", "GBPUSD_otc"]);
+        assert_eq!(hourly.columns.len(), 24);
+        assert_eq!(hourly.columns[9], "09");
+        assert_eq!(hourly.trades[0][9], 3);
+        assert_eq!(hourly.win_rate[0][9], Some(2.0 / 3.0));
+        assert!((hourly.profit[0][9] - 1.6).abs() < 1e-9);
+        assert!((hourly.return_per_unit[0][9].unwrap() - 1.6 / 6.0).abs() < 1e-9);
+        assert_eq!(hourly.trades[1][15], 0);
+        assert_eq!(hourly.win_rate[1][15], None);
+        assert_eq!(hourly.profitable(1), vec![9]);
+        assert!(hourly.profitable(4).is_empty());
+
+        let daily =
+            PerformanceMatrix::new(&entries, &TradeFilter::new(), TimeAxis::Weekday, false)?;
+        assert_eq!(daily.rows, vec!["all"]);
+        assert_eq!(daily.columns[6], "Sun");
+        assert_eq!(daily.trades[0][..], [2, 2, 0, 0, 0, 0, 1]);
+        assert_eq!(daily.profitable(1), vec![6]);
+
+        let filter = TradeFilter::new().asset("EURUSD_otc");
+        let empty = PerformanceMatrix::new(&entries[3..], &filter, TimeAxis::Hour, true)?;
+        assert!(empty.rows.is_empty());
+        Ok(())
+    }
+}
@@ -1,27 +1,84 @@
 use std::{
     collections::{HashMap, HashSet},
     ops::Deref,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex, RwLock, Weak,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
     time::{Duration, Instant},
 };
 
-use chrono::{DateTime, Utc};
-use tracing::{debug, info, warn};
+use chrono::{DateTime, TimeDelta, Utc};
+use futures_util::future::join_all;
+use tracing::{debug, error, info, warn};
 use url::Url;
 use uuid::Uuid;
 
 use crate::pocketoption::{
     error::PocketResult,
-    parser::basic::LoadHistoryPeriod,
-    types::order::SuccessCloseOrder,
+    expectancy::TradeFilter,
+    journal::{ContextCapture, Journal, JournalEntry, MarketContext},
+    levels::{Levels, LevelsConfig},
+    market_guard::{MarketGuard, MarketGuardConfig, MarketPause},
+    orders::{OrderOptions, OrderPlacement, check_market_open, check_min_payout},
+    parser::{basic::LoadHistoryPeriod, frame::is_order_frame},
+    price_orders::{PriceOrder, PriceOrderState, PriceOrders, PriceTrigger},
+    risk::{
+        ClusterGuard, DrawdownBreaker, DrawdownState, RiskManager, RiskRule, TradeIntent,
+        TradingHours,
+    },
+    scheduled_orders::{
+        SCHEDULE_REFINE_WINDOW, ScheduledOrder, ScheduledOrderState, ScheduledOrders,
+    },
+    stake::StakeRules,
+    straddle::{STRADDLE_TAG, Straddle, StraddleResult},
+    templates::{OrderTemplate, TemplateContext, TemplateExecution},
+    types::{debug::DebugStats, order::SuccessCloseOrder},
+    utils::{
+        download::{DownloadProgress, DownloadSummary, HistoryFormat, HistoryWriter},
+        expiry::{ExpirySuggestion, nearest_expiry, suggest_expiry},
+        history::{
+            CandleAudit, expected_candles, gap_pages, merge_candles, missing_ranges, range_pages,
+        },
+        location::get_public_ip,
+        metadata::{MetadataCache, MetadataSource, MetadataStatus},
+        precision::Precision,
+        trade_export::load_trade_export,
+    },
     validators::{candle_validator, order_result_validator},
-    ws::ssid::Ssid,
+    ws::{
+        session::{AuthFailure, Session, SessionProvider, SessionStatus},
+        ssid::{Ssid, SsidInfo},
+    },
+};
+#[cfg(feature = "metrics")]
+use crate::pocketoption::health::HealthReport;
+#[cfg(feature = "http-gateway")]
+use crate::pocketoption::{
+    access::AccessControl,
+    health::{serve_healthcheck, serve_healthcheck_with_access},
 };
+#[cfg(feature = "http-gateway")]
+use std::net::SocketAddr;
+#[cfg(feature = "charts")]
+use crate::pocketoption::chart::{CHART_TRADE_PADDING, ChartMarker, ChartOptions, render_chart};
+#[cfg(feature = "dashboard")]
+use crate::pocketoption::dashboard::serve_dashboard;
+#[cfg(feature = "arrow")]
+use crate::pocketoption::interop::{RecordBatch, ToRecordBatch};
+#[cfg(feature = "dashboard")]
+use binary_options_tools_core::utils::tracing::LogBroadcast;
+use tokio::task::{AbortHandle, JoinHandle};
 use binary_options_tools_core::{
+    assets::{otc_fallback, otc_symbol},
     error::BinaryOptionsToolsError,
+    utils::{audit::AUDIT_TARGET, storage::storage},
     general::{
         client::WebSocketClient,
         config::{_Config, Config},
-        stream::FilteredRecieverStream,
+        health::ConnectionEvent,
+        stream::{FilteredRecieverStream, RecieverStream},
         traits::{MessageTransfer, ValidatorTrait},
         types::{Callback, Data},
     },
@@ -31,17 +88,62 @@ use super::{
     error::PocketOptionError,
     parser::message::WebSocketMessage,
     types::{
+        account::{AccountEvent, AccountEventKind},
         base::{ChangeSymbol, RawWebsocketMessage},
         callback::PocketCallback,
         data::PocketData,
+        feed::FeedHealth,
         info::MessageInfo,
-        order::{Action, Deal, OpenOrder},
-        update::{DataCandle, UpdateBalance},
+        lanes::{StreamLaneStats, StreamPriority},
+        latency::LatencyStats,
+        order::{Action, CloseOrder, Deal, Direction, OpenOrder, PocketMessageFail, ServerIds},
+        region::{AccountProfile, RegionMismatch},
+        store::{CANDLE_STORE_FILE, CandleStore},
+        subscriptions::{
+            SubscriptionAction, SubscriptionEvent, SubscriptionPolicy, SubscriptionStatus,
+        },
+        timeframe::IntoTimeframe,
+        unparsed::UnparsedMessage,
+        update::{AssetInfo, DataCandle, UpdateBalance},
     },
     validators::{history_validator, order_validator},
-    ws::{connect::PocketConnect, listener::Handler, stream::StreamAsset},
+    ws::{
+        candle_close::CandleCloseStream, connect::PocketConnect, listener::Handler,
+        stream::StreamAsset,
+    },
 };
 
+/// Number of times the missing segments of a candles request are requested again.
+const CANDLES_MAX_RETRIES: u32 = 3;
+/// Delay before the first retry of the missing segments, doubled after every attempt.
+const CANDLES_RETRY_BACKOFF: Duration = Duration::from_millis(500);
+/// Maximum number of requests sent for the missing segments of a candles request, over all the
+/// retries.
+const CANDLES_MAX_GAP_REQUESTS: usize = 6;
+/// Number of candles requested at once by `get_candles_range`, larger pages come back incomplete.
+const CANDLES_PAGE_SIZE: i64 = 500;
+/// Minimum delay between two page requests of `download_history`, so the server doesn't throttle it.
+const HISTORY_REQUEST_INTERVAL: Duration = Duration::from_millis(250);
+/// Number of times a page of `download_history` is requested again after a failure (e.g. while the
+/// client reconnects), the delay before the first retry is doubled after every attempt.
+const HISTORY_MAX_RETRIES: u32 = 5;
+const HISTORY_RETRY_BACKOFF: Duration = Duration::from_secs(1);
+/// Maximum time the server takes to answer the authentication of a new client.
+const AUTH_TIMEOUT: Duration = Duration::from_secs(10);
+/// How often `flush_orders` checks whether the pending orders were confirmed.
+const FLUSH_POLL_INTERVAL: Duration = Duration::from_millis(20);
+/// Longest delay between two checks of the power saving mode for a stream reading the ticks.
+const POWER_SAVING_CHECK: Duration = Duration::from_secs(30);
+/// How often the message rate of the subscribed feeds is checked, see `feed_health`.
+const FEED_CHECK: Duration = Duration::from_secs(5);
+/// How often the assets sent by the server are saved to the metadata cache, if they changed.
+const METADATA_SAVE_INTERVAL: Duration = Duration::from_secs(60);
+/// Timeframe (in seconds) and lookback of the candles `suggest_expiry` measures the volatility on.
+const VOLATILITY_PERIOD: u32 = 60;
+const VOLATILITY_LOOKBACK: i64 = 3600;
+
+type PocketClient = WebSocketClient<WebSocketMessage, Handler, PocketConnect, Ssid, PocketData, ()>;
+
 /// A client for interacting with the Pocket Option trading platform.
 /// This struct provides methods for executing trades, managing positions,
 /// streaming market data, and accessing account information.
@@ -95,22 +197,188 @@ use super::{
 /// - Server errors
 ///
 /// # Thread Safety
-/// The client is inherently thread-safe as it internally uses an Arc-wrapped WebSocket client.
-/// It can be safely cloned and shared between multiple tasks.
+/// The client is inherently thread-safe and all its state lives behind a single `Arc`, so it can be
+/// cloned (a reference count increment) and shared between multiple tasks at no cost.
+///
+/// # Observer Mode
+/// A client made with `new_observer` (or `observer` from an existing client) authenticates and
+/// receives data and events like any other, but every method sending an order fails with an
+/// `ObserverMode` error, for analytics deployments where trading must be impossible.
 #[derive(Clone)]
 pub struct PocketOption {
-    client: WebSocketClient<WebSocketMessage, Handler, PocketConnect, Ssid, PocketData, ()>,
+    inner: Arc<PocketInner>,
+    observer: bool,
+}
+
+/// Reference to a client that doesn't keep it alive, for the background tasks serving it.
+#[cfg(feature = "http-gateway")]
+#[derive(Clone)]
+pub(crate) struct WeakPocketOption {
+    inner: Weak<PocketInner>,
+    observer: bool,
+}
+
+#[cfg(feature = "http-gateway")]
+impl WeakPocketOption {
+    /// The client, `None` once its last clone was dropped.
+    pub(crate) fn upgrade(&self) -> Option<PocketOption> {
+        Some(PocketOption {
+            inner: self.inner.upgrade()?,
+            observer: self.observer,
+        })
+    }
+
+    /// True once the last clone of the client was dropped.
+    pub(crate) fn is_dropped(&self) -> bool {
+        self.inner.strong_count() == 0
+    }
+}
+
+/// State shared by every clone of the client, cloning the client only clones the `Arc` around it.
+struct PocketInner {
+    client: PocketClient,
+    /// Session the client authenticates with, shared with the handler and the connector.
+    session: Session,
+    risk: RiskManager,
+    journal: Journal,
+    drawdown: RwLock<Option<DrawdownSafety>>,
+    stakes: RwLock<StakeRules>,
+    /// Background tasks started by the client, stopped by `shutdown`.
+    tasks: Mutex<Vec<AbortHandle>>,
+    /// Orders sent (or waiting to be sent) whose confirmation wasn't recieved yet.
+    pending_orders: AtomicUsize,
+    /// Rejects the trades on the assets whose market is closed, see `set_market_hours_check`.
+    market_hours_check: AtomicBool,
+    /// Trades and subscribes to the OTC version of the closed regular assets, see `set_otc_fallback`.
+    otc_fallback: AtomicBool,
+    price_orders: PriceOrders,
+    scheduled_orders: ScheduledOrders,
+    /// Task of the power saving mode, replaced by `set_power_saving`.
+    power_saving: Mutex<Option<AbortHandle>>,
+    /// Metadata cache and the task saving it, replaced by `set_metadata_cache`.
+    metadata_cache: Mutex<Option<(MetadataCache, AbortHandle)>>,
+    /// File the candle store is saved to and the task saving it, replaced by `persist_candle_store`.
+    candle_store_file: Mutex<Option<(PathBuf, AbortHandle)>>,
+}
+
+/// Counts an order as pending until it's dropped, so `flush_orders` waits for it even if the
+/// future placing it is cancelled.
+struct PendingOrder<'a>(&'a AtomicUsize);
+
+impl<'a> PendingOrder<'a> {
+    fn new(pending: &'a AtomicUsize) -> Self {
+        pending.fetch_add(1, Ordering::SeqCst);
+        Self(pending)
+    }
+}
+
+impl Drop for PendingOrder<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Drawdown circuit breaker of the client, with the demo client the trades are redirected to once
+/// it tripped.
+#[derive(Clone)]
+struct DrawdownSafety {
+    breaker: Arc<DrawdownBreaker>,
+    demo: Option<PocketOption>,
 }
 
 impl Deref for PocketOption {
     type Target = Config<PocketData, WebSocketMessage, ()>;
 
     fn deref(&self) -> &Self::Target {
-        &self.client.config
+        &self.inner.client.config
     }
 }
 
 impl PocketOption {
+    fn from_client(client: PocketClient, session: Session) -> Self {
+        let client = Self {
+            inner: Arc::new(PocketInner {
+                client,
+                session,
+                risk: RiskManager::default(),
+                journal: Journal::default(),
+                drawdown: RwLock::new(None),
+                stakes: RwLock::new(StakeRules::default()),
+                tasks: Mutex::new(Vec::new()),
+                pending_orders: AtomicUsize::new(0),
+                market_hours_check: AtomicBool::new(false),
+                otc_fallback: AtomicBool::new(false),
+                price_orders: PriceOrders::default(),
+                scheduled_orders: ScheduledOrders::default(),
+                power_saving: Mutex::new(None),
+                metadata_cache: Mutex::new(None),
+                candle_store_file: Mutex::new(None),
+            }),
+            observer: false,
+        };
+        let handle = tokio::spawn(Self::monitor_feeds(Arc::downgrade(&client.inner)));
+        client.track_task(&handle);
+        client
+    }
+
+    /// Checks the message rate of the feeds until the last clone of the client is dropped, the task
+    /// only holds a weak reference so it doesn't keep the client alive.
+    async fn monitor_feeds(inner: Weak<PocketInner>) {
+        let mut check = tokio::time::interval(FEED_CHECK);
+        loop {
+            check.tick().await;
+            let Some(inner) = inner.upgrade() else {
+                break;
+            };
+            let data = &inner.client.data;
+            data.evaluate_feeds(data.health.is_connected()).await;
+        }
+    }
+
+    fn client(&self) -> &PocketClient {
+        &self.inner.client
+    }
+
+    /// Waits for the server to accept the session of the new client, and shuts it down with an
+    /// `AuthenticationError` (or a `RegionMismatch` error) if it was rejected instead of returning a
+    /// client whose requests would only time out.
+    async fn authenticated(self) -> PocketResult<Self> {
+        if let Err(e) = self.inner.session.wait_authentication(AUTH_TIMEOUT).await {
+            self.shutdown().await;
+            return Err(self.diagnose_rejection(e).await);
+        }
+        Ok(self)
+    }
+
+    /// Reports the rejection of a well formed session of a real account created from another IP
+    /// than the one the client connects from as an IP mismatch, the usual cause after moving the
+    /// client to another machine.
+    async fn diagnose_rejection(&self, error: PocketOptionError) -> PocketOptionError {
+        let PocketOptionError::AuthenticationError {
+            failure: AuthFailure::Expired,
+            ..
+        } = error
+        else {
+            return error;
+        };
+        let Some(session_ip) = self.inner.session.ssid().info().ip_address else {
+            return error;
+        };
+        match tokio::time::timeout(AUTH_TIMEOUT, get_public_ip()).await {
+            Ok(Ok(current_ip)) => match RegionMismatch::from_ips(&session_ip, &current_ip) {
+                Some(mismatch) => {
+                    self.inner.session.mismatch(mismatch.clone());
+                    mismatch.error()
+                }
+                None => error,
+            },
+            _ => {
+                warn!(target: "PocketOption", "Couldn't get the public IP to compare it to the one of the session");
+                error
+            }
+        }
+    }
+
     /// Creates a new PocketOption client with default connection settings.
     ///
     /// # Arguments
@@ -126,7 +394,13 @@ impl PocketOption {
     pub async fn new(ssid: impl ToString) -> PocketResult<Self> {
         let ssid = Ssid::parse(ssid)?;
         let data = Data::new(PocketData::default());
-        let handler = Handler::new(ssid.clone());
+        let session = Session::new(ssid.clone());
+        let handler = Handler::new(
+            session.clone(),
+            data.quarantine(),
+            data.subscriptions(),
+            data.health.clone(),
+        );
         let timeout = Duration::from_millis(500);
         let callback = PocketCallback;
         let config = _Config::new(timeout, vec![], ())
@@ -135,14 +409,16 @@ impl PocketOption {
             .build()?;
         let client = WebSocketClient::init(
             ssid,
-            PocketConnect {},
+            PocketConnect::new(session.clone()),
             data,
             handler,
             Some(Callback::new(std::sync::Arc::new(callback))),
             config,
         )
         .await?;
-        Ok(Self { client })
+        let client = Self::from_client(client, session).authenticated().await?;
+        client.audit_config();
+        Ok(client)
     }
 
     /// Creates a new PocketOption client with a custom WebSocket URL.
@@ -163,7 +439,13 @@ impl PocketOption {
     pub async fn new_with_url(ssid: impl ToString, url: Url) -> PocketResult<Self> {
         let ssid = Ssid::parse(ssid)?;
         let data = Data::new(PocketData::default());
-        let handler = Handler::new(ssid.clone());
+        let session = Session::new(ssid.clone());
+        let handler = Handler::new(
+            session.clone(),
+            data.quarantine(),
+            data.subscriptions(),
+            data.health.clone(),
+        );
         let timeout = Duration::from_millis(500);
         let callback = PocketCallback;
         let config = _Config::new(timeout, vec![], ())
@@ -173,7 +455,7 @@ impl PocketOption {
             .build()?;
         let client = WebSocketClient::init(
             ssid,
-            PocketConnect {},
+            PocketConnect::new(session.clone()),
             data,
             handler,
             Some(Callback::new(std::sync::Arc::new(callback))),
@@ -181,7 +463,9 @@ impl PocketOption {
         )
         .await?;
         // println!("Initialized!");
-        Ok(Self { client })
+        let client = Self::from_client(client, session).authenticated().await?;
+        client.audit_config();
+        Ok(client)
     }
 
     /// Creates a new PocketOption client with a provided configuration.
@@ -207,12 +491,18 @@ impl PocketOption {
     ) -> PocketResult<Self> {
         let ssid = Ssid::parse(ssid)?;
         let data = Data::new(PocketData::default());
-        let handler = Handler::new(ssid.clone());
+        let session = Session::new(ssid.clone());
+        let handler = Handler::new(
+            session.clone(),
+            data.quarantine(),
+            data.subscriptions(),
+            data.health.clone(),
+        );
         let callback = PocketCallback;
 
         let client = WebSocketClient::init(
             ssid,
-            PocketConnect {},
+            PocketConnect::new(session.clone()),
             data,
             handler,
             Some(Callback::new(std::sync::Arc::new(callback))),
@@ -220,7 +510,158 @@ impl PocketOption {
         )
         .await?;
 
-        Ok(Self { client })
+        let client = Self::from_client(client, session).authenticated().await?;
+        client.audit_config();
+        Ok(client)
+    }
+
+    /// Creates a new PocketOption client in observer mode: it receives data and events, but can't
+    /// send any order.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let client = PocketOption::new_observer("your-session-id").await?;
+    /// let stream = client.subscribe_symbol("EURUSD_otc").await?;
+    /// assert!(client.buy("EURUSD_otc", 1.0, 60).await.is_err());
+    /// ```
+    pub async fn new_observer(ssid: impl ToString) -> PocketResult<Self> {
+        Ok(Self::new(ssid).await?.observer())
+    }
+
+    /// Returns a handle in observer mode sharing the connection and the state of this client,
+    /// every order sent through it (or its clones) fails with an `ObserverMode` error.
+    pub fn observer(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            observer: true,
+        }
+    }
+
+    #[cfg(feature = "http-gateway")]
+    pub(crate) fn downgrade(&self) -> WeakPocketOption {
+        WeakPocketOption {
+            inner: Arc::downgrade(&self.inner),
+            observer: self.observer,
+        }
+    }
+
+    /// Returns true if the client is in observer mode.
+    pub fn is_observer(&self) -> bool {
+        self.observer
+    }
+
+    /// Creates a new PocketOption client with a session of `provider`, which is asked for a fresh
+    /// session every time the server rejects the current one (see `set_session_provider`).
+    ///
+    /// # Examples
+    /// ```rust
+    /// let client = PocketOption::new_with_session_provider(HeadlessLogin::new(email, password)).await?;
+    /// ```
+    pub async fn new_with_session_provider(
+        provider: impl SessionProvider + 'static,
+    ) -> PocketResult<Self> {
+        let ssid = provider.fresh_ssid().await?;
+        let client = Self::new(Ssid::parse_lenient(&ssid, None, None)?).await?;
+        client.set_session_provider(provider);
+        Ok(client)
+    }
+
+    /// Sets the provider asked for a fresh session when the server rejects the current one (e.g. it
+    /// expired), the client then reconnects with it. The session is cached and kept until the
+    /// server rejects it too, so the provider (e.g. a headless browser logging in the site) is only
+    /// called when needed. The sessions of another account are rejected.
+    ///
+    /// # Examples
+    /// ```rust
+    /// struct HeadlessLogin;
+    ///
+    /// #[async_trait]
+    /// impl SessionProvider for HeadlessLogin {
+    ///     async fn fresh_ssid(&self) -> PocketResult<String> {
+    ///         login_with_browser().await
+    ///     }
+    /// }
+    ///
+    /// client.set_session_provider(HeadlessLogin);
+    /// ```
+    pub fn set_session_provider(&self, provider: impl SessionProvider + 'static) {
+        self.inner.session.set_provider(Arc::new(provider));
+        info!(target: AUDIT_TARGET, event = "session_provider", action = "set");
+    }
+
+    pub fn remove_session_provider(&self) {
+        self.inner.session.remove_provider();
+        info!(target: AUDIT_TARGET, event = "session_provider", action = "removed");
+    }
+
+    /// Asks the session provider for a fresh session now, it's used from the next connection.
+    pub async fn refresh_session(&self) -> PocketResult<SsidInfo> {
+        self.inner.session.refresh().await.map(|ssid| ssid.info())
+    }
+
+    /// Returns the account of the session, whether the server rejected it and the sessions
+    /// received from the provider.
+    pub fn session_status(&self) -> SessionStatus {
+        self.inner.session.status()
+    }
+
+    /// Returns the account of the session with its balance, the server the client is connected to,
+    /// the region reported by the server and its last region or IP mismatch.
+    pub async fn profile(&self) -> AccountProfile {
+        let info = self.inner.session.ssid().info();
+        AccountProfile {
+            uid: info.uid,
+            demo: info.demo,
+            balance: self.get_balance().await.balance,
+            session_ip: info.ip_address,
+            user_agent: info.user_agent,
+            server: self.client().connector.server(),
+            region: self.inner.session.region(),
+            region_mismatch: self.inner.session.region_mismatch(),
+        }
+    }
+
+    /// Fails with an `ObserverMode` error if the client is in observer mode.
+    fn ensure_trading(&self, operation: &str) -> PocketResult<()> {
+        if self.observer {
+            warn!(target: AUDIT_TARGET, event = "observer_rejected", operation);
+            return Err(PocketOptionError::ObserverMode(operation.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Fails with an `ObserverMode` error if the client is in observer mode and the raw message
+    /// sends an order.
+    fn ensure_raw_allowed(&self, message: &RawWebsocketMessage) -> PocketResult<()> {
+        if self.observer && is_order_frame(&message.to_string()) {
+            return self.ensure_trading("sending an order as a raw message");
+        }
+        Ok(())
+    }
+
+    /// Sends a raw order, recording it and its response in the audit log.
+    async fn audited_raw_order(
+        operation: &'static str,
+        message: &RawWebsocketMessage,
+        response: impl Future<Output = Result<RawWebsocketMessage, BinaryOptionsToolsError>>,
+    ) -> PocketResult<RawWebsocketMessage> {
+        info!(target: AUDIT_TARGET, event = "raw_order_request", operation, request = %message);
+        let response = response.await.inspect_err(
+            |e| warn!(target: AUDIT_TARGET, event = "raw_order_failed", operation, error = %e),
+        )?;
+        info!(target: AUDIT_TARGET, event = "raw_order_response", operation, response = %response);
+        Ok(response)
+    }
+
+    /// Records the configuration used by the client in the audit log.
+    fn audit_config(&self) {
+        let config = _Config::try_from(self.inner.client.config.clone())
+            .map_err(BinaryOptionsToolsError::from)
+            .and_then(|c| serde_json::to_string(&c).map_err(BinaryOptionsToolsError::from));
+        match config {
+            Ok(config) => info!(target: AUDIT_TARGET, event = "config_change", %config),
+            Err(e) => warn!(target: AUDIT_TARGET, event = "config_change", "Failed to serialize config, {e}"),
+        }
     }
 
     /// Executes a trade with the specified parameters.
@@ -228,7 +669,7 @@ impl PocketOption {
     /// # Arguments
     /// * `asset` - Trading symbol (e.g., "EURUSD")
     /// * `action` - Trade direction (Call/Put)
-    /// * `amount` - Trade amount in account currency
+    /// * `amount` - Trade amount in account currency, rounded with the stake rules (see `set_stake_rules`)
     /// * `time` - Trade duration in seconds
     ///
     /// # Returns
@@ -245,16 +686,90 @@ impl PocketOption {
         amount: f64,
         time: u32,
     ) -> PocketResult<(Uuid, Deal)> {
+        self.trade_with_min_payout(asset, action, amount, time, None)
+            .await
+    }
+
+    /// Executes a trade like `trade`, rejected with a `PayoutTooLow` error if the payout of the
+    /// asset is under `min_payout` percent (or unknown). The payout is checked after every other
+    /// check, right before the order is sent.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let (trade_id, deal) = client.trade_with_min_payout("EURUSD_otc", Action::Call, 1.0, 60, Some(85)).await?;
+    /// ```
+    pub async fn trade_with_min_payout(
+        &self,
+        asset: impl ToString,
+        action: Action,
+        amount: f64,
+        time: u32,
+        min_payout: Option<i32>,
+    ) -> PocketResult<(Uuid, Deal)> {
+        self.ensure_trading("trade")?;
+        let asset = self.resolve_asset(asset).await;
+        let amount = self.round_stake(amount).await?;
+        if let Some(demo) = self.check_drawdown().await {
+            warn!(target: "DrawdownBreaker", "Drawdown breaker tripped, placing the trade on '{}' on the demo account", asset.to_string());
+            return demo
+                .checked_trade(asset, action, amount, time, min_payout)
+                .await;
+        }
+        self.checked_trade(asset, action, amount, time, min_payout)
+            .await
+    }
+
+    /// Places a trade after checking it against the risk rules, and records it in the journal.
+    async fn checked_trade(
+        &self,
+        asset: impl ToString,
+        action: Action,
+        amount: f64,
+        time: u32,
+        min_payout: Option<i32>,
+    ) -> PocketResult<(Uuid, Deal)> {
+        let intent = TradeIntent::new(asset.to_string(), action.clone(), amount, time);
+        self.client().data.evaluate_market_guard().await;
+        self.risk().check(&intent)?;
+        let (id, deal) = self
+            .send_order(intent.clone(), min_payout)
+            .await
+            .inspect_err(|_| self.risk().rollback(&intent))?;
+        if let Err(e) = self.journal().record(JournalEntry::new(intent.action, intent.time, &deal)) {
+            warn!(target: "Journal", "Failed to record trade '{id}' in the journal, {e}");
+        }
+        Ok((id, deal))
+    }
+
+    async fn send_order(
+        &self,
+        intent: TradeIntent,
+        min_payout: Option<i32>,
+    ) -> PocketResult<(Uuid, Deal)> {
+        self.ensure_trading("trade")?;
+        self.inner.session.ensure_authenticated()?;
+        if min_payout.is_some() {
+            let payout = self.client().data.get_payout(&intent.asset).await;
+            check_min_payout(&intent.asset, payout, min_payout)
+                .inspect_err(|e| warn!(target: "Trade", "{e}"))?;
+        }
+        if self.market_hours_check() {
+            let is_open = self.is_open(&intent.asset).await;
+            check_market_open(&intent.asset, is_open)
+                .inspect_err(|e| warn!(target: "Trade", "{e}"))?;
+        }
         let order = OpenOrder::new(
-            amount,
-            asset.to_string(),
-            action,
-            time,
-            self.client.credentials.demo() as u32,
+            intent.amount,
+            intent.asset,
+            intent.action,
+            intent.time,
+            self.client().credentials.demo() as u32,
         )?;
         let request_id = order.request_id;
+        info!(target: AUDIT_TARGET, event = "order_request", %request_id, request = %WebSocketMessage::OpenOrder(order.clone()));
+        let _pending = PendingOrder::new(&self.inner.pending_orders);
         let res = self
-            .client
+            .client()
             .send_message_with_timout(
                 self.get_timeout()?,
                 "Trade",
@@ -262,119 +777,1119 @@ impl PocketOption {
                 MessageInfo::SuccessopenOrder,
                 &order_validator(request_id),
             )
-            .await?;
+            .await
+            .inspect_err(|e| warn!(target: AUDIT_TARGET, event = "order_failed", %request_id, error = %e))?;
         if let WebSocketMessage::SuccessopenOrder(order) = res {
             debug!("Successfully opened buy trade!");
+            info!(target: AUDIT_TARGET, event = "order_response", %request_id, trade_id = %order.id, server_ids = %order.server_ids(), response = %serde_json::to_string(&order)?);
             return Ok((order.id, order));
         }
+        warn!(target: AUDIT_TARGET, event = "order_failed", %request_id, error = %res.info());
         Err(PocketOptionError::UnexpectedIncorrectWebSocketMessage(
             res.info(),
         ))
     }
 
-    /// Places a buy (CALL) order.
+    /// Places a trade, the canonical entry point every other way of placing a trade (`buy`, `sell`,
+    /// `trade`) shares: the trade goes through the drawdown breaker, the risk rules and the journal,
+    /// and the `options` (tags, dry run, minimum payout) apply the same way to every direction.
     ///
     /// # Arguments
     /// * `asset` - Trading symbol (e.g., "EURUSD")
+    /// * `direction` - Direction of the trade (Call/Put)
     /// * `amount` - Trade amount in account currency
-    /// * `time` - Trade duration in seconds
+    /// * `expiry` - Trade duration in seconds
+    /// * `options` - Tags recorded in the journal, dry run and minimum payout
     ///
     /// # Returns
-    /// A tuple containing the trade ID (UUID) and trade details (Deal)
+    /// The id and details of the trade, or the checked order for a dry run
     ///
     /// # Examples
     /// ```rust
-    /// let (trade_id, deal) = client.buy("EURUSD", 100.0, 60).await?;
+    /// let options = OrderOptions::new().tag("breakout").min_payout(80);
+    /// let placement = client.place_order("EURUSD_otc", Direction::Call, 1.0, 60, options).await?;
     /// ```
-    pub async fn buy(
+    pub async fn place_order(
         &self,
         asset: impl ToString,
+        direction: Direction,
         amount: f64,
-        time: u32,
-    ) -> PocketResult<(Uuid, Deal)> {
-        info!(target: "Buy", "Placing a buy trade for asset '{}', with amount '{}' and time '{}'", asset.to_string(), amount, time);
-        self.trade(asset, Action::Call, amount, time).await
+        expiry: u32,
+        options: OrderOptions,
+    ) -> PocketResult<OrderPlacement> {
+        self.ensure_trading("place_order")?;
+        let asset = asset.to_string();
+        if options.dry_run {
+            let payout = self.client().data.get_payout(&asset).await;
+            options.check_payout(&asset, payout)?;
+            let amount = self.round_stake(amount).await?;
+            let intent = TradeIntent::new(&asset, direction.clone(), amount, expiry);
+            self.risk().check(&intent)?;
+            self.risk().rollback(&intent);
+            info!(target: "PlaceOrder", "Dry run of a {:?} trade on '{}' with amount '{}' and expiry '{}' passed every check", direction, asset, amount, expiry);
+            return Ok(OrderPlacement::DryRun {
+                asset,
+                direction,
+                amount,
+                expiry,
+                payout,
+            });
+        }
+        let (id, deal) = self
+            .trade_with_min_payout(asset, direction, amount, expiry, options.min_payout)
+            .await?;
+        let tagged = if options.tags.is_empty() {
+            Ok(())
+        } else {
+            self.journal().tag(&[id], &options.tags)
+        };
+        if let Err(e) = tagged {
+            warn!(target: "Journal", "Failed to tag trade '{id}' in the journal, {e}");
+        }
+        Ok(OrderPlacement::Placed { id, deal })
     }
 
-    /// Places a sell (PUT) order.
+    /// Places an offsetting trade for an already placed trade on a correlated asset, and links
+    /// both trades in the journal as a hedge group.
     ///
     /// # Arguments
-    /// * `asset` - Trading symbol (e.g., "EURUSD")
-    /// * `amount` - Trade amount in account currency
-    /// * `time` - Trade duration in seconds
+    /// * `trade_id` - Id of the primary trade, it must have been placed by this client
+    /// * `hedge_asset` - Asset correlated to the asset of the primary trade (e.g. "USDCHF" for "EURUSD")
+    /// * `ratio` - Amount of the hedge trade relative to the primary trade amount
+    /// * `negative_correlation` - If true the hedge is placed in the same direction as the primary
+    ///   trade (negatively correlated assets move in opposite directions), otherwise in the opposite direction
     ///
     /// # Returns
-    /// A tuple containing the trade ID (UUID) and trade details (Deal)
+    /// The id of the hedge group along with the id and details of the hedge trade
     ///
     /// # Examples
     /// ```rust
-    /// let (trade_id, deal) = client.sell("EURUSD", 100.0, 60).await?;
+    /// let (trade_id, _) = client.buy("EURUSD_otc", 10.0, 60).await?;
+    /// let (group, hedge_id, hedge) = client.hedge(trade_id, "USDCHF_otc", 0.5, true).await?;
     /// ```
-    pub async fn sell(
+    pub async fn hedge(
+        &self,
+        trade_id: Uuid,
+        hedge_asset: impl ToString,
+        ratio: f64,
+        negative_correlation: bool,
+    ) -> PocketResult<(Uuid, Uuid, Deal)> {
+        let primary = self.journal().entry(trade_id).ok_or_else(|| {
+            PocketOptionError::Unallowed(format!(
+                "trade '{trade_id}' wasn't placed by this client, it can't be hedged"
+            ))
+        })?;
+        if !ratio.is_finite() || ratio <= 0.0 {
+            return Err(PocketOptionError::Unallowed(format!(
+                "hedge ratio must be a positive number, got {ratio}"
+            )));
+        }
+        let action = match (primary.action, negative_correlation) {
+            (action, true) => action,
+            (Action::Call, false) => Action::Put,
+            (Action::Put, false) => Action::Call,
+        };
+        let amount = (primary.amount * ratio * 100.0).round() / 100.0;
+        info!(target: "Hedge", "Hedging trade '{}' on '{}' with a {:?} trade of {} on '{}'", trade_id, primary.asset, action, amount, hedge_asset.to_string());
+        let (hedge_id, hedge) = self.trade(hedge_asset, action, amount, primary.time).await?;
+        let group = self.journal().group(&[trade_id, hedge_id])?;
+        Ok((group, hedge_id, hedge))
+    }
+
+    /// Places a straddle: a call and a put of `amount` on `asset` expiring in `expiry` seconds, sent
+    /// together so both legs open at the same price. Both legs go through the risk rules like any
+    /// other trade, are tagged `straddle` and grouped in the journal, and their combined result is
+    /// sent to `subscribe_straddle_results` once both closed.
+    ///
+    /// # Errors
+    /// Returns a `StraddleIncomplete` error with the id of the placed leg if only one of them was
+    /// placed, it's left open.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let results = client.subscribe_straddle_results(None).await;
+    /// let straddle = client.straddle("EURUSD", 10.0, 300).await?;
+    /// let result = results.recieve().await?;
+    /// println!("Straddle {} closed with a profit of {}", result.group, result.profit);
+    /// ```
+    pub async fn straddle(
         &self,
         asset: impl ToString,
         amount: f64,
-        time: u32,
-    ) -> PocketResult<(Uuid, Deal)> {
-        info!(target: "Sell", "Placing a sell trade for asset '{}', with amount '{}' and time '{}'", asset.to_string(), amount, time);
-        self.trade(asset, Action::Put, amount, time).await
+        expiry: u32,
+    ) -> PocketResult<Straddle> {
+        self.ensure_trading("straddle")?;
+        let asset = self.resolve_asset(asset).await;
+        info!(target: "Straddle", "Placing a straddle of {} on '{}' expiring in {}s", amount, asset, expiry);
+        let (call, put) = tokio::join!(
+            self.trade(&asset, Action::Call, amount, expiry),
+            self.trade(&asset, Action::Put, amount, expiry)
+        );
+        let ((call_id, call), (put_id, put)) = match (call, put) {
+            (Ok(call), Ok(put)) => (call, put),
+            (Ok((placed, _)), Err(e)) | (Err(e), Ok((placed, _))) => {
+                warn!(target: "Straddle", "Only one leg of the straddle on '{asset}' was placed, {e}");
+                return Err(PocketOptionError::StraddleIncomplete {
+                    placed,
+                    reason: e.to_string(),
+                });
+            }
+            (Err(e), Err(_)) => return Err(e),
+        };
+        let group = self.journal().group(&[call_id, put_id])?;
+        if let Err(e) = self
+            .journal()
+            .tag(&[call_id, put_id], &[STRADDLE_TAG.to_string()])
+        {
+            warn!(target: "Journal", "Failed to tag straddle '{group}' in the journal, {e}");
+        }
+        let client = self.clone();
+        let handle = tokio::spawn(async move {
+            match tokio::join!(client.check_results(call_id), client.check_results(put_id)) {
+                (Ok(call), Ok(put)) => {
+                    let result = StraddleResult::new(group, call, put);
+                    info!(target: "Straddle", "Straddle '{}' closed with a profit of {}", group, result.profit);
+                    client.client().data.send_straddle_result(result).await;
+                }
+                (Err(e), _) | (_, Err(e)) => {
+                    warn!(target: "Straddle", "Failed to get the result of straddle '{group}', {e}")
+                }
+            }
+        });
+        self.track_task(&handle);
+        Ok(Straddle {
+            group,
+            asset,
+            amount,
+            expiry,
+            call,
+            put,
+        })
     }
 
-    /// Gets the end time of a deal by its ID.
+    /// Executes an order template: resolves its stake and expiry against the current state of the
+    /// account, runs its checks and places its trades (through the risk rules like any other trade).
+    /// The trades are tagged in the journal with `template:<name>` and the risk tags of the template,
+    /// and grouped if there are more than one.
     ///
-    /// # Arguments
-    /// * `id` - UUID of the trade
+    /// # Examples
+    /// ```rust
+    /// let store = TemplateStore::load("templates.json")?;
+    /// let execution = client.execute_template(store.get("news_straddle_eurusd")?).await?;
+    /// ```
+    pub async fn execute_template(
+        &self,
+        template: &OrderTemplate,
+    ) -> PocketResult<TemplateExecution> {
+        let context = TemplateContext {
+            balance: self.get_balance().await.balance,
+            payout: self.client().data.get_payout(&template.asset).await,
+            valid_expiries: self
+                .valid_expiries(&template.asset)
+                .await
+                .unwrap_or_default(),
+            demo: self.is_demo().await,
+            now: Utc::now(),
+        };
+        let intents = template.resolve(&context)?;
+        info!(target: "OrderTemplate", "Executing template '{}' ({} trades)", template.name, intents.len());
+        let tags = template.journal_tags();
+        let mut trades = Vec::with_capacity(intents.len());
+        for intent in intents {
+            let (id, deal) = self
+                .trade(intent.asset, intent.action, intent.amount, intent.time)
+                .await?;
+            self.journal().tag(&[id], &tags)?;
+            trades.push(deal);
+        }
+        let group = if trades.len() > 1 {
+            let ids: Vec<Uuid> = trades.iter().map(|d| d.id).collect();
+            Some(self.journal().group(&ids)?)
+        } else {
+            None
+        };
+        Ok(TemplateExecution {
+            template: template.name.clone(),
+            group,
+            trades,
+        })
+    }
+
+    /// Returns the journal of the trades placed by the client, it's shared between all the clones of the client.
+    pub fn journal(&self) -> &Journal {
+        &self.inner.journal
+    }
+
+    /// Loads the trades of a history exported from the broker website (CSV, or XLSX with the `xlsx`
+    /// feature) in the journal, so its statistics include the trades placed before using the client.
+    /// The imported trades are tagged `import`, the ones already in the journal are skipped.
     ///
     /// # Returns
-    /// Optional DateTime indicating when the trade will expire, adjusted for server time
-    /// Returns None if the trade is not found
-    pub async fn get_deal_end_time(&self, id: Uuid) -> Option<DateTime<Utc>> {
-        if let Some(trade) = self
-            .client
-            .data
-            .get_opened_deals()
-            .await
-            .iter()
-            .find(|d| *d == &id)
-        {
-            return Some(trade.close_timestamp - Duration::from_secs(2 * 3600)); // Pocket Option server seems 2 hours advanced
+    /// The number of trades added to the journal
+    ///
+    /// # Examples
+    /// ```rust
+    /// client.journal().persist_to("journal.json")?;
+    /// let added = client.import_trades("trades_export.csv")?;
+    /// println!("{:?}", client.journal().stats(None));
+    /// ```
+    pub fn import_trades(&self, path: impl AsRef<Path>) -> PocketResult<usize> {
+        let entries = load_trade_export(path)?;
+        info!(target: "Journal", "Importing {} trades from a broker export", entries.len());
+        self.journal().import(entries)
+    }
+
+    /// Sets the rounding rules of the stakes: before every order the amount is rounded to the step
+    /// of the rule of the account currency, and rejected with an `InvalidStake` error if it's out
+    /// of its limits instead of being sent to the broker. The rules are shared between all the
+    /// clones of the client.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let rules = StakeRules::new()
+    ///     .rule("USD", StakeRule::new(0.01).min(1.0).max(20000.0))
+    ///     .rule("RUB", StakeRule::new(1.0).min(50.0).rounding(StakeRounding::Down));
+    /// client.set_stake_rules(rules)?;
+    /// ```
+    pub fn set_stake_rules(&self, rules: StakeRules) -> PocketResult<()> {
+        rules.validate()?;
+        info!(target: "StakeRules", "Stake rules set to {:?}", rules);
+        info!(target: AUDIT_TARGET, event = "config_change", setting = "stake_rules", value = ?rules);
+        *self
+            .inner
+            .stakes
+            .write()
+            .map_err(|e| PocketOptionError::UnreachableError(e.to_string()))? = rules;
+        Ok(())
+    }
+
+    pub fn stake_rules(&self) -> StakeRules {
+        self.inner
+            .stakes
+            .read()
+            .map(|r| r.clone())
+            .unwrap_or_default()
+    }
+
+    /// Returns the currency of the account, the one of the stake rules if it's set, otherwise the
+    /// one of the deals of the account. `None` if it isn't set and the account has no deal yet.
+    pub async fn account_currency(&self) -> Option<String> {
+        if let Some(currency) = self.stake_rules().currency {
+            return Some(currency);
         }
+        let data = &self.client().data;
+        let mut deals = data.get_opened_deals().await;
+        deals.extend(data.get_closed_deals().await);
+        deals
+            .into_iter()
+            .map(|d| d.currency)
+            .find(|c| !c.is_empty())
+    }
 
-        if let Some(trade) = self
-            .client
-            .data
-            .get_opened_deals()
-            .await
-            .iter()
-            .find(|d| *d == &id)
-        {
-            return Some(trade.close_timestamp - Duration::from_secs(2 * 3600)); // Pocket Option server seems 2 hours advanced
+    /// Rounds a stake with the stake rules of the account currency, as it would be sent by `trade`.
+    pub async fn round_stake(&self, amount: f64) -> PocketResult<f64> {
+        let currency = self.account_currency().await;
+        let stake = self.stake_rules().apply(amount, currency.as_deref())?;
+        if stake != amount {
+            debug!(target: "StakeRules", "Rounded stake of {} to {} {}", amount, stake, currency.as_deref().unwrap_or_default());
         }
-        None
+        Ok(stake)
     }
 
-    /// Checks the results of a trade by its ID.
+    /// Returns the `RiskManager` checking every trade before it's placed, it's shared between all the clones of the client.
+    pub fn risk(&self) -> &RiskManager {
+        &self.inner.risk
+    }
+
+    /// Limits the number of trades placed on the same asset in a time window, trades over the limit
+    /// are rejected with a `RiskRuleViolation` error instead of being sent.
     ///
     /// # Arguments
-    /// * `trade_id` - UUID of the trade to check
+    /// * `max_trades` - Maximum number of trades per asset in the window
+    /// * `window` - Duration of the window
+    /// * `aligned` - If true the windows are aligned to the candle boundaries (max trades per candle), otherwise it's a sliding window
     ///
-    /// # Returns
-    /// Deal information containing profit/loss and other trade details
+    /// # Examples
+    /// ```rust
+    /// // At most 1 trade per asset per 1 minute candle
+    /// client.add_cluster_guard(1, Duration::from_secs(60), true);
+    /// ```
+    pub fn add_cluster_guard(&self, max_trades: usize, window: Duration, aligned: bool) {
+        info!(target: "RiskManager", "Limiting trades to {} per asset every {:?}", max_trades, window);
+        self.risk().add_rule(ClusterGuard::new(max_trades, window, aligned));
+    }
+
+    /// Stops trading once the balance fell more than `max_drawdown` (a fraction, `0.2` is 20%) from
+    /// its peak, the trades are then rejected with a `RiskRuleViolation` error or, if `demo_ssid` is
+    /// given, placed on the demo account to keep the strategy running for diagnostics.
+    /// When the breaker trips an error is logged, the event is written to the audit log and a
+    /// `CircuitBreaker` account event is sent to the subscribers of `subscribe_account_events`.
+    /// The trades redirected to the demo account are tracked by the demo client, use
+    /// `drawdown_demo_client` to check their results.
     ///
-    /// # Errors
-    /// Returns an OutOfRangeError if the deal is not found in closed deals after expiration
+    /// # Arguments
+    /// * `max_drawdown` - Maximum drawdown from the peak balance, between 0 and 1 (exclusive)
+    /// * `demo_ssid` - Optional SSID of the demo account the trades are redirected to once the breaker tripped
     ///
     /// # Examples
     /// ```rust
-    /// let result = client.check_results(trade_id).await?;
-    /// println!("Trade profit: {}", result.profit);
+    /// client.set_drawdown_breaker(0.2, Some(demo_ssid)).await?;
     /// ```
-    pub async fn check_results(&self, trade_id: Uuid) -> PocketResult<Deal> {
-        info!(target: "CheckResults", "Checking results for trade of id {}", trade_id);
-        if let Some(trade) = self
-            .client
-            .data
-            .get_closed_deals()
+    pub async fn set_drawdown_breaker(
+        &self,
+        max_drawdown: f64,
+        demo_ssid: Option<String>,
+    ) -> PocketResult<()> {
+        let breaker = Arc::new(DrawdownBreaker::new(max_drawdown)?);
+        breaker.update_balance(self.client().data.get_balance().await.balance);
+        let demo = match demo_ssid {
+            Some(ssid) => {
+                if !Ssid::parse(&ssid)?.demo() {
+                    return Err(PocketOptionError::Unallowed(
+                        "the fallback ssid of the drawdown breaker must be of a demo account"
+                            .into(),
+                    ));
+                }
+                Some(PocketOption::new(ssid).await?)
+            }
+            None => None,
+        };
+        info!(target: "RiskManager", "Stopping trading after a drawdown of {}%", max_drawdown * 100.0);
+        info!(target: AUDIT_TARGET, event = "config_change", setting = "drawdown_breaker", max_drawdown, demo_fallback = demo.is_some());
+        self.risk().remove_rule(breaker.name());
+        self.risk().add_shared_rule(breaker.clone());
+        let mut drawdown = self
+            .inner
+            .drawdown
+            .write()
+            .map_err(|e| PocketOptionError::UnreachableError(e.to_string()))?;
+        *drawdown = Some(DrawdownSafety { breaker, demo });
+        Ok(())
+    }
+
+    /// Returns the state of the drawdown breaker (peak balance, current drawdown, when it tripped),
+    /// `None` if it isn't set.
+    pub fn drawdown_state(&self) -> Option<DrawdownState> {
+        self.drawdown_safety().map(|d| d.breaker.state())
+    }
+
+    /// Returns the demo client the trades are redirected to once the drawdown breaker tripped.
+    pub fn drawdown_demo_client(&self) -> Option<PocketOption> {
+        self.drawdown_safety().and_then(|d| d.demo)
+    }
+
+    /// Re-arms the drawdown breaker after it tripped, the current balance becomes the new peak.
+    pub fn reset_drawdown_breaker(&self) {
+        if let Some(safety) = self.drawdown_safety() {
+            info!(target: "DrawdownBreaker", "Drawdown breaker reset");
+            info!(target: AUDIT_TARGET, event = "config_change", setting = "drawdown_breaker", action = "reset");
+            safety.breaker.reset();
+        }
+    }
+
+    /// Only allows the trades placed in the hours and weekdays (UTC) of `hours`, replacing the
+    /// previous `TradingHours` rule if any. Trades placed outside of them are rejected with a
+    /// `RiskRuleViolation` error.
+    ///
+    /// # Examples
+    /// ```rust
+    /// // Only trade from 8h to 12h on weekdays
+    /// client.set_trading_hours(TradingHours::new().hours(8..12)?.weekdays(0..5)?);
+    /// ```
+    pub fn set_trading_hours(&self, hours: TradingHours) {
+        info!(target: "RiskManager", "Only trading at hours {:?} on weekdays {:?}", hours.allowed_hours(), hours.allowed_weekdays());
+        self.risk().remove_rule(hours.name());
+        self.risk().add_rule(hours);
+    }
+
+    /// Only allows the trades placed in the hours (UTC) the closed trades of the journal matching
+    /// `filter` were profitable at, with at least `min_trades` trades. Returns the allowed hours.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let hours = client.trade_profitable_hours(&TradeFilter::new().tag("strategy:trend"), 20)?;
+    /// ```
+    pub fn trade_profitable_hours(
+        &self,
+        filter: &TradeFilter,
+        min_trades: usize,
+    ) -> PocketResult<Vec<u32>> {
+        let performance = self.journal().performance_by_hour(filter, false)?;
+        let hours = TradingHours::new().profitable(&performance, min_trades)?;
+        let allowed = hours.allowed_hours();
+        self.set_trading_hours(hours);
+        Ok(allowed)
+    }
+
+    /// Pauses the order placement while the market conditions are abnormal: quote latency, gaps in
+    /// the ticks of an asset or volatility over the thresholds of `config`. Trading resumes on its
+    /// own once the conditions stayed normal for `resume_after` seconds. The trades placed during a
+    /// pause are rejected with a `RiskRuleViolation` error, and every pause and resume is logged,
+    /// written to the audit log and sent as a `CircuitBreaker` account event to the subscribers of
+    /// `subscribe_account_events`. It replaces the previous market guard if any.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let config = MarketGuardConfig {
+    ///     max_latency: Some(800.0),
+    ///     max_gap: Some(10.0),
+    ///     ..Default::default()
+    /// };
+    /// client.set_market_guard(config)?;
+    /// ```
+    pub fn set_market_guard(&self, config: MarketGuardConfig) -> PocketResult<()> {
+        let guard = Arc::new(MarketGuard::new(config)?);
+        info!(target: "MarketGuard", "Pausing the trading on abnormal market conditions, {:?}", guard.config());
+        self.risk().remove_rule(guard.name());
+        self.risk().add_shared_rule(guard.clone());
+        self.client().data.set_market_guard(Some(guard));
+        Ok(())
+    }
+
+    pub fn remove_market_guard(&self) {
+        if let Some(guard) = self.client().data.market_guard() {
+            self.risk().remove_rule(guard.name());
+            self.client().data.set_market_guard(None);
+        }
+    }
+
+    /// Returns the assets the market guard currently pauses the trading on, empty if it isn't set.
+    pub fn market_guard_status(&self) -> Vec<MarketPause> {
+        self.client()
+            .data
+            .market_guard()
+            .map(|guard| guard.status())
+            .unwrap_or_default()
+    }
+
+    fn drawdown_safety(&self) -> Option<DrawdownSafety> {
+        self.inner.drawdown.read().ok().and_then(|d| d.clone())
+    }
+
+    /// Updates the drawdown breaker with the current balance, alerting if it tripped. Returns the
+    /// demo client the trades must be placed on if the breaker is tripped and has one.
+    async fn check_drawdown(&self) -> Option<PocketOption> {
+        let safety = self.drawdown_safety()?;
+        let balance = self.client().data.get_balance().await.balance;
+        if safety.breaker.update_balance(balance) {
+            let state = safety.breaker.state();
+            let message = format!(
+                "Drawdown breaker tripped: balance fell {:.2}% from its peak of {} to {}, over the maximum of {:.2}%. {}",
+                state.drawdown * 100.0,
+                state.peak.unwrap_or_default(),
+                balance,
+                state.max_drawdown * 100.0,
+                if safety.demo.is_some() {
+                    "Trades are redirected to the demo account"
+                } else {
+                    "Trading is stopped"
+                }
+            );
+            error!(target: "DrawdownBreaker", "{message}");
+            warn!(target: AUDIT_TARGET, event = "drawdown_breaker", drawdown = state.drawdown, peak = ?state.peak, balance, demo_fallback = safety.demo.is_some());
+            self.client()
+                .data
+                .send_account_event(AccountEvent {
+                    kind: AccountEventKind::CircuitBreaker,
+                    event: "drawdownBreaker".into(),
+                    message: Some(message),
+                    amount: Some(balance),
+                    data: serde_json::to_value(&state).unwrap_or_default(),
+                    received_at: Utc::now(),
+                })
+                .await;
+        }
+        if safety.breaker.is_tripped() {
+            safety.demo
+        } else {
+            None
+        }
+    }
+
+    /// Places a buy (CALL) order.
+    ///
+    /// # Arguments
+    /// * `asset` - Trading symbol (e.g., "EURUSD")
+    /// * `amount` - Trade amount in account currency
+    /// * `time` - Trade duration in seconds
+    ///
+    /// # Returns
+    /// A tuple containing the trade ID (UUID) and trade details (Deal)
+    ///
+    /// # Examples
+    /// ```rust
+    /// let (trade_id, deal) = client.buy("EURUSD", 100.0, 60).await?;
+    /// ```
+    pub async fn buy(
+        &self,
+        asset: impl ToString,
+        amount: f64,
+        time: u32,
+    ) -> PocketResult<(Uuid, Deal)> {
+        info!(target: "Buy", "Placing a buy trade for asset '{}', with amount '{}' and time '{}'", asset.to_string(), amount, time);
+        self.trade(asset, Action::Call, amount, time).await
+    }
+
+    /// Places a sell (PUT) order.
+    ///
+    /// # Arguments
+    /// * `asset` - Trading symbol (e.g., "EURUSD")
+    /// * `amount` - Trade amount in account currency
+    /// * `time` - Trade duration in seconds
+    ///
+    /// # Returns
+    /// A tuple containing the trade ID (UUID) and trade details (Deal)
+    ///
+    /// # Examples
+    /// ```rust
+    /// let (trade_id, deal) = client.sell("EURUSD", 100.0, 60).await?;
+    /// ```
+    pub async fn sell(
+        &self,
+        asset: impl ToString,
+        amount: f64,
+        time: u32,
+    ) -> PocketResult<(Uuid, Deal)> {
+        info!(target: "Sell", "Placing a sell trade for asset '{}', with amount '{}' and time '{}'", asset.to_string(), amount, time);
+        self.trade(asset, Action::Put, amount, time).await
+    }
+
+    /// Registers a buy (CALL) order placed once the quote of the asset crosses `price`, see
+    /// `order_at_price`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let order = client.buy_at_price("EURUSD_otc", 1.0, 60, 1.0850).await?;
+    /// ```
+    pub async fn buy_at_price(
+        &self,
+        asset: impl ToString,
+        amount: f64,
+        time: u32,
+        price: f64,
+    ) -> PocketResult<PriceOrder> {
+        self.order_at_price(asset, Action::Call, amount, time, price, None)
+            .await
+    }
+
+    /// Registers a sell (PUT) order placed once the quote of the asset crosses `price`, see
+    /// `order_at_price`.
+    pub async fn sell_at_price(
+        &self,
+        asset: impl ToString,
+        amount: f64,
+        time: u32,
+        price: f64,
+    ) -> PocketResult<PriceOrder> {
+        self.order_at_price(asset, Action::Put, amount, time, price, None)
+            .await
+    }
+
+    /// Registers an order placed once the quote of the asset crosses `price`, coming from above or
+    /// below depending on the quote when the order is registered. The client subscribes to the asset
+    /// and places the trade (through the risk rules and the journal like any other trade) from the
+    /// task recieving the ticks, as soon as the tick crossing the price arrives.
+    ///
+    /// # Arguments
+    /// * `asset` - Trading symbol (e.g., "EURUSD_otc")
+    /// * `direction` - Direction of the trade (Call/Put)
+    /// * `amount` - Trade amount in account currency
+    /// * `time` - Trade duration in seconds
+    /// * `price` - Quote triggering the trade
+    /// * `valid_for` - The order expires if the price isn't crossed in time, it waits until it's
+    ///   cancelled if `None`
+    ///
+    /// # Returns
+    /// The registered order, its state is returned by `price_orders` afterwards
+    ///
+    /// # Examples
+    /// ```rust
+    /// let order = client
+    ///     .order_at_price("EURUSD_otc", Direction::Put, 1.0, 60, 1.0900, Some(Duration::from_secs(600)))
+    ///     .await?;
+    /// client.cancel_price_order(order.id)?;
+    /// ```
+    pub async fn order_at_price(
+        &self,
+        asset: impl ToString,
+        direction: Direction,
+        amount: f64,
+        time: u32,
+        price: f64,
+        valid_for: Option<Duration>,
+    ) -> PocketResult<PriceOrder> {
+        self.ensure_trading("order_at_price")?;
+        let expires_at = valid_for
+            .and_then(|d| TimeDelta::from_std(d).ok())
+            .map(|d| Utc::now() + d);
+        let order = PriceOrder::new(asset, direction, amount, time, price, expires_at)?;
+        let stream = self.subscribe_symbol(&order.asset).await?;
+        self.inner.price_orders.insert(order.clone());
+        info!(target: "PriceOrder", "Waiting for '{}' to cross {} to place a {:?} trade with amount '{}'", order.asset, price, order.direction, amount);
+        let client = self.clone();
+        let watched = order.clone();
+        let handle =
+            tokio::spawn(async move { client.watch_price_order(watched, stream, valid_for).await });
+        self.inner
+            .price_orders
+            .set_task(order.id, handle.abort_handle());
+        self.track_task(&handle);
+        Ok(order)
+    }
+
+    /// Watches the ticks of a price order and places its trade once the price is crossed.
+    async fn watch_price_order(
+        &self,
+        order: PriceOrder,
+        stream: StreamAsset,
+        valid_for: Option<Duration>,
+    ) {
+        let orders = &self.inner.price_orders;
+        let mut trigger = PriceTrigger::new(order.price);
+        let crossing = async {
+            loop {
+                let quote = stream.recieve().await?.close;
+                if trigger.crossed(quote) {
+                    return PocketResult::Ok(quote);
+                }
+            }
+        };
+        let crossed = match valid_for {
+            Some(valid_for) => tokio::time::timeout(valid_for, crossing).await.ok(),
+            None => Some(crossing.await),
+        };
+        let quote = match crossed {
+            Some(Ok(quote)) => quote,
+            Some(Err(e)) => {
+                warn!(target: "PriceOrder", "Stopped watching price order '{}', {e}", order.id);
+                orders.transition(
+                    order.id,
+                    PriceOrderState::Failed {
+                        error: e.to_string(),
+                    },
+                );
+                return;
+            }
+            None => {
+                info!(target: "PriceOrder", "Price order '{}' expired, '{}' didn't cross {}", order.id, order.asset, order.price);
+                orders.transition(order.id, PriceOrderState::Expired);
+                return;
+            }
+        };
+        if !orders.transition(order.id, PriceOrderState::Sending { quote }) {
+            return;
+        }
+        info!(target: "PriceOrder", "'{}' crossed {} at {}, placing price order '{}'", order.asset, order.price, quote, order.id);
+        let state = match self
+            .trade(
+                &order.asset,
+                order.direction.clone(),
+                order.amount,
+                order.time,
+            )
+            .await
+        {
+            Ok((trade_id, _)) => PriceOrderState::Triggered { trade_id, quote },
+            Err(e) => {
+                warn!(target: "PriceOrder", "Failed to place price order '{}', {e}", order.id);
+                PriceOrderState::Failed {
+                    error: e.to_string(),
+                }
+            }
+        };
+        orders.finish(order.id, state);
+    }
+
+    /// Returns the price orders registered by the client with their state, oldest first.
+    pub fn price_orders(&self) -> Vec<PriceOrder> {
+        self.inner.price_orders.list()
+    }
+
+    /// Cancels a price order waiting for its price. Fails if the price was already crossed.
+    pub fn cancel_price_order(&self, id: Uuid) -> PocketResult<PriceOrder> {
+        let order = self.inner.price_orders.cancel(id)?;
+        info!(target: "PriceOrder", "Cancelled price order '{}'", id);
+        Ok(order)
+    }
+
+    /// Estimated offset between the local and the server clocks (local - server), from the latency
+    /// of the ticks recieved so far, zero before the first tick.
+    async fn clock_offset(&self) -> TimeDelta {
+        self.client()
+            .data
+            .latency_stats()
+            .await
+            .clock_offset_delta()
+            .unwrap_or_default()
+    }
+
+    /// Queues a buy (CALL) order placed at `execute_at` on the server clock, see `order_at_time`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let execute_at = "2025-06-02T14:05:00Z".parse()?;
+    /// let order = client.buy_at_time("EURUSD_otc", 1.0, 60, execute_at).await?;
+    /// ```
+    pub async fn buy_at_time(
+        &self,
+        asset: impl ToString,
+        amount: f64,
+        time: u32,
+        execute_at: DateTime<Utc>,
+    ) -> PocketResult<ScheduledOrder> {
+        self.order_at_time(asset, Action::Call, amount, time, execute_at)
+            .await
+    }
+
+    /// Queues a sell (PUT) order placed at `execute_at` on the server clock, see `order_at_time`.
+    pub async fn sell_at_time(
+        &self,
+        asset: impl ToString,
+        amount: f64,
+        time: u32,
+        execute_at: DateTime<Utc>,
+    ) -> PocketResult<ScheduledOrder> {
+        self.order_at_time(asset, Action::Put, amount, time, execute_at)
+            .await
+    }
+
+    /// Queues an order placed by the client at `execute_at` on the server clock. The server clock
+    /// is estimated from the latency of the ticks recieved (like `latency_stats`), so at least one
+    /// asset should be subscribed to, the local clock is used until a tick is recieved. The estimation
+    /// is refreshed right before the execution time and the trade goes through the risk rules and
+    /// the journal like any other trade.
+    ///
+    /// # Arguments
+    /// * `asset` - Trading symbol (e.g., "EURUSD_otc")
+    /// * `direction` - Direction of the trade (Call/Put)
+    /// * `amount` - Trade amount in account currency
+    /// * `time` - Trade duration in seconds
+    /// * `execute_at` - Server time the trade is placed at, it must be in the future
+    ///
+    /// # Returns
+    /// The queued order, its state is returned by `scheduled_orders` afterwards
+    ///
+    /// # Examples
+    /// ```rust
+    /// let order = client
+    ///     .order_at_time("EURUSD_otc", Direction::Call, 1.0, 60, Utc::now() + TimeDelta::seconds(30))
+    ///     .await?;
+    /// client.cancel_scheduled_order(order.id)?;
+    /// ```
+    pub async fn order_at_time(
+        &self,
+        asset: impl ToString,
+        direction: Direction,
+        amount: f64,
+        time: u32,
+        execute_at: DateTime<Utc>,
+    ) -> PocketResult<ScheduledOrder> {
+        self.ensure_trading("order_at_time")?;
+        let server_now = Utc::now() - self.clock_offset().await;
+        let order = ScheduledOrder::new(asset, direction, amount, time, execute_at, server_now)?;
+        self.inner.scheduled_orders.insert(order.clone());
+        info!(target: "ScheduledOrder", "Scheduled a {:?} trade on '{}' with amount '{}' at {}", order.direction, order.asset, amount, execute_at);
+        let client = self.clone();
+        let scheduled = order.clone();
+        let handle = tokio::spawn(async move { client.run_scheduled_order(scheduled).await });
+        self.inner
+            .scheduled_orders
+            .set_task(order.id, handle.abort_handle());
+        self.track_task(&handle);
+        Ok(order)
+    }
+
+    /// Waits for the execution time of a scheduled order and places its trade.
+    async fn run_scheduled_order(&self, order: ScheduledOrder) {
+        let wait = order.wait_time(self.clock_offset().await, Utc::now());
+        if let Some(wait) = wait.checked_sub(SCHEDULE_REFINE_WINDOW) {
+            tokio::time::sleep(wait).await;
+        }
+        let offset = self.clock_offset().await;
+        tokio::time::sleep(order.wait_time(offset, Utc::now())).await;
+        if !self.inner.scheduled_orders.start(order.id) {
+            return;
+        }
+        let delay = (Utc::now() - offset - order.execute_at)
+            .num_microseconds()
+            .map(|us| us as f64 / 1000.0)
+            .unwrap_or_default();
+        info!(target: "ScheduledOrder", "Placing scheduled order '{}' ({:.3} ms after its execution time)", order.id, delay);
+        let state = match self
+            .trade(
+                &order.asset,
+                order.direction.clone(),
+                order.amount,
+                order.time,
+            )
+            .await
+        {
+            Ok((trade_id, _)) => ScheduledOrderState::Placed { trade_id, delay },
+            Err(e) => {
+                warn!(target: "ScheduledOrder", "Failed to place scheduled order '{}', {e}", order.id);
+                ScheduledOrderState::Failed {
+                    error: e.to_string(),
+                }
+            }
+        };
+        self.inner.scheduled_orders.finish(order.id, state);
+    }
+
+    /// Returns the orders queued by `order_at_time` with their state, by execution time.
+    pub fn scheduled_orders(&self) -> Vec<ScheduledOrder> {
+        self.inner.scheduled_orders.list()
+    }
+
+    /// Cancels a scheduled order waiting for its execution time. Fails if it was already sent.
+    pub fn cancel_scheduled_order(&self, id: Uuid) -> PocketResult<ScheduledOrder> {
+        let order = self.inner.scheduled_orders.cancel(id)?;
+        info!(target: "ScheduledOrder", "Cancelled scheduled order '{}'", id);
+        Ok(order)
+    }
+
+    /// Gets the end time of a deal by its ID.
+    ///
+    /// # Arguments
+    /// * `id` - UUID of the trade
+    ///
+    /// # Returns
+    /// Optional DateTime indicating when the trade will expire, adjusted for server time
+    /// Returns None if the trade is not found
+    pub async fn get_deal_end_time(&self, id: Uuid) -> Option<DateTime<Utc>> {
+        if let Some(trade) = self
+            .client()
+            .data
+            .get_opened_deals()
+            .await
+            .iter()
+            .find(|d| *d == &id)
+        {
+            return Some(trade.close_timestamp - Duration::from_secs(2 * 3600)); // Pocket Option server seems 2 hours advanced
+        }
+
+        if let Some(trade) = self
+            .client()
+            .data
+            .get_opened_deals()
+            .await
+            .iter()
+            .find(|d| *d == &id)
+        {
+            return Some(trade.close_timestamp - Duration::from_secs(2 * 3600)); // Pocket Option server seems 2 hours advanced
+        }
+        None
+    }
+
+    /// Checks the results of a trade by its ID.
+    ///
+    /// # Arguments
+    /// * `trade_id` - UUID of the trade to check
+    ///
+    /// # Returns
+    /// Deal information containing profit/loss and other trade details
+    ///
+    /// # Errors
+    /// Returns an OutOfRangeError if the deal is not found in closed deals after expiration
+    ///
+    /// # Examples
+    /// ```rust
+    /// let result = client.check_results(trade_id).await?;
+    /// println!("Trade profit: {}", result.profit);
+    /// ```
+    pub async fn check_results(&self, trade_id: Uuid) -> PocketResult<Deal> {
+        info!(target: "CheckResults", "Checking results for trade of id {}", trade_id);
+        self.check_results_inner(trade_id)
+            .await
+            .inspect(|deal| info!(target: AUDIT_TARGET, event = "order_result", %trade_id, server_ids = %self.server_ids_of(deal), profit = deal.profit))
+            .inspect(|deal| self.journal_close(deal))
+    }
+
+    /// Same as `check_results`, but fails with a `TradeTimeout` error if the result of the trade
+    /// isn't received within `timeout`.
+    pub async fn check_results_within(
+        &self,
+        trade_id: Uuid,
+        timeout: Duration,
+    ) -> PocketResult<Deal> {
+        tokio::time::timeout(timeout, self.check_results(trade_id))
+            .await
+            .map_err(|_| PocketOptionError::TradeTimeout {
+                trade_id,
+                seconds: timeout.as_secs_f64(),
+            })?
+    }
+
+    /// Waits for the results of several trades concurrently, with `check_results` or with
+    /// `check_results_within` if a `timeout` is given. The results are in the order of the ids, an
+    /// id given twice is checked once.
+    ///
+    /// # Examples
+    /// ```rust
+    /// for (trade_id, result) in client.check_results_many(&trade_ids, None).await {
+    ///     match result {
+    ///         Ok(deal) => println!("Trade {trade_id} closed with a profit of {}", deal.profit),
+    ///         Err(e) => println!("Trade {trade_id} failed, {e}"),
+    ///     }
+    /// }
+    /// ```
+    pub async fn check_results_many(
+        &self,
+        trade_ids: &[Uuid],
+        timeout: Option<Duration>,
+    ) -> Vec<(Uuid, PocketResult<Deal>)> {
+        let mut seen = HashSet::new();
+        let checks = trade_ids
+            .iter()
+            .filter(|id| seen.insert(**id))
+            .map(|&trade_id| async move {
+                let result = match timeout {
+                    Some(timeout) => self.check_results_within(trade_id, timeout).await,
+                    None => self.check_results(trade_id).await,
+                };
+                (trade_id, result)
+            });
+        join_all(checks).await
+    }
+
+    /// Returns the result of a trade without waiting, `None` while the trade is open. An unknown
+    /// trade also returns `None`, `get_opened_deals` tells it apart from an open one and
+    /// `check_results` fails for it.
+    pub async fn poll_result(&self, trade_id: Uuid) -> Option<Deal> {
+        let deal = self
+            .client()
+            .data
+            .get_closed_deals()
+            .await
+            .into_iter()
+            .find(|d| d.id == trade_id)?;
+        self.journal_close(&deal);
+        Some(deal)
+    }
+
+    /// Closes an open trade before its expiry (sell-out) for a partial payout, set by the server
+    /// from the price and the time left when it closes the trade.
+    ///
+    /// # Arguments
+    /// * `trade_id` - UUID of the open trade to close
+    ///
+    /// # Returns
+    /// The closed deal with its realized profit, also recorded in the journal
+    ///
+    /// # Errors
+    /// Returns an Unallowed error if the trade isn't open, or the error sent by the server if it
+    /// refused to close the trade (e.g. too close to its expiry)
+    ///
+    /// # Examples
+    /// ```rust
+    /// let (trade_id, _) = client.buy("EURUSD_otc", 1.0, 300).await?;
+    /// let deal = client.close_deal(trade_id).await?;
+    /// println!("Closed early with a profit of {}", deal.profit);
+    /// ```
+    pub async fn close_deal(&self, trade_id: Uuid) -> PocketResult<Deal> {
+        self.ensure_trading("close_deal")?;
+        self.inner.session.ensure_authenticated()?;
+        let data = &self.client().data;
+        if data.get_closed_deals().await.iter().any(|d| d == &trade_id) {
+            return Err(PocketOptionError::Unallowed(format!(
+                "trade '{trade_id}' is already closed"
+            )));
+        }
+        if !data.get_opened_deals().await.iter().any(|d| d == &trade_id) {
+            return Err(PocketOptionError::Unallowed(format!(
+                "trade '{trade_id}' isn't in the list of opened trades"
+            )));
+        }
+        let timeout = self.get_timeout()?;
+        let request = WebSocketMessage::CloseOrder(CloseOrder::new(trade_id));
+        info!(target: AUDIT_TARGET, event = "close_request", %trade_id, request = %request);
+        // Registered before the request is sent so a quick refusal isn't missed, and removed when
+        // the call ends (even if it's cancelled)
+        let mut refusal = data.wait_close_refusal(trade_id);
+        let res = tokio::select! {
+            res = self.client().send_message_with_timout(
+                timeout,
+                "CloseDeal",
+                request,
+                MessageInfo::SuccesscloseOrder,
+                &order_result_validator(trade_id),
+            ) => res.map_err(PocketOptionError::from),
+            fail = refusal.recv() => Err(BinaryOptionsToolsError::WebSocketMessageError(
+                PocketMessageFail::Close(fail).to_string(),
+            )
+            .into()),
+        }
+        .inspect_err(
+            |e| warn!(target: AUDIT_TARGET, event = "close_failed", %trade_id, error = %e),
+        )?;
+        let deal = match res {
+            WebSocketMessage::SuccesscloseOrder(order) => {
+                order.deals.into_iter().find(|d| d.id == trade_id).ok_or(
+                    PocketOptionError::UnreachableError("Error finding correct trade".into()),
+                )?
+            }
+            res => {
+                return Err(PocketOptionError::UnexpectedIncorrectWebSocketMessage(
+                    res.info(),
+                ));
+            }
+        };
+        info!(target: AUDIT_TARGET, event = "close_response", %trade_id, server_ids = %self.server_ids_of(&deal), profit = deal.profit);
+        self.journal_close(&deal);
+        Ok(deal)
+    }
+
+    /// Records the result of a closed trade in the journal and, if the journal captures the market
+    /// context (`Journal::capture_context`), stores the candles around the trade once the last one
+    /// closed.
+    fn journal_close(&self, deal: &Deal) {
+        match self.journal().close(deal) {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(e) => {
+                warn!(target: "Journal", "Failed to record the result of trade '{}' in the journal, {e}", deal.id);
+                return;
+            }
+        }
+        let Some(capture) = self.journal().context_capture() else {
+            return;
+        };
+        let client = self.clone();
+        let deal = deal.clone();
+        let handle = tokio::spawn(async move {
+            if let Err(e) = client.capture_context(&deal, capture).await {
+                warn!(target: "Journal", "Failed to store the candles around trade '{}' in the journal, {e}", deal.id);
+            }
+        });
+        self.track_task(&handle);
+    }
+
+    async fn capture_context(&self, deal: &Deal, capture: ContextCapture) -> PocketResult<()> {
+        let (start, end) = capture.range(deal.open_timestamp, deal.close_timestamp);
+        let server_time = self.client().data.get_server_time().await;
+        // One second of margin so the server sent the last update of the last candle
+        let wait = (end - server_time).max(0) as u64 + 1;
+        tokio::time::sleep(Duration::from_secs(wait)).await;
+        let candles = self
+            .get_candles_advanced(&deal.asset, end, capture.period, end - start, true)
+            .await?;
+        debug!(target: "Journal", "Storing {} candles around trade '{}' in the journal", candles.len(), deal.id);
+        self.journal().set_context(
+            deal.id,
+            MarketContext {
+                period: capture.period,
+                before: capture.before,
+                after: capture.after,
+                candles,
+                captured_at: Utc::now(),
+            },
+        )
+    }
+
+    async fn check_results_inner(&self, trade_id: Uuid) -> PocketResult<Deal> {
+        if let Some(trade) = self
+            .client()
+            .data
+            .get_closed_deals()
             .await
             .iter()
             .find(|d| d.id == trade_id)
@@ -391,7 +1906,7 @@ impl PocketOption {
             let start = Instant::now();
             // println!("Expiration time in {exp:?} seconds.");
             let res: WebSocketMessage = match self
-                .client
+                .client()
                 .send_message_with_timeout_and_retry(
                     exp + self.get_timeout()?,
                     "CheckResult",
@@ -438,77 +1953,489 @@ impl PocketOption {
         Err(BinaryOptionsToolsError::Unallowed("Couldn't check result for a deal that is not in the list of opened trades nor closed trades.".into()).into())
     }
 
+    /// Sends a single `loadHistoryPeriod` request, returning the candles of `asset` between `time - offset` and `time`.
+    async fn load_history_period(
+        &self,
+        asset: &str,
+        time: i64,
+        period: i64,
+        offset: i64,
+    ) -> PocketResult<Vec<DataCandle>> {
+        // Without a session the server time is never received either
+        self.inner.session.ensure_authenticated()?;
+        if time == 0 {
+            return Err(PocketOptionError::GeneralParsingError(
+                "Server time is invalid.".to_string(),
+            ));
+        }
+        let request = LoadHistoryPeriod::new(asset, time, period, offset)?;
+        let index = request.index;
+        debug!(
+            "Sent get candles message, message: {:?}",
+            WebSocketMessage::GetCandles(request).to_string()
+        );
+        let request = LoadHistoryPeriod::new(asset, time, period, offset)?;
+        let res = self
+            .client()
+            .send_message_with_timeout_and_retry(
+                self.get_timeout()?,
+                "GetCandles",
+                WebSocketMessage::GetCandles(request),
+                MessageInfo::LoadHistoryPeriod,
+                &candle_validator(index),
+            )
+            .await?;
+        if let WebSocketMessage::LoadHistoryPeriod(history) = res {
+            return Ok(history.candle_data());
+        }
+        Err(PocketOptionError::UnexpectedIncorrectWebSocketMessage(
+            res.info(),
+        ))
+    }
+
+    /// Retrieves the candles of `asset` between `time - offset` and `time`.
+    ///
+    /// The server sometimes returns incomplete pages for large requests, the missing segments are
+    /// requested again (up to `CANDLES_MAX_RETRIES` times with an exponential backoff), grouped in
+    /// pages of `CANDLES_PAGE_SIZE` candles and with at most `CANDLES_MAX_GAP_REQUESTS` requests.
+    /// If some candles are still missing it returns `PocketOptionError::IncompleteCandles` with the
+    /// missing ranges, unless `allow_partial` is true (e.g. for markets that were closed during the period).
+    /// The `period` is validated before sending any request (see `IntoTimeframe`).
     pub async fn get_candles_advanced(
         &self,
         asset: impl ToString,
-        time: i64,
-        period: i64,
-        offset: i64,
-    ) -> PocketResult<Vec<DataCandle>> {
-        info!(target: "GetCandlesAdvanced", "Retrieving candles for asset '{}' with period of '{}' and offset of '{}'", asset.to_string(), period, offset);
-        if time == 0 {
-            return Err(PocketOptionError::GeneralParsingError(
-                "Server time is invalid.".to_string(),
-            ));
+        time: i64,
+        period: impl IntoTimeframe,
+        offset: i64,
+        allow_partial: bool,
+    ) -> PocketResult<Vec<DataCandle>> {
+        let period = period.into_timeframe()?.seconds();
+        let asset = asset.to_string();
+        info!(target: "GetCandlesAdvanced", "Retrieving candles for asset '{}' with period of '{}' and offset of '{}'", asset, period, offset);
+        let (start, end) = (time - offset, time);
+        let mut candles = self
+            .load_history_period(&asset, time, period, offset)
+            .await?;
+        let mut missing = missing_ranges(&candles, start, end, period);
+        let mut attempt = 0;
+        let mut requests = CANDLES_MAX_GAP_REQUESTS;
+        while !missing.is_empty() && attempt < CANDLES_MAX_RETRIES && requests > 0 {
+            tokio::time::sleep(CANDLES_RETRY_BACKOFF * 2u32.pow(attempt)).await;
+            attempt += 1;
+            let pages = gap_pages(&missing, period, CANDLES_PAGE_SIZE, requests);
+            requests -= pages.len();
+            warn!(target: "GetCandlesAdvanced", "Received incomplete candles for '{}', requesting {} missing segment(s) again in {} request(s) (attempt {}/{})", asset, missing.len(), pages.len(), attempt, CANDLES_MAX_RETRIES);
+            for (time, offset) in pages {
+                match self.load_history_period(&asset, time, period, offset).await {
+                    Ok(segment) => candles = merge_candles(candles, segment),
+                    Err(e) => {
+                        warn!(target: "GetCandlesAdvanced", "Failed to retrieve candles between {} and {}, {}", time - offset, time, e)
+                    }
+                }
+            }
+            missing = missing_ranges(&candles, start, end, period);
+        }
+        if !missing.is_empty() && !allow_partial {
+            let expected = expected_candles(start, end, period);
+            return Err(PocketOptionError::IncompleteCandles {
+                asset,
+                expected,
+                received: expected.saturating_sub(
+                    missing
+                        .iter()
+                        .map(|(from, to)| ((to - from) / period) as usize)
+                        .sum(),
+                ),
+                missing: missing
+                    .into_iter()
+                    .filter_map(|(from, to)| {
+                        Some((
+                            DateTime::from_timestamp(from, 0)?,
+                            DateTime::from_timestamp(to, 0)?,
+                        ))
+                    })
+                    .collect(),
+            });
+        }
+        Ok(candles)
+    }
+
+    /// Retrieves historical candle data for a specific asset.
+    ///
+    /// # Arguments
+    /// * `asset` - Trading symbol (e.g., "EURUSD")
+    /// * `period` - Timeframe of the candles, a `Timeframe`, a number of seconds or a `Duration`
+    /// * `offset` - Number of seconds of history to retrieve, ending at the current candle
+    /// * `allow_partial` - Returns the received candles instead of an error if some candles are still missing after the retries
+    ///
+    /// # Returns
+    /// A vector of DataCandle objects containing historical price data
+    ///
+    /// # Errors
+    /// * Returns InvalidTimeframe if the period isn't supported by the server
+    /// * Returns GeneralParsingError if server time is invalid
+    /// * Returns UnexpectedIncorrectWebSocketMessage if response format is incorrect
+    /// * Returns IncompleteCandles if some candles are missing and `allow_partial` is false
+    ///
+    /// # Examples
+    /// ```rust
+    /// let candles = client.get_candles("EURUSD", Timeframe::M1, 0, false).await?; // Get current minute candles
+    /// ```
+    pub async fn get_candles(
+        &self,
+        asset: impl ToString,
+        period: impl IntoTimeframe,
+        offset: i64,
+        allow_partial: bool,
+    ) -> PocketResult<Vec<DataCandle>> {
+        let period = period.into_timeframe()?.seconds();
+        let server_time = self.client().data.get_server_time().await;
+        let time = server_time.div_euclid(period) * period;
+        self.get_candles_advanced(asset, time, period, offset, allow_partial)
+            .await
+    }
+
+    /// `get_candles` as an Arrow record batch with the columns `time`, `open`, `high`, `low` and
+    /// `close`, see `interop`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let batch = client.get_candles_arrow("EURUSD_otc", Timeframe::M1, 86400, false).await?;
+    /// assert_eq!(batch.schema().field(0).name(), "time");
+    /// ```
+    #[cfg(feature = "arrow")]
+    pub async fn get_candles_arrow(
+        &self,
+        asset: impl ToString,
+        period: impl IntoTimeframe,
+        offset: i64,
+        allow_partial: bool,
+    ) -> PocketResult<RecordBatch> {
+        self.get_candles(asset, period, offset, allow_partial)
+            .await?
+            .to_record_batch()
+    }
+
+    /// Retrieves the candles of `asset` between `start` and `end` (server time), loaded page by page
+    /// (`CANDLES_PAGE_SIZE` candles at a time) and merged into a single list sorted by time without
+    /// duplicates. The candles the server doesn't have (e.g. while the market was closed) are
+    /// skipped like with `allow_partial`, `audit_candles` reports them. The candles after the last
+    /// closed one aren't requested.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let start = DateTime::parse_from_rfc3339("2025-01-06T00:00:00Z")?.to_utc();
+    /// let candles = client
+    ///     .get_candles_range("EURUSD_otc", Timeframe::M1, start, start + TimeDelta::days(5))
+    ///     .await?;
+    /// ```
+    pub async fn get_candles_range(
+        &self,
+        asset: impl ToString,
+        period: impl IntoTimeframe,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> PocketResult<Vec<DataCandle>> {
+        let period = period.into_timeframe()?.seconds();
+        let asset = asset.to_string();
+        if end <= start {
+            return Err(PocketOptionError::Unallowed(format!(
+                "the end of the range ({end}) must be after its start ({start})"
+            )));
+        }
+        let server_time = self.client().data.get_server_time().await;
+        if server_time == 0 {
+            return Err(PocketOptionError::GeneralParsingError(
+                "Server time is invalid.".to_string(),
+            ));
+        }
+        // The candle still being formed isn't requested
+        let end = end.timestamp().min(server_time.div_euclid(period) * period);
+        let pages = range_pages(start.timestamp(), end, period, CANDLES_PAGE_SIZE);
+        info!(target: "GetCandlesRange", "Retrieving candles for asset '{}' between {} and {} in {} page(s)", asset, start.timestamp(), end, pages.len());
+        let mut candles = Vec::new();
+        for (time, offset) in pages {
+            let page = self
+                .get_candles_advanced(&asset, time, period, offset, true)
+                .await?;
+            candles = merge_candles(candles, page);
+        }
+        candles.retain(|candle| candle.time >= start && candle.time.timestamp() < end);
+        Ok(candles)
+    }
+
+    /// Downloads the candles of `asset` of the last `days` days to the file at `path` (created, or
+    /// truncated if it exists) in `format`, without holding them in memory: the candles are
+    /// requested page by page (`CANDLES_PAGE_SIZE` candles at a time, oldest first) at most every
+    /// `HISTORY_REQUEST_INTERVAL`, and every page is written to the file before the next one is
+    /// requested. A page that fails (e.g. while the client reconnects) is requested again, and the
+    /// candles shared by two pages are only written once. `progress` is called after every page.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let summary = client
+    ///     .download_history("EURUSD_otc", Timeframe::M1, 90, "EURUSD_otc.csv", HistoryFormat::Csv, |progress| {
+    ///         println!("{:.0}%", progress.fraction() * 100.0)
+    ///     })
+    ///     .await?;
+    /// println!("{} candles, {} missing", summary.candles, summary.missing_candles);
+    /// ```
+    pub async fn download_history(
+        &self,
+        asset: impl ToString,
+        period: impl IntoTimeframe,
+        days: u32,
+        path: impl AsRef<Path>,
+        format: HistoryFormat,
+        progress: impl Fn(&DownloadProgress) + Send + Sync,
+    ) -> PocketResult<DownloadSummary> {
+        let period = period.into_timeframe()?.seconds();
+        let asset = asset.to_string();
+        if days == 0 {
+            return Err(PocketOptionError::Unallowed(
+                "the number of days to download must be positive".into(),
+            ));
+        }
+        let server_time = self.client().data.get_server_time().await;
+        if server_time == 0 {
+            return Err(PocketOptionError::GeneralParsingError(
+                "Server time is invalid.".to_string(),
+            ));
+        }
+        // The candle still being formed isn't downloaded
+        let end = server_time.div_euclid(period) * period;
+        let start = end - i64::from(days) * 86400;
+        let pages = range_pages(start, end, period, CANDLES_PAGE_SIZE);
+        let path = path.as_ref();
+        let mut writer = HistoryWriter::create(path, format)?;
+        info!(target: "DownloadHistory", "Downloading {} days of candles of '{}' to '{}' in {} page(s)", days, asset, path.display(), pages.len());
+        let mut report = DownloadProgress {
+            asset: asset.clone(),
+            pages_done: 0,
+            pages: pages.len(),
+            candles: 0,
+            last_candle: None,
+            retries: 0,
+        };
+        let mut requests = tokio::time::interval(HISTORY_REQUEST_INTERVAL);
+        requests.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        for (time, offset) in pages {
+            let page = self
+                .download_page(
+                    &asset,
+                    time,
+                    period,
+                    offset,
+                    &mut requests,
+                    &mut report.retries,
+                )
+                .await?;
+            let candles: Vec<DataCandle> = merge_candles(page, Vec::new())
+                .into_iter()
+                .filter(|candle| {
+                    (start..end).contains(&candle.time.timestamp())
+                        && report.last_candle.is_none_or(|last| candle.time > last)
+                })
+                .collect();
+            writer.write(&candles)?;
+            report.candles += candles.len();
+            report.last_candle = candles.last().map(|c| c.time).or(report.last_candle);
+            report.pages_done += 1;
+            progress(&report);
+        }
+        writer.finish()?;
+        let summary = DownloadSummary {
+            asset,
+            period,
+            path: path.to_path_buf(),
+            format,
+            start: DateTime::from_timestamp(start, 0).unwrap_or_default(),
+            end: DateTime::from_timestamp(end, 0).unwrap_or_default(),
+            candles: report.candles,
+            missing_candles: expected_candles(start, end, period).saturating_sub(report.candles),
+            retries: report.retries,
+        };
+        info!(target: "DownloadHistory", "Downloaded {} candles of '{}' ({} missing, {} retries)", summary.candles, summary.asset, summary.missing_candles, summary.retries);
+        Ok(summary)
+    }
+
+    /// Requests a page of `download_history` once `requests` allows it, sending it again after a
+    /// growing delay if it fails, up to `HISTORY_MAX_RETRIES` times.
+    async fn download_page(
+        &self,
+        asset: &str,
+        time: i64,
+        period: i64,
+        offset: i64,
+        requests: &mut tokio::time::Interval,
+        retries: &mut u32,
+    ) -> PocketResult<Vec<DataCandle>> {
+        let mut backoff = HISTORY_RETRY_BACKOFF;
+        let mut attempt = 0;
+        loop {
+            requests.tick().await;
+            match self
+                .get_candles_advanced(asset, time, period, offset, true)
+                .await
+            {
+                Ok(page) => return Ok(page),
+                Err(e) if attempt < HISTORY_MAX_RETRIES => {
+                    attempt += 1;
+                    *retries += 1;
+                    warn!(target: "DownloadHistory", "Failed to download the candles of '{asset}' before {time}, retrying in {backoff:?} ({attempt}/{HISTORY_MAX_RETRIES}), {e}");
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Downloads the candles of `asset` (like `get_candles` with `allow_partial`) and returns a data quality
+    /// report of them (gaps, duplicate timestamps, zero-range candles and boundary misalignments).
+    ///
+    /// # Examples
+    /// ```rust
+    /// let audit = client.audit_candles("EURUSD_otc", 60, 36000).await?;
+    /// if !audit.is_clean() {
+    ///     println!("Missing {} candles: {:?}", audit.missing_candles, audit.gaps);
+    /// }
+    /// ```
+    pub async fn audit_candles(
+        &self,
+        asset: impl ToString,
+        period: impl IntoTimeframe,
+        offset: i64,
+    ) -> PocketResult<CandleAudit> {
+        let period = period.into_timeframe()?.seconds();
+        let candles = self.get_candles(asset, period, offset, true).await?;
+        Ok(CandleAudit::new(&candles, period))
+    }
+
+    /// Renders the candles of `asset` between `start` and `end` (server time) to a PNG image, with
+    /// `markers` drawn on them. The missing candles are skipped (like `get_candles` with
+    /// `allow_partial`).
+    ///
+    /// # Examples
+    /// ```rust
+    /// let end = client.get_server_time().await;
+    /// let png = client
+    ///     .chart_range("EURUSD_otc", 60, end - TimeDelta::hours(1), end, &[], &ChartOptions::default())
+    ///     .await?;
+    /// std::fs::write("EURUSD_otc.png", png)?;
+    /// ```
+    #[cfg(feature = "charts")]
+    pub async fn chart_range(
+        &self,
+        asset: impl ToString,
+        period: impl IntoTimeframe,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        markers: &[ChartMarker],
+        options: &ChartOptions,
+    ) -> PocketResult<Vec<u8>> {
+        options.validate()?;
+        let period = period.into_timeframe()?.seconds();
+        if end <= start {
+            return Err(PocketOptionError::Unallowed(format!(
+                "the end of the chart ({end}) must be after its start ({start})"
+            )));
         }
-        let request = LoadHistoryPeriod::new(asset.to_string(), time, period, offset)?;
-        let index = request.index;
-        debug!(
-            "Sent get candles message, message: {:?}",
-            WebSocketMessage::GetCandles(request).to_string()
-        );
-        let request = LoadHistoryPeriod::new(asset.to_string(), time, period, offset)?;
-        let res = self
-            .client
-            .send_message_with_timeout_and_retry(
-                self.get_timeout()?,
-                "GetCandles",
-                WebSocketMessage::GetCandles(request),
-                MessageInfo::LoadHistoryPeriod,
-                &candle_validator(index),
-            )
+        let time = end.timestamp().div_euclid(period) * period;
+        let offset = (time - start.timestamp().div_euclid(period) * period).max(period);
+        let candles = self
+            .get_candles_advanced(asset, time, period, offset, true)
             .await?;
-        if let WebSocketMessage::LoadHistoryPeriod(history) = res {
-            return Ok(history.candle_data());
-        }
-        Err(PocketOptionError::UnexpectedIncorrectWebSocketMessage(
-            res.info(),
-        ))
+        render_chart(&candles, markers, options)
     }
 
-    /// Retrieves historical candle data for a specific asset.
+    /// Renders a trade to a PNG image: the candles of its asset around it (`CHART_TRADE_PADDING`
+    /// candles before its entry and after its exit), with its entry and, once it's closed, its exit.
     ///
-    /// # Arguments
-    /// * `asset` - Trading symbol (e.g., "EURUSD")
-    /// * `period` - Time period for each candle in seconds
-    /// * `offset` - Number of periods to offset from current time
-    ///
-    /// # Returns
-    /// A vector of DataCandle objects containing historical price data
-    ///
-    /// # Errors
-    /// * Returns GeneralParsingError if server time is invalid
-    /// * Returns UnexpectedIncorrectWebSocketMessage if response format is incorrect
+    /// # Examples
+    /// ```rust
+    /// let deal = client.check_results(trade_id).await?;
+    /// let png = client
+    ///     .chart_trade(trade_id, 60, &ChartOptions { title: Some(format!("{} {:+.2}", deal.asset, deal.profit)), ..Default::default() })
+    ///     .await?;
+    /// // attach `png` to the notification of the trade
+    /// ```
+    #[cfg(feature = "charts")]
+    pub async fn chart_trade(
+        &self,
+        trade_id: Uuid,
+        period: impl IntoTimeframe,
+        options: &ChartOptions,
+    ) -> PocketResult<Vec<u8>> {
+        let period = period.into_timeframe()?.seconds();
+        let closed = self.get_closed_deals().await;
+        let deal = match closed.into_iter().find(|deal| deal.id == trade_id) {
+            Some(deal) => deal,
+            None => self
+                .get_opened_deals()
+                .await
+                .into_iter()
+                .find(|deal| deal.id == trade_id)
+                .ok_or_else(|| {
+                    PocketOptionError::Unallowed(format!(
+                        "no trade '{trade_id}' in the open or closed trades"
+                    ))
+                })?,
+        };
+        let padding = TimeDelta::seconds(CHART_TRADE_PADDING * period);
+        let server_time = self.client().data.get_server_time().await;
+        let now = DateTime::from_timestamp(server_time, 0).unwrap_or_else(Utc::now);
+        let end = (deal.close_timestamp + padding).min(now);
+        self.chart_range(
+            &deal.asset,
+            period,
+            deal.open_timestamp - padding,
+            end,
+            &ChartMarker::from_deal(&deal),
+            options,
+        )
+        .await
+    }
+
+    /// Downloads the last `lookback` seconds of candles of `asset` (like `get_candles` with
+    /// `allow_partial`) and computes their pivots, session highs / lows and support / resistance
+    /// zones with the default `LevelsConfig`.
     ///
     /// # Examples
     /// ```rust
-    /// let candles = client.get_candles("EURUSD", 60, 0).await?; // Get current minute candles
+    /// let levels = client.levels("EURUSD_otc", 300, 3 * 86400).await?;
+    /// if let Some(support) = levels.nearest_support {
+    ///     println!("Support at {} ({} touches)", support.price, support.touches);
+    /// }
     /// ```
-    pub async fn get_candles(
+    pub async fn levels(
         &self,
         asset: impl ToString,
-        period: i64,
-        offset: i64,
-    ) -> PocketResult<Vec<DataCandle>> {
-        let time = self.client.data.get_server_time().await.div_euclid(period) * period;
-        self.get_candles_advanced(asset, time, period, offset).await
+        period: impl IntoTimeframe,
+        lookback: i64,
+    ) -> PocketResult<Levels> {
+        self.levels_with(asset, period, lookback, &LevelsConfig::default())
+            .await
+    }
+
+    /// Same as `levels`, with the settings of `config`.
+    pub async fn levels_with(
+        &self,
+        asset: impl ToString,
+        period: impl IntoTimeframe,
+        lookback: i64,
+        config: &LevelsConfig,
+    ) -> PocketResult<Levels> {
+        config.validate()?;
+        let candles = self.get_candles(asset, period, lookback, true).await?;
+        Levels::compute(&candles, config)
     }
 
     /// Retrieves the most recent historical data for an asset.
     ///
     /// # Arguments
     /// * `asset` - Trading symbol (e.g., "EURUSD")
-    /// * `period` - Time period for each candle in seconds
+    /// * `period` - Timeframe of the candles, a `Timeframe`, a number of seconds or a `Duration`
     ///
     /// # Returns
     /// A vector of DataCandle objects containing recent price data
@@ -520,13 +2447,15 @@ impl PocketOption {
     pub async fn history(
         &self,
         asset: impl ToString,
-        period: i64,
+        period: impl IntoTimeframe,
     ) -> PocketResult<Vec<DataCandle>> {
+        let period = period.into_timeframe()?.seconds();
+        self.inner.session.ensure_authenticated()?;
         info!(target: "History", "Retrieving candles for asset '{}' with period of '{}'", asset.to_string(), period);
 
         let request = ChangeSymbol::new(asset.to_string(), period);
         let res = self
-            .client
+            .client()
             .send_message_with_timeout_and_retry(
                 self.get_timeout()?,
                 "History",
@@ -543,34 +2472,291 @@ impl PocketOption {
         ))
     }
 
+    /// `history` as an Arrow record batch, with the schema of `get_candles_arrow`.
+    #[cfg(feature = "arrow")]
+    pub async fn history_arrow(
+        &self,
+        asset: impl ToString,
+        period: impl IntoTimeframe,
+    ) -> PocketResult<RecordBatch> {
+        self.history(asset, period).await?.to_record_batch()
+    }
+
+    /// Returns the identifiers the broker uses for a trade (ticket, request id and account), the ones
+    /// its support needs, looking for the trade in the opened deals, the closed deals and the journal.
+    pub async fn server_ids(&self, trade_id: Uuid) -> PocketResult<ServerIds> {
+        info!(target: "ServerIds", "Retrieving server ids for trade of id {}", trade_id);
+        let opened = self.client().data.get_opened_deals().await;
+        let closed = self.client().data.get_closed_deals().await;
+        if let Some(deal) = opened
+            .iter()
+            .chain(closed.iter())
+            .find(|d| d.id == trade_id)
+        {
+            return Ok(self.server_ids_of(deal));
+        }
+        self.journal()
+            .entry(trade_id)
+            .map(|entry| ServerIds {
+                ticket: entry.trade_id,
+                request_id: entry.request_id,
+                uid: entry.uid,
+            })
+            .ok_or_else(|| {
+                BinaryOptionsToolsError::Unallowed(format!(
+                    "Couldn't find the trade '{trade_id}' in the opened deals, the closed deals nor the journal"
+                ))
+                .into()
+            })
+    }
+
+    /// The closed deals sent by the server don't include the request id, so it's taken from the journal.
+    fn server_ids_of(&self, deal: &Deal) -> ServerIds {
+        let mut ids = deal.server_ids();
+        if ids.request_id.is_none() {
+            ids.request_id = self.journal().entry(deal.id).and_then(|e| e.request_id);
+        }
+        ids
+    }
+
     pub async fn get_closed_deals(&self) -> Vec<Deal> {
         info!(target: "GetClosedDeals", "Retrieving list of closed deals");
-        self.client.data.get_closed_deals().await
+        self.client().data.get_closed_deals().await
+    }
+
+    /// The closed deals as an Arrow record batch, one row per deal, see `interop`.
+    #[cfg(feature = "arrow")]
+    pub async fn closed_deals_arrow(&self) -> PocketResult<RecordBatch> {
+        self.get_closed_deals().await.to_record_batch()
     }
 
     pub async fn clear_closed_deals(&self) {
         info!(target: "ClearClosedDeals", "Clearing list of closed deals");
-        self.client.data.clean_closed_deals().await
+        self.client().data.clean_closed_deals().await
     }
 
     pub async fn get_opened_deals(&self) -> Vec<Deal> {
         info!(target: "GetOpenDeals", "Retrieving list of open deals");
-        self.client.data.get_opened_deals().await
+        self.client().data.get_opened_deals().await
     }
 
     pub async fn get_balance(&self) -> UpdateBalance {
         info!(target: "GetBalance", "Retrieving account balance");
-        self.client.data.get_balance().await
+        self.client().data.get_balance().await
     }
 
     pub async fn is_demo(&self) -> bool {
         info!(target: "IsDemo", "Retrieving demo status");
-        self.client.credentials.demo()
+        self.client().credentials.demo()
     }
 
     pub async fn get_payout(&self) -> HashMap<String, i32> {
         info!(target: "GetPayout", "Retrieving payout for all the assets");
-        self.client.data.get_full_payout().await
+        self.client().data.get_full_payout().await
+    }
+
+    /// Returns the current payout of an asset in percent, `None` if the server didn't send it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// if client.get_payout_for("EURUSD_otc").await.is_some_and(|payout| payout >= 85) {
+    ///     client.buy("EURUSD_otc", 1.0, 60).await?;
+    /// }
+    /// ```
+    pub async fn get_payout_for(&self, asset: impl AsRef<str>) -> Option<i32> {
+        self.client().data.get_payout(asset.as_ref()).await
+    }
+
+    /// Returns whether the market of an asset is currently open, `None` if the server didn't send
+    /// the asset.
+    ///
+    /// # Examples
+    /// ```rust
+    /// if client.is_open("EURUSD").await == Some(true) {
+    ///     client.buy("EURUSD", 1.0, 60).await?;
+    /// }
+    /// ```
+    pub async fn is_open(&self, asset: impl AsRef<str>) -> Option<bool> {
+        self.client()
+            .data
+            .get_asset(asset.as_ref())
+            .await
+            .map(|asset| asset.is_open())
+    }
+
+    /// Rejects the trades on the assets whose market is closed with an `AssetClosed` error before
+    /// sending them, instead of letting the server drop them. Disabled by default.
+    pub fn set_market_hours_check(&self, enabled: bool) {
+        info!(target: "Trade", "Market hours check {}", if enabled { "enabled" } else { "disabled" });
+        info!(target: AUDIT_TARGET, event = "config_change", setting = "market_hours_check", enabled);
+        self.inner
+            .market_hours_check
+            .store(enabled, Ordering::SeqCst);
+    }
+
+    pub fn market_hours_check(&self) -> bool {
+        self.inner.market_hours_check.load(Ordering::SeqCst)
+    }
+
+    /// Trades and subscribes to the OTC version of a regular asset (e.g. "EURUSD_otc" for
+    /// "EURUSD") while the market of the regular asset is closed and the OTC one is open, on
+    /// weekends for instance. Disabled by default.
+    pub fn set_otc_fallback(&self, enabled: bool) {
+        info!(target: "OtcFallback", "OTC fallback {}", if enabled { "enabled" } else { "disabled" });
+        info!(target: AUDIT_TARGET, event = "config_change", setting = "otc_fallback", enabled);
+        self.inner.otc_fallback.store(enabled, Ordering::SeqCst);
+    }
+
+    pub fn otc_fallback(&self) -> bool {
+        self.inner.otc_fallback.load(Ordering::SeqCst)
+    }
+
+    /// Symbol the trades and the subscriptions on `asset` use, its OTC version if the OTC fallback
+    /// is enabled and the regular asset is closed (see `set_otc_fallback`), `asset` otherwise.
+    ///
+    /// # Examples
+    /// ```rust
+    /// client.set_otc_fallback(true);
+    /// let symbol = client.resolve_asset("EURUSD").await; // "EURUSD_otc" on weekends
+    /// ```
+    pub async fn resolve_asset(&self, asset: impl ToString) -> String {
+        let asset = asset.to_string();
+        if !self.otc_fallback() {
+            return asset;
+        }
+        let is_open = self.is_open(&asset).await;
+        let otc_is_open = self.is_open(otc_symbol(&asset)).await;
+        match otc_fallback(&asset, is_open, otc_is_open) {
+            Some(otc) => {
+                info!(target: "OtcFallback", "The market of '{asset}' is closed, using '{otc}' instead");
+                otc
+            }
+            None => asset,
+        }
+    }
+
+    /// Returns the catalog of assets sent by the server (symbol, name, type, payout, whether it's
+    /// an OTC asset and whether it's open), sorted by symbol. Empty until the server sent it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let open: Vec<String> = client
+    ///     .get_assets()
+    ///     .await
+    ///     .into_iter()
+    ///     .filter(|asset| asset.is_open && asset.payout >= 80)
+    ///     .map(|asset| asset.symbol)
+    ///     .collect();
+    /// ```
+    pub async fn get_assets(&self) -> Vec<AssetInfo> {
+        let mut assets: Vec<AssetInfo> = self
+            .client()
+            .data
+            .get_assets()
+            .await
+            .iter()
+            .map(AssetInfo::from)
+            .collect();
+        assets.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        assets
+    }
+
+    /// Returns the expiry times (in seconds) the server accepts for an asset, sorted from shortest to longest.
+    ///
+    /// # Errors
+    /// Returns an `AssetNotFound` error if the asset isn't in the list of assets sent by the server
+    ///
+    /// # Examples
+    /// ```rust
+    /// let expiries = client.valid_expiries("EURUSD_otc").await?;
+    /// ```
+    pub async fn valid_expiries(&self, asset: impl ToString) -> PocketResult<Vec<u32>> {
+        info!(target: "ValidExpiries", "Retrieving valid expiries for asset '{}'", asset.to_string());
+        self.client()
+            .data
+            .get_asset(asset.to_string())
+            .await
+            .map(|a| a.expiries())
+            .ok_or_else(|| PocketOptionError::AssetNotFound(asset.to_string()))
+    }
+
+    /// Returns the valid expiry closest to `desired_secs` for an asset, so it can be corrected
+    /// before placing the trade instead of being silently adjusted by the broker.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let time = client.nearest_expiry("EURUSD_otc", 250).await?; // 300
+    /// let (trade_id, deal) = client.buy("EURUSD_otc", 1.0, time).await?;
+    /// ```
+    pub async fn nearest_expiry(&self, asset: impl ToString, desired_secs: u32) -> PocketResult<u32> {
+        let expiries = self.valid_expiries(asset.to_string()).await?;
+        nearest_expiry(&expiries, desired_secs)
+            .ok_or_else(|| PocketOptionError::EmptyArrayError("expiries".to_string()))
+    }
+
+    /// Returns the precision (number of digits and pip size) of the prices of an asset.
+    ///
+    /// # Errors
+    /// Returns an `AssetNotFound` error if the asset isn't in the list of assets sent by the server
+    pub async fn precision(&self, asset: impl ToString) -> PocketResult<Precision> {
+        self.client()
+            .data
+            .get_asset(asset.to_string())
+            .await
+            .map(|a| a.precision())
+            .ok_or_else(|| PocketOptionError::AssetNotFound(asset.to_string()))
+    }
+
+    /// Rounds a price to the number of digits of an asset.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let price = client.round_price("EURUSD_otc", 1.085434999).await?; // 1.08543
+    /// ```
+    pub async fn round_price(&self, asset: impl ToString, price: f64) -> PocketResult<f64> {
+        Ok(self.precision(asset).await?.round_price(price))
+    }
+
+    /// Signed number of pips between the prices `a` and `b` of an asset.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let pips = client.pips_between("EURUSD_otc", 1.08543, 1.08553).await?; // 1.0
+    /// ```
+    pub async fn pips_between(&self, asset: impl ToString, a: f64, b: f64) -> PocketResult<f64> {
+        Ok(self.precision(asset).await?.pips_between(a, b))
+    }
+
+    /// Suggests the shortest valid expiry of an asset likely to see a move of `target_move_pips`,
+    /// from the realized volatility of its candles of the last hour (see `suggest_expiry`).
+    ///
+    /// # Examples
+    /// ```rust
+    /// let suggestion = client.suggest_expiry("EURUSD_otc", 5.0).await?;
+    /// if suggestion.reaches_target {
+    ///     client.buy("EURUSD_otc", 1.0, suggestion.expiry).await?;
+    /// }
+    /// ```
+    pub async fn suggest_expiry(
+        &self,
+        asset: impl ToString,
+        target_move_pips: f64,
+    ) -> PocketResult<ExpirySuggestion> {
+        let asset = asset.to_string();
+        let expiries = self.valid_expiries(&asset).await?;
+        let precision = self.precision(&asset).await?;
+        let candles = self
+            .get_candles(&asset, VOLATILITY_PERIOD, VOLATILITY_LOOKBACK, true)
+            .await?;
+        let suggestion = suggest_expiry(
+            &candles,
+            VOLATILITY_PERIOD,
+            &precision,
+            &expiries,
+            target_move_pips,
+        )?;
+        info!(target: "SuggestExpiry", "Suggested an expiry of {}s for a move of {target_move_pips} pips on '{asset}', {:.2} pips expected", suggestion.expiry, suggestion.expected_move_pips);
+        Ok(suggestion)
     }
 
     /// Subscribes to real-time price updates for an asset.
@@ -589,13 +2775,201 @@ impl PocketOption {
     /// }
     /// ```
     pub async fn subscribe_symbol(&self, asset: impl ToString) -> PocketResult<StreamAsset> {
+        let asset = self.resolve_asset(asset).await;
         info!(target: "SubscribeSymbol", "Subscribing to asset '{}'", asset.to_string());
-        // Send 3 messages, 1 changesymbol, 2 unsubfor, 3 subfor 
-        self.client.send(WebSocketMessage::ChangeSymbol(ChangeSymbol::new(asset.to_string(), 1))).await?;
-        self.client.send(WebSocketMessage::Unsubfor(asset.to_string())).await?;
-        self.client.send(WebSocketMessage::Subfor(asset.to_string())).await?;
+        self.send_subscription(asset.to_string()).await?;
         debug!("Created StreamAsset instance.");
-        Ok(self.client.data.add_stream(asset.to_string()).await)
+        Ok(self.client().data.add_stream(asset.to_string()).await)
+    }
+
+    /// Subscribes to real-time price updates for several assets at once.
+    ///
+    /// The subscription frames of every asset are queued together without waiting between assets and
+    /// written to the websocket in batches, so subscribing to a large watchlist takes a fraction of a
+    /// second instead of one round of frames per asset.
+    ///
+    /// # Arguments
+    /// * `assets` - Trading symbols to subscribe to
+    ///
+    /// # Returns
+    /// One StreamAsset per asset, in the same order as `assets`
+    ///
+    /// # Examples
+    /// ```rust
+    /// let streams = client.subscribe_symbol_batch(["EURUSD_otc", "GBPUSD_otc", "#AAPL_otc"]).await?;
+    /// ```
+    pub async fn subscribe_symbol_batch(
+        &self,
+        assets: impl IntoIterator<Item = impl ToString>,
+    ) -> PocketResult<Vec<StreamAsset>> {
+        let assets: Vec<String> = assets.into_iter().map(|a| a.to_string()).collect();
+        info!(target: "SubscribeSymbol", "Subscribing to {} assets", assets.len());
+        let mut streams = Vec::with_capacity(assets.len());
+        for asset in assets {
+            let asset = self.resolve_asset(asset).await;
+            // Only queues the frames, the writer sends them in batches while the next ones are queued
+            self.send_subscription(asset.clone()).await?;
+            streams.push(self.client().data.add_stream(asset).await);
+        }
+        Ok(streams)
+    }
+
+    /// Queues the frames subscribing to an asset, unless the subscription limit of the server is
+    /// reached, in which case the asset waits for a slot or the least recently used asset is rotated
+    /// out depending on the `SubscriptionPolicy`.
+    async fn send_subscription(&self, asset: String) -> PocketResult<()> {
+        let actions = self.client().data.subscriptions().request(asset);
+        self.send_subscription_actions(actions).await
+    }
+
+    async fn send_subscription_actions(
+        &self,
+        actions: Vec<SubscriptionAction>,
+    ) -> PocketResult<()> {
+        for action in actions {
+            for message in action.messages() {
+                self.client().send(message).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Unsubscribes from an asset, releasing its slot for the next queued subscription. The streams
+    /// of the asset stop recieving updates.
+    ///
+    /// # Arguments
+    /// * `asset` - Trading symbol to unsubscribe from
+    ///
+    /// # Examples
+    /// ```rust
+    /// client.unsubscribe_symbol("EURUSD_otc").await?;
+    /// ```
+    pub async fn unsubscribe_symbol(&self, asset: impl ToString) -> PocketResult<()> {
+        let asset = asset.to_string();
+        info!(target: "UnsubscribeSymbol", "Unsubscribing from asset '{}'", asset);
+        let actions = self.client().data.subscriptions().unsubscribe(&asset);
+        self.client().data.remove_stream_asset(&asset).await;
+        self.send_subscription_actions(actions).await
+    }
+
+    /// Sets how the subscription limit of the server is handled: the limit itself (detected from the
+    /// subscriptions that never recieve a tick if not set) and whether the surplus subscriptions are
+    /// queued or replace the least recently used ones. Queued assets are subscribed if the new limit
+    /// leaves room for them.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let policy = SubscriptionPolicy { max_subscriptions: Some(10), rotate: false, ..Default::default() };
+    /// client.set_subscription_policy(policy).await?;
+    /// ```
+    pub async fn set_subscription_policy(&self, policy: SubscriptionPolicy) -> PocketResult<()> {
+        let actions = self.client().data.subscriptions().set_policy(policy);
+        self.send_subscription_actions(actions).await
+    }
+
+    pub fn subscription_policy(&self) -> SubscriptionPolicy {
+        self.client().data.subscriptions().policy()
+    }
+
+    /// Returns the subscribed assets, the ones waiting for a slot and the subscription limit in use.
+    pub fn subscription_status(&self) -> SubscriptionStatus {
+        self.client().data.subscriptions().status()
+    }
+
+    /// Sets the lane the ticks of an asset go through: once the streams stop keeping up with the
+    /// feed the oldest best-effort ticks are dropped, the critical ones (e.g. the asset currently
+    /// traded) are never shed. Every asset is best-effort by default.
+    ///
+    /// # Examples
+    /// ```rust
+    /// client.set_stream_priority("EURUSD_otc", StreamPriority::Critical);
+    /// let stream = client.subscribe_symbol("EURUSD_otc").await?;
+    /// ```
+    pub fn set_stream_priority(&self, asset: impl AsRef<str>, priority: StreamPriority) {
+        info!(target: "StreamLanes", "Ticks of '{}' set to the {:?} lane", asset.as_ref(), priority);
+        self.client()
+            .data
+            .set_stream_priority(asset.as_ref(), priority)
+    }
+
+    /// Returns the critical assets, the ticks waiting in each lane and the number of best-effort
+    /// ticks shed (by asset).
+    pub fn stream_lane_stats(&self) -> StreamLaneStats {
+        self.client().data.stream_lane_stats()
+    }
+
+    /// Enables the power saving mode: once no stream of the client read the ticks for `idle`, every
+    /// asset is unsubscribed (the connection and the session stay alive, trades can still be placed)
+    /// and subscribed again when a stream is requested for it. `None` disables the mode, the
+    /// suspended assets are only subscribed again on demand.
+    ///
+    /// # Examples
+    /// ```rust
+    /// client.set_power_saving(Some(Duration::from_secs(10 * 60)))?;
+    /// // Later, the stream subscribes to the asset again if it was suspended
+    /// let stream = client.subscribe_symbol("EURUSD_otc").await?;
+    /// ```
+    pub fn set_power_saving(&self, idle: Option<Duration>) -> PocketResult<()> {
+        if idle.is_some_and(|idle| idle.is_zero()) {
+            return Err(PocketOptionError::Unallowed(
+                "the idle time of the power saving mode must be positive".into(),
+            ));
+        }
+        let mut task = self
+            .inner
+            .power_saving
+            .lock()
+            .map_err(|e| PocketOptionError::UnreachableError(e.to_string()))?;
+        if let Some(task) = task.take() {
+            task.abort();
+        }
+        let Some(idle) = idle else {
+            info!(target: "PowerSaving", "Power saving mode disabled");
+            return Ok(());
+        };
+        info!(target: "PowerSaving", "Power saving mode enabled, the assets are unsubscribed after {idle:?} without a stream");
+        let handle = tokio::spawn(Self::power_saving(Arc::downgrade(&self.inner), idle));
+        self.track_task(&handle);
+        *task = Some(handle.abort_handle());
+        Ok(())
+    }
+
+    /// Runs the power saving mode until the last clone of the client is dropped, the task only holds
+    /// a weak reference so it doesn't keep the client alive.
+    async fn power_saving(inner: Weak<PocketInner>, idle: Duration) {
+        let mut check =
+            tokio::time::interval(POWER_SAVING_CHECK.min(idle / 4).max(Duration::from_secs(1)));
+        let mut idle_since = Instant::now();
+        loop {
+            check.tick().await;
+            let Some(inner) = inner.upgrade() else {
+                break;
+            };
+            let client = Self {
+                inner,
+                observer: false,
+            };
+            let data = &client.client().data;
+            if data.has_stream_consumers() {
+                idle_since = Instant::now();
+                continue;
+            }
+            if idle_since.elapsed() < idle {
+                continue;
+            }
+            let status = data.subscriptions().status();
+            if status.active.is_empty() && status.queued.is_empty() {
+                continue;
+            }
+            let actions = data.subscriptions().suspend();
+            for asset in status.active.iter().chain(status.queued.iter()) {
+                data.remove_stream_asset(asset).await;
+            }
+            info!(target: "PowerSaving", "No stream for {idle:?}, unsubscribed from {} assets", status.active.len() + status.queued.len());
+            if let Err(e) = client.send_subscription_actions(actions).await {
+                warn!(target: "PowerSaving", "Failed to unsubscribe the idle assets, {e}");
+            }
+        }
     }
 
     /// Subscribes to chunked real-time price updates for an asset.
@@ -617,14 +2991,12 @@ impl PocketOption {
         asset: impl ToString,
         chunck_size: impl Into<usize>,
     ) -> PocketResult<StreamAsset> {
+        let asset = self.resolve_asset(asset).await;
         info!(target: "SubscribeSymbolChuncked", "Subscribing to asset '{}'", asset.to_string());
-        // Send 3 messages, 1 changesymbol, 2 unsubfor, 3 subfor, honestly no clue why pocket option does that
-        self.client.send(WebSocketMessage::ChangeSymbol(ChangeSymbol::new(asset.to_string(), 1))).await?;
-        self.client.send(WebSocketMessage::Unsubfor(asset.to_string())).await?;
-        self.client.send(WebSocketMessage::Subfor(asset.to_string())).await?;
+        self.send_subscription(asset.to_string()).await?;
         debug!("Created StreamAsset instance.");
         Ok(self
-            .client
+            .client()
             .data
             .add_stream_chuncked(asset.to_string(), chunck_size.into())
             .await)
@@ -650,19 +3022,50 @@ impl PocketOption {
         asset: impl ToString,
         time: impl Into<Duration>,
     ) -> PocketResult<StreamAsset> {
+        let asset = self.resolve_asset(asset).await;
         info!(target: "SubscribeSymbolTimed", "Subscribing to asset '{}'", asset.to_string());
-        // Send 3 messages, 1 changesymbol, 2 unsubfor, 3 subfor 
-        self.client.send(WebSocketMessage::ChangeSymbol(ChangeSymbol::new(asset.to_string(), 1))).await?;
-        self.client.send(WebSocketMessage::Unsubfor(asset.to_string())).await?;
-        self.client.send(WebSocketMessage::Subfor(asset.to_string())).await?;
+        self.send_subscription(asset.to_string()).await?;
         debug!("Created StreamAsset instance.");
         Ok(self
-            .client
+            .client()
             .data
             .add_stream_timed(asset.to_string(), time.into())
             .await)
     }
 
+    /// Subscribes to the close of the candles of an asset, a `ClosedCandle` is sent when each period
+    /// of `period` ends on the server clock, even if the last tick of the period is late or never
+    /// arrives, with the best known candle of the period.
+    ///
+    /// # Arguments
+    /// * `asset` - Trading symbol to subscribe to (e.g., "EURUSD_otc")
+    /// * `period` - Duration of the candles, at least 1 second
+    /// * `timeout` - Optional maximum time to wait for each candle
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::time::Duration;
+    /// let closes = client.subscribe_candle_close("EURUSD_otc", Duration::from_secs(60), None).await?;
+    /// let mut stream = closes.to_stream();
+    /// while let Some(closed) = stream.next().await {
+    ///     let closed = closed?;
+    ///     println!("{} closed with {} ticks: {:?}", closed.close_time, closed.ticks, closed.candle);
+    /// }
+    /// ```
+    pub async fn subscribe_candle_close(
+        &self,
+        asset: impl ToString,
+        period: impl Into<Duration>,
+        timeout: Option<Duration>,
+    ) -> PocketResult<CandleCloseStream> {
+        info!(target: "SubscribeCandleClose", "Subscribing to the candle close of asset '{}'", asset.to_string());
+        self.send_subscription(asset.to_string()).await?;
+        self.client()
+            .data
+            .add_candle_close_stream(asset.to_string(), period.into(), timeout)
+            .await
+    }
+
     /// Sends a raw WebSocket message without waiting for a response.
     ///
     /// # Arguments
@@ -676,9 +3079,9 @@ impl PocketOption {
     /// client.send_raw_message(r#"42["signals/subscribe"]"#).await?;
     /// ```
     pub async fn send_raw_message(&self, message: impl ToString) -> PocketResult<()> {
-        self.client
-            .raw_send(RawWebsocketMessage::from(message.to_string()))
-            .await?;
+        let message = RawWebsocketMessage::from(message.to_string());
+        self.ensure_raw_allowed(&message)?;
+        self.client().raw_send(message).await?;
         Ok(())
     }
 
@@ -709,10 +3112,11 @@ impl PocketOption {
         //  * create_raw_order_iterator: return a stream like the StreamAsset
         //  * send_raw_message: send message without validator
         //  * OTHER: Create a callback related function to add new options for the callback + add support for struct or functions in it (like the Validator) so future me will have it easy
-        Ok(self
-            .client
-            .send_raw_message(message.into(), validator)
-            .await?)
+        let message = message.into();
+        self.ensure_raw_allowed(&message)?;
+        let request = message.clone();
+        let response = self.client().send_raw_message(message, validator);
+        Self::audited_raw_order("create_raw_order", &request, response).await
     }
 
     /// Sends a raw WebSocket message and waits for a validated response with a timeout.
@@ -743,17 +3147,18 @@ impl PocketOption {
         &self,
         message: impl Into<RawWebsocketMessage>,
         validator: Box<dyn ValidatorTrait<RawWebsocketMessage> + Send + Sync>,
-        timeout: Duration,
-    ) -> PocketResult<RawWebsocketMessage> {
-        Ok(self
-            .client
-            .send_raw_message_with_timout(
-                timeout,
-                "CreateRawOrder".to_string(),
-                message.into(),
-                validator,
-            )
-            .await?)
+        timeout: Duration,
+    ) -> PocketResult<RawWebsocketMessage> {
+        let message = message.into();
+        self.ensure_raw_allowed(&message)?;
+        let request = message.clone();
+        let response = self.client().send_raw_message_with_timout(
+            timeout,
+            "CreateRawOrder".to_string(),
+            message,
+            validator,
+        );
+        Self::audited_raw_order("create_raw_order_with_timeout", &request, response).await
     }
 
     /// Sends a raw WebSocket message with timeout and automatic retry on failure.
@@ -786,15 +3191,21 @@ impl PocketOption {
         validator: Box<dyn ValidatorTrait<RawWebsocketMessage> + Send + Sync>,
         timeout: Duration,
     ) -> PocketResult<RawWebsocketMessage> {
-        Ok(self
-            .client
-            .send_raw_message_with_timeout_and_retry(
-                timeout,
-                "CreateRawOrderWithRetry".to_string(),
-                message.into(),
-                validator,
-            )
-            .await?)
+        let message = message.into();
+        self.ensure_raw_allowed(&message)?;
+        let request = message.clone();
+        let response = self.client().send_raw_message_with_timeout_and_retry(
+            timeout,
+            "CreateRawOrderWithRetry".to_string(),
+            message,
+            validator,
+        );
+        Self::audited_raw_order(
+            "create_raw_order_with_timeout_and_retry",
+            &request,
+            response,
+        )
+        .await
     }
 
     /// Creates a stream of validated WebSocket messages.
@@ -828,37 +3239,610 @@ impl PocketOption {
         validator: Box<dyn ValidatorTrait<RawWebsocketMessage> + Send + Sync>,
         timeout: Option<Duration>,
     ) -> PocketResult<FilteredRecieverStream<RawWebsocketMessage>> {
+        let message = message.into();
+        self.ensure_raw_allowed(&message)?;
         Ok(self
-            .client
-            .send_raw_message_iterator(message.into(), validator, timeout)
+            .client()
+            .send_raw_message_iterator(message, validator, timeout)
             .await?)
     }
 
+    /// Taps the raw messages recieved from the server (frames of events the models don't know)
+    /// without sending anything, to observe undocumented messages of the broker.
+    ///
+    /// # Arguments
+    /// * `validator` - Validator instance to filter incoming messages
+    /// * `timeout` - Optional maximum time to wait for each message
+    pub fn tap_raw(
+        &self,
+        validator: Box<dyn ValidatorTrait<RawWebsocketMessage> + Send + Sync>,
+        timeout: Option<Duration>,
+    ) -> FilteredRecieverStream<RawWebsocketMessage> {
+        info!(target: "TapRaw", "Tapping the raw messages");
+        FilteredRecieverStream::new(self.client().data.raw_reciever(), timeout, validator)
+    }
+
+    /// Subscribes to the administrative messages about the account (bonus credited, margin calls,
+    /// account restricted, maintenance notices...), every subscriber receives all the events.
+    ///
+    /// # Arguments
+    /// * `timeout` - Optional maximum time to wait for each event
+    ///
+    /// # Examples
+    /// ```rust
+    /// let events = client.subscribe_account_events(None).await;
+    /// let mut stream = events.to_stream();
+    /// while let Some(event) = stream.next().await {
+    ///     let event = event?;
+    ///     println!("{:?}: {:?}", event.kind, event.message);
+    /// }
+    /// ```
+    pub async fn subscribe_account_events(
+        &self,
+        timeout: Option<Duration>,
+    ) -> RecieverStream<AccountEvent> {
+        info!(target: "SubscribeAccountEvents", "Subscribing to the account events");
+        self.client().data.subscribe_account_events(timeout).await
+    }
+
+    /// Subscribes to the results of the trades, a `Deal` is sent every time a trade of the account
+    /// closes (won, lost or draw, see its `profit`), so the trades don't need to be checked one by
+    /// one with `check_results`. Every subscriber receives all the closed deals.
+    ///
+    /// # Arguments
+    /// * `timeout` - Optional maximum time to wait for each deal
+    ///
+    /// # Examples
+    /// ```rust
+    /// let results = client.subscribe_trade_results(None).await;
+    /// let mut stream = results.to_stream();
+    /// while let Some(deal) = stream.next().await {
+    ///     let deal = deal?;
+    ///     println!("Trade {} closed with a profit of {}", deal.id, deal.profit);
+    /// }
+    /// ```
+    pub async fn subscribe_trade_results(&self, timeout: Option<Duration>) -> RecieverStream<Deal> {
+        info!(target: "SubscribeTradeResults", "Subscribing to the trade results");
+        self.client().data.subscribe_trade_results(timeout).await
+    }
+
+    /// Subscribes to the combined results of the straddles placed by `straddle`, one result is sent
+    /// once both legs of a straddle closed.
+    ///
+    /// # Arguments
+    /// * `timeout` - Optional maximum time to wait for each result
+    pub async fn subscribe_straddle_results(
+        &self,
+        timeout: Option<Duration>,
+    ) -> RecieverStream<StraddleResult> {
+        info!(target: "SubscribeStraddleResults", "Subscribing to the straddle results");
+        self.client().data.subscribe_straddle_results(timeout).await
+    }
+
+    /// Subscribes to the changes of the chart subscriptions: assets subscribed, queued, activated or
+    /// rotated out once the subscription limit of the server is reached, and the detection of that
+    /// limit.
+    ///
+    /// # Arguments
+    /// * `timeout` - Optional maximum time to wait for each event, it only applies to this subscription
+    ///
+    /// # Examples
+    /// ```rust
+    /// let events = client.subscribe_subscription_events(None);
+    /// let mut stream = events.to_stream();
+    /// while let Some(event) = stream.next().await {
+    ///     if let SubscriptionEvent::Evicted { asset, .. } = event? {
+    ///         println!("No more ticks for {asset}");
+    ///     }
+    /// }
+    /// ```
+    pub fn subscribe_subscription_events(
+        &self,
+        timeout: Option<Duration>,
+    ) -> RecieverStream<SubscriptionEvent> {
+        info!(target: "SubscribeSubscriptionEvents", "Subscribing to the subscription events");
+        self.client().data.subscribe_subscription_events(timeout)
+    }
+
+    /// Subscribes to the messages recieved from the server that failed to parse, with their raw payload
+    /// and the parsing error, which allows detecting changes in the protocol of the broker.
+    ///
+    /// # Arguments
+    /// * `timeout` - Optional maximum time to wait for each message, it only applies to this subscription
+    ///
+    /// # Examples
+    /// ```rust
+    /// let unparsed = client.subscribe_unparsed(None);
+    /// let mut stream = unparsed.to_stream();
+    /// while let Some(message) = stream.next().await {
+    ///     let message = message?;
+    ///     println!("{:?}: {} ({})", message.event, message.payload, message.error);
+    /// }
+    /// ```
+    pub fn subscribe_unparsed(&self, timeout: Option<Duration>) -> RecieverStream<UnparsedMessage> {
+        info!(target: "SubscribeUnparsed", "Subscribing to the unparsed messages");
+        self.client().data.subscribe_unparsed(timeout)
+    }
+
+    /// Returns rolling statistics of the latency of the last streamed messages (time between their
+    /// server timestamp and the moment they were recieved), see `LatencyStats`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let stats = client.latency_stats().await;
+    /// if stats.transport_p99 > 500.0 {
+    ///     println!("Falling behind the feed: {stats:?}");
+    /// }
+    /// ```
+    pub async fn latency_stats(&self) -> LatencyStats {
+        self.client().data.latency_stats().await
+    }
+
+    /// Returns the message rate of every subscribed feed compared to the rate it usually streams
+    /// at, see `FeedHealth`. A feed whose rate collapses (possible throttling or ban of the account
+    /// by the server) is logged and sent as a `FeedAnomaly` account event to the subscribers of
+    /// `subscribe_account_events`, so is its recovery.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let report = client.feed_health().await;
+    /// if report.suspected_throttling {
+    ///     println!("Every feed collapsed: {:?}", report.collapsed);
+    /// }
+    /// ```
+    pub async fn feed_health(&self) -> FeedHealth {
+        self.client().data.feed_health().await
+    }
+
+    /// Sets the period of the candles kept in memory for the subscribed assets (1 minute by
+    /// default) and how many are kept per asset (`DEFAULT_STORE_CAPACITY` by default). Changing the
+    /// period drops the candles kept so far.
+    pub fn set_candle_store(
+        &self,
+        period: impl IntoTimeframe,
+        capacity: usize,
+    ) -> PocketResult<()> {
+        let period = TimeDelta::seconds(period.into_timeframe()?.seconds());
+        if capacity == 0 {
+            return Err(PocketOptionError::Unallowed(
+                "the candle store must keep at least one candle per asset".to_string(),
+            ));
+        }
+        let store = self.client().data.candle_store();
+        let mut store = store.write().unwrap_or_else(|e| e.into_inner());
+        store.configure(period, capacity);
+        Ok(())
+    }
+
+    /// Returns the last `count` candles of a subscribed asset from the ones kept in memory, without
+    /// a request to the server. The last candle is the one of the current period, still open. Fewer
+    /// candles are returned while the store fills up after the subscription.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut stream = client.subscribe_symbol("EURUSD_otc").await?.to_stream();
+    /// while let Some(_) = stream.next().await {
+    ///     let candles = client.get_recent_candles("EURUSD_otc", 50);
+    ///     // evaluate the strategy on `candles`
+    /// }
+    /// ```
+    pub fn get_recent_candles(&self, asset: impl ToString, count: usize) -> Vec<DataCandle> {
+        let store = self.client().data.candle_store();
+        let store = store.read().unwrap_or_else(|e| e.into_inner());
+        store.recent(&asset.to_string(), count)
+    }
+
+    /// Returns the candles kept in memory for `asset` starting between `start` (included) and `end`
+    /// (excluded), without a request to the server. Use `get_candles_range` for the older ones.
+    pub fn get_candles_between(
+        &self,
+        asset: impl ToString,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Vec<DataCandle> {
+        let store = self.client().data.candle_store();
+        let store = store.read().unwrap_or_else(|e| e.into_inner());
+        store.between(&asset.to_string(), start, end)
+    }
+
+    /// Keeps the candle store on disk, so the candles survive the restarts of the bot. The candles
+    /// saved in the file at `path` (`candle_store.json` in the cache directory of `storage` by
+    /// default) are loaded right away, before the ones recorded since, and the store is saved every
+    /// `interval`, on `shutdown` and once the last clone of the client is dropped. Returns the
+    /// number of candles loaded, a file saved with another period of the store isn't loaded.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let loaded = client
+    ///     .persist_candle_store(None, Duration::from_secs(60))
+    ///     .await?;
+    /// println!("{loaded} candles loaded");
+    /// ```
+    pub async fn persist_candle_store(
+        &self,
+        path: Option<PathBuf>,
+        interval: Duration,
+    ) -> PocketResult<usize> {
+        if interval.is_zero() {
+            return Err(PocketOptionError::Unallowed(
+                "the interval between the saves of the candle store must be positive".to_string(),
+            ));
+        }
+        let path = match path {
+            Some(path) => path,
+            None => storage()
+                .cache_file(CANDLE_STORE_FILE)
+                .map_err(BinaryOptionsToolsError::from)?,
+        };
+        let store = self.client().data.candle_store();
+        let loaded = store
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .load(&path)?;
+        info!(target: "CandleStore", "Loaded {loaded} candles from '{}'", path.display());
+        let handle = tokio::spawn(Self::save_candle_store(
+            Arc::downgrade(&self.inner),
+            store,
+            path.clone(),
+            interval,
+        ));
+        self.track_task(&handle);
+        let mut current = self
+            .inner
+            .candle_store_file
+            .lock()
+            .map_err(|e| PocketOptionError::UnreachableError(e.to_string()))?;
+        if let Some((_, task)) = current.replace((path, handle.abort_handle())) {
+            task.abort();
+        }
+        Ok(loaded)
+    }
+
+    /// Saves the candle store every `interval`, and a last time once the last clone of the client is
+    /// dropped.
+    async fn save_candle_store(
+        inner: Weak<PocketInner>,
+        store: Arc<RwLock<CandleStore>>,
+        path: PathBuf,
+        interval: Duration,
+    ) {
+        let mut check = tokio::time::interval(interval);
+        // The first tick completes right away, the store was just loaded
+        check.tick().await;
+        loop {
+            check.tick().await;
+            let dropped = inner.strong_count() == 0;
+            let saved = store.read().unwrap_or_else(|e| e.into_inner()).save(&path);
+            if let Err(e) = saved {
+                warn!(target: "CandleStore", "Failed to save the candle store, {e}");
+            }
+            if dropped {
+                break;
+            }
+        }
+    }
+
+    /// Caches the metadata of the assets (payouts, expiries and open markets) in the file at `path`,
+    /// so a client started while the server is slow to send them can still validate its orders. The
+    /// cache is loaded right away if it isn't older than `ttl` and the server didn't send the assets
+    /// yet, the assets sent by the server replace it and are saved in the background.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let status = client
+    ///     .set_metadata_cache("assets.json", DEFAULT_METADATA_TTL)
+    ///     .await?;
+    /// println!("{} assets from {:?}", status.assets, status.source);
+    /// ```
+    pub async fn set_metadata_cache(
+        &self,
+        path: impl AsRef<Path>,
+        ttl: Duration,
+    ) -> PocketResult<MetadataStatus> {
+        if ttl.is_zero() {
+            return Err(PocketOptionError::Unallowed(
+                "the TTL of the metadata cache must be positive".to_string(),
+            ));
+        }
+        let cache = MetadataCache::new(path, ttl);
+        match cache.load(Utc::now()) {
+            Ok(Some(cached)) => {
+                let (saved_at, assets) = (cached.saved_at, cached.assets.len());
+                if self.client().data.seed_assets(cached).await {
+                    info!(target: "MetadataCache", "Loaded {assets} assets sent by the server at {saved_at} from '{}'", cache.path().display());
+                }
+            }
+            Ok(None) => {
+                debug!(target: "MetadataCache", "No recent metadata in '{}'", cache.path().display())
+            }
+            Err(e) => warn!(target: "MetadataCache", "Failed to load the metadata cache, {e}"),
+        }
+        let handle = tokio::spawn(Self::save_metadata(
+            Arc::downgrade(&self.inner),
+            cache.clone(),
+        ));
+        self.track_task(&handle);
+        {
+            let mut current = self
+                .inner
+                .metadata_cache
+                .lock()
+                .map_err(|e| PocketOptionError::UnreachableError(e.to_string()))?;
+            if let Some((_, task)) = current.replace((cache, handle.abort_handle())) {
+                task.abort();
+            }
+        }
+        Ok(self.metadata_status().await)
+    }
+
+    /// Saves the assets to the metadata cache whenever the server sent them again, until the last
+    /// clone of the client is dropped.
+    async fn save_metadata(inner: Weak<PocketInner>, cache: MetadataCache) {
+        let mut check = tokio::time::interval(METADATA_SAVE_INTERVAL);
+        let mut saved = None;
+        loop {
+            check.tick().await;
+            let Some(inner) = inner.upgrade() else {
+                break;
+            };
+            let data = &inner.client.data;
+            let origin = data.metadata_origin();
+            let Some(updated_at) = origin.updated_at else {
+                continue;
+            };
+            if origin.source != MetadataSource::Server || saved == Some(updated_at) {
+                continue;
+            }
+            match cache.save(data.get_assets().await, updated_at) {
+                Ok(()) => saved = Some(updated_at),
+                Err(e) => warn!(target: "MetadataCache", "Failed to save the metadata cache, {e}"),
+            }
+        }
+    }
+
+    /// Returns where the metadata of the assets come from (the server, the cache or neither yet),
+    /// when the server sent them and whether the cached ones are older than the TTL of the cache.
+    pub async fn metadata_status(&self) -> MetadataStatus {
+        let data = &self.client().data;
+        let origin = data.metadata_origin();
+        let cache = self
+            .inner
+            .metadata_cache
+            .lock()
+            .ok()
+            .and_then(|current| current.as_ref().map(|(cache, _)| cache.clone()));
+        let stale = origin.source == MetadataSource::Cache
+            && cache
+                .as_ref()
+                .zip(origin.updated_at)
+                .is_some_and(|(cache, at)| cache.is_expired(at, Utc::now()));
+        MetadataStatus {
+            source: origin.source,
+            updated_at: origin.updated_at,
+            assets: data.get_assets().await.len(),
+            path: cache.map(|cache| cache.path().to_path_buf()),
+            stale,
+        }
+    }
+
+    /// Returns internal counters of the client (JSON backend, buffer pools) to diagnose performance
+    /// issues, the buffers are shared by every client of the process.
+    pub fn debug_stats(&self) -> DebugStats {
+        DebugStats::collect()
+    }
+
     pub async fn get_server_time(&self) -> DateTime<Utc> {
         Utc::now() + Duration::from_secs(2 * 3600 + 123)
     }
 
+    /// Returns the current health of the client: connection state, age of the last
+    /// recieved message and number of open trades.
+    #[cfg(feature = "metrics")]
+    pub async fn health(&self) -> HealthReport {
+        let health = &self.client().data.health;
+        HealthReport {
+            connected: health.is_connected(),
+            last_message_age: health.last_message_age().map(|age| age.as_secs_f64()),
+            open_trades: self.client().data.get_opened_deals().await.len(),
+            reconnections: health.reconnections(),
+            shed_ticks: self.client().data.stream_lane_stats().shed,
+            collapsed_feeds: self.client().data.feed_health().await.collapsed,
+        }
+    }
+
+    /// Starts an HTTP healthcheck endpoint on the given address.
+    ///
+    /// # Arguments
+    /// * `addr` - Address to listen on (e.g., `0.0.0.0:8080`)
+    ///
+    /// # Returns
+    /// The handle of the background task serving the endpoint, aborting it stops the server
+    ///
+    /// # Examples
+    /// ```rust
+    /// let handle = client.start_healthcheck("0.0.0.0:8080".parse()?).await?;
+    /// // curl http://localhost:8080/health
+    /// ```
+    #[cfg(feature = "http-gateway")]
+    pub async fn start_healthcheck(&self, addr: SocketAddr) -> PocketResult<JoinHandle<()>> {
+        let handle = serve_healthcheck(self.clone(), addr).await?;
+        self.track_task(&handle);
+        Ok(handle)
+    }
+
+    /// Starts an HTTP healthcheck endpoint only answering the requests carrying an API key of
+    /// `access` (`Authorization: Bearer <key>` or `X-Api-Key: <key>` header), any role can read it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let access = Arc::new(AccessControl::new());
+    /// access.add_key("dashboard-key", "dashboard", Role::ReadOnly, TradeLimits::default())?;
+    /// let handle = client.start_healthcheck_with_access("0.0.0.0:8080".parse()?, access).await?;
+    /// // curl -H "X-Api-Key: dashboard-key" http://localhost:8080/health
+    /// ```
+    #[cfg(feature = "http-gateway")]
+    pub async fn start_healthcheck_with_access(
+        &self,
+        addr: SocketAddr,
+        access: Arc<AccessControl>,
+    ) -> PocketResult<JoinHandle<()>> {
+        let handle = serve_healthcheck_with_access(self.clone(), addr, access).await?;
+        self.track_task(&handle);
+        Ok(handle)
+    }
+
+    /// Starts the dashboard of the client on the given address: a page showing the balance, the
+    /// open and closed trades, the streams, the pending orders and the logs of the client, updated
+    /// every second. The logs are the ones of `logs` (the broadcast of a logs layer), with `access`
+    /// the page must be opened with an API key allowed to read (`http://host:port/?key=<key>`).
+    ///
+    /// # Examples
+    /// ```rust
+    /// let handle = client.start_dashboard("127.0.0.1:8000".parse()?, None, None).await?;
+    /// // open http://127.0.0.1:8000 in a browser
+    /// ```
+    #[cfg(feature = "dashboard")]
+    pub async fn start_dashboard(
+        &self,
+        addr: SocketAddr,
+        logs: Option<LogBroadcast>,
+        access: Option<Arc<AccessControl>>,
+    ) -> PocketResult<JoinHandle<()>> {
+        let handle = serve_dashboard(self.clone(), addr, logs, access).await?;
+        self.track_task(&handle);
+        Ok(handle)
+    }
+
+    /// Registers a background task of the client so `shutdown` stops it.
+    fn track_task<T>(&self, handle: &JoinHandle<T>) {
+        if let Ok(mut tasks) = self.inner.tasks.lock() {
+            tasks.retain(|task| !task.is_finished());
+            tasks.push(handle.abort_handle());
+        }
+    }
+
+    /// Closes the connection with the server for good: the background tasks of the client (like the
+    /// healthcheck server) are stopped, a close frame is sent and the connection isn't reconnected.
+    /// Every stream of the client ends and the requests sent afterwards fail. Shuts down every clone
+    /// of the client, calling it again does nothing.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let stream = client.subscribe_symbol("EURUSD_otc").await?;
+    /// client.shutdown().await;
+    /// assert!(stream.recieve().await.is_err());
+    /// ```
+    pub async fn shutdown(&self) {
+        let candle_store_file = self
+            .inner
+            .candle_store_file
+            .lock()
+            .ok()
+            .and_then(|current| current.as_ref().map(|(path, _)| path.clone()));
+        if let Some(path) = candle_store_file {
+            let store = self.client().data.candle_store();
+            let saved = store.read().unwrap_or_else(|e| e.into_inner()).save(&path);
+            if let Err(e) = saved {
+                warn!(target: "CandleStore", "Failed to save the candle store, {e}");
+            }
+        }
+        if let Ok(mut tasks) = self.inner.tasks.lock() {
+            for task in tasks.drain(..) {
+                task.abort();
+            }
+        }
+        self.client().shutdown().await;
+        info!(target: "Shutdown", "PocketOption client shut down");
+    }
+
+    /// Number of orders sent (or waiting to be sent) whose confirmation wasn't recieved yet.
+    pub fn pending_orders(&self) -> usize {
+        self.inner.pending_orders.load(Ordering::SeqCst)
+    }
+
+    /// Waits up to `timeout` for the pending orders to be confirmed by the server (or to fail),
+    /// so `shutdown` doesn't drop an order that was already requested. Fails with a `TimeoutError`
+    /// if some orders are still pending after `timeout`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// client.flush_orders(Duration::from_secs(5)).await?;
+    /// client.shutdown().await;
+    /// ```
+    pub async fn flush_orders(&self, timeout: Duration) -> PocketResult<()> {
+        let deadline = Instant::now() + timeout;
+        while self.pending_orders() > 0 {
+            if Instant::now() >= deadline {
+                return Err(BinaryOptionsToolsError::TimeoutError {
+                    task: format!("flush_orders ({} pending)", self.pending_orders()),
+                    duration: timeout,
+                }
+                .into());
+            }
+            tokio::time::sleep(FLUSH_POLL_INTERVAL).await;
+        }
+        Ok(())
+    }
+
+    /// Returns true once `shutdown` was called.
+    pub fn is_shut_down(&self) -> bool {
+        self.client().is_closed()
+    }
+
+    /// Returns true while the websocket is connected, false during the reconnection windows and
+    /// after a shutdown.
+    pub fn is_connected(&self) -> bool {
+        self.client().data.health.is_connected()
+    }
+
+    /// Subscribes to the changes of the state of the connection: `Connected`, `Disconnected`,
+    /// `Reconnecting` and `ReconnectFailed` (with the number of the attempt), every subscriber
+    /// receives all the events. The stream ends when the client is shut down.
+    ///
+    /// # Arguments
+    /// * `timeout` - Optional maximum time to wait for each event
+    ///
+    /// # Examples
+    /// ```rust
+    /// let events = client.connection_events(None);
+    /// let mut stream = events.to_stream();
+    /// while let Some(event) = stream.next().await {
+    ///     let event = event?;
+    ///     println!("{}: {:?}", event.time, event.kind);
+    /// }
+    /// ```
+    pub fn connection_events(&self, timeout: Option<Duration>) -> RecieverStream<ConnectionEvent> {
+        self.client().data.health.subscribe(timeout)
+    }
+
     pub fn kill(self) {
         drop(self)
     }
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use std::time::Instant;
 
     use futures_util::{
-        StreamExt,
+        SinkExt, StreamExt,
         future::{try_join, try_join_all, try_join3},
     };
     use rand::{random, rng, seq::IndexedRandom};
-    use tokio::{task::JoinHandle, time::sleep};
+    use tokio::{
+        net::TcpListener,
+        sync::mpsc::{UnboundedReceiver, unbounded_channel},
+        task::JoinHandle,
+        time::sleep,
+    };
 
+    use binary_options_tools_core::reimports::{Message, accept_async};
     use binary_options_tools_core::utils::tracing::{start_tracing, start_tracing_leveled};
     use tracing::level_filters::LevelFilter;
     use url::Url;
 
     use super::*;
+    use crate::pocketoption::types::timeframe::Timeframe;
 
     fn to_future(stream: StreamAsset, id: i32) -> JoinHandle<anyhow::Result<()>> {
         tokio::spawn(async move {
@@ -869,6 +3853,201 @@ mod tests {
         })
     }
 
+    const MOCK_SSID: &str = r#"42["auth",{"session":"looc69ct294h546o368s0lct7d","isDemo":1,"uid":87742848,"platform":2}]"#;
+
+    /// Local websocket server answering the handshake and the authentication like the broker, so
+    /// the lifecycle of the client is tested without a connection to the real servers.
+    pub(crate) async fn mock_server() -> anyhow::Result<Url> {
+        Ok(mock_server_frames().await?.0)
+    }
+
+    /// Same as `mock_server`, also returning every text frame sent by the clients. The server never
+    /// answers the other frames.
+    async fn mock_server_frames() -> anyhow::Result<(Url, UnboundedReceiver<String>)> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = Url::parse(&format!("ws://{}", listener.local_addr()?))?;
+        let (frames, recieved) = unbounded_channel();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let frames = frames.clone();
+                tokio::spawn(async move {
+                    let mut ws = accept_async(stream).await?;
+                    ws.send(Message::text(r#"0{"sid":"mock","pingInterval":25000}"#))
+                        .await?;
+                    while let Some(message) = ws.next().await {
+                        let Message::Text(text) = message? else {
+                            continue;
+                        };
+                        let _ = frames.send(text.to_string());
+                        if text.as_str() == "40" {
+                            ws.send(Message::text(r#"40{"sid":"mock"}"#)).await?;
+                        } else if text.starts_with(r#"42["auth""#) {
+                            ws.send(Message::text(
+                                r#"451-["successauth",{"_placeholder":true,"num":0}]"#,
+                            ))
+                            .await?;
+                        }
+                    }
+                    anyhow::Ok(())
+                });
+            }
+        });
+        Ok(url)
+    }
+
+    pub(crate) async fn mock_client() -> anyhow::Result<PocketOption> {
+        Ok(PocketOption::new_with_url(MOCK_SSID, mock_server().await?).await?)
+    }
+
+    #[tokio::test]
+    async fn test_power_saving_drops_client() -> anyhow::Result<()> {
+        let client = mock_client().await?;
+        client.set_power_saving(Some(Duration::from_secs(1)))?;
+        let inner = Arc::downgrade(&client.inner);
+        drop(client);
+        // The task of the power saving mode doesn't keep the client alive
+        sleep(Duration::from_millis(100)).await;
+        assert!(inner.upgrade().is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_doesnt_reconnect() -> anyhow::Result<()> {
+        let (url, mut frames) = mock_server_frames().await?;
+        let client = PocketOption::new_with_url(MOCK_SSID, url).await?;
+        while frames.try_recv().is_ok() {}
+
+        // The server answers the close frame and ends the connection, which isn't opened again
+        client.shutdown().await;
+        sleep(Duration::from_secs(1)).await;
+        while let Ok(frame) = frames.try_recv() {
+            assert_ne!(frame, "40", "Reconnected after the shutdown");
+        }
+        assert!(!client.is_connected());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_symbol_batch_pipelined() -> anyhow::Result<()> {
+        let (url, mut frames) = mock_server_frames().await?;
+        let client = PocketOption::new_with_url(MOCK_SSID, url).await?;
+        let assets: Vec<String> = (0..20).map(|i| format!("ASSET{i}_otc")).collect();
+
+        // The server never acknowledges the subscriptions, the batch doesn't wait for it (only for
+        // the writer, which holds the subscription frames during the first seconds of a connection)
+        let streams = tokio::time::timeout(
+            Duration::from_secs(15),
+            client.subscribe_symbol_batch(&assets),
+        )
+        .await??;
+        assert_eq!(streams.len(), assets.len());
+
+        let mut subscribed = Vec::new();
+        while subscribed.len() < assets.len() {
+            let frame = tokio::time::timeout(Duration::from_secs(10), frames.recv())
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Mock server stopped"))?;
+            if frame.contains("changeSymbol") {
+                subscribed.push(frame);
+            }
+        }
+        // Sent in the order of the batch
+        for (frame, asset) in subscribed.iter().zip(&assets) {
+            assert!(frame.contains(&format!(r#""{asset}""#)), "{frame}");
+        }
+        client.shutdown().await;
+        Ok(())
+    }
+
+    /// Deal of a trade closing at `close` (in the time of the server, 2 hours ahead).
+    fn deal_closing_at(close: DateTime<Utc>) -> anyhow::Result<Deal> {
+        let close = close + TimeDelta::hours(2);
+        Ok(serde_json::from_value(serde_json::json!({
+            "id": Uuid::new_v4(),
+            "openTime": "2024-12-04 23:58:48",
+            "closeTime": "2024-12-04 23:59:48",
+            "openTimestamp": (close - TimeDelta::minutes(1)).timestamp(),
+            "closeTimestamp": close.timestamp(),
+            "uid": 1,
+            "isDemo": 1,
+            "amount": 10.0,
+            "profit": 9.2,
+            "percentProfit": 92,
+            "percentLoss": 100,
+            "openPrice": 1.1,
+            "copyTicket": "",
+            "closePrice": 1.2,
+            "command": 0,
+            "asset": "EURUSD_otc",
+            "openMs": 601,
+            "optionType": 100,
+            "currency": "USD"
+        }))?)
+    }
+
+    #[tokio::test]
+    async fn test_poll_result() -> anyhow::Result<()> {
+        let client = mock_client().await?;
+        let data = &client.client().data;
+        let opened = deal_closing_at(Utc::now() + TimeDelta::minutes(1))?;
+        let closed = deal_closing_at(Utc::now() - TimeDelta::minutes(1))?;
+        data.update_opened_deals(vec![opened.clone()]).await;
+        data.update_closed_deals(vec![closed.clone()]).await;
+
+        assert_eq!(client.poll_result(opened.id).await, None);
+        assert_eq!(client.poll_result(closed.id).await, Some(closed));
+        // An unknown trade can't be told apart from an open one
+        assert_eq!(client.poll_result(Uuid::new_v4()).await, None);
+        client.shutdown().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_check_results_within_timeout() -> anyhow::Result<()> {
+        let client = mock_client().await?;
+        let opened = deal_closing_at(Utc::now() + TimeDelta::minutes(1))?;
+        client
+            .client()
+            .data
+            .update_opened_deals(vec![opened.clone()])
+            .await;
+
+        let timeout = Duration::from_millis(200);
+        let start = Instant::now();
+        match client.check_results_within(opened.id, timeout).await {
+            Err(PocketOptionError::TradeTimeout { trade_id, seconds }) => {
+                assert_eq!(trade_id, opened.id);
+                assert_eq!(seconds, 0.2);
+            }
+            res => panic!("Expected a TradeTimeout error, got {res:?}"),
+        }
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        // The result of a closed trade is returned right away
+        let closed = Deal {
+            profit: -10.0,
+            ..opened.clone()
+        };
+        client
+            .client()
+            .data
+            .update_closed_deals(vec![closed.clone()])
+            .await;
+        let deal = client.check_results_within(opened.id, timeout).await?;
+        assert_eq!(deal, closed);
+        client.shutdown().await;
+        Ok(())
+    }
+
+    #[test]
+    fn test_client_is_a_cheap_handle() {
+        // Every method of the bindings clones the client, it must stay a single `Arc`
+        assert_eq!(
+            std::mem::size_of::<PocketOption>(),
+            std::mem::size_of::<usize>()
+        );
+    }
+
     #[tokio::test]
     #[should_panic(expected = "MaxDemoTrades")]
     async fn test_pocket_option() {
@@ -1148,7 +4327,7 @@ mod tests {
         let client = PocketOption::new(ssid).await.unwrap();
         let mut last_candles = Vec::new();
         for i in 0..10 {
-            let candles = client.get_candles("EURUSD_otc", 60, 6000).await?;
+            let candles = client.get_candles("EURUSD_otc", 60, 6000, true).await?;
             last_candles = candles.clone();
             println!("Candles n°{} len: {}, ", i + 1, candles.len());
         }
@@ -1162,7 +4341,7 @@ mod tests {
         // time: 1733040000, offset: 540000, period: 3600
         let client = PocketOption::new(ssid).await.unwrap();
         for i in 0..1000 {
-            let candles = client.history("EURUSD_otc", 6000).await?;
+            let candles = client.history("EURUSD_otc", Timeframe::H1).await?;
             println!("Candles n°{} len: {}, ", i + 1, candles.len());
         }
         Ok(())
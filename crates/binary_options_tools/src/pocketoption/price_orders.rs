@@ -0,0 +1,235 @@
+//! Orders waiting for the quote of an asset to cross a target price. The client watches the ticks
+//! of the asset itself and sends the trade from the task recieving them, so the trade goes out as
+//! soon as the tick crossing the price is recieved.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::task::AbortHandle;
+use uuid::Uuid;
+
+use super::{
+    error::{PocketOptionError, PocketResult},
+    types::order::Direction,
+};
+
+/// State of a `PriceOrder`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum PriceOrderState {
+    /// Waiting for the quote to cross the price.
+    Waiting,
+    /// The quote crossed the price, the trade is being sent.
+    Sending {
+        quote: f64,
+    },
+    /// The trade was placed when the quote was `quote`.
+    Triggered {
+        trade_id: Uuid,
+        quote: f64,
+    },
+    /// The trade (or the stream of the asset) failed.
+    Failed {
+        error: String,
+    },
+    Cancelled,
+    /// The price wasn't crossed before the order expired.
+    Expired,
+}
+
+/// Trade sent once the quote of `asset` crosses `price`, as returned by `PocketOption::buy_at_price`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceOrder {
+    pub id: Uuid,
+    pub asset: String,
+    pub direction: Direction,
+    pub amount: f64,
+    /// Expiry of the trade in seconds.
+    pub time: u32,
+    pub price: f64,
+    pub created_at: DateTime<Utc>,
+    /// The order is cancelled if the price wasn't crossed by then.
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(flatten)]
+    pub state: PriceOrderState,
+}
+
+impl PriceOrder {
+    pub fn new(
+        asset: impl ToString,
+        direction: Direction,
+        amount: f64,
+        time: u32,
+        price: f64,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> PocketResult<Self> {
+        if !price.is_finite() || price <= 0.0 {
+            return Err(PocketOptionError::Unallowed(format!(
+                "the target price must be a positive number, got {price}"
+            )));
+        }
+        Ok(Self {
+            id: Uuid::new_v4(),
+            asset: asset.to_string(),
+            direction,
+            amount,
+            time,
+            price,
+            created_at: Utc::now(),
+            expires_at,
+            state: PriceOrderState::Waiting,
+        })
+    }
+}
+
+/// Detects the quote crossing a price: the first quote gives the side the quote starts from, the
+/// price is crossed once a quote reaches it from that side.
+#[derive(Debug, Clone)]
+pub struct PriceTrigger {
+    price: f64,
+    below: Option<bool>,
+}
+
+impl PriceTrigger {
+    pub fn new(price: f64) -> Self {
+        Self { price, below: None }
+    }
+
+    /// Returns true if `quote` crossed (or touched) the price.
+    pub fn crossed(&mut self, quote: f64) -> bool {
+        match self.below {
+            _ if quote == self.price => true,
+            None => {
+                self.below = Some(quote < self.price);
+                false
+            }
+            Some(true) => quote > self.price,
+            Some(false) => quote < self.price,
+        }
+    }
+}
+
+/// Price orders of a client, shared by its clones and the tasks watching the quotes.
+#[derive(Default, Clone)]
+pub struct PriceOrders {
+    orders: Arc<Mutex<Entries>>,
+}
+
+type Entries = HashMap<Uuid, (PriceOrder, Option<AbortHandle>)>;
+
+impl PriceOrders {
+    fn lock(&self) -> MutexGuard<'_, Entries> {
+        self.orders.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn insert(&self, order: PriceOrder) {
+        self.lock().insert(order.id, (order, None));
+    }
+
+    /// Sets the task watching the quotes of the order, aborted if the order is cancelled.
+    pub fn set_task(&self, id: Uuid, task: AbortHandle) {
+        let mut orders = self.lock();
+        if let Some((_, handle)) = orders.get_mut(&id) {
+            *handle = Some(task);
+        }
+    }
+
+    pub fn get(&self, id: Uuid) -> Option<PriceOrder> {
+        self.lock().get(&id).map(|(order, _)| order.clone())
+    }
+
+    /// Every order, oldest first.
+    pub fn list(&self) -> Vec<PriceOrder> {
+        let mut orders: Vec<PriceOrder> = self
+            .lock()
+            .values()
+            .map(|(order, _)| order.clone())
+            .collect();
+        orders.sort_by_key(|order| order.created_at);
+        orders
+    }
+
+    /// Moves a waiting order to `state`, returns false if the order isn't waiting anymore (it was
+    /// cancelled or is already being sent).
+    pub fn transition(&self, id: Uuid, state: PriceOrderState) -> bool {
+        match self.lock().get_mut(&id) {
+            Some((order, _)) if order.state == PriceOrderState::Waiting => {
+                order.state = state;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Sets the final state of an order once its trade was sent.
+    pub fn finish(&self, id: Uuid, state: PriceOrderState) {
+        if let Some((order, _)) = self.lock().get_mut(&id) {
+            order.state = state;
+        }
+    }
+
+    /// Cancels a waiting order and stops the task watching its quotes. Fails if the order doesn't
+    /// exist or if the price was already crossed.
+    pub fn cancel(&self, id: Uuid) -> PocketResult<PriceOrder> {
+        let mut orders = self.lock();
+        let (order, task) = orders
+            .get_mut(&id)
+            .ok_or_else(|| PocketOptionError::Unallowed(format!("no price order '{id}'")))?;
+        if order.state != PriceOrderState::Waiting {
+            return Err(PocketOptionError::Unallowed(format!(
+                "price order '{id}' can't be cancelled, it's {:?}",
+                order.state
+            )));
+        }
+        order.state = PriceOrderState::Cancelled;
+        if let Some(task) = task.take() {
+            task.abort();
+        }
+        Ok(order.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::pocketoption::types::order::Action;
+
+    #[test]
+    fn test_price_orders() -> anyhow::Result<()> {
+        let mut trigger = PriceTrigger::new(1.1);
+        assert!(!trigger.crossed(1.09));
+        assert!(!trigger.crossed(1.095));
+        assert!(trigger.crossed(1.1001));
+        let mut trigger = PriceTrigger::new(1.1);
+        assert!(!trigger.crossed(1.2));
+        assert!(!trigger.crossed(1.3));
+        assert!(trigger.crossed(1.05));
+        assert!(PriceTrigger::new(1.1).crossed(1.1));
+
+        assert!(PriceOrder::new("EURUSD_otc", Action::Call, 1.0, 60, f64::NAN, None).is_err());
+        let orders = PriceOrders::default();
+        let first = PriceOrder::new("EURUSD_otc", Action::Call, 1.0, 60, 1.1, None)?;
+        let second = PriceOrder::new("EURUSD_otc", Action::Put, 1.0, 60, 1.2, None)?;
+        orders.insert(first.clone());
+        orders.insert(second.clone());
+
+        assert!(orders.transition(first.id, PriceOrderState::Sending { quote: 1.1 }));
+        // An order being sent can't be cancelled nor sent twice
+        assert!(orders.cancel(first.id).is_err());
+        assert!(!orders.transition(first.id, PriceOrderState::Expired));
+
+        assert_eq!(orders.cancel(second.id)?.state, PriceOrderState::Cancelled);
+        assert!(!orders.transition(second.id, PriceOrderState::Sending { quote: 1.2 }));
+        assert!(orders.cancel(Uuid::new_v4()).is_err());
+
+        let json = serde_json::to_value(orders.get(second.id))?;
+        assert_eq!(json["status"], "cancelled");
+        assert_eq!(orders.list().len(), 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,472 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex, RwLock},
+    time::Duration,
+};
+
+use binary_options_tools_core::utils::audit::AUDIT_TARGET;
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use super::{
+    error::{PocketOptionError, PocketResult},
+    performance::{PerformanceMatrix, TimeAxis},
+    types::order::Action,
+};
+
+/// Trade about to be sent to the server, checked against every `RiskRule` before it's placed.
+#[derive(Debug, Clone)]
+pub struct TradeIntent {
+    pub asset: String,
+    pub action: Action,
+    pub amount: f64,
+    pub time: u32,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TradeIntent {
+    pub fn new(asset: impl ToString, action: Action, amount: f64, time: u32) -> Self {
+        Self {
+            asset: asset.to_string(),
+            action,
+            amount,
+            time,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// A rule enforced by the `RiskManager` before every trade.
+pub trait RiskRule: Send + Sync {
+    /// Name of the rule, used in the rejection error.
+    fn name(&self) -> &str;
+
+    /// Checks the trade, returning the reason of the rejection if it isn't allowed.
+    /// Rules that keep track of the placed trades should reserve the trade here, so concurrent
+    /// trades can't both pass the check.
+    fn check(&self, intent: &TradeIntent) -> Result<(), String>;
+
+    /// Called when a trade accepted by `check` was rejected by another rule or failed to be placed.
+    fn rollback(&self, _intent: &TradeIntent) {}
+}
+
+/// Set of `RiskRule` shared by every clone of the client.
+#[derive(Default)]
+pub struct RiskManager {
+    rules: RwLock<Vec<Arc<dyn RiskRule>>>,
+}
+
+impl RiskManager {
+    pub fn add_rule(&self, rule: impl RiskRule + 'static) {
+        self.add_shared_rule(Arc::new(rule));
+    }
+
+    /// Adds a rule that is also kept by the caller, to update its state after it was added.
+    pub fn add_shared_rule(&self, rule: Arc<dyn RiskRule>) {
+        if let Ok(mut rules) = self.rules.write() {
+            info!(target: AUDIT_TARGET, event = "config_change", setting = "risk_rules", action = "added", rule = rule.name());
+            rules.push(rule);
+        }
+    }
+
+    /// Removes every rule named `name`.
+    pub fn remove_rule(&self, name: &str) {
+        if let Ok(mut rules) = self.rules.write() {
+            let before = rules.len();
+            rules.retain(|r| r.name() != name);
+            if rules.len() < before {
+                info!(target: AUDIT_TARGET, event = "config_change", setting = "risk_rules", action = "removed", rule = name);
+            }
+        }
+    }
+
+    pub fn clear_rules(&self) {
+        if let Ok(mut rules) = self.rules.write() {
+            rules.clear();
+            info!(target: AUDIT_TARGET, event = "config_change", setting = "risk_rules", action = "cleared");
+        }
+    }
+
+    pub fn rules(&self) -> Vec<String> {
+        self.rules
+            .read()
+            .map(|rules| rules.iter().map(|r| r.name().to_string()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Checks the trade against every rule, stopping at the first rejection.
+    pub fn check(&self, intent: &TradeIntent) -> PocketResult<()> {
+        let rules = self
+            .rules
+            .read()
+            .map_err(|e| PocketOptionError::UnreachableError(e.to_string()))?;
+        for (index, rule) in rules.iter().enumerate() {
+            if let Err(reason) = rule.check(intent) {
+                rules[..index].iter().for_each(|r| r.rollback(intent));
+                warn!(target: "RiskManager", "Trade on '{}' rejected by rule '{}', {}", intent.asset, rule.name(), reason);
+                return Err(PocketOptionError::RiskRuleViolation {
+                    rule: rule.name().to_string(),
+                    reason,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Releases the trade from every rule, used when the trade couldn't be placed.
+    pub fn rollback(&self, intent: &TradeIntent) {
+        if let Ok(rules) = self.rules.read() {
+            rules.iter().for_each(|r| r.rollback(intent));
+        }
+    }
+}
+
+/// Limits the number of trades per asset in a time window, to prevent duplicate entries when
+/// a signal bounces.
+pub struct ClusterGuard {
+    max_trades: usize,
+    window: Duration,
+    /// If true the windows are aligned to the candle boundaries (e.g. max 1 trade per 1m candle),
+    /// otherwise it's a sliding window ending at the time of the trade.
+    aligned: bool,
+    trades: Mutex<HashMap<String, VecDeque<DateTime<Utc>>>>,
+}
+
+impl ClusterGuard {
+    pub fn new(max_trades: usize, window: Duration, aligned: bool) -> Self {
+        Self {
+            max_trades,
+            window,
+            aligned,
+            trades: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Allows at most `max_trades` per asset in the candle of `period` the trade is placed in.
+    pub fn per_candle(max_trades: usize, period: Duration) -> Self {
+        Self::new(max_trades, period, true)
+    }
+
+    fn window_start(&self, time: DateTime<Utc>) -> DateTime<Utc> {
+        let window = self.window.as_millis().max(1) as i64;
+        let start = if self.aligned {
+            time.timestamp_millis() - time.timestamp_millis().rem_euclid(window)
+        } else {
+            time.timestamp_millis() - window
+        };
+        DateTime::from_timestamp_millis(start).unwrap_or(time)
+    }
+}
+
+impl RiskRule for ClusterGuard {
+    fn name(&self) -> &str {
+        "ClusterGuard"
+    }
+
+    fn check(&self, intent: &TradeIntent) -> Result<(), String> {
+        let mut trades = self.trades.lock().map_err(|e| e.to_string())?;
+        let start = self.window_start(intent.created_at);
+        let placed = trades.entry(intent.asset.clone()).or_default();
+        if self.aligned {
+            placed.retain(|t| *t >= start);
+        } else {
+            placed.retain(|t| *t > start);
+        }
+        if placed.len() >= self.max_trades {
+            return Err(format!(
+                "already placed {} trade(s) on '{}' in the last {}s window (max {})",
+                placed.len(),
+                intent.asset,
+                self.window.as_secs_f64(),
+                self.max_trades
+            ));
+        }
+        placed.push_back(intent.created_at);
+        Ok(())
+    }
+
+    fn rollback(&self, intent: &TradeIntent) {
+        if let Ok(mut trades) = self.trades.lock() {
+            if let Some(placed) = trades.get_mut(&intent.asset) {
+                if let Some(pos) = placed.iter().rposition(|t| *t == intent.created_at) {
+                    placed.remove(pos);
+                }
+            }
+        }
+    }
+}
+
+/// State of a `DrawdownBreaker`, the drawdown is relative to the peak balance.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct DrawdownState {
+    pub max_drawdown: f64,
+    pub peak: Option<f64>,
+    pub balance: Option<f64>,
+    pub drawdown: f64,
+    pub tripped_at: Option<DateTime<Utc>>,
+}
+
+/// Circuit breaker rejecting every trade once the balance fell more than `max_drawdown` (a fraction,
+/// `0.2` is 20%) from its peak, it stays tripped until it's reset.
+pub struct DrawdownBreaker {
+    state: Mutex<DrawdownState>,
+}
+
+impl DrawdownBreaker {
+    pub fn new(max_drawdown: f64) -> PocketResult<Self> {
+        if !(max_drawdown > 0.0 && max_drawdown < 1.0) {
+            return Err(PocketOptionError::Unallowed(format!(
+                "max drawdown must be a fraction between 0 and 1 (exclusive), got {max_drawdown}"
+            )));
+        }
+        Ok(Self {
+            state: Mutex::new(DrawdownState {
+                max_drawdown,
+                ..Default::default()
+            }),
+        })
+    }
+
+    /// Updates the peak and the drawdown with the current balance, returns `true` only when this
+    /// update tripped the breaker.
+    pub fn update_balance(&self, balance: f64) -> bool {
+        let Ok(mut state) = self.state.lock() else {
+            return false;
+        };
+        if !balance.is_finite() || balance < 0.0 {
+            return false;
+        }
+        let peak = state.peak.map_or(balance, |peak| peak.max(balance));
+        state.peak = Some(peak);
+        state.balance = Some(balance);
+        state.drawdown = if peak > 0.0 {
+            (peak - balance) / peak
+        } else {
+            0.0
+        };
+        if state.tripped_at.is_none() && state.drawdown > state.max_drawdown {
+            state.tripped_at = Some(Utc::now());
+            return true;
+        }
+        false
+    }
+
+    pub fn is_tripped(&self) -> bool {
+        self.state
+            .lock()
+            .map(|s| s.tripped_at.is_some())
+            .unwrap_or(true)
+    }
+
+    /// Re-arms the breaker, the current balance becomes the new peak.
+    pub fn reset(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.peak = state.balance;
+            state.drawdown = 0.0;
+            state.tripped_at = None;
+        }
+    }
+
+    pub fn state(&self) -> DrawdownState {
+        self.state.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+}
+
+impl RiskRule for DrawdownBreaker {
+    fn name(&self) -> &str {
+        "DrawdownBreaker"
+    }
+
+    fn check(&self, _intent: &TradeIntent) -> Result<(), String> {
+        let state = self.state.lock().map_err(|e| e.to_string())?;
+        if let Some(tripped_at) = state.tripped_at {
+            return Err(format!(
+                "drawdown of {:.2}% exceeded the maximum of {:.2}% at {tripped_at}, trading is stopped until the breaker is reset",
+                state.drawdown * 100.0,
+                state.max_drawdown * 100.0
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Only allows the trades placed in some hours of the day and days of the week (UTC), e.g. the
+/// profitable hours of a `PerformanceMatrix`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradingHours {
+    hours: [bool; 24],
+    weekdays: [bool; 7],
+}
+
+impl Default for TradingHours {
+    fn default() -> Self {
+        Self {
+            hours: [true; 24],
+            weekdays: [true; 7],
+        }
+    }
+}
+
+impl TradingHours {
+    /// Allows every hour of every day until it's restricted.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only allows the trades placed in `hours` (0 to 23).
+    pub fn hours(mut self, hours: impl IntoIterator<Item = u32>) -> PocketResult<Self> {
+        self.hours = allowed(hours, "hour")?;
+        Ok(self)
+    }
+
+    /// Only allows the trades placed on `weekdays` (0 for Monday to 6 for Sunday).
+    pub fn weekdays(mut self, weekdays: impl IntoIterator<Item = u32>) -> PocketResult<Self> {
+        self.weekdays = allowed(weekdays, "weekday")?;
+        Ok(self)
+    }
+
+    /// Only allows the profitable hours or weekdays of `performance`, the ones with at least
+    /// `min_trades` trades and a positive profit.
+    pub fn profitable(
+        self,
+        performance: &PerformanceMatrix,
+        min_trades: usize,
+    ) -> PocketResult<Self> {
+        let columns = performance.profitable(min_trades);
+        match performance.axis {
+            TimeAxis::Hour => self.hours(columns),
+            TimeAxis::Weekday => self.weekdays(columns),
+        }
+    }
+
+    pub fn allowed_hours(&self) -> Vec<u32> {
+        (0..24).filter(|h| self.hours[*h as usize]).collect()
+    }
+
+    pub fn allowed_weekdays(&self) -> Vec<u32> {
+        (0..7).filter(|d| self.weekdays[*d as usize]).collect()
+    }
+
+    pub fn is_allowed(&self, time: DateTime<Utc>) -> bool {
+        self.hours[time.hour() as usize]
+            && self.weekdays[time.weekday().num_days_from_monday() as usize]
+    }
+}
+
+fn allowed<const N: usize>(
+    columns: impl IntoIterator<Item = u32>,
+    kind: &str,
+) -> PocketResult<[bool; N]> {
+    let mut allowed = [false; N];
+    for column in columns {
+        match allowed.get_mut(column as usize) {
+            Some(allowed) => *allowed = true,
+            None => {
+                return Err(PocketOptionError::Unallowed(format!(
+                    "invalid {kind} {column}, it must be between 0 and {}",
+                    N - 1
+                )));
+            }
+        }
+    }
+    Ok(allowed)
+}
+
+impl RiskRule for TradingHours {
+    fn name(&self) -> &str {
+        "TradingHours"
+    }
+
+    fn check(&self, intent: &TradeIntent) -> Result<(), String> {
+        if self.is_allowed(intent.created_at) {
+            return Ok(());
+        }
+        Err(format!(
+            "trading isn't allowed on {} (UTC), the allowed hours are {:?} and the allowed weekdays {:?}",
+            intent.created_at.format("%a %H:%M"),
+            self.allowed_hours(),
+            self.allowed_weekdays()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn intent_at(asset: &str, timestamp: i64) -> TradeIntent {
+        let mut intent = TradeIntent::new(asset, Action::Call, 1.0, 60);
+        intent.created_at = DateTime::from_timestamp(timestamp, 0).unwrap();
+        intent
+    }
+
+    #[test]
+    fn test_cluster_guard_per_candle() {
+        let manager = RiskManager::default();
+        manager.add_rule(ClusterGuard::per_candle(1, Duration::from_secs(60)));
+        assert!(manager.check(&intent_at("EURUSD_otc", 120)).is_ok());
+        assert!(manager.check(&intent_at("EURUSD_otc", 150)).is_err());
+        // Other assets aren't affected
+        assert!(manager.check(&intent_at("GBPUSD_otc", 150)).is_ok());
+        // Next candle
+        assert!(manager.check(&intent_at("EURUSD_otc", 180)).is_ok());
+    }
+
+    #[test]
+    fn test_cluster_guard_sliding_and_rollback() {
+        let manager = RiskManager::default();
+        manager.add_rule(ClusterGuard::new(2, Duration::from_secs(30), false));
+        let first = intent_at("EURUSD_otc", 100);
+        assert!(manager.check(&first).is_ok());
+        assert!(manager.check(&intent_at("EURUSD_otc", 110)).is_ok());
+        assert!(manager.check(&intent_at("EURUSD_otc", 125)).is_err());
+        // The first trade failed to be placed, so it doesn't count
+        manager.rollback(&first);
+        assert!(manager.check(&intent_at("EURUSD_otc", 125)).is_ok());
+        assert!(manager.check(&intent_at("EURUSD_otc", 145)).is_ok());
+    }
+
+    #[test]
+    fn test_drawdown_breaker() -> anyhow::Result<()> {
+        assert!(DrawdownBreaker::new(1.5).is_err());
+        let breaker = Arc::new(DrawdownBreaker::new(0.2)?);
+        let manager = RiskManager::default();
+        manager.add_shared_rule(breaker.clone());
+        assert!(!breaker.update_balance(100.0));
+        assert!(!breaker.update_balance(150.0));
+        assert!(!breaker.update_balance(125.0));
+        assert!(manager.check(&intent_at("EURUSD_otc", 100)).is_ok());
+        // 150 -> 119 is a 20.7% drawdown
+        assert!(breaker.update_balance(119.0));
+        assert!(!breaker.update_balance(110.0));
+        assert!(breaker.is_tripped());
+        assert!(manager.check(&intent_at("EURUSD_otc", 100)).is_err());
+        // Recovering doesn't re-arm the breaker
+        breaker.update_balance(150.0);
+        assert!(manager.check(&intent_at("EURUSD_otc", 100)).is_err());
+        breaker.reset();
+        assert_eq!(breaker.state().peak, Some(150.0));
+        assert!(manager.check(&intent_at("EURUSD_otc", 100)).is_ok());
+        manager.remove_rule("DrawdownBreaker");
+        assert!(manager.rules().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_trading_hours() -> anyhow::Result<()> {
+        assert!(TradingHours::new().hours([24]).is_err());
+        let manager = RiskManager::default();
+        // 1970-01-01 was a Thursday
+        manager.add_rule(TradingHours::new().hours([8, 9])?.weekdays(0..5)?);
+        assert!(manager.check(&intent_at("EURUSD_otc", 8 * 3600)).is_ok());
+        assert!(manager.check(&intent_at("EURUSD_otc", 10 * 3600)).is_err());
+        // Saturday
+        assert!(
+            manager
+                .check(&intent_at("EURUSD_otc", 2 * 86400 + 8 * 3600))
+                .is_err()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,219 @@
+//! Orders queued in the client and placed at a timestamp of the server clock, for the entries that
+//! must happen at an exact time (signals given for a server time) without the jitter of a sleep in
+//! the calling code.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::task::AbortHandle;
+use uuid::Uuid;
+
+use super::{
+    error::{PocketOptionError, PocketResult},
+    types::order::Direction,
+};
+
+/// Time before the execution the clock offset is estimated again, the order waits for the
+/// execution time with the latest estimation.
+pub const SCHEDULE_REFINE_WINDOW: Duration = Duration::from_secs(1);
+
+/// State of a `ScheduledOrder`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ScheduledOrderState {
+    /// Waiting for the execution time.
+    Scheduled,
+    /// The execution time was reached, the trade is being sent.
+    Sending,
+    /// The trade was placed, `delay` is the time in milliseconds between the execution time and the
+    /// start of the placement, on the estimated server clock.
+    Placed {
+        trade_id: Uuid,
+        delay: f64,
+    },
+    Failed {
+        error: String,
+    },
+    Cancelled,
+}
+
+/// Trade placed at `execute_at` (on the server clock), as returned by `PocketOption::buy_at_time`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledOrder {
+    pub id: Uuid,
+    pub asset: String,
+    pub direction: Direction,
+    pub amount: f64,
+    /// Expiry of the trade in seconds.
+    pub time: u32,
+    pub execute_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub state: ScheduledOrderState,
+}
+
+impl ScheduledOrder {
+    /// Fails if `execute_at` is already past on the server clock (`server_now`).
+    pub fn new(
+        asset: impl ToString,
+        direction: Direction,
+        amount: f64,
+        time: u32,
+        execute_at: DateTime<Utc>,
+        server_now: DateTime<Utc>,
+    ) -> PocketResult<Self> {
+        if execute_at <= server_now {
+            return Err(PocketOptionError::Unallowed(format!(
+                "the execution time {execute_at} is already past, the server time is {server_now}"
+            )));
+        }
+        Ok(Self {
+            id: Uuid::new_v4(),
+            asset: asset.to_string(),
+            direction,
+            amount,
+            time,
+            execute_at,
+            created_at: Utc::now(),
+            state: ScheduledOrderState::Scheduled,
+        })
+    }
+
+    /// Time left before the execution for a local clock ahead of the server clock by
+    /// `clock_offset`, zero once the execution time is reached.
+    pub fn wait_time(&self, clock_offset: TimeDelta, local_now: DateTime<Utc>) -> Duration {
+        (self.execute_at + clock_offset - local_now)
+            .to_std()
+            .unwrap_or_default()
+    }
+}
+
+/// Scheduled orders of a client, shared by its clones and the tasks waiting for the execution times.
+#[derive(Default, Clone)]
+pub struct ScheduledOrders {
+    orders: Arc<Mutex<Entries>>,
+}
+
+type Entries = HashMap<Uuid, (ScheduledOrder, Option<AbortHandle>)>;
+
+impl ScheduledOrders {
+    fn lock(&self) -> MutexGuard<'_, Entries> {
+        self.orders.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn insert(&self, order: ScheduledOrder) {
+        self.lock().insert(order.id, (order, None));
+    }
+
+    /// Sets the task waiting for the execution time of the order, aborted if the order is cancelled.
+    pub fn set_task(&self, id: Uuid, task: AbortHandle) {
+        let mut orders = self.lock();
+        if let Some((_, handle)) = orders.get_mut(&id) {
+            *handle = Some(task);
+        }
+    }
+
+    /// Every order, by execution time.
+    pub fn list(&self) -> Vec<ScheduledOrder> {
+        let mut orders: Vec<ScheduledOrder> = self
+            .lock()
+            .values()
+            .map(|(order, _)| order.clone())
+            .collect();
+        orders.sort_by_key(|order| order.execute_at);
+        orders
+    }
+
+    /// Marks a scheduled order as being sent, returns false if it was cancelled.
+    pub fn start(&self, id: Uuid) -> bool {
+        match self.lock().get_mut(&id) {
+            Some((order, _)) if order.state == ScheduledOrderState::Scheduled => {
+                order.state = ScheduledOrderState::Sending;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Sets the final state of an order once its trade was sent.
+    pub fn finish(&self, id: Uuid, state: ScheduledOrderState) {
+        if let Some((order, _)) = self.lock().get_mut(&id) {
+            order.state = state;
+        }
+    }
+
+    /// Cancels an order waiting for its execution time. Fails if the order doesn't exist or was
+    /// already sent.
+    pub fn cancel(&self, id: Uuid) -> PocketResult<ScheduledOrder> {
+        let mut orders = self.lock();
+        let (order, task) = orders
+            .get_mut(&id)
+            .ok_or_else(|| PocketOptionError::Unallowed(format!("no scheduled order '{id}'")))?;
+        if order.state != ScheduledOrderState::Scheduled {
+            return Err(PocketOptionError::Unallowed(format!(
+                "scheduled order '{id}' can't be cancelled, it's {:?}",
+                order.state
+            )));
+        }
+        order.state = ScheduledOrderState::Cancelled;
+        if let Some(task) = task.take() {
+            task.abort();
+        }
+        Ok(order.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::pocketoption::types::order::Action;
+
+    #[test]
+    fn test_scheduled_orders() -> anyhow::Result<()> {
+        let now = Utc::now();
+        let execute_at = now + TimeDelta::seconds(10);
+        assert!(ScheduledOrder::new("EURUSD_otc", Action::Call, 1.0, 60, now, now).is_err());
+        let order = ScheduledOrder::new("EURUSD_otc", Action::Call, 1.0, 60, execute_at, now)?;
+
+        // The local clock is 250ms ahead of the server clock, the order waits 250ms longer
+        let offset = TimeDelta::milliseconds(250);
+        assert_eq!(order.wait_time(offset, now), Duration::from_millis(10_250));
+        assert_eq!(order.wait_time(-offset, now), Duration::from_millis(9_750));
+        assert_eq!(
+            order.wait_time(offset, now + TimeDelta::seconds(11)),
+            Duration::ZERO
+        );
+
+        let orders = ScheduledOrders::default();
+        let later =
+            ScheduledOrder::new("EURUSD_otc", Action::Put, 1.0, 60, execute_at + offset, now)?;
+        orders.insert(later.clone());
+        orders.insert(order.clone());
+        assert_eq!(orders.list()[0].id, order.id);
+
+        assert!(orders.start(order.id));
+        assert!(orders.cancel(order.id).is_err());
+        assert_eq!(
+            orders.cancel(later.id)?.state,
+            ScheduledOrderState::Cancelled
+        );
+        assert!(!orders.start(later.id));
+
+        orders.finish(
+            order.id,
+            ScheduledOrderState::Failed {
+                error: "closed".into(),
+            },
+        );
+        let json = serde_json::to_value(orders.list())?;
+        assert_eq!(json[0]["status"], "failed");
+        assert_eq!(json[1]["status"], "cancelled");
+        Ok(())
+    }
+}
@@ -0,0 +1,208 @@
+//! Rounding rules of the stakes per account currency, applied before an order is sent since the
+//! broker rejects the amounts that aren't a multiple of its step or are out of its limits.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::error::{PocketOptionError, PocketResult};
+
+/// Decimals the rounded stakes are cleaned to, so `0.1 + 0.2` isn't sent as `0.30000000000000004`.
+const STAKE_DECIMALS: i32 = 8;
+
+/// How a stake that isn't a multiple of the step is rounded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StakeRounding {
+    #[default]
+    Nearest,
+    /// Never stakes more than requested.
+    Down,
+    Up,
+}
+
+/// Step, minimum and maximum of the stakes in a currency.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StakeRule {
+    /// Stakes are rounded to a multiple of the step (e.g. `0.01` for cents, `1.0` for whole units).
+    pub step: f64,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub rounding: StakeRounding,
+}
+
+impl Default for StakeRule {
+    fn default() -> Self {
+        Self {
+            step: 0.01,
+            min: None,
+            max: None,
+            rounding: StakeRounding::default(),
+        }
+    }
+}
+
+impl StakeRule {
+    pub fn new(step: f64) -> Self {
+        Self {
+            step,
+            ..Default::default()
+        }
+    }
+
+    pub fn min(mut self, min: f64) -> Self {
+        self.min = Some(min);
+        self
+    }
+
+    pub fn max(mut self, max: f64) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    pub fn rounding(mut self, rounding: StakeRounding) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if !self.step.is_finite() || self.step <= 0.0 {
+            return Err(format!("the step must be positive, got {}", self.step));
+        }
+        if let Some((min, max)) = self.min.zip(self.max).filter(|(min, max)| min > max) {
+            return Err(format!("the minimum {min} is over the maximum {max}"));
+        }
+        Ok(())
+    }
+
+    /// Rounds `amount` to the step, returning the reason of the rejection if it's out of limits.
+    pub fn apply(&self, amount: f64) -> Result<f64, String> {
+        if !amount.is_finite() || amount <= 0.0 {
+            return Err("the stake must be a positive number".to_string());
+        }
+        // The tolerance keeps amounts like 0.29999999999999998 on the step they represent
+        let steps = amount / self.step;
+        let steps = match self.rounding {
+            StakeRounding::Nearest => steps.round(),
+            StakeRounding::Down => (steps + 1e-9).floor(),
+            StakeRounding::Up => (steps - 1e-9).ceil(),
+        };
+        let scale = 10f64.powi(STAKE_DECIMALS);
+        let stake = (steps * self.step * scale).round() / scale;
+        if let Some(min) = self.min.filter(|min| stake < *min) {
+            return Err(format!("the minimum stake is {min}"));
+        }
+        if let Some(max) = self.max.filter(|max| stake > *max) {
+            return Err(format!("the maximum stake is {max}"));
+        }
+        if stake <= 0.0 {
+            return Err(format!("it rounds to 0 with a step of {}", self.step));
+        }
+        Ok(stake)
+    }
+}
+
+/// Stake rules of the client, the rule of the account currency is applied to every order.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StakeRules {
+    /// Currency of the account, learnt from the deals of the account if it isn't set.
+    pub currency: Option<String>,
+    /// Rules by currency code (e.g. "USD").
+    pub currencies: HashMap<String, StakeRule>,
+    /// Rule of the currencies without their own rule.
+    pub default: StakeRule,
+}
+
+impl StakeRules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn currency(mut self, currency: impl ToString) -> Self {
+        self.currency = Some(currency.to_string().to_uppercase());
+        self
+    }
+
+    /// Sets the rule of `currency`, replacing the previous one.
+    pub fn rule(mut self, currency: impl ToString, rule: StakeRule) -> Self {
+        self.currencies
+            .insert(currency.to_string().to_uppercase(), rule);
+        self
+    }
+
+    pub fn default_rule(mut self, rule: StakeRule) -> Self {
+        self.default = rule;
+        self
+    }
+
+    /// Checks that every rule is valid.
+    pub fn validate(&self) -> PocketResult<()> {
+        std::iter::once(("default", &self.default))
+            .chain(self.currencies.iter().map(|(c, r)| (c.as_str(), r)))
+            .try_for_each(|(currency, rule)| {
+                rule.validate().map_err(|reason| {
+                    PocketOptionError::Unallowed(format!(
+                        "invalid stake rule for '{currency}', {reason}"
+                    ))
+                })
+            })
+    }
+
+    /// Rule applied to the stakes in `currency`.
+    pub fn rule_for(&self, currency: Option<&str>) -> &StakeRule {
+        currency
+            .and_then(|c| self.currencies.get(&c.to_uppercase()))
+            .unwrap_or(&self.default)
+    }
+
+    /// Rounds `amount` with the rule of `currency`, failing with an `InvalidStake` error if it's
+    /// out of the limits of the rule.
+    pub fn apply(&self, amount: f64, currency: Option<&str>) -> PocketResult<f64> {
+        self.rule_for(currency)
+            .apply(amount)
+            .map_err(|reason| PocketOptionError::InvalidStake {
+                amount,
+                currency: currency.unwrap_or("account currency").to_string(),
+                reason,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stake_rules() -> anyhow::Result<()> {
+        let rules = StakeRules::new()
+            .rule("usd", StakeRule::new(0.01).min(1.0).max(1000.0))
+            .rule(
+                "RUB",
+                StakeRule::new(1.0).min(50.0).rounding(StakeRounding::Down),
+            );
+        rules.validate()?;
+
+        assert_eq!(rules.apply(1.005_1, Some("USD"))?, 1.01);
+        assert_eq!(rules.apply(0.1 + 0.2 + 1.0, Some("USD"))?, 1.3);
+        assert!(rules.apply(0.5, Some("USD")).is_err());
+        assert!(rules.apply(1500.0, Some("USD")).is_err());
+        assert_eq!(rules.apply(120.9, Some("rub"))?, 120.0);
+        assert!(rules.apply(49.9, Some("RUB")).is_err());
+        // The default rule only rounds to cents
+        assert_eq!(rules.apply(0.333, None)?, 0.33);
+        assert!(rules.apply(0.001, None).is_err());
+        assert!(rules.apply(f64::NAN, None).is_err());
+
+        assert!(
+            StakeRules::new()
+                .default_rule(StakeRule::new(0.0))
+                .validate()
+                .is_err()
+        );
+        let parsed: StakeRules = serde_json::from_str(r#"{"currencies": {"EUR": {"step": 0.5}}}"#)?;
+        assert_eq!(parsed.apply(2.3, Some("EUR"))?, 2.5);
+        Ok(())
+    }
+}
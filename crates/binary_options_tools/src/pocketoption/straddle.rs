@@ -0,0 +1,106 @@
+//! Straddles: a call and a put of the same amount and expiry on the same asset, placed together
+//! and linked in the journal. A straddle profits when the price moves far enough in either
+//! direction before the expiry, typically around news releases.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::types::order::Deal;
+
+/// Tag of the trades of a straddle in the journal.
+pub const STRADDLE_TAG: &str = "straddle";
+
+/// Legs of a straddle once both were placed, returned by `PocketOption::straddle`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Straddle {
+    /// Journal group linking both legs.
+    pub group: Uuid,
+    pub asset: String,
+    pub amount: f64,
+    pub expiry: u32,
+    pub call: Deal,
+    pub put: Deal,
+}
+
+/// Combined result of both legs of a straddle, sent once both closed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StraddleResult {
+    pub group: Uuid,
+    pub asset: String,
+    pub call: Deal,
+    pub put: Deal,
+    /// Profit of both legs together, positive if the winning leg paid more than the losing leg cost.
+    pub profit: f64,
+    /// "win", "loss" or "draw", from the combined profit.
+    pub result: String,
+}
+
+impl StraddleResult {
+    pub fn new(group: Uuid, call: Deal, put: Deal) -> Self {
+        let profit = ((call.profit + put.profit) * 100.0).round() / 100.0;
+        let result = if profit > 0.0 {
+            "win"
+        } else if profit < 0.0 {
+            "loss"
+        } else {
+            "draw"
+        };
+        Self {
+            group,
+            asset: call.asset.clone(),
+            call,
+            put,
+            profit,
+            result: result.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn deal(command: i32, profit: f64) -> Deal {
+        serde_json::from_value(json!({
+            "id": Uuid::new_v4(),
+            "openTime": "2024-12-04 23:58:48",
+            "closeTime": "2024-12-04 23:59:48",
+            "openTimestamp": 1733356728,
+            "closeTimestamp": 1733356788,
+            "uid": 1,
+            "isDemo": 1,
+            "amount": 10.0,
+            "profit": profit,
+            "percentProfit": 92,
+            "percentLoss": 100,
+            "openPrice": 1.1,
+            "copyTicket": "",
+            "closePrice": 1.2,
+            "command": command,
+            "asset": "EURUSD_otc",
+            "openMs": 601,
+            "optionType": 100,
+            "currency": "USD"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_straddle_result() {
+        let group = Uuid::new_v4();
+        let result = StraddleResult::new(group, deal(0, 9.2), deal(1, -10.0));
+        assert_eq!(result.profit, -0.8);
+        assert_eq!(result.result, "loss");
+        assert_eq!(result.asset, "EURUSD_otc");
+        assert_eq!(result.group, group);
+
+        let result = StraddleResult::new(group, deal(0, 19.2), deal(1, -10.0));
+        assert_eq!((result.profit, result.result.as_str()), (9.2, "win"));
+        assert_eq!(
+            StraddleResult::new(group, deal(0, 0.0), deal(1, 0.0)).result,
+            "draw"
+        );
+    }
+}
@@ -0,0 +1,378 @@
+//! Predefined trades (`OrderTemplate`) that can be saved under a name and executed later, so
+//! operational tooling can trigger them safely (e.g. "execute template news_straddle_eurusd")
+//! instead of building the orders by hand.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use binary_options_tools_core::error::BinaryOptionsToolsError;
+use chrono::{DateTime, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{
+    error::{PocketOptionError, PocketResult},
+    risk::TradeIntent,
+    types::order::{Action, Deal},
+    utils::expiry::nearest_expiry,
+};
+
+/// How the stake of every trade of a template is computed.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AmountRule {
+    /// Same stake for every execution.
+    Fixed { amount: f64 },
+    /// Percentage of the balance at the time of the execution, clamped to `min` and `max`.
+    BalancePercent {
+        percent: f64,
+        #[serde(default)]
+        min: Option<f64>,
+        #[serde(default)]
+        max: Option<f64>,
+    },
+}
+
+impl AmountRule {
+    pub fn resolve(&self, balance: f64) -> PocketResult<f64> {
+        let amount = match self {
+            Self::Fixed { amount } => *amount,
+            Self::BalancePercent { percent, min, max } => {
+                let amount = balance * percent / 100.0;
+                let amount = min.map_or(amount, |min| amount.max(min));
+                max.map_or(amount, |max| amount.min(max))
+            }
+        };
+        let amount = (amount * 100.0).round() / 100.0;
+        if !amount.is_finite() || amount <= 0.0 {
+            return Err(PocketOptionError::Unallowed(format!(
+                "the stake of a trade must be a positive number, got {amount}"
+            )));
+        }
+        Ok(amount)
+    }
+}
+
+/// How the expiry of every trade of a template is computed.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ExpiryRule {
+    /// Exact expiry in seconds.
+    Fixed { seconds: u32 },
+    /// Expiry accepted by the server for the asset closest to `seconds`.
+    Nearest { seconds: u32 },
+    /// Until the close of the current candle of `period` seconds, or of the next one if less than
+    /// `min_seconds` remain.
+    CandleClose {
+        period: u32,
+        #[serde(default)]
+        min_seconds: u32,
+    },
+}
+
+impl ExpiryRule {
+    /// Resolves the expiry in seconds, `valid` are the expiries accepted for the asset and `now` the server time.
+    pub fn resolve(&self, valid: &[u32], now: DateTime<Utc>) -> PocketResult<u32> {
+        match self {
+            Self::Fixed { seconds } => Ok(*seconds),
+            Self::Nearest { seconds } => nearest_expiry(valid, *seconds)
+                .ok_or_else(|| PocketOptionError::EmptyArrayError("expiries".to_string())),
+            Self::CandleClose {
+                period,
+                min_seconds,
+            } => {
+                if *period == 0 {
+                    return Err(PocketOptionError::Unallowed(
+                        "the period of a candle must be greater than 0".to_string(),
+                    ));
+                }
+                let elapsed = now.timestamp().rem_euclid(*period as i64) as u32;
+                let mut remaining = period - elapsed;
+                while remaining < *min_seconds {
+                    remaining += period;
+                }
+                Ok(remaining)
+            }
+        }
+    }
+}
+
+/// Condition checked before a template is executed, the execution is rejected if any fails.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TemplateCheck {
+    /// Payout of the asset must be at least `percent`.
+    MinPayout { percent: i32 },
+    /// Stake of every trade must not exceed `amount`.
+    MaxAmount { amount: f64 },
+    /// Can only be executed on a demo account.
+    DemoOnly,
+    /// Can only be executed between the `start` and `end` hours (UTC), `end` excluded.
+    Hours { start: u32, end: u32 },
+}
+
+impl TemplateCheck {
+    fn check(&self, context: &TemplateContext, amount: f64) -> Result<(), String> {
+        match self {
+            Self::MinPayout { percent } => match context.payout {
+                Some(payout) if payout >= *percent => Ok(()),
+                Some(payout) => Err(format!(
+                    "payout is {payout}%, under the minimum of {percent}%"
+                )),
+                None => Err("the payout of the asset is unknown".to_string()),
+            },
+            Self::MaxAmount { amount: max } if amount > *max => {
+                Err(format!("stake of {amount} is over the maximum of {max}"))
+            }
+            Self::MaxAmount { .. } => Ok(()),
+            Self::DemoOnly if !context.demo => {
+                Err("it can only be executed on a demo account".to_string())
+            }
+            Self::DemoOnly => Ok(()),
+            Self::Hours { start, end } => {
+                let hour = context.now.hour();
+                let allowed = if start <= end {
+                    (*start..*end).contains(&hour)
+                } else {
+                    hour >= *start || hour < *end
+                };
+                if allowed {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "it can only be executed between {start}h and {end}h UTC"
+                    ))
+                }
+            }
+        }
+    }
+}
+
+/// State of the account and the asset a template is resolved against.
+#[derive(Debug, Clone)]
+pub struct TemplateContext {
+    pub balance: f64,
+    pub payout: Option<i32>,
+    pub valid_expiries: Vec<u32>,
+    pub demo: bool,
+    pub now: DateTime<Utc>,
+}
+
+/// A predefined trade, or group of trades placed together (e.g. a straddle with a call and a put).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OrderTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub asset: String,
+    /// Direction of every trade placed by the template.
+    pub actions: Vec<Action>,
+    pub amount: AmountRule,
+    pub expiry: ExpiryRule,
+    #[serde(default)]
+    pub checks: Vec<TemplateCheck>,
+    /// Risk tags recorded in the journal with every trade of the template.
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl OrderTemplate {
+    /// Checks the template against the context and returns the trades to place.
+    pub fn resolve(&self, context: &TemplateContext) -> PocketResult<Vec<TradeIntent>> {
+        let rejected = |reason: String| PocketOptionError::TemplateRejected {
+            template: self.name.clone(),
+            reason,
+        };
+        if self.actions.is_empty() {
+            return Err(rejected("it doesn't place any trade".to_string()));
+        }
+        let amount = self.amount.resolve(context.balance)?;
+        let time = self.expiry.resolve(&context.valid_expiries, context.now)?;
+        for check in &self.checks {
+            check.check(context, amount).map_err(rejected)?;
+        }
+        Ok(self
+            .actions
+            .iter()
+            .map(|action| TradeIntent::new(&self.asset, action.clone(), amount, time))
+            .collect())
+    }
+
+    /// Tags recorded in the journal with the trades of the template.
+    pub fn journal_tags(&self) -> Vec<String> {
+        let mut tags = vec![format!("template:{}", self.name)];
+        tags.extend(self.tags.iter().cloned());
+        tags
+    }
+}
+
+/// Trades placed by an execution of a template.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateExecution {
+    pub template: String,
+    /// Journal group of the trades, only set when the template placed more than one trade.
+    pub group: Option<Uuid>,
+    pub trades: Vec<Deal>,
+}
+
+/// Serializes the updates of the template files, so two templates saved at the same time don't
+/// overwrite each other.
+static TEMPLATES_LOCK: Mutex<()> = Mutex::new(());
+
+/// File backed store of order templates, indexed by their name.
+/// The file is rewritten atomically so a crash never leaves it half written.
+pub struct TemplateStore {
+    path: PathBuf,
+    templates: BTreeMap<String, OrderTemplate>,
+}
+
+impl TemplateStore {
+    pub fn load(path: impl AsRef<Path>) -> PocketResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        let templates = Self::read(&path)?;
+        Ok(Self { path, templates })
+    }
+
+    /// Saves a template, replacing the one with the same name if any.
+    pub fn insert(&mut self, template: OrderTemplate) -> PocketResult<()> {
+        self.update(|templates| {
+            templates.insert(template.name.clone(), template);
+        })
+    }
+
+    pub fn remove(&mut self, name: &str) -> PocketResult<Option<OrderTemplate>> {
+        self.update(|templates| templates.remove(name))
+    }
+
+    pub fn get(&self, name: &str) -> PocketResult<&OrderTemplate> {
+        self.templates
+            .get(name)
+            .ok_or_else(|| PocketOptionError::Unallowed(format!("template '{name}' doesn't exist")))
+    }
+
+    pub fn templates(&self) -> &BTreeMap<String, OrderTemplate> {
+        &self.templates
+    }
+
+    fn read(path: &Path) -> PocketResult<BTreeMap<String, OrderTemplate>> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(e) => Err(BinaryOptionsToolsError::from(e).into()),
+        }
+    }
+
+    /// Applies a change to the templates of the file, re-read under the lock so the templates saved
+    /// by the other stores since this one was loaded are kept.
+    fn update<T>(
+        &mut self,
+        change: impl FnOnce(&mut BTreeMap<String, OrderTemplate>) -> T,
+    ) -> PocketResult<T> {
+        let _guard = TEMPLATES_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        self.templates = Self::read(&self.path)?;
+        let result = change(&mut self.templates);
+        self.save()?;
+        Ok(result)
+    }
+
+    fn save(&self) -> PocketResult<()> {
+        // Unique per save, so another process saving the same templates doesn't write to it
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(format!(".{}.tmp", Uuid::new_v4()));
+        std::fs::write(&tmp, serde_json::to_string_pretty(&self.templates)?)
+            .map_err(BinaryOptionsToolsError::from)?;
+        std::fs::rename(tmp, &self.path).map_err(BinaryOptionsToolsError::from)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn straddle() -> OrderTemplate {
+        serde_json::from_str(
+            r#"{
+                "name": "news_straddle_eurusd",
+                "asset": "EURUSD_otc",
+                "actions": ["call", "put"],
+                "amount": {"kind": "balance_percent", "percent": 1.0, "min": 1.0, "max": 20.0},
+                "expiry": {"kind": "candle_close", "period": 60, "min_seconds": 15},
+                "checks": [{"kind": "min_payout", "percent": 80}, {"kind": "hours", "start": 22, "end": 6}],
+                "tags": ["news"]
+            }"#,
+        )
+        .expect("Valid template")
+    }
+
+    fn context() -> TemplateContext {
+        TemplateContext {
+            balance: 1234.0,
+            payout: Some(92),
+            valid_expiries: vec![60, 120, 300],
+            demo: false,
+            now: Utc.with_ymd_and_hms(2024, 11, 28, 23, 0, 50).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_resolve_template() -> anyhow::Result<()> {
+        let intents = straddle().resolve(&context())?;
+        assert_eq!(intents.len(), 2);
+        // 10 seconds left in the current candle, under the minimum so it expires at the next close
+        assert!(intents.iter().all(|i| i.amount == 12.34 && i.time == 70));
+        assert_eq!(
+            straddle().journal_tags(),
+            ["template:news_straddle_eurusd", "news"]
+        );
+
+        let low_payout = TemplateContext {
+            payout: Some(70),
+            ..context()
+        };
+        assert!(matches!(
+            straddle().resolve(&low_payout),
+            Err(PocketOptionError::TemplateRejected { .. })
+        ));
+        let closed_hours = TemplateContext {
+            now: Utc.with_ymd_and_hms(2024, 11, 28, 12, 0, 0).unwrap(),
+            ..context()
+        };
+        assert!(straddle().resolve(&closed_hours).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_template_store() -> anyhow::Result<()> {
+        let path = std::env::temp_dir().join(format!("templates-{}.json", Uuid::new_v4()));
+        let mut store = TemplateStore::load(&path)?;
+        store.insert(straddle())?;
+
+        let mut store = TemplateStore::load(&path)?;
+        assert_eq!(store.get("news_straddle_eurusd")?, &straddle());
+        assert!(store.remove("news_straddle_eurusd")?.is_some());
+        assert!(TemplateStore::load(&path)?.templates().is_empty());
+
+        // Stores loaded before each other's saves keep both templates
+        let (mut first, mut second) = (TemplateStore::load(&path)?, TemplateStore::load(&path)?);
+        let other = OrderTemplate {
+            name: "other".to_string(),
+            ..straddle()
+        };
+        first.insert(straddle())?;
+        second.insert(other)?;
+        let names: Vec<_> = TemplateStore::load(&path)?
+            .templates()
+            .keys()
+            .cloned()
+            .collect();
+        assert_eq!(names, ["news_straddle_eurusd", "other"]);
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+}
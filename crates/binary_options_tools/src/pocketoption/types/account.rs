@@ -0,0 +1,116 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Kind of an administrative / account level message sent by the server.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountEventKind {
+    Bonus,
+    MarginCall,
+    Restriction,
+    Maintenance,
+    Notice,
+    /// Sent by the client itself when one of its circuit breakers trips (e.g. the drawdown breaker).
+    CircuitBreaker,
+    /// Sent by the client itself when the message rate of a feed collapses or recovers, see
+    /// `feed_health`.
+    FeedAnomaly,
+}
+
+impl AccountEventKind {
+    /// Classifies a server event by its name, returns `None` for the events that aren't account events.
+    pub fn from_event(event: &str) -> Option<Self> {
+        let event = event.to_lowercase();
+        let contains = |keywords: &[&str]| keywords.iter().any(|k| event.contains(k));
+        if contains(&["maintenance", "technical"]) {
+            Some(Self::Maintenance)
+        } else if contains(&["bonus", "cashback", "promo"]) {
+            Some(Self::Bonus)
+        } else if contains(&["margin"]) {
+            Some(Self::MarginCall)
+        } else if contains(&["restrict", "block", "ban", "suspend", "verification"]) {
+            Some(Self::Restriction)
+        } else if contains(&["notification", "notice", "announcement", "alert", "admin"]) {
+            Some(Self::Notice)
+        } else {
+            None
+        }
+    }
+}
+
+/// Administrative message about the account (bonus credited, account restricted, maintenance...).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountEvent {
+    pub kind: AccountEventKind,
+    /// Name of the event as sent by the server.
+    pub event: String,
+    pub message: Option<String>,
+    pub amount: Option<f64>,
+    /// Full payload of the event.
+    pub data: Value,
+    pub received_at: DateTime<Utc>,
+}
+
+impl AccountEvent {
+    pub fn new(event: impl ToString, data: Value) -> Option<Self> {
+        let event = event.to_string();
+        let kind = AccountEventKind::from_event(&event)?;
+        let message = ["message", "text", "msg", "title", "description"]
+            .iter()
+            .find_map(|key| data.get(key)?.as_str().map(String::from));
+        let amount =
+            ["amount", "bonus", "sum", "value"]
+                .iter()
+                .find_map(|key| match data.get(key)? {
+                    Value::Number(number) => number.as_f64(),
+                    Value::String(number) => number.parse().ok(),
+                    _ => None,
+                });
+        Some(Self {
+            kind,
+            event,
+            message,
+            amount,
+            data,
+            received_at: Utc::now(),
+        })
+    }
+
+    /// Parses a `42["event", data]` text frame, returns `None` if it isn't an account event.
+    pub fn from_text(text: &str) -> Option<Self> {
+        let (event, data): (String, Value) = serde_json::from_str(text.strip_prefix("42")?).ok()?;
+        Self::new(event, data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_account_events() {
+        let event = AccountEvent::from_text(
+            r#"42["bonus/credited",{"amount":"25.5","message":"Welcome bonus"}]"#,
+        )
+        .unwrap();
+        assert_eq!(event.kind, AccountEventKind::Bonus);
+        assert_eq!(event.amount, Some(25.5));
+        assert_eq!(event.message.as_deref(), Some("Welcome bonus"));
+
+        let event =
+            AccountEvent::new("technicalMaintenance", json!({"text": "Back soon"})).unwrap();
+        assert_eq!(event.kind, AccountEventKind::Maintenance);
+        assert_eq!(
+            AccountEventKind::from_event("accountRestricted"),
+            Some(AccountEventKind::Restriction)
+        );
+        assert!(AccountEvent::from_text(r#"42["updateBalance",{"balance":1}]"#).is_none());
+        assert!(
+            AccountEvent::from_text(r#"451-["notifications/load",{"_placeholder":true,"num":0}]"#)
+                .is_none()
+        );
+    }
+}
@@ -13,7 +13,7 @@ use binary_options_tools_core::{
     general::{config::Config, send::SenderMessage, traits::WCallback, types::Data},
 };
 
-use super::{base::ChangeSymbol, data::PocketData, order::SuccessCloseOrder};
+use super::{data::PocketData, order::SuccessCloseOrder, subscriptions::SubscriptionAction};
 
 #[derive(Clone)]
 pub struct PocketCallback;
@@ -23,12 +23,13 @@ impl PocketCallback {
         data: &Data<PocketData, WebSocketMessage>,
         sender: &SenderMessage,
     ) -> BinaryOptionsResult<()> {
-        for asset in data.stream_assets().await {
+        // Only the assets holding a slot, the queued ones are still waiting for one
+        for asset in data.subscriptions().resubscribe() {
             // Send 3 messages, 1: change symbol, 2: unsubscribe symbol, 3: subscribe symbol
             debug!("Updating asset: {asset}");
-            sender.send(WebSocketMessage::ChangeSymbol(ChangeSymbol::new(asset.to_string(), 1))).await?;
-            sender.send(WebSocketMessage::Unsubfor(asset.to_string())).await?;
-            sender.send(WebSocketMessage::Subfor(asset.to_string())).await?;
+            for message in SubscriptionAction::Subscribe(asset).messages() {
+                sender.send(message).await?;
+            }
             sleep(Duration::from_secs(1)).await;
         }
         Ok(())
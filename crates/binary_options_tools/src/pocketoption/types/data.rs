@@ -1,46 +1,101 @@
 use std::{
     collections::{HashMap, HashSet},
-    sync::Arc,
+    sync::{Arc, RwLock},
     time::Duration,
 };
 
-use async_channel::{Receiver, Sender, bounded};
+use async_channel::{Sender, bounded};
 use async_trait::async_trait;
-use chrono::Utc;
-use tokio::sync::Mutex;
+use chrono::{DateTime, Utc};
+use tokio::sync::{Mutex, oneshot};
 use tracing::{info, warn};
 use uuid::Uuid;
 
 use binary_options_tools_core::{
-    constants::MAX_CHANNEL_CAPACITY, error::BinaryOptionsResult, general::traits::DataHandler,
+    constants::MAX_CHANNEL_CAPACITY,
+    error::BinaryOptionsResult,
+    general::{stream::RecieverStream, traits::DataHandler},
+    utils::audit::AUDIT_TARGET,
 };
 
 use crate::pocketoption::{
-    error::PocketResult, parser::message::WebSocketMessage, ws::stream::StreamAsset,
+    error::PocketResult,
+    market_guard::{MarketGuard, MarketGuardEvent},
+    parser::message::WebSocketMessage,
+    straddle::StraddleResult,
+    utils::metadata::{CachedMetadata, MetadataOrigin, MetadataSource},
+    ws::{candle_close::CandleCloseStream, stream::StreamAsset},
 };
 
 use super::{
-    order::Deal,
-    update::{UpdateAssets, UpdateBalance, UpdateStream},
+    account::{AccountEvent, AccountEventKind},
+    feed::{FeedAlert, FeedHealth, FeedMonitor},
+    lanes::{StreamLaneStats, StreamLanes, StreamPriority},
+    latency::{LatencyStats, LatencyTracker},
+    order::{Deal, FailCloseOrder},
+    store::CandleStore,
+    subscriptions::{SubscriptionEvent, Subscriptions},
+    unparsed::{Quarantine, UnparsedMessage},
+    update::{Asset, UpdateAssets, UpdateBalance, UpdateStream},
 };
 
-pub struct Channels(Sender<WebSocketMessage>, Receiver<WebSocketMessage>);
-
 #[derive(Default, Clone)]
 pub struct PocketData {
     balance: Arc<Mutex<UpdateBalance>>,
     opened_deals: Arc<Mutex<HashMap<Uuid, Deal>>>,
     closed_deals: Arc<Mutex<HashSet<Deal>>>,
     payout_data: Arc<Mutex<HashMap<String, i32>>>,
+    assets: Arc<Mutex<HashMap<String, Asset>>>,
     server_time: Arc<Mutex<i64>>,
-    stream_channels: Arc<Channels>,
+    stream_lanes: Arc<StreamLanes>,
     stream_assets: Arc<Mutex<Vec<String>>>,
+    account_events: Arc<Mutex<Vec<Sender<AccountEvent>>>>,
+    trade_results: Arc<Mutex<Vec<Sender<Deal>>>>,
+    straddle_results: Arc<Mutex<Vec<Sender<StraddleResult>>>>,
+    quarantine: Quarantine,
+    subscriptions: Subscriptions,
+    latency: Arc<Mutex<LatencyTracker>>,
+    feeds: Arc<Mutex<FeedMonitor>>,
+    market_guard: Arc<RwLock<Option<Arc<MarketGuard>>>>,
+    candle_store: Arc<RwLock<CandleStore>>,
+    metadata: Arc<RwLock<MetadataOrigin>>,
+    close_refusals: CloseWaiters,
+}
+
+/// `close_deal` calls waiting for the refusal of the sell-out of a trade, by trade.
+type CloseWaiters = Arc<std::sync::Mutex<HashMap<Uuid, Vec<oneshot::Sender<FailCloseOrder>>>>>;
+
+/// Wait for the refusal of the sell-out of a trade, registered by `PocketData::wait_close_refusal`
+/// and removed once dropped.
+pub struct CloseRefusal {
+    waiters: CloseWaiters,
+    trade_id: Uuid,
+    receiver: Option<oneshot::Receiver<FailCloseOrder>>,
+}
+
+impl CloseRefusal {
+    /// Waits for the server to refuse closing the trade, never completes if it doesn't.
+    pub async fn recv(&mut self) -> FailCloseOrder {
+        if let Some(receiver) = self.receiver.as_mut() {
+            if let Ok(fail) = receiver.await {
+                return fail;
+            }
+            self.receiver = None;
+        }
+        std::future::pending().await
+    }
 }
 
-impl Default for Channels {
-    fn default() -> Self {
-        let (s, r) = bounded(MAX_CHANNEL_CAPACITY);
-        Self(s, r)
+impl Drop for CloseRefusal {
+    fn drop(&mut self) {
+        self.receiver = None;
+        let mut waiters = self.waiters.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(senders) = waiters.get_mut(&self.trade_id) {
+            senders.retain(|sender| !sender.is_closed());
+            if senders.is_empty() {
+                waiters.remove(&self.trade_id);
+            }
+        }
     }
 }
 
@@ -96,7 +151,18 @@ impl PocketData {
         for d in deals.iter() {
             self.remove_opened_deal(d.id).await;
         }
-        let new: HashSet<Deal> = HashSet::from_iter(deals);
+        // The same deal can be recieved in both the `successcloseOrder` and the `updateClosedDeals`
+        // messages, it's only sent to the subscribers the first time
+        let new: HashSet<Deal> = deals.into_iter().filter(|d| !closed.contains(d)).collect();
+        if !new.is_empty() {
+            let mut subscribers = self.trade_results.lock().await;
+            subscribers.retain(|s| !s.is_closed());
+            for deal in new.iter() {
+                for subscriber in subscribers.iter() {
+                    let _ = subscriber.try_send(deal.clone());
+                }
+            }
+        }
         closed.extend(new);
     }
 
@@ -110,8 +176,53 @@ impl PocketData {
     }
 
     pub async fn update_payout_data(&self, payout: UpdateAssets) {
+        let mut assets = self.assets.lock().await;
+        *assets = payout
+            .0
+            .iter()
+            .map(|a| (a.symbol.clone(), a.clone()))
+            .collect();
         let mut data = self.payout_data.lock().await;
         *data = payout.into();
+        self.set_metadata_origin(MetadataSource::Server, Utc::now());
+    }
+
+    /// Fills the assets with the ones of the metadata cache until the server sends the current ones,
+    /// returns `false` (and keeps the assets) if it already did.
+    pub async fn seed_assets(&self, cached: CachedMetadata) -> bool {
+        let mut assets = self.assets.lock().await;
+        if self.metadata_origin().source == MetadataSource::Server {
+            return false;
+        }
+        let payout = UpdateAssets(cached.assets);
+        *assets = payout
+            .0
+            .iter()
+            .map(|a| (a.symbol.clone(), a.clone()))
+            .collect();
+        let mut data = self.payout_data.lock().await;
+        *data = payout.into();
+        self.set_metadata_origin(MetadataSource::Cache, cached.saved_at);
+        true
+    }
+
+    pub fn metadata_origin(&self) -> MetadataOrigin {
+        *self.metadata.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn set_metadata_origin(&self, source: MetadataSource, updated_at: DateTime<Utc>) {
+        *self.metadata.write().unwrap_or_else(|e| e.into_inner()) = MetadataOrigin {
+            source,
+            updated_at: Some(updated_at),
+        };
+    }
+
+    pub async fn get_asset(&self, asset: impl ToString) -> Option<Asset> {
+        self.assets.lock().await.get(&asset.to_string()).cloned()
+    }
+
+    pub async fn get_assets(&self) -> Vec<Asset> {
+        self.assets.lock().await.values().cloned().collect()
     }
 
     pub async fn get_full_payout(&self) -> HashMap<String, i32> {
@@ -140,34 +251,289 @@ impl PocketData {
         info!("Created new channels and StreamAsset instance");
         let mut assets = self.stream_assets.lock().await;
         assets.push(asset.clone());
-        StreamAsset::new(self.stream_channels.1.clone(), asset)
+        StreamAsset::new(self.stream_lanes.receiver(), asset)
     }
 
     pub async fn add_stream_chuncked(&self, asset: String, chunck_size: usize) -> StreamAsset {
         info!("Created new channels and StreamAsset instance");
         let mut assets = self.stream_assets.lock().await;
         assets.push(asset.clone());
-        StreamAsset::new_chuncked(self.stream_channels.1.clone(), asset, chunck_size)
+        StreamAsset::new_chuncked(self.stream_lanes.receiver(), asset, chunck_size)
     }
 
     pub async fn add_stream_timed(&self, asset: String, time: Duration) -> StreamAsset {
         info!("Created new channels and StreamAsset instance");
         let mut assets = self.stream_assets.lock().await;
         assets.push(asset.clone());
-        StreamAsset::new_timed(self.stream_channels.1.clone(), asset, time)
+        StreamAsset::new_timed(self.stream_lanes.receiver(), asset, time)
+    }
+
+    /// The close timer starts on the server clock estimated with the latency of the ticks recieved so far.
+    pub async fn add_candle_close_stream(
+        &self,
+        asset: String,
+        period: Duration,
+        timeout: Option<Duration>,
+    ) -> PocketResult<CandleCloseStream> {
+        info!("Created new channels and CandleCloseStream instance");
+        let clock_offset = self.latency_stats().await.clock_offset_delta();
+        let mut assets = self.stream_assets.lock().await;
+        assets.push(asset.clone());
+        CandleCloseStream::new(
+            self.stream_lanes.receiver(),
+            asset,
+            period,
+            clock_offset,
+            timeout,
+        )
+    }
+
+    /// Whether a stream (of any asset) is alive to read the ticks.
+    pub fn has_stream_consumers(&self) -> bool {
+        self.stream_lanes.has_consumers()
+    }
+
+    /// Sets the lane the ticks of `asset` go through, the critical ones are never shed.
+    pub fn set_stream_priority(&self, asset: &str, priority: StreamPriority) {
+        self.stream_lanes.set_priority(asset, priority)
+    }
+
+    pub fn stream_lane_stats(&self) -> StreamLaneStats {
+        self.stream_lanes.stats()
     }
 
     pub async fn stream_assets(&self) -> Vec<String> {
         self.stream_assets.lock().await.clone()
     }
 
-    pub async fn send_stream(&self, stream: UpdateStream) -> PocketResult<()> {
-        if self.stream_channels.0.receiver_count() > 1 {
-            self.stream_channels
-                .0
-                .force_send(WebSocketMessage::UpdateStream(stream))?;
+    pub async fn remove_stream_asset(&self, asset: &str) {
+        self.stream_assets.lock().await.retain(|a| a != asset);
+    }
+
+    /// Every subscriber receives all the account events, the events of a subscriber that stopped
+    /// reading are dropped once its channel is full.
+    pub async fn subscribe_account_events(
+        &self,
+        timeout: Option<Duration>,
+    ) -> RecieverStream<AccountEvent> {
+        let (sender, receiver) = bounded(MAX_CHANNEL_CAPACITY);
+        self.account_events.lock().await.push(sender);
+        RecieverStream::new_timed(receiver, timeout)
+    }
+
+    /// Every subscriber receives the deals closed after it subscribed, the deals of a subscriber that
+    /// stopped reading are dropped once its channel is full.
+    pub async fn subscribe_trade_results(&self, timeout: Option<Duration>) -> RecieverStream<Deal> {
+        let (sender, receiver) = bounded(MAX_CHANNEL_CAPACITY);
+        self.trade_results.lock().await.push(sender);
+        RecieverStream::new_timed(receiver, timeout)
+    }
+
+    pub async fn subscribe_straddle_results(
+        &self,
+        timeout: Option<Duration>,
+    ) -> RecieverStream<StraddleResult> {
+        let (sender, receiver) = bounded(MAX_CHANNEL_CAPACITY);
+        self.straddle_results.lock().await.push(sender);
+        RecieverStream::new_timed(receiver, timeout)
+    }
+
+    pub async fn send_straddle_result(&self, result: StraddleResult) {
+        let mut subscribers = self.straddle_results.lock().await;
+        subscribers.retain(|s| !s.is_closed());
+        for subscriber in subscribers.iter() {
+            let _ = subscriber.try_send(result.clone());
+        }
+    }
+
+    pub async fn send_account_event(&self, event: AccountEvent) {
+        let mut subscribers = self.account_events.lock().await;
+        subscribers.retain(|s| !s.is_closed());
+        for subscriber in subscribers.iter() {
+            let _ = subscriber.try_send(event.clone());
         }
-        Ok(())
+    }
+
+    pub fn set_market_guard(&self, guard: Option<Arc<MarketGuard>>) {
+        if let Ok(mut market_guard) = self.market_guard.write() {
+            *market_guard = guard;
+        }
+    }
+
+    pub fn market_guard(&self) -> Option<Arc<MarketGuard>> {
+        self.market_guard.read().ok().and_then(|g| g.clone())
+    }
+
+    /// Checks the market conditions of the guard now, so the gaps are noticed even without new
+    /// ticks, and sends the transitions.
+    pub async fn evaluate_market_guard(&self) {
+        if let Some(guard) = self.market_guard() {
+            self.send_market_guard_events(guard.evaluate(Utc::now()))
+                .await;
+        }
+    }
+
+    /// Logs the transitions of the market guard and sends them as `CircuitBreaker` account events.
+    async fn send_market_guard_events(&self, events: Vec<MarketGuardEvent>) {
+        for event in events {
+            let asset = event.asset.as_deref().unwrap_or("every asset");
+            let message = if event.paused {
+                let message = format!("Trading paused on {asset}, {}", event.reasons.join(", "));
+                warn!(target: "MarketGuard", "{message}");
+                message
+            } else {
+                let message =
+                    format!("Trading resumed on {asset}, market conditions are back to normal");
+                info!(target: "MarketGuard", "{message}");
+                message
+            };
+            info!(target: AUDIT_TARGET, event = "market_guard", asset = ?event.asset, paused = event.paused, reasons = ?event.reasons);
+            self.send_account_event(AccountEvent {
+                kind: AccountEventKind::CircuitBreaker,
+                event: if event.paused {
+                    "marketGuardPaused".into()
+                } else {
+                    "marketGuardResumed".into()
+                },
+                message: Some(message),
+                amount: None,
+                data: serde_json::to_value(&event).unwrap_or_default(),
+                received_at: event.time,
+            })
+            .await;
+        }
+    }
+
+    /// Checks the message rate of the subscribed feeds and sends their collapses and recoveries,
+    /// the observation restarts while the connection is down.
+    pub async fn evaluate_feeds(&self, connected: bool) {
+        let now = Utc::now();
+        let alerts = {
+            let mut feeds = self.feeds.lock().await;
+            if !connected {
+                feeds.reset(now);
+                return;
+            }
+            feeds.evaluate(&self.subscriptions.status().active, now)
+        };
+        self.send_feed_alerts(alerts).await;
+    }
+
+    pub async fn feed_health(&self) -> FeedHealth {
+        self.feeds.lock().await.health(Utc::now())
+    }
+
+    /// Logs the collapses and recoveries of the feeds and sends them as `FeedAnomaly` account events.
+    async fn send_feed_alerts(&self, alerts: Vec<FeedAlert>) {
+        for alert in alerts {
+            let message = if alert.collapsed {
+                let message = format!(
+                    "Message rate of '{}' collapsed to {:.2}/s from the expected {:.2}/s, the server may be throttling the account",
+                    alert.asset, alert.observed_rate, alert.expected_rate
+                );
+                warn!(target: "FeedHealth", "{message}");
+                message
+            } else {
+                let message = format!(
+                    "Message rate of '{}' recovered to {:.2}/s",
+                    alert.asset, alert.observed_rate
+                );
+                info!(target: "FeedHealth", "{message}");
+                message
+            };
+            info!(target: AUDIT_TARGET, event = "feed_anomaly", asset = %alert.asset, collapsed = alert.collapsed, expected_rate = alert.expected_rate, observed_rate = alert.observed_rate);
+            self.send_account_event(AccountEvent {
+                kind: AccountEventKind::FeedAnomaly,
+                event: if alert.collapsed {
+                    "feedCollapsed".into()
+                } else {
+                    "feedRecovered".into()
+                },
+                message: Some(message),
+                amount: None,
+                data: serde_json::to_value(&alert).unwrap_or_default(),
+                received_at: alert.time,
+            })
+            .await;
+        }
+    }
+
+    /// Returns the quarantine shared with the message handler.
+    pub fn quarantine(&self) -> Quarantine {
+        self.quarantine.clone()
+    }
+
+    /// Returns the subscriptions shared with the message handler.
+    pub fn subscriptions(&self) -> Subscriptions {
+        self.subscriptions.clone()
+    }
+
+    pub fn subscribe_subscription_events(
+        &self,
+        timeout: Option<Duration>,
+    ) -> RecieverStream<SubscriptionEvent> {
+        self.subscriptions.subscribe(timeout)
+    }
+
+    pub fn subscribe_unparsed(&self, timeout: Option<Duration>) -> RecieverStream<UnparsedMessage> {
+        self.quarantine.subscribe(timeout)
+    }
+
+    /// Registers a wait for the refusal of the sell-out of `trade_id`, until the returned
+    /// `CloseRefusal` is dropped.
+    pub fn wait_close_refusal(&self, trade_id: Uuid) -> CloseRefusal {
+        let (sender, receiver) = oneshot::channel();
+        self.close_refusals
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(trade_id)
+            .or_default()
+            .push(sender);
+        CloseRefusal {
+            waiters: self.close_refusals.clone(),
+            trade_id,
+            receiver: Some(receiver),
+        }
+    }
+
+    /// Sends a refused sell-out to the `close_deal` calls waiting for its trade. A refusal without
+    /// a ticket is only attributed while a single trade is being closed.
+    fn send_close_refusal(&self, fail: &FailCloseOrder) {
+        let mut waiters = self
+            .close_refusals
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let trade_id = match fail.ticket() {
+            Some(trade_id) => trade_id,
+            None if waiters.len() == 1 => *waiters.keys().next().expect("One trade is closing"),
+            None => {
+                warn!(target: "CloseDeal", "Refused sell-out without a ticket while {} trades are closing, {fail}", waiters.len());
+                return;
+            }
+        };
+        match waiters.remove(&trade_id) {
+            Some(senders) => {
+                for sender in senders {
+                    let _ = sender.send(fail.clone());
+                }
+            }
+            None => {
+                warn!(target: "CloseDeal", "Refused sell-out of trade '{trade_id}' which isn't being closed, {fail}")
+            }
+        }
+    }
+
+    /// Returns the store of the recent candles of the subscribed assets, fed with their ticks.
+    pub fn candle_store(&self) -> Arc<RwLock<CandleStore>> {
+        self.candle_store.clone()
+    }
+
+    pub async fn latency_stats(&self) -> LatencyStats {
+        self.latency.lock().await.stats()
+    }
+
+    pub async fn send_stream(&self, stream: UpdateStream) -> PocketResult<()> {
+        self.stream_lanes.send(stream)
     }
 }
 
@@ -202,12 +568,41 @@ impl DataHandler for PocketData {
                     Some(item) => self.update_server_time(item.time.timestamp()).await,
                     None => warn!("Missing data in 'updateStream' message"),
                 }
+                self.latency.lock().await.record_stream(stream);
+                self.feeds.lock().await.record_stream(stream);
+                self.candle_store
+                    .write()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .record_stream(stream);
+                if let Some(guard) = self.market_guard() {
+                    self.send_market_guard_events(guard.record_stream(stream))
+                        .await;
+                }
                 self.send_stream(stream.clone()).await?;
             }
+            WebSocketMessage::AccountEvent(event) => {
+                info!(target: "AccountEvent", "Recieved {:?} event '{}': {}", event.kind, event.event, event.data);
+                self.send_account_event(event.clone()).await
+            }
+            WebSocketMessage::FailCloseOrder(fail) => self.send_close_refusal(fail),
             _ => {}
         }
         Ok(())
     }
+
+    /// Ends the candle streams and the account, trade result, subscription and unparsed message
+    /// subscriptions.
+    async fn close(&self) {
+        self.stream_lanes.close();
+        for subscriber in self.account_events.lock().await.drain(..) {
+            subscriber.close();
+        }
+        for subscriber in self.trade_results.lock().await.drain(..) {
+            subscriber.close();
+        }
+        self.subscriptions.close();
+        self.quarantine.close();
+    }
 }
 
 /*
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+use binary_options_tools_core::utils::pool::PoolStats;
+
+use crate::pocketoption::parser::json::{JSON_BACKEND, buffer_stats};
+
+/// Internal counters of the client, used to diagnose performance issues.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DebugStats {
+    /// Backend the messages are parsed with (`serde_json` or `simd-json`).
+    pub json_backend: String,
+    /// Pool of the scratch buffers of the parser.
+    pub parser_buffers: PoolStats,
+}
+
+impl DebugStats {
+    pub fn collect() -> Self {
+        Self {
+            json_backend: JSON_BACKEND.to_string(),
+            parser_buffers: buffer_stats(),
+        }
+    }
+}
@@ -0,0 +1,274 @@
+//! Message rate of the subscribed feeds.
+//!
+//! Every subscribed asset learns the tick rate it usually streams at, and the rate observed over the
+//! last window is compared to it. A rate collapsing far below the expected one while the connection
+//! is up usually means the server throttles (or banned) the account rather than a quiet market,
+//! especially when every feed collapses at once. The collapses and the recoveries are sent as alerts,
+//! the current state of every feed is reported by `feed_health`.
+
+use std::collections::{HashMap, VecDeque};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::update::UpdateStream;
+
+/// Window the observed rate of a feed is measured over.
+pub const FEED_WINDOW: TimeDelta = TimeDelta::seconds(30);
+/// A feed collapsed once its observed rate falls under this fraction of the expected rate.
+pub const FEED_COLLAPSE_RATIO: f64 = 0.2;
+/// A collapsed feed recovers once its observed rate is back over this fraction of the expected rate.
+pub const FEED_RECOVERY_RATIO: f64 = 0.5;
+/// Weight of the last window in the expected rate, learned once per window from the healthy windows.
+const BASELINE_WEIGHT: f64 = 0.2;
+
+/// State of the feed of a subscribed asset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeedStatus {
+    pub asset: String,
+    /// Ticks per second the feed usually streams at, `None` until a full window was observed.
+    pub expected_rate: Option<f64>,
+    /// Ticks per second over the last window.
+    pub observed_rate: f64,
+    /// Time the rate collapsed at, `None` while the feed is healthy.
+    pub collapsed_since: Option<DateTime<Utc>>,
+}
+
+/// Anomaly report of the feeds returned by `feed_health`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FeedHealth {
+    pub feeds: Vec<FeedStatus>,
+    /// Assets whose rate collapsed.
+    pub collapsed: Vec<String>,
+    /// Every feed with an expected rate collapsed at once, which points to a throttling or a ban of
+    /// the account rather than to a quiet market.
+    pub suspected_throttling: bool,
+}
+
+impl FeedHealth {
+    pub fn is_healthy(&self) -> bool {
+        self.collapsed.is_empty()
+    }
+}
+
+/// Collapse (or recovery) of the rate of a feed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeedAlert {
+    pub asset: String,
+    /// `true` when the rate collapsed, `false` when it recovered.
+    pub collapsed: bool,
+    pub expected_rate: f64,
+    pub observed_rate: f64,
+    pub time: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+struct FeedRate {
+    ticks: VecDeque<DateTime<Utc>>,
+    /// Start of the observation, the rate is only judged once a full window was observed.
+    observed_since: DateTime<Utc>,
+    expected: Option<f64>,
+    /// Last time the expected rate learned from the observed one.
+    learned_at: DateTime<Utc>,
+    collapsed_since: Option<DateTime<Utc>>,
+}
+
+impl FeedRate {
+    fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            ticks: VecDeque::new(),
+            observed_since: now,
+            expected: None,
+            learned_at: now,
+            collapsed_since: None,
+        }
+    }
+
+    fn trim(&mut self, now: DateTime<Utc>) {
+        while self
+            .ticks
+            .front()
+            .is_some_and(|tick| now - *tick > FEED_WINDOW)
+        {
+            self.ticks.pop_front();
+        }
+    }
+
+    /// Rate over the time observed so far (at most a window), and whether a full window was observed.
+    fn observed_rate(&self, now: DateTime<Utc>) -> (f64, bool) {
+        let observed = (now - self.observed_since).min(FEED_WINDOW);
+        let seconds = observed.num_milliseconds() as f64 / 1000.0;
+        let ticks = self
+            .ticks
+            .iter()
+            .filter(|tick| now - **tick <= FEED_WINDOW)
+            .count();
+        let rate = if seconds > 0.0 {
+            ticks as f64 / seconds
+        } else {
+            0.0
+        };
+        (rate, observed >= FEED_WINDOW)
+    }
+}
+
+/// Tracks the tick rate of every subscribed asset, fed with the `updateStream` messages.
+#[derive(Debug, Default)]
+pub struct FeedMonitor {
+    feeds: HashMap<String, FeedRate>,
+}
+
+impl FeedMonitor {
+    pub fn record_tick(&mut self, asset: &str, received_at: DateTime<Utc>) {
+        self.feeds
+            .entry(asset.to_string())
+            .or_insert_with(|| FeedRate::new(received_at))
+            .ticks
+            .push_back(received_at);
+    }
+
+    pub fn record_stream(&mut self, stream: &UpdateStream) {
+        for item in stream.0.iter() {
+            self.record_tick(&item.active, item.received_at);
+        }
+    }
+
+    /// Restarts the observation of every feed, the expected rates are kept. Used while the
+    /// connection is down so the missing ticks aren't taken for a collapse.
+    pub fn reset(&mut self, now: DateTime<Utc>) {
+        for feed in self.feeds.values_mut() {
+            feed.ticks.clear();
+            feed.observed_since = now;
+        }
+    }
+
+    /// Compares the observed rate of the `active` subscriptions to their expected rate at `now`,
+    /// and returns the feeds that collapsed or recovered. The other assets stop being tracked.
+    pub fn evaluate(&mut self, active: &[String], now: DateTime<Utc>) -> Vec<FeedAlert> {
+        self.feeds.retain(|asset, _| active.contains(asset));
+        for asset in active {
+            self.feeds
+                .entry(asset.clone())
+                .or_insert_with(|| FeedRate::new(now));
+        }
+        let mut alerts = Vec::new();
+        for (asset, feed) in self.feeds.iter_mut() {
+            feed.trim(now);
+            let (observed, full_window) = feed.observed_rate(now);
+            if !full_window {
+                continue;
+            }
+            let Some(expected) = feed.expected else {
+                feed.expected = Some(observed);
+                feed.learned_at = now;
+                continue;
+            };
+            let collapsed = match feed.collapsed_since {
+                None => expected > 0.0 && observed < expected * FEED_COLLAPSE_RATIO,
+                Some(_) => observed < expected * FEED_RECOVERY_RATIO,
+            };
+            if collapsed != feed.collapsed_since.is_some() {
+                feed.collapsed_since = collapsed.then_some(now);
+                alerts.push(FeedAlert {
+                    asset: asset.clone(),
+                    collapsed,
+                    expected_rate: expected,
+                    observed_rate: observed,
+                    time: now,
+                });
+            }
+            // A collapsed feed would lower the expected rate until the collapse looks normal
+            if !collapsed && now - feed.learned_at >= FEED_WINDOW {
+                feed.expected =
+                    Some(expected * (1.0 - BASELINE_WEIGHT) + observed * BASELINE_WEIGHT);
+                feed.learned_at = now;
+            }
+        }
+        alerts.sort_by(|a, b| a.asset.cmp(&b.asset));
+        alerts
+    }
+
+    pub fn health(&self, now: DateTime<Utc>) -> FeedHealth {
+        let mut feeds: Vec<FeedStatus> = self
+            .feeds
+            .iter()
+            .map(|(asset, feed)| FeedStatus {
+                asset: asset.clone(),
+                expected_rate: feed.expected,
+                observed_rate: feed.observed_rate(now).0,
+                collapsed_since: feed.collapsed_since,
+            })
+            .collect();
+        feeds.sort_by(|a, b| a.asset.cmp(&b.asset));
+        let collapsed: Vec<String> = feeds
+            .iter()
+            .filter(|feed| feed.collapsed_since.is_some())
+            .map(|feed| feed.asset.clone())
+            .collect();
+        let suspected_throttling = !collapsed.is_empty()
+            && feeds
+                .iter()
+                .filter(|feed| feed.expected_rate.is_some_and(|rate| rate > 0.0))
+                .all(|feed| feed.collapsed_since.is_some());
+        FeedHealth {
+            feeds,
+            collapsed,
+            suspected_throttling,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feed_monitor() {
+        let start = Utc::now();
+        let at = |millis: i64| start + TimeDelta::milliseconds(millis);
+        let active = vec!["EURUSD_otc".to_string(), "GBPUSD_otc".to_string()];
+        let mut monitor = FeedMonitor::default();
+        monitor.evaluate(&active, start);
+        // Both feeds tick twice per second for a window, their expected rate is learned
+        for i in 0..60 {
+            monitor.record_tick("EURUSD_otc", at(i * 500));
+            monitor.record_tick("GBPUSD_otc", at(i * 500));
+        }
+        assert!(monitor.evaluate(&active, at(30_000)).is_empty());
+        let health = monitor.health(at(30_000));
+        assert_eq!(health.feeds[0].expected_rate, Some(2.0));
+        assert!(health.is_healthy());
+
+        // Only GBPUSD keeps ticking
+        for i in 60..120 {
+            monitor.record_tick("GBPUSD_otc", at(i * 500));
+        }
+        let alerts = monitor.evaluate(&active, at(60_000));
+        assert_eq!(alerts.len(), 1);
+        assert!(alerts[0].collapsed);
+        assert_eq!(alerts[0].asset, "EURUSD_otc");
+        let health = monitor.health(at(60_000));
+        assert_eq!(health.collapsed, vec!["EURUSD_otc"]);
+        assert!(!health.suspected_throttling);
+        assert_eq!(health.feeds[0].expected_rate, Some(2.0));
+
+        // Every feed stops
+        let alerts = monitor.evaluate(&active, at(90_000));
+        assert_eq!(alerts.len(), 1);
+        assert!(monitor.health(at(90_000)).suspected_throttling);
+
+        // A disconnection isn't a collapse, the ticks are judged again after a full window
+        monitor.reset(at(90_000));
+        for i in 180..240 {
+            monitor.record_tick("EURUSD_otc", at(i * 500));
+        }
+        assert!(monitor.evaluate(&active, at(100_000)).is_empty());
+        let alerts = monitor.evaluate(&active, at(120_000));
+        assert_eq!(alerts.len(), 1);
+        assert!(!alerts[0].collapsed);
+
+        // Unsubscribed assets stop being tracked
+        monitor.evaluate(&active[..1], at(120_000));
+        assert_eq!(monitor.health(at(120_000)).feeds.len(), 1);
+    }
+}
@@ -31,6 +31,10 @@ pub enum MessageInfo {
     OpenPendingOrder,
     SuccessopenPendingOrder,
     FailopenPendingOrder,
+    CloseOrder,
+    FailcloseOrder,
+    /// Administrative message about the account, the name of the event is kept by the handler.
+    AccountEvent,
     None,
 
     #[serde(other)]
@@ -0,0 +1,243 @@
+//! Priority lanes of the candle streams.
+//!
+//! The ticks of the critical assets (e.g. the asset currently being traded) go through a lane that
+//! is never shed, the ticks of the other assets through a bounded lane that drops its oldest ticks
+//! once the streams stop keeping up with the feed. The streams read both lanes, the critical one
+//! first, so a slow consumer of a best-effort asset never delays nor drops the critical ticks.
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    str::FromStr,
+    sync::{Mutex, RwLock},
+};
+
+use async_channel::{Receiver, RecvError, Sender, bounded, unbounded};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use binary_options_tools_core::constants::MAX_CHANNEL_CAPACITY;
+
+use crate::pocketoption::{
+    error::{PocketOptionError, PocketResult},
+    parser::message::WebSocketMessage,
+};
+
+use super::update::UpdateStream;
+
+/// Lane the ticks of an asset go through.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamPriority {
+    /// Never shed, the lane grows as long as the streams don't read it.
+    Critical,
+    /// Shed first, the oldest ticks are dropped once the lane is full.
+    #[default]
+    BestEffort,
+}
+
+impl FromStr for StreamPriority {
+    type Err = PocketOptionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "critical" => Ok(Self::Critical),
+            "best_effort" => Ok(Self::BestEffort),
+            _ => Err(PocketOptionError::GeneralParsingError(format!(
+                "unknown stream priority '{s}', expected critical or best_effort"
+            ))),
+        }
+    }
+}
+
+/// Critical assets and ticks shed from the best-effort lane since the client started.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StreamLaneStats {
+    pub critical: Vec<String>,
+    /// Best-effort ticks dropped because the streams didn't read them in time.
+    pub shed: u64,
+    pub shed_by_asset: BTreeMap<String, u64>,
+    /// Ticks waiting in each lane.
+    pub pending_critical: usize,
+    pub pending_best_effort: usize,
+}
+
+/// Receiving half of the lanes held by every stream, yields the critical ticks first.
+#[derive(Debug, Clone)]
+pub struct StreamReceiver {
+    critical: Receiver<WebSocketMessage>,
+    best_effort: Receiver<WebSocketMessage>,
+}
+
+impl StreamReceiver {
+    pub async fn recv(&self) -> Result<WebSocketMessage, RecvError> {
+        // A closed critical lane disables its branch, the stream ends with the best-effort lane
+        tokio::select! {
+            biased;
+            Ok(msg) = self.critical.recv() => Ok(msg),
+            msg = self.best_effort.recv() => msg,
+        }
+    }
+}
+
+/// A single channel read as the best-effort lane, the critical lane is closed.
+impl From<Receiver<WebSocketMessage>> for StreamReceiver {
+    fn from(best_effort: Receiver<WebSocketMessage>) -> Self {
+        let (_, critical) = unbounded();
+        Self {
+            critical,
+            best_effort,
+        }
+    }
+}
+
+/// Both lanes, the priorities of the assets and the shed counters, shared with the message handler.
+#[derive(Debug)]
+pub struct StreamLanes {
+    critical: (Sender<WebSocketMessage>, Receiver<WebSocketMessage>),
+    best_effort: (Sender<WebSocketMessage>, Receiver<WebSocketMessage>),
+    priorities: RwLock<HashSet<String>>,
+    shed: Mutex<BTreeMap<String, u64>>,
+}
+
+impl Default for StreamLanes {
+    fn default() -> Self {
+        Self::new(MAX_CHANNEL_CAPACITY)
+    }
+}
+
+impl StreamLanes {
+    /// Lanes whose best-effort lane holds up to `capacity` ticks.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            critical: unbounded(),
+            best_effort: bounded(capacity),
+            priorities: RwLock::new(HashSet::new()),
+            shed: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn receiver(&self) -> StreamReceiver {
+        StreamReceiver {
+            critical: self.critical.1.clone(),
+            best_effort: self.best_effort.1.clone(),
+        }
+    }
+
+    /// Whether a stream is alive to read the ticks, the lanes keep a receiver to create the streams.
+    pub fn has_consumers(&self) -> bool {
+        self.best_effort.1.receiver_count() > 1
+    }
+
+    pub fn set_priority(&self, asset: &str, priority: StreamPriority) {
+        if let Ok(mut critical) = self.priorities.write() {
+            match priority {
+                StreamPriority::Critical => critical.insert(asset.to_string()),
+                StreamPriority::BestEffort => critical.remove(asset),
+            };
+        }
+    }
+
+    pub fn priority(&self, asset: &str) -> StreamPriority {
+        match self.priorities.read() {
+            Ok(critical) if critical.contains(asset) => StreamPriority::Critical,
+            _ => StreamPriority::BestEffort,
+        }
+    }
+
+    /// Sends a tick to the lane of its asset, shedding the oldest best-effort tick if that lane is full.
+    pub fn send(&self, stream: UpdateStream) -> PocketResult<()> {
+        if !self.has_consumers() {
+            return Ok(());
+        }
+        let priority = stream.0.first().map_or(StreamPriority::BestEffort, |item| {
+            self.priority(&item.active)
+        });
+        let message = WebSocketMessage::UpdateStream(stream);
+        if priority == StreamPriority::Critical {
+            self.critical.0.force_send(message)?;
+        } else if let Some(WebSocketMessage::UpdateStream(shed)) =
+            self.best_effort.0.force_send(message)?
+        {
+            let asset = shed.0.first().map_or("", |item| item.active.as_str());
+            debug!(target: "StreamLanes", "Best-effort lane full, shed a tick of '{asset}'");
+            if let Ok(mut counts) = self.shed.lock() {
+                *counts.entry(asset.to_string()).or_default() += 1;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn stats(&self) -> StreamLaneStats {
+        let mut critical: Vec<String> = self
+            .priorities
+            .read()
+            .map(|critical| critical.iter().cloned().collect())
+            .unwrap_or_default();
+        critical.sort();
+        let shed_by_asset = self.shed.lock().map(|s| s.clone()).unwrap_or_default();
+        StreamLaneStats {
+            critical,
+            shed: shed_by_asset.values().sum(),
+            shed_by_asset,
+            pending_critical: self.critical.0.len(),
+            pending_best_effort: self.best_effort.0.len(),
+        }
+    }
+
+    /// Ends every stream.
+    pub fn close(&self) {
+        self.critical.0.close();
+        self.best_effort.0.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick(asset: &str, price: f64) -> UpdateStream {
+        serde_json::from_value(serde_json::json!([[asset, 1_700_000_000.0, price]])).unwrap()
+    }
+
+    fn price(message: WebSocketMessage) -> f64 {
+        match message {
+            WebSocketMessage::UpdateStream(stream) => stream.0[0].price,
+            _ => panic!("expected a tick"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_lanes() -> anyhow::Result<()> {
+        let lanes = StreamLanes::new(2);
+        // Nothing is queued without a stream
+        lanes.send(tick("EURUSD_otc", 1.0))?;
+        assert_eq!(lanes.stats().pending_best_effort, 0);
+
+        let receiver = lanes.receiver();
+        lanes.set_priority("EURUSD_otc", "critical".parse()?);
+        for i in 0..3 {
+            lanes.send(tick("EURUSD_otc", i as f64))?;
+            lanes.send(tick("GBPUSD_otc", 10.0 + i as f64))?;
+        }
+        let stats = lanes.stats();
+        assert_eq!(stats.critical, vec!["EURUSD_otc"]);
+        assert_eq!(stats.shed, 1);
+        assert_eq!(stats.shed_by_asset["GBPUSD_otc"], 1);
+        assert_eq!((stats.pending_critical, stats.pending_best_effort), (3, 2));
+
+        // Every critical tick first, then the best-effort ticks left
+        let mut prices = Vec::new();
+        for _ in 0..5 {
+            prices.push(price(receiver.recv().await?));
+        }
+        assert_eq!(prices, [0.0, 1.0, 2.0, 11.0, 12.0]);
+
+        lanes.set_priority("EURUSD_otc", StreamPriority::BestEffort);
+        assert_eq!(lanes.priority("EURUSD_otc"), StreamPriority::BestEffort);
+        assert!("urgent".parse::<StreamPriority>().is_err());
+
+        lanes.close();
+        assert!(receiver.recv().await.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::VecDeque;
+
+use chrono::TimeDelta;
+use serde::{Deserialize, Serialize};
+
+use super::update::UpdateStream;
+
+/// Number of messages the latency statistics are computed over.
+pub const LATENCY_WINDOW: usize = 1000;
+
+/// Rolling statistics of the latency of the streamed messages, in milliseconds.
+///
+/// The latency of a message is the difference between the local time it was recieved at and
+/// its server timestamp, so it includes the offset between both clocks. The minimum latency of
+/// the window is used as the estimation of that offset, a `transport_p50` growing over time means
+/// the client is falling behind the feed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct LatencyStats {
+    pub samples: usize,
+    pub last: f64,
+    pub mean: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub max: f64,
+    /// Estimated offset between the local and the server clocks (minimum latency of the window).
+    pub clock_offset: f64,
+    /// Median latency without the clock offset.
+    pub transport_p50: f64,
+    /// 99th percentile of the latency without the clock offset.
+    pub transport_p99: f64,
+}
+
+impl LatencyStats {
+    /// Offset between the local and the server clocks (local - server), `None` before the first
+    /// message.
+    pub fn clock_offset_delta(&self) -> Option<TimeDelta> {
+        (self.samples > 0).then(|| TimeDelta::microseconds((self.clock_offset * 1000.0) as i64))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LatencyTracker {
+    latencies: VecDeque<f64>,
+}
+
+impl LatencyTracker {
+    pub fn record(&mut self, latency: f64) {
+        if !latency.is_finite() {
+            return;
+        }
+        if self.latencies.len() >= LATENCY_WINDOW {
+            self.latencies.pop_front();
+        }
+        self.latencies.push_back(latency);
+    }
+
+    pub fn record_stream(&mut self, stream: &UpdateStream) {
+        for item in stream.0.iter() {
+            self.record(item.latency());
+        }
+    }
+
+    pub fn stats(&self) -> LatencyStats {
+        let Some(&last) = self.latencies.back() else {
+            return LatencyStats::default();
+        };
+        let mut sorted: Vec<f64> = self.latencies.iter().copied().collect();
+        sorted.sort_by(f64::total_cmp);
+        let percentile = |p: f64| sorted[((sorted.len() - 1) as f64 * p).round() as usize];
+        let clock_offset = sorted[0];
+        LatencyStats {
+            samples: sorted.len(),
+            last,
+            mean: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            p50: percentile(0.5),
+            p90: percentile(0.9),
+            p99: percentile(0.99),
+            max: sorted[sorted.len() - 1],
+            clock_offset,
+            transport_p50: percentile(0.5) - clock_offset,
+            transport_p99: percentile(0.99) - clock_offset,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_stats() {
+        let mut tracker = LatencyTracker::default();
+        assert_eq!(tracker.stats(), LatencyStats::default());
+        for latency in 0..=100 {
+            tracker.record(latency as f64 + 50.0);
+        }
+        tracker.record(f64::NAN);
+        let stats = tracker.stats();
+        assert_eq!(stats.samples, 101);
+        assert_eq!(stats.last, 150.0);
+        assert_eq!(stats.p50, 100.0);
+        assert_eq!(stats.p99, 149.0);
+        assert_eq!(stats.clock_offset, 50.0);
+        assert_eq!(stats.transport_p50, 50.0);
+
+        for _ in 0..LATENCY_WINDOW {
+            tracker.record(10.0);
+        }
+        assert_eq!(tracker.stats().samples, LATENCY_WINDOW);
+        assert_eq!(tracker.stats().max, 10.0);
+    }
+
+    #[test]
+    fn test_stream_latency() -> anyhow::Result<()> {
+        let stream: UpdateStream =
+            serde_json::from_str(r#"[["EURUSD_otc",1732830108.205,1.08543]]"#)?;
+        let mut tracker = LatencyTracker::default();
+        tracker.record_stream(&stream);
+        let stats = tracker.stats();
+        assert_eq!(stats.samples, 1);
+        assert!(stats.last > 0.0);
+        Ok(())
+    }
+}
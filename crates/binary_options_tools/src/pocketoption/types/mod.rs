@@ -1,7 +1,18 @@
+pub mod account;
 pub mod base;
 pub mod callback;
 pub mod data;
+pub mod debug;
+pub mod feed;
 pub mod info;
+pub mod lanes;
+pub mod latency;
+pub mod numeric;
 pub mod order;
+pub mod region;
+pub mod store;
+pub mod subscriptions;
 pub mod success;
+pub mod timeframe;
+pub mod unparsed;
 pub mod update;
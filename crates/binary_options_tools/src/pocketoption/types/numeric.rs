@@ -0,0 +1,196 @@
+//! Lenient deserializers for the numbers of the server payloads, which sometimes arrive as strings
+//! and with locale specific formats (`"1,5"`, `"1 234,56"`, `"1.234,56"`).
+
+use serde::{Deserialize, Deserializer, de::Error};
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Number {
+    Integer(i64),
+    Float(f64),
+    Text(String),
+}
+
+/// Parses a number written with any of the common decimal and grouping separators.
+/// When both `,` and `.` are present the last one is the decimal separator, a single `,` is a decimal
+/// separator and repeated separators are grouping separators.
+pub fn parse_number(value: &str) -> Option<f64> {
+    let mut number: String = value
+        .trim()
+        .chars()
+        .filter(|c| !c.is_whitespace() && !matches!(c, '_' | '\''))
+        .collect();
+    let (commas, dots) = (number.matches(',').count(), number.matches('.').count());
+    match (commas, dots) {
+        (0, 0) | (0, 1) => {}
+        (1, 0) => number = number.replace(',', "."),
+        (_, 0) => number = number.replace(',', ""),
+        (0, _) => number = number.replace('.', ""),
+        _ => {
+            if number.rfind(',') > number.rfind('.') {
+                number = number.replace('.', "").replace(',', ".");
+            } else {
+                number = number.replace(',', "");
+            }
+        }
+    }
+    number.parse::<f64>().ok().filter(|n| n.is_finite())
+}
+
+fn to_f64<E: Error>(number: Number) -> Result<f64, E> {
+    match number {
+        Number::Integer(number) => Ok(number as f64),
+        Number::Float(number) => Ok(number),
+        Number::Text(text) => {
+            parse_number(&text).ok_or_else(|| E::custom(format!("Invalid number '{text}'")))
+        }
+    }
+}
+
+fn to_integer<T: TryFrom<i64>, E: Error>(number: Number) -> Result<T, E> {
+    if let Number::Integer(number) = number {
+        return T::try_from(number)
+            .map_err(|_| E::custom(format!("Integer '{number}' out of range")));
+    }
+    let number = to_f64(number)?;
+    if number.fract() != 0.0 {
+        return Err(E::custom(format!("Expected an integer, found '{number}'")));
+    }
+    T::try_from(number as i64).map_err(|_| E::custom(format!("Integer '{number}' out of range")))
+}
+
+pub fn f64<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    to_f64(Number::deserialize(deserializer)?)
+}
+
+pub fn option_f64<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<Number>::deserialize(deserializer)?
+        .map(to_f64)
+        .transpose()
+}
+
+pub fn integer<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: TryFrom<i64>,
+{
+    to_integer(Number::deserialize(deserializer)?)
+}
+
+pub fn option_integer<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: TryFrom<i64>,
+{
+    Option::<Number>::deserialize(deserializer)?
+        .map(to_integer)
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, distr::Alphanumeric};
+    use serde_json::{Value, json};
+
+    use super::*;
+    use crate::pocketoption::types::{
+        order::Deal,
+        update::{UpdateBalance, UpdateStream},
+    };
+
+    #[test]
+    fn test_parse_number() {
+        let cases = [
+            ("1.5", 1.5),
+            ("1,5", 1.5),
+            (" 1 234,56 ", 1234.56),
+            ("1.234,56", 1234.56),
+            ("1,234.56", 1234.56),
+            ("1,234,567", 1234567.0),
+            ("1.234.567", 1234567.0),
+            ("1'234.5", 1234.5),
+            ("-0,00012", -0.00012),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(parse_number(text), Some(expected), "{text}");
+        }
+        assert_eq!(parse_number("abc"), None);
+        assert_eq!(parse_number("NaN"), None);
+        assert_eq!(parse_number(""), None);
+    }
+
+    #[test]
+    fn test_lenient_payloads() -> anyhow::Result<()> {
+        let stream: UpdateStream =
+            serde_json::from_str(r#"[["EURUSD_otc","1732830108,205","1,08543"]]"#)?;
+        assert_eq!(stream.0[0].price, 1.08543);
+        assert_eq!(stream.0[0].time.timestamp_millis(), 1732830108205);
+        let balance: UpdateBalance = serde_json::from_str(r#"{"isDemo":1,"balance":"50 000,25"}"#)?;
+        assert_eq!(balance.balance, 50000.25);
+        Ok(())
+    }
+
+    fn random_value(rng: &mut impl Rng) -> Value {
+        match rng.random_range(0..6) {
+            0 => json!(rng.random::<f64>() * 10f64.powi(rng.random_range(-6..9))),
+            1 => json!(rng.random::<i32>()),
+            2 => json!(format!("{:.5}", rng.random::<f64>() * 1000.0).replace('.', ",")),
+            3 => json!(
+                (0..rng.random_range(0..12))
+                    .map(|_| rng.sample(Alphanumeric) as char)
+                    .collect::<String>()
+            ),
+            4 => Value::Null,
+            _ => json!([rng.random::<bool>()]),
+        }
+    }
+
+    /// Random payloads must only ever produce errors, never panic, and valid numbers in any format
+    /// must be accepted.
+    #[test]
+    fn fuzz_numeric_payloads() {
+        let mut rng = rand::rng();
+        for _ in 0..2000 {
+            let stream = json!([["EURUSD_otc", random_value(&mut rng), random_value(&mut rng)]]);
+            let _ = serde_json::from_value::<UpdateStream>(stream);
+            let deal = json!({
+                "id": "27897152-8199-4d2f-946a-59de837b85d6",
+                "openTime": "2024-12-04 23:58:48",
+                "closeTime": "2024-12-04 23:59:48",
+                "openTimestamp": random_value(&mut rng),
+                "closeTimestamp": 1733356788,
+                "uid": random_value(&mut rng),
+                "isDemo": 1,
+                "amount": random_value(&mut rng),
+                "profit": random_value(&mut rng),
+                "percentProfit": random_value(&mut rng),
+                "percentLoss": 100,
+                "openPrice": random_value(&mut rng),
+                "copyTicket": "",
+                "closePrice": random_value(&mut rng),
+                "command": 0,
+                "asset": "EURTRY_otc",
+                "openMs": 601,
+                "optionType": 100,
+                "currency": "USD"
+            });
+            let _ = serde_json::from_value::<Deal>(deal);
+
+            let price = rng.random::<f64>() * 1000.0;
+            let formatted = format!("{price:.5}");
+            let text = match rng.random_range(0..3) {
+                0 => formatted,
+                1 => formatted.replace('.', ","),
+                _ => format!(" {} ", formatted.replace('.', ",")),
+            };
+            let parsed = parse_number(&text).unwrap();
+            assert!((parsed - price).abs() < 1e-4, "{text} -> {parsed}");
+        }
+    }
+}
@@ -7,22 +7,46 @@ use serde_json::Value;
 use uuid::Uuid;
 
 use crate::pocketoption::{
-    error::PocketResult, parser::message::WebSocketMessage, utils::basic::get_index,
+    error::{PocketOptionError, PocketResult},
+    parser::message::WebSocketMessage,
+    utils::basic::get_index,
 };
 
-use super::update::{float_time, string_time};
+use super::{
+    numeric,
+    update::{float_time, string_time},
+};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum Action {
     Call, // Buy
     Put,  // Sell
 }
 
+/// Direction of a trade, used by `PocketOption::place_order`.
+pub type Direction = Action;
+
+/// Parses `"call"` / `"buy"` or `"put"` / `"sell"`, case insensitive.
+impl std::str::FromStr for Action {
+    type Err = PocketOptionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "call" | "buy" => Ok(Self::Call),
+            "put" | "sell" => Ok(Self::Put),
+            _ => Err(PocketOptionError::GeneralParsingError(format!(
+                "Invalid direction '{s}', expected 'call', 'buy', 'put' or 'sell'"
+            ))),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum PocketMessageFail {
     Order(FailOpenOrder),
     Pending(FailOpenPendingOrder),
+    Close(FailCloseOrder),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -92,6 +116,22 @@ pub struct FailOpenPendingOrder {
     extra: HashMap<String, Value>,
 }
 
+/// Request closing an open trade before its expiry (sell-out), the server answers with a
+/// `successcloseOrder` message with the deal and its partial payout, or a `failcloseOrder` message.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CloseOrder {
+    pub ticket: Uuid,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FailCloseOrder {
+    #[serde(default)]
+    ticket: Option<Uuid>,
+    error: String,
+    #[serde(flatten)]
+    extra: HashMap<String, Value>,
+}
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
 pub struct UpdateClosedDeals(pub Vec<Deal>);
 
@@ -116,13 +156,21 @@ pub struct Deal {
     pub close_timestamp: DateTime<Utc>,
     pub refund_time: Option<Value>,
     pub refund_timestamp: Option<Value>,
+    #[serde(deserialize_with = "numeric::integer")]
     pub uid: u64,
+    #[serde(default, deserialize_with = "numeric::option_integer")]
     pub request_id: Option<u64>,
+    #[serde(deserialize_with = "numeric::f64")]
     pub amount: f64,
+    #[serde(deserialize_with = "numeric::f64")]
     pub profit: f64,
+    #[serde(deserialize_with = "numeric::integer")]
     pub percent_profit: i32,
+    #[serde(deserialize_with = "numeric::integer")]
     pub percent_loss: i32,
+    #[serde(deserialize_with = "numeric::f64")]
     pub open_price: f64,
+    #[serde(deserialize_with = "numeric::f64")]
     pub close_price: f64,
     pub command: i32,
     pub asset: String,
@@ -136,11 +184,47 @@ pub struct Deal {
     #[serde(rename = "isAI")]
     pub is_ai: Option<bool>,
     pub currency: String,
+    #[serde(default, deserialize_with = "numeric::option_f64")]
     pub amount_usd: Option<f64>,
-    #[serde(rename = "amountUSD")]
+    #[serde(
+        rename = "amountUSD",
+        default,
+        deserialize_with = "numeric::option_f64"
+    )]
     pub amount_usd2: Option<f64>,
 }
 
+/// Identifiers the broker uses for a deal, the ones to give to its support when opening a ticket.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ServerIds {
+    /// Id of the deal on the trade server (the one returned by `trade`, `buy` and `sell`).
+    pub ticket: Uuid,
+    /// Id of the order request, only sent by the server when the deal is opened.
+    pub request_id: Option<u64>,
+    /// Account that placed the deal.
+    pub uid: u64,
+}
+
+impl fmt::Display for ServerIds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ticket: {}, account: {}", self.ticket, self.uid)?;
+        if let Some(request_id) = self.request_id {
+            write!(f, ", request id: {request_id}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Deal {
+    pub fn server_ids(&self) -> ServerIds {
+        ServerIds {
+            ticket: self.id,
+            request_id: self.request_id,
+            uid: self.uid,
+        }
+    }
+}
+
 impl Hash for Deal {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         self.id.hash(state);
@@ -199,11 +283,22 @@ impl fmt::Display for FailOpenPendingOrder {
     }
 }
 
+impl fmt::Display for FailCloseOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Error: {}", self.error)?;
+        if let Some(ticket) = self.ticket {
+            writeln!(f, "Error for trade: {ticket}")?;
+        }
+        writeln!(f, "Extra data: {:?}", self.extra)
+    }
+}
+
 impl fmt::Display for PocketMessageFail {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Order(order) => order.fmt(f),
             Self::Pending(order) => order.fmt(f),
+            Self::Close(order) => order.fmt(f),
         }
     }
 }
@@ -213,10 +308,24 @@ impl From<PocketMessageFail> for WebSocketMessage {
         match value {
             PocketMessageFail::Order(order) => Self::FailOpenOrder(order),
             PocketMessageFail::Pending(pending) => Self::FailOpenPendingOrder(pending),
+            PocketMessageFail::Close(close) => Self::FailCloseOrder(close),
         }
     }
 }
 
+impl CloseOrder {
+    pub fn new(ticket: Uuid) -> Self {
+        Self { ticket }
+    }
+}
+
+impl FailCloseOrder {
+    /// Trade the server refused to close, some refusals don't carry it.
+    pub fn ticket(&self) -> Option<Uuid> {
+        self.ticket
+    }
+}
+
 impl FailOpenOrder {
     pub fn new(error: impl ToString, amount: f64, asset: impl ToString) -> Self {
         Self {
@@ -294,6 +403,13 @@ mod tests {
         let order_raw = File::open("tests/success_open_order.json")?;
         let bufreader = BufReader::new(order_raw);
         let order: Deal = serde_json::from_reader(bufreader)?;
+        let ids = order.server_ids();
+        assert_eq!(ids.request_id, Some(19656019));
+        assert_eq!(ids.uid, 87742848);
+        assert_eq!(
+            ids.to_string(),
+            "ticket: 27897152-8199-4d2f-946a-59de837b85d6, account: 87742848, request id: 19656019"
+        );
         dbg!(order);
         Ok(())
     }
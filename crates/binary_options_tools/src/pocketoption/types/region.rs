@@ -0,0 +1,271 @@
+//! Region and IP of the account.
+//!
+//! The sessions of the real accounts are bound to the IP (and the region) they were created from,
+//! moving the bot to another VPS usually ends with the server rejecting the session or closing the
+//! connection. The messages of the server about such a mismatch are recognized and surfaced as a
+//! `RegionMismatch` error with the steps to fix it, instead of a generic disconnection.
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::pocketoption::error::PocketOptionError;
+
+/// Keys of the payloads holding the region reported by the server.
+const REGION_KEYS: [&str; 4] = ["region", "country", "geo", "location"];
+/// Words of the event names reporting a mismatch.
+const MISMATCH_WORDS: [&str; 5] = ["mismatch", "change", "invalid", "wrong", "differ"];
+/// Words of the event names refusing a region.
+const REFUSAL_WORDS: [&str; 5] = [
+    "restrict",
+    "forbidden",
+    "block",
+    "notallowed",
+    "unavailable",
+];
+
+/// What didn't match the one of the session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MismatchKind {
+    /// The client connects from another IP than the one the session was created from.
+    Ip,
+    /// The account isn't allowed from the region the client connects from.
+    Region,
+}
+
+impl fmt::Display for MismatchKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ip => write!(f, "IP"),
+            Self::Region => write!(f, "region"),
+        }
+    }
+}
+
+/// Region or IP mismatch reported by the server, or found by the client once the server rejected
+/// the session of an account created from another IP.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegionMismatch {
+    pub kind: MismatchKind,
+    /// Name of the event sent by the server, `None` if the client found the mismatch itself.
+    pub event: Option<String>,
+    pub reason: String,
+    /// Region reported by the server, if any.
+    pub region: Option<String>,
+    /// IP the session was created from.
+    pub session_ip: Option<String>,
+    /// IP the client connects from, if known.
+    pub current_ip: Option<String>,
+    /// Steps to fix the mismatch.
+    pub hints: Vec<String>,
+    pub received_at: DateTime<Utc>,
+}
+
+impl RegionMismatch {
+    pub fn new(kind: MismatchKind, reason: impl ToString) -> Self {
+        Self {
+            kind,
+            event: None,
+            reason: reason.to_string(),
+            region: None,
+            session_ip: None,
+            current_ip: None,
+            hints: hints(kind),
+            received_at: Utc::now(),
+        }
+    }
+
+    /// Mismatch between the IP the session was created from and the one the client connects from,
+    /// `None` if they are the same.
+    pub fn from_ips(session_ip: &str, current_ip: &str) -> Option<Self> {
+        if session_ip == current_ip {
+            return None;
+        }
+        let mut mismatch = Self::new(
+            MismatchKind::Ip,
+            format!(
+                "the session was created from {session_ip} but the client connects from {current_ip}"
+            ),
+        );
+        mismatch.session_ip = Some(session_ip.to_string());
+        mismatch.current_ip = Some(current_ip.to_string());
+        Some(mismatch)
+    }
+
+    /// Parses a `42["event", data]` text frame, returns `None` if it isn't about a region or IP
+    /// mismatch.
+    pub fn from_text(text: &str) -> Option<Self> {
+        let (event, data): (String, Value) = serde_json::from_str(text.strip_prefix("42")?).ok()?;
+        let kind = mismatch_kind(&event)?;
+        let reason = ["message", "text", "msg", "reason", "error"]
+            .iter()
+            .find_map(|key| data.get(key)?.as_str().map(String::from))
+            .unwrap_or_else(|| format!("the server sent '{event}'"));
+        let mut mismatch = Self::new(kind, reason);
+        mismatch.region = region_of(&data);
+        mismatch.current_ip = ["ip", "current_ip", "currentIp"]
+            .iter()
+            .find_map(|key| data.get(key)?.as_str().map(String::from));
+        mismatch.event = Some(event);
+        Some(mismatch)
+    }
+
+    pub fn error(&self) -> PocketOptionError {
+        PocketOptionError::RegionMismatch {
+            kind: self.kind,
+            reason: self.reason.clone(),
+            hints: self.hints.clone(),
+        }
+    }
+}
+
+impl fmt::Display for RegionMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} mismatch, {}", self.kind, self.reason)
+    }
+}
+
+/// Profile of the account returned by `PocketOption::profile`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountProfile {
+    pub uid: u32,
+    pub demo: bool,
+    pub balance: f64,
+    /// IP the session was created from, `None` for the demo accounts.
+    pub session_ip: Option<String>,
+    pub user_agent: String,
+    /// Host of the websocket server the client is connected to.
+    pub server: Option<String>,
+    /// Region reported by the server, `None` until it sends one.
+    pub region: Option<String>,
+    /// Last region or IP mismatch, `None` if the server never reported one.
+    pub region_mismatch: Option<RegionMismatch>,
+}
+
+/// Region found in the payload of a server event, `None` if it has none.
+pub fn region_of(data: &Value) -> Option<String> {
+    REGION_KEYS.iter().find_map(|key| match data.get(key)? {
+        Value::String(region) if !region.is_empty() => Some(region.clone()),
+        Value::Object(region) => ["name", "code"]
+            .iter()
+            .find_map(|key| region.get(*key)?.as_str().map(String::from)),
+        _ => None,
+    })
+}
+
+/// Region reported by a `42["event", data]` text frame, `None` if it has none.
+pub fn reported_region(text: &str) -> Option<String> {
+    let (_, data): (String, Value) = serde_json::from_str(text.strip_prefix("42")?).ok()?;
+    region_of(&data)
+}
+
+/// Lowercase words of an event name, split on the separators and the case changes (`ipChanged`,
+/// `ip_changed` and `geo/restricted` are two words).
+fn words(event: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    let mut previous_lower = false;
+    for c in event.chars() {
+        if !c.is_ascii_alphanumeric() {
+            previous_lower = false;
+            words.push(String::new());
+            continue;
+        }
+        if (c.is_ascii_uppercase() && previous_lower) || words.is_empty() {
+            words.push(String::new());
+        }
+        previous_lower = c.is_ascii_lowercase();
+        if let Some(word) = words.last_mut() {
+            word.push(c.to_ascii_lowercase());
+        }
+    }
+    words.retain(|word| !word.is_empty());
+    words
+}
+
+fn mismatch_kind(event: &str) -> Option<MismatchKind> {
+    let words = words(event);
+    let has = |keywords: &[&str]| {
+        words
+            .iter()
+            .any(|word| keywords.iter().any(|k| word.contains(k)))
+    };
+    let mismatch = has(&MISMATCH_WORDS);
+    // "IPMismatch" is a single word
+    let ip = words.iter().any(|word| {
+        word == "ip"
+            || word
+                .strip_prefix("ip")
+                .is_some_and(|rest| MISMATCH_WORDS.iter().any(|k| rest.starts_with(k)))
+    });
+    if ip && mismatch {
+        Some(MismatchKind::Ip)
+    } else if has(&["region", "geo", "country"]) && (mismatch || has(&REFUSAL_WORDS)) {
+        Some(MismatchKind::Region)
+    } else {
+        None
+    }
+}
+
+fn hints(kind: MismatchKind) -> Vec<String> {
+    let mut hints = vec![
+        "log in the site from the new machine (or through the same VPN / proxy as the client) and copy a fresh SSID, the sessions of the real accounts are bound to the IP they were created from".to_string(),
+        "set a session provider so the session is renewed automatically after an IP change".to_string(),
+    ];
+    if kind == MismatchKind::Region {
+        hints.push(
+            "run the client from a region the account is allowed in, e.g. a VPS or a proxy in the country of the account"
+                .to_string(),
+        );
+    }
+    hints
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_region_mismatch() {
+        let mismatch = RegionMismatch::from_text(
+            r#"42["ipChanged",{"message":"Your IP address has changed","ip":"5.6.7.8"}]"#,
+        )
+        .unwrap();
+        assert_eq!(mismatch.kind, MismatchKind::Ip);
+        assert_eq!(mismatch.reason, "Your IP address has changed");
+        assert_eq!(mismatch.current_ip.as_deref(), Some("5.6.7.8"));
+        assert_eq!(mismatch.hints.len(), 2);
+
+        let mismatch =
+            RegionMismatch::from_text(r#"42["geo/restricted",{"region":{"code":"US"}}]"#).unwrap();
+        assert_eq!(mismatch.kind, MismatchKind::Region);
+        assert_eq!(mismatch.region.as_deref(), Some("US"));
+        assert_eq!(mismatch.hints.len(), 3);
+        assert!(matches!(
+            mismatch.error(),
+            PocketOptionError::RegionMismatch {
+                kind: MismatchKind::Region,
+                ..
+            }
+        ));
+
+        // Neither the other events nor the words merely containing "ip" are mismatches
+        assert!(RegionMismatch::from_text(r#"42["updateBalance",{"balance":1}]"#).is_none());
+        assert!(RegionMismatch::from_text(r#"42["tipChanged",{}]"#).is_none());
+        assert!(RegionMismatch::from_text(r#"42["IPMismatch",{}]"#).is_some());
+        assert!(RegionMismatch::from_text(r#"42["accountRestricted",{}]"#).is_none());
+
+        assert!(RegionMismatch::from_ips("1.2.3.4", "1.2.3.4").is_none());
+        let mismatch = RegionMismatch::from_ips("1.2.3.4", "5.6.7.8").unwrap();
+        assert_eq!(mismatch.session_ip.as_deref(), Some("1.2.3.4"));
+        assert_eq!(
+            reported_region(r#"42["updateProfile",{"country":"DE"}]"#).as_deref(),
+            Some("DE")
+        );
+        assert_eq!(region_of(&json!({"region": ""})), None);
+    }
+}
@@ -0,0 +1,343 @@
+//! Recent candles of the subscribed assets, kept in memory.
+//!
+//! The ticks of every subscribed asset are aggregated into candles of the period of the store, the
+//! last `capacity` candles of each asset are kept in a ring buffer. The strategies read their last
+//! candles (`get_recent_candles`) or a time range (`get_candles_between`) without a request to the
+//! server, and the candles survive the reconnections and the resubscriptions. The store can also be
+//! saved to a file and loaded back, so the candles survive the restarts (`persist_candle_store`).
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+    path::Path,
+    sync::Mutex,
+};
+
+use binary_options_tools_core::{
+    error::BinaryOptionsToolsError, utils::storage::create_parent_dirs,
+};
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::pocketoption::{
+    error::{PocketOptionError, PocketResult},
+    ws::candle_close::period_start,
+};
+
+use super::update::{DataCandle, UpdateStream, UpdateStreamItem};
+
+/// Serializes the saves of the stores (the periodic one and the one of `shutdown`), so an older
+/// snapshot never replaces a newer one.
+static SAVE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Period of the candles of the default store.
+pub const DEFAULT_STORE_PERIOD: TimeDelta = TimeDelta::seconds(60);
+/// Number of candles kept per asset by the default store.
+pub const DEFAULT_STORE_CAPACITY: usize = 500;
+/// Name of the file the store is saved to in the cache directory (see `storage`), by default.
+pub const CANDLE_STORE_FILE: &str = "candle_store.json";
+
+/// Candles kept for an asset by the store.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredAsset {
+    pub asset: String,
+    pub candles: usize,
+    pub first: Option<DateTime<Utc>>,
+    pub last: Option<DateTime<Utc>>,
+}
+
+/// Content of the file the store is saved to.
+#[derive(Debug, Serialize, Deserialize)]
+struct StoreFile {
+    /// Period of the candles, in seconds.
+    period: i64,
+    candles: HashMap<String, Vec<DataCandle>>,
+}
+
+/// Last candles of every asset whose ticks were recorded, the candle of the current period is the
+/// last one and keeps changing until the period ends.
+#[derive(Debug)]
+pub struct CandleStore {
+    period: TimeDelta,
+    capacity: usize,
+    candles: HashMap<String, VecDeque<DataCandle>>,
+}
+
+impl Default for CandleStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_STORE_PERIOD, DEFAULT_STORE_CAPACITY)
+    }
+}
+
+impl CandleStore {
+    pub fn new(period: TimeDelta, capacity: usize) -> Self {
+        Self {
+            period,
+            capacity: capacity.max(1),
+            candles: HashMap::new(),
+        }
+    }
+
+    pub fn period(&self) -> TimeDelta {
+        self.period
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Changes the period and the capacity of the store. A new period drops the candles, they can't
+    /// be converted, a smaller capacity only drops the oldest ones.
+    pub fn configure(&mut self, period: TimeDelta, capacity: usize) {
+        if period != self.period {
+            self.candles.clear();
+            self.period = period;
+        }
+        self.capacity = capacity.max(1);
+        for candles in self.candles.values_mut() {
+            while candles.len() > self.capacity {
+                candles.pop_front();
+            }
+        }
+    }
+
+    pub fn record_tick(&mut self, item: &UpdateStreamItem) {
+        let start = period_start(item.time, self.period);
+        let candles = self.candles.entry(item.active.clone()).or_default();
+        match candles.back_mut() {
+            Some(last) if last.time == start => {
+                last.high = last.high.max(item.price);
+                last.low = last.low.min(item.price);
+                last.close = item.price;
+                last.received_at = Some(item.received_at);
+                last.latency = Some(item.latency());
+            }
+            // A late tick of a period already closed in the store
+            Some(last) if last.time > start => {}
+            _ => {
+                candles.push_back(DataCandle {
+                    time: start,
+                    ..DataCandle::from(item)
+                });
+                if candles.len() > self.capacity {
+                    candles.pop_front();
+                }
+            }
+        }
+    }
+
+    pub fn record_stream(&mut self, stream: &UpdateStream) {
+        for item in stream.0.iter() {
+            self.record_tick(item);
+        }
+    }
+
+    /// The last `count` candles of `asset` sorted by time, fewer if the store doesn't have them yet.
+    pub fn recent(&self, asset: &str, count: usize) -> Vec<DataCandle> {
+        self.candles
+            .get(asset)
+            .map(|candles| {
+                let skip = candles.len().saturating_sub(count);
+                candles.iter().skip(skip).cloned().collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The candles of `asset` starting between `start` (included) and `end` (excluded), sorted by
+    /// time.
+    pub fn between(
+        &self,
+        asset: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Vec<DataCandle> {
+        self.candles
+            .get(asset)
+            .map(|candles| {
+                candles
+                    .iter()
+                    .filter(|candle| candle.time >= start && candle.time < end)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn assets(&self) -> Vec<StoredAsset> {
+        let mut assets: Vec<StoredAsset> = self
+            .candles
+            .iter()
+            .map(|(asset, candles)| StoredAsset {
+                asset: asset.clone(),
+                candles: candles.len(),
+                first: candles.front().map(|candle| candle.time),
+                last: candles.back().map(|candle| candle.time),
+            })
+            .collect();
+        assets.sort_by(|a, b| a.asset.cmp(&b.asset));
+        assets
+    }
+
+    /// Writes the candles to the file at `path`. The file is written next to it first, so a crash
+    /// while saving never leaves a truncated file.
+    pub fn save(&self, path: &Path) -> PocketResult<()> {
+        let _guard = SAVE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let file = StoreFile {
+            period: self.period.num_seconds(),
+            candles: self
+                .candles
+                .iter()
+                .map(|(asset, candles)| (asset.clone(), candles.iter().cloned().collect()))
+                .collect(),
+        };
+        let content = serde_json::to_vec(&file)
+            .map_err(|e| PocketOptionError::GeneralParsingError(e.to_string()))?;
+        create_parent_dirs(path).map_err(BinaryOptionsToolsError::from)?;
+        // Unique per save, so another process saving to the same file doesn't write to it
+        let mut temporary = path.to_path_buf().into_os_string();
+        temporary.push(format!(".{}.tmp", Uuid::new_v4()));
+        fs::write(&temporary, content).map_err(BinaryOptionsToolsError::from)?;
+        fs::rename(&temporary, path).map_err(BinaryOptionsToolsError::from)?;
+        Ok(())
+    }
+
+    /// Adds the candles saved in the file at `path` before the ones recorded since, and returns the
+    /// number of candles loaded. Nothing is loaded if the file doesn't exist or was saved with
+    /// another period, its candles can't be converted.
+    pub fn load(&mut self, path: &Path) -> PocketResult<usize> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(BinaryOptionsToolsError::from(e).into()),
+        };
+        let file: StoreFile = serde_json::from_str(&content).map_err(|e| {
+            PocketOptionError::GeneralParsingError(format!(
+                "Invalid candle store file '{}', {e}",
+                path.display()
+            ))
+        })?;
+        if file.period != self.period.num_seconds() {
+            return Ok(0);
+        }
+        let mut loaded = 0;
+        for (asset, saved) in file.candles {
+            let candles = self.candles.entry(asset).or_default();
+            let before = candles.len();
+            let first = candles.front().map(|candle| candle.time);
+            for candle in saved
+                .into_iter()
+                .rev()
+                .filter(|candle| first.is_none_or(|first| candle.time < first))
+            {
+                candles.push_front(candle);
+            }
+            while candles.len() > self.capacity {
+                candles.pop_front();
+            }
+            loaded += candles.len().saturating_sub(before);
+        }
+        Ok(loaded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick(asset: &str, millis: i64, price: f64) -> UpdateStreamItem {
+        UpdateStreamItem {
+            active: asset.to_string(),
+            time: DateTime::from_timestamp_millis(millis).unwrap(),
+            price,
+            received_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_candle_store() {
+        let mut store = CandleStore::new(TimeDelta::seconds(60), 3);
+        for (millis, price) in [(0, 1.0), (30_000, 1.5), (59_999, 0.5), (60_000, 2.0)] {
+            store.record_tick(&tick("EURUSD_otc", millis, price));
+        }
+        let candles = store.recent("EURUSD_otc", 50);
+        assert_eq!(candles.len(), 2);
+        assert_eq!(
+            (
+                candles[0].open,
+                candles[0].high,
+                candles[0].low,
+                candles[0].close
+            ),
+            (1.0, 1.5, 0.5, 0.5)
+        );
+        assert_eq!(candles[1].time.timestamp(), 60);
+
+        // A late tick doesn't reopen a previous candle
+        store.record_tick(&tick("EURUSD_otc", 10_000, 9.0));
+        assert_eq!(store.recent("EURUSD_otc", 2)[0].high, 1.5);
+
+        // Only the last `capacity` candles are kept
+        for minute in 2..5 {
+            store.record_tick(&tick("EURUSD_otc", minute * 60_000, 3.0));
+        }
+        let candles = store.recent("EURUSD_otc", 10);
+        assert_eq!(candles.len(), 3);
+        assert_eq!(candles[0].time.timestamp(), 120);
+        assert_eq!(store.recent("EURUSD_otc", 1)[0].time.timestamp(), 240);
+        assert!(store.recent("GBPUSD_otc", 10).is_empty());
+
+        let at = |seconds| DateTime::from_timestamp(seconds, 0).unwrap();
+        let candles = store.between("EURUSD_otc", at(180), at(240));
+        assert_eq!(candles.len(), 1);
+        assert_eq!(candles[0].time, at(180));
+        assert_eq!(store.assets()[0].last, Some(at(240)));
+
+        // A new period drops the candles, a smaller capacity the oldest ones
+        store.configure(TimeDelta::seconds(60), 1);
+        assert_eq!(store.recent("EURUSD_otc", 10).len(), 1);
+        store.configure(TimeDelta::seconds(5), 10);
+        assert!(store.assets().is_empty());
+    }
+
+    #[test]
+    fn test_candle_store_file() -> anyhow::Result<()> {
+        let path = std::env::temp_dir().join(format!("candle_store-{}.json", uuid::Uuid::new_v4()));
+        let mut store = CandleStore::new(TimeDelta::seconds(60), 3);
+        assert_eq!(store.load(&path)?, 0);
+        for minute in 0..3 {
+            store.record_tick(&tick("EURUSD_otc", minute * 60_000, 1.0 + minute as f64));
+        }
+        // Concurrent saves don't write to the same temporary file, and leave none behind
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| store.save(&path).unwrap());
+            }
+        });
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        let leftovers = fs::read_dir(std::env::temp_dir())?
+            .filter_map(|e| e.ok())
+            .filter(|e| {
+                let file = e.file_name().to_string_lossy().into_owned();
+                file.starts_with(&name) && file.ends_with(".tmp")
+            })
+            .count();
+        assert_eq!(leftovers, 0);
+
+        // After a restart the saved candles come before the ones recorded since, up to the capacity
+        let mut restarted = CandleStore::new(TimeDelta::seconds(60), 3);
+        restarted.record_tick(&tick("EURUSD_otc", 3 * 60_000, 4.0));
+        assert_eq!(restarted.load(&path)?, 2);
+        let candles = restarted.recent("EURUSD_otc", 10);
+        let times: Vec<i64> = candles.iter().map(|c| c.time.timestamp()).collect();
+        assert_eq!(times, vec![60, 120, 180]);
+        assert_eq!(candles[0].close, 2.0);
+
+        // A file saved with another period is ignored
+        let mut other = CandleStore::new(TimeDelta::seconds(5), 3);
+        assert_eq!(other.load(&path)?, 0);
+        assert!(other.assets().is_empty());
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,559 @@
+//! Limit of simultaneous chart subscriptions of the server.
+//!
+//! The server only streams a limited number of assets at once, past that limit it accepts the
+//! subscription frames but never sends a tick for the new asset. The limit is either configured or
+//! detected (a new subscription stays silent while the older ones keep ticking), and once it's known
+//! the surplus subscriptions are queued until a slot is released, or the least recently used
+//! subscription is rotated out to make room for them.
+//!
+//! In power saving mode the client drops every subscription once no stream consumed the ticks for a
+//! while, the assets are subscribed again when a stream is requested for them.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_channel::{Sender, bounded};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use binary_options_tools_core::{constants::MAX_CHANNEL_CAPACITY, general::stream::RecieverStream};
+
+use crate::pocketoption::parser::message::WebSocketMessage;
+
+use super::{base::ChangeSymbol, update::UpdateStream};
+
+/// Time without any tick after which a new subscription is considered rejected by the server.
+pub const DETECTION_DELAY: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SubscriptionPolicy {
+    /// Maximum number of simultaneous subscriptions, detected from the silent subscriptions if `None`.
+    pub max_subscriptions: Option<usize>,
+    /// Once the limit is reached, unsubscribes the least recently used asset to make room for the
+    /// new one instead of queuing the new one until a slot is released.
+    pub rotate: bool,
+    /// Time without any tick after which a new subscription is considered rejected by the server.
+    pub detection_delay: Duration,
+}
+
+impl Default for SubscriptionPolicy {
+    fn default() -> Self {
+        Self {
+            max_subscriptions: None,
+            rotate: true,
+            detection_delay: DETECTION_DELAY,
+        }
+    }
+}
+
+/// Frames to send to the server after a change of the subscriptions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubscriptionAction {
+    Subscribe(String),
+    Unsubscribe(String),
+}
+
+impl SubscriptionAction {
+    /// The server expects 3 messages to subscribe to an asset: changeSymbol, unsubfor and subfor.
+    pub fn messages(&self) -> Vec<WebSocketMessage> {
+        match self {
+            Self::Subscribe(asset) => vec![
+                WebSocketMessage::ChangeSymbol(ChangeSymbol::new(asset.clone(), 1)),
+                WebSocketMessage::Unsubfor(asset.clone()),
+                WebSocketMessage::Subfor(asset.clone()),
+            ],
+            Self::Unsubscribe(asset) => vec![WebSocketMessage::Unsubfor(asset.clone())],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum SubscriptionEvent {
+    /// The asset is subscribed and should start ticking.
+    Subscribed { asset: String },
+    /// The limit is reached, the asset is subscribed once a slot is released.
+    Queued { asset: String, position: usize },
+    /// A queued asset got a slot and is now subscribed.
+    Activated { asset: String },
+    /// The least recently used asset was unsubscribed to make room for a new one, its streams stop
+    /// recieving ticks.
+    Evicted { asset: String, replaced_by: String },
+    /// The asset was unsubscribed (or removed from the queue) by the user.
+    Unsubscribed { asset: String },
+    /// Subscriptions that never recieved a tick while the older ones kept ticking revealed the limit
+    /// of the server.
+    LimitDetected { limit: usize, silent: Vec<String> },
+    /// No stream read the ticks for the idle time of the power saving mode, the assets (subscribed
+    /// or queued) were unsubscribed until they are requested again.
+    Suspended { assets: Vec<String> },
+}
+
+/// Subscriptions at a point in time.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SubscriptionStatus {
+    /// Subscribed assets, from the least to the most recently used.
+    pub active: Vec<String>,
+    /// Assets waiting for a slot, in order.
+    pub queued: Vec<String>,
+    /// Limit in use, configured or detected.
+    pub limit: Option<usize>,
+    /// Whether the limit was detected from the silent subscriptions.
+    pub detected: bool,
+    /// Assets unsubscribed by the power saving mode and not requested since.
+    #[serde(default)]
+    pub suspended: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    subscribed_at: Instant,
+    last_used: Instant,
+    last_tick: Option<Instant>,
+}
+
+impl Slot {
+    fn new(now: Instant) -> Self {
+        Self {
+            subscribed_at: now,
+            last_used: now,
+            last_tick: None,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Changes {
+    actions: Vec<SubscriptionAction>,
+    events: Vec<SubscriptionEvent>,
+}
+
+#[derive(Debug, Default)]
+struct SubscriptionState {
+    policy: SubscriptionPolicy,
+    active: HashMap<String, Slot>,
+    queued: VecDeque<String>,
+    detected: Option<usize>,
+    suspended: Vec<String>,
+}
+
+impl SubscriptionState {
+    fn limit(&self) -> Option<usize> {
+        self.policy.max_subscriptions.or(self.detected)
+    }
+
+    fn is_full(&self) -> bool {
+        self.limit().is_some_and(|limit| self.active.len() >= limit)
+    }
+
+    fn least_recently_used(&self) -> Option<String> {
+        self.active
+            .iter()
+            .min_by_key(|(_, slot)| slot.last_used)
+            .map(|(asset, _)| asset.clone())
+    }
+
+    fn activate(&mut self, asset: String, now: Instant, changes: &mut Changes) {
+        self.active.insert(asset.clone(), Slot::new(now));
+        changes.actions.push(SubscriptionAction::Subscribe(asset));
+    }
+
+    /// Subscribes to `asset` if there is a slot for it, rotating or queuing it otherwise.
+    fn request(&mut self, asset: String, now: Instant, changes: &mut Changes) {
+        self.suspended.retain(|a| a != &asset);
+        if let Some(slot) = self.active.get_mut(&asset) {
+            // Already subscribed, the frames are sent again like before the limit was handled
+            slot.last_used = now;
+            changes.actions.push(SubscriptionAction::Subscribe(asset));
+            return;
+        }
+        if self.queued.contains(&asset) {
+            return;
+        }
+        if self.is_full() {
+            match self.least_recently_used().filter(|_| self.policy.rotate) {
+                Some(evicted) => {
+                    self.active.remove(&evicted);
+                    changes
+                        .actions
+                        .push(SubscriptionAction::Unsubscribe(evicted.clone()));
+                    changes.events.push(SubscriptionEvent::Evicted {
+                        asset: evicted,
+                        replaced_by: asset.clone(),
+                    });
+                }
+                None => {
+                    self.queued.push_back(asset.clone());
+                    changes.events.push(SubscriptionEvent::Queued {
+                        asset,
+                        position: self.queued.len() - 1,
+                    });
+                    return;
+                }
+            }
+        }
+        changes.events.push(SubscriptionEvent::Subscribed {
+            asset: asset.clone(),
+        });
+        self.activate(asset, now, changes);
+    }
+
+    fn unsubscribe(&mut self, asset: &str, now: Instant, changes: &mut Changes) {
+        if self.active.remove(asset).is_some() {
+            changes
+                .actions
+                .push(SubscriptionAction::Unsubscribe(asset.to_string()));
+        } else if let Some(position) = self.queued.iter().position(|a| a == asset) {
+            self.queued.remove(position);
+        } else {
+            return;
+        }
+        changes.events.push(SubscriptionEvent::Unsubscribed {
+            asset: asset.to_string(),
+        });
+        self.fill(now, changes);
+    }
+
+    /// Subscribes to the queued assets while there are free slots.
+    fn fill(&mut self, now: Instant, changes: &mut Changes) {
+        while !self.is_full() {
+            let Some(asset) = self.queued.pop_front() else {
+                break;
+            };
+            changes.events.push(SubscriptionEvent::Activated {
+                asset: asset.clone(),
+            });
+            self.activate(asset, now, changes);
+        }
+    }
+
+    /// Unsubscribes every asset and empties the queue, the assets are kept as suspended.
+    fn suspend(&mut self, changes: &mut Changes) {
+        let mut active: Vec<(String, Slot)> = self.active.drain().collect();
+        if active.is_empty() && self.queued.is_empty() {
+            return;
+        }
+        active.sort_by_key(|(_, slot)| slot.last_used);
+        let mut assets = Vec::with_capacity(active.len() + self.queued.len());
+        for (asset, _) in active {
+            changes
+                .actions
+                .push(SubscriptionAction::Unsubscribe(asset.clone()));
+            assets.push(asset);
+        }
+        assets.extend(self.queued.drain(..));
+        for asset in assets.iter() {
+            if !self.suspended.contains(asset) {
+                self.suspended.push(asset.clone());
+            }
+        }
+        changes.events.push(SubscriptionEvent::Suspended { assets });
+    }
+
+    fn set_policy(&mut self, policy: SubscriptionPolicy, now: Instant, changes: &mut Changes) {
+        self.policy = policy;
+        self.fill(now, changes);
+    }
+
+    fn record_ticks<'a>(
+        &mut self,
+        assets: impl IntoIterator<Item = &'a str>,
+        now: Instant,
+        changes: &mut Changes,
+    ) {
+        for asset in assets {
+            if let Some(slot) = self.active.get_mut(asset) {
+                slot.last_tick = Some(now);
+            }
+        }
+        self.detect(now, changes);
+    }
+
+    /// Detects the limit from the subscriptions that never ticked while an older subscription
+    /// ticked since, which means the server is streaming but refused them.
+    fn detect(&mut self, now: Instant, changes: &mut Changes) {
+        if self.limit().is_some() {
+            return;
+        }
+        let mut silent: Vec<(String, Instant)> = self
+            .active
+            .iter()
+            .filter(|(_, slot)| {
+                slot.last_tick.is_none()
+                    && now.duration_since(slot.subscribed_at) >= self.policy.detection_delay
+                    && self.active.values().any(|other| {
+                        other
+                            .last_tick
+                            .is_some_and(|tick| tick > slot.subscribed_at)
+                    })
+            })
+            .map(|(asset, slot)| (asset.clone(), slot.subscribed_at))
+            .collect();
+        if silent.is_empty() {
+            return;
+        }
+        silent.sort_by_key(|(_, subscribed_at)| *subscribed_at);
+        let silent: Vec<String> = silent.into_iter().map(|(asset, _)| asset).collect();
+        let limit = self.active.len() - silent.len();
+        self.detected = Some(limit);
+        changes.events.push(SubscriptionEvent::LimitDetected {
+            limit,
+            silent: silent.clone(),
+        });
+        for asset in silent.iter() {
+            self.active.remove(asset);
+            changes
+                .actions
+                .push(SubscriptionAction::Unsubscribe(asset.clone()));
+        }
+        // The refused subscriptions are the most recent requests, they go through the policy again
+        for asset in silent {
+            self.request(asset, now, changes);
+        }
+    }
+
+    fn status(&self) -> SubscriptionStatus {
+        let mut active: Vec<(&String, &Slot)> = self.active.iter().collect();
+        active.sort_by_key(|(_, slot)| slot.last_used);
+        SubscriptionStatus {
+            active: active.into_iter().map(|(asset, _)| asset.clone()).collect(),
+            queued: self.queued.iter().cloned().collect(),
+            limit: self.limit(),
+            detected: self.policy.max_subscriptions.is_none() && self.detected.is_some(),
+            suspended: self.suspended.clone(),
+        }
+    }
+}
+
+/// Shared between the message handler and the client, keeps track of the subscriptions and sends
+/// the changes to the subscribers of `subscribe_subscription_events`.
+#[derive(Debug, Clone, Default)]
+pub struct Subscriptions {
+    state: Arc<Mutex<SubscriptionState>>,
+    subscribers: Arc<Mutex<Vec<Sender<SubscriptionEvent>>>>,
+}
+
+impl Subscriptions {
+    /// Every subscriber receives all the events, the events of a subscriber that stopped reading
+    /// are dropped once its channel is full.
+    pub fn subscribe(&self, timeout: Option<Duration>) -> RecieverStream<SubscriptionEvent> {
+        let (sender, receiver) = bounded(MAX_CHANNEL_CAPACITY);
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.push(sender);
+        }
+        RecieverStream::new_timed(receiver, timeout)
+    }
+
+    /// Ends the streams of every subscriber, used when the client shuts down.
+    pub fn close(&self) {
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            for subscriber in subscribers.drain(..) {
+                subscriber.close();
+            }
+        }
+    }
+
+    fn apply(
+        &self,
+        change: impl FnOnce(&mut SubscriptionState, &mut Changes),
+    ) -> Vec<SubscriptionAction> {
+        let mut changes = Changes::default();
+        match self.state.lock() {
+            Ok(mut state) => change(&mut state, &mut changes),
+            Err(e) => warn!(target: "Subscriptions", "Subscriptions state poisoned, {e}"),
+        }
+        if !changes.events.is_empty() {
+            self.send(changes.events);
+        }
+        changes.actions
+    }
+
+    fn send(&self, events: Vec<SubscriptionEvent>) {
+        for event in events.iter() {
+            match event {
+                SubscriptionEvent::LimitDetected { limit, silent } => warn!(
+                    target: "Subscriptions",
+                    "The server streams at most {limit} assets at once, no tick recieved for {silent:?}"
+                ),
+                event => info!(target: "Subscriptions", "{event:?}"),
+            }
+        }
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.retain(|s| !s.is_closed());
+            for subscriber in subscribers.iter() {
+                for event in events.iter() {
+                    let _ = subscriber.try_send(event.clone());
+                }
+            }
+        }
+    }
+
+    /// Returns the frames to send to subscribe to `asset`, none if it's queued.
+    pub fn request(&self, asset: impl ToString) -> Vec<SubscriptionAction> {
+        self.apply(|state, changes| state.request(asset.to_string(), Instant::now(), changes))
+    }
+
+    /// Returns the frames to send to unsubscribe from `asset` and subscribe to the queued assets
+    /// taking its slot.
+    pub fn unsubscribe(&self, asset: &str) -> Vec<SubscriptionAction> {
+        self.apply(|state, changes| state.unsubscribe(asset, Instant::now(), changes))
+    }
+
+    /// Returns the frames to send to unsubscribe from every asset, for the power saving mode.
+    pub fn suspend(&self) -> Vec<SubscriptionAction> {
+        self.apply(|state, changes| state.suspend(changes))
+    }
+
+    pub fn set_policy(&self, policy: SubscriptionPolicy) -> Vec<SubscriptionAction> {
+        self.apply(|state, changes| state.set_policy(policy, Instant::now(), changes))
+    }
+
+    pub fn policy(&self) -> SubscriptionPolicy {
+        self.state
+            .lock()
+            .map(|state| state.policy)
+            .unwrap_or_default()
+    }
+
+    /// Records the ticks of the stream, returning the frames to send if they revealed the limit.
+    pub fn record_stream(&self, stream: &UpdateStream) -> Vec<SubscriptionAction> {
+        let assets = stream.0.iter().map(|item| item.active.as_str());
+        self.apply(|state, changes| state.record_ticks(assets, Instant::now(), changes))
+    }
+
+    /// Subscribed assets to send again after a reconnection, their detection timers restart.
+    pub fn resubscribe(&self) -> Vec<String> {
+        let now = Instant::now();
+        self.state
+            .lock()
+            .map(|mut state| {
+                state
+                    .active
+                    .iter_mut()
+                    .map(|(asset, slot)| {
+                        *slot = Slot {
+                            last_used: slot.last_used,
+                            ..Slot::new(now)
+                        };
+                        asset.clone()
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn status(&self) -> SubscriptionStatus {
+        self.state
+            .lock()
+            .map(|state| state.status())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscription_limit() {
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let mut state = SubscriptionState::default();
+        let mut changes = Changes::default();
+        for (i, asset) in ["A", "B", "C"].into_iter().enumerate() {
+            state.request(asset.into(), at(i as u64), &mut changes);
+        }
+        assert_eq!(changes.actions.len(), 3);
+
+        // "C" stays silent while the older subscriptions keep ticking: the limit is 2
+        state.record_ticks(["A", "B"], at(5), &mut Changes::default());
+        let mut changes = Changes::default();
+        state.record_ticks(["A"], at(13), &mut changes);
+        assert_eq!(
+            changes.events[0],
+            SubscriptionEvent::LimitDetected {
+                limit: 2,
+                silent: vec!["C".into()]
+            }
+        );
+        // Rotation: "A" is the least recently used, it makes room for "C"
+        assert_eq!(
+            changes.actions,
+            vec![
+                SubscriptionAction::Unsubscribe("C".into()),
+                SubscriptionAction::Unsubscribe("A".into()),
+                SubscriptionAction::Subscribe("C".into()),
+            ]
+        );
+        assert_eq!(state.status().active, vec!["B", "C"]);
+
+        // Without rotation the surplus is queued until a slot is released
+        state.policy.rotate = false;
+        let mut changes = Changes::default();
+        state.request("D".into(), at(14), &mut changes);
+        assert!(changes.actions.is_empty());
+        assert_eq!(state.status().queued, vec!["D"]);
+        let mut changes = Changes::default();
+        state.unsubscribe("B", at(15), &mut changes);
+        assert_eq!(
+            changes.actions,
+            vec![
+                SubscriptionAction::Unsubscribe("B".into()),
+                SubscriptionAction::Subscribe("D".into()),
+            ]
+        );
+        assert_eq!(
+            changes.events[1],
+            SubscriptionEvent::Activated { asset: "D".into() }
+        );
+        assert!(state.status().detected);
+    }
+
+    #[test]
+    fn test_subscriptions_suspend() {
+        let start = Instant::now();
+        let mut state = SubscriptionState {
+            policy: SubscriptionPolicy {
+                max_subscriptions: Some(1),
+                rotate: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        state.request("A".into(), start, &mut Changes::default());
+        state.request("B".into(), start, &mut Changes::default());
+
+        let mut changes = Changes::default();
+        state.suspend(&mut changes);
+        assert_eq!(
+            changes.actions,
+            vec![SubscriptionAction::Unsubscribe("A".into())]
+        );
+        assert_eq!(
+            changes.events,
+            vec![SubscriptionEvent::Suspended {
+                assets: vec!["A".into(), "B".into()]
+            }]
+        );
+        let status = state.status();
+        assert!(status.active.is_empty() && status.queued.is_empty());
+        assert_eq!(status.suspended, vec!["A", "B"]);
+
+        // Nothing left to unsubscribe, no event
+        let mut changes = Changes::default();
+        state.suspend(&mut changes);
+        assert!(changes.events.is_empty());
+
+        // Requesting an asset again subscribes it and only it
+        let mut changes = Changes::default();
+        state.request("B".into(), start, &mut changes);
+        assert_eq!(
+            changes.actions,
+            vec![SubscriptionAction::Subscribe("B".into())]
+        );
+        assert_eq!(state.status().suspended, vec!["A"]);
+    }
+}
@@ -0,0 +1,246 @@
+//! Candle periods supported by the server, so a period is validated once instead of being sent as any
+//! integer and silently answered with no candles.
+
+use core::fmt;
+use std::{str::FromStr, time::Duration};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::pocketoption::error::{PocketOptionError, PocketResult};
+
+/// Period of the candles, every variant is a timeframe of the charts of the platform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Timeframe {
+    S5,
+    S10,
+    S15,
+    S30,
+    M1,
+    M2,
+    M3,
+    M5,
+    M10,
+    M15,
+    M30,
+    H1,
+    H4,
+    D1,
+}
+
+impl Timeframe {
+    pub const ALL: [Timeframe; 14] = [
+        Self::S5,
+        Self::S10,
+        Self::S15,
+        Self::S30,
+        Self::M1,
+        Self::M2,
+        Self::M3,
+        Self::M5,
+        Self::M10,
+        Self::M15,
+        Self::M30,
+        Self::H1,
+        Self::H4,
+        Self::D1,
+    ];
+
+    pub const fn seconds(self) -> i64 {
+        match self {
+            Self::S5 => 5,
+            Self::S10 => 10,
+            Self::S15 => 15,
+            Self::S30 => 30,
+            Self::M1 => 60,
+            Self::M2 => 120,
+            Self::M3 => 180,
+            Self::M5 => 300,
+            Self::M10 => 600,
+            Self::M15 => 900,
+            Self::M30 => 1800,
+            Self::H1 => 3600,
+            Self::H4 => 14400,
+            Self::D1 => 86400,
+        }
+    }
+
+    pub fn duration(self) -> Duration {
+        Duration::from_secs(self.seconds() as u64)
+    }
+
+    /// Returns the timeframe of `seconds`, or `PocketOptionError::InvalidTimeframe` if the server
+    /// doesn't support it.
+    pub fn from_seconds(seconds: i64) -> PocketResult<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|t| t.seconds() == seconds)
+            .ok_or_else(|| PocketOptionError::InvalidTimeframe(seconds.to_string()))
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::S5 => "S5",
+            Self::S10 => "S10",
+            Self::S15 => "S15",
+            Self::S30 => "S30",
+            Self::M1 => "M1",
+            Self::M2 => "M2",
+            Self::M3 => "M3",
+            Self::M5 => "M5",
+            Self::M10 => "M10",
+            Self::M15 => "M15",
+            Self::M30 => "M30",
+            Self::H1 => "H1",
+            Self::H4 => "H4",
+            Self::D1 => "D1",
+        }
+    }
+}
+
+impl fmt::Display for Timeframe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parses the name of the timeframe (`"M1"`), a duration (`"1m"`, `"30s"`, `"4h"`, `"1d"`) or a number of seconds.
+impl FromStr for Timeframe {
+    type Err = PocketOptionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = s.trim();
+        if let Some(timeframe) = Self::ALL
+            .into_iter()
+            .find(|t| t.name().eq_ignore_ascii_case(value))
+        {
+            return Ok(timeframe);
+        }
+        let invalid = || PocketOptionError::InvalidTimeframe(s.to_string());
+        let split = value
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(value.len());
+        let (amount, unit) = value.split_at(split);
+        let amount: i64 = amount.parse().map_err(|_| invalid())?;
+        let multiplier = match unit.to_ascii_lowercase().as_str() {
+            "" | "s" => 1,
+            "m" => 60,
+            "h" => 3600,
+            "d" => 86400,
+            _ => return Err(invalid()),
+        };
+        amount
+            .checked_mul(multiplier)
+            .and_then(|seconds| Self::from_seconds(seconds).ok())
+            .ok_or_else(invalid)
+    }
+}
+
+impl TryFrom<i64> for Timeframe {
+    type Error = PocketOptionError;
+
+    fn try_from(seconds: i64) -> Result<Self, Self::Error> {
+        Self::from_seconds(seconds)
+    }
+}
+
+impl From<Timeframe> for i64 {
+    fn from(timeframe: Timeframe) -> Self {
+        timeframe.seconds()
+    }
+}
+
+/// Serialized as the number of seconds, like the periods sent by the server, and deserialized from the
+/// seconds or any string accepted by `FromStr`.
+impl Serialize for Timeframe {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(self.seconds())
+    }
+}
+
+impl<'de> Deserialize<'de> for Timeframe {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Seconds(i64),
+            Text(String),
+        }
+        match Raw::deserialize(deserializer)? {
+            Raw::Seconds(seconds) => Self::from_seconds(seconds),
+            Raw::Text(text) => text.parse(),
+        }
+        .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Anything that can be used as the period of the candles: a `Timeframe`, a number of seconds or a `Duration`.
+/// Numbers and durations are validated against the timeframes supported by the server.
+pub trait IntoTimeframe {
+    fn into_timeframe(self) -> PocketResult<Timeframe>;
+}
+
+impl IntoTimeframe for Timeframe {
+    fn into_timeframe(self) -> PocketResult<Timeframe> {
+        Ok(self)
+    }
+}
+
+impl IntoTimeframe for Duration {
+    fn into_timeframe(self) -> PocketResult<Timeframe> {
+        if self.subsec_nanos() != 0 {
+            return Err(PocketOptionError::InvalidTimeframe(format!("{self:?}")));
+        }
+        Timeframe::from_seconds(self.as_secs() as i64)
+    }
+}
+
+impl IntoTimeframe for &str {
+    fn into_timeframe(self) -> PocketResult<Timeframe> {
+        self.parse()
+    }
+}
+
+macro_rules! impl_into_timeframe {
+    ($($int:ty),*) => {
+        $(
+            impl IntoTimeframe for $int {
+                fn into_timeframe(self) -> PocketResult<Timeframe> {
+                    i64::try_from(self)
+                        .map_err(|_| PocketOptionError::InvalidTimeframe(self.to_string()))
+                        .and_then(Timeframe::from_seconds)
+                }
+            }
+        )*
+    };
+}
+
+impl_into_timeframe!(i32, i64, u32, u64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeframe_parsing() -> anyhow::Result<()> {
+        assert_eq!(Timeframe::from_seconds(60)?, Timeframe::M1);
+        assert!(Timeframe::from_seconds(45).is_err());
+        assert_eq!("m5".parse::<Timeframe>()?, Timeframe::M5);
+        assert_eq!("4h".parse::<Timeframe>()?, Timeframe::H4);
+        assert_eq!("30".parse::<Timeframe>()?, Timeframe::S30);
+        assert!("7m".parse::<Timeframe>().is_err());
+        assert!("1w".parse::<Timeframe>().is_err());
+        assert_eq!(300u32.into_timeframe()?, Timeframe::M5);
+        assert_eq!(Duration::from_secs(900).into_timeframe()?, Timeframe::M15);
+        assert!(Duration::from_millis(1500).into_timeframe().is_err());
+        assert!((-60i64).into_timeframe().is_err());
+
+        assert_eq!(serde_json::to_string(&Timeframe::H1)?, "3600");
+        assert_eq!(
+            serde_json::from_str::<Timeframe>("\"S15\"")?,
+            Timeframe::S15
+        );
+        assert_eq!(serde_json::from_str::<Timeframe>("86400")?, Timeframe::D1);
+        assert!(serde_json::from_str::<Timeframe>("61").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_channel::{Sender, bounded};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use binary_options_tools_core::{constants::MAX_CHANNEL_CAPACITY, general::stream::RecieverStream};
+
+/// Message recieved from the server that couldn't be parsed by any of the models.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnparsedMessage {
+    /// Type of message the payload was expected to be (the event of the previous header), if any.
+    pub event: Option<String>,
+    /// Raw payload as recieved.
+    pub payload: String,
+    /// Parsing error.
+    pub error: String,
+    pub received_at: DateTime<Utc>,
+}
+
+impl UnparsedMessage {
+    pub fn new(event: Option<String>, payload: impl ToString, error: impl ToString) -> Self {
+        Self {
+            event,
+            payload: payload.to_string(),
+            error: error.to_string(),
+            received_at: Utc::now(),
+        }
+    }
+}
+
+/// Shared between the message handler and the client, forwards every message that failed to
+/// parse to the subscribers of `subscribe_unparsed`.
+#[derive(Debug, Clone, Default)]
+pub struct Quarantine {
+    subscribers: Arc<Mutex<Vec<Sender<UnparsedMessage>>>>,
+}
+
+impl Quarantine {
+    /// Every subscriber receives all the unparsed messages, the messages of a subscriber that
+    /// stopped reading are dropped once its channel is full.
+    pub fn subscribe(&self, timeout: Option<Duration>) -> RecieverStream<UnparsedMessage> {
+        let (sender, receiver) = bounded(MAX_CHANNEL_CAPACITY);
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.push(sender);
+        }
+        RecieverStream::new_timed(receiver, timeout)
+    }
+
+    /// Ends the streams of every subscriber, used when the client shuts down.
+    pub fn close(&self) {
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            for subscriber in subscribers.drain(..) {
+                subscriber.close();
+            }
+        }
+    }
+
+    pub fn send(&self, message: UnparsedMessage) {
+        warn!(
+            target: "Unparsed",
+            "Failed to parse message of type '{}', {}:\n {}",
+            message.event.as_deref().unwrap_or("unknown"),
+            message.error,
+            message.payload
+        );
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.retain(|s| !s.is_closed());
+            for subscriber in subscribers.iter() {
+                let _ = subscriber.try_send(message.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_quarantine() -> anyhow::Result<()> {
+        let quarantine = Quarantine::default();
+        let unparsed = quarantine.subscribe(Some(Duration::from_secs(1)));
+        let dropped = quarantine.subscribe(None);
+        drop(dropped);
+        quarantine.send(UnparsedMessage::new(
+            Some("updateStream".into()),
+            r#"[["EURUSD_otc"]]"#,
+            "invalid length 1",
+        ));
+        let mut stream = unparsed.to_stream();
+        let message = stream.next().await.unwrap()?;
+        assert_eq!(message.event.as_deref(), Some("updateStream"));
+        assert_eq!(message.payload, r#"[["EURUSD_otc"]]"#);
+        assert_eq!(quarantine.subscribers.lock().unwrap().len(), 1);
+        Ok(())
+    }
+}
@@ -3,7 +3,11 @@ use core::fmt;
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::pocketoption::error::PocketOptionError;
+use binary_options_tools_core::assets::is_otc_symbol;
+
+use crate::pocketoption::{error::PocketOptionError, utils::precision::Precision};
+
+use super::numeric;
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct UpdateStream(pub Vec<UpdateStreamItem>);
@@ -13,12 +17,28 @@ pub struct UpdateStreamItem {
     pub active: String,
     #[serde(with = "float_time")]
     pub time: DateTime<Utc>,
+    #[serde(deserialize_with = "numeric::f64")]
     pub price: f64,
+    /// Local time the message was recieved (parsed) at.
+    #[serde(skip_deserializing, default = "Utc::now")]
+    pub received_at: DateTime<Utc>,
+}
+
+impl UpdateStreamItem {
+    /// Milliseconds between the server timestamp of the item and the moment it was recieved,
+    /// includes the offset between the server and the local clocks.
+    pub fn latency(&self) -> f64 {
+        (self.received_at - self.time)
+            .num_microseconds()
+            .map(|us| us as f64 / 1000.0)
+            .unwrap_or(f64::MAX)
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct UpdateHistoryNewFast {
     pub asset: String,
+    #[serde(deserialize_with = "numeric::integer")]
     pub period: i64,
     pub history: Vec<Candle>,
 }
@@ -44,6 +64,7 @@ pub struct RawCandle {
     asset: String,
     #[serde(with = "float_time")]
     time: DateTime<Utc>,
+    #[serde(deserialize_with = "numeric::f64")]
     price: f64,
 }
 
@@ -52,9 +73,13 @@ pub struct ProcessedCandle {
     symbol_id: i32,
     #[serde(with = "float_time")]
     time: DateTime<Utc>,
+    #[serde(deserialize_with = "numeric::f64")]
     open: f64,
+    #[serde(deserialize_with = "numeric::f64")]
     close: f64,
+    #[serde(deserialize_with = "numeric::f64")]
     high: f64,
+    #[serde(deserialize_with = "numeric::f64")]
     low: f64,
     asset: String,
 }
@@ -66,12 +91,19 @@ pub struct DataCandle {
     pub close: f64,
     pub high: f64,
     pub low: f64,
+    /// Local time the last update of the candle was recieved at, only set for streamed candles.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub received_at: Option<DateTime<Utc>>,
+    /// Latency in milliseconds of the last update of the candle (see `UpdateStreamItem::latency`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency: Option<f64>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct UpdateCandle {
     #[serde(with = "float_time")]
     time: DateTime<Utc>,
+    #[serde(deserialize_with = "numeric::f64")]
     price: f64,
 }
 
@@ -79,6 +111,7 @@ pub struct UpdateCandle {
 #[serde(rename_all = "camelCase")]
 pub struct UpdateBalance {
     is_demo: u32,
+    #[serde(deserialize_with = "numeric::f64")]
     pub balance: f64,
     uid: Option<i64>,
     login: Option<i64>,
@@ -93,7 +126,9 @@ pub struct Asset {
     pub symbol: String,
     pub name: String,
     pub asset_type: AssetType,
+    /// Number of decimals of the prices.
     pub in1: i32,
+    #[serde(deserialize_with = "numeric::integer")]
     pub payout: i32,
     pub in3: i32,
     pub in4: i32,
@@ -110,7 +145,7 @@ pub struct Asset {
     pub in12: i64,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum AssetType {
     Stock,
@@ -126,6 +161,75 @@ pub struct TimeCandle {
     time: Duration,
 }
 
+impl Asset {
+    /// Expiry times (in seconds) the server accepts for this asset, sorted from shortest to longest.
+    pub fn expiries(&self) -> Vec<u32> {
+        let mut expiries: Vec<u32> = self
+            .times
+            .iter()
+            .filter_map(|t| u32::try_from(t.seconds()).ok())
+            .collect();
+        expiries.sort_unstable();
+        expiries.dedup();
+        expiries
+    }
+
+    /// Precision (digits and pip size) of the prices of this asset.
+    pub fn precision(&self) -> Precision {
+        Precision::new(
+            u32::try_from(self.in1).unwrap_or_default(),
+            matches!(self.asset_type, AssetType::Currency),
+        )
+    }
+
+    /// Whether the asset is an OTC one, traded when its market is closed (weekends...).
+    pub fn is_otc(&self) -> bool {
+        self.in6 == 1 || is_otc_symbol(&self.symbol)
+    }
+
+    /// Whether the asset can be traded right now, the trades on a closed asset are rejected.
+    pub fn is_open(&self) -> bool {
+        self.val
+    }
+}
+
+/// Summary of an asset of the catalog sent by the server, see `PocketOption::get_assets`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssetInfo {
+    pub id: i32,
+    pub symbol: String,
+    /// Name shown by the platform (e.g. "Apple OTC").
+    pub name: String,
+    pub asset_type: AssetType,
+    /// Payout of the asset in percent.
+    pub payout: i32,
+    pub is_otc: bool,
+    pub is_open: bool,
+    /// Expiry times (in seconds) the server accepts, from shortest to longest.
+    pub expiries: Vec<u32>,
+}
+
+impl From<&Asset> for AssetInfo {
+    fn from(asset: &Asset) -> Self {
+        Self {
+            id: asset.id,
+            symbol: asset.symbol.clone(),
+            name: asset.name.clone(),
+            asset_type: asset.asset_type,
+            payout: asset.payout,
+            is_otc: asset.is_otc(),
+            is_open: asset.is_open(),
+            expiries: asset.expiries(),
+        }
+    }
+}
+
+impl TimeCandle {
+    pub fn seconds(&self) -> i64 {
+        self.time.num_seconds()
+    }
+}
+
 impl DataCandle {
     fn new(time: DateTime<Utc>, open: f64, close: f64, high: f64, low: f64) -> Self {
         Self {
@@ -134,6 +238,8 @@ impl DataCandle {
             close,
             high,
             low,
+            received_at: None,
+            latency: None,
         }
     }
 
@@ -144,6 +250,8 @@ impl DataCandle {
             close: price,
             high: price,
             low: price,
+            received_at: None,
+            latency: None,
         }
     }
 }
@@ -166,7 +274,11 @@ impl From<&Candle> for DataCandle {
 
 impl From<&UpdateStreamItem> for DataCandle {
     fn from(value: &UpdateStreamItem) -> Self {
-        Self::new_price(value.time, value.price)
+        Self {
+            received_at: Some(value.received_at),
+            latency: Some(value.latency()),
+            ..Self::new_price(value.time, value.price)
+        }
     }
 }
 
@@ -222,7 +334,11 @@ impl TryFrom<Vec<DataCandle>> for DataCandle {
             high = high.max(c.high);
             low = low.min(c.low);
         });
-        Ok(DataCandle::new(time, open, close, high, low))
+        Ok(DataCandle {
+            received_at: last.received_at,
+            latency: last.latency,
+            ..DataCandle::new(time, open, close, high, low)
+        })
     }
 }
 
@@ -242,7 +358,7 @@ pub mod float_time {
     where
         D: Deserializer<'de>,
     {
-        let s = f64::deserialize(deserializer)?.to_string();
+        let s = crate::pocketoption::types::numeric::f64(deserializer)?.to_string();
         let (secs, milis) = match s.split_once(".") {
             Some((seconds, miliseconds)) => {
                 let secs: i64 = seconds
@@ -347,6 +463,25 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_asset_info() -> Result<(), Box<dyn Error>> {
+        let assets: Vec<Asset> =
+            serde_json::from_reader(BufReader::new(File::open("tests/assets2.json")?))?;
+        let infos: Vec<AssetInfo> = assets.iter().map(AssetInfo::from).collect();
+        let find = |symbol: &str| infos.iter().find(|a| a.symbol == symbol).cloned();
+
+        let apple = find("#AAPL_otc").ok_or("missing asset")?;
+        assert_eq!(apple.name, "Apple OTC");
+        assert_eq!(apple.asset_type, AssetType::Stock);
+        assert!(apple.is_otc && apple.is_open);
+        assert_eq!(apple.expiries.first(), Some(&60));
+        let apple = find("#AAPL").ok_or("missing asset")?;
+        assert!(!apple.is_otc && !apple.is_open);
+        // The snapshot was taken on a weekend, the OTC assets are the ones open
+        assert!(infos.iter().filter(|a| a.is_otc).all(|a| a.is_open));
+        Ok(())
+    }
+
     #[test]
     fn test_deserialize_update_history() -> Result<(), Box<dyn Error>> {
         let history_raw = File::open("tests/update_history_new.txt")?;
@@ -1,5 +1,6 @@
 use binary_options_tools_core::{
     error::BinaryOptionsToolsError,
+    general::config::ConnectionTuning,
     reimports::{
         Connector, MaybeTlsStream, Request, WebSocketStream, connect_async_tls_with_config,
         generate_key,
@@ -16,6 +17,7 @@ use crate::pocketoption::{
 pub async fn try_connect(
     ssid: Ssid,
     url: String,
+    tuning: ConnectionTuning,
 ) -> PocketResult<WebSocketStream<MaybeTlsStream<TcpStream>>> {
     let tls_connector = native_tls::TlsConnector::builder().build()?;
 
@@ -42,8 +44,13 @@ pub async fn try_connect(
         .body(())
         .map_err(BinaryOptionsToolsError::from)?;
 
-    let (ws, _) = connect_async_tls_with_config(request, None, false, Some(connector))
-        .await
-        .map_err(BinaryOptionsToolsError::from)?;
+    let (ws, _) = connect_async_tls_with_config(
+        request,
+        Some(tuning.websocket_config()),
+        tuning.tcp_nodelay,
+        Some(connector),
+    )
+    .await
+    .map_err(BinaryOptionsToolsError::from)?;
     Ok(ws)
 }
@@ -0,0 +1,354 @@
+//! Files the candle history is downloaded to by `download_history`: CSV, JSON lines (the format of
+//! the recordings, so a download can be replayed with `CandleReplay`) or Parquet (`export-parquet`
+//! feature). The candles are written page by page, a download never holds the whole history.
+
+use std::{
+    fmt,
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use binary_options_tools_core::error::BinaryOptionsToolsError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::pocketoption::{
+    error::{PocketOptionError, PocketResult},
+    types::update::DataCandle,
+};
+
+use super::recording::{CandleRecorder, Compression};
+
+/// Format of a downloaded history.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryFormat {
+    /// `time,open,high,low,close` with the time in RFC 3339.
+    #[default]
+    Csv,
+    /// One json candle per line, compressed according to the extension of the file like the
+    /// recordings (see `Compression::from_path`).
+    JsonLines,
+    /// Columns `time` (timestamp in milliseconds), `open`, `high`, `low` and `close`, one row group
+    /// per page of candles. Requires the `export-parquet` feature.
+    Parquet,
+}
+
+impl HistoryFormat {
+    /// Format of a file named `path`: Parquet for `.parquet`, JSON lines for `.jsonl` and `.ndjson`
+    /// (optionally compressed, e.g. `.jsonl.gz`), CSV otherwise.
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        let name = path
+            .as_ref()
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+        let name = name
+            .trim_end_matches(".gz")
+            .trim_end_matches(".zst")
+            .trim_end_matches(".zstd");
+        if name.ends_with(".parquet") {
+            Self::Parquet
+        } else if name.ends_with(".jsonl") || name.ends_with(".ndjson") {
+            Self::JsonLines
+        } else {
+            Self::Csv
+        }
+    }
+}
+
+impl fmt::Display for HistoryFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Csv => write!(f, "csv"),
+            Self::JsonLines => write!(f, "jsonl"),
+            Self::Parquet => write!(f, "parquet"),
+        }
+    }
+}
+
+impl FromStr for HistoryFormat {
+    type Err = PocketOptionError;
+
+    /// Parses `csv`, `jsonl` (or `json_lines`, `ndjson`) and `parquet`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "csv" => Ok(Self::Csv),
+            "jsonl" | "json_lines" | "ndjson" => Ok(Self::JsonLines),
+            "parquet" => Ok(Self::Parquet),
+            _ => Err(PocketOptionError::GeneralParsingError(format!(
+                "Invalid history format '{s}', expected 'csv', 'jsonl' or 'parquet'"
+            ))),
+        }
+    }
+}
+
+/// Progress of a download, sent to its callback after every page.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DownloadProgress {
+    pub asset: String,
+    pub pages_done: usize,
+    pub pages: usize,
+    /// Candles written so far.
+    pub candles: usize,
+    /// Time of the last candle written.
+    pub last_candle: Option<DateTime<Utc>>,
+    /// Pages requested again after a failure (e.g. during a reconnection) so far.
+    pub retries: u32,
+}
+
+impl DownloadProgress {
+    /// Fraction of the pages downloaded, from 0 to 1.
+    pub fn fraction(&self) -> f64 {
+        if self.pages == 0 {
+            1.0
+        } else {
+            self.pages_done as f64 / self.pages as f64
+        }
+    }
+}
+
+/// Result of a finished download.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DownloadSummary {
+    pub asset: String,
+    pub period: i64,
+    pub path: PathBuf,
+    pub format: HistoryFormat,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub candles: usize,
+    /// Candles of the range the server didn't send (e.g. while the market was closed).
+    pub missing_candles: usize,
+    pub retries: u32,
+}
+
+/// Writes the pages of a download to its file, in the order of the pages.
+pub(crate) enum HistoryWriter {
+    Csv(BufWriter<File>),
+    JsonLines(CandleRecorder),
+    #[cfg(feature = "export-parquet")]
+    Parquet(parquet_writer::ParquetWriter),
+}
+
+impl HistoryWriter {
+    /// Creates (or truncates) the file at `path`.
+    pub fn create(path: &Path, format: HistoryFormat) -> PocketResult<Self> {
+        let create = || File::create(path).map_err(BinaryOptionsToolsError::from);
+        match format {
+            HistoryFormat::Csv => {
+                let mut writer = BufWriter::new(create()?);
+                writeln!(writer, "time,open,high,low,close")
+                    .map_err(BinaryOptionsToolsError::from)?;
+                Ok(Self::Csv(writer))
+            }
+            HistoryFormat::JsonLines => {
+                // The recorder appends to the file, it's truncated first
+                create()?;
+                Ok(Self::JsonLines(CandleRecorder::create_with(
+                    path,
+                    Compression::from_path(path),
+                    None,
+                )?))
+            }
+            #[cfg(feature = "export-parquet")]
+            HistoryFormat::Parquet => Ok(Self::Parquet(parquet_writer::ParquetWriter::new(
+                create()?,
+            )?)),
+            #[cfg(not(feature = "export-parquet"))]
+            HistoryFormat::Parquet => Err(PocketOptionError::Unallowed(
+                "writing Parquet files requires the `export-parquet` feature, download the history as CSV or JSON lines instead"
+                    .to_string(),
+            )),
+        }
+    }
+
+    pub fn write(&mut self, candles: &[DataCandle]) -> PocketResult<()> {
+        match self {
+            Self::Csv(writer) => {
+                for candle in candles {
+                    writeln!(
+                        writer,
+                        "{},{},{},{},{}",
+                        candle.time.to_rfc3339(),
+                        candle.open,
+                        candle.high,
+                        candle.low,
+                        candle.close
+                    )
+                    .map_err(BinaryOptionsToolsError::from)?;
+                }
+                // Flushed page by page so an interrupted download keeps the pages written
+                writer.flush().map_err(BinaryOptionsToolsError::from)?;
+            }
+            Self::JsonLines(recorder) => {
+                for candle in candles {
+                    recorder.record(candle)?;
+                }
+                recorder.flush()?;
+            }
+            #[cfg(feature = "export-parquet")]
+            Self::Parquet(writer) => writer.write(candles)?,
+        }
+        Ok(())
+    }
+
+    /// Writes what's left, the Parquet files are only readable once finished.
+    pub fn finish(self) -> PocketResult<()> {
+        match self {
+            Self::Csv(mut writer) => writer.flush().map_err(BinaryOptionsToolsError::from)?,
+            Self::JsonLines(mut recorder) => recorder.flush()?,
+            #[cfg(feature = "export-parquet")]
+            Self::Parquet(writer) => writer.finish()?,
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "export-parquet")]
+mod parquet_writer {
+    use std::{fs::File, sync::Arc};
+
+    use parquet::{
+        basic::Compression,
+        data_type::{DoubleType, Int64Type},
+        errors::ParquetError,
+        file::{properties::WriterProperties, writer::SerializedFileWriter},
+        schema::parser::parse_message_type,
+    };
+
+    use crate::pocketoption::{
+        error::{PocketOptionError, PocketResult},
+        types::update::DataCandle,
+    };
+
+    const SCHEMA: &str = "
+        message candle {
+            REQUIRED INT64 time (TIMESTAMP(MILLIS, true));
+            REQUIRED DOUBLE open;
+            REQUIRED DOUBLE high;
+            REQUIRED DOUBLE low;
+            REQUIRED DOUBLE close;
+        }
+    ";
+
+    fn parquet_error(e: ParquetError) -> PocketOptionError {
+        PocketOptionError::ExportError(e.to_string())
+    }
+
+    pub struct ParquetWriter(SerializedFileWriter<File>);
+
+    impl ParquetWriter {
+        pub fn new(file: File) -> PocketResult<Self> {
+            let schema = Arc::new(parse_message_type(SCHEMA).map_err(parquet_error)?);
+            let properties = Arc::new(
+                WriterProperties::builder()
+                    .set_compression(Compression::SNAPPY)
+                    .build(),
+            );
+            Ok(Self(
+                SerializedFileWriter::new(file, schema, properties).map_err(parquet_error)?,
+            ))
+        }
+
+        /// Writes the candles as a row group, in the order of the columns of the schema.
+        pub fn write(&mut self, candles: &[DataCandle]) -> PocketResult<()> {
+            if candles.is_empty() {
+                return Ok(());
+            }
+            let times: Vec<i64> = candles.iter().map(|c| c.time.timestamp_millis()).collect();
+            let prices: [Vec<f64>; 4] = [
+                candles.iter().map(|c| c.open).collect(),
+                candles.iter().map(|c| c.high).collect(),
+                candles.iter().map(|c| c.low).collect(),
+                candles.iter().map(|c| c.close).collect(),
+            ];
+            let mut row_group = self.0.next_row_group().map_err(parquet_error)?;
+            let mut index = 0;
+            while let Some(mut column) = row_group.next_column().map_err(parquet_error)? {
+                if index == 0 {
+                    column.typed::<Int64Type>().write_batch(&times, None, None)
+                } else {
+                    column
+                        .typed::<DoubleType>()
+                        .write_batch(&prices[index - 1], None, None)
+                }
+                .map_err(parquet_error)?;
+                column.close().map_err(parquet_error)?;
+                index += 1;
+            }
+            row_group.close().map_err(parquet_error)?;
+            Ok(())
+        }
+
+        pub fn finish(self) -> PocketResult<()> {
+            self.0.close().map_err(parquet_error)?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::pocketoption::utils::recording::CandleReplay;
+
+    fn candle(timestamp: i64, price: f64) -> DataCandle {
+        DataCandle {
+            time: DateTime::from_timestamp(timestamp, 0).unwrap(),
+            open: price,
+            high: price + 0.5,
+            low: price - 0.5,
+            close: price,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_history_writer() -> anyhow::Result<()> {
+        assert_eq!(
+            HistoryFormat::from_path("EURUSD.jsonl.gz"),
+            HistoryFormat::JsonLines
+        );
+        assert_eq!(
+            HistoryFormat::from_path("EURUSD.parquet"),
+            HistoryFormat::Parquet
+        );
+        assert_eq!(HistoryFormat::from_path("EURUSD.txt"), HistoryFormat::Csv);
+        assert_eq!(
+            "json-lines".parse::<HistoryFormat>()?,
+            HistoryFormat::JsonLines
+        );
+        assert!("xlsx".parse::<HistoryFormat>().is_err());
+
+        let dir = std::env::temp_dir();
+        let csv = dir.join("test_history_writer.csv");
+        let mut writer = HistoryWriter::create(&csv, HistoryFormat::Csv)?;
+        writer.write(&[candle(0, 1.0), candle(60, 1.5)])?;
+        writer.write(&[candle(120, 2.0)])?;
+        writer.finish()?;
+        let content = std::fs::read_to_string(&csv)?;
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], "time,open,high,low,close");
+        assert_eq!(lines[2], "1970-01-01T00:01:00+00:00,1.5,2,1,1.5");
+        std::fs::remove_file(&csv)?;
+
+        // The JSON lines downloads are replayed like recordings
+        let jsonl = dir.join("test_history_writer.jsonl");
+        let mut writer = HistoryWriter::create(&jsonl, HistoryFormat::JsonLines)?;
+        writer.write(&[candle(0, 1.0), candle(60, 1.5)])?;
+        writer.finish()?;
+        let mut replay = CandleReplay::open(&jsonl)?;
+        assert_eq!(replay.next_candle()?.unwrap().close, 1.0);
+        let last = replay.next_candle()?.unwrap();
+        assert_eq!((last.time.timestamp(), last.high), (60, 2.0));
+        assert!(replay.next_candle()?.is_none());
+        std::fs::remove_file(&jsonl)?;
+        Ok(())
+    }
+}
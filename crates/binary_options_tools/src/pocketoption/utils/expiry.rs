@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+
+use crate::pocketoption::{
+    error::{PocketOptionError, PocketResult},
+    types::update::DataCandle,
+    utils::precision::Precision,
+};
+
+/// Expiry suggested by `suggest_expiry` for a target move, with the volatility it's based on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExpirySuggestion {
+    /// Shortest valid expiry (in seconds) the target move is expected within, the longest one if
+    /// the volatility is too low for any of them.
+    pub expiry: u32,
+    pub target_move_pips: f64,
+    /// Mean absolute move expected over the expiry, in pips.
+    pub expected_move_pips: f64,
+    /// Realized volatility, the standard deviation of the close to close moves of one candle in pips.
+    pub volatility_pips: f64,
+    /// False when even the longest expiry is too short for the target move.
+    pub reaches_target: bool,
+    /// Number of candles the volatility was measured over.
+    pub candles: usize,
+}
+
+/// Returns the expiry from `valid` closest to `desired` (both in seconds).
+/// When `desired` is exactly between two valid expiries the shortest one is used,
+/// `valid` is expected to be sorted from shortest to longest.
+pub fn nearest_expiry(valid: &[u32], desired: u32) -> Option<u32> {
+    valid
+        .iter()
+        .copied()
+        .min_by_key(|expiry| (expiry.abs_diff(desired), *expiry))
+}
+
+/// Suggests the shortest of the `valid` expiries whose expected move reaches `target_move_pips`,
+/// from the realized volatility of `candles` (of `period` seconds).
+/// The prices are modeled as a random walk: the moves of the candles are scaled by the square
+/// root of the number of candles in the expiry, and the expected absolute move of a random walk
+/// is `sqrt(2 / pi)` times its standard deviation.
+pub fn suggest_expiry(
+    candles: &[DataCandle],
+    period: u32,
+    precision: &Precision,
+    valid: &[u32],
+    target_move_pips: f64,
+) -> PocketResult<ExpirySuggestion> {
+    if !(target_move_pips.is_finite() && target_move_pips > 0.0) {
+        return Err(PocketOptionError::Unallowed(format!(
+            "the target move must be a positive number of pips, got {target_move_pips}"
+        )));
+    }
+    let mut expiries = valid.to_vec();
+    expiries.sort_unstable();
+    let longest = *expiries
+        .last()
+        .ok_or_else(|| PocketOptionError::EmptyArrayError("expiries".to_string()))?;
+    let moves: Vec<f64> = candles
+        .windows(2)
+        .map(|pair| (pair[1].close - pair[0].close) / precision.pip_size)
+        .filter(|m| m.is_finite())
+        .collect();
+    if moves.len() < 2 {
+        return Err(PocketOptionError::EmptyArrayError("candles".to_string()));
+    }
+    let mean = moves.iter().sum::<f64>() / moves.len() as f64;
+    let variance = moves.iter().map(|m| (m - mean).powi(2)).sum::<f64>() / (moves.len() - 1) as f64;
+    let volatility_pips = variance.sqrt();
+    let expected_move = |expiry: u32| {
+        volatility_pips
+            * (expiry as f64 / period as f64).sqrt()
+            * (2.0 / std::f64::consts::PI).sqrt()
+    };
+    let reached = expiries
+        .iter()
+        .copied()
+        .find(|expiry| expected_move(*expiry) >= target_move_pips);
+    let expiry = reached.unwrap_or(longest);
+    Ok(ExpirySuggestion {
+        expiry,
+        target_move_pips,
+        expected_move_pips: expected_move(expiry),
+        volatility_pips,
+        reaches_target: reached.is_some(),
+        candles: candles.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+
+    use super::*;
+
+    #[test]
+    fn test_nearest_expiry() {
+        let valid = [60, 120, 180, 300, 600, 900, 1800, 2700, 3600];
+        assert_eq!(nearest_expiry(&valid, 60), Some(60));
+        assert_eq!(nearest_expiry(&valid, 5), Some(60));
+        assert_eq!(nearest_expiry(&valid, 250), Some(300));
+        assert_eq!(nearest_expiry(&valid, 240), Some(180));
+        assert_eq!(nearest_expiry(&valid, 100_000), Some(3600));
+        assert_eq!(nearest_expiry(&[], 60), None);
+    }
+
+    #[test]
+    fn test_suggest_expiry() -> anyhow::Result<()> {
+        let eurusd = Precision::new(5, true);
+        // Alternating moves of 2 pips
+        let candles: Vec<DataCandle> = (0..21)
+            .map(|i| DataCandle {
+                time: DateTime::from_timestamp(1_700_000_000 + i * 60, 0).unwrap(),
+                close: if i % 2 == 0 { 1.1 } else { 1.1002 },
+                ..Default::default()
+            })
+            .collect();
+        let valid = [3600, 60, 300, 900];
+
+        let suggestion = suggest_expiry(&candles, 60, &eurusd, &valid, 5.0)?;
+        assert!((suggestion.volatility_pips - 2.05).abs() < 0.01);
+        // sqrt(5) * 2.05 * 0.8 is 3.66 pips in 5 minutes, 15 minutes are needed for 5 pips
+        assert_eq!(suggestion.expiry, 900);
+        assert!(suggestion.reaches_target && suggestion.expected_move_pips >= 5.0);
+        assert_eq!(suggestion.candles, 21);
+
+        let suggestion = suggest_expiry(&candles, 60, &eurusd, &valid, 100.0)?;
+        assert_eq!(suggestion.expiry, 3600);
+        assert!(!suggestion.reaches_target);
+
+        assert!(suggest_expiry(&candles, 60, &eurusd, &valid, 0.0).is_err());
+        assert!(suggest_expiry(&candles, 60, &eurusd, &[], 5.0).is_err());
+        assert!(suggest_expiry(&candles[..2], 60, &eurusd, &valid, 5.0).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,304 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::pocketoption::types::update::DataCandle;
+
+/// Returns the ranges `[start, end)` (unix timestamps) of the candles of `period` seconds missing
+/// from `candles` between `start` and `end`.
+/// Only the candles that fully fit in the requested range are expected, so the candle still being
+/// formed at `end` is never reported as missing.
+pub fn missing_ranges(
+    candles: &[DataCandle],
+    start: i64,
+    end: i64,
+    period: i64,
+) -> Vec<(i64, i64)> {
+    if period <= 0 {
+        return Vec::new();
+    }
+    let received: HashSet<i64> = candles
+        .iter()
+        .map(|c| c.time.timestamp().div_euclid(period))
+        .collect();
+    let first = (start + period - 1).div_euclid(period);
+    let last = end.div_euclid(period);
+    let mut ranges: Vec<(i64, i64)> = Vec::new();
+    for bucket in (first..last).filter(|b| !received.contains(b)) {
+        let (from, to) = (bucket * period, (bucket + 1) * period);
+        match ranges.last_mut() {
+            Some(range) if range.1 == from => range.1 = to,
+            _ => ranges.push((from, to)),
+        }
+    }
+    ranges
+}
+
+/// Number of candles of `period` seconds expected between `start` and `end`.
+pub fn expected_candles(start: i64, end: i64, period: i64) -> usize {
+    if period <= 0 {
+        return 0;
+    }
+    let first = (start + period - 1).div_euclid(period);
+    (end.div_euclid(period) - first).max(0) as usize
+}
+
+/// Splits the candles of `period` seconds between `start` and `end` into pages of at most
+/// `page_size` candles, returned as the `(time, offset)` of the requests loading them, oldest first.
+/// The range is widened to the candles containing `start` and `end`.
+pub fn range_pages(start: i64, end: i64, period: i64, page_size: i64) -> Vec<(i64, i64)> {
+    if period <= 0 || page_size <= 0 {
+        return Vec::new();
+    }
+    let last = (end + period - 1).div_euclid(period) * period;
+    let mut from = start.div_euclid(period) * period;
+    let mut pages = Vec::new();
+    while from < last {
+        let to = (from + period * page_size).min(last);
+        pages.push((to, to - from));
+        from = to;
+    }
+    pages
+}
+
+/// Groups the `ranges` of missing candles of `period` seconds (sorted, as returned by
+/// `missing_ranges`) into at most `max_pages` requests of at most `page_size` candles, returned as
+/// the `(time, offset)` of the requests, oldest first. Close ranges share a request and the ranges
+/// longer than a page are split.
+pub fn gap_pages(
+    ranges: &[(i64, i64)],
+    period: i64,
+    page_size: i64,
+    max_pages: usize,
+) -> Vec<(i64, i64)> {
+    if period <= 0 || page_size <= 0 {
+        return Vec::new();
+    }
+    let span = period * page_size;
+    let mut pages: Vec<(i64, i64)> = Vec::new();
+    for &(from, to) in ranges {
+        let mut from = from;
+        while from < to && pages.len() <= max_pages {
+            match pages.last_mut() {
+                // The previous request can be widened to this range
+                Some(page) if page.0 + span > from => {
+                    page.1 = to.min(page.0 + span);
+                    from = page.1;
+                }
+                _ => {
+                    let end = to.min(from + span);
+                    pages.push((from, end));
+                    from = end;
+                }
+            }
+        }
+    }
+    pages.truncate(max_pages);
+    pages
+        .into_iter()
+        .map(|(from, to)| (to, to - from))
+        .collect()
+}
+
+/// Merges two lists of candles, sorted by time and without duplicates.
+pub fn merge_candles(candles: Vec<DataCandle>, other: Vec<DataCandle>) -> Vec<DataCandle> {
+    let mut merged = BTreeMap::new();
+    for candle in other.into_iter().chain(candles) {
+        merged.insert(candle.time.timestamp_millis(), candle);
+    }
+    merged.into_values().collect()
+}
+
+pub fn format_ranges(ranges: &[(DateTime<Utc>, DateTime<Utc>)]) -> String {
+    ranges
+        .iter()
+        .map(|(start, end)| format!("[{}, {})", start.to_rfc3339(), end.to_rfc3339()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Returns the most common interval (in seconds) between consecutive candles.
+pub fn infer_period(candles: &[DataCandle]) -> Option<i64> {
+    let mut timestamps: Vec<i64> = candles.iter().map(|c| c.time.timestamp()).collect();
+    timestamps.sort_unstable();
+    let mut intervals: HashMap<i64, usize> = HashMap::new();
+    timestamps
+        .windows(2)
+        .map(|w| w[1] - w[0])
+        .filter(|interval| *interval > 0)
+        .for_each(|interval| *intervals.entry(interval).or_default() += 1);
+    intervals
+        .into_iter()
+        .max_by_key(|(interval, count)| (*count, -interval))
+        .map(|(interval, _)| interval)
+}
+
+/// Data quality report of a list of candles, to check (or clean) a dataset before using it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CandleAudit {
+    pub period: i64,
+    pub candles: usize,
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+    /// Ranges `[start, end)` without candles between the first and the last candle.
+    pub gaps: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+    pub missing_candles: usize,
+    /// Timestamps shared by more than one candle.
+    pub duplicates: Vec<DateTime<Utc>>,
+    /// Candles where the high is equal to the low (no price movement or placeholder data).
+    pub zero_range: Vec<DateTime<Utc>>,
+    /// Candles whose timestamp isn't a multiple of the period.
+    pub misaligned: Vec<DateTime<Utc>>,
+}
+
+impl CandleAudit {
+    pub fn new(candles: &[DataCandle], period: i64) -> Self {
+        let mut audit = Self {
+            period,
+            candles: candles.len(),
+            start: candles.iter().map(|c| c.time).min(),
+            end: candles.iter().map(|c| c.time).max(),
+            ..Default::default()
+        };
+        let mut seen: HashMap<i64, usize> = HashMap::new();
+        for candle in candles {
+            let timestamp = candle.time.timestamp_millis();
+            *seen.entry(timestamp).or_default() += 1;
+            if seen[&timestamp] == 2 {
+                audit.duplicates.push(candle.time);
+            }
+            if candle.high == candle.low {
+                audit.zero_range.push(candle.time);
+            }
+            if period > 0 && timestamp.rem_euclid(period * 1000) != 0 {
+                audit.misaligned.push(candle.time);
+            }
+        }
+        if let (Some(start), Some(end)) = (audit.start, audit.end) {
+            let missing =
+                missing_ranges(candles, start.timestamp(), end.timestamp() + period, period);
+            audit.missing_candles = missing
+                .iter()
+                .map(|(from, to)| ((to - from) / period) as usize)
+                .sum();
+            audit.gaps = missing
+                .into_iter()
+                .filter_map(|(from, to)| {
+                    Some((
+                        DateTime::from_timestamp(from, 0)?,
+                        DateTime::from_timestamp(to, 0)?,
+                    ))
+                })
+                .collect();
+        }
+        audit
+    }
+
+    /// Audits the candles using the most common interval between them as the period.
+    pub fn infer(candles: &[DataCandle]) -> Self {
+        Self::new(candles, infer_period(candles).unwrap_or_default())
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.gaps.is_empty()
+            && self.duplicates.is_empty()
+            && self.zero_range.is_empty()
+            && self.misaligned.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candle(timestamp: i64) -> DataCandle {
+        DataCandle {
+            time: DateTime::from_timestamp(timestamp, 0).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_missing_ranges() {
+        let candles: Vec<DataCandle> = [0, 60, 240, 300, 480].into_iter().map(candle).collect();
+        assert_eq!(
+            missing_ranges(&candles, 0, 600, 60),
+            vec![(120, 240), (360, 480), (540, 600)]
+        );
+        assert_eq!(expected_candles(0, 600, 60), 10);
+        // The candle being formed at the end of the range isn't expected
+        assert_eq!(missing_ranges(&candles, 0, 519, 60).len(), 2);
+        // Neither is the candle partially before the start of the range
+        assert!(missing_ranges(&candles[1..], 30, 120, 60).is_empty());
+
+        let merged = merge_candles(candles.clone(), vec![candle(120), candle(180), candle(60)]);
+        assert_eq!(merged.len(), 7);
+        assert!(merged.windows(2).all(|w| w[0].time < w[1].time));
+        assert_eq!(
+            missing_ranges(&merged, 0, 600, 60),
+            vec![(360, 480), (540, 600)]
+        );
+    }
+
+    #[test]
+    fn test_range_pages() {
+        assert_eq!(
+            range_pages(30, 600, 60, 4),
+            vec![(240, 240), (480, 240), (600, 120)]
+        );
+        // The candle containing the end is included
+        assert_eq!(range_pages(0, 90, 60, 10), vec![(120, 120)]);
+        assert!(range_pages(600, 600, 60, 10).is_empty());
+        assert!(range_pages(0, 600, 0, 10).is_empty());
+    }
+
+    #[test]
+    fn test_gap_pages() {
+        let gaps = [(120, 240), (360, 480), (540, 600), (1200, 1500)];
+        // The close gaps share a request, the far one gets its own
+        assert_eq!(gap_pages(&gaps, 60, 10, 10), vec![(600, 480), (1500, 300)]);
+        // Gaps longer than a page are split
+        assert_eq!(
+            gap_pages(&[(0, 600)], 60, 4, 10),
+            vec![(240, 240), (480, 240), (600, 120)]
+        );
+        assert_eq!(
+            gap_pages(&[(0, 600)], 60, 4, 2),
+            vec![(240, 240), (480, 240)]
+        );
+        assert_eq!(gap_pages(&gaps, 60, 10, 1), vec![(600, 480)]);
+        assert!(gap_pages(&gaps, 60, 10, 0).is_empty());
+        assert!(gap_pages(&[], 60, 10, 10).is_empty());
+    }
+
+    #[test]
+    fn test_candle_audit() {
+        let mut candles: Vec<DataCandle> = [0, 60, 60, 240, 301].into_iter().map(candle).collect();
+        candles[0].high = 1.5;
+        candles[1].high = 1.5;
+        candles[2].high = 1.5;
+        candles[4].high = 1.5;
+        assert_eq!(infer_period(&candles), Some(60));
+        let audit = CandleAudit::infer(&candles);
+        assert_eq!(audit.period, 60);
+        assert_eq!(audit.candles, 5);
+        assert_eq!(audit.duplicates.len(), 1);
+        assert_eq!(audit.zero_range, vec![candles[3].time]);
+        assert_eq!(audit.misaligned, vec![candles[4].time]);
+        assert_eq!(audit.missing_candles, 2);
+        assert_eq!(audit.gaps.len(), 1);
+        assert!(!audit.is_clean());
+        assert!(
+            CandleAudit::new(
+                &[candle(0), candle(60)].map(|mut c| {
+                    c.high = 1.0;
+                    c.low = 0.5;
+                    c
+                }),
+                60
+            )
+            .is_clean()
+        );
+    }
+}
@@ -0,0 +1,168 @@
+//! Metadata of the assets kept on disk.
+//!
+//! The server only sends the catalog of the assets (with their payouts, the expiries they accept and
+//! whether their market is open) once the connection is up, a client started while the server is
+//! slow to send it can't validate its orders. The last catalog received is saved to a cache file,
+//! a new client starts with it (if it isn't older than the TTL of the cache) until the server sends
+//! the current one.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use binary_options_tools_core::error::BinaryOptionsToolsError;
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::pocketoption::{
+    error::{PocketOptionError, PocketResult},
+    types::update::Asset,
+};
+
+/// Time a cache file can be used for after it was saved, by default.
+pub const DEFAULT_METADATA_TTL: Duration = Duration::from_secs(24 * 3600);
+
+/// Where the metadata of the assets used by the client come from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetadataSource {
+    /// Neither the server nor a cache provided them yet.
+    #[default]
+    None,
+    /// Loaded from the cache file, the server didn't send them yet.
+    Cache,
+    /// Sent by the server.
+    Server,
+}
+
+/// Source of the metadata of the assets and the time they were sent by the server.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MetadataOrigin {
+    pub source: MetadataSource,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// State of the metadata of the assets returned by `metadata_status`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetadataStatus {
+    pub source: MetadataSource,
+    /// Time the server sent the metadata (the ones loaded from the cache keep the time of the server).
+    pub updated_at: Option<DateTime<Utc>>,
+    pub assets: usize,
+    /// Cache file, `None` if the metadata aren't cached.
+    pub path: Option<PathBuf>,
+    /// The metadata come from the cache and are older than its TTL, the server still didn't send
+    /// the current ones.
+    pub stale: bool,
+}
+
+/// Content of a cache file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedMetadata {
+    /// Time the server sent the assets.
+    pub saved_at: DateTime<Utc>,
+    pub assets: Vec<Asset>,
+}
+
+/// Cache file of the metadata of the assets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataCache {
+    path: PathBuf,
+    ttl: TimeDelta,
+}
+
+impl MetadataCache {
+    pub fn new(path: impl AsRef<Path>, ttl: Duration) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            ttl: TimeDelta::from_std(ttl).unwrap_or(TimeDelta::MAX),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn ttl(&self) -> TimeDelta {
+        self.ttl
+    }
+
+    /// Whether metadata sent by the server at `updated_at` are older than the TTL at `now`.
+    pub fn is_expired(&self, updated_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        now - updated_at > self.ttl
+    }
+
+    /// Reads the cache file, `None` if it doesn't exist or is older than the TTL at `now`.
+    pub fn load(&self, now: DateTime<Utc>) -> PocketResult<Option<CachedMetadata>> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(BinaryOptionsToolsError::from(e).into()),
+        };
+        let cached: CachedMetadata = serde_json::from_str(&content).map_err(|e| {
+            PocketOptionError::GeneralParsingError(format!(
+                "Invalid metadata cache '{}', {e}",
+                self.path.display()
+            ))
+        })?;
+        Ok((!self.is_expired(cached.saved_at, now)).then_some(cached))
+    }
+
+    /// Replaces the cache file with the assets sent by the server at `saved_at`. The file is written
+    /// next to it first, so a crash while saving never leaves a truncated cache.
+    pub fn save(&self, assets: Vec<Asset>, saved_at: DateTime<Utc>) -> PocketResult<()> {
+        let mut cached = CachedMetadata { saved_at, assets };
+        cached.assets.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        let content = serde_json::to_vec(&cached)
+            .map_err(|e| PocketOptionError::GeneralParsingError(e.to_string()))?;
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).map_err(BinaryOptionsToolsError::from)?;
+        }
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push(".tmp");
+        fs::write(&temporary, content).map_err(BinaryOptionsToolsError::from)?;
+        fs::rename(&temporary, &self.path).map_err(BinaryOptionsToolsError::from)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::pocketoption::types::update::UpdateAssets;
+
+    #[test]
+    fn test_metadata_cache() -> anyhow::Result<()> {
+        let assets: UpdateAssets = serde_json::from_str(include_str!("../../../tests/data.json"))?;
+        let path = std::env::temp_dir().join("test_metadata_cache.json");
+        let cache = MetadataCache::new(&path, Duration::from_secs(3600));
+        let _ = fs::remove_file(&path);
+        assert!(cache.load(Utc::now())?.is_none());
+
+        let saved_at = Utc::now() - TimeDelta::minutes(10);
+        cache.save(assets.0.clone(), saved_at)?;
+        let cached = cache.load(Utc::now())?.unwrap();
+        assert_eq!(cached.saved_at, saved_at);
+        assert_eq!(cached.assets.len(), assets.0.len());
+        let asset = &assets.0[0];
+        let loaded = cached
+            .assets
+            .iter()
+            .find(|a| a.symbol == asset.symbol)
+            .unwrap();
+        assert_eq!(
+            (loaded.payout, loaded.val, loaded.times.len()),
+            (asset.payout, asset.val, asset.times.len())
+        );
+
+        // An expired cache isn't used
+        assert!(cache.load(Utc::now() + TimeDelta::hours(1))?.is_none());
+        fs::write(&path, "{")?;
+        assert!(cache.load(Utc::now()).is_err());
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+}
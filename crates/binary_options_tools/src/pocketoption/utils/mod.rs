@@ -1,3 +1,10 @@
 pub mod basic;
 pub mod connect;
+pub mod download;
+pub mod expiry;
+pub mod history;
 pub mod location;
+pub mod metadata;
+pub mod precision;
+pub mod recording;
+pub mod trade_export;
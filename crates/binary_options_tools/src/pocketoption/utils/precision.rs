@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+/// Precision of the prices of an asset.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Precision {
+    /// Number of decimals of the prices.
+    pub digits: u32,
+    /// Size of a pip, one tick (`10^-digits`) for every asset except currencies quoted with a
+    /// fractional pip (5 digits, or 3 for JPY pairs) where a pip is ten ticks.
+    pub pip_size: f64,
+}
+
+impl Precision {
+    pub fn new(digits: u32, fractional_pip: bool) -> Self {
+        let pip_digits = if fractional_pip && (digits == 3 || digits == 5) {
+            digits - 1
+        } else {
+            digits
+        };
+        Self {
+            digits,
+            pip_size: 1.0 / 10f64.powi(pip_digits as i32),
+        }
+    }
+
+    /// Rounds a price to the number of decimals of the asset.
+    pub fn round_price(&self, price: f64) -> f64 {
+        round_to(price, self.digits)
+    }
+
+    /// Signed number of pips from `a` to `b`, rounded to a tenth of a pip to avoid floating point
+    /// errors (`1.08543` to `1.08553` is exactly `1.0` pip).
+    pub fn pips_between(&self, a: f64, b: f64) -> f64 {
+        round_to((b - a) / self.pip_size, 1)
+    }
+}
+
+fn round_to(value: f64, digits: u32) -> f64 {
+    let factor = 10f64.powi(digits as i32);
+    (value * factor).round() / factor
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_precision() {
+        let eurusd = Precision::new(5, true);
+        assert_eq!(eurusd.pip_size, 0.0001);
+        assert_eq!(eurusd.round_price(1.085434999), 1.08543);
+        assert_eq!(eurusd.pips_between(1.08543, 1.08553), 1.0);
+        assert_eq!(eurusd.pips_between(1.1, 1.0999), -1.0);
+
+        let audjpy = Precision::new(3, true);
+        assert_eq!(audjpy.pip_size, 0.01);
+        assert_eq!(audjpy.pips_between(97.123, 97.178), 5.5);
+
+        let btc = Precision::new(2, false);
+        assert_eq!(btc.pip_size, 0.01);
+        assert_eq!(btc.round_price(96543.216), 96543.22);
+        assert_eq!(btc.pips_between(96543.21, 96544.21), 100.0);
+    }
+}
@@ -0,0 +1,554 @@
+//! Recordings of candle streams (one json candle per line) and their replay, with checkpoints so a
+//! long job over a big recording can resume where it stopped instead of reading it from the start.
+//!
+//! Recordings can be compressed with gzip or zstd (`compression` feature): the candles are written
+//! in chunks, each one a complete gzip member or zstd frame, so an interrupted recording only loses
+//! the candles of its last chunk and a recording can be appended to like a plain one.
+
+use std::{
+    borrow::Cow,
+    fmt,
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use binary_options_tools_core::error::BinaryOptionsToolsError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::pocketoption::{
+    error::{PocketOptionError, PocketResult},
+    types::update::DataCandle,
+};
+
+/// Candles written at once to a compressed recording by default, the plain recordings are
+/// written candle by candle.
+pub const DEFAULT_CHUNK_SIZE: usize = 512;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// Compression of a recording, with its level.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Compression {
+    #[default]
+    None,
+    /// Level from 0 (fastest) to 9 (smallest).
+    Gzip(u32),
+    /// Level from 1 (fastest) to 22 (smallest).
+    Zstd(i32),
+}
+
+impl Compression {
+    pub fn gzip() -> Self {
+        Self::Gzip(6)
+    }
+
+    pub fn zstd() -> Self {
+        Self::Zstd(3)
+    }
+
+    /// Compression of a recording named `path`: gzip for `.gz`, zstd for `.zst` and `.zstd`,
+    /// none otherwise.
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        match path.as_ref().extension().and_then(|e| e.to_str()) {
+            Some("gz") => Self::gzip(),
+            Some("zst" | "zstd") => Self::zstd(),
+            _ => Self::None,
+        }
+    }
+
+    /// Compression of the recording starting with `header`, recognized by the magic bytes of gzip
+    /// and zstd. The level isn't needed to read a recording, the default one is returned.
+    fn detect(header: &[u8]) -> Self {
+        if header.starts_with(GZIP_MAGIC) {
+            Self::gzip()
+        } else if header.starts_with(ZSTD_MAGIC) {
+            Self::zstd()
+        } else {
+            Self::None
+        }
+    }
+
+    /// Returns the compression with another level, failing if it's out of the range of the
+    /// algorithm.
+    pub fn level(self, level: i32) -> PocketResult<Self> {
+        match self {
+            Self::Gzip(_) if (0..=9).contains(&level) => Ok(Self::Gzip(level as u32)),
+            Self::Zstd(_) if (1..=22).contains(&level) => Ok(Self::Zstd(level)),
+            Self::None => Err(PocketOptionError::Unallowed(
+                "a compression level was given for an uncompressed recording".to_string(),
+            )),
+            _ => Err(PocketOptionError::Unallowed(format!(
+                "{level} isn't a valid {self} compression level"
+            ))),
+        }
+    }
+
+    /// Fails if the crate was built without the `compression` feature and the recording is
+    /// compressed.
+    fn check(self) -> PocketResult<()> {
+        if cfg!(feature = "compression") || self == Self::None {
+            Ok(())
+        } else {
+            Err(PocketOptionError::Unallowed(format!(
+                "{self} recordings require the `compression` feature"
+            )))
+        }
+    }
+
+    /// Candles written at once by default with this compression.
+    pub fn default_chunk_size(self) -> usize {
+        match self {
+            Self::None => 1,
+            _ => DEFAULT_CHUNK_SIZE,
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::None => write!(f, "none"),
+            Self::Gzip(_) => write!(f, "gzip"),
+            Self::Zstd(_) => write!(f, "zstd"),
+        }
+    }
+}
+
+impl FromStr for Compression {
+    type Err = PocketOptionError;
+
+    /// Parses `none`, `gzip` (or `gz`) and `zstd` (or `zst`), with their default level.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" | "" => Ok(Self::None),
+            "gzip" | "gz" => Ok(Self::gzip()),
+            "zstd" | "zst" => Ok(Self::zstd()),
+            _ => Err(PocketOptionError::GeneralParsingError(format!(
+                "Invalid compression '{s}', expected 'none', 'gzip' or 'zstd'"
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "compression")]
+fn compress(compression: Compression, data: &[u8]) -> io::Result<Cow<'_, [u8]>> {
+    match compression {
+        Compression::None => Ok(Cow::Borrowed(data)),
+        Compression::Gzip(level) => {
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::new(level));
+            encoder.write_all(data)?;
+            Ok(Cow::Owned(encoder.finish()?))
+        }
+        Compression::Zstd(level) => Ok(Cow::Owned(zstd::encode_all(data, level)?)),
+    }
+}
+
+#[cfg(not(feature = "compression"))]
+fn compress(_: Compression, data: &[u8]) -> io::Result<Cow<'_, [u8]>> {
+    Ok(Cow::Borrowed(data))
+}
+
+/// Reader of the candles of a compressed recording, reading every chunk of it.
+#[cfg(feature = "compression")]
+fn decoder(compression: Compression, file: File) -> io::Result<Box<dyn BufRead + Send>> {
+    Ok(match compression {
+        Compression::None => Box::new(BufReader::new(file)),
+        Compression::Gzip(_) => Box::new(BufReader::new(flate2::read::MultiGzDecoder::new(file))),
+        Compression::Zstd(_) => Box::new(BufReader::new(zstd::Decoder::new(file)?)),
+    })
+}
+
+#[cfg(not(feature = "compression"))]
+fn decoder(_: Compression, file: File) -> io::Result<Box<dyn BufRead + Send>> {
+    Ok(Box::new(BufReader::new(file)))
+}
+
+/// Appends the candles of a stream to a recording.
+pub struct CandleRecorder {
+    file: File,
+    compression: Compression,
+    chunk_size: usize,
+    chunk: Vec<u8>,
+    pending: usize,
+}
+
+impl CandleRecorder {
+    /// Opens the recording at `path`, the candles are appended if it already exists. The recording
+    /// is compressed according to the extension of `path` (see `Compression::from_path`).
+    pub fn create(path: impl AsRef<Path>) -> PocketResult<Self> {
+        let compression = Compression::from_path(&path);
+        Self::create_with(path, compression, None)
+    }
+
+    /// Opens the recording at `path` with `compression`, the candles are written every
+    /// `chunk_size` candles (`Compression::default_chunk_size` if `None`). Appending to a
+    /// recording with another compression is allowed but makes it unreadable.
+    pub fn create_with(
+        path: impl AsRef<Path>,
+        compression: Compression,
+        chunk_size: Option<usize>,
+    ) -> PocketResult<Self> {
+        compression.check()?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(BinaryOptionsToolsError::from)?;
+        Ok(Self {
+            file,
+            compression,
+            chunk_size: chunk_size
+                .unwrap_or(compression.default_chunk_size())
+                .max(1),
+            chunk: Vec::new(),
+            pending: 0,
+        })
+    }
+
+    /// Buffers the candle, the chunk is written once it has `chunk_size` candles so an
+    /// interrupted recording keeps every candle of the previous chunks.
+    pub fn record(&mut self, candle: &DataCandle) -> PocketResult<()> {
+        serde_json::to_writer(&mut self.chunk, candle)?;
+        self.chunk.push(b'\n');
+        self.pending += 1;
+        if self.pending >= self.chunk_size {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the buffered candles to the recording, as a complete chunk.
+    pub fn flush(&mut self) -> PocketResult<()> {
+        if self.chunk.is_empty() {
+            return Ok(());
+        }
+        compress(self.compression, &self.chunk)
+            .and_then(|data| self.file.write_all(&data))
+            .and_then(|_| self.file.flush())
+            .map_err(BinaryOptionsToolsError::from)?;
+        self.chunk.clear();
+        self.pending = 0;
+        Ok(())
+    }
+}
+
+impl Drop for CandleRecorder {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            warn!(target: "CandleRecorder", "Failed to write the last candles of the recording, {e}");
+        }
+    }
+}
+
+/// Position in a recording to resume its replay from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayCheckpoint {
+    /// Offset in bytes of the next candle.
+    pub offset: u64,
+    /// Time of the last candle read before the checkpoint, if any.
+    pub time: Option<DateTime<Utc>>,
+}
+
+/// Source of the candles of a replay.
+enum Source {
+    Plain(BufReader<File>),
+    /// Compressed recordings can't seek, they are read again from the start to move backwards.
+    Compressed(Box<dyn BufRead + Send>),
+}
+
+impl Source {
+    fn reader(&mut self) -> &mut dyn BufRead {
+        match self {
+            Self::Plain(reader) => reader,
+            Self::Compressed(reader) => reader,
+        }
+    }
+}
+
+/// Reads a recording candle by candle, the candles are expected in chronological order for `seek`.
+/// Checkpoints of compressed recordings are positions in the decompressed candles.
+pub struct CandleReplay {
+    path: PathBuf,
+    compression: Compression,
+    source: Source,
+    offset: u64,
+    time: Option<DateTime<Utc>>,
+}
+
+impl CandleReplay {
+    /// Opens the recording at `path`, its compression is recognized from its first bytes.
+    pub fn open(path: impl AsRef<Path>) -> PocketResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut header = Vec::with_capacity(ZSTD_MAGIC.len());
+        File::open(&path)
+            .and_then(|file| file.take(ZSTD_MAGIC.len() as u64).read_to_end(&mut header))
+            .map_err(BinaryOptionsToolsError::from)?;
+        let compression = Compression::detect(&header);
+        compression.check()?;
+        let source = Self::source(&path, compression)?;
+        Ok(Self {
+            path,
+            compression,
+            source,
+            offset: 0,
+            time: None,
+        })
+    }
+
+    fn source(path: &Path, compression: Compression) -> PocketResult<Source> {
+        let file = File::open(path).map_err(BinaryOptionsToolsError::from)?;
+        Ok(match compression {
+            Compression::None => Source::Plain(BufReader::new(file)),
+            _ => Source::Compressed(
+                decoder(compression, file).map_err(BinaryOptionsToolsError::from)?,
+            ),
+        })
+    }
+
+    /// Opens the recording at `path` and moves to a checkpoint taken with `checkpoint`.
+    pub fn resume(path: impl AsRef<Path>, checkpoint: &ReplayCheckpoint) -> PocketResult<Self> {
+        let mut replay = Self::open(path)?;
+        let at_line_start = checkpoint.offset == 0 || {
+            let mut previous = [0];
+            replay.seek_to(checkpoint.offset - 1)?
+                && replay.source.reader().read_exact(&mut previous).is_ok()
+                && previous[0] == b'\n'
+        };
+        if !at_line_start {
+            return Err(PocketOptionError::Unallowed(format!(
+                "Checkpoint at byte {} isn't the start of a candle of '{}', it was taken on another recording",
+                checkpoint.offset,
+                replay.path.display()
+            )));
+        }
+        replay.seek_to(checkpoint.offset)?;
+        replay.time = checkpoint.time;
+        Ok(replay)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+
+    /// Returns the position of the replay, to resume it later with `resume`.
+    pub fn checkpoint(&self) -> ReplayCheckpoint {
+        ReplayCheckpoint {
+            offset: self.offset,
+            time: self.time,
+        }
+    }
+
+    /// Moves to the first candle at or after `time` (the end of the recording if there is none),
+    /// with a binary search so it doesn't read the candles before it. Compressed recordings are
+    /// read from the start instead.
+    pub fn seek(&mut self, time: DateTime<Utc>) -> PocketResult<()> {
+        if let Source::Compressed(_) = self.source {
+            self.seek_to(0)?;
+            while let Some((start, candle)) = self.read_candle()? {
+                if candle.time >= time {
+                    self.seek_to(start)?;
+                    break;
+                }
+            }
+            self.time = None;
+            return Ok(());
+        }
+        let len = self.len()?;
+        let (mut low, mut high) = (0, len);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            match self.candle_from(middle)? {
+                Some((_, candle)) if candle.time < time => low = middle + 1,
+                _ => high = middle,
+            }
+        }
+        let offset = self.candle_from(low)?.map_or(len, |(start, _)| start);
+        self.seek_to(offset)?;
+        self.time = None;
+        Ok(())
+    }
+
+    /// Returns the next candle, `None` at the end of the recording.
+    pub fn next_candle(&mut self) -> PocketResult<Option<DataCandle>> {
+        Ok(self.read_candle()?.map(|(_, candle)| candle))
+    }
+
+    /// Reads the next candle, with the offset of its line.
+    fn read_candle(&mut self) -> PocketResult<Option<(u64, DataCandle)>> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let start = self.offset;
+            let read = self
+                .source
+                .reader()
+                .read_line(&mut line)
+                .map_err(BinaryOptionsToolsError::from)?;
+            if read == 0 {
+                return Ok(None);
+            }
+            self.offset += read as u64;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let candle: DataCandle = serde_json::from_str(line.trim())?;
+            self.time = Some(candle.time);
+            return Ok(Some((start, candle)));
+        }
+    }
+
+    /// Size of a plain recording, 0 for the compressed ones.
+    fn len(&self) -> PocketResult<u64> {
+        match &self.source {
+            Source::Plain(reader) => Ok(reader
+                .get_ref()
+                .metadata()
+                .map_err(BinaryOptionsToolsError::from)?
+                .len()),
+            Source::Compressed(_) => Ok(0),
+        }
+    }
+
+    /// Moves to `offset`, returns false if it's past the end of the recording.
+    fn seek_to(&mut self, offset: u64) -> PocketResult<bool> {
+        if let Source::Plain(reader) = &mut self.source {
+            reader
+                .seek(SeekFrom::Start(offset))
+                .map_err(BinaryOptionsToolsError::from)?;
+            self.offset = offset;
+            return Ok(offset <= self.len()?);
+        }
+        if offset < self.offset {
+            self.source = Self::source(&self.path, self.compression)?;
+            self.offset = 0;
+        }
+        let skipped = io::copy(
+            &mut self.source.reader().take(offset - self.offset),
+            &mut io::sink(),
+        )
+        .map_err(BinaryOptionsToolsError::from)?;
+        self.offset += skipped;
+        Ok(self.offset == offset)
+    }
+
+    /// First candle starting at or after `offset`, with the offset it starts at.
+    fn candle_from(&mut self, offset: u64) -> PocketResult<Option<(u64, DataCandle)>> {
+        // Skips the end of the line `offset` falls in, unless it's the start of a line
+        self.seek_to(offset.saturating_sub(1))?;
+        if offset > 0 {
+            let mut skipped = Vec::new();
+            let read = self
+                .source
+                .reader()
+                .read_until(b'\n', &mut skipped)
+                .map_err(BinaryOptionsToolsError::from)?;
+            self.offset += read as u64;
+        }
+        self.read_candle()
+    }
+}
+
+impl Iterator for CandleReplay {
+    type Item = PocketResult<DataCandle>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_candle().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    fn test_replay_checkpoint_and_seek() -> anyhow::Result<()> {
+        let path = std::env::temp_dir().join(format!("candles-{}.jsonl", Uuid::new_v4()));
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let mut recorder = CandleRecorder::create(&path)?;
+        for i in 0..100 {
+            let candle = DataCandle {
+                time: start + TimeDelta::seconds(i),
+                close: i as f64,
+                ..Default::default()
+            };
+            recorder.record(&candle)?;
+        }
+
+        let mut replay = CandleReplay::open(&path)?;
+        assert_eq!(replay.by_ref().take(30).count(), 30);
+        let checkpoint = replay.checkpoint();
+        assert_eq!(checkpoint.time, Some(start + TimeDelta::seconds(29)));
+        let mut resumed = CandleReplay::resume(&path, &checkpoint)?;
+        assert_eq!(resumed.next_candle()?.unwrap().close, 30.0);
+
+        resumed.seek(start + TimeDelta::seconds(75))?;
+        assert_eq!(resumed.next_candle()?.unwrap().close, 75.0);
+        resumed.seek(start - TimeDelta::seconds(10))?;
+        assert_eq!(resumed.next_candle()?.unwrap().close, 0.0);
+        resumed.seek(start + TimeDelta::seconds(500))?;
+        assert!(resumed.next_candle()?.is_none());
+
+        let shifted = ReplayCheckpoint {
+            offset: checkpoint.offset + 3,
+            time: None,
+        };
+        assert!(CandleReplay::resume(&path, &shifted).is_err());
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_compressed_recording() -> anyhow::Result<()> {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        for compression in [Compression::gzip(), Compression::zstd().level(19)?] {
+            let path = std::env::temp_dir().join(format!("candles-{}.rec", Uuid::new_v4()));
+            // Two recorders append to the same file, the second chunk is left for the drop
+            for part in 0..2 {
+                let mut recorder = CandleRecorder::create_with(&path, compression, Some(16))?;
+                for i in (part * 50)..(part * 50 + 50) {
+                    let candle = DataCandle {
+                        time: start + TimeDelta::seconds(i),
+                        close: i as f64,
+                        ..Default::default()
+                    };
+                    recorder.record(&candle)?;
+                }
+            }
+
+            let mut replay = CandleReplay::open(&path)?;
+            assert_eq!(replay.compression().to_string(), compression.to_string());
+            assert_eq!(replay.by_ref().take(40).count(), 40);
+            let checkpoint = replay.checkpoint();
+            assert_eq!(replay.count(), 60);
+            let mut resumed = CandleReplay::resume(&path, &checkpoint)?;
+            assert_eq!(resumed.next_candle()?.unwrap().close, 40.0);
+            resumed.seek(start + TimeDelta::seconds(10))?;
+            assert_eq!(resumed.next_candle()?.unwrap().close, 10.0);
+            resumed.seek(start + TimeDelta::seconds(500))?;
+            assert!(resumed.next_candle()?.is_none());
+            std::fs::remove_file(&path)?;
+        }
+        assert_eq!(
+            Compression::from_path("ticks.jsonl.zst"),
+            Compression::zstd()
+        );
+        assert!(Compression::gzip().level(12).is_err());
+        assert_eq!("GZ".parse::<Compression>()?, Compression::gzip());
+        Ok(())
+    }
+}
@@ -0,0 +1,371 @@
+//! Parser of the trade history exported from the Pocket Option website (CSV, or XLSX with the
+//! `xlsx` feature), so the trades placed before using the crate can be merged in the journal.
+
+use std::{collections::HashMap, path::Path};
+
+use binary_options_tools_core::error::BinaryOptionsToolsError;
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use uuid::Uuid;
+
+use crate::pocketoption::{
+    error::{PocketOptionError, PocketResult},
+    journal::JournalEntry,
+    types::order::Action,
+};
+
+/// Tag of the journal entries loaded from a broker export.
+pub const IMPORT_TAG: &str = "import";
+
+const DATE_FORMATS: [&str; 8] = [
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M:%S",
+    "%d.%m.%Y %H:%M:%S",
+    "%d.%m.%Y %H:%M",
+    "%d/%m/%Y %H:%M:%S",
+    "%d/%m/%Y %H:%M",
+    "%m/%d/%Y %H:%M:%S",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Column {
+    Id,
+    Asset,
+    Action,
+    Amount,
+    OpenPrice,
+    OpenedAt,
+    ClosedAt,
+    Profit,
+    Payout,
+    Duration,
+}
+
+impl Column {
+    /// Names used for the column in the exports (lowercase, punctuation removed).
+    fn from_header(header: &str) -> Option<Self> {
+        let header: String = header
+            .chars()
+            .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+            .collect::<String>()
+            .to_lowercase();
+        let column = match header.trim() {
+            "id" | "trade id" | "deal id" | "order id" | "ticket" => Self::Id,
+            "asset" | "symbol" | "instrument" | "pair" => Self::Asset,
+            "direction" | "type" | "action" | "command" | "forecast" => Self::Action,
+            "amount" | "investment" | "stake" | "trade amount" | "sum" => Self::Amount,
+            "open price" | "opening price" | "open quote" | "strike price" => Self::OpenPrice,
+            "open time" | "opening time" | "opened" | "open date" | "time" | "date" => {
+                Self::OpenedAt
+            }
+            "close time" | "closing time" | "closed" | "close date" | "expiration time"
+            | "expiration" => Self::ClosedAt,
+            "profit" | "result" | "pl" | "pnl" | "net profit" => Self::Profit,
+            "payout" | "income" | "return" => Self::Payout,
+            "duration" | "expiry" | "time frame" | "timeframe" => Self::Duration,
+            _ => return None,
+        };
+        Some(column)
+    }
+}
+
+/// Loads the trades of an export file, the format is picked from the extension (`.xlsx` files
+/// need the `xlsx` feature, everything else is parsed as CSV).
+pub fn load_trade_export(path: impl AsRef<Path>) -> PocketResult<Vec<JournalEntry>> {
+    let path = path.as_ref();
+    let is_xlsx = path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("xlsx") || e.eq_ignore_ascii_case("xls"));
+    if is_xlsx {
+        return entries_from_rows(read_xlsx(path)?);
+    }
+    let content = std::fs::read_to_string(path).map_err(BinaryOptionsToolsError::from)?;
+    parse_trade_export(&content)
+}
+
+/// Parses the trades of a CSV export, the delimiter (`,`, `;` or tab) is detected from the header.
+pub fn parse_trade_export(content: &str) -> PocketResult<Vec<JournalEntry>> {
+    let content = content.trim_start_matches('\u{feff}');
+    let header = content.lines().next().unwrap_or_default();
+    let delimiter = [',', ';', '\t']
+        .into_iter()
+        .max_by_key(|d| header.matches(*d).count())
+        .unwrap_or(',');
+    entries_from_rows(parse_csv(content, delimiter))
+}
+
+#[cfg(feature = "xlsx")]
+fn read_xlsx(path: &Path) -> PocketResult<Vec<Vec<String>>> {
+    use calamine::{Data, DataType, Reader, open_workbook_auto};
+
+    let mut workbook = open_workbook_auto(path)
+        .map_err(|e| PocketOptionError::GeneralParsingError(e.to_string()))?;
+    let range = workbook
+        .worksheet_range_at(0)
+        .ok_or_else(|| PocketOptionError::EmptyArrayError("worksheets".to_string()))?
+        .map_err(|e| PocketOptionError::GeneralParsingError(e.to_string()))?;
+    Ok(range
+        .rows()
+        .map(|row| {
+            row.iter()
+                .map(|cell| match cell {
+                    Data::DateTime(_) | Data::DateTimeIso(_) => cell
+                        .as_datetime()
+                        .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string())
+                        .unwrap_or_default(),
+                    cell => cell.to_string(),
+                })
+                .collect()
+        })
+        .collect())
+}
+
+#[cfg(not(feature = "xlsx"))]
+fn read_xlsx(_: &Path) -> PocketResult<Vec<Vec<String>>> {
+    Err(PocketOptionError::Unallowed(
+        "reading XLSX exports requires the `xlsx` feature, export the history as CSV instead"
+            .to_string(),
+    ))
+}
+
+/// Splits a CSV document in rows of fields, quoted fields can contain delimiters, quotes (`""`)
+/// and line breaks.
+fn parse_csv(content: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if c == delimiter && !quoted => row.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows.retain(|r| r.iter().any(|f| !f.trim().is_empty()));
+    rows
+}
+
+fn entries_from_rows(rows: Vec<Vec<String>>) -> PocketResult<Vec<JournalEntry>> {
+    let mut rows = rows.into_iter();
+    let header = rows
+        .next()
+        .ok_or_else(|| PocketOptionError::EmptyArrayError("trade export".to_string()))?;
+    let columns: HashMap<Column, usize> = header
+        .iter()
+        .enumerate()
+        .filter_map(|(i, h)| Column::from_header(h).map(|c| (c, i)))
+        .rev()
+        .collect();
+    for required in [
+        Column::Asset,
+        Column::Action,
+        Column::Amount,
+        Column::OpenedAt,
+    ] {
+        if !columns.contains_key(&required) {
+            return Err(PocketOptionError::GeneralParsingError(format!(
+                "the trade export has no {required:?} column, found columns: {}",
+                header.join(", ")
+            )));
+        }
+    }
+    rows.enumerate()
+        .map(|(line, row)| {
+            parse_row(&columns, &row).map_err(|e| {
+                PocketOptionError::GeneralParsingError(format!("row {}: {e}", line + 2))
+            })
+        })
+        .collect()
+}
+
+fn parse_row(columns: &HashMap<Column, usize>, row: &[String]) -> Result<JournalEntry, String> {
+    let get = |column: Column| {
+        columns
+            .get(&column)
+            .and_then(|i| row.get(*i))
+            .map(|f| f.trim())
+            .filter(|f| !f.is_empty())
+    };
+    let required = |column: Column| get(column).ok_or_else(|| format!("missing {column:?}"));
+
+    let asset = normalize_asset(required(Column::Asset)?);
+    let action = parse_action(required(Column::Action)?)?;
+    let amount = parse_number(required(Column::Amount)?)?;
+    let opened_at = parse_date(required(Column::OpenedAt)?)?;
+    let closed_at = get(Column::ClosedAt).map(parse_date).transpose()?;
+    let profit = match (get(Column::Profit), get(Column::Payout)) {
+        (Some(profit), _) => Some(parse_number(profit)?),
+        // The payout is the amount returned, stake included
+        (None, Some(payout)) => Some(parse_number(payout)? - amount),
+        (None, None) => None,
+    };
+    let time = match (get(Column::Duration), closed_at) {
+        (Some(duration), _) => parse_duration(duration)?,
+        (None, Some(closed_at)) => (closed_at - opened_at).num_seconds().max(0) as u32,
+        (None, None) => 0,
+    };
+    let trade_id = match get(Column::Id) {
+        Some(id) => Uuid::parse_str(id)
+            .unwrap_or_else(|_| Uuid::new_v5(&Uuid::NAMESPACE_OID, id.as_bytes())),
+        // Stable id so importing the same file twice doesn't duplicate the trades
+        None => Uuid::new_v5(&Uuid::NAMESPACE_OID, row.join("\u{1f}").as_bytes()),
+    };
+    Ok(JournalEntry {
+        trade_id,
+        request_id: None,
+        uid: 0,
+        asset,
+        action,
+        amount,
+        time,
+        open_price: get(Column::OpenPrice)
+            .map(parse_number)
+            .transpose()?
+            .unwrap_or_default(),
+        opened_at,
+        group: None,
+        tags: vec![IMPORT_TAG.to_string()],
+        profit,
+        // Exports without a close time only list closed trades once they have a result
+        closed_at: closed_at
+            .or_else(|| profit.map(|_| opened_at + chrono::Duration::seconds(time as i64))),
+        context: None,
+    })
+}
+
+/// Converts the display name of an asset (`EUR/USD OTC`) to its symbol (`EURUSD_otc`).
+fn normalize_asset(asset: &str) -> String {
+    let symbol: String = asset
+        .chars()
+        .filter(|c| !matches!(c, '/' | ' ' | '-'))
+        .collect();
+    match symbol.len().checked_sub(3) {
+        Some(i)
+            if !symbol.contains('_')
+                && symbol.is_char_boundary(i)
+                && symbol[i..].eq_ignore_ascii_case("otc") =>
+        {
+            format!("{}_otc", &symbol[..i])
+        }
+        _ => symbol,
+    }
+}
+
+fn parse_action(action: &str) -> Result<Action, String> {
+    match action.to_lowercase().as_str() {
+        "call" | "up" | "higher" | "buy" | "0" => Ok(Action::Call),
+        "put" | "down" | "lower" | "sell" | "1" => Ok(Action::Put),
+        other => Err(format!("unknown direction '{other}'")),
+    }
+}
+
+/// Parses amounts like `$1,250.00`, `-12,5` or `9.20 USD`.
+fn parse_number(number: &str) -> Result<f64, String> {
+    let mut cleaned: String = number
+        .chars()
+        .filter(|c| c.is_ascii_digit() || matches!(c, '.' | ',' | '-'))
+        .collect();
+    if cleaned.contains('.') {
+        cleaned.retain(|c| c != ',');
+    } else {
+        cleaned = cleaned.replace(',', ".");
+    }
+    cleaned
+        .parse()
+        .map_err(|_| format!("invalid number '{number}'"))
+}
+
+fn parse_date(date: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(date) {
+        return Ok(date.with_timezone(&Utc));
+    }
+    if let Ok(timestamp) = date.parse::<f64>() {
+        return DateTime::from_timestamp_millis((timestamp * 1000.0) as i64)
+            .ok_or_else(|| format!("invalid timestamp '{date}'"));
+    }
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(date, format).ok())
+        .map(|date| Utc.from_utc_datetime(&date))
+        .ok_or_else(|| format!("invalid date '{date}'"))
+}
+
+/// Parses durations in seconds (`60`), `mm:ss` / `hh:mm:ss` or with a unit (`30s`, `5m`, `1h`).
+fn parse_duration(duration: &str) -> Result<u32, String> {
+    let invalid = || format!("invalid duration '{duration}'");
+    if duration.contains(':') {
+        return duration
+            .split(':')
+            .try_fold(0u32, |total, part| {
+                Ok(total * 60 + part.trim().parse::<u32>()?)
+            })
+            .map_err(|_: std::num::ParseIntError| invalid());
+    }
+    let (value, unit) = duration.split_at(
+        duration
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(duration.len()),
+    );
+    let value: u32 = value.parse().map_err(|_| invalid())?;
+    match unit.trim().to_lowercase().as_str() {
+        "" | "s" | "sec" => Ok(value),
+        "m" | "min" => Ok(value * 60),
+        "h" => Ok(value * 3600),
+        _ => Err(invalid()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_trade_export() -> anyhow::Result<()> {
+        let entries = parse_trade_export(include_str!("../../../tests/trade_export.csv"))?;
+        assert_eq!(entries.len(), 3);
+        let win = &entries[0];
+        assert_eq!(win.asset, "EURUSD_otc");
+        assert_eq!(win.action, Action::Call);
+        assert_eq!((win.amount, win.profit, win.time), (10.0, Some(9.2), 60));
+        assert_eq!(win.opened_at.to_rfc3339(), "2024-11-28T23:00:50+00:00");
+        assert_eq!(win.tags, [IMPORT_TAG]);
+
+        let loss = &entries[1];
+        assert_eq!(loss.asset, "AUDNZD_otc");
+        assert_eq!(loss.action, Action::Put);
+        assert_eq!(
+            (loss.amount, loss.profit, loss.time),
+            (1250.0, Some(-1250.0), 300)
+        );
+        assert_eq!(entries[2].profit, Some(0.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_export_without_ids() -> anyhow::Result<()> {
+        let export =
+            "Symbol,Type,Investment,Opening time,Duration,Payout\nEURUSD,up,2,1732834850,1m,3.84\n";
+        let entries = parse_trade_export(export)?;
+        assert_eq!(entries[0].time, 60);
+        assert!((entries[0].profit.unwrap() - 1.84).abs() < 1e-9);
+        // Importing the same file twice gives the same ids
+        assert_eq!(parse_trade_export(export)?[0].trade_id, entries[0].trade_id);
+        assert!(parse_trade_export("Symbol,Type\nEURUSD,up\n").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,267 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use futures_util::Stream;
+use futures_util::stream::unfold;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tracing::debug;
+
+use binary_options_tools_core::{error::BinaryOptionsToolsError, general::stream::StreamDeadline};
+
+use crate::pocketoption::{
+    error::{PocketOptionError, PocketResult},
+    parser::message::WebSocketMessage,
+    types::{
+        lanes::StreamReceiver,
+        update::{DataCandle, UpdateStreamItem},
+    },
+};
+
+/// Candle of a period that just closed, sent when the period ends on the server clock even if no
+/// tick was recieved for it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClosedCandle {
+    pub asset: String,
+    /// Duration of the period in seconds.
+    pub period: u64,
+    pub open_time: DateTime<Utc>,
+    pub close_time: DateTime<Utc>,
+    /// Best known candle of the period, if no tick was recieved during the period it's a flat candle
+    /// at the last known price, `None` if no price is known yet.
+    pub candle: Option<DataCandle>,
+    /// Number of ticks recieved during the period.
+    pub ticks: usize,
+}
+
+#[derive(Default)]
+struct CloseState {
+    /// Offset between the local and server clocks (local - server).
+    clock_offset: Option<TimeDelta>,
+    open_time: Option<DateTime<Utc>>,
+    current: Option<DataCandle>,
+    ticks: usize,
+    last_price: Option<f64>,
+}
+
+impl CloseState {
+    fn server_now(&self) -> DateTime<Utc> {
+        Utc::now() - self.clock_offset.unwrap_or_default()
+    }
+
+    fn add_tick(&mut self, item: &UpdateStreamItem) {
+        // The lowest offset seen is the closest to the real clock offset (the one with the least transport latency)
+        let offset = item.received_at - item.time;
+        if self.clock_offset.is_none_or(|o| offset < o) {
+            self.clock_offset = Some(offset);
+        }
+        let tick = DataCandle::from(item);
+        self.current = Some(match self.current.take() {
+            Some(mut current) => {
+                current.time = tick.time;
+                current.high = current.high.max(tick.high);
+                current.low = current.low.min(tick.low);
+                current.close = tick.close;
+                current.received_at = tick.received_at;
+                current.latency = tick.latency;
+                current
+            }
+            None => tick,
+        });
+        self.ticks += 1;
+    }
+
+    fn close(&mut self, asset: &str, period: TimeDelta, open_time: DateTime<Utc>) -> ClosedCandle {
+        let close_time = open_time + period;
+        let candle = self.current.take().or_else(|| {
+            self.last_price.map(|price| DataCandle {
+                time: close_time,
+                open: price,
+                close: price,
+                high: price,
+                low: price,
+                ..Default::default()
+            })
+        });
+        if let Some(candle) = &candle {
+            self.last_price = Some(candle.close);
+        }
+        self.open_time = Some(close_time);
+        ClosedCandle {
+            asset: asset.to_string(),
+            period: period.num_seconds() as u64,
+            open_time,
+            close_time,
+            candle,
+            ticks: std::mem::take(&mut self.ticks),
+        }
+    }
+}
+
+/// Start of the period of duration `period` containing `time`.
+pub fn period_start(time: DateTime<Utc>, period: TimeDelta) -> DateTime<Utc> {
+    let period_ms = period.num_milliseconds().max(1);
+    let start = time.timestamp_millis().div_euclid(period_ms) * period_ms;
+    DateTime::from_timestamp_millis(start).unwrap_or(time)
+}
+
+/// Stream of the candles of an asset sent when each period closes on the server clock, the close
+/// doesn't depend on the arrival of the ticks so a late or missing final tick never delays it.
+pub struct CandleCloseStream {
+    reciever: StreamReceiver,
+    asset: String,
+    period: TimeDelta,
+    state: Mutex<CloseState>,
+    deadline: Arc<StreamDeadline>,
+}
+
+impl CandleCloseStream {
+    /// `clock_offset` is the initial estimation of the offset between the local and the server clocks
+    /// (local - server), it's refined with the ticks recieved.
+    pub fn new(
+        reciever: impl Into<StreamReceiver>,
+        asset: String,
+        period: Duration,
+        clock_offset: Option<TimeDelta>,
+        timeout: Option<Duration>,
+    ) -> PocketResult<Self> {
+        let period = TimeDelta::from_std(period)
+            .ok()
+            .filter(|p| p.num_seconds() > 0)
+            .ok_or_else(|| {
+                PocketOptionError::GeneralParsingError(format!(
+                    "Invalid candle period '{period:?}', expected at least 1 second"
+                ))
+            })?;
+        Ok(Self {
+            reciever: reciever.into(),
+            asset,
+            period,
+            state: Mutex::new(CloseState {
+                clock_offset,
+                ..Default::default()
+            }),
+            deadline: Arc::new(StreamDeadline::new(timeout)),
+        })
+    }
+
+    /// Deadline of this stream, can be used to reset or extend the timeout after the stream was created.
+    pub fn deadline(&self) -> Arc<StreamDeadline> {
+        self.deadline.clone()
+    }
+
+    pub async fn recieve(&self) -> PocketResult<ClosedCandle> {
+        Ok(self
+            .deadline
+            .run("CandleCloseStream", async {
+                self.next_close()
+                    .await
+                    .map_err(BinaryOptionsToolsError::from)
+            })
+            .await?)
+    }
+
+    async fn next_close(&self) -> PocketResult<ClosedCandle> {
+        let mut state = self.state.lock().await;
+        loop {
+            let now = state.server_now();
+            let open_time = *state
+                .open_time
+                .get_or_insert_with(|| period_start(now, self.period));
+            let close_time = open_time + self.period;
+            let wait = (close_time - now).to_std().unwrap_or_default();
+            tokio::select! {
+                msg = self.reciever.recv() => {
+                    let msg = msg.map_err(BinaryOptionsToolsError::from)?;
+                    let WebSocketMessage::UpdateStream(stream) = msg else {
+                        continue;
+                    };
+                    for item in stream.0.iter().filter(|item| item.active == self.asset) {
+                        if item.time < open_time {
+                            debug!(target: "CandleClose", "Ignoring late tick of '{}' at {}", self.asset, item.time);
+                        } else if item.time >= close_time {
+                            // The period ended on the server before the timer fired
+                            let closed = state.close(&self.asset, self.period, open_time);
+                            state.open_time = Some(period_start(item.time, self.period));
+                            state.add_tick(item);
+                            return Ok(closed);
+                        } else {
+                            state.add_tick(item);
+                        }
+                    }
+                }
+                _ = tokio::time::sleep(wait) => {
+                    return Ok(state.close(&self.asset, self.period, open_time));
+                }
+            }
+        }
+    }
+
+    pub fn to_stream(&self) -> impl Stream<Item = PocketResult<ClosedCandle>> + '_ {
+        Box::pin(unfold(self, |state| async move {
+            let item = state.recieve().await;
+            Some((item, state))
+        }))
+    }
+
+    pub fn to_stream_static(
+        self: Arc<Self>,
+    ) -> impl Stream<Item = PocketResult<ClosedCandle>> + 'static {
+        Box::pin(unfold(self, |state| async move {
+            let item = state.recieve().await;
+            Some((item, state))
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_channel::bounded;
+
+    use super::*;
+    use crate::pocketoption::types::update::UpdateStream;
+
+    #[test]
+    fn test_period_start() {
+        let time = DateTime::from_timestamp_millis(1732830108205).unwrap();
+        assert_eq!(
+            period_start(time, TimeDelta::seconds(60)).timestamp(),
+            1732830060
+        );
+        assert_eq!(
+            period_start(time, TimeDelta::seconds(5)).timestamp(),
+            1732830105
+        );
+    }
+
+    #[tokio::test]
+    async fn test_close_without_ticks() -> anyhow::Result<()> {
+        let (sender, reciever) = bounded(8);
+        let stream = CandleCloseStream::new(
+            reciever,
+            "EURUSD_otc".into(),
+            Duration::from_secs(1),
+            None,
+            None,
+        )?;
+        let closed = tokio::time::timeout(Duration::from_secs(2), stream.recieve()).await??;
+        assert_eq!(closed.ticks, 0);
+        assert!(closed.candle.is_none());
+        assert_eq!(closed.close_time - closed.open_time, TimeDelta::seconds(1));
+
+        let time = (closed.close_time + TimeDelta::milliseconds(500)).timestamp_millis() as f64;
+        let tick: UpdateStream =
+            serde_json::from_str(&format!(r#"[["EURUSD_otc",{},1.08543]]"#, time / 1000.0))?;
+        sender.send(WebSocketMessage::UpdateStream(tick)).await?;
+        let closed = tokio::time::timeout(Duration::from_secs(2), stream.recieve()).await??;
+        assert_eq!(closed.ticks, 1);
+        assert_eq!(closed.candle.map(|c| c.close), Some(1.08543));
+
+        // No tick during the period, the candle is flat at the last known price
+        let closed = tokio::time::timeout(Duration::from_secs(2), stream.recieve()).await??;
+        assert_eq!(closed.ticks, 0);
+        assert_eq!(closed.candle.map(|c| c.high), Some(1.08543));
+        Ok(())
+    }
+}
@@ -1,4 +1,7 @@
-use std::time::Duration;
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
 
 use async_channel::{Sender, bounded};
 use async_trait::async_trait;
@@ -11,33 +14,65 @@ use crate::pocketoption::{error::PocketOptionError, utils::connect::try_connect}
 use binary_options_tools_core::{
     error::{BinaryOptionsResult, BinaryOptionsToolsError},
     general::{
-        config::Config,
+        config::{Config, ConnectionTuning},
         traits::{Connect, DataHandler, InnerConfig, MessageTransfer},
     },
     reimports::{MaybeTlsStream, WebSocketStream},
 };
 
-use super::ssid::Ssid;
+use super::{session::Session, ssid::Ssid};
 
 #[derive(Clone)]
-pub struct PocketConnect;
+pub struct PocketConnect {
+    session: Session,
+    /// Host of the server of the last connection.
+    server: Arc<RwLock<Option<String>>>,
+}
+
+impl PocketConnect {
+    pub fn new(session: Session) -> Self {
+        Self {
+            session,
+            server: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Host of the websocket server the client connected to last, `None` before the first
+    /// connection.
+    pub fn server(&self) -> Option<String> {
+        self.server.read().ok().and_then(|s| s.clone())
+    }
+
+    fn set_server(&self, url: &str) {
+        let host = Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(String::from))
+            .unwrap_or_else(|| url.to_string());
+        if let Ok(mut server) = self.server.write() {
+            *server = Some(host);
+        }
+    }
+}
 
 #[async_trait]
 impl Connect for PocketConnect {
     type Creds = Ssid;
 
+    /// Connects with the current session instead of `_creds` (the one the client was created with),
+    /// a fresh one of the session provider if the server rejected it.
     async fn connect<T: DataHandler, Transfer: MessageTransfer, U: InnerConfig>(
         &self,
-        creds: Self::Creds,
+        _creds: Self::Creds,
         config: &Config<T, Transfer, U>,
     ) -> BinaryOptionsResult<WebSocketStream<MaybeTlsStream<TcpStream>>> {
         async fn send_ws(
             creds: Ssid,
             url: String,
+            tuning: ConnectionTuning,
             sender: Sender<(WebSocketStream<MaybeTlsStream<TcpStream>>, String)>,
         ) -> BinaryOptionsResult<()> {
             info!(target: "TryConnect", "Trying to connecto to {}", url);
-            if let Ok(connect) = try_connect(creds, url.clone()).await {
+            if let Ok(connect) = try_connect(creds, url.clone(), tuning).await {
                 info!(target: "SuccessConnect", "Succesfully connected to {}", url);
                 sender.send((connect, url.clone())).await.map_err(|e| {
                     BinaryOptionsToolsError::GeneralMessageSendingError(e.to_string())
@@ -48,23 +83,40 @@ impl Connect for PocketConnect {
                 url,
             ))
         }
+        let creds = self.session.connection_ssid().await;
+        let tuning = config.get_connection_tuning()?;
         let (sender, reciever) = bounded(1); // It should stop after recieving only one message
         let default_urls = config.get_default_connection_url()?;
-        let default_connections = default_urls
-            .iter()
-            .map(|url| tokio::spawn(send_ws(creds.clone(), url.to_string(), sender.clone())));
+        let default_connections = default_urls.iter().map(|url| {
+            tokio::spawn(send_ws(
+                creds.clone(),
+                url.to_string(),
+                tuning,
+                sender.clone(),
+            ))
+        });
         tokio::select! {
-            res = reciever.recv() => return Ok(res.map(|(r, _)| r)?),
+            res = reciever.recv() => {
+                let (res, url) = res?;
+                self.set_server(&url);
+                return Ok(res);
+            },
             _ = join_all(default_connections) => {}
         }
         let urls = creds.servers().await?;
-        let connections = urls
-            .iter()
-            .map(|url| tokio::spawn(send_ws(creds.clone(), url.to_owned(), sender.clone())));
+        let connections = urls.iter().map(|url| {
+            tokio::spawn(send_ws(
+                creds.clone(),
+                url.to_owned(),
+                tuning,
+                sender.clone(),
+            ))
+        });
         tokio::select! {
             res = reciever.recv() => match res {
                 Ok((res, url)) => {
                     config.add_default_connection_url(Url::parse(&url)?)?;
+                    self.set_server(&url);
                     Ok(res)
                 },
                 Err(e) => Err(e.into())
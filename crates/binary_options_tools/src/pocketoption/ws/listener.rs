@@ -1,9 +1,13 @@
+use std::sync::{Arc, Mutex};
+
 use async_trait::async_trait;
-use serde_json::Value;
+use serde_json::{Value, from_str};
 
 use binary_options_tools_core::{
     error::{BinaryOptionsResult, BinaryOptionsToolsError},
     general::{
+        health::{ConnectionEvent, ConnectionEventKind, ConnectionHealth},
+        priority::Priority,
         send::SenderMessage,
         traits::{MessageHandler, MessageTransfer},
         types::MessageType,
@@ -12,35 +16,75 @@ use binary_options_tools_core::{
 };
 
 use crate::pocketoption::{
-    error::PocketResult,
+    error::{PocketOptionError, PocketResult},
     parser::message::WebSocketMessage,
-    types::{base::ChangeSymbol, info::MessageInfo},
+    types::{
+        account::{AccountEvent, AccountEventKind},
+        base::{ChangeSymbol, RawWebsocketMessage},
+        info::MessageInfo,
+        region::{RegionMismatch, reported_region},
+        subscriptions::Subscriptions,
+        unparsed::{Quarantine, UnparsedMessage},
+    },
 };
 
-use super::ssid::Ssid;
+use super::session::Session;
 
 #[derive(Clone)]
 pub struct Handler {
-    ssid: Ssid,
+    /// Session sent to authenticate, marked as expired if the server rejects it.
+    session: Session,
+    /// Name of the account event whose payload is sent in the next binary message.
+    account_event: Arc<Mutex<Option<String>>>,
+    /// Recieves the messages that failed to parse.
+    quarantine: Quarantine,
+    /// Records the ticks of the subscribed assets to detect the subscription limit of the server.
+    subscriptions: Subscriptions,
+    /// Recieves the `AuthenticationFailed` and `RegionMismatch` events.
+    health: Arc<ConnectionHealth>,
 }
 
 impl Handler {
-    pub fn new(ssid: Ssid) -> Self {
-        Self { ssid }
+    pub fn new(
+        session: Session,
+        quarantine: Quarantine,
+        subscriptions: Subscriptions,
+        health: Arc<ConnectionHealth>,
+    ) -> Self {
+        Self {
+            session,
+            account_event: Arc::new(Mutex::new(None)),
+            quarantine,
+            subscriptions,
+            health,
+        }
     }
 
     pub fn handle_binary_msg(
         &self,
-        bytes: &Vec<u8>,
+        bytes: &[u8],
         previous: &Option<MessageInfo>,
     ) -> PocketResult<WebSocketMessage> {
-        let msg = String::from_utf8(bytes.to_owned())?;
-        let message = match previous {
-            Some(previous) => WebSocketMessage::parse_with_context(msg, previous),
-            None => {
-                let message: WebSocketMessage = serde_json::from_str(&msg)?;
-                message
+        // The payload is parsed straight from the frame, it's only copied if it's kept as raw data
+        let msg = std::str::from_utf8(bytes)
+            .map_err(|e| PocketOptionError::GeneralParsingError(e.to_string()))?;
+        if previous == &Some(MessageInfo::AccountEvent) {
+            let event = self.account_event.lock().ok().and_then(|mut e| e.take());
+            if let Some(event) = event.and_then(|e| AccountEvent::new(e, from_str(msg).ok()?)) {
+                return Ok(WebSocketMessage::AccountEvent(event));
             }
+        }
+        let message = match previous {
+            Some(previous) => match WebSocketMessage::try_parse_with_context(msg, previous) {
+                Ok(message) => message,
+                Err(e) => {
+                    self.quarantine
+                        .send(UnparsedMessage::new(Some(previous.to_string()), msg, &e));
+                    WebSocketMessage::Raw(RawWebsocketMessage::from(msg))
+                }
+            },
+            None => serde_json::from_str(msg)
+                .inspect_err(|e| self.quarantine.send(UnparsedMessage::new(None, msg, e)))?,
         };
 
         Ok(message)
@@ -57,19 +101,28 @@ impl Handler {
             }
             _ if text.starts_with("40") && text.contains("sid") => {
                 sender
-                    .priority_send(Message::text(self.ssid.to_string()))
+                    .priority_send(Message::text(self.session.ssid().to_string()))
                     .await?;
             }
             _ if text == "2" => {
-                sender.priority_send(Message::text("3")).await?;
+                sender
+                    .send_with_priority(Message::text("3"), Priority::Keepalive)
+                    .await?;
                 // write.send(Message::text("3".into())).await.unwrap();
                 // write.flush().await.unwrap();
             }
             _ if text.starts_with("451-") => {
                 let msg = text.strip_prefix("451-").unwrap();
-                let (info, _): (MessageInfo, Value) =
-                    serde_json::from_str(msg).map_err(BinaryOptionsToolsError::from)?;
-                if let MessageInfo::Raw(_) = info {
+                let (info, _): (MessageInfo, Value) = serde_json::from_str(msg)
+                    .inspect_err(|e| self.quarantine.send(UnparsedMessage::new(None, text, e)))
+                    .map_err(BinaryOptionsToolsError::from)?;
+                if let MessageInfo::Raw(event) = &info {
+                    if AccountEventKind::from_event(event).is_some() {
+                        if let Ok(mut account_event) = self.account_event.lock() {
+                            *account_event = Some(event.to_owned());
+                        }
+                        return Ok(Some(MessageInfo::AccountEvent));
+                    }
                     return Ok(Some(MessageInfo::Raw(format!("451-{}", msg))))
                 }
                 if info == MessageInfo::UpdateClosedDeals {
@@ -104,16 +157,53 @@ impl MessageHandler for Handler {
     ) -> BinaryOptionsResult<(Option<MessageType<WebSocketMessage>>, bool)> {
         match message {
             Message::Binary(binary) => {
-                let msg = self.handle_binary_msg(&binary.to_vec(), previous)?;
+                let msg = self.handle_binary_msg(binary, previous)?;
+                if let WebSocketMessage::UpdateStream(stream) = &msg {
+                    for action in self.subscriptions.record_stream(stream) {
+                        for message in action.messages() {
+                            sender.send(message).await?;
+                        }
+                    }
+                }
                 if let Some(raw) = msg.get_raw() {
                     return Ok((Some(MessageType::Raw(raw)), false))
                 }
                 return Ok((Some(MessageType::Transfer(msg)), false));
             }
             Message::Text(text) => {
+                if let Some(mismatch) = RegionMismatch::from_text(text.as_ref()) {
+                    self.health.send_event(
+                        ConnectionEvent::new(ConnectionEventKind::RegionMismatch).reason(&mismatch),
+                    );
+                    self.session.mismatch(mismatch);
+                    // Same as a rejection, only a fresh session of the provider can help
+                    return Ok((None, self.session.has_provider()));
+                }
+                if let Some(region) = reported_region(text.as_ref()) {
+                    self.session.set_region(region);
+                }
+                if let Some(event) = AccountEvent::from_text(text.as_ref()) {
+                    return Ok((
+                        Some(MessageType::Transfer(WebSocketMessage::AccountEvent(event))),
+                        false,
+                    ));
+                }
+                if text.contains(r#"["NotAuthorized""#) {
+                    let rejection = self.session.reject();
+                    self.health.send_event(
+                        ConnectionEvent::new(ConnectionEventKind::AuthenticationFailed)
+                            .reason(&rejection),
+                    );
+                    // Closes the connection to reconnect with a fresh session of the provider,
+                    // without a provider reconnecting wouldn't help
+                    return Ok((None, self.session.has_provider()));
+                }
                 let res = self
                     .handle_text_msg::<WebSocketMessage>(text.as_ref(), sender)
                     .await?;
+                if res == Some(MessageInfo::Successauth) {
+                    self.session.accept();
+                }
                 return Ok((res.map(|r| {
                     if let Some(raw) = r.get_raw() {
                         MessageType::Raw(raw)
@@ -124,7 +214,9 @@ impl MessageHandler for Handler {
             }
             Message::Frame(_) => {} // TODO:
             Message::Ping(b) => {
-                sender.priority_send(Message::Pong(b.to_owned())).await?;
+                sender
+                    .send_with_priority(Message::Pong(b.to_owned()), Priority::Keepalive)
+                    .await?;
             } // TODO:
             Message::Pong(_) => {}  // TODO:
             Message::Close(_) => return Ok((None, true)),
@@ -1,7 +1,9 @@
 // pub mod api;
 // pub mod basic;
+pub mod candle_close;
 pub mod connect;
 pub mod listener;
 pub mod regions;
+pub mod session;
 pub mod ssid;
 pub mod stream;
@@ -0,0 +1,466 @@
+//! Session the client authenticates with, and the provider asked for a fresh one once the server
+//! rejects it, so unattended bots keep running after the session copied from the browser expired
+//! (e.g. a provider logging in the site again with a headless browser).
+//!
+//! The answer of the server to the authentication is tracked too, so a rejected session fails fast
+//! with an `AuthenticationError` telling whether it expired or is malformed instead of letting the
+//! requests wait for their timeouts, or with a `RegionMismatch` error once the server reports that
+//! the client connects from another IP or region than the one of the session.
+
+use std::{
+    fmt,
+    sync::{
+        Arc, RwLock,
+        atomic::{AtomicBool, AtomicU32, Ordering},
+    },
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Mutex, watch};
+use tracing::{info, warn};
+
+use crate::pocketoption::{
+    error::{PocketOptionError, PocketResult},
+    types::region::RegionMismatch,
+};
+
+use super::ssid::Ssid;
+
+/// Source of fresh sessions of the account of the client.
+#[async_trait]
+pub trait SessionProvider: Send + Sync {
+    /// Returns a new session of the account, in any format accepted by `Ssid::parse_lenient` (the
+    /// `42["auth",...]` message, the cookies of the site...).
+    async fn fresh_ssid(&self) -> PocketResult<String>;
+}
+
+/// Why the server rejected a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthFailure {
+    /// The session is well formed but the server doesn't accept it anymore (it expired, or the
+    /// user logged out from the browser), a fresh one is needed.
+    Expired,
+    /// The session can't be one issued by the server, e.g. it was truncated while copying it.
+    Malformed,
+}
+
+impl fmt::Display for AuthFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Expired => write!(f, "expired"),
+            Self::Malformed => write!(f, "malformed"),
+        }
+    }
+}
+
+/// Rejection of a session by the server, with the reason it was classified as expired or malformed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuthRejection {
+    pub failure: AuthFailure,
+    pub reason: String,
+    pub time: DateTime<Utc>,
+}
+
+impl AuthRejection {
+    pub fn error(&self) -> PocketOptionError {
+        PocketOptionError::AuthenticationError {
+            failure: self.failure,
+            reason: self.reason.clone(),
+        }
+    }
+}
+
+impl fmt::Display for AuthRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the session is {}, {}", self.failure, self.reason)
+    }
+}
+
+/// Answer of the server to the authentication of the current connection.
+#[derive(Debug, Clone, PartialEq)]
+enum AuthState {
+    Pending,
+    Authenticated,
+    Rejected(AuthRejection),
+    Mismatch(RegionMismatch),
+}
+
+/// State of the session, as returned by `PocketOption::session_status`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionStatus {
+    pub uid: u32,
+    pub demo: bool,
+    /// True once the server rejected the session, until a fresh one is received.
+    pub expired: bool,
+    pub provider: bool,
+    /// Number of fresh sessions received from the provider.
+    pub refreshes: u32,
+    pub refreshed_at: Option<DateTime<Utc>>,
+    /// Last rejection of the session by the server, `None` once it's accepted again.
+    #[serde(default)]
+    pub rejection: Option<AuthRejection>,
+}
+
+/// Current session of a client, shared by the handler sending it and the connector, the fresh
+/// sessions of the provider are cached until the server rejects them too.
+#[derive(Clone)]
+pub struct Session {
+    ssid: Arc<RwLock<Ssid>>,
+    provider: Arc<RwLock<Option<Arc<dyn SessionProvider>>>>,
+    expired: Arc<AtomicBool>,
+    refreshes: Arc<AtomicU32>,
+    refreshed_at: Arc<RwLock<Option<DateTime<Utc>>>>,
+    /// Held while the provider is called, so it's only called once for concurrent refreshes.
+    refreshing: Arc<Mutex<()>>,
+    auth: Arc<watch::Sender<AuthState>>,
+    /// True once the server accepted a session of the client, a rejection after it means the
+    /// session expired.
+    accepted_once: Arc<AtomicBool>,
+    /// Region reported by the server.
+    region: Arc<RwLock<Option<String>>>,
+    /// Last region or IP mismatch reported by the server, kept once the session is accepted again.
+    mismatch: Arc<RwLock<Option<RegionMismatch>>>,
+}
+
+impl Session {
+    pub fn new(ssid: Ssid) -> Self {
+        Self {
+            ssid: Arc::new(RwLock::new(ssid)),
+            provider: Arc::new(RwLock::new(None)),
+            expired: Arc::new(AtomicBool::new(false)),
+            refreshes: Arc::new(AtomicU32::new(0)),
+            refreshed_at: Arc::new(RwLock::new(None)),
+            refreshing: Arc::new(Mutex::new(())),
+            auth: Arc::new(watch::Sender::new(AuthState::Pending)),
+            accepted_once: Arc::new(AtomicBool::new(false)),
+            region: Arc::new(RwLock::new(None)),
+            mismatch: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    pub fn ssid(&self) -> Ssid {
+        self.ssid.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn set_provider(&self, provider: Arc<dyn SessionProvider>) {
+        if let Ok(mut current) = self.provider.write() {
+            *current = Some(provider);
+        }
+    }
+
+    pub fn remove_provider(&self) {
+        if let Ok(mut current) = self.provider.write() {
+            *current = None;
+        }
+    }
+
+    fn provider(&self) -> Option<Arc<dyn SessionProvider>> {
+        self.provider.read().ok().and_then(|p| p.clone())
+    }
+
+    pub fn has_provider(&self) -> bool {
+        self.provider().is_some()
+    }
+
+    /// Marks the session as rejected by the server, the next connection asks the provider for a
+    /// fresh one.
+    pub fn expire(&self) {
+        if !self.expired.swap(true, Ordering::SeqCst) {
+            warn!(target: "Session", "The session was rejected by the server");
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expired.load(Ordering::SeqCst)
+    }
+
+    /// Records that the server accepted the session (`successauth`).
+    pub fn accept(&self) {
+        self.accepted_once.store(true, Ordering::SeqCst);
+        self.expired.store(false, Ordering::SeqCst);
+        self.auth.send_replace(AuthState::Authenticated);
+    }
+
+    /// Records that the server rejected the session (`NotAuthorized`) and returns why: a session
+    /// that doesn't look like one issued by the server is malformed, a well formed one (or one the
+    /// server accepted before) expired.
+    pub fn reject(&self) -> AuthRejection {
+        self.expired.store(true, Ordering::SeqCst);
+        let ssid = self.ssid();
+        let (failure, reason) = match ssid.malformation() {
+            Some(reason) => (AuthFailure::Malformed, reason),
+            None if self.accepted_once.load(Ordering::SeqCst) => (
+                AuthFailure::Expired,
+                "the server accepted it before, it expired or was closed (e.g. by logging out from the browser)"
+                    .to_string(),
+            ),
+            None => (
+                AuthFailure::Expired,
+                match ssid.info().last_activity {
+                    Some(last) => format!(
+                        "it's well formed but the server doesn't know it anymore (last activity on {}), copy a fresh one from the browser",
+                        last.format("%Y-%m-%d %H:%M UTC")
+                    ),
+                    None => "it's well formed but the server doesn't know it anymore, copy a fresh one from the browser"
+                        .to_string(),
+                },
+            ),
+        };
+        let rejection = AuthRejection {
+            failure,
+            reason,
+            time: Utc::now(),
+        };
+        warn!(target: "Session", "Authentication failed, {rejection}");
+        self.auth
+            .send_replace(AuthState::Rejected(rejection.clone()));
+        rejection
+    }
+
+    /// Last rejection of the session, `None` if the server didn't answer yet or accepted it.
+    pub fn rejection(&self) -> Option<AuthRejection> {
+        match &*self.auth.borrow() {
+            AuthState::Rejected(rejection) => Some(rejection.clone()),
+            _ => None,
+        }
+    }
+
+    /// Records a region or IP mismatch reported by the server, the session is rejected until a
+    /// fresh one is accepted.
+    pub fn mismatch(&self, mismatch: RegionMismatch) {
+        self.expired.store(true, Ordering::SeqCst);
+        warn!(target: "Session", "{mismatch}, {}", mismatch.hints.join("; "));
+        if let Some(region) = &mismatch.region {
+            self.set_region(region.clone());
+        }
+        if let Ok(mut current) = self.mismatch.write() {
+            *current = Some(mismatch.clone());
+        }
+        self.auth.send_replace(AuthState::Mismatch(mismatch));
+    }
+
+    /// Last region or IP mismatch reported by the server, `None` if it never reported one.
+    pub fn region_mismatch(&self) -> Option<RegionMismatch> {
+        self.mismatch.read().ok().and_then(|m| m.clone())
+    }
+
+    pub fn set_region(&self, region: String) {
+        if let Ok(mut current) = self.region.write() {
+            *current = Some(region);
+        }
+    }
+
+    /// Region reported by the server, `None` until it sends one.
+    pub fn region(&self) -> Option<String> {
+        self.region.read().ok().and_then(|r| r.clone())
+    }
+
+    /// Fails with an `AuthenticationError` (or a `RegionMismatch` error) if the server rejected the
+    /// session and no provider can replace it, the requests would only wait for their timeouts.
+    pub fn ensure_authenticated(&self) -> PocketResult<()> {
+        if self.has_provider() {
+            return Ok(());
+        }
+        match &*self.auth.borrow() {
+            AuthState::Rejected(rejection) => Err(rejection.error()),
+            AuthState::Mismatch(mismatch) => Err(mismatch.error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Waits up to `timeout` for the answer of the server to the authentication, and fails with an
+    /// `AuthenticationError` (or a `RegionMismatch` error) if it rejected the session. Without an answer the session is assumed
+    /// to be valid.
+    pub async fn wait_authentication(&self, timeout: Duration) -> PocketResult<()> {
+        let mut auth = self.auth.subscribe();
+        let state =
+            tokio::time::timeout(timeout, auth.wait_for(|state| *state != AuthState::Pending))
+                .await;
+        match state {
+            Ok(Ok(state)) => match &*state {
+                AuthState::Rejected(rejection) => Err(rejection.error()),
+                AuthState::Mismatch(mismatch) => Err(mismatch.error()),
+                _ => Ok(()),
+            },
+            _ => {
+                warn!(target: "Session", "The server didn't answer the authentication within {timeout:?}");
+                Ok(())
+            }
+        }
+    }
+
+    pub fn status(&self) -> SessionStatus {
+        let info = self.ssid().info();
+        SessionStatus {
+            uid: info.uid,
+            demo: info.demo,
+            expired: self.is_expired(),
+            provider: self.has_provider(),
+            refreshes: self.refreshes.load(Ordering::SeqCst),
+            refreshed_at: self.refreshed_at.read().ok().and_then(|t| *t),
+            rejection: self.rejection(),
+        }
+    }
+
+    /// Asks the provider for a fresh session and caches it. The refreshes requested while the
+    /// provider is called wait for its session instead of calling it again.
+    ///
+    /// Fails if no provider is set, if the provider fails or if its session isn't one of the
+    /// account of the client.
+    pub async fn refresh(&self) -> PocketResult<Ssid> {
+        let provider = self
+            .provider()
+            .ok_or_else(|| PocketOptionError::Unallowed("no session provider is set".into()))?;
+        let refreshes = self.refreshes.load(Ordering::SeqCst);
+        let _refreshing = self.refreshing.lock().await;
+        if self.refreshes.load(Ordering::SeqCst) != refreshes {
+            return Ok(self.ssid());
+        }
+        let current = self.ssid().info();
+        let raw = provider.fresh_ssid().await?;
+        // The account of the whole auth messages is kept to check it, the uid is only given for the
+        // sessions that don't have one (cookies, tokens)
+        let ssid = Ssid::parse_lenient(&raw, None, None)
+            .or_else(|_| Ssid::parse_lenient(&raw, Some(current.uid), Some(current.demo)))?;
+        let info = ssid.info();
+        if info.uid != current.uid || info.demo != current.demo {
+            return Err(PocketOptionError::Unallowed(format!(
+                "the session provider returned a session of the {} account {}, expected the {} account {}",
+                account_kind(info.demo),
+                info.uid,
+                account_kind(current.demo),
+                current.uid
+            )));
+        }
+        if let Ok(mut current) = self.ssid.write() {
+            *current = ssid.clone();
+        }
+        if let Ok(mut refreshed_at) = self.refreshed_at.write() {
+            *refreshed_at = Some(Utc::now());
+        }
+        self.refreshes.fetch_add(1, Ordering::SeqCst);
+        self.expired.store(false, Ordering::SeqCst);
+        info!(target: "Session", "Recieved a fresh session for account {}", info.uid);
+        Ok(ssid)
+    }
+
+    /// Session to connect with, a fresh one if the server rejected the current one and a provider
+    /// is set, the current one is kept if the provider fails.
+    pub async fn connection_ssid(&self) -> Ssid {
+        if !self.is_expired() || !self.has_provider() {
+            return self.ssid();
+        }
+        if let Err(e) = self.refresh().await {
+            warn!(target: "Session", "Failed to get a fresh session, connecting with the current one, {e}");
+        }
+        self.ssid()
+    }
+}
+
+fn account_kind(demo: bool) -> &'static str {
+    if demo { "demo" } else { "real" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::pocketoption::types::region::MismatchKind;
+
+    struct Sessions(std::sync::Mutex<Vec<String>>);
+
+    #[async_trait]
+    impl SessionProvider for Sessions {
+        async fn fresh_ssid(&self) -> PocketResult<String> {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            self.0
+                .lock()
+                .unwrap()
+                .pop()
+                .ok_or_else(|| PocketOptionError::Unallowed("no session left".into()))
+        }
+    }
+
+    fn auth(session: &str, uid: u32) -> String {
+        format!(r#"42["auth",{{"session":"{session}","isDemo":1,"uid":{uid},"platform":2}}]"#)
+    }
+
+    #[tokio::test]
+    async fn test_session_refresh() -> anyhow::Result<()> {
+        let session = Session::new(Ssid::parse(auth("first", 87742848))?);
+        assert!(session.refresh().await.is_err());
+
+        let provider = Sessions(std::sync::Mutex::new(vec![
+            auth("other_account", 1),
+            "fresh".to_string(),
+        ]));
+        session.set_provider(Arc::new(provider));
+        // The session isn't refreshed until the server rejects it
+        assert_eq!(session.connection_ssid().await.info().session_id, "first");
+
+        session.expire();
+        let (a, b) = tokio::join!(session.connection_ssid(), session.connection_ssid());
+        assert_eq!(a.info().session_id, "fresh");
+        assert_eq!(b.info().session_id, "fresh");
+        let status = session.status();
+        assert_eq!(status.refreshes, 1);
+        assert!(!status.expired && status.provider && status.demo);
+
+        // The sessions of other accounts are rejected and the current one is kept
+        assert!(session.refresh().await.is_err());
+        assert_eq!(session.ssid().info().session_id, "fresh");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_session_rejection() -> anyhow::Result<()> {
+        let timeout = std::time::Duration::from_millis(10);
+        // A truncated token can't be a session of the server
+        let session = Session::new(Ssid::parse(auth("vtftn12", 27658142))?);
+        assert!(session.wait_authentication(timeout).await.is_ok());
+        assert_eq!(session.reject().failure, AuthFailure::Malformed);
+        assert!(matches!(
+            session.wait_authentication(timeout).await,
+            Err(PocketOptionError::AuthenticationError {
+                failure: AuthFailure::Malformed,
+                ..
+            })
+        ));
+        assert!(session.ensure_authenticated().is_err());
+
+        let session = Session::new(Ssid::parse(auth("vtftn12e6f5f5008moitsd6skl", 27658142))?);
+        session.accept();
+        assert!(session.wait_authentication(timeout).await.is_ok());
+        let rejection = session.reject();
+        assert_eq!(rejection.failure, AuthFailure::Expired);
+        assert!(rejection.reason.contains("accepted it before"));
+        assert!(session.status().expired);
+        assert_eq!(session.status().rejection, Some(rejection));
+
+        // The client reconnects with a fresh session of the provider instead of failing
+        session.set_provider(Arc::new(Sessions(std::sync::Mutex::new(vec![]))));
+        assert!(session.ensure_authenticated().is_ok());
+        session.accept();
+        assert!(session.status().rejection.is_none());
+
+        // A mismatch reported by the server is kept for the profile once accepted again
+        let session = Session::new(Ssid::parse(auth("vtftn12e6f5f5008moitsd6skl", 27658142))?);
+        let mut mismatch = RegionMismatch::new(MismatchKind::Region, "restricted");
+        mismatch.region = Some("US".to_string());
+        session.mismatch(mismatch);
+        assert!(matches!(
+            session.wait_authentication(timeout).await,
+            Err(PocketOptionError::RegionMismatch { .. })
+        ));
+        assert!(session.ensure_authenticated().is_err());
+        assert_eq!(session.region().as_deref(), Some("US"));
+        session.accept();
+        assert!(session.ensure_authenticated().is_ok());
+        assert!(session.region_mismatch().is_some());
+        Ok(())
+    }
+}
@@ -1,8 +1,10 @@
 use core::fmt;
 use std::collections::HashMap;
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{Map, Value, json};
+use url::form_urlencoded;
 
 use crate::pocketoption::error::{PocketOptionError, PocketResult};
 use binary_options_tools_core::general::traits::Credentials;
@@ -41,6 +43,35 @@ pub struct Real {
     raw: String,
 }
 
+/// Platform id of the web version of the broker, the one of the sessions copied from the browser.
+const WEB_PLATFORM: u32 = 2;
+
+/// Cookie holding the session of the real accounts.
+const SESSION_COOKIE: &str = "ci_session";
+
+/// Length of the ids and of the signature of the sessions issued by the server, in hex digits.
+const SESSION_HASH_LEN: usize = 32;
+/// Minimum length of the session tokens of the demo accounts.
+const MIN_DEMO_TOKEN_LEN: usize = 20;
+
+const AUTH_FRAME_HINT: &str = r#"copy the whole 42["auth",...] message sent by the browser (devtools > Network > WS > Messages)"#;
+
+/// Account and session of an SSID, as returned by `parse_ssid`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SsidInfo {
+    pub demo: bool,
+    pub uid: u32,
+    pub platform: u32,
+    /// Id of the session, the whole session token for the demo accounts.
+    pub session_id: String,
+    /// Address and browser the session of a real account was created from.
+    pub ip_address: Option<String>,
+    pub user_agent: String,
+    pub last_activity: Option<DateTime<Utc>>,
+    /// Normalized SSID, the `42["auth",...]` message accepted by the clients.
+    pub ssid: String,
+}
+
 #[derive(Debug, Serialize, Clone)]
 #[serde(untagged)]
 pub enum Ssid {
@@ -79,6 +110,47 @@ impl Ssid {
         }
     }
 
+    /// Parses the session strings users copy from the browser, normalizing them to the
+    /// `42["auth",...]` message: the whole message (even quoted as a string literal), its json
+    /// object, the cookies of the site (with the `ci_session` cookie) or just the session token.
+    /// The cookies and the tokens don't have the id of the account, it must be given with `uid`,
+    /// and the account is only known to be a demo one if `demo` is given or the token isn't the
+    /// session of a real account.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let ssid = Ssid::parse_lenient("ci_session=a%3A4%3A%7B...", Some(87742848), None)?;
+    /// println!("{}", ssid.info().ssid);
+    /// ```
+    pub fn parse_lenient(raw: &str, uid: Option<u32>, demo: Option<bool>) -> PocketResult<Self> {
+        Self::parse(normalize(raw, uid, demo)?)
+    }
+
+    pub fn info(&self) -> SsidInfo {
+        match self {
+            Self::Demo(demo) => SsidInfo {
+                demo: true,
+                uid: demo.uid,
+                platform: demo.platform,
+                session_id: demo.session.clone(),
+                ip_address: None,
+                user_agent: self.user_agent(),
+                last_activity: None,
+                ssid: self.to_string(),
+            },
+            Self::Real(real) => SsidInfo {
+                demo: false,
+                uid: real.uid,
+                platform: real.platform,
+                session_id: real.session.session_id.clone(),
+                ip_address: Some(real.session.ip_address.clone()),
+                user_agent: real.session.user_agent.clone(),
+                last_activity: DateTime::from_timestamp(real.session.last_activity as i64, 0),
+                ssid: self.to_string(),
+            },
+        }
+    }
+
     pub async fn server(&self) -> PocketResult<String> {
         match self {
             Self::Demo(_) => Ok(Regions::DEMO.0.to_string()),
@@ -114,7 +186,169 @@ impl Ssid {
             Self::Real(_) => false,
         }
     }
+
+    /// Why the session can't be one issued by the server (e.g. it was truncated or edited while
+    /// copying it), `None` if it looks valid. Used to tell a malformed session from an expired one
+    /// once the server rejects it.
+    pub fn malformation(&self) -> Option<String> {
+        let is_hash = |value: &str| {
+            value.len() == SESSION_HASH_LEN && value.chars().all(|c| c.is_ascii_hexdigit())
+        };
+        match self {
+            Self::Demo(demo) if demo.uid == 0 => Some("the uid of the account is 0".to_string()),
+            Self::Demo(demo) if !demo.session.chars().all(|c| c.is_ascii_alphanumeric()) => Some(
+                "the session token contains characters the server never uses, it was probably edited"
+                    .to_string(),
+            ),
+            Self::Demo(demo) if demo.session.len() < MIN_DEMO_TOKEN_LEN => Some(format!(
+                "the session token only has {} characters, it was probably truncated",
+                demo.session.len()
+            )),
+            Self::Demo(_) => None,
+            Self::Real(real) if real.uid == 0 => Some("the uid of the account is 0".to_string()),
+            Self::Real(real) if !is_hash(&real.session.session_id) => Some(format!(
+                "the session id '{}' isn't {SESSION_HASH_LEN} hex digits",
+                real.session.session_id
+            )),
+            Self::Real(real) => {
+                // The serialized session data is followed by its signature
+                let signature = real.session_token().and_then(|session| {
+                    session
+                        .rsplit_once('}')
+                        .map(|(_, signature)| signature.to_string())
+                });
+                match signature {
+                    Some(signature) if is_hash(&signature) => None,
+                    _ => Some(format!(
+                        "the signature at the end of the session isn't {SESSION_HASH_LEN} hex digits, it was probably truncated"
+                    )),
+                }
+            }
+        }
+    }
+}
+
+impl Real {
+    /// Session string of the auth message, the serialized session data and its signature.
+    fn session_token(&self) -> Option<String> {
+        let object = self
+            .raw
+            .trim()
+            .strip_prefix(r#"42["auth","#)?
+            .strip_suffix(']')?;
+        serde_json::from_str::<Demo>(object)
+            .ok()
+            .map(|auth| auth.session)
+    }
+}
+fn ssid_error(message: impl fmt::Display) -> PocketOptionError {
+    PocketOptionError::SsidParsingError(message.to_string())
+}
+
+/// Returns the `42["auth",...]` message of the session string `raw`, see `Ssid::parse_lenient`.
+pub fn normalize(raw: &str, uid: Option<u32>, demo: Option<bool>) -> PocketResult<String> {
+    let mut raw = raw.trim().to_string();
+    // Copied as a string literal, e.g. `"42[\"auth\",{...}]"` from the devtools console
+    if raw.len() > 1 && raw.starts_with('"') && raw.ends_with('"') {
+        raw = serde_json::from_str::<String>(&raw)
+            .map_err(|e| ssid_error(format!("invalid quoted ssid, {e}")))?
+            .trim()
+            .to_string();
+    } else if raw.len() > 1 && raw.starts_with('\'') && raw.ends_with('\'') {
+        raw = raw[1..raw.len() - 1].trim().to_string();
+    }
+    if raw.is_empty() {
+        return Err(ssid_error(format!("the ssid is empty, {AUTH_FRAME_HINT}")));
+    }
+
+    let mut auth = if let Some(frame) = raw.strip_prefix("42") {
+        let frame: Vec<Value> = serde_json::from_str(frame).map_err(|e| {
+            ssid_error(format!(
+                "the message isn't valid json, {e}. Make sure it was copied entirely"
+            ))
+        })?;
+        match frame.as_slice() {
+            [Value::String(event), Value::Object(auth)] if event == "auth" => auth.clone(),
+            [Value::String(event), ..] => {
+                return Err(ssid_error(format!(
+                    "the message is a '{event}' message instead of an 'auth' one, {AUTH_FRAME_HINT}"
+                )));
+            }
+            _ => {
+                return Err(ssid_error(format!(
+                    "the message isn't an auth message, {AUTH_FRAME_HINT}"
+                )));
+            }
+        }
+    } else if raw.starts_with('{') {
+        serde_json::from_str::<Map<String, Value>>(&raw)
+            .map_err(|e| ssid_error(format!("the auth object isn't valid json, {e}")))?
+    } else {
+        let session = if raw.contains(&format!("{SESSION_COOKIE}=")) {
+            cookie(&raw, SESSION_COOKIE)
+                .ok_or_else(|| ssid_error(format!("the '{SESSION_COOKIE}' cookie is empty")))?
+        } else if raw.contains('=') && raw.contains(';') {
+            return Err(ssid_error(format!(
+                "the cookies don't have the '{SESSION_COOKIE}' cookie of the session, {AUTH_FRAME_HINT}"
+            )));
+        } else {
+            raw.clone()
+        };
+        let mut auth = Map::new();
+        auth.insert("session".into(), Value::String(session));
+        auth
+    };
+
+    let session = match auth.get("session") {
+        Some(Value::String(session)) if !session.trim().is_empty() => session.trim().to_string(),
+        Some(_) => return Err(ssid_error("the session of the ssid is empty")),
+        None => {
+            return Err(ssid_error(format!(
+                "the ssid doesn't have a session, {AUTH_FRAME_HINT}"
+            )));
+        }
+    };
+    // The sessions of the real accounts are serialized php arrays
+    let is_demo = match (demo, auth.get("isDemo").and_then(Value::as_u64)) {
+        (Some(demo), _) => demo,
+        (None, Some(is_demo)) => is_demo == 1,
+        (None, None) => !session.starts_with("a:"),
+    };
+    let uid = match (auth.get("uid").and_then(Value::as_u64), uid) {
+        (_, Some(uid)) => uid as u64,
+        (Some(uid), None) => uid,
+        (None, None) => {
+            return Err(ssid_error(format!(
+                "the id of the account is missing, give the uid of the account (shown in the profile) or {AUTH_FRAME_HINT}"
+            )));
+        }
+    };
+    if uid == 0 || uid > u32::MAX as u64 {
+        return Err(ssid_error(format!("invalid account id {uid}")));
+    }
+    if !is_demo && !session.starts_with("a:") {
+        return Err(ssid_error(
+            "the session of a real account must be the serialized session (starting with 'a:4:{'), the token looks like the one of a demo account",
+        ));
+    }
+    auth.insert("session".into(), Value::String(session));
+    auth.insert("isDemo".into(), json!(is_demo as u32));
+    auth.insert("uid".into(), json!(uid));
+    auth.entry("platform").or_insert(json!(WEB_PLATFORM));
+    Ok(format!(r#"42["auth",{}]"#, Value::Object(auth)))
 }
+
+/// Returns the url-decoded value of the cookie `name`, `None` if it's empty.
+fn cookie(cookies: &str, name: &str) -> Option<String> {
+    cookies
+        .split(';')
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(key, _)| key.trim() == name)
+        .and_then(|(_, value)| form_urlencoded::parse(value.trim().as_bytes()).next())
+        .map(|(value, _)| value.into_owned())
+        .filter(|value| !value.is_empty())
+}
+
 impl fmt::Display for Demo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let ssid = serde_json::to_string(&self).map_err(|_| fmt::Error)?;
@@ -177,8 +411,53 @@ mod tests {
         ];
         for ssid in ssids {
             let valid = Ssid::parse(ssid)?;
+            assert_eq!(valid.malformation(), None);
             dbg!(valid);
         }
+        // Truncated signature
+        let truncated = ssids[2].replace("9bef184e52d025d1f07068eeaf555637", "9bef184e");
+        assert!(Ssid::parse(truncated)?.malformation().is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_lenient() -> Result<(), Box<dyn Error>> {
+        let frame = r#"42["auth",{"session":"vtftn12e6f5f5008moitsd6skl","isDemo":1,"uid":27658142,"platform":2}]"#;
+        let expected = Ssid::parse(frame)?.info();
+        assert!(expected.demo);
+        assert_eq!(expected.uid, 27658142);
+
+        let quoted = serde_json::to_string(frame)?;
+        let object = frame
+            .strip_prefix(r#"42["auth","#)
+            .unwrap()
+            .strip_suffix(']')
+            .unwrap();
+        for raw in [frame, &format!("  {frame}\t"), &quoted, object] {
+            assert_eq!(Ssid::parse_lenient(raw, None, None)?.info(), expected);
+        }
+        let token = Ssid::parse_lenient("vtftn12e6f5f5008moitsd6skl", Some(27658142), None)?;
+        assert_eq!(token.info(), expected);
+
+        let session = r#"a:4:{s:10:"session_id";s:32:"ae3aa847add89c341ec18d8ae5bf8527";s:10:"ip_address";s:15:"191.113.157.139";s:10:"user_agent";s:11:"Mozilla/5.0";s:13:"last_activity";i:1732926685;}31666d2dc07fdd866353937b97901e2b"#;
+        let cookies = format!(
+            "lang=en; {SESSION_COOKIE}={}; _ga=GA1.1",
+            form_urlencoded::byte_serialize(session.as_bytes()).collect::<String>()
+        );
+        let real = Ssid::parse_lenient(&cookies, Some(87742848), None)?.info();
+        assert!(!real.demo);
+        assert_eq!(real.session_id, "ae3aa847add89c341ec18d8ae5bf8527");
+        assert_eq!(real.ip_address.as_deref(), Some("191.113.157.139"));
+        assert_eq!(real.user_agent, "Mozilla/5.0");
+
+        // Clear errors instead of json errors
+        assert!(Ssid::parse_lenient(&cookies, None, None).is_err());
+        assert!(Ssid::parse_lenient("lang=en; _ga=GA1.1", Some(1), None).is_err());
+        assert!(
+            Ssid::parse_lenient(r#"42["changeSymbol",{"asset":"EURUSD"}]"#, None, None).is_err()
+        );
+        assert!(Ssid::parse_lenient("vtftn12e6f5f5008moitsd6skl", Some(1), Some(false)).is_err());
+        assert!(Ssid::parse_lenient("  ", None, None).is_err());
         Ok(())
     }
 }
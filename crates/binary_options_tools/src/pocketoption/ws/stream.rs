@@ -10,13 +10,14 @@ use crate::pocketoption::{
     error::PocketResult, parser::message::WebSocketMessage, types::update::DataCandle,
 };
 
-use async_channel::{Receiver, RecvError};
+use crate::pocketoption::types::lanes::StreamReceiver;
+use async_channel::RecvError;
 use futures_util::Stream;
 use futures_util::stream::unfold;
 
 #[derive(Clone)]
 pub struct StreamAsset {
-    reciever: Receiver<WebSocketMessage>,
+    reciever: StreamReceiver,
     asset: String,
     condition: ConditonnalUpdate,
 }
@@ -67,6 +68,10 @@ impl ConditonnalUpdate {
                     current.high = current.high.max(new_candle.high);
                     current.low = current.low.min(new_candle.low);
                     current.close = new_candle.close;
+                current.received_at = new_candle.received_at;
+                current.latency = new_candle.latency;
+                    current.received_at = new_candle.received_at;
+                    current.latency = new_candle.latency;
                 }
                 *count += 1;
                 
@@ -90,6 +95,8 @@ impl ConditonnalUpdate {
                 current.high = current.high.max(new_candle.high);
                 current.low = current.low.min(new_candle.low);
                 current.close = new_candle.close;
+                current.received_at = new_candle.received_at;
+                current.latency = new_candle.latency;
                 
                 let elapsed = (new_candle.time - start_time.unwrap())
                     .to_std()
@@ -117,34 +124,39 @@ impl ConditonnalUpdate {
 }
 
 impl StreamAsset {
-    pub fn new(reciever: Receiver<WebSocketMessage>, asset: String) -> Self {
+    pub fn new(reciever: impl Into<StreamReceiver>, asset: String) -> Self {
         Self {
-            reciever,
+            reciever: reciever.into(),
             asset,
             condition: ConditonnalUpdate::None,
         }
     }
 
     pub fn new_chuncked(
-        reciever: Receiver<WebSocketMessage>,
+        reciever: impl Into<StreamReceiver>,
         asset: String,
         chunk_size: usize,
     ) -> Self {
         Self {
-            reciever,
+            reciever: reciever.into(),
             asset,
             condition: ConditonnalUpdate::new_size(chunk_size),
         }
     }
 
-    pub fn new_timed(reciever: Receiver<WebSocketMessage>, asset: String, time: Duration) -> Self {
+    pub fn new_timed(reciever: impl Into<StreamReceiver>, asset: String, time: Duration) -> Self {
         Self {
-            reciever,
+            reciever: reciever.into(),
             asset,
             condition: ConditonnalUpdate::new_time(time),
         }
     }
 
+    /// Asset the candles of the stream are of.
+    pub fn asset(&self) -> &str {
+        &self.asset
+    }
+
     pub async fn recieve(&self) -> PocketResult<DataCandle> {
         let mut condition = self.condition.clone();
         
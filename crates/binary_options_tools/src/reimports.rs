@@ -1,5 +1,5 @@
 pub use binary_options_tools_core::general::traits::ValidatorTrait;
 pub use binary_options_tools_core::general::stream::FilteredRecieverStream;
-pub use binary_options_tools_core::general::config::ConfigBuilder;
+pub use binary_options_tools_core::general::config::{_Config, ConfigBuilder, ConnectionTuning};
 
 pub use binary_options_tools_macros::Config;
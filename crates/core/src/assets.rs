@@ -0,0 +1,64 @@
+//! Symbols of the OTC assets and the fallback from a closed regular asset to its OTC version.
+//!
+//! Brokers quote an OTC version of most regular assets (e.g. `EURUSD_otc` for `EURUSD`), traded
+//! while the regular market is closed on weekends and holidays.
+
+/// Suffix of the symbols of the OTC assets.
+pub const OTC_SUFFIX: &str = "_otc";
+
+pub fn is_otc_symbol(symbol: &str) -> bool {
+    symbol.ends_with(OTC_SUFFIX)
+}
+
+/// OTC version of a symbol, the symbol itself if it's already an OTC one.
+pub fn otc_symbol(symbol: &str) -> String {
+    if is_otc_symbol(symbol) {
+        symbol.to_string()
+    } else {
+        format!("{symbol}{OTC_SUFFIX}")
+    }
+}
+
+/// Regular version of a symbol, the symbol itself if it's already a regular one.
+pub fn regular_symbol(symbol: &str) -> &str {
+    symbol.strip_suffix(OTC_SUFFIX).unwrap_or(symbol)
+}
+
+/// Symbol to use instead of the regular `symbol` when its market is closed and the market of its
+/// OTC version is open, `None` to keep `symbol`.
+/// `is_open` and `otc_is_open` are the open states of both symbols, `None` if they're unknown, an
+/// unknown state never switches the symbol.
+pub fn otc_fallback(
+    symbol: &str,
+    is_open: Option<bool>,
+    otc_is_open: Option<bool>,
+) -> Option<String> {
+    match (is_otc_symbol(symbol), is_open, otc_is_open) {
+        (false, Some(false), Some(true)) => Some(otc_symbol(symbol)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_otc_fallback() {
+        assert_eq!(otc_symbol("EURUSD"), "EURUSD_otc");
+        assert_eq!(otc_symbol("#AAPL_otc"), "#AAPL_otc");
+        assert_eq!(regular_symbol("EURUSD_otc"), "EURUSD");
+        assert_eq!(regular_symbol("EURUSD"), "EURUSD");
+
+        assert_eq!(
+            otc_fallback("EURUSD", Some(false), Some(true)),
+            Some("EURUSD_otc".to_string())
+        );
+        assert_eq!(otc_fallback("EURUSD", Some(true), Some(true)), None);
+        // Both closed, the order is left to fail on the regular asset
+        assert_eq!(otc_fallback("EURUSD", Some(false), Some(false)), None);
+        assert_eq!(otc_fallback("EURUSD", Some(false), None), None);
+        assert_eq!(otc_fallback("EURUSD", None, Some(true)), None);
+        assert_eq!(otc_fallback("EURUSD_otc", Some(false), Some(true)), None);
+    }
+}
@@ -1,7 +1,11 @@
 pub const MAX_LOGGING_CHANNEL_CAPACITY: usize = 128;
 pub const MAX_CHANNEL_CAPACITY: usize = 8;
+/// Maximum number of queued messages written to the websocket before flushing it
+pub const MAX_WRITE_BATCH: usize = 64;
 pub const RECONNECT_CALLBACK: u64 = 5;
 /// How much time to wait after the reconnection before starting the callback  
 pub const TIMEOUT_TIME: u64 = 16;
 pub const MAX_ALLOWED_LOOPS: u32 = 8;
 pub const SLEEP_INTERVAL: u64 = 2;
+/// Milliseconds the writer gets to send the close frame before the connection is dropped on shutdown
+pub const SHUTDOWN_GRACE_PERIOD: u64 = 250;
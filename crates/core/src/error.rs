@@ -4,7 +4,10 @@ use thiserror::Error;
 
 use tokio_tungstenite::tungstenite::{Error as TungsteniteError, Message, http};
 
-use crate::general::traits::MessageTransfer;
+use crate::{
+    general::traits::MessageTransfer,
+    utils::locale::{Catalog, Locale, translate},
+};
 
 #[derive(Error, Debug)]
 pub enum BinaryOptionsToolsError {
@@ -54,12 +57,93 @@ pub enum BinaryOptionsToolsError {
     TimeoutError { task: String, duration: Duration },
     #[error("Failed to parse duration, error {0}")]
     ChronoDurationParsingError(#[from] chrono::OutOfRangeError),
+    #[error("IO error, {0}")]
+    IOError(#[from] std::io::Error),
     #[error("Unknown error during execution, error {0}")]
     UnknownError(#[from] anyhow::Error),
 }
 
 pub type BinaryOptionsResult<T> = Result<T, BinaryOptionsToolsError>;
 
+/// Translations of the most common messages of `BinaryOptionsToolsError`, the English ones are
+/// the messages of the variants.
+const CATALOG: &Catalog = &[
+    (
+        "websocket_closed",
+        [
+            "Websocket connection was closed by the server, {0}",
+            "El servidor cerró la conexión websocket, {0}",
+            "A conexão websocket foi encerrada pelo servidor, {0}",
+            "Сервер закрыл websocket-соединение, {0}",
+        ],
+    ),
+    (
+        "websocket_connection",
+        [
+            "Failed to connect to websocket server: {0}",
+            "No se pudo conectar al servidor websocket: {0}",
+            "Falha ao conectar ao servidor websocket: {0}",
+            "Не удалось подключиться к websocket-серверу: {0}",
+        ],
+    ),
+    (
+        "max_reconnect",
+        [
+            "Failed to reconnect '{0}' times, maximum allowed number of reconnections was reached, breaking",
+            "La reconexión falló {0} veces, se alcanzó el número máximo de reconexiones permitido",
+            "A reconexão falhou {0} vezes, o número máximo de reconexões permitido foi atingido",
+            "Не удалось переподключиться {0} раз, достигнуто максимальное число переподключений",
+        ],
+    ),
+    (
+        "unallowed",
+        [
+            "Unallowed operation, {0}",
+            "Operación no permitida, {0}",
+            "Operação não permitida, {0}",
+            "Недопустимая операция, {0}",
+        ],
+    ),
+    (
+        "timeout",
+        [
+            "Failed to execute '{0}' task before the maximum allowed time of '{1}'",
+            "La tarea '{0}' no terminó antes del tiempo máximo permitido de '{1}'",
+            "A tarefa '{0}' não terminou antes do tempo máximo permitido de '{1}'",
+            "Задача '{0}' не завершилась за максимально допустимое время '{1}'",
+        ],
+    ),
+];
+
+impl BinaryOptionsToolsError {
+    /// Message of the error in `locale`, the messages missing from the catalog are in English.
+    pub fn localized(&self, locale: Locale) -> String {
+        if locale == Locale::En {
+            return self.to_string();
+        }
+        let translated = match self {
+            Self::WebsocketConnectionClosed(reason) => {
+                translate(CATALOG, "websocket_closed", locale, &[reason])
+            }
+            Self::WebsocketConnectionError(e) => {
+                translate(CATALOG, "websocket_connection", locale, &[e])
+            }
+            Self::MaxReconnectAttemptsReached(attempts) => {
+                translate(CATALOG, "max_reconnect", locale, &[attempts])
+            }
+            Self::Unallowed(reason) => translate(CATALOG, "unallowed", locale, &[reason]),
+            Self::TimeoutError { task, duration } => translate(
+                CATALOG,
+                "timeout",
+                locale,
+                &[task, &format!("{duration:?}")],
+            ),
+            _ => None,
+        };
+        translated.unwrap_or_else(|| self.to_string())
+    }
+}
+
 impl<Transfer> From<Transfer> for BinaryOptionsToolsError
 where
     Transfer: MessageTransfer,
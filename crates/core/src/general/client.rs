@@ -1,24 +1,27 @@
 use std::ops::Deref;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
-use async_channel::{Receiver, RecvError};
+use async_channel::Receiver;
 use futures_util::future::try_join3;
-use futures_util::stream::{SplitSink, SplitStream, select_all};
-use futures_util::{SinkExt, StreamExt};
+use futures_util::stream::{SplitSink, SplitStream};
+use futures_util::{Sink, SinkExt, StreamExt};
 use tokio::net::TcpStream;
 use tokio::task::JoinHandle;
-use tokio::time::sleep;
-use tokio_tungstenite::tungstenite::Message;
+use tokio::time::{sleep, timeout};
+use tokio_tungstenite::tungstenite::{self, Message};
 use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
 use tracing::{debug, error, info, warn};
 
-use crate::constants::MAX_CHANNEL_CAPACITY;
+use crate::constants::{MAX_CHANNEL_CAPACITY, MAX_WRITE_BATCH, SHUTDOWN_GRACE_PERIOD};
 use crate::error::{BinaryOptionsResult, BinaryOptionsToolsError};
-use crate::general::stream::RecieverStream;
 use crate::general::types::MessageType;
+use crate::utils::thread::spawn_dedicated;
 
 use super::config::Config;
+use super::health::{ConnectionEvent, ConnectionEventKind};
+use super::priority::{Priority, PriorityReceiver};
 use super::send::SenderMessage;
 use super::stream::FilteredRecieverStream;
 use super::traits::{
@@ -56,6 +59,8 @@ where
     pub reconnect_callback: Option<Callback<T, Transfer, U>>,
     pub config: Config<T, Transfer, U>,
     _event_loop: JoinHandle<BinaryOptionsResult<()>>,
+    /// Set by `shutdown`, the event loop doesn't reconnect once it's set.
+    closed: Arc<AtomicBool>,
 }
 
 impl<Transfer, Handler, Connector, Creds, T, U> Deref
@@ -127,6 +132,7 @@ where
         config: Config<T, Transfer, U>,
     ) -> BinaryOptionsResult<Self> {
         let _connection = connector.connect(credentials.clone(), &config).await?; // Check if it's possible to connect before building the struct
+        let closed = Arc::new(AtomicBool::new(false));
         let (_event_loop, sender) = Self::start_loops(
             handler.clone(),
             credentials.clone(),
@@ -134,6 +140,7 @@ where
             connector.clone(),
             reconnect_callback.clone(),
             config.clone(),
+            closed.clone(),
         )
         .await?;
         info!("Started WebSocketClient");
@@ -147,6 +154,7 @@ where
             reconnect_callback,
             config,
             _event_loop,
+            closed,
         })
     }
 
@@ -157,6 +165,7 @@ where
         connector: Connector,
         reconnect_callback: Option<Callback<T, Transfer, U>>,
         config: Config<T, Transfer, U>,
+        closed: Arc<AtomicBool>,
     ) -> BinaryOptionsResult<(JoinHandle<BinaryOptionsResult<()>>, SenderMessage)> {
         let (mut write, mut read) = connector
             .connect(credentials.clone(), &config)
@@ -164,7 +173,9 @@ where
             .split();
         let (sender, (reciever, reciever_priority)) = SenderMessage::new(MAX_CHANNEL_CAPACITY);
         let loop_sender = sender.clone();
-        let task = tokio::task::spawn(async move {
+        data.health.set_connected(true);
+        let dedicated = config.get_connection_tuning()?.dedicated_reader;
+        let event_loop = async move {
             let previous: Option<<Transfer as MessageTransfer>::Info> = None;
             let loops = 0;
             let mut reconnected = false;
@@ -184,6 +195,7 @@ where
                     &connector,
                     &credentials,
                     loops,
+                    &closed,
                 )
                 .await
                 {
@@ -191,15 +203,27 @@ where
                         info!("Reconnected successfully!");
                         (write, read) = res.split();
                         reconnected = true;
+                        data.health.set_connected(true);
+                        data.health.add_reconnection();
                     }
                     Err(e) => {
+                        data.health.set_connected(false);
+                        if closed.load(Ordering::SeqCst) {
+                            info!("Connection closed by shutdown, not reconnecting");
+                            return Ok(());
+                        }
                         if let BinaryOptionsToolsError::MaxReconnectAttemptsReached(_) = e {
                             panic!("Error: {}", e);
                         }
                     }
                 }
             }
-        });
+        };
+        let task = if dedicated {
+            spawn_dedicated("websocket-reader", event_loop)?
+        } else {
+            tokio::task::spawn(event_loop)
+        };
         Ok((task, sender))
     }
 
@@ -211,7 +235,7 @@ where
         loop_sender: &SenderMessage,
         read: &mut SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>,
         write: &mut SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>,
-        reciever: &Receiver<Message>,
+        reciever: &PriorityReceiver,
         reciever_priority: &Receiver<Message>,
         config: &Config<T, Transfer, U>,
         reconnect_callback: &Option<Callback<T, Transfer, U>>,
@@ -219,6 +243,7 @@ where
         connector: &Connector,
         credentials: &Creds,
         mut loops: u32,
+        closed: &AtomicBool,
     ) -> BinaryOptionsResult<WebSocketStream<MaybeTlsStream<TcpStream>>> {
         let listener_future =
             WebSocketInnerClient::<Transfer, Handler, Connector, Creds, T, U>::listener_loop(
@@ -227,6 +252,7 @@ where
                 handler.clone(),
                 loop_sender,
                 read,
+                config.get_connection_tuning()?.reduced_logging,
             );
         let sender_future =
             WebSocketInnerClient::<Transfer, Handler, Connector, Creds, T, U>::sender_loop(
@@ -246,42 +272,70 @@ where
                 config.clone(),
             );
 
-        match try_join3(listener_future, sender_future, callback).await {
+        let res = try_join3(listener_future, sender_future, callback).await;
+        data.health.set_connected(false);
+        if closed.load(Ordering::SeqCst) {
+            // The server answered the close frame of `shutdown`
+            return Err(BinaryOptionsToolsError::WebsocketConnectionClosed(
+                "the client was shut down".into(),
+            ));
+        }
+        match res {
             Ok(_) => {
-                if let Ok(websocket) = connector.connect(credentials.clone(), config).await {
-                    return Ok(websocket);
-                } else {
-                    loops += 1;
-                    let sleep_interval = config.get_sleep_interval()?;
-                    let max_loops = config.get_max_allowed_loops()?;
-                    warn!(
-                        "Error reconnecting... trying again in {sleep_interval} seconds (try {loops} of {max_loops}"
-                    );
-                    sleep(Duration::from_secs(config.get_sleep_interval()?)).await;
-                    if loops >= max_loops {
-                        return Err(BinaryOptionsToolsError::MaxReconnectAttemptsReached(
-                            max_loops,
-                        ));
+                data.health.send_event(
+                    ConnectionEvent::new(ConnectionEventKind::Reconnecting).attempt(loops + 1),
+                );
+                match connector.connect(credentials.clone(), config).await {
+                    Ok(websocket) => return Ok(websocket),
+                    Err(e) => {
+                        loops += 1;
+                        data.health.send_event(
+                            ConnectionEvent::new(ConnectionEventKind::ReconnectFailed)
+                                .attempt(loops)
+                                .reason(e),
+                        );
+                        let sleep_interval = config.get_sleep_interval()?;
+                        let max_loops = config.get_max_allowed_loops()?;
+                        warn!(
+                            "Error reconnecting... trying again in {sleep_interval} seconds (try {loops} of {max_loops}"
+                        );
+                        sleep(Duration::from_secs(config.get_sleep_interval()?)).await;
+                        if loops >= max_loops {
+                            return Err(BinaryOptionsToolsError::MaxReconnectAttemptsReached(
+                                max_loops,
+                            ));
+                        }
                     }
                 }
             }
             Err(e) => {
                 warn!("Error in event loop, {e}, reconnecting...");
                 // println!("Reconnecting...");
-                if let Ok(websocket) = connector.connect(credentials.clone(), config).await {
-                    return Ok(websocket);
-                } else {
-                    loops += 1;
-                    let sleep_interval = config.get_sleep_interval()?;
-                    let max_loops = config.get_max_allowed_loops()?;
-                    warn!(
-                        "Error reconnecting... trying again in {sleep_interval} seconds (try {loops} of {max_loops}"
-                    );
-                    sleep(Duration::from_secs(config.get_sleep_interval()?)).await;
-                    if loops >= max_loops {
-                        return Err(BinaryOptionsToolsError::MaxReconnectAttemptsReached(
-                            max_loops,
-                        ));
+                data.health.send_event(
+                    ConnectionEvent::new(ConnectionEventKind::Reconnecting)
+                        .attempt(loops + 1)
+                        .reason(&e),
+                );
+                match connector.connect(credentials.clone(), config).await {
+                    Ok(websocket) => return Ok(websocket),
+                    Err(e) => {
+                        loops += 1;
+                        data.health.send_event(
+                            ConnectionEvent::new(ConnectionEventKind::ReconnectFailed)
+                                .attempt(loops)
+                                .reason(e),
+                        );
+                        let sleep_interval = config.get_sleep_interval()?;
+                        let max_loops = config.get_max_allowed_loops()?;
+                        warn!(
+                            "Error reconnecting... trying again in {sleep_interval} seconds (try {loops} of {max_loops}"
+                        );
+                        sleep(Duration::from_secs(config.get_sleep_interval()?)).await;
+                        if loops >= max_loops {
+                            return Err(BinaryOptionsToolsError::MaxReconnectAttemptsReached(
+                                max_loops,
+                            ));
+                        }
                     }
                 }
             }
@@ -293,13 +347,15 @@ where
         // unreachable!("Please contact @Rick-29 on github.com this error is completely unexpected and should not happen.")
     }
 
-    /// Recieves all the messages from the websocket connection and handles it
+    /// Recieves all the messages from the websocket connection and handles it, `quiet` skips the
+    /// debug logs of every message.
     async fn listener_loop(
         mut previous: Option<<<Handler as MessageHandler>::Transfer as MessageTransfer>::Info>,
         data: &Data<T, Transfer>,
         handler: Handler,
         sender: &SenderMessage,
         ws: &mut SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>,
+        quiet: bool,
     ) -> BinaryOptionsResult<()> {
         while let Some(msg) = &ws.next().await {
             let msg = msg
@@ -308,6 +364,7 @@ where
                 .map_err(|e| {
                     BinaryOptionsToolsError::WebsocketRecievingConnectionError(e.to_string())
                 })?;
+            data.health.message_received();
             match handler.process_message(msg, &previous, sender).await {
                 Ok((msg, close)) => {
                     if close {
@@ -319,11 +376,15 @@ where
                     if let Some(msg) = msg {
                         match msg {
                             MessageType::Info(info) => {
-                                debug!("Recieved info: {}", info);
+                                if !quiet {
+                                    debug!("Recieved info: {}", info);
+                                }
                                 previous = Some(info);
                             }
                             MessageType::Transfer(transfer) => {
-                                debug!("Recieved data of type: {}", transfer.info());
+                                if !quiet {
+                                    debug!("Recieved data of type: {}", transfer.info());
+                                }
                                 if let Some(senders) = data.update_data(transfer.clone()).await? {
                                     for sender in senders {
                                         sender.send(transfer.clone()).await.map_err(|e| {
@@ -335,7 +396,9 @@ where
                                 }
                             }
                             MessageType::Raw(raw) => {
-                                debug!("Recieved raw message: {:?}", raw);
+                                if !quiet {
+                                    debug!("Recieved raw message: {:?}", raw);
+                                }
                                 data.raw_send(raw).await?;
                             }
                         }
@@ -349,47 +412,50 @@ where
         Err(BinaryOptionsToolsError::WebSocketMessageError("Unexpected error encountered while recieving data from websocket connection. Loop terminated unexpectedly".to_string()))
     }
 
-    /// Recieves all the messages and sends them to the websocket
+    /// Recieves all the messages and sends them to the websocket, during the first `time` seconds only
+    /// the handshake and keepalive messages are sent, then the handshake messages first and the rest by
+    /// priority, so the orders are never delayed by the subscription messages.
+    /// The messages already waiting in the queue are written together and flushed once (up to
+    /// `MAX_WRITE_BATCH`), so bursts like subscribing to many assets don't pay a flush per frame.
     async fn sender_loop(
         ws: &mut SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>,
-        reciever: &Receiver<Message>,
+        reciever: &PriorityReceiver,
         reciever_priority: &Receiver<Message>,
         time: u64,
     ) -> BinaryOptionsResult<()> {
         async fn priority_mesages(
             ws: &mut SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>,
+            reciever: &PriorityReceiver,
             reciever_priority: &Receiver<Message>,
         ) -> BinaryOptionsResult<()> {
-            while let Ok(msg) = reciever_priority.recv().await {
-                ws.send(msg)
-                    .await
-                    .inspect_err(|e| warn!("Error sending message to websocket, {e}"))?;
-                ws.flush().await?;
-                debug!("Sent message to websocket!");
+            loop {
+                let msg = tokio::select! {
+                    biased;
+                    msg = reciever_priority.recv() => msg?,
+                    msg = reciever.recv_lane(Priority::Keepalive) => msg?,
+                };
+                write_batch(ws, msg, || reciever_priority.try_recv().ok()).await?;
             }
-            Err(BinaryOptionsToolsError::ChannelRequestRecievingError(
-                RecvError,
-            ))
         }
 
         tokio::select! {
-            res = priority_mesages(ws, reciever_priority) => res?,
+            res = priority_mesages(ws, reciever, reciever_priority) => res?,
             _ = sleep(Duration::from_secs(time)) => {}
         }
-        let stream1 = RecieverStream::new(reciever.to_owned());
-        let stream2 = RecieverStream::new(reciever_priority.to_owned());
-        let mut fused_streams = select_all([stream1.to_stream(), stream2.to_stream()]);
-
-        while let Some(Ok(msg)) = fused_streams.next().await {
-            ws.send(msg)
-                .await
-                .inspect_err(|e| warn!("Error sending message to websocket, {e}"))?;
-            ws.flush().await?;
-            debug!("Sent message to websocket!");
+        loop {
+            let msg = tokio::select! {
+                biased;
+                msg = reciever_priority.recv() => msg?,
+                msg = reciever.recv() => msg?,
+            };
+            write_batch(ws, msg, || {
+                reciever_priority
+                    .try_recv()
+                    .ok()
+                    .or_else(|| reciever.try_recv())
+            })
+            .await?;
         }
-        Err(BinaryOptionsToolsError::ChannelRequestRecievingError(
-            RecvError,
-        ))
     }
 
     // async fn api_loop(
@@ -427,6 +493,32 @@ where
         .await?)
     }
 
+    /// Closes the connection for good: sends a close frame to the server (the event loop doesn't
+    /// reconnect once the server answers it), stops the event loop and closes the channels of
+    /// `data`, ending every stream of the client.
+    /// The messages sent afterwards fail, calling it again does nothing.
+    pub async fn shutdown(&self) {
+        if self.closed.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        info!("Shutting down WebSocketClient");
+        let grace = Duration::from_millis(SHUTDOWN_GRACE_PERIOD);
+        // The close frame goes through the handshake lane, so it's written before any queued message
+        match timeout(grace, self.sender.priority_send(Message::Close(None))).await {
+            Ok(Ok(())) => sleep(grace).await,
+            Ok(Err(e)) => debug!("Error sending the closing frame, {e}"),
+            Err(_) => debug!("Timed out queueing the closing frame"),
+        }
+        self._event_loop.abort();
+        self.data.health.set_connected(false);
+        self.data.close().await;
+    }
+
+    /// Whether `shutdown` was called.
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
     pub async fn send(&self, msg: Transfer) -> BinaryOptionsResult<()> {
         self.sender.send::<Transfer>(msg).await
     }
@@ -525,25 +617,60 @@ where
     }
 }
 
-// impl<Transfer, Handler, Connector, Creds, T, C> Drop
-//     for WebSocketClient<Transfer, Handler, Connector, Creds, T, C>
-// where
-//     Transfer: MessageTransfer,
-//     Handler: MessageHandler,
-//     Connector: Connect,
-//     Creds: Credentials,
-//     T: DataHandler,
-//     C: Callback,
-// {
-//     fn drop(&mut self) {
-//         self._event_loop.abort();
-//         info!(target: "Drop", "Dropping WebSocketClient instance");
-//     }
-// }
+impl<Transfer, Handler, Connector, Creds, T, U> Drop
+    for WebSocketInnerClient<Transfer, Handler, Connector, Creds, T, U>
+where
+    Transfer: MessageTransfer,
+    Handler: MessageHandler,
+    Connector: Connect,
+    Creds: Credentials,
+    T: DataHandler,
+    U: InnerConfig,
+{
+    fn drop(&mut self) {
+        // Only called once every clone of the `WebSocketClient` is dropped, so the connection is no longer used
+        self._event_loop.abort();
+        self.data.health.set_connected(false);
+        info!(target: "Drop", "Dropping WebSocketClient instance");
+    }
+}
+
+/// Writes `msg` and the messages returned by `next` (up to `MAX_WRITE_BATCH` in total) to the
+/// websocket, then flushes it once. Returns the number of messages written.
+async fn write_batch<S>(
+    ws: &mut S,
+    msg: Message,
+    mut next: impl FnMut() -> Option<Message>,
+) -> BinaryOptionsResult<usize>
+where
+    S: Sink<Message, Error = tungstenite::Error> + Unpin,
+{
+    let mut msg = Some(msg);
+    let mut batch = 0;
+    while let Some(m) = msg {
+        ws.feed(m)
+            .await
+            .inspect_err(|e| warn!("Error sending message to websocket, {e}"))?;
+        batch += 1;
+        msg = if batch < MAX_WRITE_BATCH {
+            next()
+        } else {
+            None
+        };
+    }
+    ws.flush().await?;
+    debug!("Sent {batch} message(s) to websocket!");
+    Ok(batch)
+}
 
 #[cfg(test)]
 mod tests {
-    use std::time::Duration;
+    use std::{
+        collections::VecDeque,
+        pin::Pin,
+        task::{Context, Poll},
+        time::Duration,
+    };
 
     use async_channel::{Receiver, Sender, bounded};
     use futures_util::{
@@ -555,8 +682,71 @@ mod tests {
     use tokio::time::sleep;
     use tracing::info;
 
+    use super::*;
     use crate::utils::tracing::start_tracing;
 
+    /// Sink keeping the messages written between two flushes together.
+    #[derive(Default)]
+    struct BatchSink {
+        pending: Vec<Message>,
+        flushed: Vec<Vec<Message>>,
+    }
+
+    impl Sink<Message> for BatchSink {
+        type Error = tungstenite::Error;
+
+        fn poll_ready(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn start_send(mut self: Pin<&mut Self>, item: Message) -> Result<(), Self::Error> {
+            self.pending.push(item);
+            Ok(())
+        }
+
+        fn poll_flush(
+            mut self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            let batch = std::mem::take(&mut self.pending);
+            if !batch.is_empty() {
+                self.flushed.push(batch);
+            }
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            self.poll_flush(cx)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_write_batch_limit() -> anyhow::Result<()> {
+        let total = MAX_WRITE_BATCH * 2 + 10;
+        let mut queue: VecDeque<Message> =
+            (0..total).map(|i| Message::text(i.to_string())).collect();
+        let mut ws = BatchSink::default();
+        while let Some(msg) = queue.pop_front() {
+            write_batch(&mut ws, msg, || queue.pop_front()).await?;
+        }
+
+        // The queued messages are written together, a flush every `MAX_WRITE_BATCH` messages
+        let sizes: Vec<usize> = ws.flushed.iter().map(Vec::len).collect();
+        assert_eq!(sizes, vec![MAX_WRITE_BATCH, MAX_WRITE_BATCH, 10]);
+        let sent: Vec<Message> = ws.flushed.into_iter().flatten().collect();
+        let expected: Vec<Message> = (0..total).map(|i| Message::text(i.to_string())).collect();
+        assert_eq!(sent, expected);
+
+        // A message alone is flushed right away
+        let mut ws = BatchSink::default();
+        assert_eq!(
+            write_batch(&mut ws, Message::text("ping"), || None).await?,
+            1
+        );
+        assert_eq!(ws.flushed.len(), 1);
+        Ok(())
+    }
+
     struct RecieverStream<T> {
         inner: Receiver<T>,
     }
@@ -1,6 +1,7 @@
 use std::{collections::HashSet, time::Duration};
 
 use serde::{Deserialize, Serialize};
+use tokio_tungstenite::tungstenite::protocol::WebSocketConfig;
 use url::Url;
 
 use crate::constants::{MAX_ALLOWED_LOOPS, RECONNECT_CALLBACK, SLEEP_INTERVAL, TIMEOUT_TIME};
@@ -23,6 +24,8 @@ pub struct _Config<T: DataHandler, Transfer: MessageTransfer, U: InnerConfig> {
     pub callbacks: Vec<Callback<T, Transfer, U>>,
     pub connection_initialization_timeout: Duration,
     pub timeout: Duration, // General timeout
+    #[serde(default)]
+    pub connection_tuning: ConnectionTuning,
     #[serde(bound = "U: Serialize + for<'d> Deserialize<'d>")]
     pub extra: U,
     // #[serde(skip)]
@@ -39,7 +42,59 @@ impl<T: DataHandler, Transfer: MessageTransfer, U: InnerConfig> _Config<T, Trans
             callbacks,
             timeout: Duration::from_secs(TIMEOUT_TIME),
             connection_initialization_timeout: initialization_timeout,
+            connection_tuning: ConnectionTuning::default(),
             extra,
         }
     }
 }
+
+/// Socket and reader options of the websocket connection, applied on every (re)connection.
+/// The defaults favour throughput, `low_latency` favours the delay of every single frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConnectionTuning {
+    /// Sets TCP_NODELAY, the small frames (orders, pings) are sent without waiting for the
+    /// acknowledgement of the previous ones (Nagle's algorithm).
+    pub tcp_nodelay: bool,
+    /// Capacity of the read buffer of the websocket, in bytes.
+    pub read_buffer_size: usize,
+    /// Bytes buffered before they're written to the socket, 0 writes every frame right away.
+    pub write_buffer_size: usize,
+    /// Runs the reader of the websocket on a thread of its own with a raised priority, so the
+    /// messages don't wait behind the tasks of the application.
+    pub dedicated_reader: bool,
+    /// Skips the debug logs written for every message received.
+    pub reduced_logging: bool,
+}
+
+impl Default for ConnectionTuning {
+    fn default() -> Self {
+        Self {
+            tcp_nodelay: false,
+            read_buffer_size: 128 * 1024,
+            write_buffer_size: 128 * 1024,
+            dedicated_reader: false,
+            reduced_logging: false,
+        }
+    }
+}
+
+impl ConnectionTuning {
+    /// Preset for the short expiries (5 to 30 seconds): every frame is sent and read as soon as
+    /// possible, at the cost of more syscalls and a thread per client.
+    pub fn low_latency() -> Self {
+        Self {
+            tcp_nodelay: true,
+            read_buffer_size: 16 * 1024,
+            write_buffer_size: 0,
+            dedicated_reader: true,
+            reduced_logging: true,
+        }
+    }
+
+    pub fn websocket_config(&self) -> WebSocketConfig {
+        WebSocketConfig::default()
+            .read_buffer_size(self.read_buffer_size)
+            .write_buffer_size(self.write_buffer_size)
+    }
+}
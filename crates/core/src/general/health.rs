@@ -0,0 +1,183 @@
+use std::{
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use async_channel::{Sender, bounded};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::constants::MAX_CHANNEL_CAPACITY;
+
+use super::stream::RecieverStream;
+
+/// Transitions of the state of the websocket connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionEventKind {
+    Connected,
+    Disconnected,
+    /// A reconnection attempt is starting.
+    Reconnecting,
+    /// A reconnection attempt failed, the client tries again after the sleep interval until the
+    /// maximum number of attempts is reached.
+    ReconnectFailed,
+    /// The server rejected the credentials of the client, the reason tells whether they expired
+    /// or are malformed. The session providers refresh them on this event.
+    AuthenticationFailed,
+    /// The server reported that the client connects from another IP or region than the one of the
+    /// session, which happens after moving the client to another machine.
+    RegionMismatch,
+}
+
+/// Change of the state of the connection, sent to the subscribers of `ConnectionHealth::subscribe`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConnectionEvent {
+    pub kind: ConnectionEventKind,
+    pub time: DateTime<Utc>,
+    /// Number of the reconnection attempt, for `Reconnecting` and `ReconnectFailed`.
+    pub attempt: Option<u32>,
+    /// Reason of the disconnection or of the failure, if known.
+    pub reason: Option<String>,
+}
+
+impl ConnectionEvent {
+    pub fn new(kind: ConnectionEventKind) -> Self {
+        Self {
+            kind,
+            time: Utc::now(),
+            attempt: None,
+            reason: None,
+        }
+    }
+
+    pub fn attempt(mut self, attempt: u32) -> Self {
+        self.attempt = Some(attempt);
+        self
+    }
+
+    pub fn reason(mut self, reason: impl ToString) -> Self {
+        self.reason = Some(reason.to_string());
+        self
+    }
+}
+
+/// Tracks the state of the websocket connection, it's shared between the event loop and the client.
+#[derive(Debug, Default)]
+pub struct ConnectionHealth {
+    connected: AtomicBool,
+    reconnections: AtomicU64,
+    last_message: Mutex<Option<Instant>>,
+    subscribers: Mutex<Vec<Sender<ConnectionEvent>>>,
+}
+
+impl ConnectionHealth {
+    /// Sets the state of the connection, sending a `Connected` or `Disconnected` event if it changed.
+    pub fn set_connected(&self, connected: bool) {
+        if self.connected.swap(connected, Ordering::Relaxed) != connected {
+            self.send_event(ConnectionEvent::new(if connected {
+                ConnectionEventKind::Connected
+            } else {
+                ConnectionEventKind::Disconnected
+            }));
+        }
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    pub fn add_reconnection(&self) {
+        self.reconnections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn reconnections(&self) -> u64 {
+        self.reconnections.load(Ordering::Relaxed)
+    }
+
+    pub fn message_received(&self) {
+        if let Ok(mut last) = self.last_message.lock() {
+            *last = Some(Instant::now());
+        }
+    }
+
+    /// Time elapsed since the last message was recieved, `None` if no message was recieved yet.
+    pub fn last_message_age(&self) -> Option<Duration> {
+        self.last_message
+            .lock()
+            .ok()
+            .and_then(|last| last.map(|l| l.elapsed()))
+    }
+
+    /// Every subscriber receives all the connection events sent after it subscribed, the events of
+    /// a subscriber that stopped reading are dropped once its channel is full.
+    pub fn subscribe(&self, timeout: Option<Duration>) -> RecieverStream<ConnectionEvent> {
+        let (sender, receiver) = bounded(MAX_CHANNEL_CAPACITY);
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.push(sender);
+        }
+        RecieverStream::new_timed(receiver, timeout)
+    }
+
+    pub fn send_event(&self, event: ConnectionEvent) {
+        let Ok(mut subscribers) = self.subscribers.lock() else {
+            return;
+        };
+        subscribers.retain(|s| !s.is_closed());
+        for subscriber in subscribers.iter() {
+            let _ = subscriber.try_send(event.clone());
+        }
+    }
+
+    /// Ends the streams of the subscribers of the connection events.
+    pub fn close(&self) {
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            for subscriber in subscribers.drain(..) {
+                subscriber.close();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_connection_events() -> anyhow::Result<()> {
+        let health = ConnectionHealth::default();
+        let events = health.subscribe(None);
+        health.set_connected(true);
+        // Only the changes of state are sent
+        health.set_connected(true);
+        health.set_connected(false);
+        health.send_event(
+            ConnectionEvent::new(ConnectionEventKind::ReconnectFailed)
+                .attempt(1)
+                .reason("refused"),
+        );
+        health.close();
+
+        let mut stream = events.to_stream();
+        let mut kinds = Vec::new();
+        // The stream fails once the events are closed
+        while let Some(Ok(event)) = stream.next().await {
+            kinds.push(event.kind);
+        }
+        assert_eq!(
+            kinds,
+            vec![
+                ConnectionEventKind::Connected,
+                ConnectionEventKind::Disconnected,
+                ConnectionEventKind::ReconnectFailed
+            ]
+        );
+        assert!(!health.is_connected());
+        Ok(())
+    }
+}
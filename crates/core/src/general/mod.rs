@@ -1,5 +1,7 @@
 pub mod client;
 pub mod config;
+pub mod health;
+pub mod priority;
 pub mod traits;
 pub mod types;
 
@@ -0,0 +1,124 @@
+use async_channel::{Receiver, RecvError, Sender, bounded};
+use serde::{Deserialize, Serialize};
+use tokio_tungstenite::tungstenite::Message;
+
+/// Lane of the outgoing queue a message is sent through, when the socket is congested the messages
+/// of the most urgent lanes are written first (`Order` > `Keepalive` > `Subscription` > `Misc`).
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Priority {
+    Order,
+    Keepalive,
+    Subscription,
+    #[default]
+    Misc,
+}
+
+impl Priority {
+    /// Every lane, from the most to the least urgent.
+    pub const ALL: [Priority; 4] = [
+        Priority::Order,
+        Priority::Keepalive,
+        Priority::Subscription,
+        Priority::Misc,
+    ];
+}
+
+/// Sending half of the outgoing queue, one channel per lane.
+#[derive(Debug, Clone)]
+pub struct PrioritySender {
+    lanes: [Sender<Message>; 4],
+}
+
+/// Receiving half of the outgoing queue, always yields the message of the most urgent non empty lane.
+#[derive(Debug, Clone)]
+pub struct PriorityReceiver {
+    lanes: [Receiver<Message>; 4],
+}
+
+/// Creates a prioritized queue with a capacity of `cap` messages per lane.
+pub fn priority_queue(cap: usize) -> (PrioritySender, PriorityReceiver) {
+    let (s0, r0) = bounded(cap);
+    let (s1, r1) = bounded(cap);
+    let (s2, r2) = bounded(cap);
+    let (s3, r3) = bounded(cap);
+    (
+        PrioritySender {
+            lanes: [s0, s1, s2, s3],
+        },
+        PriorityReceiver {
+            lanes: [r0, r1, r2, r3],
+        },
+    )
+}
+
+impl PrioritySender {
+    pub async fn send(
+        &self,
+        msg: Message,
+        priority: Priority,
+    ) -> Result<(), async_channel::SendError<Message>> {
+        self.lanes[priority as usize].send(msg).await
+    }
+}
+
+impl PriorityReceiver {
+    /// Number of messages waiting in each lane, from the most to the least urgent.
+    pub fn pending(&self) -> [usize; 4] {
+        [
+            self.lanes[0].len(),
+            self.lanes[1].len(),
+            self.lanes[2].len(),
+            self.lanes[3].len(),
+        ]
+    }
+
+    /// Takes the message of the most urgent lane that has one waiting, without waiting.
+    pub fn try_recv(&self) -> Option<Message> {
+        self.lanes.iter().find_map(|lane| lane.try_recv().ok())
+    }
+
+    /// Waits for the next message of the given lane only.
+    pub async fn recv_lane(&self, priority: Priority) -> Result<Message, RecvError> {
+        self.lanes[priority as usize].recv().await
+    }
+
+    /// Waits for the next message, taking it from the most urgent lane that has one waiting.
+    pub async fn recv(&self) -> Result<Message, RecvError> {
+        // Receiving from `async_channel` is cancel safe, the lanes that lose the race keep their messages
+        tokio::select! {
+            biased;
+            msg = self.lanes[0].recv() => msg,
+            msg = self.lanes[1].recv() => msg,
+            msg = self.lanes[2].recv() => msg,
+            msg = self.lanes[3].recv() => msg,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_priority_queue() -> anyhow::Result<()> {
+        let (sender, reciever) = priority_queue(8);
+        sender.send(Message::text("misc"), Priority::Misc).await?;
+        sender
+            .send(Message::text("subfor"), Priority::Subscription)
+            .await?;
+        sender.send(Message::text("3"), Priority::Keepalive).await?;
+        sender.send(Message::text("order"), Priority::Order).await?;
+        assert_eq!(reciever.pending(), [1, 1, 1, 1]);
+        let mut order = Vec::new();
+        for _ in 0..4 {
+            order.push(reciever.recv().await?.into_text()?.to_string());
+        }
+        assert_eq!(order, ["order", "3", "subfor", "misc"]);
+        assert_eq!(reciever.pending(), [0, 0, 0, 0]);
+        assert!(reciever.try_recv().is_none());
+        Ok(())
+    }
+}
@@ -11,18 +11,21 @@ use crate::{
 };
 
 use super::{
+    priority::{Priority, PriorityReceiver, PrioritySender, priority_queue},
     stream::FilteredRecieverStream, traits::{DataHandler, MessageTransfer, RawMessage, ValidatorTrait}, types::Data
 };
 
+/// Sends the messages to the websocket writer, the regular messages go through the prioritized lanes
+/// while `priority_send` is reserved for the connection handshake.
 #[derive(Clone)]
 pub struct SenderMessage {
-    sender: Sender<Message>,
+    sender: PrioritySender,
     sender_priority: Sender<Message>,
 }
 
 impl SenderMessage {
-    pub fn new(cap: usize) -> (Self, (Receiver<Message>, Receiver<Message>)) {
-        let (s, r) = bounded(cap);
+    pub fn new(cap: usize) -> (Self, (PriorityReceiver, Receiver<Message>)) {
+        let (s, r) = priority_queue(cap);
         let (sp, rp) = bounded(cap);
 
         (
@@ -69,14 +72,23 @@ impl SenderMessage {
         msg: Transfer::Raw,
     ) -> BinaryOptionsResult<()> {
         self.sender
-            .send(msg.message())
+            .send(msg.message(), Priority::Misc)
             .await
             .map_err(|e| BinaryOptionsToolsError::ChannelRequestSendingError(e.to_string()))
     }
 
     pub async fn send<Transfer: MessageTransfer>(&self, msg: Transfer) -> BinaryOptionsResult<()> {
+        let priority = msg.priority();
+        self.send_with_priority(msg.into(), priority).await
+    }
+
+    pub async fn send_with_priority(
+        &self,
+        msg: Message,
+        priority: Priority,
+    ) -> BinaryOptionsResult<()> {
         self.sender
-            .send(msg.into())
+            .send(msg, priority)
             .await
             .map_err(|e| BinaryOptionsToolsError::ChannelRequestSendingError(e.to_string()))
     }
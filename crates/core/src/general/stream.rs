@@ -1,42 +1,142 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use async_channel::{Receiver, RecvError};
 use futures_util::{Stream, stream::unfold};
+use tokio::time::Instant;
 
-use crate::{error::{BinaryOptionsResult, BinaryOptionsToolsError}, utils::time::timeout};
+use crate::error::{BinaryOptionsResult, BinaryOptionsToolsError};
 
 use super::traits::ValidatorTrait;
 
+/// Idle deadline of a single stream consumer.
+///
+/// The deadline is set `timeout` after the creation of the stream and after every received item, if it's
+/// reached before the next item the consumer gets a `TimeoutError` but the stream keeps working (the next
+/// call waits for a new `timeout`), so a timeout never affects the other consumers of the same messages.
+/// It's shared (`Arc`) so the deadline can be reset or extended while the consumer is waiting.
+#[derive(Debug, Default)]
+pub struct StreamDeadline {
+    timeout: Mutex<Option<Duration>>,
+    deadline: Mutex<Option<Instant>>,
+}
+
+impl StreamDeadline {
+    pub fn new(timeout: Option<Duration>) -> Self {
+        Self {
+            deadline: Mutex::new(timeout.map(|t| Instant::now() + t)),
+            timeout: Mutex::new(timeout),
+        }
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout.lock().map(|t| *t).unwrap_or_default()
+    }
+
+    /// Changes the timeout (`None` disables it) and restarts the deadline.
+    pub fn set_timeout(&self, timeout: Option<Duration>) {
+        if let Ok(mut current) = self.timeout.lock() {
+            *current = timeout;
+        }
+        self.reset();
+    }
+
+    /// Restarts the deadline, the consumer now has `timeout` to receive the next item.
+    pub fn reset(&self) {
+        let timeout = self.timeout();
+        if let Ok(mut deadline) = self.deadline.lock() {
+            *deadline = timeout.map(|t| Instant::now() + t);
+        }
+    }
+
+    /// Gives the consumer `duration` more to receive the next item, without changing the timeout.
+    /// It does nothing if the stream doesn't have a timeout.
+    pub fn extend(&self, duration: Duration) {
+        if self.timeout().is_none() {
+            return;
+        }
+        if let Ok(mut deadline) = self.deadline.lock() {
+            *deadline = Some(deadline.unwrap_or_else(Instant::now).max(Instant::now()) + duration);
+        }
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        self.deadline.lock().map(|d| *d).unwrap_or_default()
+    }
+
+    /// Waits for `future` until the deadline, following the changes made to the deadline while waiting.
+    pub async fn run<F, T>(&self, task: &str, future: F) -> BinaryOptionsResult<T>
+    where
+        F: Future<Output = BinaryOptionsResult<T>>,
+    {
+        tokio::pin!(future);
+        loop {
+            let Some(deadline) = self.deadline() else {
+                return future.await;
+            };
+            tokio::select! {
+                biased;
+                result = &mut future => {
+                    self.reset();
+                    return result;
+                }
+                _ = tokio::time::sleep_until(deadline) => {
+                    // The deadline may have been reset or extended while waiting
+                    if self.deadline().is_some_and(|current| current > deadline) {
+                        continue;
+                    }
+                    self.reset();
+                    return Err(BinaryOptionsToolsError::TimeoutError {
+                        task: task.to_string(),
+                        duration: self.timeout().unwrap_or_default(),
+                    });
+                }
+            }
+        }
+    }
+}
+
 pub struct RecieverStream<T> {
     inner: Receiver<T>,
-    timeout: Option<Duration>,
+    deadline: Arc<StreamDeadline>,
 }
 
 pub struct FilteredRecieverStream<T> {
     inner: Receiver<T>,
-    timeout: Option<Duration>,
+    deadline: Arc<StreamDeadline>,
     filter: Box<dyn ValidatorTrait<T> + Send + Sync>
 }
 
 
 impl<T> RecieverStream<T> {
     pub fn new(inner: Receiver<T>) -> Self {
+        Self::new_timed(inner, None)
+    }
+
+    pub fn new_timed(inner: Receiver<T>, timeout: Option<Duration>) -> Self {
         Self {
             inner,
-            timeout: None,
+            deadline: Arc::new(StreamDeadline::new(timeout)),
         }
     }
 
-    pub fn new_timed(inner: Receiver<T>, timeout: Option<Duration>) -> Self {
-        Self { inner, timeout }
+    /// Deadline of this consumer, can be used to reset or extend the timeout after the stream was created.
+    pub fn deadline(&self) -> Arc<StreamDeadline> {
+        self.deadline.clone()
     }
 
-
     async fn receive(&self) -> BinaryOptionsResult<T> {
-        match self.timeout {
-            Some(time) => timeout(time, self.inner.recv(), "RecieverStream".to_string()).await,
-            None => Ok(self.inner.recv().await?),
-        }
+        self.deadline
+            .run("RecieverStream", async {
+                self.inner
+                    .recv()
+                    .await
+                    .map_err(BinaryOptionsToolsError::from)
+            })
+            .await
     }
 
     pub fn to_stream(&self) -> impl Stream<Item = BinaryOptionsResult<T>> + '_ {
@@ -60,7 +160,16 @@ impl<T> RecieverStream<T> {
 
 impl<T> FilteredRecieverStream<T> {
     pub fn new(inner: Receiver<T>, timeout: Option<Duration>, filter: Box<dyn ValidatorTrait<T> + Send + Sync>) -> Self {
-        Self { inner, timeout, filter }
+        Self {
+            inner,
+            deadline: Arc::new(StreamDeadline::new(timeout)),
+            filter,
+        }
+    }
+
+    /// Deadline of this consumer, can be used to reset or extend the timeout after the stream was created.
+    pub fn deadline(&self) -> Arc<StreamDeadline> {
+        self.deadline.clone()
     }
 
     pub fn new_base(inner: Receiver<T>) -> Self {
@@ -84,10 +193,7 @@ impl<T> FilteredRecieverStream<T> {
     }
 
     async fn receive(&self) -> BinaryOptionsResult<T> {
-        match self.timeout {
-            Some(time) => timeout(time, self.recv(), "RecieverStream".to_string()).await,
-            None => Ok(self.inner.recv().await?),
-        }
+        self.deadline.run("RecieverStream", self.recv()).await
     }
 
     pub fn to_stream(&self) -> impl Stream<Item = BinaryOptionsResult<T>> + '_ {
@@ -112,4 +218,29 @@ fn default_filter<T>() -> Box<dyn ValidatorTrait<T> + Send + Sync> {
     Box::new(move |_: &T| {
         true
     })
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_stream_deadline() -> anyhow::Result<()> {
+        let (sender, receiver) = async_channel::unbounded();
+        let stream = RecieverStream::new_timed(receiver, Some(Duration::from_millis(50)));
+        let deadline = stream.deadline();
+        let start = Instant::now();
+        let extend = async {
+            tokio::time::sleep(Duration::from_millis(30)).await;
+            deadline.extend(Duration::from_millis(100));
+        };
+        let (res, _) = tokio::join!(stream.receive(), extend);
+        assert!(res.is_err());
+        assert!(start.elapsed() >= Duration::from_millis(120));
+
+        // The stream keeps working after a timeout
+        sender.send(1).await?;
+        assert_eq!(stream.receive().await?, 1);
+        Ok(())
+    }
+}
@@ -8,6 +8,7 @@ use crate::error::BinaryOptionsResult;
 
 use super::{
     config::Config,
+    priority::Priority,
     send::SenderMessage,
     types::{Data, MessageType},
 };
@@ -24,6 +25,10 @@ pub trait DataHandler: Clone + Send + Sync {
     type Transfer: MessageTransfer;
 
     async fn update(&self, message: &Self::Transfer) -> BinaryOptionsResult<()>;
+
+    /// Called once when the client shuts down, closes the channels feeding the streams of the
+    /// handler so they end instead of waiting for data that will never arrive.
+    async fn close(&self) {}
 }
 
 /// Allows users to add a callback that will be called when the websocket connection is established after being disconnected, you will have access to the `Data` struct providing access to any required information stored during execution
@@ -57,6 +62,11 @@ pub trait MessageTransfer:
     fn to_error(&self) -> Self::TransferError;
 
     fn error_info(&self) -> Option<Vec<Self::Info>>;
+
+    /// Lane of the outgoing queue used to send this message.
+    fn priority(&self) -> Priority {
+        Priority::Misc
+    }
 }
 
 pub trait MessageInformation:
@@ -11,6 +11,7 @@ use crate::error::BinaryOptionsResult;
 use crate::error::BinaryOptionsToolsError;
 
 use super::config;
+use super::health::ConnectionHealth;
 use super::send::SenderMessage;
 use super::traits::InnerConfig;
 use super::traits::WCallback;
@@ -38,6 +39,7 @@ where
     inner: Arc<T>,
     pub pending_requests: PendingRequests<Transfer>,
     pub raw_requests: (Sender<Transfer::Raw>, Receiver<Transfer::Raw>),
+    pub health: Arc<ConnectionHealth>,
 }
 
 impl<T: DataHandler + Default, Transfer: MessageTransfer> Default for Data<T, Transfer> {
@@ -47,6 +49,7 @@ impl<T: DataHandler + Default, Transfer: MessageTransfer> Default for Data<T, Tr
             raw_requests,
             inner: Default::default(),
             pending_requests: Default::default(),
+            health: Default::default(),
         }
     }
 }
@@ -94,6 +97,7 @@ where
             inner: Arc::new(inner),
             pending_requests: Arc::new(Mutex::new(HashMap::new())),
             raw_requests,
+            health: Arc::new(ConnectionHealth::default()),
         }
     }
 
@@ -144,6 +148,17 @@ where
         Ok(())
     }
 
+    /// Closes the channels of the raw messages, of the pending requests, of the connection events
+    /// and of the `DataHandler`, every receiver waiting on them gets an error.
+    pub async fn close(&self) {
+        self.raw_requests.0.close();
+        for (sender, _) in self.pending_requests.lock().await.values() {
+            sender.close();
+        }
+        self.health.close();
+        self.inner.close().await;
+    }
+
     pub async fn update_data(
         &self,
         message: Transfer,
@@ -1,3 +1,4 @@
+pub mod assets;
 pub mod constants;
 pub mod error;
 pub mod general;
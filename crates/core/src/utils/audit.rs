@@ -0,0 +1,190 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tracing_subscriber::{
+    Layer, Registry,
+    filter::{LevelFilter, Targets},
+    fmt::MakeWriter,
+};
+
+use crate::{
+    error::{BinaryOptionsResult, BinaryOptionsToolsError},
+    utils::redact::RedactingWriter,
+};
+
+/// Target used by every event that should end up in the audit log.
+pub const AUDIT_TARGET: &str = "Audit";
+
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuditRecord {
+    pub seq: u64,
+    pub timestamp: String,
+    pub prev_hash: String,
+    pub event: Value,
+    pub hash: String,
+}
+
+impl AuditRecord {
+    fn compute_hash(seq: u64, timestamp: &str, prev_hash: &str, event: &Value) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(seq.to_be_bytes());
+        hasher.update(timestamp.as_bytes());
+        hasher.update(prev_hash.as_bytes());
+        hasher.update(event.to_string().as_bytes());
+        hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.hash == Self::compute_hash(self.seq, &self.timestamp, &self.prev_hash, &self.event)
+    }
+}
+
+struct AuditState {
+    file: File,
+    seq: u64,
+    prev_hash: String,
+}
+
+/// Append-only writer where every record contains the hash of the previous one, so any edit,
+/// removal or reordering of the records can be detected with `verify_audit_log`.
+#[derive(Clone)]
+pub struct AuditWriter {
+    state: Arc<Mutex<AuditState>>,
+}
+
+impl AuditWriter {
+    /// Opens (or creates) the audit file, continuing the hash chain of the existing records.
+    pub fn open(path: impl AsRef<Path>) -> BinaryOptionsResult<Self> {
+        let (seq, prev_hash) = match File::open(&path) {
+            Ok(file) => match BufReader::new(file).lines().map_while(Result::ok).last() {
+                Some(line) => {
+                    let record: AuditRecord = serde_json::from_str(&line)?;
+                    (record.seq + 1, record.hash)
+                }
+                None => (0, GENESIS_HASH.to_string()),
+            },
+            Err(_) => (0, GENESIS_HASH.to_string()),
+        };
+        let file = OpenOptions::new().append(true).create(true).open(path)?;
+        Ok(Self {
+            state: Arc::new(Mutex::new(AuditState {
+                file,
+                seq,
+                prev_hash,
+            })),
+        })
+    }
+
+    fn append(&self, event: Value) -> std::io::Result<()> {
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|e| std::io::Error::other(e.to_string()))?;
+        let timestamp = Utc::now().to_rfc3339();
+        let hash = AuditRecord::compute_hash(state.seq, &timestamp, &state.prev_hash, &event);
+        let record = AuditRecord {
+            seq: state.seq,
+            timestamp,
+            prev_hash: state.prev_hash.clone(),
+            event,
+            hash: hash.clone(),
+        };
+        let mut line = serde_json::to_string(&record)?;
+        line.push('\n');
+        state.file.write_all(line.as_bytes())?;
+        state.file.flush()?;
+        state.seq += 1;
+        state.prev_hash = hash;
+        Ok(())
+    }
+}
+
+impl Write for AuditWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if let Ok(event) = serde_json::from_slice::<Value>(buf) {
+            self.append(event)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for AuditWriter {
+    type Writer = AuditWriter;
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+/// Creates a layer that only records the events with the `Audit` target into a hash-chained file.
+pub fn audit_layer(
+    path: impl AsRef<Path>,
+) -> BinaryOptionsResult<Box<dyn Layer<Registry> + Send + Sync>> {
+    let writer = AuditWriter::open(path)?;
+    let layer = tracing_subscriber::fmt::layer::<Registry>()
+        .json()
+        .flatten_event(true)
+        .with_ansi(false)
+        .with_writer(RedactingWriter::new(writer))
+        .with_filter(Targets::new().with_target(AUDIT_TARGET, LevelFilter::TRACE))
+        .boxed();
+    Ok(layer)
+}
+
+/// Checks the whole hash chain of an audit file, returning the number of valid records.
+pub fn verify_audit_log(path: impl AsRef<Path>) -> BinaryOptionsResult<u64> {
+    let file = File::open(path)?;
+    let mut prev_hash = GENESIS_HASH.to_string();
+    let mut count = 0;
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let record: AuditRecord = serde_json::from_str(&line?)?;
+        if record.seq != index as u64 || record.prev_hash != prev_hash || !record.is_valid() {
+            return Err(BinaryOptionsToolsError::GeneralParsingError(format!(
+                "Audit log has been tampered with at record {index}"
+            )));
+        }
+        prev_hash = record.hash;
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit_chain() -> anyhow::Result<()> {
+        let path = std::env::temp_dir().join(format!("audit-{}.log", uuid::Uuid::new_v4()));
+        let mut writer = AuditWriter::open(&path)?;
+        writer.write_all(br#"{"event":"order_request","amount":1.0}"#)?;
+        writer.write_all(br#"{"event":"order_response","id":"abc"}"#)?;
+        // Reopening continues the chain
+        let mut writer = AuditWriter::open(&path)?;
+        writer.write_all(br#"{"event":"config_change"}"#)?;
+        assert_eq!(verify_audit_log(&path)?, 3);
+
+        let content = std::fs::read_to_string(&path)?.replace("1.0", "100.0");
+        std::fs::write(&path, content)?;
+        assert!(verify_audit_log(&path).is_err());
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+}
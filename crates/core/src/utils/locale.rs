@@ -0,0 +1,140 @@
+//! Language of the user-facing error messages, with the catalog helpers the errors of each crate
+//! use to translate their most common messages.
+
+use std::{
+    fmt,
+    str::FromStr,
+    sync::{LazyLock, RwLock},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::BinaryOptionsToolsError;
+
+/// Environment variable selecting the locale, checked before `LC_ALL`, `LC_MESSAGES` and `LANG`.
+pub const LOCALE_ENV: &str = "BOT_LOCALE";
+
+/// Number of locales, the length of every entry of a `Catalog`.
+pub const LOCALES: usize = 4;
+
+/// Translations of messages by key, in the order of `Locale` (en, es, pt, ru). The templates
+/// contain `{0}`, `{1}`... placeholders for the arguments of the message.
+pub type Catalog = [(&'static str, [&'static str; LOCALES])];
+
+static LOCALE: LazyLock<RwLock<Locale>> = LazyLock::new(|| RwLock::new(Locale::from_env()));
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Es,
+    Pt,
+    Ru,
+}
+
+impl Locale {
+    pub const ALL: [Locale; LOCALES] = [Self::En, Self::Es, Self::Pt, Self::Ru];
+
+    /// Locale of `BOT_LOCALE`, or of the system (`LC_ALL`, `LC_MESSAGES` then `LANG`) if it isn't
+    /// set, English if none of them is a supported language.
+    pub fn from_env() -> Self {
+        [LOCALE_ENV, "LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| value.parse().ok())
+            .unwrap_or_default()
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let code = match self {
+            Self::En => "en",
+            Self::Es => "es",
+            Self::Pt => "pt",
+            Self::Ru => "ru",
+        };
+        f.write_str(code)
+    }
+}
+
+impl FromStr for Locale {
+    type Err = BinaryOptionsToolsError;
+
+    /// Parses the language of a locale, like `es`, `pt-BR` or `ru_RU.UTF-8`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let language = s
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|locale| locale.to_string() == language)
+            .ok_or_else(|| {
+                BinaryOptionsToolsError::GeneralParsingError(format!(
+                    "Unsupported locale '{s}', expected one of 'en', 'es', 'pt' or 'ru'"
+                ))
+            })
+    }
+}
+
+/// Locale of the error messages, `Locale::from_env` unless changed with `set_locale`.
+pub fn locale() -> Locale {
+    LOCALE.read().map(|l| *l).unwrap_or_default()
+}
+
+pub fn set_locale(locale: Locale) {
+    if let Ok(mut current) = LOCALE.write() {
+        *current = locale;
+    }
+}
+
+/// Translates the message `key` of `catalog`, `None` if the catalog doesn't have it.
+pub fn translate(
+    catalog: &Catalog,
+    key: &str,
+    locale: Locale,
+    args: &[&dyn fmt::Display],
+) -> Option<String> {
+    let (_, templates) = catalog.iter().find(|(k, _)| *k == key)?;
+    let mut message = templates[locale.index()].to_string();
+    for (i, arg) in args.iter().enumerate() {
+        message = message.replace(&format!("{{{i}}}"), &arg.to_string());
+    }
+    Some(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locale_and_translate() -> anyhow::Result<()> {
+        assert_eq!("pt_BR.UTF-8".parse::<Locale>()?, Locale::Pt);
+        assert_eq!("RU".parse::<Locale>()?, Locale::Ru);
+        assert!("fr_FR".parse::<Locale>().is_err());
+
+        const CATALOG: &Catalog = &[(
+            "greeting",
+            [
+                "Hello {0}, {1}",
+                "Hola {0}, {1}",
+                "Olá {0}, {1}",
+                "Привет {0}, {1}",
+            ],
+        )];
+        assert_eq!(
+            translate(CATALOG, "greeting", Locale::Es, &[&"Ana", &2]).as_deref(),
+            Some("Hola Ana, 2")
+        );
+        assert!(translate(CATALOG, "missing", Locale::Es, &[]).is_none());
+        Ok(())
+    }
+}
@@ -1,2 +1,8 @@
+pub mod audit;
+pub mod locale;
+pub mod pool;
+pub mod redact;
+pub mod storage;
+pub mod thread;
 pub mod time;
 pub mod tracing;
@@ -0,0 +1,151 @@
+use std::{
+    ops::{Deref, DerefMut},
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Counters of a `BufferPool`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PoolStats {
+    /// Number of buffers handed out.
+    pub acquired: u64,
+    /// Buffers handed out that were reused from the pool instead of allocated.
+    pub reused: u64,
+    /// Buffers that had to be allocated because the pool was empty.
+    pub allocated: u64,
+    /// Buffers dropped instead of returned, because the pool was full or they grew too large.
+    pub discarded: u64,
+    /// Buffers currently waiting in the pool.
+    pub pooled: usize,
+}
+
+/// Pool of reusable byte buffers, so the per-message scratch buffers of the hot paths don't hit the
+/// allocator for every message.
+#[derive(Debug)]
+pub struct BufferPool {
+    buffers: Mutex<Vec<Vec<u8>>>,
+    max_pooled: usize,
+    max_capacity: usize,
+    acquired: AtomicU64,
+    reused: AtomicU64,
+    discarded: AtomicU64,
+}
+
+impl BufferPool {
+    /// Creates a pool keeping at most `max_pooled` buffers, buffers that grew over `max_capacity`
+    /// bytes are released instead of being kept.
+    pub const fn new(max_pooled: usize, max_capacity: usize) -> Self {
+        Self {
+            buffers: Mutex::new(Vec::new()),
+            max_pooled,
+            max_capacity,
+            acquired: AtomicU64::new(0),
+            reused: AtomicU64::new(0),
+            discarded: AtomicU64::new(0),
+        }
+    }
+
+    /// Takes an empty buffer from the pool (or allocates one), it's returned to the pool when dropped.
+    pub fn acquire(&self) -> PooledBuffer<'_> {
+        self.acquired.fetch_add(1, Ordering::Relaxed);
+        let buffer = self.buffers.lock().ok().and_then(|mut b| b.pop());
+        if buffer.is_some() {
+            self.reused.fetch_add(1, Ordering::Relaxed);
+        }
+        PooledBuffer {
+            pool: self,
+            buffer: buffer.unwrap_or_default(),
+        }
+    }
+
+    fn release(&self, mut buffer: Vec<u8>) {
+        if buffer.capacity() <= self.max_capacity {
+            if let Ok(mut buffers) = self.buffers.lock() {
+                if buffers.len() < self.max_pooled {
+                    buffer.clear();
+                    buffers.push(buffer);
+                    return;
+                }
+            }
+        }
+        self.discarded.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        let acquired = self.acquired.load(Ordering::Relaxed);
+        let reused = self.reused.load(Ordering::Relaxed);
+        PoolStats {
+            acquired,
+            reused,
+            allocated: acquired - reused,
+            discarded: self.discarded.load(Ordering::Relaxed),
+            pooled: self.buffers.lock().map(|b| b.len()).unwrap_or_default(),
+        }
+    }
+}
+
+/// Buffer borrowed from a `BufferPool`, returned to it when dropped.
+pub struct PooledBuffer<'a> {
+    pool: &'a BufferPool,
+    buffer: Vec<u8>,
+}
+
+impl Deref for PooledBuffer<'_> {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.buffer
+    }
+}
+
+impl DerefMut for PooledBuffer<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.buffer
+    }
+}
+
+impl Drop for PooledBuffer<'_> {
+    fn drop(&mut self) {
+        self.pool.release(std::mem::take(&mut self.buffer));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_pool() {
+        let pool = BufferPool::new(1, 64);
+        {
+            let mut buffer = pool.acquire();
+            buffer.extend_from_slice(b"[[\"EURUSD_otc\",1732830108.205,1.08543]]");
+        }
+        let stats = pool.stats();
+        assert_eq!((stats.acquired, stats.allocated, stats.pooled), (1, 1, 1));
+        {
+            let buffer = pool.acquire();
+            assert!(buffer.is_empty() && buffer.capacity() > 0);
+            // Only one buffer is kept, the second one is discarded
+            let _other = pool.acquire();
+        }
+        {
+            let mut large = pool.acquire();
+            large.reserve(1024);
+        }
+        assert_eq!(
+            pool.stats(),
+            PoolStats {
+                acquired: 4,
+                reused: 2,
+                allocated: 2,
+                discarded: 2,
+                pooled: 0,
+            }
+        );
+    }
+}
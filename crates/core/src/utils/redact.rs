@@ -0,0 +1,151 @@
+use std::{
+    borrow::Cow,
+    io::Write,
+    sync::{
+        LazyLock, RwLock,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use regex::Regex;
+use tracing_subscriber::fmt::MakeWriter;
+
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Patterns that are always redacted: the auth frame sent to the server and any
+/// `session` / `ssid` / `token` / `password` value, both in JSON and in `Debug` output.
+const DEFAULT_PATTERNS: [&str; 2] = [
+    r#"42\[\\?"auth\\?",\{.*?\}\]"#,
+    r#"(?i)(?P<prefix>\\?"?\b(?:session|ssid|secret|token|password)\\?"?\s*[:=]\s*)(?P<quote>\\?")(?:[^"\\]|\\.)*?\\?""#,
+];
+
+static REDACTION_ENABLED: AtomicBool = AtomicBool::new(true);
+
+static SECRET_PATTERNS: LazyLock<RwLock<Vec<Regex>>> = LazyLock::new(|| {
+    RwLock::new(
+        DEFAULT_PATTERNS
+            .iter()
+            .map(|p| Regex::new(p).expect("Default redaction patterns are valid"))
+            .collect(),
+    )
+});
+
+/// Enables or disables the redaction of secrets, it is enabled by default and should only be
+/// disabled for deep debugging sessions.
+pub fn set_redaction(enabled: bool) {
+    REDACTION_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_redaction_enabled() -> bool {
+    REDACTION_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Registers an extra pattern to redact, the whole match is replaced. Only a group named `prefix`
+/// is kept (e.g. `(?P<prefix>apikey=)\w+` gives `apikey=[REDACTED]`), and a group named `quote` is
+/// used as the quote surrounding the redacted value. The other groups are redacted too.
+pub fn add_secret_pattern(pattern: &str) -> Result<(), regex::Error> {
+    let regex = Regex::new(pattern)?;
+    if let Ok(mut patterns) = SECRET_PATTERNS.write() {
+        patterns.push(regex);
+    }
+    Ok(())
+}
+
+/// Replaces every secret found in `input` with `[REDACTED]`.
+pub fn redact(input: &str) -> Cow<'_, str> {
+    if !is_redaction_enabled() {
+        return Cow::Borrowed(input);
+    }
+    let Ok(patterns) = SECRET_PATTERNS.read() else {
+        return Cow::Borrowed(input);
+    };
+    let mut output = Cow::Borrowed(input);
+    for pattern in patterns.iter() {
+        if pattern.is_match(&output) {
+            let replaced = pattern
+                .replace_all(&output, |caps: &regex::Captures| {
+                    let prefix = caps.name("prefix").map(|p| p.as_str()).unwrap_or_default();
+                    let quote = caps.name("quote").map(|q| q.as_str()).unwrap_or_default();
+                    format!("{prefix}{quote}{REDACTED}{quote}")
+                })
+                .into_owned();
+            output = Cow::Owned(replaced);
+        }
+    }
+    output
+}
+
+/// `MakeWriter` wrapper that redacts secrets from every line before handing it to the inner writer.
+#[derive(Clone)]
+pub struct RedactingWriter<M> {
+    inner: M,
+}
+
+impl<M> RedactingWriter<M> {
+    pub fn new(inner: M) -> Self {
+        Self { inner }
+    }
+}
+
+pub struct RedactingWrite<W> {
+    inner: W,
+}
+
+impl<W: Write> Write for RedactingWrite<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match std::str::from_utf8(buf) {
+            Ok(text) => self.inner.write_all(redact(text).as_bytes())?,
+            Err(_) => self.inner.write_all(buf)?,
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<'a, M: MakeWriter<'a>> MakeWriter<'a> for RedactingWriter<M> {
+    type Writer = RedactingWrite<M::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWrite {
+            inner: self.inner.make_writer(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_auth_frame() {
+        let frame = r#"42["auth",{"session":"abcdef","isDemo":1,"uid":123,"platform":2}]"#;
+        let redacted = redact(frame);
+        assert!(!redacted.contains("abcdef"));
+        assert!(redacted.contains(REDACTED));
+    }
+
+    #[test]
+    fn test_redact_fields() {
+        let line = r#"{"message":"Demo { session: \"abcdef\", is_demo: 1 }","ssid":"123456"}"#;
+        let redacted = redact(line);
+        assert!(!redacted.contains("abcdef"));
+        assert!(!redacted.contains("123456"));
+        assert!(redacted.contains("is_demo: 1"));
+    }
+
+    #[test]
+    fn test_redact_user_patterns() {
+        add_secret_pattern(r"apikey=(\w+)").unwrap();
+        let redacted = redact("GET /orders?apikey=s3cr3tvalue&limit=5");
+        assert!(!redacted.contains("s3cr3tvalue"));
+        assert_eq!(redacted, format!("GET /orders?{REDACTED}&limit=5"));
+
+        add_secret_pattern(r"(?P<prefix>bearer )\S+").unwrap();
+        let redacted = redact("authorization: bearer abc.def.ghi");
+        assert!(!redacted.contains("abc.def"));
+        assert_eq!(redacted, format!("authorization: bearer {REDACTED}"));
+    }
+}
@@ -0,0 +1,135 @@
+//! Directories the library writes its files to (logs, cache, data such as journals), resolved per
+//! platform and created on demand, so file paths are joined with `Path` instead of string formatting.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::{LazyLock, RwLock},
+};
+
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+/// Environment variables overriding the directories of `Storage::from_env`.
+pub const DATA_DIR_ENV: &str = "BOT_DATA_DIR";
+pub const LOG_DIR_ENV: &str = "BOT_LOG_DIR";
+pub const CACHE_DIR_ENV: &str = "BOT_CACHE_DIR";
+
+static STORAGE: LazyLock<RwLock<Storage>> = LazyLock::new(|| RwLock::new(Storage::from_env()));
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Storage {
+    pub data_dir: PathBuf,
+    pub log_dir: PathBuf,
+    pub cache_dir: PathBuf,
+}
+
+impl Storage {
+    /// Every directory under `root`: `root` for the data, `root/logs` and `root/cache`.
+    pub fn in_dir(root: impl AsRef<Path>) -> Self {
+        let root = root.as_ref();
+        Self {
+            data_dir: root.to_path_buf(),
+            log_dir: root.join("logs"),
+            cache_dir: root.join("cache"),
+        }
+    }
+
+    /// Data, log and cache directories of the platform (e.g. `~/.local/share/binaryoptionstools`
+    /// on Linux, `%APPDATA%\BinaryOptionsTools\data` on Windows), or `.binary-options-tools` in
+    /// the current directory if the home directory is unknown.
+    pub fn platform() -> Self {
+        match ProjectDirs::from("", "", "BinaryOptionsTools") {
+            Some(dirs) => Self {
+                data_dir: dirs.data_dir().to_path_buf(),
+                log_dir: dirs
+                    .state_dir()
+                    .unwrap_or_else(|| dirs.data_local_dir())
+                    .join("logs"),
+                cache_dir: dirs.cache_dir().to_path_buf(),
+            },
+            None => Self::in_dir(".binary-options-tools"),
+        }
+    }
+
+    /// Platform directories, each one overridden by its environment variable (`BOT_DATA_DIR`,
+    /// `BOT_LOG_DIR` and `BOT_CACHE_DIR`) if set.
+    pub fn from_env() -> Self {
+        let platform = Self::platform();
+        let dir = |name: &str, default: PathBuf| {
+            std::env::var_os(name)
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from)
+                .unwrap_or(default)
+        };
+        Self {
+            data_dir: dir(DATA_DIR_ENV, platform.data_dir),
+            log_dir: dir(LOG_DIR_ENV, platform.log_dir),
+            cache_dir: dir(CACHE_DIR_ENV, platform.cache_dir),
+        }
+    }
+
+    /// Path of the file `name` in the data directory, which is created if it doesn't exist.
+    pub fn data_file(&self, name: impl AsRef<Path>) -> io::Result<PathBuf> {
+        file_in(&self.data_dir, name)
+    }
+
+    pub fn log_file(&self, name: impl AsRef<Path>) -> io::Result<PathBuf> {
+        file_in(&self.log_dir, name)
+    }
+
+    pub fn cache_file(&self, name: impl AsRef<Path>) -> io::Result<PathBuf> {
+        file_in(&self.cache_dir, name)
+    }
+}
+
+impl Default for Storage {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+/// Joins `name` to `dir`, creating the directories of the resulting path.
+pub fn file_in(dir: impl AsRef<Path>, name: impl AsRef<Path>) -> io::Result<PathBuf> {
+    let path = dir.as_ref().join(name);
+    create_parent_dirs(&path)?;
+    Ok(path)
+}
+
+/// Creates the missing directories of the file at `path`.
+pub fn create_parent_dirs(path: impl AsRef<Path>) -> io::Result<()> {
+    match path.as_ref().parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => std::fs::create_dir_all(parent),
+        _ => Ok(()),
+    }
+}
+
+/// Directories used by the library, `Storage::from_env` unless changed with `set_storage`.
+pub fn storage() -> Storage {
+    STORAGE.read().map(|s| s.clone()).unwrap_or_default()
+}
+
+pub fn set_storage(storage: Storage) {
+    if let Ok(mut current) = STORAGE.write() {
+        *current = storage;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_storage_dirs() -> anyhow::Result<()> {
+        let root = std::env::temp_dir().join(format!("storage-{}", uuid::Uuid::new_v4()));
+        let storage = Storage::in_dir(&root);
+        let log = storage.log_file("errors.log")?;
+        assert_eq!(log, root.join("logs").join("errors.log"));
+        assert!(root.join("logs").is_dir());
+        let nested = storage.cache_file(Path::new("candles").join("EURUSD_otc.json"))?;
+        assert!(nested.parent().is_some_and(Path::is_dir));
+        assert_eq!(file_in("", "relative.log")?, PathBuf::from("relative.log"));
+        std::fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+}
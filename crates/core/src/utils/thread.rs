@@ -0,0 +1,75 @@
+//! Dedicated threads of the client, see `ConnectionTuning::dedicated_reader`.
+
+use std::future::Future;
+
+use tokio::{runtime::Builder, sync::oneshot, task::JoinHandle};
+use tracing::warn;
+
+use crate::error::BinaryOptionsResult;
+
+/// Runs `future` on a thread named `name` with its own runtime and a raised priority. The thread
+/// ends once the task finishes or its handle is aborted.
+pub fn spawn_dedicated<F>(name: &str, future: F) -> BinaryOptionsResult<JoinHandle<F::Output>>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let runtime = Builder::new_current_thread().enable_all().build()?;
+    let (done, finished) = oneshot::channel::<()>();
+    let handle = runtime.spawn(async move {
+        // Dropped with the task, whether it finished or was aborted
+        let _done = done;
+        future.await
+    });
+    std::thread::Builder::new()
+        .name(name.to_string())
+        .spawn(move || {
+            raise_thread_priority();
+            runtime.block_on(async {
+                let _ = finished.await;
+            });
+        })?;
+    Ok(handle)
+}
+
+/// Raises the priority of the current thread, a failure (e.g. missing privileges) is only logged.
+fn raise_thread_priority() {
+    #[cfg(target_os = "linux")]
+    {
+        // The nice value is per thread on Linux, lowering it needs CAP_SYS_NICE
+        if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, -10) } != 0 {
+            warn!(target: "DedicatedThread", "Couldn't raise the priority of the thread, {}", std::io::Error::last_os_error());
+        }
+    }
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::System::Threading::{
+            GetCurrentThread, SetThreadPriority, THREAD_PRIORITY_ABOVE_NORMAL,
+        };
+        if unsafe { SetThreadPriority(GetCurrentThread(), THREAD_PRIORITY_ABOVE_NORMAL) } == 0 {
+            warn!(target: "DedicatedThread", "Couldn't raise the priority of the thread, {}", std::io::Error::last_os_error());
+        }
+    }
+    #[cfg(not(any(target_os = "linux", windows)))]
+    warn!(target: "DedicatedThread", "Thread priorities aren't supported on this platform, the thread keeps the default one");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_spawn_dedicated() -> anyhow::Result<()> {
+        let caller = std::thread::current().id();
+        let handle = spawn_dedicated("test-dedicated", async move {
+            assert_eq!(std::thread::current().name(), Some("test-dedicated"));
+            std::thread::current().id() != caller
+        })?;
+        assert!(handle.await?);
+
+        let handle = spawn_dedicated("test-aborted", std::future::pending::<()>())?;
+        handle.abort();
+        assert!(handle.await.unwrap_err().is_cancelled());
+        Ok(())
+    }
+}
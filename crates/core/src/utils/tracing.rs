@@ -1,109 +1,401 @@
-use std::{fs::OpenOptions, io::Write, time::Duration};
+use std::fs::OpenOptions;
 
-use async_channel::{Sender, bounded};
-use serde_json::Value;
 use tracing::level_filters::LevelFilter;
-use tracing_subscriber::{
-    Layer, Registry,
-    fmt::{self, MakeWriter},
-    layer::SubscriberExt,
-    util::SubscriberInitExt,
-};
+use tracing_subscriber::{Layer, fmt, layer::SubscriberExt, util::SubscriberInitExt};
+
+use crate::utils::{redact::RedactingWriter, storage::storage};
 
-use crate::{constants::MAX_LOGGING_CHANNEL_CAPACITY, general::stream::RecieverStream};
+#[cfg(feature = "logging-stream")]
+pub use stream::{
+    FieldPredicate, LogBroadcast, LogFilter, StreamWriter, stream_logs_broadcast_layer,
+    stream_logs_layer, stream_logs_layer_filtered,
+};
 
 pub fn start_tracing(terminal: bool) -> anyhow::Result<()> {
-    let error_logs = OpenOptions::new()
-        .append(true)
-        .create(true)
-        .open("errors.log")?;
+    start_tracing_leveled(terminal, LevelFilter::DEBUG)
+}
 
-    let sub = tracing_subscriber::registry()
-        // .with(filtered_layer)
-        .with(
-            // log-error file, to log the errors that arise
-            fmt::layer()
-                .with_ansi(false)
-                .with_writer(error_logs)
-                .with_filter(LevelFilter::WARN),
-        );
-    if terminal {
-        sub.with(fmt::Layer::default().with_filter(LevelFilter::DEBUG))
-            .try_init()?;
-    } else {
-        sub.try_init()?;
+/// Installs the logs, the errors are also written to `errors.log` in the log directory (see
+/// `storage`). If the file can't be created (permissions, read-only filesystem) the logs keep going
+/// to the terminal and a warning is logged.
+pub fn start_tracing_leveled(terminal: bool, level: LevelFilter) -> anyhow::Result<()> {
+    let (error_logs, error) = match storage()
+        .log_file("errors.log")
+        .and_then(|path| OpenOptions::new().append(true).create(true).open(path))
+    {
+        // log-error file, to log the errors that arise
+        Ok(file) => (
+            Some(
+                fmt::layer()
+                    .with_ansi(false)
+                    .with_writer(RedactingWriter::new(file))
+                    .with_filter(LevelFilter::WARN),
+            ),
+            None,
+        ),
+        Err(e) => (None, Some(e)),
+    };
+    let terminal = terminal.then(|| {
+        fmt::Layer::default()
+            .with_writer(RedactingWriter::new(std::io::stdout))
+            .with_filter(level)
+    });
+    tracing_subscriber::registry()
+        .with(error_logs)
+        .with(terminal)
+        .try_init()?;
+    if let Some(e) = error {
+        tracing::warn!("Couldn't open the 'errors.log' file, logging without it: {e}");
     }
 
     Ok(())
 }
 
-pub fn start_tracing_leveled(terminal: bool, level: LevelFilter) -> anyhow::Result<()> {
-    let error_logs = OpenOptions::new()
-        .append(true)
-        .create(true)
-        .open("errors.log")?;
-
-    let sub = tracing_subscriber::registry()
-        // .with(filtered_layer)
+/// Writes every record of `level` to stdout as one JSON object per line, without creating any file,
+/// for containers with a read-only filesystem and log collectors.
+pub fn start_tracing_json(level: LevelFilter) -> anyhow::Result<()> {
+    tracing_subscriber::registry()
         .with(
-            // log-error file, to log the errors that arise
             fmt::layer()
-                .with_ansi(false)
-                .with_writer(error_logs)
-                .with_filter(LevelFilter::WARN),
-        );
-    if terminal {
-        sub.with(fmt::Layer::default().with_filter(level))
-            .try_init()?;
-    } else {
-        sub.try_init()?;
-    }
-
+                .json()
+                .flatten_event(true)
+                .with_writer(RedactingWriter::new(std::io::stdout))
+                .with_filter(level),
+        )
+        .try_init()?;
     Ok(())
 }
 
-#[derive(Clone)]
-pub struct StreamWriter {
-    sender: Sender<String>,
-}
+#[cfg(feature = "logging-stream")]
+mod stream {
+    use std::{
+        io::Write,
+        str::FromStr,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use async_channel::{Sender, bounded};
+    use serde_json::Value;
+    use tracing::level_filters::LevelFilter;
+    use tracing_subscriber::{
+        Layer, Registry,
+        filter::{FilterExt, filter_fn},
+        fmt::MakeWriter,
+    };
+
+    use crate::{
+        constants::MAX_LOGGING_CHANNEL_CAPACITY, error::BinaryOptionsToolsError,
+        general::stream::RecieverStream, utils::redact::redact,
+    };
+
+    /// Condition on a field of a log record, e.g. `asset == "EURUSD"`, `asset != EURUSD` or `trade_id`
+    /// (the field exists). The field is looked up in the event and then in its spans.
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum FieldPredicate {
+        Equals(String, String),
+        NotEquals(String, String),
+        Exists(String),
+    }
+
+    impl FieldPredicate {
+        pub fn matches(&self, record: &Value) -> bool {
+            match self {
+                Self::Equals(field, expected) => {
+                    find_field(record, field).is_some_and(|v| value_eq(v, expected))
+                }
+                Self::NotEquals(field, expected) => {
+                    !find_field(record, field).is_some_and(|v| value_eq(v, expected))
+                }
+                Self::Exists(field) => find_field(record, field).is_some(),
+            }
+        }
+    }
+
+    impl FromStr for FieldPredicate {
+        type Err = BinaryOptionsToolsError;
 
-impl Write for StreamWriter {
-    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        if let Ok(item) = serde_json::from_slice::<Value>(buf) {
-            self.sender
-                .send_blocking(item.to_string())
-                .map_err(std::io::Error::other)?;
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let unquote = |value: &str| {
+                let value = value.trim();
+                value
+                    .strip_prefix('"')
+                    .and_then(|v| v.strip_suffix('"'))
+                    .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+                    .unwrap_or(value)
+                    .to_string()
+            };
+            let (field, predicate) = if let Some((field, value)) = s.split_once("!=") {
+                (field, Self::NotEquals(String::new(), unquote(value)))
+            } else if let Some((field, value)) = s.split_once("==").or_else(|| s.split_once('=')) {
+                (field, Self::Equals(String::new(), unquote(value)))
+            } else {
+                (s, Self::Exists(String::new()))
+            };
+            let field = field.trim().to_string();
+            if field.is_empty() || field.contains(char::is_whitespace) {
+                return Err(BinaryOptionsToolsError::GeneralParsingError(format!(
+                    "Invalid log filter '{s}', expected 'field == value', 'field != value' or 'field'"
+                )));
+            }
+            Ok(match predicate {
+                Self::Equals(_, value) => Self::Equals(field, value),
+                Self::NotEquals(_, value) => Self::NotEquals(field, value),
+                Self::Exists(_) => Self::Exists(field),
+            })
         }
-        Ok(buf.len())
     }
 
-    fn flush(&mut self) -> std::io::Result<()> {
-        Ok(())
+    fn find_field<'a>(record: &'a Value, field: &str) -> Option<&'a Value> {
+        record
+            .get(field)
+            .or_else(|| record.get("span").and_then(|span| span.get(field)))
+            .or_else(|| {
+                record
+                    .get("spans")
+                    .and_then(Value::as_array)
+                    .and_then(|spans| spans.iter().rev().find_map(|span| span.get(field)))
+            })
     }
-}
 
-impl<'a> MakeWriter<'a> for StreamWriter {
-    type Writer = StreamWriter;
-    fn make_writer(&'a self) -> Self::Writer {
-        self.clone()
+    fn value_eq(value: &Value, expected: &str) -> bool {
+        match value {
+            Value::String(value) => value == expected,
+            value => value.to_string() == expected,
+        }
+    }
+
+    /// Selects the records sent by a stream of logs: only the records whose target starts with one of
+    /// `targets` (all of them if it's empty) and matching every predicate are sent.
+    #[derive(Debug, Clone, Default)]
+    pub struct LogFilter {
+        targets: Vec<String>,
+        predicates: Vec<FieldPredicate>,
+    }
+
+    impl LogFilter {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn target(mut self, target: impl ToString) -> Self {
+            self.targets.push(target.to_string());
+            self
+        }
+
+        pub fn predicate(mut self, predicate: FieldPredicate) -> Self {
+            self.predicates.push(predicate);
+            self
+        }
+
+        pub fn allows_target(&self, target: &str) -> bool {
+            self.targets.is_empty() || self.targets.iter().any(|t| target.starts_with(t.as_str()))
+        }
+
+        pub fn matches(&self, record: &Value) -> bool {
+            let target = record.get("target").and_then(Value::as_str);
+            target.is_none_or(|target| self.allows_target(target))
+                && self.predicates.iter().all(|p| p.matches(record))
+        }
     }
-}
 
-pub fn stream_logs_layer(
-    level: LevelFilter,
-    timout: Option<Duration>,
-) -> (
-    Box<dyn Layer<Registry> + Send + Sync>,
-    RecieverStream<String>,
-) {
-    let (sender, receiver) = bounded(MAX_LOGGING_CHANNEL_CAPACITY);
-    let receiver = RecieverStream::new_timed(receiver, timout);
-    let writer = StreamWriter { sender };
-    let layer = tracing_subscriber::fmt::layer::<Registry>()
-        .json()
-        .flatten_event(true)
-        .with_writer(writer)
-        .with_filter(level)
-        .boxed();
-    (layer, receiver)
+    /// Fan-out of a stream of logs to every consumer subscribed to it, so one layer can feed
+    /// several iterators, each one with its own filter and timeout.
+    #[derive(Clone, Default)]
+    pub struct LogBroadcast {
+        consumers: Arc<Mutex<Vec<(Sender<String>, LogFilter)>>>,
+    }
+
+    impl LogBroadcast {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Adds a consumer receiving the records allowed by `filter`.
+        pub fn subscribe(
+            &self,
+            filter: LogFilter,
+            timeout: Option<Duration>,
+        ) -> RecieverStream<String> {
+            let (sender, receiver) = bounded(MAX_LOGGING_CHANNEL_CAPACITY);
+            if let Ok(mut consumers) = self.consumers.lock() {
+                consumers.push((sender, filter));
+            }
+            RecieverStream::new_timed(receiver, timeout)
+        }
+
+        pub fn consumers(&self) -> usize {
+            self.consumers.lock().map(|c| c.len()).unwrap_or_default()
+        }
+
+        fn send(&self, record: &Value) {
+            let Ok(mut consumers) = self.consumers.lock() else {
+                return;
+            };
+            // Dropped iterators are removed
+            consumers.retain(|(sender, _)| !sender.is_closed());
+            let mut message = None;
+            for (sender, filter) in consumers.iter().filter(|(_, f)| f.matches(record)) {
+                let message =
+                    message.get_or_insert_with(|| redact(&record.to_string()).into_owned());
+                // A consumer that stopped reading must never block the logs of the application,
+                // so the records it can't keep up with are dropped
+                let _ = sender.try_send(message.clone());
+            }
+        }
+    }
+
+    #[derive(Clone)]
+    pub struct StreamWriter {
+        broadcast: LogBroadcast,
+    }
+
+    impl Write for StreamWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if let Ok(item) = serde_json::from_slice::<Value>(buf) {
+                self.broadcast.send(&item);
+            }
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for StreamWriter {
+        type Writer = StreamWriter;
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    /// Layer sending the records of `level` to every consumer of `broadcast`, it can be created as many
+    /// times as needed (e.g. once per subscriber) without losing the consumers.
+    pub fn stream_logs_broadcast_layer(
+        level: LevelFilter,
+        broadcast: LogBroadcast,
+    ) -> Box<dyn Layer<Registry> + Send + Sync> {
+        tracing_subscriber::fmt::layer::<Registry>()
+            .json()
+            .flatten_event(true)
+            .with_writer(StreamWriter { broadcast })
+            .with_filter(level)
+            .boxed()
+    }
+
+    pub fn stream_logs_layer(
+        level: LevelFilter,
+        timout: Option<Duration>,
+    ) -> (
+        Box<dyn Layer<Registry> + Send + Sync>,
+        RecieverStream<String>,
+    ) {
+        stream_logs_layer_filtered(level, timout, LogFilter::default())
+    }
+
+    /// Same as `stream_logs_layer` but only sends the records allowed by `filter`, the targets are
+    /// filtered before the records are formatted.
+    pub fn stream_logs_layer_filtered(
+        level: LevelFilter,
+        timout: Option<Duration>,
+        filter: LogFilter,
+    ) -> (
+        Box<dyn Layer<Registry> + Send + Sync>,
+        RecieverStream<String>,
+    ) {
+        let broadcast = LogBroadcast::new();
+        let targets = filter.clone();
+        let receiver = broadcast.subscribe(filter, timout);
+        let layer = tracing_subscriber::fmt::layer::<Registry>()
+            .json()
+            .flatten_event(true)
+            .with_writer(StreamWriter { broadcast })
+            .with_filter(level.and(filter_fn(move |meta| targets.allows_target(meta.target()))))
+            .boxed();
+        (layer, receiver)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use futures_util::StreamExt;
+        use serde_json::json;
+
+        use super::*;
+
+        #[test]
+        fn test_log_filter() -> anyhow::Result<()> {
+            let record = json!({
+                "level": "INFO",
+                "target": "GetCandlesAdvanced",
+                "asset": "EURUSD",
+                "span": {"trade_id": 42}
+            });
+            assert_eq!(
+                "asset == \"EURUSD\"".parse::<FieldPredicate>()?,
+                FieldPredicate::Equals("asset".into(), "EURUSD".into())
+            );
+            assert!(
+                "asset == EURUSD"
+                    .parse::<FieldPredicate>()?
+                    .matches(&record)
+            );
+            assert!("trade_id=42".parse::<FieldPredicate>()?.matches(&record));
+            assert!(
+                "asset != 'GBPUSD'"
+                    .parse::<FieldPredicate>()?
+                    .matches(&record)
+            );
+            assert!(!"amount".parse::<FieldPredicate>()?.matches(&record));
+            assert!("== EURUSD".parse::<FieldPredicate>().is_err());
+
+            let filter = LogFilter::new()
+                .target("GetCandles")
+                .predicate("asset == EURUSD".parse()?);
+            assert!(filter.allows_target("GetCandlesAdvanced"));
+            assert!(!filter.allows_target("History"));
+            assert!(filter.matches(&record));
+            assert!(!filter.matches(&json!({"asset": "GBPUSD"})));
+            assert!(!filter.matches(&json!({"target": "History", "asset": "EURUSD"})));
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn test_log_broadcast() -> anyhow::Result<()> {
+            let broadcast = LogBroadcast::new();
+            let all = broadcast.subscribe(LogFilter::new(), None);
+            let eurusd = broadcast.subscribe(
+                LogFilter::new().predicate("asset == EURUSD".parse()?),
+                Some(Duration::from_millis(50)),
+            );
+            drop(broadcast.subscribe(LogFilter::new(), None));
+            broadcast.send(&json!({"asset": "EURUSD"}));
+            broadcast.send(&json!({"asset": "GBPUSD"}));
+            assert_eq!(broadcast.consumers(), 2);
+
+            let mut all = all.to_stream();
+            assert!(
+                all.next()
+                    .await
+                    .transpose()?
+                    .is_some_and(|r| r.contains("EURUSD"))
+            );
+            assert!(
+                all.next()
+                    .await
+                    .transpose()?
+                    .is_some_and(|r| r.contains("GBPUSD"))
+            );
+            let mut eurusd = eurusd.to_stream();
+            assert!(
+                eurusd
+                    .next()
+                    .await
+                    .transpose()?
+                    .is_some_and(|r| r.contains("EURUSD"))
+            );
+            assert!(eurusd.next().await.is_some_and(|r| r.is_err()));
+            Ok(())
+        }
+    }
 }